@@ -1,5 +1,5 @@
 import { Component, createSignal, createEffect, onCleanup, Show, Switch, Match } from 'solid-js';
-import { connectWebSocket, type TelemetryEvent } from './api';
+import { connectWebSocket, fetchMeta, type TelemetryEvent } from './api';
 import Sidebar from './components/Sidebar';
 import StatusBar from './components/StatusBar';
 import CommandPalette from './components/CommandPalette';
@@ -17,6 +17,15 @@ const App: Component = () => {
   initTheme();
   const [route, setRoute] = createSignal(getHashRoute());
   const [latestEvent, setLatestEvent] = createSignal<TelemetryEvent | null>(null);
+  const [title, setTitle] = createSignal<string | undefined>(undefined);
+
+  // Per-project branding so multiple dashboards are distinguishable
+  fetchMeta()
+    .then((meta) => {
+      setTitle(meta.title);
+      document.title = `${meta.title} · DevRig`;
+    })
+    .catch(() => {});
 
   // Hash-based routing
   function getHashRoute(): string {
@@ -87,7 +96,7 @@ const App: Component = () => {
         {/* Sidebar + Content row */}
         <div class="flex flex-1 min-h-0 max-[960px]:flex-col">
           {/* Sidebar */}
-          <Sidebar currentRoute={route()} />
+          <Sidebar currentRoute={route()} title={title()} />
 
           {/* View content */}
           <main data-testid="main-content" class="flex-1 overflow-hidden bg-surface-0 stencil-bg">
@@ -37,6 +37,7 @@ export interface StoredSpan {
 export interface TraceDetailResponse {
   trace_id: string;
   spans: StoredSpan[];
+  logs: StoredLog[];
 }
 
 export interface StoredLog {
@@ -84,6 +85,19 @@ export interface StatusResponse {
   metric_count: number;
   services: string[];
   trace_count: number;
+  orphan_span_count: number;
+  orphan_pairs?: OrphanPair[];
+}
+
+export interface OrphanPair {
+  upstream: string | null;
+  downstream: string;
+  count: number;
+}
+
+export interface MetaResponse {
+  title: string;
+  version: string;
 }
 
 export interface RelatedResponse {
@@ -189,6 +203,10 @@ export function fetchMetricSeries(
   return fetchJson<MetricSeriesResponse>(`${BASE_URL}/api/metrics/series?${query.toString()}`);
 }
 
+export function fetchMeta(): Promise<MetaResponse> {
+  return fetchJson<MetaResponse>(`${BASE_URL}/api/meta`);
+}
+
 export function fetchStatus(): Promise<StatusResponse> {
   return fetchJson<StatusResponse>(`${BASE_URL}/api/status`);
 }
@@ -224,11 +242,18 @@ export interface DeployedServiceInfo {
   last_deployed: string;
 }
 
+export interface PortForwardInfo {
+  local_port: number;
+  port_auto: boolean;
+  target: string;
+}
+
 export interface AddonInfo {
   name: string;
   addon_type: string;
   namespace: string;
   installed_at: string;
+  port_forwards: PortForwardInfo[];
 }
 
 export interface ClusterResponse {
@@ -4,6 +4,7 @@ import { theme, toggleTheme } from '../lib/theme';
 
 interface SidebarProps {
   currentRoute: string;
+  title?: string;
 }
 
 interface NavItem {
@@ -94,8 +95,11 @@ const Sidebar: Component<SidebarProps> = (props) => {
               <span /><span /><span /><span />
               <span /><span /><span /><span />
             </div>
-            <span class="font-label text-[9px] text-text-muted uppercase tracking-[0.25em] mt-2 text-center whitespace-nowrap">
-              Observability Platform
+            <span
+              data-testid="sidebar-title"
+              class="font-label text-[9px] text-text-muted uppercase tracking-[0.25em] mt-2 text-center whitespace-nowrap"
+            >
+              {props.title ?? 'Observability Platform'}
             </span>
           </a>
         </div>
@@ -84,6 +84,29 @@ const StatusView: Component = () => {
                 <StatCard label="Metrics" value={formatNumber(data().metric_count)} unit="points" href="#/metrics" />
               </div>
 
+              {/* Trace context propagation warning */}
+              <Show when={data().orphan_span_count > 0}>
+                <div data-testid="orphan-span-warning" class="border-2 border-warning/40 bg-warning/5 px-6 py-4">
+                  <p class="font-display text-lg text-warning tracking-[0.06em] uppercase">
+                    {formatNumber(data().orphan_span_count)} orphan spans
+                  </p>
+                  <p class="text-sm text-text-secondary mt-1">
+                    These spans reference a parent that never arrived. Trace context
+                    (<code class="font-mono">traceparent</code>) may not be propagated between:
+                  </p>
+                  <ul class="mt-2 space-y-1">
+                    <For each={data().orphan_pairs ?? []}>
+                      {(pair) => (
+                        <li class="font-mono text-xs text-text-primary">
+                          {pair.upstream ?? '?'} &rarr; {pair.downstream}
+                          <span class="text-text-muted"> ({pair.count})</span>
+                        </li>
+                      )}
+                    </For>
+                  </ul>
+                </div>
+              </Show>
+
               {/* Services */}
               <div class="border-2 border-border bg-surface-1">
                 <div class="px-6 py-4 border-b border-border flex items-center justify-between">
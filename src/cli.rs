@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use clap::{Args, Parser, Subcommand};
 use clap_complete::aot::Shell;
 use std::path::PathBuf;
@@ -17,15 +18,66 @@ pub struct GlobalOpts {
     /// Use a specific config file
     #[arg(short = 'f', long = "file", global = true)]
     pub config_file: Option<PathBuf>,
+
+    /// Never pull images; use only those already present locally
+    #[arg(long, global = true)]
+    pub no_pull: bool,
 }
 
 #[derive(Debug, Subcommand)]
 pub enum Commands {
     /// Start all services
     Start {
-        /// Specific services to start (start all if empty)
+        /// Specific services to start (start all if empty); globs like
+        /// `api-*` match against [services]
         services: Vec<String>,
 
+        /// Start the resources listed in a [profiles] entry (plus their dependencies)
+        #[arg(short, long, conflicts_with = "services")]
+        profile: Option<String>,
+
+        /// Startup summary format: table, or json (one object on stdout)
+        #[arg(long, default_value = "table", value_parser = ["table", "json"])]
+        summary_format: String,
+
+        /// Keep at most N log lines per second from each service (services
+        /// with max_log_lines_per_sec use their own limit)
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+        max_log_rate: Option<u32>,
+
+        /// Buffer up to N log lines for the log file and dashboard before the
+        /// oldest are dropped (default 4096)
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+        log_buffer: Option<u32>,
+
+        /// Print the launch order, ports, images, addons and per-service env
+        /// that start would use, then exit without starting anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Rebuild [cluster.image] entries even when their build context is
+        /// unchanged since the last start
+        #[arg(long)]
+        force_rebuild: bool,
+
+        /// Override the timeout of every ready check, in seconds
+        #[arg(long, value_name = "SECS", value_parser = clap::value_parser!(u64).range(1..))]
+        ready_timeout: Option<u64>,
+
+        /// Run in the background and return once everything is up; stop it
+        /// with `devrig stop`
+        #[arg(short, long, conflicts_with = "dry_run")]
+        detach: bool,
+
+        /// Stop everything when a service fails and won't be restarted, and
+        /// exit with that service's exit code
+        #[arg(long)]
+        exit_on_failure: bool,
+
+        /// Run in this process even with --detach (set on the detached child)
+        #[arg(long, hide = true)]
+        foreground: bool,
+
         /// Start Vite dev server for dashboard hot-reload
         #[cfg(debug_assertions)]
         #[arg(long, hide = true)]
@@ -39,6 +91,27 @@ pub enum Commands {
         /// Stop all running devrig instances
         #[arg(long)]
         all: bool,
+
+        /// Also remove docker containers; volumes and state are kept
+        #[arg(long)]
+        remove_containers: bool,
+    },
+    /// Restart services in a running project without touching docker/compose/cluster
+    Restart {
+        /// Services to restart
+        #[arg(required = true)]
+        services: Vec<String>,
+    },
+    /// Run more or fewer instances of services in a running project
+    Scale {
+        /// Target instance counts, as <service>=<count> (e.g. api=3)
+        #[arg(required = true, value_name = "SERVICE=COUNT")]
+        replicas: Vec<String>,
+    },
+    /// Re-read .env files and restart or signal services whose env changed
+    ReloadEnv {
+        /// Services to check (default: all running services)
+        services: Vec<String>,
     },
     /// Stop and remove all resources
     Delete {
@@ -51,36 +124,138 @@ pub enum Commands {
         /// Show all running devrig instances
         #[arg(long)]
         all: bool,
+
+        /// Print a `name -> port (url)` map instead; with a NAME, print only
+        /// that resource's port
+        #[arg(long, value_name = "NAME", conflicts_with = "all")]
+        ports: Option<Option<String>>,
+
+        /// Output format: table, json
+        #[arg(long, value_parser = ["table", "json"])]
+        format: Option<String>,
+    },
+    /// Show live CPU and memory usage of containers and services
+    Top,
+    /// Open the dashboard, or a resource's URL, in the default browser
+    Open {
+        /// Service, docker or compose name to open instead of the dashboard
+        name: Option<String>,
+    },
+    /// Wait until resources of the running project are ready
+    Wait {
+        /// Service, docker, compose or cluster names to wait for
+        #[arg(required = true)]
+        names: Vec<String>,
+
+        /// Give up after this many seconds
+        #[arg(long, value_name = "SECS", default_value_t = 120)]
+        timeout: u64,
     },
     /// Generate a starter devrig.toml
-    Init,
-    /// Check that dependencies are installed
-    Doctor,
+    Init {
+        /// Write only [project] and a single service
+        #[arg(long, conflicts_with = "full")]
+        minimal: bool,
+
+        /// Write a heavily commented example covering every section
+        #[arg(long, conflicts_with = "template")]
+        full: bool,
+
+        /// Scaffold a project for a common stack (devrig.toml plus any
+        /// compose file or k8s manifests it needs)
+        #[arg(
+            long,
+            conflicts_with = "minimal",
+            value_parser = ["rust-postgres", "node-redis", "fullstack-k3d"]
+        )]
+        template: Option<String>,
+
+        /// Overwrite files that already exist
+        #[arg(long)]
+        force: bool,
+    },
+    /// Check that dependencies are installed and look for stale state
+    Doctor {
+        /// Clean up stale PID files, registry entries, kubeconfigs and
+        /// orphaned containers left by runs that did not shut down cleanly
+        #[arg(long)]
+        fix: bool,
+    },
     /// Show resolved environment variables for a service
     Env {
         /// Service name to show env for
         service: String,
+        /// Print `export KEY='value'` lines for `eval "$(devrig env api --export)"`
+        /// (secrets are not masked)
+        #[arg(long, conflicts_with = "format")]
+        export: bool,
+        /// Output format: export (same as --export) or dotenv (`KEY=value`,
+        /// secrets are not masked)
+        #[arg(long, value_parser = ["export", "dotenv"])]
+        format: Option<String>,
     },
-    /// Execute a command in a docker container
+    /// Execute a command in a docker or compose container, or on the host
+    /// with a service's env (--service)
     Exec {
-        /// Docker service name
-        docker: String,
+        /// Docker, compose or (with --service) `[services]` name
+        name: String,
+        /// Only match `[docker.*]` services
+        #[arg(long, conflicts_with_all = ["compose", "service"])]
+        docker: bool,
+        /// Only match compose services
+        #[arg(long, conflicts_with = "service")]
+        compose: bool,
+        /// Run the command locally in the service's working dir, with the
+        /// env devrig injects into it (ports, DEVRIG_*, OTEL_*)
+        #[arg(long)]
+        service: bool,
         /// Command to execute
         #[arg(last = true)]
         command: Vec<String>,
     },
-    /// Reset init-completed flag for a docker service
+    /// Reset init-completed flag for a docker service, or wipe its data
     Reset {
         /// Docker service name
-        docker: String,
+        #[arg(required_unless_present = "all", conflicts_with = "all")]
+        docker: Option<String>,
+        /// Reset every docker service: remove its container and volumes,
+        /// then recreate it with init scripts re-run
+        #[arg(long)]
+        all: bool,
+        /// Remove the container and its volumes without recreating it; the
+        /// next `devrig start` does
+        #[arg(long)]
+        volumes_only: bool,
+        /// Don't ask before removing containers and volumes
+        #[arg(long, short = 'y')]
+        yes: bool,
     },
 
     /// Validate the configuration file
-    Validate,
+    Validate {
+        /// Output format: text (rendered diagnostics) or json (an array of
+        /// diagnostics with byte spans, for editors)
+        #[arg(long, default_value = "text", value_parser = ["text", "json"])]
+        format: String,
+    },
+
+    /// Inspect the resolved configuration
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+
+    /// Show the project slug and the resource names devrig derives from it
+    Whoami {
+        /// Print as JSON
+        #[arg(long)]
+        json: bool,
+    },
 
     /// Show and filter service logs
     Logs {
-        /// Services to show logs for (all if empty)
+        /// Services to show logs for (all if empty); globs like `api-*`
+        /// match against [services]
         services: Vec<String>,
 
         /// Follow log output (live tail)
@@ -111,15 +286,52 @@ pub enum Commands {
         #[arg(long, default_value = "text")]
         format: String,
 
-        /// Write output to file
+        /// Also append output to this file
         #[arg(short = 'o', long)]
         output: Option<PathBuf>,
 
+        /// With --output, write only to the file, not stdout
+        #[arg(short = 'q', long, requires = "output")]
+        quiet: bool,
+
+        /// With --output, roll the file over to FILE.1 once it passes this
+        /// size (e.g. 10M, 512K)
+        #[arg(long, value_name = "SIZE", requires = "output")]
+        rotate: Option<String>,
+
         /// Show timestamps
         #[arg(short = 't', long)]
         timestamps: bool,
+
+        /// Annotate each line with the service's current phase (running, restarting, failed)
+        #[arg(long, alias = "service-state")]
+        with_state: bool,
+
+        /// Show every process and OTLP log correlated to a trace ID, time-ordered
+        #[arg(long, value_name = "TRACE_ID")]
+        narrow: Option<String>,
+
+        /// Write the filtered logs, with secrets masked, to a shareable zip archive
+        #[arg(long, value_name = "FILE", conflicts_with_all = ["follow", "output"])]
+        export_bundle: Option<PathBuf>,
+
+        /// Print only the number of matching lines; exit 1 if there are none
+        #[arg(long, conflicts_with_all = ["follow", "export_bundle"])]
+        count: bool,
+
+        /// With --count, exit 1 if any lines match instead
+        #[arg(long, requires = "count")]
+        expect_zero: bool,
+
+        /// Read docker/compose resources straight from their containers
+        /// (works without the dashboard's log bridge)
+        #[arg(long, conflicts_with_all = ["narrow", "with_state", "export_bundle", "count"])]
+        container: bool,
     },
 
+    /// Print a JSON Schema for devrig.toml, for editor completion and checks
+    Schema,
+
     /// Generate shell completions
     Completions {
         /// Shell to generate completions for
@@ -150,6 +362,13 @@ pub enum Commands {
     /// Update devrig to the latest version
     Update,
 
+    /// Print the devrig version
+    Version {
+        /// Also check whether a newer release is available (cached for a day)
+        #[arg(long)]
+        check: bool,
+    },
+
     /// Manage the devrig Claude Code skill
     Skill {
         #[command(subcommand)]
@@ -173,10 +392,22 @@ pub enum QueryCommands {
         #[arg(long)]
         min_duration: Option<u64>,
 
-        /// Show traces from the last duration (e.g. "5m", "1h")
+        /// One row per request: only traces with a root span, filtered on the root
         #[arg(long)]
+        root_only: bool,
+
+        /// Show traces from the last duration (e.g. "5m", "1h")
+        #[arg(long, conflicts_with_all = ["from", "to"])]
         last: Option<String>,
 
+        /// Only traces at or after this RFC 3339 timestamp
+        #[arg(long, alias = "since")]
+        from: Option<DateTime<Utc>>,
+
+        /// Only traces at or before this RFC 3339 timestamp
+        #[arg(long, alias = "until")]
+        to: Option<DateTime<Utc>>,
+
         /// Max results to return
         #[arg(short = 'n', long, default_value = "20")]
         limit: usize,
@@ -215,9 +446,17 @@ pub enum QueryCommands {
         trace_id: Option<String>,
 
         /// Show logs from the last duration (e.g. "5m", "1h")
-        #[arg(long)]
+        #[arg(long, conflicts_with_all = ["from", "to"])]
         last: Option<String>,
 
+        /// Only logs at or after this RFC 3339 timestamp
+        #[arg(long, alias = "since")]
+        from: Option<DateTime<Utc>>,
+
+        /// Only logs at or before this RFC 3339 timestamp
+        #[arg(long, alias = "until")]
+        to: Option<DateTime<Utc>>,
+
         /// Max results to return
         #[arg(short = 'n', long, default_value = "50")]
         limit: usize,
@@ -225,6 +464,11 @@ pub enum QueryCommands {
         /// Output format: table, json, jsonl
         #[arg(long, alias = "output")]
         format: Option<String>,
+
+        /// Print every field of each record in JSON output, including
+        /// attributes and trace/span ids
+        #[arg(long)]
+        include_attributes: bool,
     },
 
     /// Query metrics from the OTel collector
@@ -238,9 +482,17 @@ pub enum QueryCommands {
         service: Option<String>,
 
         /// Show metrics from the last duration (e.g. "5m", "1h")
-        #[arg(long)]
+        #[arg(long, conflicts_with_all = ["from", "to"])]
         last: Option<String>,
 
+        /// Only metrics at or after this RFC 3339 timestamp
+        #[arg(long, alias = "since")]
+        from: Option<DateTime<Utc>>,
+
+        /// Only metrics at or before this RFC 3339 timestamp
+        #[arg(long, alias = "until")]
+        to: Option<DateTime<Utc>>,
+
         /// Max results to return
         #[arg(short = 'n', long, default_value = "50")]
         limit: usize,
@@ -268,11 +520,39 @@ pub enum QueryCommands {
     },
 }
 
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommands {
+    /// Print the config with secrets expanded and templates resolved
+    /// (secret values are masked)
+    Show {
+        /// Output format: toml, json
+        #[arg(long, default_value = "toml", value_parser = ["toml", "json"])]
+        format: String,
+    },
+    /// Compare two config files: services, docker, env and cluster deploys
+    /// added, removed or changed
+    Diff {
+        /// The config to compare from
+        old: PathBuf,
+
+        /// The config to compare to
+        new: PathBuf,
+
+        /// Output format: text or json
+        #[arg(long, default_value = "text", value_parser = ["text", "json"])]
+        format: String,
+    },
+}
+
 #[derive(Debug, Subcommand)]
 pub enum SkillCommands {
-    /// Install the devrig skill for Claude Code
+    /// Install the devrig skill for Claude Code, Cursor or VS Code
     Install {
-        /// Install globally to ~/.claude/skills/ instead of project-local
+        /// Editor to install for (repeatable). Defaults to the targets already
+        /// installed, or claude when there are none
+        #[arg(long = "target", value_parser = ["claude", "cursor", "vscode"])]
+        targets: Vec<String>,
+        /// Install to the user-wide location instead of project-local
         #[arg(long)]
         global: bool,
     },
@@ -298,4 +578,47 @@ pub enum ClusterCommands {
         #[arg(long)]
         no_apply: bool,
     },
+    /// Show recent Kubernetes events (Warnings by default)
+    Events {
+        /// Only show events from this namespace (default: namespaces devrig deploys into)
+        #[arg(short, long)]
+        namespace: Option<String>,
+
+        /// Only show events newer than this duration (e.g. "10m", "1h")
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Include Normal events as well as Warnings
+        #[arg(long)]
+        include_normal: bool,
+    },
+    /// Preview what re-applying deploy manifests would change (kubectl diff)
+    Diff {
+        /// Deploy to diff (omit to diff all deploys)
+        deploy: Option<String>,
+    },
+    /// Cordon, drain, or uncordon a cluster node (for resilience testing)
+    Node {
+        /// What to do with the node
+        #[arg(value_parser = ["cordon", "drain", "uncordon"])]
+        action: String,
+
+        /// Node name (default: the first agent node)
+        node: Option<String>,
+
+        /// With drain, skip DaemonSet-managed pods
+        #[arg(long)]
+        ignore_daemonsets: bool,
+
+        /// With drain, also evict pods not managed by a controller
+        #[arg(long)]
+        force: bool,
+    },
+    /// Load locally built images into the cluster nodes (k3d image import),
+    /// for clusters without a registry
+    ImportImage {
+        /// Image references to import, e.g. `myapp:dev`
+        #[arg(required = true, value_name = "IMAGE")]
+        images: Vec<String>,
+    },
 }
@@ -1,10 +1,11 @@
 use std::collections::{BTreeMap, BTreeSet};
 use std::path::Path;
+use std::process::Stdio;
 use std::time::{Duration, Instant};
 
 use anyhow::{bail, Context, Result};
 use chrono::Utc;
-use tokio::io::AsyncReadExt;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::process::Command;
 use tokio_util::sync::CancellationToken;
 use tokio_util::task::TaskTracker;
@@ -12,9 +13,10 @@ use tracing::{debug, error, info, warn};
 
 use std::collections::HashMap;
 
-use crate::config::model::AddonConfig;
+use crate::config::model::{AddonConfig, PortProtocol};
 use crate::config::interpolate::resolve_template;
-use crate::orchestrator::state::AddonState;
+use crate::orchestrator::ports::{find_free_port_excluding, AllocatedPorts};
+use crate::orchestrator::state::{AddonState, PortForwardState};
 
 // ---------------------------------------------------------------------------
 // Helm value conversion
@@ -69,13 +71,39 @@ async fn run_kubectl(
     kubeconfig: &Path,
     cancel: &CancellationToken,
 ) -> Result<String> {
-    let child = Command::new("kubectl")
+    run_kubectl_with_input(args, None, kubeconfig, cancel).await
+}
+
+/// Run a kubectl command, optionally writing `input` to its stdin.
+async fn run_kubectl_with_input(
+    args: &[&str],
+    input: Option<&str>,
+    kubeconfig: &Path,
+    cancel: &CancellationToken,
+) -> Result<String> {
+    let mut child = Command::new("kubectl")
         .args(args)
         .env("KUBECONFIG", kubeconfig)
-        .output();
+        .stdin(if input.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("running kubectl")?;
+
+    if let Some(input) = input {
+        let mut stdin = child.stdin.take().context("opening kubectl stdin")?;
+        stdin
+            .write_all(input.as_bytes())
+            .await
+            .context("writing kubectl stdin")?;
+    }
 
     let output = tokio::select! {
-        result = child => result.context("running kubectl")?,
+        result = child.wait_with_output() => result.context("running kubectl")?,
         _ = cancel.cancelled() => bail!("cancelled"),
     };
 
@@ -91,6 +119,33 @@ async fn run_kubectl(
     Ok(String::from_utf8_lossy(&output.stdout).to_string())
 }
 
+/// Create `namespace` if it doesn't exist yet. Equivalent to
+/// `kubectl create namespace <ns> --dry-run=client -o yaml | kubectl apply -f -`,
+/// so it is safe to run when the namespace is already there.
+async fn ensure_namespace(
+    namespace: &str,
+    kubeconfig: &Path,
+    cancel: &CancellationToken,
+) -> Result<()> {
+    let manifest = run_kubectl(
+        &[
+            "create",
+            "namespace",
+            namespace,
+            "--dry-run=client",
+            "-o",
+            "yaml",
+        ],
+        kubeconfig,
+        cancel,
+    )
+    .await?;
+    run_kubectl_with_input(&["apply", "-f", "-"], Some(&manifest), kubeconfig, cancel)
+        .await
+        .with_context(|| format!("creating namespace '{}'", namespace))?;
+    Ok(())
+}
+
 // ---------------------------------------------------------------------------
 // Individual addon installers
 // ---------------------------------------------------------------------------
@@ -101,9 +156,11 @@ async fn install_helm_addon(
     chart: &str,
     repo: Option<&str>,
     namespace: &str,
+    create_namespace: bool,
     version: Option<&str>,
     values: &BTreeMap<String, toml::Value>,
     values_files: &[String],
+    set_string: &BTreeMap<String, String>,
     wait: bool,
     timeout: &str,
     skip_crds: bool,
@@ -163,9 +220,12 @@ async fn install_helm_addon(
         resolved_chart,
         "--namespace".to_string(),
         namespace.to_string(),
-        "--create-namespace".to_string(),
     ];
 
+    if create_namespace {
+        args.push("--create-namespace".to_string());
+    }
+
     if skip_crds {
         args.push("--skip-crds".to_string());
     }
@@ -181,6 +241,33 @@ async fn install_helm_addon(
         args.push(v.to_string());
     }
 
+    args.extend(helm_values_args(
+        values_files,
+        values,
+        set_string,
+        config_dir,
+    ));
+
+    let arg_refs: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+    run_helm(&arg_refs, kubeconfig, cancel)
+        .await
+        .with_context(|| format!("installing helm addon '{}'", name))?;
+
+    debug!(addon = %name, chart = %chart, "helm addon installed");
+    Ok(())
+}
+
+/// The values arguments for a helm install, in precedence order (helm lets
+/// later ones win): `-f` for each values file in listed order, then `--set`
+/// for `values`, then `--set-string` for `set_string`.
+fn helm_values_args(
+    values_files: &[String],
+    values: &BTreeMap<String, toml::Value>,
+    set_string: &BTreeMap<String, String>,
+    config_dir: &Path,
+) -> Vec<String> {
+    let mut args = Vec::new();
+
     // Add -f for each values file
     for vf in values_files {
         let vf_path = if Path::new(vf).is_absolute() {
@@ -198,13 +285,13 @@ async fn install_helm_addon(
         args.push(format!("{}={}", k, toml_value_to_helm_set(v)));
     }
 
-    let arg_refs: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
-    run_helm(&arg_refs, kubeconfig, cancel)
-        .await
-        .with_context(|| format!("installing helm addon '{}'", name))?;
+    // Add --set-string for values that must not be type-coerced
+    for (k, v) in set_string {
+        args.push("--set-string".to_string());
+        args.push(format!("{}={}", k, v));
+    }
 
-    debug!(addon = %name, chart = %chart, "helm addon installed");
-    Ok(())
+    args
 }
 
 /// Returns true if the error looks like a missing-CRD / resource-mapping failure,
@@ -243,10 +330,12 @@ fn resolve_manifest_templates(
     Ok(Some(tmp_path))
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn install_manifest_addon(
     name: &str,
     path: &str,
     namespace: Option<&str>,
+    create_namespace: bool,
     template_vars: &HashMap<String, String>,
     kubeconfig: &Path,
     config_dir: &Path,
@@ -265,6 +354,12 @@ async fn install_manifest_addon(
         .map(|p| p.to_string_lossy().to_string())
         .unwrap_or_else(|| manifest_path.to_string_lossy().to_string());
 
+    if let Some(ns) = namespace.filter(|_| create_namespace) {
+        ensure_namespace(ns, kubeconfig, cancel)
+            .await
+            .with_context(|| format!("preparing namespace for addon '{}'", name))?;
+    }
+
     let mut args = vec!["apply", "-f", &apply_path];
     let ns_str;
     if let Some(ns) = namespace {
@@ -302,10 +397,12 @@ async fn install_manifest_addon(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn install_kustomize_addon(
     name: &str,
     path: &str,
     namespace: Option<&str>,
+    create_namespace: bool,
     _template_vars: &HashMap<String, String>,
     kubeconfig: &Path,
     config_dir: &Path,
@@ -318,6 +415,12 @@ async fn install_kustomize_addon(
     };
     let kustomize_str = kustomize_path.to_string_lossy().to_string();
 
+    if let Some(ns) = namespace.filter(|_| create_namespace) {
+        ensure_namespace(ns, kubeconfig, cancel)
+            .await
+            .with_context(|| format!("preparing namespace for addon '{}'", name))?;
+    }
+
     let mut args = vec!["apply", "-k", &kustomize_str];
     let ns_str;
     if let Some(ns) = namespace {
@@ -430,6 +533,18 @@ pub fn topo_sort_addons(addons: &BTreeMap<String, AddonConfig>) -> Result<Vec<St
 // Bulk install/uninstall
 // ---------------------------------------------------------------------------
 
+/// Resolve `{{ }}` templates in one addon value; `field_ctx` names it in errors.
+fn resolve_value_template(
+    value: &str,
+    template_vars: &HashMap<String, String>,
+    field_ctx: &str,
+) -> Result<String> {
+    resolve_template(value, template_vars, field_ctx).map_err(|errs| {
+        let msgs: Vec<String> = errs.iter().map(|e| e.to_string()).collect();
+        anyhow::anyhow!("{}", msgs.join("; "))
+    })
+}
+
 /// Resolve `{{ }}` templates in the string values of a TOML values map.
 fn resolve_values_templates(
     values: &BTreeMap<String, toml::Value>,
@@ -441,13 +556,7 @@ fn resolve_values_templates(
         let resolved_val = match value {
             toml::Value::String(s) => {
                 let field_ctx = format!("cluster.addons.{addon_name}.values.{key}");
-                match resolve_template(s, template_vars, &field_ctx) {
-                    Ok(r) => toml::Value::String(r),
-                    Err(errs) => {
-                        let msgs: Vec<String> = errs.iter().map(|e| e.to_string()).collect();
-                        bail!("{}", msgs.join("; "));
-                    }
-                }
+                toml::Value::String(resolve_value_template(s, template_vars, &field_ctx)?)
             }
             other => other.clone(),
         };
@@ -456,6 +565,22 @@ fn resolve_values_templates(
     Ok(resolved)
 }
 
+/// Resolve `{{ }}` templates in an addon's `set_string` values.
+fn resolve_set_string_templates(
+    set_string: &BTreeMap<String, String>,
+    template_vars: &HashMap<String, String>,
+    addon_name: &str,
+) -> Result<BTreeMap<String, String>> {
+    set_string
+        .iter()
+        .map(|(key, value)| {
+            let field_ctx = format!("cluster.addons.{addon_name}.set_string.{key}");
+            let resolved = resolve_value_template(value, template_vars, &field_ctx)?;
+            Ok((key.clone(), resolved))
+        })
+        .collect()
+}
+
 /// Install all addons in dependency order (topological sort, alphabetical tie-break).
 /// Returns a map of addon states for persistence.
 pub async fn install_addons(
@@ -477,9 +602,11 @@ pub async fn install_addons(
                 chart,
                 repo,
                 namespace,
+                create_namespace,
                 version,
                 values,
                 values_files,
+                set_string,
                 wait,
                 timeout,
                 skip_crds,
@@ -487,14 +614,18 @@ pub async fn install_addons(
             } => {
                 let resolved_values =
                     resolve_values_templates(values, template_vars, name)?;
+                let resolved_set_string =
+                    resolve_set_string_templates(set_string, template_vars, name)?;
                 install_helm_addon(
                     name,
                     chart,
                     repo.as_deref(),
                     namespace,
+                    *create_namespace,
                     version.as_deref(),
                     &resolved_values,
                     values_files,
+                    &resolved_set_string,
                     *wait,
                     timeout,
                     *skip_crds,
@@ -509,16 +640,21 @@ pub async fn install_addons(
                         addon_type: "helm".to_string(),
                         namespace: namespace.clone(),
                         installed_at: Utc::now(),
+                        port_forwards: Vec::new(),
                     },
                 );
             }
             AddonConfig::Manifest {
-                path, namespace, ..
+                path,
+                namespace,
+                create_namespace,
+                ..
             } => {
                 install_manifest_addon(
                     name,
                     path,
                     namespace.as_deref(),
+                    *create_namespace,
                     template_vars,
                     kubeconfig,
                     config_dir,
@@ -531,16 +667,21 @@ pub async fn install_addons(
                         addon_type: "manifest".to_string(),
                         namespace: namespace.as_deref().unwrap_or("default").to_string(),
                         installed_at: Utc::now(),
+                        port_forwards: Vec::new(),
                     },
                 );
             }
             AddonConfig::Kustomize {
-                path, namespace, ..
+                path,
+                namespace,
+                create_namespace,
+                ..
             } => {
                 install_kustomize_addon(
                     name,
                     path,
                     namespace.as_deref(),
+                    *create_namespace,
                     template_vars,
                     kubeconfig,
                     config_dir,
@@ -553,6 +694,7 @@ pub async fn install_addons(
                         addon_type: "kustomize".to_string(),
                         namespace: namespace.as_deref().unwrap_or("default").to_string(),
                         installed_at: Utc::now(),
+                        port_forwards: Vec::new(),
                     },
                 );
             }
@@ -657,16 +799,30 @@ impl PortForwardManager {
     }
 
     /// Start port-forwards for all addons that have port_forward entries.
-    pub fn start_port_forwards(&self, addons: &BTreeMap<String, AddonConfig>, kubeconfig: &Path) {
+    ///
+    /// An `"auto"` key gets a free local port outside `allocated`; every
+    /// local port used is added to `allocated`. Returns the forwards started
+    /// for each addon.
+    pub fn start_port_forwards(
+        &self,
+        addons: &BTreeMap<String, AddonConfig>,
+        kubeconfig: &Path,
+        allocated: &mut AllocatedPorts,
+    ) -> BTreeMap<String, Vec<PortForwardState>> {
+        let mut started: BTreeMap<String, Vec<PortForwardState>> = BTreeMap::new();
         for (name, addon) in addons {
             let namespace = addon.namespace().unwrap_or("default").to_string();
 
             for (port_str, target) in addon.port_forward() {
-                let local_port = match port_str.parse::<u16>() {
-                    Ok(p) => p,
-                    Err(_) => {
-                        warn!(addon = %name, port = %port_str, "invalid port-forward port, skipping");
-                        continue;
+                let (local_port, port_auto) = if port_str == "auto" {
+                    (find_free_port_excluding(allocated, PortProtocol::Tcp), true)
+                } else {
+                    match port_str.parse::<u16>() {
+                        Ok(p) => (p, false),
+                        Err(_) => {
+                            warn!(addon = %name, port = %port_str, "invalid port-forward port, skipping");
+                            continue;
+                        }
                     }
                 };
 
@@ -679,6 +835,16 @@ impl PortForwardManager {
                     }
                 };
 
+                allocated.insert((local_port, PortProtocol::Tcp));
+                started
+                    .entry(name.clone())
+                    .or_default()
+                    .push(PortForwardState {
+                        local_port,
+                        port_auto,
+                        target: target.clone(),
+                    });
+
                 let cancel = self.cancel.clone();
                 let kubeconfig = kubeconfig.to_path_buf();
                 let addon_name = name.clone();
@@ -785,6 +951,7 @@ impl PortForwardManager {
                 });
             }
         }
+        started
     }
 
     /// Stop all port-forward processes.
@@ -846,16 +1013,103 @@ mod tests {
         assert_eq!(toml_value_to_helm_set(&val), "{a,b,c}");
     }
 
+    #[test]
+    fn helm_values_args_order_files_then_set_then_set_string() {
+        let values_files = vec!["values-dev.yaml".to_string(), "/abs/local.yaml".to_string()];
+        let values = BTreeMap::from([
+            ("replicas".to_string(), toml::Value::Integer(2)),
+            ("image.tag".to_string(), toml::Value::from("1.0")),
+        ]);
+        let set_string = BTreeMap::from([("image.tag".to_string(), "1.0".to_string())]);
+
+        let args = helm_values_args(&values_files, &values, &set_string, Path::new("/project"));
+        assert_eq!(
+            args,
+            vec![
+                "-f",
+                "/project/values-dev.yaml",
+                "-f",
+                "/abs/local.yaml",
+                "--set",
+                "image.tag=1.0",
+                "--set",
+                "replicas=2",
+                "--set-string",
+                "image.tag=1.0",
+            ]
+        );
+    }
+
+    #[test]
+    fn set_string_templates_are_resolved() {
+        let vars = HashMap::from([(
+            "cluster.image.myapp.tag".to_string(),
+            "1700000000".to_string(),
+        )]);
+        let set_string = BTreeMap::from([(
+            "image.tag".to_string(),
+            "{{ cluster.image.myapp.tag }}".to_string(),
+        )]);
+        let resolved = resolve_set_string_templates(&set_string, &vars, "myapp").unwrap();
+        assert_eq!(resolved["image.tag"], "1700000000");
+
+        let unknown = BTreeMap::from([("a".to_string(), "{{ nope }}".to_string())]);
+        let err = resolve_set_string_templates(&unknown, &vars, "myapp")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("cluster.addons.myapp.set_string.a"), "{err}");
+    }
+
     /// Helper to build a minimal Manifest addon for topo-sort tests.
     fn manifest_addon(deps: Vec<&str>) -> AddonConfig {
         AddonConfig::Manifest {
             path: "./test.yaml".to_string(),
             namespace: None,
+            create_namespace: true,
             port_forward: BTreeMap::new(),
             depends_on: deps.into_iter().map(String::from).collect(),
         }
     }
 
+    #[tokio::test]
+    async fn auto_port_forward_gets_a_free_port() {
+        let addon = AddonConfig::Manifest {
+            path: "./test.yaml".to_string(),
+            namespace: None,
+            create_namespace: true,
+            port_forward: BTreeMap::from([
+                ("auto".to_string(), "svc/grafana:80".to_string()),
+                ("9000".to_string(), "svc/traefik:9000".to_string()),
+            ]),
+            depends_on: vec![],
+        };
+        let addons = BTreeMap::from([("ui".to_string(), addon)]);
+        let mut allocated = AllocatedPorts::from([(3000, PortProtocol::Tcp)]);
+
+        let mgr = PortForwardManager::new();
+        let started = mgr.start_port_forwards(&addons, Path::new("/nonexistent"), &mut allocated);
+        mgr.stop().await;
+
+        let forwards = &started["ui"];
+        assert_eq!(forwards.len(), 2);
+        assert_eq!(
+            forwards[0],
+            PortForwardState {
+                local_port: 9000,
+                port_auto: false,
+                target: "svc/traefik:9000".to_string(),
+            }
+        );
+        let auto = &forwards[1];
+        assert!(auto.port_auto);
+        assert_eq!(auto.target, "svc/grafana:80");
+        assert!(auto.local_port != 3000 && auto.local_port != 9000);
+        assert!(
+            allocated.contains(&(auto.local_port, PortProtocol::Tcp))
+                && allocated.contains(&(9000, PortProtocol::Tcp))
+        );
+    }
+
     #[test]
     fn topo_sort_no_deps_is_alphabetical() {
         let mut addons = BTreeMap::new();
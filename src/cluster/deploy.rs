@@ -1,7 +1,8 @@
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
 use chrono::Utc;
-use std::collections::BTreeMap;
-use std::path::Path;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 use tokio::process::Command;
 use tokio_util::sync::CancellationToken;
@@ -11,14 +12,14 @@ use crate::config::model::{ClusterDeployConfig, ClusterImageConfig};
 use crate::orchestrator::state::ClusterDeployState;
 
 /// Run a subprocess command with optional working directory and environment variable,
-/// racing the process against the cancellation token.
+/// racing the process against the cancellation token. Returns its stdout.
 async fn run_cmd(
     cmd: &str,
     args: &[&str],
     working_dir: Option<&Path>,
     env: Option<(&str, &Path)>,
     cancel: &CancellationToken,
-) -> Result<()> {
+) -> Result<String> {
     let mut command = Command::new(cmd);
     command.args(args);
 
@@ -49,7 +50,7 @@ async fn run_cmd(
         );
     }
 
-    Ok(())
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
 }
 
 use crate::platform;
@@ -112,6 +113,264 @@ fn interpolate_image_refs(value: &str, deployed: &BTreeMap<String, ClusterDeploy
     result
 }
 
+/// Name of the ConfigMap devrig generates for a deploy's `env`.
+pub fn env_config_map_name(name: &str) -> String {
+    format!("{name}-devrig-env")
+}
+
+/// Render the ConfigMap manifests for a deploy's `env`, one in each of
+/// `namespaces`, as a `List`. JSON is valid YAML, and serde handles quoting
+/// of arbitrary values.
+fn render_env_config_map(
+    name: &str,
+    namespaces: &BTreeSet<String>,
+    env: &BTreeMap<String, String>,
+) -> String {
+    let items: Vec<serde_json::Value> = namespaces
+        .iter()
+        .map(|namespace| {
+            serde_json::json!({
+                "apiVersion": "v1",
+                "kind": "ConfigMap",
+                "metadata": {
+                    "name": env_config_map_name(name),
+                    "namespace": namespace,
+                    "labels": { "app.kubernetes.io/managed-by": "devrig" },
+                },
+                "data": env,
+            })
+        })
+        .collect();
+    serde_json::to_string_pretty(&serde_json::json!({
+        "apiVersion": "v1",
+        "kind": "List",
+        "items": items,
+    }))
+    .expect("ConfigMap manifest must serialize")
+}
+
+/// Env keys in a ConfigMap manifest written by [`render_env_config_map`].
+/// Empty when it can't be parsed.
+fn config_map_env_keys(manifest: &str) -> BTreeSet<String> {
+    let Ok(manifest) = serde_json::from_str::<serde_json::Value>(manifest) else {
+        return BTreeSet::new();
+    };
+    manifest["items"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|item| item["data"].as_object())
+        .flat_map(|data| data.keys().cloned())
+        .collect()
+}
+
+/// Kinds of the workloads that run pods from a pod template, so `kubectl
+/// set env` and `rollout restart` apply to them.
+const WORKLOAD_KINDS: &[&str] = &["Deployment", "StatefulSet", "DaemonSet"];
+
+/// A workload applied from a deploy's manifests.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Workload {
+    namespace: String,
+    /// `kind/name` as kubectl takes it, e.g. `deployment/api`.
+    resource: String,
+}
+
+/// Pick the workloads out of `kubectl apply -o json` output: a single
+/// object, or a `List` of them when several were applied.
+fn applied_workloads(apply_output: &str) -> Result<Vec<Workload>> {
+    let output: serde_json::Value =
+        serde_json::from_str(apply_output).context("parsing kubectl apply output")?;
+    let objects = match output["kind"].as_str() {
+        Some("List") => output["items"].as_array().cloned().unwrap_or_default(),
+        _ => vec![output],
+    };
+    Ok(objects
+        .iter()
+        .filter_map(|object| {
+            let kind = object["kind"].as_str()?;
+            if !WORKLOAD_KINDS.contains(&kind) {
+                return None;
+            }
+            let metadata = &object["metadata"];
+            Some(Workload {
+                namespace: metadata["namespace"]
+                    .as_str()
+                    .unwrap_or("default")
+                    .to_string(),
+                resource: format!("{}/{}", kind.to_lowercase(), metadata["name"].as_str()?),
+            })
+        })
+        .collect())
+}
+
+/// Namespaces of `workloads`, sorted and deduplicated.
+fn workload_namespaces(workloads: &[Workload]) -> BTreeSet<String> {
+    workloads.iter().map(|w| w.namespace.clone()).collect()
+}
+
+/// Apply a deploy's manifests and return the workloads they define.
+async fn apply_deploy_manifests(
+    name: &str,
+    manifests_path: &Path,
+    kubeconfig_path: &Path,
+    cancel: &CancellationToken,
+) -> Result<Vec<Workload>> {
+    let manifests_str = manifests_path.to_string_lossy();
+    debug!(name, manifests = %manifests_str, "applying manifests");
+    let output = run_cmd(
+        "kubectl",
+        &["apply", "-f", &manifests_str, "-o", "json"],
+        None,
+        Some(("KUBECONFIG", kubeconfig_path)),
+        cancel,
+    )
+    .await?;
+    applied_workloads(&output)
+}
+
+/// Restart `workloads` so they pick up a new image.
+async fn restart_workloads(
+    name: &str,
+    workloads: &[Workload],
+    kubeconfig_path: &Path,
+    cancel: &CancellationToken,
+) -> Result<()> {
+    for workload in workloads {
+        debug!(
+            name,
+            workload = %workload.resource,
+            namespace = %workload.namespace,
+            "restarting workload"
+        );
+        run_cmd(
+            "kubectl",
+            &[
+                "rollout",
+                "restart",
+                "-n",
+                &workload.namespace,
+                &workload.resource,
+            ],
+            None,
+            Some(("KUBECONFIG", kubeconfig_path)),
+            cancel,
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+/// Run `kubectl set env` on each of `workloads` with `args`, e.g.
+/// `--from=configmap/<name>` or `KEY-` to remove a variable.
+async fn set_workload_env(
+    workloads: &[Workload],
+    args: &[String],
+    kubeconfig_path: &Path,
+    cancel: &CancellationToken,
+) -> Result<()> {
+    for workload in workloads {
+        let mut cmd_args = vec!["set", "env", "-n", &workload.namespace, &workload.resource];
+        cmd_args.extend(args.iter().map(String::as_str));
+        run_cmd(
+            "kubectl",
+            &cmd_args,
+            None,
+            Some(("KUBECONFIG", kubeconfig_path)),
+            cancel,
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+/// Apply the env ConfigMap in each workload's namespace and inject it into
+/// the deploy's `workloads` with `kubectl set env --from`. Keys dropped
+/// since the last apply are removed from the workloads, so they don't keep
+/// referencing them. The ConfigMap manifest is written to the project state
+/// dir. Returns the ConfigMap name, or `None` when the deploy has no env.
+async fn apply_env(
+    name: &str,
+    env: &BTreeMap<String, String>,
+    workloads: &[Workload],
+    kubeconfig_path: &Path,
+    state_dir: &Path,
+    cancel: &CancellationToken,
+) -> Result<Option<String>> {
+    let manifest_path = state_dir.join(format!("deploy-{name}-env.yaml"));
+    let dropped: Vec<String> = std::fs::read_to_string(&manifest_path)
+        .map(|previous| config_map_env_keys(&previous))
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|key| !env.contains_key(key))
+        .map(|key| format!("{key}-"))
+        .collect();
+    if !dropped.is_empty() {
+        debug!(name, ?dropped, "removing dropped env keys");
+        set_workload_env(workloads, &dropped, kubeconfig_path, cancel).await?;
+    }
+
+    if env.is_empty() {
+        let _ = std::fs::remove_file(&manifest_path);
+        return Ok(None);
+    }
+    if workloads.is_empty() {
+        bail!(
+            "deploy '{}' sets env, but its manifests define no deployment, statefulset or daemonset to inject it into",
+            name
+        );
+    }
+
+    let config_map = env_config_map_name(name);
+    let manifest = render_env_config_map(name, &workload_namespaces(workloads), env);
+    std::fs::write(&manifest_path, manifest)
+        .with_context(|| format!("writing ConfigMap to '{}'", manifest_path.display()))?;
+
+    debug!(name, config_map, "applying env ConfigMap");
+    let manifest_str = manifest_path.to_string_lossy();
+    run_cmd(
+        "kubectl",
+        &["apply", "-f", &manifest_str],
+        None,
+        Some(("KUBECONFIG", kubeconfig_path)),
+        cancel,
+    )
+    .await?;
+
+    let from = format!("--from=configmap/{config_map}");
+    set_workload_env(workloads, &[from], kubeconfig_path, cancel).await?;
+
+    Ok(Some(config_map))
+}
+
+/// Delete a previously generated env ConfigMap from each of `namespaces`.
+pub async fn delete_env_config_map(
+    config_map: &str,
+    namespaces: &[String],
+    kubeconfig_path: &Path,
+    cancel: &CancellationToken,
+) -> Result<()> {
+    for namespace in namespaces {
+        debug!(config_map, namespace, "deleting env ConfigMap");
+        run_cmd(
+            "kubectl",
+            &[
+                "delete",
+                "configmap",
+                "-n",
+                namespace,
+                config_map,
+                "--ignore-not-found",
+            ],
+            None,
+            Some(("KUBECONFIG", kubeconfig_path)),
+            cancel,
+        )
+        .await?;
+    }
+    Ok(())
+}
+
 /// Build, push (if registry is available), and apply manifests for a cluster deploy entry.
 /// Returns the deploy state with the image tag and timestamp.
 pub async fn run_deploy(
@@ -120,6 +379,7 @@ pub async fn run_deploy(
     registry_port: Option<u16>,
     kubeconfig_path: &Path,
     config_dir: &Path,
+    state_dir: &Path,
     cancel: &CancellationToken,
 ) -> Result<ClusterDeployState> {
     let context_path = config_dir.join(&deploy_config.context);
@@ -156,13 +416,14 @@ pub async fn run_deploy(
     }
 
     // kubectl apply
-    let manifests_str = manifests_path.to_string_lossy();
-    debug!(name, manifests = %manifests_str, "applying manifests");
-    run_cmd(
-        "kubectl",
-        &["apply", "-f", &manifests_str],
-        None,
-        Some(("KUBECONFIG", kubeconfig_path)),
+    let workloads = apply_deploy_manifests(name, &manifests_path, kubeconfig_path, cancel).await?;
+
+    let config_map = apply_env(
+        name,
+        &deploy_config.env,
+        &workloads,
+        kubeconfig_path,
+        state_dir,
         cancel,
     )
     .await?;
@@ -170,16 +431,69 @@ pub async fn run_deploy(
     Ok(ClusterDeployState {
         image_tag: tag,
         last_deployed: Utc::now(),
+        config_map,
+        namespaces: workload_namespaces(&workloads).into_iter().collect(),
+        context_checksum: None,
     })
 }
 
-/// Rebuild: same as run_deploy but also restarts the deployment to pick up the new image.
+/// Deploys to diff: the one named, or every deploy when `name` is `None`.
+pub fn select_deploys<'a>(
+    deploys: &'a BTreeMap<String, ClusterDeployConfig>,
+    name: Option<&str>,
+) -> Result<Vec<(&'a str, &'a ClusterDeployConfig)>> {
+    match name {
+        Some(name) => match deploys.get_key_value(name) {
+            Some((name, deploy)) => Ok(vec![(name.as_str(), deploy)]),
+            None => bail!(
+                "unknown cluster deploy '{}' (available: {})",
+                name,
+                deploys.keys().cloned().collect::<Vec<_>>().join(", ")
+            ),
+        },
+        None => Ok(deploys.iter().map(|(n, d)| (n.as_str(), d)).collect()),
+    }
+}
+
+/// Run `kubectl diff` on a deploy's manifests, previewing what `kubectl
+/// apply` on the next deploy would change. Returns the diff, empty when
+/// the cluster already matches.
+pub async fn diff_deploy(
+    name: &str,
+    deploy_config: &ClusterDeployConfig,
+    kubeconfig_path: &Path,
+    config_dir: &Path,
+) -> Result<String> {
+    let manifests_path = config_dir.join(&deploy_config.manifests);
+    debug!(name, manifests = %manifests_path.display(), "diffing manifests");
+    let output = Command::new("kubectl")
+        .arg("diff")
+        .arg("-f")
+        .arg(&manifests_path)
+        .env("KUBECONFIG", kubeconfig_path)
+        .output()
+        .await
+        .context("running kubectl")?;
+
+    // kubectl diff exits 1 when there are differences and above 1 on error.
+    match output.status.code() {
+        Some(0) | Some(1) => Ok(String::from_utf8_lossy(&output.stdout).into_owned()),
+        _ => bail!(
+            "kubectl diff failed for '{}': {}",
+            name,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+    }
+}
+
+/// Rebuild: same as run_deploy but also restarts the deploy's workloads to pick up the new image.
 pub async fn run_rebuild(
     name: &str,
     deploy_config: &ClusterDeployConfig,
     registry_port: Option<u16>,
     kubeconfig_path: &Path,
     config_dir: &Path,
+    state_dir: &Path,
     cancel: &CancellationToken,
 ) -> Result<()> {
     let context_path = config_dir.join(&deploy_config.context);
@@ -216,13 +530,14 @@ pub async fn run_rebuild(
     }
 
     // kubectl apply
-    let manifests_str = manifests_path.to_string_lossy();
-    debug!(name, manifests = %manifests_str, "applying manifests");
-    run_cmd(
-        "kubectl",
-        &["apply", "-f", &manifests_str],
-        None,
-        Some(("KUBECONFIG", kubeconfig_path)),
+    let workloads = apply_deploy_manifests(name, &manifests_path, kubeconfig_path, cancel).await?;
+
+    apply_env(
+        name,
+        &deploy_config.env,
+        &workloads,
+        kubeconfig_path,
+        state_dir,
         cancel,
     )
     .await?;
@@ -232,31 +547,131 @@ pub async fn run_rebuild(
     }
 
     // Rollout restart to pick up the new image
-    let deployment = format!("deployment/{name}");
-    debug!(name, "restarting deployment");
-    run_cmd(
-        "kubectl",
-        &["rollout", "restart", &deployment],
-        None,
-        Some(("KUBECONFIG", kubeconfig_path)),
-        cancel,
-    )
-    .await?;
+    restart_workloads(name, &workloads, kubeconfig_path, cancel).await
+}
 
-    Ok(())
+/// Files in a build context, relative to it and sorted: what git tracks
+/// plus untracked files it doesn't ignore, or every file outside `.git`
+/// when the context isn't in a git repository.
+fn context_files(context: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let output = std::process::Command::new("git")
+        .arg("ls-files")
+        .args(["-z", "--cached", "--others", "--exclude-standard"])
+        .current_dir(context)
+        .output();
+    let mut files = match output {
+        Ok(output) if output.status.success() => output
+            .stdout
+            .split(|b| *b == 0)
+            .filter(|path| !path.is_empty())
+            .map(|path| PathBuf::from(String::from_utf8_lossy(path).into_owned()))
+            .collect(),
+        _ => {
+            let mut files = Vec::new();
+            let mut dirs = vec![PathBuf::new()];
+            while let Some(dir) = dirs.pop() {
+                for entry in std::fs::read_dir(context.join(&dir))? {
+                    let entry = entry?;
+                    let path = dir.join(entry.file_name());
+                    if entry.file_type()?.is_dir() {
+                        if entry.file_name() != ".git" {
+                            dirs.push(path);
+                        }
+                    } else {
+                        files.push(path);
+                    }
+                }
+            }
+            files
+        }
+    };
+    files.sort();
+    Ok(files)
+}
+
+/// SHA-256 over everything that goes into an image build: the Dockerfile,
+/// the build args, and the path and contents of every file in the context
+/// (see [`context_files`]).
+pub fn context_checksum(
+    context: &Path,
+    dockerfile: &str,
+    build_args: &[String],
+) -> std::io::Result<String> {
+    let mut hasher = Sha256::new();
+    hasher.update(dockerfile.as_bytes());
+    hasher.update([0]);
+    hasher.update(std::fs::read(context.join(dockerfile))?);
+    for arg in build_args {
+        hasher.update(arg.as_bytes());
+        hasher.update([0]);
+    }
+    for path in context_files(context)? {
+        hasher.update(path.to_string_lossy().as_bytes());
+        hasher.update([0]);
+        // Deleted but still in the index: the path alone marks the change
+        if let Ok(content) = std::fs::read(context.join(&path)) {
+            hasher.update(Sha256::digest(&content));
+        }
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Whether an image built by an earlier start can be used as is: it is
+/// still present locally and, with a registry, was pushed to this one.
+async fn image_reusable(tag: &str, registry_port: Option<u16>) -> bool {
+    let expected_prefix = match registry_port {
+        Some(port) => format!("localhost:{port}/"),
+        None => "devrig-".to_string(),
+    };
+    if !tag.starts_with(&expected_prefix) {
+        return false;
+    }
+    Command::new("docker")
+        .args(["image", "inspect", tag])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .await
+        .is_ok_and(|status| status.success())
 }
 
 /// Build and push an image to the registry without applying any manifests.
 /// Used for `[cluster.image.*]` entries that only need the image available.
+///
+/// When `previous` was built from an identical context (see
+/// [`context_checksum`]) and its image is still available, the build is
+/// skipped and its tag reused.
 pub async fn run_image_build(
     name: &str,
     image_config: &ClusterImageConfig,
     registry_port: Option<u16>,
     config_dir: &Path,
     deployed: &BTreeMap<String, ClusterDeployState>,
+    previous: Option<&ClusterDeployState>,
     cancel: &CancellationToken,
 ) -> Result<ClusterDeployState> {
     let context_path = config_dir.join(&image_config.context);
+    let build_args = format_build_args(&image_config.build_args, deployed);
+
+    let checksum = match context_checksum(&context_path, &image_config.dockerfile, &build_args) {
+        Ok(checksum) => Some(checksum),
+        Err(e) => {
+            debug!(name, error = %e, "could not checksum build context; rebuilding");
+            None
+        }
+    };
+    if let Some(previous) = previous {
+        if checksum.is_some()
+            && previous.context_checksum == checksum
+            && image_reusable(&previous.image_tag, registry_port).await
+        {
+            debug!(name, tag = %previous.image_tag, "build context unchanged; reusing image");
+            return Ok(ClusterDeployState {
+                context_checksum: checksum,
+                ..previous.clone()
+            });
+        }
+    }
 
     // Build the image tag
     let tag = if let Some(port) = registry_port {
@@ -271,7 +686,6 @@ pub async fn run_image_build(
     // Docker build
     debug!(name, tag, "building image");
     let secret_args = format_secret_args(&image_config.build_secrets);
-    let build_args = format_build_args(&image_config.build_args, deployed);
     let args = docker_build_args(&tag, &image_config.dockerfile, &secret_args, &build_args, false);
     run_cmd("docker", &args, Some(&context_path), None, cancel).await?;
 
@@ -293,6 +707,9 @@ pub async fn run_image_build(
     Ok(ClusterDeployState {
         image_tag: tag,
         last_deployed: Utc::now(),
+        config_map: None,
+        namespaces: Vec::new(),
+        context_checksum: checksum,
     })
 }
 
@@ -386,11 +803,18 @@ pub async fn fresh_rebuild_image(
     Ok(ClusterDeployState {
         image_tag: tag,
         last_deployed: Utc::now(),
+        config_map: None,
+        namespaces: Vec::new(),
+        context_checksum: None,
     })
 }
 
 /// Build, push (with --no-cache), and optionally apply manifests + rollout restart
 /// for a `[cluster.deploy.*]` entry. Used by `devrig cluster rebuild`.
+///
+/// The env ConfigMap from `previous` is left in place, still injected into
+/// the workloads, and carried over into the returned state.
+#[allow(clippy::too_many_arguments)]
 pub async fn fresh_rebuild_deploy(
     name: &str,
     deploy_config: &ClusterDeployConfig,
@@ -398,6 +822,7 @@ pub async fn fresh_rebuild_deploy(
     kubeconfig_path: &Path,
     config_dir: &Path,
     apply_manifests: bool,
+    previous: Option<&ClusterDeployState>,
     cancel: &CancellationToken,
 ) -> Result<ClusterDeployState> {
     let context_path = config_dir.join(&deploy_config.context);
@@ -430,34 +855,22 @@ pub async fn fresh_rebuild_deploy(
 
     println!("  Pushed '{name}' -> {tag}");
 
+    let mut namespaces: BTreeSet<String> = previous
+        .into_iter()
+        .flat_map(|p| p.namespaces.iter().cloned())
+        .collect();
     if apply_manifests {
         // kubectl apply
-        let manifests_str = manifests_path.to_string_lossy();
-        debug!(name, manifests = %manifests_str, "applying manifests");
-        run_cmd(
-            "kubectl",
-            &["apply", "-f", &manifests_str],
-            None,
-            Some(("KUBECONFIG", kubeconfig_path)),
-            cancel,
-        )
-        .await?;
+        let workloads =
+            apply_deploy_manifests(name, &manifests_path, kubeconfig_path, cancel).await?;
 
         if cancel.is_cancelled() {
             bail!("cancelled");
         }
 
         // Rollout restart
-        let deployment = format!("deployment/{name}");
-        debug!(name, "restarting deployment");
-        run_cmd(
-            "kubectl",
-            &["rollout", "restart", &deployment],
-            None,
-            Some(("KUBECONFIG", kubeconfig_path)),
-            cancel,
-        )
-        .await?;
+        restart_workloads(name, &workloads, kubeconfig_path, cancel).await?;
+        namespaces.extend(workload_namespaces(&workloads));
 
         println!("  Applied manifests and restarted deployment '{name}'");
     }
@@ -465,6 +878,9 @@ pub async fn fresh_rebuild_deploy(
     Ok(ClusterDeployState {
         image_tag: tag,
         last_deployed: Utc::now(),
+        config_map: previous.and_then(|p| p.config_map.clone()),
+        namespaces: namespaces.into_iter().collect(),
+        context_checksum: None,
     })
 }
 
@@ -481,6 +897,9 @@ mod tests {
             ClusterDeployState {
                 image_tag: "localhost:12345/bloom:1700000000".to_string(),
                 last_deployed: Utc::now(),
+                config_map: None,
+                namespaces: Vec::new(),
+                context_checksum: None,
             },
         );
 
@@ -489,6 +908,41 @@ mod tests {
         assert_eq!(result, "localhost:12345/bloom:1700000000");
     }
 
+    #[test]
+    fn select_deploys_by_name_or_all() {
+        let deploy = |manifests: &str| ClusterDeployConfig {
+            enabled: Default::default(),
+            context: ".".to_string(),
+            dockerfile: "Dockerfile".to_string(),
+            manifests: manifests.to_string(),
+            watch: false,
+            depends_on: vec![],
+            build_secrets: BTreeMap::new(),
+            env: BTreeMap::new(),
+        };
+        let deploys = BTreeMap::from([
+            ("api".to_string(), deploy("k8s/api")),
+            ("web".to_string(), deploy("k8s/web")),
+        ]);
+
+        let all: Vec<&str> = select_deploys(&deploys, None)
+            .unwrap()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(all, vec!["api", "web"]);
+
+        let one = select_deploys(&deploys, Some("web")).unwrap();
+        assert_eq!(one.len(), 1);
+        assert_eq!(one[0].1.manifests, "k8s/web");
+
+        let err = select_deploys(&deploys, Some("db"))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("unknown cluster deploy 'db'"), "{err}");
+        assert!(err.contains("api, web"), "{err}");
+    }
+
     #[test]
     fn interpolate_image_refs_no_match_unchanged() {
         let deployed = BTreeMap::new();
@@ -511,6 +965,9 @@ mod tests {
             ClusterDeployState {
                 image_tag: "localhost:5000/bloom:123".to_string(),
                 last_deployed: Utc::now(),
+                config_map: None,
+                namespaces: Vec::new(),
+                context_checksum: None,
             },
         );
 
@@ -528,9 +985,113 @@ mod tests {
         assert!(!args.contains(&"--no-cache"));
     }
 
+    #[test]
+    fn context_checksum_tracks_files_dockerfile_and_build_args() {
+        let dir = tempfile::tempdir().unwrap();
+        let ctx = dir.path();
+        std::fs::write(ctx.join("Dockerfile"), "FROM scratch\n").unwrap();
+        std::fs::create_dir_all(ctx.join("src")).unwrap();
+        std::fs::write(ctx.join("src/main.rs"), "fn main() {}\n").unwrap();
+        let sum = || context_checksum(ctx, "Dockerfile", &[]).unwrap();
+
+        let base = sum();
+        assert_eq!(sum(), base);
+
+        // Git metadata is not part of the context
+        std::fs::create_dir_all(ctx.join(".git")).unwrap();
+        std::fs::write(ctx.join(".git/HEAD"), "ref: refs/heads/main\n").unwrap();
+        assert_eq!(sum(), base);
+
+        std::fs::write(ctx.join("src/main.rs"), "fn main() { }\n").unwrap();
+        let edited = sum();
+        assert_ne!(edited, base);
+
+        std::fs::rename(ctx.join("src/main.rs"), ctx.join("src/lib.rs")).unwrap();
+        assert_ne!(sum(), edited);
+
+        let args = vec!["VERSION=2".to_string()];
+        assert_ne!(context_checksum(ctx, "Dockerfile", &args).unwrap(), sum());
+
+        assert!(context_checksum(ctx, "Missing.Dockerfile", &[]).is_err());
+    }
+
     #[test]
     fn docker_build_args_includes_no_cache() {
         let args = docker_build_args("tag:1", "Dockerfile", &[], &[], true);
         assert!(args.contains(&"--no-cache"));
     }
+
+    #[test]
+    fn env_config_map_manifest_carries_env() {
+        let env = BTreeMap::from([
+            (
+                "DATABASE_URL".to_string(),
+                "postgres://db:5432/app".to_string(),
+            ),
+            ("QUOTED".to_string(), "say \"hi\"".to_string()),
+        ]);
+        let namespaces = BTreeSet::from(["apps".to_string(), "default".to_string()]);
+        let rendered = render_env_config_map("api", &namespaces, &env);
+        let manifest: serde_json::Value = serde_json::from_str(&rendered).unwrap();
+
+        assert_eq!(manifest["kind"], "List");
+        let items = manifest["items"].as_array().unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0]["kind"], "ConfigMap");
+        assert_eq!(items[0]["metadata"]["name"], "api-devrig-env");
+        assert_eq!(items[0]["metadata"]["namespace"], "apps");
+        assert_eq!(items[1]["metadata"]["namespace"], "default");
+        assert_eq!(items[1]["data"]["DATABASE_URL"], "postgres://db:5432/app");
+        assert_eq!(items[1]["data"]["QUOTED"], "say \"hi\"");
+
+        assert_eq!(
+            config_map_env_keys(&rendered)
+                .into_iter()
+                .collect::<Vec<_>>(),
+            ["DATABASE_URL", "QUOTED"]
+        );
+        assert!(config_map_env_keys("not json").is_empty());
+    }
+
+    #[test]
+    fn applied_workloads_keeps_pod_template_kinds() {
+        let output = r#"{
+            "apiVersion": "v1",
+            "kind": "List",
+            "items": [
+                { "kind": "Service", "metadata": { "name": "api", "namespace": "apps" } },
+                { "kind": "Deployment", "metadata": { "name": "api-server", "namespace": "apps" } },
+                { "kind": "ConfigMap", "metadata": { "name": "api-config", "namespace": "apps" } },
+                { "kind": "StatefulSet", "metadata": { "name": "api-cache", "namespace": "default" } },
+                { "kind": "DaemonSet", "metadata": { "name": "api-agent", "namespace": "monitoring" } }
+            ]
+        }"#;
+        let workloads = applied_workloads(output).unwrap();
+        let resources: Vec<(&str, &str)> = workloads
+            .iter()
+            .map(|w| (w.namespace.as_str(), w.resource.as_str()))
+            .collect();
+        assert_eq!(
+            resources,
+            [
+                ("apps", "deployment/api-server"),
+                ("default", "statefulset/api-cache"),
+                ("monitoring", "daemonset/api-agent"),
+            ]
+        );
+        assert_eq!(
+            workload_namespaces(&workloads)
+                .into_iter()
+                .collect::<Vec<_>>(),
+            ["apps", "default", "monitoring"]
+        );
+
+        // A single applied object is printed on its own, not in a List
+        let single =
+            r#"{ "kind": "Deployment", "metadata": { "name": "api", "namespace": "apps" } }"#;
+        assert_eq!(applied_workloads(single).unwrap().len(), 1);
+        let service =
+            r#"{ "kind": "Service", "metadata": { "name": "api", "namespace": "apps" } }"#;
+        assert!(applied_workloads(service).unwrap().is_empty());
+    }
 }
@@ -0,0 +1,269 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::config::model::ClusterConfig;
+use crate::orchestrator::state::ClusterDeployState;
+
+/// A Kubernetes event, flattened for display by `devrig cluster events`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClusterEvent {
+    pub timestamp: DateTime<Utc>,
+    pub namespace: String,
+    /// `Normal` or `Warning`.
+    pub event_type: String,
+    pub reason: String,
+    /// Involved object as `Kind/name`, e.g. `Pod/api-7d9f-x2x`.
+    pub object: String,
+    pub message: String,
+    pub count: u32,
+}
+
+impl ClusterEvent {
+    pub fn is_warning(&self) -> bool {
+        self.event_type == "Warning"
+    }
+}
+
+#[derive(Deserialize)]
+struct EventList {
+    #[serde(default)]
+    items: Vec<RawEvent>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawEvent {
+    #[serde(rename = "type", default)]
+    event_type: String,
+    #[serde(default)]
+    reason: String,
+    #[serde(default)]
+    message: String,
+    #[serde(default)]
+    count: Option<u32>,
+    #[serde(default)]
+    involved_object: ObjectRef,
+    #[serde(default)]
+    metadata: EventMetadata,
+    #[serde(default)]
+    last_timestamp: Option<DateTime<Utc>>,
+    #[serde(default)]
+    event_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    first_timestamp: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize, Default)]
+struct ObjectRef {
+    #[serde(default)]
+    kind: String,
+    #[serde(default)]
+    name: String,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct EventMetadata {
+    #[serde(default)]
+    namespace: String,
+    #[serde(default)]
+    creation_timestamp: Option<DateTime<Utc>>,
+}
+
+/// Parse `kubectl get events -o json` output into events sorted oldest first.
+///
+/// The timestamp is the most recent of `lastTimestamp`, `eventTime`,
+/// `firstTimestamp`, and the object's creation time; events with none of
+/// these are dropped.
+pub fn parse_events(json: &str) -> Result<Vec<ClusterEvent>> {
+    let list: EventList = serde_json::from_str(json).context("parsing kubectl events JSON")?;
+    let mut events: Vec<ClusterEvent> = list
+        .items
+        .into_iter()
+        .filter_map(|raw| {
+            let timestamp = raw
+                .last_timestamp
+                .or(raw.event_time)
+                .or(raw.first_timestamp)
+                .or(raw.metadata.creation_timestamp)?;
+            Some(ClusterEvent {
+                timestamp,
+                namespace: raw.metadata.namespace,
+                event_type: raw.event_type,
+                reason: raw.reason,
+                object: format!("{}/{}", raw.involved_object.kind, raw.involved_object.name),
+                message: raw.message.trim().to_string(),
+                count: raw.count.unwrap_or(1),
+            })
+        })
+        .collect();
+    events.sort_by_key(|e| e.timestamp);
+    Ok(events)
+}
+
+/// Namespaces devrig deploys into: those each `[cluster.deploy.*]` applied
+/// its manifests to, as recorded in `deployed`, plus every addon namespace.
+/// A deploy with none recorded (not deployed yet, or by an older devrig)
+/// counts as `default`.
+pub fn deployed_namespaces(
+    cluster: &ClusterConfig,
+    deployed: &BTreeMap<String, ClusterDeployState>,
+) -> BTreeSet<String> {
+    let mut namespaces = BTreeSet::new();
+    for name in cluster.deploy.keys() {
+        match deployed
+            .get(name)
+            .filter(|state| !state.namespaces.is_empty())
+        {
+            Some(state) => namespaces.extend(state.namespaces.iter().cloned()),
+            None => {
+                namespaces.insert("default".to_string());
+            }
+        }
+    }
+    for addon in cluster.addons.values() {
+        if let Some(ns) = addon.namespace() {
+            namespaces.insert(ns.to_string());
+        }
+    }
+    namespaces
+}
+
+/// Keep events in `namespaces` (any if `None`), at or after `since`, and,
+/// unless `include_normal` is set, of type `Warning`.
+pub fn filter_events(
+    events: Vec<ClusterEvent>,
+    namespaces: Option<&BTreeSet<String>>,
+    since: Option<DateTime<Utc>>,
+    include_normal: bool,
+) -> Vec<ClusterEvent> {
+    events
+        .into_iter()
+        .filter(|e| namespaces.is_none_or(|ns| ns.contains(&e.namespace)))
+        .filter(|e| since.is_none_or(|cutoff| e.timestamp >= cutoff))
+        .filter(|e| include_normal || e.is_warning())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EVENTS_JSON: &str = r#"{
+        "apiVersion": "v1",
+        "items": [
+            {
+                "type": "Warning",
+                "reason": "BackOff",
+                "message": "Back-off restarting failed container\n",
+                "count": 7,
+                "involvedObject": { "kind": "Pod", "name": "api-7d9f" },
+                "metadata": { "namespace": "default", "creationTimestamp": "2026-01-01T10:00:00Z" },
+                "lastTimestamp": "2026-01-01T10:05:00Z"
+            },
+            {
+                "type": "Normal",
+                "reason": "Scheduled",
+                "message": "Successfully assigned default/api-7d9f",
+                "involvedObject": { "kind": "Pod", "name": "api-7d9f" },
+                "metadata": { "namespace": "default" },
+                "lastTimestamp": null,
+                "eventTime": "2026-01-01T10:00:00.123456Z"
+            },
+            {
+                "type": "Warning",
+                "reason": "FailedMount",
+                "message": "secret not found",
+                "involvedObject": { "kind": "Pod", "name": "coredns-1" },
+                "metadata": { "namespace": "kube-system" },
+                "firstTimestamp": "2026-01-01T10:02:00Z"
+            },
+            {
+                "type": "Warning",
+                "reason": "Undated",
+                "involvedObject": { "kind": "Pod", "name": "ghost" },
+                "metadata": { "namespace": "default" }
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn parse_events_sorts_by_timestamp_and_drops_undated() {
+        let events = parse_events(EVENTS_JSON).unwrap();
+        let reasons: Vec<&str> = events.iter().map(|e| e.reason.as_str()).collect();
+        assert_eq!(reasons, vec!["Scheduled", "FailedMount", "BackOff"]);
+
+        let backoff = &events[2];
+        assert_eq!(backoff.object, "Pod/api-7d9f");
+        assert_eq!(backoff.message, "Back-off restarting failed container");
+        assert_eq!(backoff.count, 7);
+        assert!(backoff.is_warning());
+        assert_eq!(events[0].count, 1);
+    }
+
+    #[test]
+    fn filter_events_defaults_to_warnings_in_namespaces() {
+        let events = parse_events(EVENTS_JSON).unwrap();
+        let default_ns = BTreeSet::from(["default".to_string()]);
+
+        let warnings = filter_events(events.clone(), Some(&default_ns), None, false);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].reason, "BackOff");
+
+        let all = filter_events(events.clone(), None, None, true);
+        assert_eq!(all.len(), 3);
+
+        let cutoff = "2026-01-01T10:01:00Z".parse().unwrap();
+        let recent = filter_events(events, None, Some(cutoff), true);
+        let reasons: Vec<&str> = recent.iter().map(|e| e.reason.as_str()).collect();
+        assert_eq!(reasons, vec!["FailedMount", "BackOff"]);
+    }
+
+    #[test]
+    fn deployed_namespaces_include_addons() {
+        let cluster: ClusterConfig = toml::from_str(
+            r#"
+            [deploy.api]
+            context = "./api"
+            manifests = "k8s/api"
+
+            [deploy.web]
+            context = "./web"
+            manifests = "k8s/web"
+
+            [addons.traefik]
+            type = "helm"
+            chart = "traefik/traefik"
+            repo = "https://traefik.github.io/charts"
+            namespace = "traefik"
+        "#,
+        )
+        .unwrap();
+        let applied = |namespaces: &[&str]| ClusterDeployState {
+            image_tag: "devrig-api:latest".to_string(),
+            last_deployed: Utc::now(),
+            config_map: None,
+            namespaces: namespaces.iter().map(|ns| ns.to_string()).collect(),
+            context_checksum: None,
+        };
+
+        // Not deployed yet: assumed to land in `default`
+        let namespaces = deployed_namespaces(&cluster, &BTreeMap::new());
+        assert_eq!(
+            namespaces.into_iter().collect::<Vec<_>>(),
+            vec!["default", "traefik"]
+        );
+
+        let deployed = BTreeMap::from([
+            ("api".to_string(), applied(&["apps", "jobs"])),
+            ("web".to_string(), applied(&["frontend"])),
+        ]);
+        let namespaces = deployed_namespaces(&cluster, &deployed);
+        assert_eq!(
+            namespaces.into_iter().collect::<Vec<_>>(),
+            vec!["apps", "frontend", "jobs", "traefik"]
+        );
+    }
+}
@@ -182,7 +182,7 @@ fn build_namespace_filters(config: &ClusterLogsConfig) -> String {
         NamespaceFilter::All => {
             if let Some(excludes) = &config.exclude_namespaces {
                 if !excludes.is_empty() {
-                    let pattern = format!("^({})$", excludes.join("|"));
+                    let pattern = exclude_regex(excludes);
                     return format!(
                         r#"
     [FILTER]
@@ -204,7 +204,7 @@ fn build_namespace_filters(config: &ClusterLogsConfig) -> String {
 fn build_pod_filters(config: &ClusterLogsConfig) -> String {
     if let Some(pods) = &config.exclude_pods {
         if !pods.is_empty() {
-            let pattern = format!("^({})$", pods.join("|"));
+            let pattern = exclude_regex(pods);
             return format!(
                 r#"
     [FILTER]
@@ -219,6 +219,15 @@ fn build_pod_filters(config: &ClusterLogsConfig) -> String {
     String::new()
 }
 
+/// One anchored regex matching any of the `exclude_*` entries.
+fn exclude_regex(entries: &[String]) -> String {
+    let patterns: Vec<String> = entries
+        .iter()
+        .map(|entry| ClusterLogsConfig::exclude_pattern(entry))
+        .collect();
+    format!("^({})$", patterns.join("|"))
+}
+
 /// Extract host from an endpoint string like "host.k3d.internal:4318".
 fn extract_host(endpoint: &str) -> &str {
     endpoint.rsplit_once(':').map(|(h, _)| h).unwrap_or(endpoint)
@@ -304,6 +313,22 @@ mod tests {
         assert!(filters.contains("^(noisy-.*)$"));
     }
 
+    #[test]
+    fn exclude_entries_mix_exact_names_globs_and_regexes() {
+        let config = ClusterLogsConfig {
+            namespaces: NamespaceFilter::All,
+            exclude_namespaces: Some(vec!["istio-*".to_string(), "kube-system".to_string()]),
+            exclude_pods: Some(vec![
+                ".*-canary".to_string(),
+                "web.v2".to_string(),
+                "job-?".to_string(),
+            ]),
+            ..default_config()
+        };
+        assert!(build_namespace_filters(&config).contains("^(istio-.*|kube-system)$"));
+        assert!(build_pod_filters(&config).contains(r"^(.*-canary|web\.v2|job-.)$"));
+    }
+
     #[test]
     fn pod_filter_none() {
         let config = default_config();
@@ -1,7 +1,10 @@
 pub mod addon;
 pub mod deploy;
+pub mod events;
 pub mod log_collector;
+pub mod nodes;
 pub mod registry;
+pub mod retry;
 pub mod watcher;
 
 use anyhow::{bail, Context, Result};
@@ -10,6 +13,7 @@ use tokio::process::Command;
 use tracing::debug;
 
 use crate::config::model::{ClusterConfig, ClusterRegistryAuth};
+use retry::{run_with_retry, CommandOutput, RetryPolicy};
 
 /// Manages the lifecycle of a k3d Kubernetes cluster for a devrig project.
 pub struct K3dManager {
@@ -30,7 +34,7 @@ impl K3dManager {
         network_name: &str,
         config_dir: &Path,
     ) -> Self {
-        let cluster_name = format!("devrig-{}", slug);
+        let cluster_name = crate::identity::cluster_name(slug);
         let kubeconfig_path = state_dir.join("kubeconfig");
         Self {
             cluster_name,
@@ -78,6 +82,11 @@ impl K3dManager {
             args.push(format!("{}@server:*", entry));
         }
 
+        if let Some(image) = &self.config.k3s_image {
+            args.push("--image".to_string());
+            args.push(image.clone());
+        }
+
         if self.config.registry {
             args.push("--registry-create".to_string());
             args.push(format!("k3d-{}-reg:0.0.0.0:0", self.cluster_name));
@@ -129,6 +138,30 @@ impl K3dManager {
         Ok(exists)
     }
 
+    /// Import locally built images into every node's image cache with
+    /// `k3d image import`, so pods can use them without a registry. k3d's
+    /// output is streamed to the terminal.
+    pub async fn import_images(&self, images: &[String]) -> Result<()> {
+        if !self.cluster_exists().await? {
+            bail!(
+                "cluster '{}' does not exist -- create it with `devrig cluster create` or `devrig start`",
+                self.cluster_name
+            );
+        }
+        let status = Command::new("k3d")
+            .args(["image", "import"])
+            .args(images)
+            .args(["-c", &self.cluster_name])
+            .status()
+            .await
+            .context("running k3d image import")?;
+        if !status.success() {
+            bail!("k3d image import failed ({})", status);
+        }
+        debug!(cluster = %self.cluster_name, images = ?images, "images imported");
+        Ok(())
+    }
+
     /// Write the cluster kubeconfig to the local state directory.
     ///
     /// After writing, checks whether the kubeconfig contains an unresolved
@@ -221,44 +254,53 @@ impl K3dManager {
     }
 
     /// Run kubectl with the cluster kubeconfig, returning stdout on success.
+    ///
+    /// Read-only invocations are retried on transient API server errors.
     pub async fn kubectl(&self, args: &[&str]) -> Result<String> {
-        let output = Command::new("kubectl")
-            .args(args)
-            .env("KUBECONFIG", &self.kubeconfig_path)
-            .output()
-            .await
-            .context("running kubectl")?;
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            bail!(
-                "kubectl {} failed: {}",
-                args.first().unwrap_or(&""),
-                stderr.trim()
-            );
-        }
-
-        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+        let label = format!("kubectl {}", args.first().unwrap_or(&""));
+        run_with_retry(
+            self.retry_policy(retry::kubectl_is_read_only(args)),
+            &label,
+            || async {
+                Command::new("kubectl")
+                    .args(args)
+                    .env("KUBECONFIG", &self.kubeconfig_path)
+                    .output()
+                    .await
+                    .map(CommandOutput::from)
+                    .context("running kubectl")
+            },
+        )
+        .await
     }
 
     /// Execute a k3d command, returning stdout on success or bailing with stderr.
+    ///
+    /// Read-only invocations are retried on transient errors.
     async fn run_k3d(&self, args: &[&str]) -> Result<String> {
-        let output = Command::new("k3d")
-            .args(args)
-            .output()
-            .await
-            .context("running k3d")?;
+        let label = format!("k3d {}", args.first().unwrap_or(&""));
+        run_with_retry(
+            self.retry_policy(retry::k3d_is_read_only(args)),
+            &label,
+            || async {
+                Command::new("k3d")
+                    .args(args)
+                    .output()
+                    .await
+                    .map(CommandOutput::from)
+                    .context("running k3d")
+            },
+        )
+        .await
+    }
 
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            bail!(
-                "k3d {} failed: {}",
-                args.first().unwrap_or(&""),
-                stderr.trim()
-            );
+    /// Retry policy for a command; mutating commands are never retried.
+    fn retry_policy(&self, read_only: bool) -> RetryPolicy {
+        if read_only {
+            RetryPolicy::new(self.config.command_retries)
+        } else {
+            RetryPolicy::none()
         }
-
-        Ok(String::from_utf8_lossy(&output.stdout).to_string())
     }
 
     /// Return the cluster name.
@@ -385,6 +427,9 @@ mod tests {
                 logs: None,
                 registries: vec![],
                 k3s_args: vec![],
+                k3s_image: None,
+                env: BTreeMap::new(),
+                command_retries: 3,
             },
             &config_dir.join(".devrig"),
             "test-net",
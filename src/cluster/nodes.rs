@@ -0,0 +1,159 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// A cluster node, as listed by `kubectl get nodes -o json`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClusterNode {
+    pub name: String,
+    /// Whether the node runs the k3s server (control plane).
+    pub control_plane: bool,
+    /// Whether the node is cordoned.
+    pub unschedulable: bool,
+}
+
+#[derive(Deserialize)]
+struct NodeList {
+    #[serde(default)]
+    items: Vec<RawNode>,
+}
+
+#[derive(Deserialize)]
+struct RawNode {
+    metadata: NodeMetadata,
+    #[serde(default)]
+    spec: NodeSpec,
+}
+
+#[derive(Deserialize)]
+struct NodeMetadata {
+    name: String,
+    #[serde(default)]
+    labels: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize, Default)]
+struct NodeSpec {
+    #[serde(default)]
+    unschedulable: bool,
+}
+
+/// Labels k3s puts on server nodes.
+const CONTROL_PLANE_LABELS: &[&str] = &[
+    "node-role.kubernetes.io/control-plane",
+    "node-role.kubernetes.io/master",
+];
+
+/// Parse `kubectl get nodes -o json` output into nodes sorted by name.
+pub fn parse_nodes(json: &str) -> Result<Vec<ClusterNode>> {
+    let list: NodeList = serde_json::from_str(json).context("parsing kubectl nodes JSON")?;
+    let mut nodes: Vec<ClusterNode> = list
+        .items
+        .into_iter()
+        .map(|raw| ClusterNode {
+            control_plane: CONTROL_PLANE_LABELS
+                .iter()
+                .any(|label| raw.metadata.labels.contains_key(*label)),
+            name: raw.metadata.name,
+            unschedulable: raw.spec.unschedulable,
+        })
+        .collect();
+    nodes.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(nodes)
+}
+
+/// The node `devrig cluster node` acts on when none is named: the first
+/// agent (non-control-plane) node.
+pub fn default_node(nodes: &[ClusterNode]) -> Option<&ClusterNode> {
+    nodes.iter().find(|node| !node.control_plane)
+}
+
+/// kubectl arguments for `action` (`cordon`, `drain` or `uncordon`) on
+/// `node`. `ignore_daemonsets` and `force` only apply to `drain`.
+pub fn node_args(action: &str, node: &str, ignore_daemonsets: bool, force: bool) -> Vec<String> {
+    let mut args = vec![action.to_string(), node.to_string()];
+    if action == "drain" {
+        if ignore_daemonsets {
+            args.push("--ignore-daemonsets".to_string());
+        }
+        if force {
+            args.push("--force".to_string());
+        }
+    }
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NODES_JSON: &str = r#"{
+        "items": [
+            {
+                "metadata": {
+                    "name": "k3d-devrig-app-agent-1",
+                    "labels": { "kubernetes.io/hostname": "k3d-devrig-app-agent-1" }
+                },
+                "spec": { "unschedulable": true }
+            },
+            {
+                "metadata": {
+                    "name": "k3d-devrig-app-server-0",
+                    "labels": {
+                        "node-role.kubernetes.io/control-plane": "true",
+                        "node-role.kubernetes.io/master": "true"
+                    }
+                },
+                "spec": {}
+            },
+            {
+                "metadata": { "name": "k3d-devrig-app-agent-0", "labels": {} },
+                "spec": { "podCIDR": "10.42.1.0/24" }
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn parses_roles_and_cordon_state() {
+        let nodes = parse_nodes(NODES_JSON).unwrap();
+        let names: Vec<&str> = nodes.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "k3d-devrig-app-agent-0",
+                "k3d-devrig-app-agent-1",
+                "k3d-devrig-app-server-0"
+            ]
+        );
+        assert!(!nodes[0].control_plane);
+        assert!(!nodes[0].unschedulable);
+        assert!(nodes[1].unschedulable);
+        assert!(nodes[2].control_plane);
+    }
+
+    #[test]
+    fn default_node_is_first_agent() {
+        let nodes = parse_nodes(NODES_JSON).unwrap();
+        assert_eq!(default_node(&nodes).unwrap().name, "k3d-devrig-app-agent-0");
+
+        let servers_only: Vec<ClusterNode> =
+            nodes.into_iter().filter(|n| n.control_plane).collect();
+        assert!(default_node(&servers_only).is_none());
+    }
+
+    #[test]
+    fn drain_flags_only_apply_to_drain() {
+        assert_eq!(
+            node_args("drain", "agent-0", true, true),
+            vec!["drain", "agent-0", "--ignore-daemonsets", "--force"]
+        );
+        assert_eq!(
+            node_args("drain", "agent-0", false, false),
+            vec!["drain", "agent-0"]
+        );
+        assert_eq!(
+            node_args("cordon", "agent-0", true, true),
+            vec!["cordon", "agent-0"]
+        );
+    }
+}
@@ -0,0 +1,218 @@
+use anyhow::{bail, Result};
+use std::future::Future;
+use std::time::Duration;
+use tracing::debug;
+
+/// Stderr fragments that indicate a transient API server or connection
+/// problem rather than a real failure. Matched case-insensitively.
+const TRANSIENT_PATTERNS: &[&str] = &[
+    "the server is currently unable to handle the request",
+    "unable to connect to the server",
+    "connection refused",
+    "connection reset by peer",
+    "tls handshake timeout",
+    "i/o timeout",
+    "etcdserver: request timed out",
+    "etcdserver: leader changed",
+    "the server was unable to return a response",
+    "serviceunavailable",
+];
+
+/// Captured result of one k3d/kubectl invocation.
+#[derive(Debug, Clone)]
+pub struct CommandOutput {
+    pub success: bool,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl From<std::process::Output> for CommandOutput {
+    fn from(output: std::process::Output) -> Self {
+        Self {
+            success: output.status.success(),
+            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        }
+    }
+}
+
+/// How many times to retry a transient failure, with exponential backoff.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub retries: u32,
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl RetryPolicy {
+    pub fn new(retries: u32) -> Self {
+        Self {
+            retries,
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(5),
+        }
+    }
+
+    /// A policy that never retries, for mutating operations.
+    pub fn none() -> Self {
+        Self::new(0)
+    }
+
+    fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt);
+        self.initial_delay
+            .saturating_mul(factor)
+            .min(self.max_delay)
+    }
+}
+
+/// Whether `stderr` matches a known transient error.
+pub fn is_transient(stderr: &str) -> bool {
+    let lower = stderr.to_lowercase();
+    TRANSIENT_PATTERNS.iter().any(|p| lower.contains(p))
+}
+
+/// Whether a kubectl invocation only reads cluster state and is safe to retry.
+pub fn kubectl_is_read_only(args: &[&str]) -> bool {
+    let verb = args.iter().find(|a| !a.starts_with('-'));
+    matches!(
+        verb.copied(),
+        Some(
+            "get"
+                | "describe"
+                | "logs"
+                | "top"
+                | "explain"
+                | "version"
+                | "cluster-info"
+                | "api-resources"
+                | "api-versions"
+        )
+    )
+}
+
+/// Whether a k3d invocation only reads state (`cluster list`, `kubeconfig get`, ...).
+pub fn k3d_is_read_only(args: &[&str]) -> bool {
+    matches!(args.get(1).copied(), Some("list" | "get"))
+}
+
+/// Run `run` until it succeeds, retrying only failures whose stderr looks
+/// transient and only up to `policy.retries` times. `label` names the
+/// command in the final error (e.g. `kubectl get`).
+pub async fn run_with_retry<F, Fut>(policy: RetryPolicy, label: &str, mut run: F) -> Result<String>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<CommandOutput>>,
+{
+    let mut attempt = 0;
+    loop {
+        let output = run().await?;
+        if output.success {
+            return Ok(output.stdout);
+        }
+        let stderr = output.stderr.trim();
+        if attempt < policy.retries && is_transient(stderr) {
+            let delay = policy.delay(attempt);
+            attempt += 1;
+            debug!(
+                command = %label,
+                attempt,
+                delay_ms = delay.as_millis() as u64,
+                error = %stderr,
+                "transient failure, retrying"
+            );
+            tokio::time::sleep(delay).await;
+            continue;
+        }
+        bail!("{} failed: {}", label, stderr);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn fail(stderr: &str) -> CommandOutput {
+        CommandOutput {
+            success: false,
+            stdout: String::new(),
+            stderr: stderr.to_string(),
+        }
+    }
+
+    fn ok(stdout: &str) -> CommandOutput {
+        CommandOutput {
+            success: true,
+            stdout: stdout.to_string(),
+            stderr: String::new(),
+        }
+    }
+
+    fn fast(retries: u32) -> RetryPolicy {
+        RetryPolicy {
+            retries,
+            initial_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(1),
+        }
+    }
+
+    #[tokio::test]
+    async fn transient_failure_succeeds_on_second_attempt() {
+        let calls = AtomicU32::new(0);
+        let out = run_with_retry(fast(3), "kubectl get", || {
+            let n = calls.fetch_add(1, Ordering::SeqCst);
+            async move {
+                Ok(if n == 0 {
+                    fail("Error from server (ServiceUnavailable): the server is currently unable to handle the request")
+                } else {
+                    ok("pods")
+                })
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(out, "pods");
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn permanent_failure_and_exhausted_retries_bail() {
+        let calls = AtomicU32::new(0);
+        let err = run_with_retry(fast(3), "kubectl get", || {
+            calls.fetch_add(1, Ordering::SeqCst);
+            async {
+                Ok(fail(
+                    "error: the server doesn't have a resource type \"podz\"",
+                ))
+            }
+        })
+        .await
+        .unwrap_err();
+        assert!(err.to_string().starts_with("kubectl get failed"));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        let calls = AtomicU32::new(0);
+        let result = run_with_retry(fast(2), "k3d cluster", || {
+            calls.fetch_add(1, Ordering::SeqCst);
+            async { Ok(fail("connection refused")) }
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn read_only_detection() {
+        assert!(kubectl_is_read_only(&["get", "pods", "-o", "json"]));
+        assert!(kubectl_is_read_only(&[
+            "--kubeconfig=/tmp/kc",
+            "get",
+            "pods"
+        ]));
+        assert!(!kubectl_is_read_only(&["apply", "-f", "k8s/"]));
+        assert!(k3d_is_read_only(&["cluster", "list", "-o", "json"]));
+        assert!(k3d_is_read_only(&["kubeconfig", "get", "devrig-x"]));
+        assert!(!k3d_is_read_only(&["cluster", "create", "devrig-x"]));
+    }
+}
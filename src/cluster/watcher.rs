@@ -33,6 +33,7 @@ pub async fn start_watchers(
     registry_port: Option<u16>,
     kubeconfig_path: PathBuf,
     config_dir: PathBuf,
+    state_dir: PathBuf,
     cancel: CancellationToken,
     tracker: &TaskTracker,
 ) -> Result<()> {
@@ -45,6 +46,7 @@ pub async fn start_watchers(
         let deploy_config = deploy_config.clone();
         let kubeconfig_path = kubeconfig_path.clone();
         let config_dir = config_dir.clone();
+        let state_dir = state_dir.clone();
         let cancel = cancel.clone();
 
         tracker.spawn(async move {
@@ -54,6 +56,7 @@ pub async fn start_watchers(
                 registry_port,
                 kubeconfig_path,
                 config_dir,
+                state_dir,
                 cancel,
             )
             .await
@@ -250,6 +253,7 @@ async fn watch_and_rebuild(
     registry_port: Option<u16>,
     kubeconfig_path: PathBuf,
     config_dir: PathBuf,
+    state_dir: PathBuf,
     cancel: CancellationToken,
 ) -> Result<()> {
     let watch_path = config_dir.join(&deploy_config.context);
@@ -347,6 +351,7 @@ async fn watch_and_rebuild(
                 let rebuild_config = deploy_config.clone();
                 let rebuild_kubeconfig = kubeconfig_path.clone();
                 let rebuild_config_dir = config_dir.clone();
+                let rebuild_state_dir = state_dir.clone();
 
                 tokio::spawn(async move {
                     match deploy::run_rebuild(
@@ -355,6 +360,7 @@ async fn watch_and_rebuild(
                         registry_port,
                         &rebuild_kubeconfig,
                         &rebuild_config_dir,
+                        &rebuild_state_dir,
                         &child_cancel,
                     )
                     .await
@@ -1,16 +1,25 @@
 use anyhow::{bail, Context, Result};
-use std::collections::BTreeMap;
+use comfy_table::modifiers::UTF8_ROUND_CORNERS;
+use comfy_table::presets::UTF8_FULL_CONDENSED;
+use comfy_table::{Cell, ContentArrangement, Table};
+use is_terminal::IsTerminal;
+use owo_colors::OwoColorize;
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::Path;
 use tokio_util::sync::CancellationToken;
 
-use crate::cluster::deploy::{fresh_rebuild_deploy, fresh_rebuild_image};
+use crate::cluster::deploy::{
+    diff_deploy, fresh_rebuild_deploy, fresh_rebuild_image, select_deploys,
+};
+use crate::cluster::events;
+use crate::cluster::nodes;
 use crate::cluster::registry::get_registry_port;
 use crate::cluster::K3dManager;
 use crate::config;
 use crate::config::resolve::resolve_config;
 use crate::identity::ProjectIdentity;
 use crate::orchestrator::graph::{DependencyResolver, ResourceKind};
-use crate::orchestrator::state::ClusterDeployState;
+use crate::orchestrator::state::{ClusterDeployState, ProjectState};
 
 pub async fn run_create(config_file: Option<&Path>) -> Result<()> {
     let config_path = resolve_config(config_file)?;
@@ -28,7 +37,7 @@ pub async fn run_create(config_file: Option<&Path>) -> Result<()> {
     let state_dir = config_dir.join(".devrig");
 
     // Need network name - use the slug-based convention
-    let network_name = format!("devrig-{}-net", identity.slug);
+    let network_name = identity.network_name();
 
     let k3d_mgr = K3dManager::new(&identity.slug, cluster_config, &state_dir, &network_name, config_dir);
     k3d_mgr
@@ -60,7 +69,7 @@ pub async fn run_delete(config_file: Option<&Path>) -> Result<()> {
         .unwrap_or_else(|| Path::new("."));
     let state_dir = config_dir.join(".devrig");
 
-    let network_name = format!("devrig-{}-net", identity.slug);
+    let network_name = identity.network_name();
 
     let k3d_mgr = K3dManager::new(&identity.slug, cluster_config, &state_dir, &network_name, config_dir);
     k3d_mgr
@@ -117,6 +126,255 @@ pub async fn run_kubectl(config_file: Option<&Path>, args: Vec<String>) -> Resul
     Ok(())
 }
 
+/// Print recent Kubernetes events from the project cluster, oldest first.
+///
+/// Without `namespace`, events are limited to the namespaces devrig deploys
+/// into. Only Warnings are shown unless `include_normal` is set.
+pub async fn run_events(
+    config_file: Option<&Path>,
+    namespace: Option<String>,
+    since: Option<String>,
+    include_normal: bool,
+) -> Result<()> {
+    let config_path = resolve_config(config_file)?;
+    let (config, _source) = config::load_config(&config_path)?;
+    let cluster_config = config
+        .cluster
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("no [cluster] section in config"))?;
+
+    let state_dir = config_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(".devrig");
+    let kubeconfig_path = state_dir.join("kubeconfig");
+    if !kubeconfig_path.exists() {
+        bail!("kubeconfig not found -- is the cluster running? Start with `devrig start` first.");
+    }
+
+    let since_cutoff = since
+        .map(|s| {
+            humantime::parse_duration(&s)
+                .map_err(|e| anyhow::anyhow!("invalid --since '{}': {}", s, e))
+                .map(|d| chrono::Utc::now() - chrono::Duration::from_std(d).unwrap_or_default())
+        })
+        .transpose()?;
+
+    let mut args = vec!["get", "events", "-o", "json"];
+    match namespace.as_deref() {
+        Some(ns) => args.extend(["-n", ns]),
+        None => args.push("--all-namespaces"),
+    }
+    let output = tokio::process::Command::new("kubectl")
+        .args(&args)
+        .env("KUBECONFIG", &kubeconfig_path)
+        .output()
+        .await
+        .context("running kubectl")?;
+    if !output.status.success() {
+        bail!(
+            "kubectl get events failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    let events = events::parse_events(&String::from_utf8_lossy(&output.stdout))?;
+    let namespaces = match namespace {
+        Some(ns) => BTreeSet::from([ns]),
+        None => {
+            let deployed = ProjectState::load(&state_dir)
+                .and_then(|state| state.cluster)
+                .map(|cluster| cluster.deployed_services)
+                .unwrap_or_default();
+            events::deployed_namespaces(cluster_config, &deployed)
+        }
+    };
+    let events = events::filter_events(events, Some(&namespaces), since_cutoff, include_normal);
+    print_events(&events, include_normal);
+    Ok(())
+}
+
+/// Print what re-applying each deploy's manifests would change in the
+/// project cluster. Without `deploy`, every deploy is diffed.
+pub async fn run_diff(config_file: Option<&Path>, deploy: Option<String>) -> Result<()> {
+    let config_path = resolve_config(config_file)?;
+    let (config, _source) = config::load_config(&config_path)?;
+    let cluster_config = config
+        .cluster
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("no [cluster] section in config"))?;
+
+    let config_dir = config_path.parent().unwrap_or_else(|| Path::new("."));
+    let kubeconfig_path = config_dir.join(".devrig").join("kubeconfig");
+    if !kubeconfig_path.exists() {
+        bail!("kubeconfig not found -- is the cluster running? Start with `devrig start` first.");
+    }
+
+    let deploys = select_deploys(&cluster_config.deploy, deploy.as_deref())?;
+    if deploys.is_empty() {
+        println!("No [cluster.deploy] entries to diff.");
+        return Ok(());
+    }
+    for (name, deploy_config) in deploys {
+        let diff = diff_deploy(name, deploy_config, &kubeconfig_path, config_dir).await?;
+        if diff.is_empty() {
+            println!("{}: no changes", name);
+        } else {
+            println!("{}:", name);
+            print!("{}", diff);
+        }
+    }
+    Ok(())
+}
+
+/// Load locally built images into the project cluster's nodes.
+pub async fn run_import_image(config_file: Option<&Path>, images: &[String]) -> Result<()> {
+    let config_path = resolve_config(config_file)?;
+    let (config, _source) = config::load_config(&config_path)?;
+    let identity = ProjectIdentity::from_config(&config, &config_path)?;
+    let cluster_config = config
+        .cluster
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("no [cluster] section in config"))?;
+
+    let config_dir = config_path.parent().unwrap_or_else(|| Path::new("."));
+    let state_dir = config_dir.join(".devrig");
+    let network_name = identity.network_name();
+    let k3d_mgr = K3dManager::new(
+        &identity.slug,
+        cluster_config,
+        &state_dir,
+        &network_name,
+        config_dir,
+    );
+    k3d_mgr.import_images(images).await?;
+
+    println!(
+        "Imported {} into cluster '{}'",
+        images.join(", "),
+        k3d_mgr.cluster_name()
+    );
+    Ok(())
+}
+
+/// Cordon, drain, or uncordon a node of the project cluster. Without
+/// `node`, the first agent node is used.
+pub async fn run_node(
+    config_file: Option<&Path>,
+    action: &str,
+    node: Option<String>,
+    ignore_daemonsets: bool,
+    force: bool,
+) -> Result<()> {
+    if action != "drain" && (ignore_daemonsets || force) {
+        bail!("--ignore-daemonsets and --force only apply to `drain`");
+    }
+
+    let config_path = resolve_config(config_file)?;
+    let kubeconfig_path = config_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(".devrig")
+        .join("kubeconfig");
+    if !kubeconfig_path.exists() {
+        bail!("kubeconfig not found -- is the cluster running? Start with `devrig start` first.");
+    }
+
+    let node = match node {
+        Some(node) => node,
+        None => {
+            let output = tokio::process::Command::new("kubectl")
+                .args(["get", "nodes", "-o", "json"])
+                .env("KUBECONFIG", &kubeconfig_path)
+                .output()
+                .await
+                .context("running kubectl")?;
+            if !output.status.success() {
+                bail!(
+                    "kubectl get nodes failed: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+            }
+            let nodes = nodes::parse_nodes(&String::from_utf8_lossy(&output.stdout))?;
+            match nodes::default_node(&nodes) {
+                Some(node) => node.name.clone(),
+                None => bail!(
+                    "the cluster has no agent nodes; name a node to {} (available: {})",
+                    action,
+                    nodes
+                        .iter()
+                        .map(|n| n.name.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            }
+        }
+    };
+
+    // Inherit stdout/stderr so drain progress (evictions) is shown as it happens.
+    let status = tokio::process::Command::new("kubectl")
+        .args(nodes::node_args(action, &node, ignore_daemonsets, force))
+        .env("KUBECONFIG", &kubeconfig_path)
+        .status()
+        .await
+        .context("running kubectl")?;
+    if !status.success() {
+        bail!("kubectl {} {} failed", action, node);
+    }
+    Ok(())
+}
+
+fn print_events(events: &[events::ClusterEvent], include_normal: bool) {
+    if events.is_empty() {
+        if include_normal {
+            println!("  No events found.");
+        } else {
+            println!("  No Warning events found. Use --include-normal to show all events.");
+        }
+        return;
+    }
+
+    let use_color = std::io::stdout().is_terminal();
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL_CONDENSED)
+        .apply_modifier(UTF8_ROUND_CORNERS)
+        .set_content_arrangement(ContentArrangement::Dynamic);
+    table.set_header(vec![
+        "Time",
+        "Type",
+        "Namespace",
+        "Object",
+        "Reason",
+        "Message",
+    ]);
+
+    for event in events {
+        let event_type = match (use_color, event.is_warning()) {
+            (true, true) => format!("{}", event.event_type.yellow()),
+            (true, false) => format!("{}", event.event_type.green()),
+            (false, _) => event.event_type.clone(),
+        };
+        let message = if event.count > 1 {
+            format!("{} (x{})", event.message, event.count)
+        } else {
+            event.message.clone()
+        };
+        table.add_row(vec![
+            Cell::new(event.timestamp.format("%H:%M:%S")),
+            Cell::new(event_type),
+            Cell::new(&event.namespace),
+            Cell::new(&event.object),
+            Cell::new(&event.reason),
+            Cell::new(message),
+        ]);
+    }
+
+    for line in table.to_string().lines() {
+        println!("  {}", line);
+    }
+}
+
 /// Rebuild and re-push cluster images with --no-cache for a completely fresh build.
 /// Respects dependency order via depends_on fields.
 pub async fn run_rebuild_images(
@@ -217,6 +475,10 @@ pub async fn run_rebuild_images(
 
     let cancel = CancellationToken::new();
     let mut deployed: BTreeMap<String, ClusterDeployState> = BTreeMap::new();
+    let previous = ProjectState::load(&state_dir)
+        .and_then(|state| state.cluster)
+        .map(|cluster| cluster.deployed_services)
+        .unwrap_or_default();
 
     for (name, kind) in &rebuild_order {
         match kind {
@@ -243,10 +505,13 @@ pub async fn run_rebuild_images(
                     &kubeconfig_path,
                     config_dir,
                     !no_apply,
+                    previous.get(name),
                     &cancel,
                 )
                 .await
                 .with_context(|| format!("rebuilding deploy '{}'", name))?;
+                // Keep tracking the new tag and the deploy's env ConfigMap
+                ProjectState::update_cluster_deploy_state(&state_dir, name, &state);
                 deployed.insert(name.clone(), state);
             }
             _ => {}
@@ -1,7 +1,14 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
-pub fn run() -> Result<()> {
+use crate::orchestrator::control;
+use crate::orchestrator::registry::InstanceRegistry;
+use crate::orchestrator::state::ProjectState;
+
+pub fn run(config_path: Option<&Path>, fix: bool) -> Result<()> {
     println!("devrig doctor");
     println!("=============");
     println!();
@@ -73,5 +80,596 @@ pub fn run() -> Result<()> {
         println!("Note: docker, docker compose, and k3d are only needed for infrastructure services (v0.2+).");
     }
 
+    println!();
+    check_stale_state(config_path, fix)
+}
+
+// -----------------------------------------------------------------------
+// Stale state
+// -----------------------------------------------------------------------
+
+/// A project devrig knows about, from the instance registry or the config
+/// in the current directory.
+#[derive(Debug, Clone)]
+struct Project {
+    slug: String,
+    state_dir: PathBuf,
+    config_path: PathBuf,
+    registered: bool,
+}
+
+/// State left behind by a devrig run that did not shut down cleanly.
+#[derive(Debug, Clone, PartialEq)]
+enum StaleState {
+    /// A PID file naming a process that is gone. The control socket next
+    /// to it is removed with it.
+    PidFile {
+        slug: String,
+        state_dir: PathBuf,
+        pid: u32,
+    },
+    /// Services recorded as running although their process is gone.
+    ServicePhases {
+        slug: String,
+        state_dir: PathBuf,
+        services: Vec<String>,
+    },
+    /// A registry entry whose config or state file no longer exists.
+    RegistryEntry { slug: String, reason: &'static str },
+    /// A kubeconfig for a k3d cluster that no longer exists.
+    Kubeconfig {
+        slug: String,
+        state_dir: PathBuf,
+        path: PathBuf,
+        cluster: String,
+    },
+    /// Containers labelled for a project devrig no longer tracks.
+    Containers { slug: String, names: Vec<String> },
+}
+
+impl StaleState {
+    fn describe(&self) -> String {
+        match self {
+            StaleState::PidFile { slug, pid, .. } => {
+                format!("{}: PID file names pid {}, which is not running", slug, pid)
+            }
+            StaleState::ServicePhases { slug, services, .. } => format!(
+                "{}: {} recorded as running but not alive",
+                slug,
+                services.join(", ")
+            ),
+            StaleState::RegistryEntry { slug, reason } => {
+                format!("{}: registered instance {}", slug, reason)
+            }
+            StaleState::Kubeconfig { slug, cluster, .. } => format!(
+                "{}: kubeconfig points at cluster '{}', which does not exist",
+                slug, cluster
+            ),
+            StaleState::Containers { slug, names } => format!(
+                "{}: orphaned containers {} (project is not registered)",
+                slug,
+                names.join(", ")
+            ),
+        }
+    }
+
+    /// Clean up this state and say what was done.
+    fn fix(&self, registry: &mut InstanceRegistry) -> Result<String> {
+        match self {
+            StaleState::PidFile {
+                slug, state_dir, ..
+            } => {
+                remove_if_exists(&state_dir.join("pid"))?;
+                remove_if_exists(&control::socket_path(state_dir))?;
+                Ok(format!(
+                    "{}: removed stale PID file and control socket",
+                    slug
+                ))
+            }
+            StaleState::ServicePhases {
+                slug,
+                state_dir,
+                services,
+            } => {
+                for service in services {
+                    ProjectState::update_service_phase(state_dir, service, "stopped");
+                }
+                Ok(format!(
+                    "{}: marked {} as stopped",
+                    slug,
+                    services.join(", ")
+                ))
+            }
+            StaleState::RegistryEntry { slug, .. } => {
+                registry.unregister(slug);
+                registry.save()?;
+                Ok(format!("{}: removed from the instance registry", slug))
+            }
+            StaleState::Kubeconfig {
+                slug,
+                state_dir,
+                path,
+                ..
+            } => {
+                remove_if_exists(path)?;
+                if let Some(mut state) = ProjectState::load(state_dir) {
+                    state.cluster = None;
+                    state.save(state_dir)?;
+                }
+                Ok(format!("{}: removed kubeconfig {}", slug, path.display()))
+            }
+            StaleState::Containers { slug, names } => {
+                let output = Command::new("docker")
+                    .arg("rm")
+                    .arg("-f")
+                    .args(names)
+                    .output()
+                    .context("running docker rm")?;
+                if !output.status.success() {
+                    anyhow::bail!(
+                        "docker rm failed: {}",
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    );
+                }
+                Ok(format!("{}: removed containers {}", slug, names.join(", ")))
+            }
+        }
+    }
+}
+
+fn remove_if_exists(path: &Path) -> Result<()> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(e).with_context(|| format!("removing {}", path.display()))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Report stale state for every known project, and clean it up when `fix`
+/// is set. Projects whose devrig process is alive are never touched.
+fn check_stale_state(config_path: Option<&Path>, fix: bool) -> Result<()> {
+    println!("State");
+    println!("-----");
+
+    let mut registry = InstanceRegistry::load();
+    let projects = known_projects(&registry, config_path);
+    let clusters = k3d_clusters();
+
+    let mut stale: Vec<StaleState> = Vec::new();
+    let mut known_slugs = BTreeSet::new();
+    for project in &projects {
+        let live = runner_pid(&project.state_dir).is_some_and(crate::platform::is_process_alive);
+        if live || project.config_path.exists() {
+            known_slugs.insert(project.slug.clone());
+        }
+        if !live {
+            stale.extend(check_project(
+                project,
+                crate::platform::is_process_alive,
+                clusters.as_deref(),
+            ));
+        }
+    }
+    if let Some(containers) = devrig_containers() {
+        stale.extend(orphaned_containers(containers, &known_slugs, Utc::now()));
+    }
+
+    if stale.is_empty() {
+        println!("  [ok] no stale state found");
+        return Ok(());
+    }
+
+    for item in &stale {
+        if fix {
+            match item.fix(&mut registry) {
+                Ok(done) => println!("  [fixed] {}", done),
+                Err(e) => println!("  [!!] {}: {:#}", item.describe(), e),
+            }
+        } else {
+            println!("  [!!] {}", item.describe());
+        }
+    }
+    if !fix {
+        println!();
+        println!("Run `devrig doctor --fix` to clean this up.");
+    }
     Ok(())
 }
+
+/// Registered instances, plus the project in the current directory when
+/// it has state but is not registered.
+fn known_projects(registry: &InstanceRegistry, config_path: Option<&Path>) -> Vec<Project> {
+    let mut projects: Vec<Project> = registry
+        .list()
+        .iter()
+        .map(|entry| Project {
+            slug: entry.slug.clone(),
+            state_dir: PathBuf::from(&entry.state_dir),
+            config_path: PathBuf::from(&entry.config_path),
+            registered: true,
+        })
+        .collect();
+
+    let current = crate::config::resolve::resolve_config(config_path)
+        .ok()
+        .and_then(|config_path| {
+            let state_dir = ProjectState::state_dir_for(config_path.parent()?);
+            let state = ProjectState::load(&state_dir)?;
+            Some(Project {
+                slug: state.slug,
+                state_dir,
+                config_path,
+                registered: false,
+            })
+        });
+    if let Some(current) = current {
+        if !projects.iter().any(|p| p.slug == current.slug) {
+            projects.push(current);
+        }
+    }
+    projects
+}
+
+/// PID of the devrig process recorded in a project's PID file.
+fn runner_pid(state_dir: &Path) -> Option<u32> {
+    std::fs::read_to_string(state_dir.join("pid"))
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Stale state of one project whose devrig process is not running.
+/// `clusters` is the list of existing k3d clusters, or `None` if k3d could
+/// not be asked, in which case kubeconfigs are left alone.
+fn check_project(
+    project: &Project,
+    is_alive: impl Fn(u32) -> bool,
+    clusters: Option<&[String]>,
+) -> Vec<StaleState> {
+    let mut stale = Vec::new();
+    let slug = project.slug.clone();
+    let state_dir = project.state_dir.clone();
+
+    if let Some(pid) = runner_pid(&state_dir) {
+        stale.push(StaleState::PidFile {
+            slug: slug.clone(),
+            state_dir: state_dir.clone(),
+            pid,
+        });
+    }
+
+    let state = ProjectState::load(&state_dir);
+    if project.registered {
+        let reason = if !project.config_path.exists() {
+            Some("has no config file")
+        } else if state.is_none() {
+            Some("has no state file")
+        } else {
+            None
+        };
+        if let Some(reason) = reason {
+            stale.push(StaleState::RegistryEntry {
+                slug: slug.clone(),
+                reason,
+            });
+        }
+    }
+    let Some(state) = state else {
+        return stale;
+    };
+
+    let services: Vec<String> = state
+        .services
+        .iter()
+        .filter(|(_, svc)| {
+            matches!(
+                svc.phase.as_deref(),
+                Some("running" | "starting" | "restarting")
+            ) && !is_alive(svc.pid)
+        })
+        .map(|(name, _)| name.clone())
+        .collect();
+    if !services.is_empty() {
+        stale.push(StaleState::ServicePhases {
+            slug: slug.clone(),
+            state_dir: state_dir.clone(),
+            services,
+        });
+    }
+
+    if let (Some(cluster), Some(clusters)) = (&state.cluster, clusters) {
+        let path = PathBuf::from(&cluster.kubeconfig_path);
+        if path.exists() && !clusters.contains(&cluster.cluster_name) {
+            stale.push(StaleState::Kubeconfig {
+                slug,
+                state_dir,
+                path,
+                cluster: cluster.cluster_name.clone(),
+            });
+        }
+    }
+    stale
+}
+
+/// Names of existing k3d clusters, or `None` if k3d is unavailable.
+fn k3d_clusters() -> Option<Vec<String>> {
+    let output = Command::new("k3d")
+        .args(["cluster", "list", "-o", "json"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())?;
+    let clusters: Vec<serde_json::Value> = serde_json::from_slice(&output.stdout).ok()?;
+    Some(
+        clusters
+            .iter()
+            .filter_map(|c| c.get("name").and_then(|n| n.as_str()))
+            .map(str::to_string)
+            .collect(),
+    )
+}
+
+/// Containers younger than this are never reported as orphaned: their
+/// project may still be starting up.
+const ORPHAN_MIN_AGE: chrono::Duration = chrono::Duration::minutes(5);
+
+/// A devrig-managed container, as listed by `docker ps`.
+#[derive(Debug, Clone, PartialEq)]
+struct ListedContainer {
+    slug: String,
+    name: String,
+    /// `None` when Docker's timestamp could not be parsed.
+    created: Option<DateTime<Utc>>,
+}
+
+/// devrig-managed containers, or `None` if Docker is unavailable.
+fn devrig_containers() -> Option<Vec<ListedContainer>> {
+    let output = Command::new("docker")
+        .args([
+            "ps",
+            "-a",
+            "--filter",
+            "label=devrig.managed-by=devrig",
+            "--format",
+            "{{.Label \"devrig.project\"}}\t{{.Names}}\t{{.CreatedAt}}",
+        ])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())?;
+    Some(parse_container_list(&String::from_utf8_lossy(
+        &output.stdout,
+    )))
+}
+
+fn parse_container_list(output: &str) -> Vec<ListedContainer> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let (slug, name) = (fields.next()?, fields.next()?);
+            if slug.is_empty() || name.is_empty() {
+                return None;
+            }
+            Some(ListedContainer {
+                slug: slug.to_string(),
+                name: name.to_string(),
+                created: fields.next().and_then(parse_created_at),
+            })
+        })
+        .collect()
+}
+
+/// Parse Docker's `CreatedAt`, e.g. `2026-01-01 10:00:00 +0000 UTC`.
+fn parse_created_at(created: &str) -> Option<DateTime<Utc>> {
+    let without_zone_name = created.rsplit_once(' ').map_or(created, |(time, _)| time);
+    DateTime::parse_from_str(without_zone_name, "%Y-%m-%d %H:%M:%S %z")
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// Group containers whose project is not in `known` by project. Containers
+/// created less than [`ORPHAN_MIN_AGE`] before `now`, or at an unknown
+/// time, are left out.
+fn orphaned_containers(
+    containers: Vec<ListedContainer>,
+    known: &BTreeSet<String>,
+    now: DateTime<Utc>,
+) -> Vec<StaleState> {
+    let mut by_project: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for container in containers {
+        let old_enough = container
+            .created
+            .is_some_and(|created| now - created >= ORPHAN_MIN_AGE);
+        if old_enough && !known.contains(&container.slug) {
+            by_project
+                .entry(container.slug)
+                .or_default()
+                .push(container.name);
+        }
+    }
+    by_project
+        .into_iter()
+        .map(|(slug, names)| StaleState::Containers { slug, names })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orchestrator::state::{ClusterState, ServiceState};
+
+    fn service(pid: u32, phase: Option<&str>) -> ServiceState {
+        ServiceState {
+            pid,
+            port: None,
+            port_auto: false,
+            protocol: None,
+            phase: phase.map(str::to_string),
+            exit_code: None,
+            ready: None,
+        }
+    }
+
+    fn project_with_state(dir: &Path, state: Option<ProjectState>) -> Project {
+        let config_path = dir.join("devrig.toml");
+        std::fs::write(&config_path, "").unwrap();
+        let state_dir = dir.join(".devrig");
+        if let Some(state) = state {
+            state.save(&state_dir).unwrap();
+        }
+        Project {
+            slug: "app-1234abcd".to_string(),
+            state_dir,
+            config_path,
+            registered: true,
+        }
+    }
+
+    fn state(services: Vec<(&str, ServiceState)>) -> ProjectState {
+        ProjectState {
+            slug: "app-1234abcd".to_string(),
+            config_path: "devrig.toml".to_string(),
+            services: services
+                .into_iter()
+                .map(|(name, svc)| (name.to_string(), svc))
+                .collect(),
+            started_at: Utc::now(),
+            docker: BTreeMap::new(),
+            compose_services: BTreeMap::new(),
+            network_name: None,
+            cluster: None,
+            dashboard: None,
+        }
+    }
+
+    #[test]
+    fn clean_project_has_no_stale_state() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = project_with_state(
+            dir.path(),
+            Some(state(vec![
+                ("web", service(10, Some("running"))),
+                ("worker", service(11, Some("failed"))),
+            ])),
+        );
+        assert!(check_project(&project, |pid| pid == 10, Some(&[])).is_empty());
+    }
+
+    #[test]
+    fn crashed_run_is_detected_and_fixed() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut crashed = state(vec![
+            ("api", service(20, Some("running"))),
+            ("web", service(21, Some("starting"))),
+            ("done", service(22, Some("stopped"))),
+        ]);
+        let kubeconfig = dir.path().join("kubeconfig.yaml");
+        std::fs::write(&kubeconfig, "").unwrap();
+        crashed.cluster = Some(ClusterState {
+            cluster_name: "devrig-app-1234abcd".to_string(),
+            kubeconfig_path: kubeconfig.display().to_string(),
+            registry_name: None,
+            registry_port: None,
+            deployed_services: BTreeMap::new(),
+            installed_addons: BTreeMap::new(),
+        });
+        let project = project_with_state(dir.path(), Some(crashed));
+        std::fs::write(project.state_dir.join("pid"), "4242\n").unwrap();
+        std::fs::write(control::socket_path(&project.state_dir), "").unwrap();
+
+        let stale = check_project(&project, |_| false, Some(&["other".to_string()]));
+        assert_eq!(stale.len(), 3, "{stale:?}");
+        assert!(matches!(stale[0], StaleState::PidFile { pid: 4242, .. }));
+        assert!(matches!(
+            &stale[1],
+            StaleState::ServicePhases { services, .. } if services == &["api", "web"]
+        ));
+        assert!(matches!(stale[2], StaleState::Kubeconfig { .. }));
+
+        let mut registry = InstanceRegistry::default();
+        for item in &stale {
+            item.fix(&mut registry).unwrap();
+        }
+        assert!(!project.state_dir.join("pid").exists());
+        assert!(!control::socket_path(&project.state_dir).exists());
+        assert!(!kubeconfig.exists());
+        let fixed = ProjectState::load(&project.state_dir).unwrap();
+        assert!(fixed.cluster.is_none());
+        assert_eq!(fixed.services["api"].phase.as_deref(), Some("stopped"));
+        assert_eq!(fixed.services["done"].phase.as_deref(), Some("stopped"));
+
+        // Running doctor again finds nothing.
+        assert!(check_project(&project, |_| false, Some(&[])).is_empty());
+    }
+
+    #[test]
+    fn kubeconfig_is_kept_when_k3d_is_unavailable() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut with_cluster = state(vec![]);
+        let kubeconfig = dir.path().join("kubeconfig.yaml");
+        std::fs::write(&kubeconfig, "").unwrap();
+        with_cluster.cluster = Some(ClusterState {
+            cluster_name: "devrig-app-1234abcd".to_string(),
+            kubeconfig_path: kubeconfig.display().to_string(),
+            registry_name: None,
+            registry_port: None,
+            deployed_services: BTreeMap::new(),
+            installed_addons: BTreeMap::new(),
+        });
+        let project = project_with_state(dir.path(), Some(with_cluster));
+        assert!(check_project(&project, |_| false, None).is_empty());
+    }
+
+    #[test]
+    fn registry_entry_without_state_is_stale() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = project_with_state(dir.path(), None);
+        assert_eq!(
+            check_project(&project, |_| false, None),
+            vec![StaleState::RegistryEntry {
+                slug: "app-1234abcd".to_string(),
+                reason: "has no state file",
+            }]
+        );
+    }
+
+    #[test]
+    fn only_unknown_projects_have_orphaned_containers() {
+        let containers = parse_container_list(
+            "app-1234abcd\tdevrig-app-1234abcd-postgres\t2026-01-01 09:00:00 +0000 UTC\n\
+             gone-5678ef01\tdevrig-gone-5678ef01-redis\t2026-01-01 09:00:00 +0000 UTC\n\
+             gone-5678ef01\tdevrig-gone-5678ef01-postgres\t2026-01-01 10:00:00 +0100 CET\n\
+             \tunlabelled\t2026-01-01 09:00:00 +0000 UTC\n",
+        );
+        assert_eq!(containers.len(), 3);
+        assert_eq!(
+            containers[2].created,
+            Some("2026-01-01T09:00:00Z".parse().unwrap())
+        );
+        let known = BTreeSet::from(["app-1234abcd".to_string()]);
+        let now = "2026-01-01T12:00:00Z".parse().unwrap();
+        assert_eq!(
+            orphaned_containers(containers, &known, now),
+            vec![StaleState::Containers {
+                slug: "gone-5678ef01".to_string(),
+                names: vec![
+                    "devrig-gone-5678ef01-redis".to_string(),
+                    "devrig-gone-5678ef01-postgres".to_string(),
+                ],
+            }]
+        );
+    }
+
+    #[test]
+    fn recent_containers_are_not_orphaned() {
+        // A project that is still starting may not be registered yet
+        let containers = parse_container_list(
+            "new-1234abcd\tdevrig-new-1234abcd-postgres\t2026-01-01 11:58:00 +0000 UTC\n\
+             odd-5678ef01\tdevrig-odd-5678ef01-redis\tsometime\n",
+        );
+        assert_eq!(containers.len(), 2);
+        let now = "2026-01-01T12:00:00Z".parse().unwrap();
+        assert!(orphaned_containers(containers, &BTreeSet::new(), now).is_empty());
+    }
+}
@@ -1,11 +1,69 @@
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
+use std::collections::BTreeMap;
 use std::path::Path;
 
+use crate::config;
+use crate::config::interpolate::{build_template_vars, resolve_config_templates};
+use crate::config::model::DevrigConfig;
 use crate::docker::exec::exec_in_container;
 use crate::docker::DockerManager;
 use crate::orchestrator::state::ProjectState;
+use crate::orchestrator::{full_service_env, service_working_dir};
+use crate::platform;
 
-pub async fn run(config_path: Option<&Path>, docker_name: &str, command: Vec<String>) -> Result<()> {
+/// Which kind of container an exec name may resolve to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecTarget {
+    /// `[docker.*]` first, then compose; a name in both is ambiguous.
+    Any,
+    Docker,
+    Compose,
+}
+
+/// Find the container ID for `name` among docker and compose services.
+fn resolve_container<'a>(
+    state: &'a ProjectState,
+    name: &str,
+    target: ExecTarget,
+) -> Result<&'a str> {
+    let docker = state.docker.get(name).map(|d| d.container_id.as_str());
+    let compose = state
+        .compose_services
+        .get(name)
+        .map(|c| c.container_id.as_str());
+
+    match (target, docker, compose) {
+        (ExecTarget::Any, Some(_), Some(_)) => bail!(
+            "'{}' is both a docker and a compose service; pass --docker or --compose",
+            name
+        ),
+        (ExecTarget::Any | ExecTarget::Docker, Some(id), _) => Ok(id),
+        (ExecTarget::Any | ExecTarget::Compose, _, Some(id)) => Ok(id),
+        (ExecTarget::Docker, None, _) => bail!(
+            "docker '{}' not found (available: {:?})",
+            name,
+            state.docker.keys().collect::<Vec<_>>()
+        ),
+        (ExecTarget::Compose, _, None) => bail!(
+            "compose service '{}' not found (available: {:?})",
+            name,
+            state.compose_services.keys().collect::<Vec<_>>()
+        ),
+        (ExecTarget::Any, None, None) => bail!(
+            "'{}' not found (docker: {:?}, compose: {:?})",
+            name,
+            state.docker.keys().collect::<Vec<_>>(),
+            state.compose_services.keys().collect::<Vec<_>>()
+        ),
+    }
+}
+
+pub async fn run(
+    config_path: Option<&Path>,
+    name: &str,
+    target: ExecTarget,
+    command: Vec<String>,
+) -> Result<()> {
     let config_path = match config_path {
         Some(p) => p.to_path_buf(),
         None => crate::config::resolve::resolve_config(None)?,
@@ -18,21 +76,14 @@ pub async fn run(config_path: Option<&Path>, docker_name: &str, command: Vec<Str
         anyhow::anyhow!("no running project state found -- is the project running?")
     })?;
 
-    let docker_state = state.docker.get(docker_name).ok_or_else(|| {
-        anyhow::anyhow!(
-            "docker '{}' not found (available: {:?})",
-            docker_name,
-            state.docker.keys().collect::<Vec<_>>()
-        )
-    })?;
+    let container_id = resolve_container(&state, name, target)?;
 
     if command.is_empty() {
         bail!("no command specified");
     }
 
     let mgr = DockerManager::new(state.slug.clone()).await?;
-    let (exit_code, output) =
-        exec_in_container(mgr.docker(), &docker_state.container_id, command).await?;
+    let (exit_code, output) = exec_in_container(mgr.docker(), container_id, command).await?;
 
     print!("{}", output);
 
@@ -42,3 +93,247 @@ pub async fn run(config_path: Option<&Path>, docker_name: &str, command: Vec<Str
 
     Ok(())
 }
+
+/// The env service `name` is started with, from `config` and the ports,
+/// compose services and dashboard recorded in `state`.
+fn service_env(
+    mut config: DevrigConfig,
+    state: &ProjectState,
+    name: &str,
+) -> BTreeMap<String, String> {
+    let resolved_ports = state.resolved_ports();
+    let template_vars = build_template_vars(&config, &resolved_ports);
+    let _ = resolve_config_templates(&mut config, &template_vars);
+
+    full_service_env(
+        &config,
+        name,
+        &resolved_ports,
+        &state.compose_services,
+        state.dashboard.as_ref(),
+    )
+}
+
+/// Run `command` on the host the way `[services]` entry `name` runs:
+/// through the platform shell, in its working dir, with the env devrig
+/// injects into it added to the current one.
+pub async fn run_service(
+    config_path: Option<&Path>,
+    name: &str,
+    command: Vec<String>,
+) -> Result<()> {
+    let config_path = match config_path {
+        Some(p) => p.to_path_buf(),
+        None => crate::config::resolve::resolve_config(None)?,
+    };
+
+    let (config, _source, _secrets) = config::load_config_with_secrets(&config_path)?;
+    let Some(svc) = config.services.get(name) else {
+        bail!(
+            "unknown service '{}' (available: {:?})",
+            name,
+            config.services.keys().collect::<Vec<_>>()
+        );
+    };
+
+    let project_dir = config_path.parent().unwrap_or(Path::new("."));
+    let state_dir = ProjectState::state_dir_for(project_dir);
+    let state = ProjectState::load(&state_dir).ok_or_else(|| {
+        anyhow::anyhow!("no running project state found -- is the project running?")
+    })?;
+
+    // A single argument is a shell snippet; several are quoted back together.
+    let command = match command.as_slice() {
+        [] => bail!("no command specified"),
+        [single] => single.clone(),
+        args => shlex::try_join(args.iter().map(String::as_str))
+            .context("command contains a NUL byte")?,
+    };
+
+    let working_dir =
+        service_working_dir(&config_path, svc).unwrap_or_else(|| project_dir.to_path_buf());
+    let env = service_env(config, &state, name);
+
+    let status = platform::shell_command(&command)
+        .current_dir(&working_dir)
+        .envs(&env)
+        .status()
+        .await
+        .with_context(|| format!("running {:?} for service {}", command, name))?;
+
+    if !status.success() {
+        std::process::exit(status.code().unwrap_or(1));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orchestrator::state::{ComposeServiceState, DockerState};
+    use std::collections::BTreeMap;
+
+    fn docker_state(id: &str) -> DockerState {
+        DockerState {
+            container_id: id.to_string(),
+            container_name: id.to_string(),
+            port: None,
+            port_auto: false,
+            protocol: None,
+            named_ports: BTreeMap::new(),
+            init_completed: false,
+            init_completed_at: None,
+            init_checksum: None,
+            image_digest: None,
+            phase: None,
+            stop_timeout: None,
+            health: None,
+        }
+    }
+
+    fn compose_state(id: &str) -> ComposeServiceState {
+        ComposeServiceState {
+            container_id: id.to_string(),
+            container_name: id.to_string(),
+            port: None,
+        }
+    }
+
+    fn state() -> ProjectState {
+        ProjectState {
+            slug: "test".to_string(),
+            config_path: "devrig.toml".to_string(),
+            services: BTreeMap::new(),
+            started_at: chrono::Utc::now(),
+            docker: BTreeMap::from([
+                ("postgres".to_string(), docker_state("docker-pg")),
+                ("redis".to_string(), docker_state("docker-redis")),
+            ]),
+            compose_services: BTreeMap::from([
+                ("mailpit".to_string(), compose_state("compose-mailpit")),
+                ("redis".to_string(), compose_state("compose-redis")),
+            ]),
+            network_name: None,
+            cluster: None,
+            dashboard: None,
+        }
+    }
+
+    #[test]
+    fn resolves_docker_and_compose_names() {
+        let state = state();
+        assert_eq!(
+            resolve_container(&state, "postgres", ExecTarget::Any).unwrap(),
+            "docker-pg"
+        );
+        assert_eq!(
+            resolve_container(&state, "mailpit", ExecTarget::Any).unwrap(),
+            "compose-mailpit"
+        );
+        assert_eq!(
+            resolve_container(&state, "mailpit", ExecTarget::Compose).unwrap(),
+            "compose-mailpit"
+        );
+        assert!(resolve_container(&state, "mailpit", ExecTarget::Docker).is_err());
+        assert!(resolve_container(&state, "postgres", ExecTarget::Compose).is_err());
+    }
+
+    #[test]
+    fn name_in_both_maps_needs_disambiguation() {
+        let state = state();
+        let err = resolve_container(&state, "redis", ExecTarget::Any).unwrap_err();
+        assert!(err.to_string().contains("--docker or --compose"));
+        assert_eq!(
+            resolve_container(&state, "redis", ExecTarget::Docker).unwrap(),
+            "docker-redis"
+        );
+        assert_eq!(
+            resolve_container(&state, "redis", ExecTarget::Compose).unwrap(),
+            "compose-redis"
+        );
+    }
+
+    #[test]
+    fn service_env_uses_recorded_ports_and_dashboard() {
+        let config: DevrigConfig = toml::from_str(
+            r#"
+            [project]
+            name = "test"
+
+            [services.api]
+            command = "cargo run"
+            port = "auto"
+
+            [services.api.env]
+            DATABASE_URL = "postgres://localhost:{{ docker.postgres.port }}/app"
+
+            [docker.postgres]
+            image = "postgres:16"
+            port = "auto"
+            "#,
+        )
+        .unwrap();
+        let mut state = state();
+        state.docker.get_mut("postgres").unwrap().port = Some(54321);
+        state.services.insert(
+            "api".to_string(),
+            crate::orchestrator::state::ServiceState {
+                pid: 1,
+                port: Some(41000),
+                port_auto: true,
+                protocol: None,
+                phase: None,
+                exit_code: None,
+                ready: None,
+            },
+        );
+        state.dashboard = Some(crate::orchestrator::state::DashboardState {
+            dashboard_port: 4000,
+            grpc_port: 4317,
+            http_port: 4318,
+            tls_cert: None,
+            otel_tls: false,
+        });
+
+        let env = service_env(config, &state, "api");
+        assert_eq!(env["PORT"], "41000");
+        assert_eq!(env["DATABASE_URL"], "postgres://localhost:54321/app");
+        assert_eq!(env["DEVRIG_POSTGRES_PORT"], "54321");
+        assert_eq!(env["DEVRIG_MAILPIT_HOST"], "localhost");
+        assert_eq!(env["OTEL_EXPORTER_OTLP_ENDPOINT"], "http://localhost:4318");
+        assert_eq!(env["OTEL_SERVICE_NAME"], "api");
+    }
+
+    #[test]
+    fn compose_urls_use_configured_protocols() {
+        let config: DevrigConfig = toml::from_str(
+            r#"
+            [project]
+            name = "test"
+
+            [services.api]
+            command = "cargo run"
+
+            [compose]
+            file = "compose.yml"
+            protocols = { mailpit = "https" }
+            "#,
+        )
+        .unwrap();
+        let mut state = state();
+        state.compose_services.get_mut("mailpit").unwrap().port = Some(8025);
+        state.compose_services.get_mut("redis").unwrap().port = Some(6379);
+
+        let env = service_env(config, &state, "api");
+        assert_eq!(env["DEVRIG_MAILPIT_URL"], "https://localhost:8025");
+        assert_eq!(env["DEVRIG_REDIS_URL"], "http://localhost:6379");
+    }
+
+    #[test]
+    fn unknown_name_lists_both_maps() {
+        let err = resolve_container(&state(), "nope", ExecTarget::Any).unwrap_err();
+        let msg = err.to_string();
+        assert!(msg.contains("postgres") && msg.contains("mailpit"));
+    }
+}
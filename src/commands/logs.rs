@@ -1,12 +1,23 @@
-use anyhow::{bail, Result};
-use chrono::{Duration, Utc};
+use anyhow::{bail, Context, Result};
+use bollard::container::LogOutput;
+use bollard::query_parameters::LogsOptions;
+use bollard::Docker;
+use chrono::{DateTime, Duration, Utc};
+use futures_util::StreamExt;
 use regex::Regex;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::io::{BufRead, BufReader, Write};
 use std::path::{Path, PathBuf};
 
 use crate::config::resolve::resolve_config;
+use crate::config::secrets::SecretRegistry;
+use crate::config::select;
+use crate::config::{load_config, load_config_with_secrets};
+use crate::orchestrator::state::ProjectState;
+use crate::otel::types::{LogSeverity, StoredLog};
 use crate::ui::filter::LogFilter;
-use crate::ui::logs::{LogLevel, LogLine};
+use crate::ui::logs::{detect_log_level, LogLevel, LogLine};
 
 /// Parse a human-readable duration string like "5m", "1h", "30s".
 fn parse_duration(s: &str) -> Result<Duration> {
@@ -50,10 +61,633 @@ fn parse_level(s: &str) -> Result<LogLevel> {
     }
 }
 
+/// How often `--follow` checks the log file for new lines.
+const FOLLOW_POLL: std::time::Duration = std::time::Duration::from_millis(250);
+
+/// How often `--follow --with-state` re-reads service phases from state.json.
+const PHASE_REFRESH: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// How often `--follow --narrow` asks the dashboard for newly correlated
+/// store logs.
+const STORE_REFRESH: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Read each service's current lifecycle phase from `.devrig/state.json`.
+///
+/// Returns an empty map when devrig is not running.
+fn load_phases(state_dir: &Path) -> BTreeMap<String, String> {
+    ProjectState::load(state_dir)
+        .map(|state| {
+            state
+                .services
+                .into_iter()
+                .filter_map(|(name, svc)| svc.phase.map(|phase| (name, phase)))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Write one log line in the requested format, annotated with the service's
+/// phase when `phases` is given.
+fn write_line(
+    out: &mut dyn Write,
+    line: &LogLine,
+    format: &str,
+    timestamps: bool,
+    phases: Option<&BTreeMap<String, String>>,
+) -> Result<()> {
+    let phase = phases.and_then(|p| p.get(&line.service));
+    match format {
+        "json" => {
+            let mut value = serde_json::to_value(line)?;
+            if let (Some(phase), Some(obj)) = (phase, value.as_object_mut()) {
+                obj.insert("phase".to_string(), serde_json::Value::from(phase.as_str()));
+            }
+            serde_json::to_writer(&mut *out, &value)?;
+            writeln!(out)?;
+        }
+        _ => {
+            if timestamps {
+                write!(out, "{} ", line.timestamp.format("%H:%M:%S%.3f"))?;
+            }
+            if let Some(ref level) = line.level {
+                write!(out, "{:>5} ", level.as_str())?;
+            }
+            match phase {
+                Some(phase) => writeln!(out, "{} [{}] | {}", line.service, phase, line.text)?,
+                None => writeln!(out, "{} | {}", line.service, line.text)?,
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Convert an OTel store log record into a `LogLine` for display.
+fn stored_to_logline(log: &StoredLog) -> LogLine {
+    let is_stderr = log
+        .attributes
+        .iter()
+        .any(|(k, v)| k == "log.source" && v == "stderr");
+    let level = match log.severity {
+        LogSeverity::Trace => LogLevel::Trace,
+        LogSeverity::Debug => LogLevel::Debug,
+        LogSeverity::Info => LogLevel::Info,
+        LogSeverity::Warn => LogLevel::Warn,
+        LogSeverity::Error | LogSeverity::Fatal => LogLevel::Error,
+    };
+    LogLine {
+        timestamp: log.timestamp,
+        service: log.service_name.clone(),
+        text: log.body.clone(),
+        is_stderr,
+        level: Some(level),
+    }
+}
+
+/// Union process log lines that mention `trace_id` with store log records
+/// correlated to it, ordered by timestamp.
+///
+/// Lines present in both sources (same timestamp, service and text) are
+/// emitted once.
+fn narrow_to_trace(file_lines: Vec<LogLine>, store_logs: &[StoredLog], trace_id: &str) -> Vec<LogLine> {
+    let needle = trace_id.to_lowercase();
+    let mut merged: Vec<LogLine> = file_lines
+        .into_iter()
+        .filter(|l| l.text.to_lowercase().contains(&needle))
+        .collect();
+
+    for log in store_logs {
+        if !log
+            .trace_id
+            .as_deref()
+            .is_some_and(|t| t.eq_ignore_ascii_case(trace_id))
+        {
+            continue;
+        }
+        let line = stored_to_logline(log);
+        let duplicate = merged.iter().any(|l| {
+            l.timestamp == line.timestamp && l.service == line.service && l.text == line.text
+        });
+        if !duplicate {
+            merged.push(line);
+        }
+    }
+
+    merged.sort_by_key(|l| l.timestamp);
+    merged
+}
+
+/// `metadata.json` written alongside the logs in an `--export-bundle` archive.
+#[derive(Debug, Serialize)]
+struct BundleMetadata {
+    project: String,
+    devrig_version: &'static str,
+    exported_at: DateTime<Utc>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    services: Vec<String>,
+    line_count: usize,
+}
+
+/// Write `lines` as `logs.jsonl` plus `metadata.json` into a zip archive at
+/// `path`, masking every value known to `secrets` in the log text.
+fn write_bundle(
+    path: &Path,
+    lines: &[LogLine],
+    secrets: &SecretRegistry,
+    metadata: &BundleMetadata,
+) -> Result<()> {
+    let file = std::fs::File::create(path)
+        .map_err(|e| anyhow::anyhow!("failed to create {}: {}", path.display(), e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    zip.start_file("logs.jsonl", options)?;
+    for line in lines {
+        let masked = LogLine {
+            text: secrets.mask_value(&line.text),
+            ..line.clone()
+        };
+        serde_json::to_writer(&mut zip, &masked)?;
+        writeln!(zip)?;
+    }
+
+    zip.start_file("metadata.json", options)?;
+    serde_json::to_writer_pretty(&mut zip, metadata)?;
+
+    zip.finish()?;
+    Ok(())
+}
+
+/// Fetch store log records correlated to `trace_id` from the dashboard API,
+/// keeping those at or after `since` that pass `filter`.
+///
+/// Returns an empty list when the dashboard is not running so that
+/// `--narrow` still works against process logs alone.
+async fn fetch_trace_logs(
+    config_path: &Path,
+    trace_id: &str,
+    filter: &LogFilter,
+    since: Option<DateTime<Utc>>,
+) -> Result<Vec<StoredLog>> {
+    let Ok(api) = crate::commands::query::dashboard_api(Some(config_path)) else {
+        return Ok(Vec::new());
+    };
+    let mut url = crate::commands::query::api_url(&api.base_url, &["logs"])?;
+    crate::commands::query::push_param(&mut url, "trace_id", Some(trace_id));
+    crate::commands::query::push_param(&mut url, "limit", Some(10000));
+    let resp = api
+        .client()?
+        .get(url)
+        .send()
+        .await
+        .context("could not reach dashboard")?;
+    if !resp.status().is_success() {
+        bail!("dashboard API returned {}", resp.status());
+    }
+    let logs: Vec<StoredLog> = resp.json().await.unwrap_or_default();
+    Ok(logs
+        .into_iter()
+        .filter(|log| since.is_none_or(|cutoff| log.timestamp >= cutoff))
+        .filter(|log| filter.matches(&stored_to_logline(log)))
+        .collect())
+}
+
+/// Store records from a `--follow --narrow` poll that have not been shown
+/// yet, as time-ordered lines. `seen` holds the record ids fetched so far
+/// and `printed` the lines already written from either source.
+fn unseen_store_lines(
+    store_logs: Vec<StoredLog>,
+    trace_id: &str,
+    seen: &mut HashSet<u64>,
+    printed: &mut HashSet<(DateTime<Utc>, String, String)>,
+) -> Vec<LogLine> {
+    let fresh: Vec<StoredLog> = store_logs
+        .into_iter()
+        .filter(|log| seen.insert(log.record_id))
+        .collect();
+    narrow_to_trace(Vec::new(), &fresh, trace_id)
+        .into_iter()
+        .filter(|line| printed.insert(line_key(line)))
+        .collect()
+}
+
+/// Identifies a line for `--narrow` deduplication across the log file and
+/// the store, the way [`narrow_to_trace`] compares them.
+fn line_key(line: &LogLine) -> (DateTime<Utc>, String, String) {
+    (line.timestamp, line.service.clone(), line.text.clone())
+}
+
+/// Follows a JSONL log file, reopening it from the start when a new
+/// `devrig start` truncates or recreates it.
+struct LogTail {
+    path: PathBuf,
+    reader: BufReader<std::fs::File>,
+    /// Bytes consumed so far, including any partial line in `pending`.
+    pos: u64,
+    #[cfg(unix)]
+    inode: u64,
+    pending: String,
+}
+
+impl LogTail {
+    /// Continue tailing from wherever `reader` has read up to.
+    fn new(path: &Path, mut reader: BufReader<std::fs::File>) -> Result<Self> {
+        use std::io::Seek;
+
+        let pos = reader.stream_position()?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            #[cfg(unix)]
+            inode: std::os::unix::fs::MetadataExt::ino(&reader.get_ref().metadata()?),
+            reader,
+            pos,
+            pending: String::new(),
+        })
+    }
+
+    /// Return the next complete line, or `None` if nothing new has been
+    /// written yet. Partial writes are kept until the line is finished.
+    fn next_line(&mut self) -> Result<Option<String>> {
+        let n = self.reader.read_line(&mut self.pending)?;
+        self.pos += n as u64;
+        if n > 0 && self.pending.ends_with('\n') {
+            return Ok(Some(std::mem::take(&mut self.pending)));
+        }
+        if self.rotated() {
+            self.reopen()?;
+        }
+        Ok(None)
+    }
+
+    /// Whether the file on disk was truncated or replaced since we opened it.
+    fn rotated(&self) -> bool {
+        let Ok(meta) = std::fs::metadata(&self.path) else {
+            return false;
+        };
+        #[cfg(unix)]
+        if std::os::unix::fs::MetadataExt::ino(&meta) != self.inode {
+            return true;
+        }
+        meta.len() < self.pos
+    }
+
+    fn reopen(&mut self) -> Result<()> {
+        let file = std::fs::File::open(&self.path)?;
+        #[cfg(unix)]
+        {
+            self.inode = std::os::unix::fs::MetadataExt::ino(&file.metadata()?);
+        }
+        self.reader = BufReader::new(file);
+        self.pos = 0;
+        self.pending.clear();
+        Ok(())
+    }
+}
+
+/// Read JSONL log lines from `reader`, keeping those that pass `filter` and
+/// are no older than `since`. Blank and malformed lines are skipped.
+fn read_matching_lines(
+    reader: &mut impl BufRead,
+    filter: &LogFilter,
+    since: Option<DateTime<Utc>>,
+) -> Result<Vec<LogLine>> {
+    let mut lines = Vec::new();
+    for line_result in reader.lines() {
+        let line_str = line_result?;
+        if line_str.trim().is_empty() {
+            continue;
+        }
+        let log_line: LogLine = match serde_json::from_str(&line_str) {
+            Ok(l) => l,
+            Err(_) => continue, // skip malformed lines
+        };
+
+        // Apply --since filter
+        if let Some(cutoff) = since {
+            if log_line.timestamp < cutoff {
+                continue;
+            }
+        }
+
+        if filter.matches(&log_line) {
+            lines.push(log_line);
+        }
+    }
+    Ok(lines)
+}
+
+/// Docker and compose containers `--container` reads from, as
+/// `(name, container id)`: those named in `services` (globs allowed), or all
+/// of them when `services` is empty.
+fn container_sources(state: &ProjectState, services: &[String]) -> Result<Vec<(String, String)>> {
+    let available: Vec<String> = state
+        .docker
+        .keys()
+        .chain(state.compose_services.keys())
+        .cloned()
+        .collect();
+    if available.is_empty() {
+        bail!("no docker or compose containers are running");
+    }
+    let names = if services.is_empty() {
+        available.clone()
+    } else {
+        select::expand_patterns(services, available.iter())?
+    };
+    names
+        .into_iter()
+        .map(|name| {
+            let id = state
+                .docker
+                .get(&name)
+                .map(|d| d.container_id.clone())
+                .or_else(|| {
+                    state
+                        .compose_services
+                        .get(&name)
+                        .map(|c| c.container_id.clone())
+                })
+                .with_context(|| {
+                    format!(
+                        "'{}' is not a running docker or compose resource (available: {})",
+                        name,
+                        available.join(", ")
+                    )
+                })?;
+            Ok((name, id))
+        })
+        .collect()
+}
+
+/// Split one frame of `docker logs --timestamps` output into `LogLine`s,
+/// taking each line's timestamp from the prefix Docker adds.
+fn container_lines(service: &str, output: &LogOutput) -> Vec<LogLine> {
+    let (message, is_stderr) = match output {
+        LogOutput::StdOut { message } | LogOutput::Console { message } => (message, false),
+        LogOutput::StdErr { message } => (message, true),
+        LogOutput::StdIn { .. } => return Vec::new(),
+    };
+    String::from_utf8_lossy(message)
+        .lines()
+        .filter_map(|raw| {
+            let (timestamp, text) = match raw
+                .split_once(' ')
+                .map(|(ts, text)| (DateTime::parse_from_rfc3339(ts), text))
+            {
+                Some((Ok(ts), text)) => (ts.with_timezone(&Utc), text),
+                _ => (Utc::now(), raw),
+            };
+            let text = text.trim_end();
+            (!text.is_empty()).then(|| LogLine {
+                timestamp,
+                service: service.to_string(),
+                text: text.to_string(),
+                is_stderr,
+                level: detect_log_level(text),
+            })
+        })
+        .collect()
+}
+
+/// `--container`: read each container's output straight from Docker rather
+/// than from the log file, so docker logs are available without the
+/// dashboard's log bridge.
 #[allow(clippy::too_many_arguments)]
-pub fn run(
+async fn run_container_logs(
+    sources: &[(String, String)],
+    filter: &LogFilter,
+    since: Option<DateTime<Utc>>,
+    tail: Option<usize>,
+    follow: bool,
+    out: &mut dyn Write,
+    format: &str,
+    timestamps: bool,
+) -> Result<()> {
+    let docker = Docker::connect_with_local_defaults().context("connecting to Docker daemon")?;
+    let streams = |follow: bool, since: &HashMap<&str, i32>| {
+        futures_util::stream::select_all(sources.iter().map(|(service, id)| {
+            let options = LogsOptions {
+                follow,
+                stdout: true,
+                stderr: true,
+                timestamps: true,
+                since: since.get(service.as_str()).copied().unwrap_or(0),
+                tail: "all".to_string(),
+                ..Default::default()
+            };
+            docker
+                .logs(id, Some(options))
+                .map(move |item| (service.as_str(), item))
+                .boxed()
+        }))
+    };
+
+    // History first, merged across containers in time order.
+    let started = Utc::now().timestamp() as i32;
+    let cutoff = since.map_or(0, |c| c.timestamp() as i32);
+    let from_cutoff: HashMap<&str, i32> =
+        sources.iter().map(|(s, _)| (s.as_str(), cutoff)).collect();
+    let mut last_seen: HashMap<&str, DateTime<Utc>> = HashMap::new();
+    let mut lines = Vec::new();
+    let mut history = streams(false, &from_cutoff);
+    while let Some((service, item)) = history.next().await {
+        let output = item.with_context(|| format!("reading container logs for '{}'", service))?;
+        for line in container_lines(service, &output) {
+            last_seen.insert(service, line.timestamp);
+            lines.push(line);
+        }
+    }
+    drop(history);
+    lines.retain(|l| since.is_none_or(|cutoff| l.timestamp >= cutoff) && filter.matches(l));
+    lines.sort_by_key(|l| l.timestamp);
+    if let Some(n) = tail {
+        let skip = lines.len().saturating_sub(n);
+        lines.drain(..skip);
+    }
+    for line in &lines {
+        write_line(out, line, format, timestamps, None)?;
+    }
+    if !follow {
+        return Ok(());
+    }
+
+    // Then live output. Docker's `since` is whole seconds, so lines from the
+    // last second of history come back and are skipped by timestamp.
+    let resume: HashMap<&str, i32> = sources
+        .iter()
+        .map(|(s, _)| {
+            let since = last_seen
+                .get(s.as_str())
+                .map_or(started, |ts| ts.timestamp() as i32);
+            (s.as_str(), since)
+        })
+        .collect();
+    let mut live = streams(true, &resume);
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+    loop {
+        out.flush()?;
+        let (service, item) = tokio::select! {
+            _ = &mut ctrl_c => break,
+            next = live.next() => match next {
+                Some(next) => next,
+                None => break, // every container stopped
+            },
+        };
+        let output = match item {
+            Ok(output) => output,
+            Err(e) => {
+                eprintln!(
+                    "warning: container log stream for '{}' ended: {}",
+                    service, e
+                );
+                continue;
+            }
+        };
+        for line in container_lines(service, &output) {
+            if last_seen
+                .get(service)
+                .is_some_and(|seen| line.timestamp <= *seen)
+            {
+                continue;
+            }
+            if filter.matches(&line) {
+                write_line(out, &line, format, timestamps, None)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// How many rolled-over `--output` files to keep (`FILE.1` is the newest).
+const ROTATE_KEEP: usize = 5;
+
+/// Parse a size like "10M", "512K", "1G" or a plain byte count.
+fn parse_size(s: &str) -> Result<u64> {
+    let lower = s.trim().to_ascii_lowercase();
+    let lower = lower.strip_suffix('b').unwrap_or(&lower);
+    let (num, multiplier) = match lower.chars().last() {
+        Some('k') => (&lower[..lower.len() - 1], 1 << 10),
+        Some('m') => (&lower[..lower.len() - 1], 1 << 20),
+        Some('g') => (&lower[..lower.len() - 1], 1 << 30),
+        _ => (lower, 1),
+    };
+    let n: u64 = num
+        .trim()
+        .parse()
+        .map_err(|_| anyhow::anyhow!("invalid size '{}' (use e.g. 10M, 512K, 1G)", s))?;
+    if n == 0 {
+        bail!("size must be greater than zero");
+    }
+    n.checked_mul(multiplier)
+        .ok_or_else(|| anyhow::anyhow!("size '{}' is too large", s))
+}
+
+/// The `--output` file. Lines are appended, and with `--rotate` the file is
+/// rolled over to `FILE.1` (shifting older copies up to `FILE.5`) once a
+/// line takes it past the limit.
+struct OutputFile {
+    path: PathBuf,
+    file: std::io::BufWriter<std::fs::File>,
+    written: u64,
+    rotate: Option<u64>,
+}
+
+impl OutputFile {
+    fn open(path: &Path, rotate: Option<u64>) -> Result<Self> {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("opening {}", path.display()))?;
+        let written = file.metadata()?.len();
+        Ok(Self {
+            path: path.to_path_buf(),
+            file: std::io::BufWriter::new(file),
+            written,
+            rotate,
+        })
+    }
+
+    fn rolled(&self, n: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{}", n));
+        PathBuf::from(name)
+    }
+
+    fn roll(&mut self) -> std::io::Result<()> {
+        self.file.flush()?;
+        for n in (1..ROTATE_KEEP).rev() {
+            let from = self.rolled(n);
+            if from.exists() {
+                std::fs::rename(&from, self.rolled(n + 1))?;
+            }
+        }
+        std::fs::rename(&self.path, self.rolled(1))?;
+        self.file = std::io::BufWriter::new(std::fs::File::create(&self.path)?);
+        self.written = 0;
+        Ok(())
+    }
+}
+
+impl Write for OutputFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.file.write_all(buf)?;
+        self.written += buf.len() as u64;
+        // Only roll at the end of a line so no line is split across files.
+        if self.rotate.is_some_and(|limit| self.written >= limit) && buf.ends_with(b"\n") {
+            self.roll()?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Writes everything to both sides.
+struct Tee<A, B>(A, B);
+
+impl<A: Write, B: Write> Write for Tee<A, B> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.write_all(buf)?;
+        self.1.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.0.flush()?;
+        self.1.flush()
+    }
+}
+
+/// Where output goes: stdout, plus the `--output` file when one is given
+/// (only the file with `--quiet`).
+fn open_output(output: Option<&Path>, quiet: bool, rotate: Option<u64>) -> Result<Box<dyn Write>> {
+    let Some(path) = output else {
+        return Ok(Box::new(std::io::stdout()));
+    };
+    let file = OutputFile::open(path, rotate)?;
+    Ok(if quiet {
+        Box::new(file)
+    } else {
+        Box::new(Tee(std::io::stdout(), file))
+    })
+}
+
+/// Whether `--count` should exit successfully: some lines matched, or none
+/// did when `--expect-zero` is set.
+fn count_passes(count: usize, expect_zero: bool) -> bool {
+    (count == 0) == expect_zero
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn run(
     config_file: Option<&Path>,
     services: Vec<String>,
+    follow: bool,
     tail: Option<usize>,
     since: Option<String>,
     grep: Option<String>,
@@ -61,8 +695,19 @@ pub fn run(
     level: Option<String>,
     format: String,
     output: Option<PathBuf>,
+    quiet: bool,
+    rotate: Option<String>,
     timestamps: bool,
+    narrow: Option<String>,
+    with_state: bool,
+    export_bundle: Option<PathBuf>,
+    count: bool,
+    expect_zero: bool,
+    container: bool,
 ) -> Result<()> {
+    let rotate = rotate
+        .map(|size| parse_size(&size).context("invalid --rotate"))
+        .transpose()?;
     let config_path = resolve_config(config_file)?;
     let state_dir = config_path
         .parent()
@@ -70,12 +715,32 @@ pub fn run(
         .join(".devrig");
 
     let log_file = state_dir.join("logs").join("current.jsonl");
-    if !log_file.exists() {
-        bail!(
-            "No log file found at {}. Are services running?",
-            log_file.display()
-        );
-    }
+    // --container reads from Docker, so it needs running containers rather
+    // than the log file.
+    let containers = if container {
+        let state = ProjectState::load(&state_dir)
+            .context("devrig is not running for this project (no .devrig/state.json)")?;
+        Some(container_sources(&state, &services)?)
+    } else {
+        if !log_file.exists() {
+            bail!(
+                "No log file found at {}. Are services running?",
+                log_file.display()
+            );
+        }
+        None
+    };
+
+    // Globs expand against [services]; literal names are used as given.
+    // With --container the sources are already picked.
+    let services = if containers.is_some() {
+        Vec::new()
+    } else if services.iter().any(|s| select::is_pattern(s)) {
+        let (config, _source) = load_config(&config_path)?;
+        select::expand_patterns(&services, config.services.keys())?
+    } else {
+        services
+    };
 
     // Build filter
     let mut filter = LogFilter::new();
@@ -99,31 +764,40 @@ pub fn run(
         .map(|s| parse_duration(&s).map(|d| Utc::now() - d))
         .transpose()?;
 
+    if let Some(sources) = containers {
+        let mut out = open_output(output.as_deref(), quiet, rotate)?;
+        run_container_logs(
+            &sources,
+            &filter,
+            since_cutoff,
+            tail,
+            follow,
+            &mut out,
+            &format,
+            timestamps,
+        )
+        .await?;
+        out.flush()?;
+        return Ok(());
+    }
+
     // Read and filter lines from JSONL
     let file = std::fs::File::open(&log_file)?;
-    let reader = BufReader::new(file);
-
-    let mut lines: Vec<LogLine> = Vec::new();
-    for line_result in reader.lines() {
-        let line_str = line_result?;
-        if line_str.trim().is_empty() {
-            continue;
-        }
-        let log_line: LogLine = match serde_json::from_str(&line_str) {
-            Ok(l) => l,
-            Err(_) => continue, // skip malformed lines
-        };
-
-        // Apply --since filter
-        if let Some(cutoff) = since_cutoff {
-            if log_line.timestamp < cutoff {
-                continue;
-            }
-        }
+    let mut reader = BufReader::new(file);
+    let mut lines = read_matching_lines(&mut reader, &filter, since_cutoff)?;
 
-        if filter.matches(&log_line) {
-            lines.push(log_line);
-        }
+    // Apply --narrow: union with correlated store logs, time-ordered.
+    // Store records seen here are not repeated when following.
+    let mut seen_records = HashSet::new();
+    if let Some(ref trace_id) = narrow {
+        let store_logs = fetch_trace_logs(&config_path, trace_id, &filter, since_cutoff)
+            .await
+            .unwrap_or_else(|e| {
+                eprintln!("warning: {:#}; showing process logs only", e);
+                Vec::new()
+            });
+        seen_records.extend(store_logs.iter().map(|log| log.record_id));
+        lines = narrow_to_trace(lines, &store_logs, trace_id);
     }
 
     // Apply --tail
@@ -132,30 +806,104 @@ pub fn run(
         lines = lines.into_iter().skip(skip).collect();
     }
 
+    if count {
+        println!("{}", lines.len());
+        if !count_passes(lines.len(), expect_zero) {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    if let Some(ref path) = export_bundle {
+        let (config, _source, secrets) = load_config_with_secrets(&config_path)?;
+        let metadata = BundleMetadata {
+            project: config.project.name,
+            devrig_version: env!("CARGO_PKG_VERSION"),
+            exported_at: Utc::now(),
+            from: since_cutoff.or_else(|| lines.first().map(|l| l.timestamp)),
+            to: lines.last().map(|l| l.timestamp),
+            services: filter.services.clone(),
+            line_count: lines.len(),
+        };
+        write_bundle(path, &lines, &secrets, &metadata)?;
+        println!("Wrote {} log lines to {}", lines.len(), path.display());
+        return Ok(());
+    }
+
     // Output
-    let mut out: Box<dyn Write> = if let Some(ref path) = output {
-        Box::new(std::io::BufWriter::new(std::fs::File::create(path)?))
-    } else {
-        Box::new(std::io::stdout())
-    };
+    let mut out = open_output(output.as_deref(), quiet, rotate)?;
 
-    match format.as_str() {
-        "json" => {
-            for line in &lines {
-                serde_json::to_writer(&mut out, line)?;
-                writeln!(out)?;
-            }
-        }
-        _ => {
-            for line in &lines {
-                if timestamps {
-                    write!(out, "{} ", line.timestamp.format("%H:%M:%S%.3f"))?;
+    let mut phases = with_state.then(|| load_phases(&state_dir));
+    for line in &lines {
+        write_line(&mut out, line, &format, timestamps, phases.as_ref())?;
+    }
+
+    if follow {
+        let needle = narrow.as_deref().map(str::to_lowercase);
+        // With --narrow, lines already shown from either source.
+        let mut printed: HashSet<_> = if narrow.is_some() {
+            lines.iter().map(line_key).collect()
+        } else {
+            HashSet::new()
+        };
+        let mut refreshed = std::time::Instant::now();
+        let mut store_polled = std::time::Instant::now();
+        let mut tail = LogTail::new(&log_file, reader)?;
+        let ctrl_c = tokio::signal::ctrl_c();
+        tokio::pin!(ctrl_c);
+        loop {
+            let Some(line_str) = tail.next_line()? else {
+                out.flush()?;
+                tokio::select! {
+                    _ = &mut ctrl_c => break,
+                    _ = tokio::time::sleep(FOLLOW_POLL) => {}
                 }
-                if let Some(ref level) = line.level {
-                    write!(out, "{:>5} ", level.as_str())?;
+                if let Some(ref mut map) = phases {
+                    if refreshed.elapsed() >= PHASE_REFRESH {
+                        *map = load_phases(&state_dir);
+                        refreshed = std::time::Instant::now();
+                    }
+                }
+                if let Some(ref trace_id) = narrow {
+                    if store_polled.elapsed() >= STORE_REFRESH {
+                        // Failures were reported before following; the
+                        // next poll simply tries again.
+                        let store_logs =
+                            fetch_trace_logs(&config_path, trace_id, &filter, since_cutoff)
+                                .await
+                                .unwrap_or_default();
+                        let fresh = unseen_store_lines(
+                            store_logs,
+                            trace_id,
+                            &mut seen_records,
+                            &mut printed,
+                        );
+                        for line in &fresh {
+                            write_line(&mut out, line, &format, timestamps, phases.as_ref())?;
+                        }
+                        store_polled = std::time::Instant::now();
+                    }
+                }
+                continue;
+            };
+
+            let Ok(log_line) = serde_json::from_str::<LogLine>(line_str.trim()) else {
+                continue;
+            };
+            if since_cutoff.is_some_and(|cutoff| log_line.timestamp < cutoff) {
+                continue;
+            }
+            if !filter.matches(&log_line) {
+                continue;
+            }
+            if let Some(ref needle) = needle {
+                if !log_line.text.to_lowercase().contains(needle)
+                    || !printed.insert(line_key(&log_line))
+                {
+                    continue;
                 }
-                writeln!(out, "{} | {}", line.service, line.text)?;
             }
+            write_line(&mut out, &log_line, &format, timestamps, phases.as_ref())?;
         }
     }
 
@@ -208,9 +956,395 @@ mod tests {
         assert_eq!(parse_level("error").unwrap(), LogLevel::Error);
     }
 
+    fn file_line(secs: i64, service: &str, text: &str) -> LogLine {
+        LogLine {
+            timestamp: chrono::DateTime::from_timestamp(secs, 0).unwrap(),
+            service: service.to_string(),
+            text: text.to_string(),
+            is_stderr: false,
+            level: None,
+        }
+    }
+
+    fn store_log(secs: i64, service: &str, body: &str, trace_id: Option<&str>) -> StoredLog {
+        StoredLog {
+            record_id: 0,
+            timestamp: chrono::DateTime::from_timestamp(secs, 0).unwrap(),
+            service_name: service.to_string(),
+            severity: LogSeverity::Error,
+            body: body.to_string(),
+            trace_id: trace_id.map(String::from),
+            span_id: None,
+            attributes: vec![("log.source".to_string(), "otlp".to_string())],
+        }
+    }
+
+    #[test]
+    fn narrow_unions_and_orders_by_timestamp() {
+        let trace = "4bf92f3577b34da6a3ce929d0e0e4736";
+        let file_lines = vec![
+            file_line(10, "api", &format!("GET /users trace_id={}", trace)),
+            file_line(11, "api", "unrelated request"),
+            file_line(30, "worker", &format!("job done trace={}", trace)),
+        ];
+        let store_logs = vec![
+            store_log(20, "payments", "charge failed", Some(trace)),
+            store_log(5, "gateway", "request received", Some(trace)),
+            store_log(15, "payments", "other trace", Some("deadbeef")),
+            store_log(25, "payments", "no trace", None),
+        ];
+
+        let merged = narrow_to_trace(file_lines, &store_logs, trace);
+        let summary: Vec<(&str, i64)> = merged
+            .iter()
+            .map(|l| (l.service.as_str(), l.timestamp.timestamp()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![("gateway", 5), ("api", 10), ("payments", 20), ("worker", 30)]
+        );
+        assert_eq!(merged[2].level, Some(LogLevel::Error));
+    }
+
+    #[test]
+    fn narrow_deduplicates_lines_present_in_both_sources() {
+        let trace = "abc123";
+        let file_lines = vec![file_line(1, "api", "handling abc123")];
+        let mut dup = store_log(1, "api", "handling abc123", Some(trace));
+        dup.attributes = vec![("log.source".to_string(), "stdout".to_string())];
+
+        let merged = narrow_to_trace(file_lines, &[dup], trace);
+        assert_eq!(merged.len(), 1);
+    }
+
+    #[test]
+    fn follow_narrow_shows_only_new_store_lines() {
+        let trace = "4bf92f3577b34da6a3ce929d0e0e4736";
+        let shown = file_line(10, "api", &format!("GET /users trace_id={}", trace));
+        let mut seen = HashSet::from([1]);
+        let mut printed = HashSet::from([line_key(&shown)]);
+        let log = |id, secs, service: &str, body: &str| StoredLog {
+            record_id: id,
+            ..store_log(secs, service, body, Some(trace))
+        };
+
+        let poll = vec![
+            log(1, 5, "gateway", "request received"),
+            log(3, 30, "payments", "refund issued"),
+            log(2, 20, "payments", "charge failed"),
+            log(4, 10, "api", &shown.text),
+        ];
+        let fresh = unseen_store_lines(poll.clone(), trace, &mut seen, &mut printed);
+        let texts: Vec<&str> = fresh.iter().map(|l| l.text.as_str()).collect();
+        assert_eq!(texts, vec!["charge failed", "refund issued"]);
+
+        // The next poll returns the same records; nothing is repeated.
+        assert!(unseen_store_lines(poll, trace, &mut seen, &mut printed).is_empty());
+    }
+
     #[test]
     fn parse_level_invalid() {
         assert!(parse_level("critical").is_err());
         assert!(parse_level("").is_err());
     }
+
+    #[test]
+    fn with_state_annotates_lines_with_service_phase() {
+        use crate::orchestrator::state::ServiceState;
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut services = BTreeMap::new();
+        for (name, phase) in [("api", Some("restarting")), ("web", None)] {
+            services.insert(
+                name.to_string(),
+                ServiceState {
+                    pid: 1,
+                    port: None,
+                    port_auto: false,
+                    protocol: None,
+                    phase: phase.map(String::from),
+                    exit_code: None,
+                    ready: None,
+                },
+            );
+        }
+        ProjectState {
+            slug: "test".to_string(),
+            config_path: "devrig.toml".to_string(),
+            services,
+            started_at: Utc::now(),
+            docker: BTreeMap::new(),
+            compose_services: BTreeMap::new(),
+            network_name: None,
+            cluster: None,
+            dashboard: None,
+        }
+        .save(dir.path())
+        .unwrap();
+
+        let phases = load_phases(dir.path());
+        let mut out = Vec::new();
+        write_line(
+            &mut out,
+            &file_line(0, "api", "boom"),
+            "text",
+            false,
+            Some(&phases),
+        )
+        .unwrap();
+        write_line(
+            &mut out,
+            &file_line(0, "web", "ok"),
+            "text",
+            false,
+            Some(&phases),
+        )
+        .unwrap();
+        write_line(
+            &mut out,
+            &file_line(0, "api", "boom"),
+            "json",
+            false,
+            Some(&phases),
+        )
+        .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+
+        assert_eq!(lines[0], "api [restarting] | boom");
+        assert_eq!(lines[1], "web | ok");
+        let json: serde_json::Value = serde_json::from_str(lines[2]).unwrap();
+        assert_eq!(json["phase"], "restarting");
+    }
+
+    #[test]
+    fn count_reads_fixture_and_reports_exit_status() {
+        let mut fixture = Vec::new();
+        for (secs, service, text, level) in [
+            (10, "api", "listening", LogLevel::Info),
+            (20, "api", "db timeout", LogLevel::Error),
+            (30, "worker", "job failed", LogLevel::Error),
+            (40, "api", "request failed", LogLevel::Error),
+        ] {
+            let line = LogLine {
+                level: Some(level),
+                ..file_line(secs, service, text)
+            };
+            serde_json::to_writer(&mut fixture, &line).unwrap();
+            fixture.extend_from_slice(b"\nnot json\n\n");
+        }
+
+        let mut filter = LogFilter::new();
+        filter.services = vec!["api".to_string()];
+        filter.min_level = Some(LogLevel::Error);
+        let lines = read_matching_lines(&mut fixture.as_slice(), &filter, None).unwrap();
+        assert_eq!(lines.len(), 2);
+        assert!(count_passes(lines.len(), false));
+        assert!(!count_passes(lines.len(), true));
+
+        filter.services = vec!["web".to_string()];
+        let lines = read_matching_lines(&mut fixture.as_slice(), &filter, None).unwrap();
+        assert_eq!(lines.len(), 0);
+        assert!(!count_passes(lines.len(), false));
+        assert!(count_passes(lines.len(), true));
+    }
+
+    #[test]
+    fn tail_follows_appends_and_reattaches_after_truncation() {
+        use std::io::Write as _;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("current.jsonl");
+        std::fs::write(&path, "old 1\nold 2\n").unwrap();
+
+        let mut reader = BufReader::new(std::fs::File::open(&path).unwrap());
+        let existing = read_matching_lines(&mut reader, &LogFilter::new(), None).unwrap();
+        assert!(existing.is_empty(), "plain text lines are skipped");
+        let mut tail = LogTail::new(&path, reader).unwrap();
+        assert_eq!(tail.next_line().unwrap(), None);
+
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        write!(file, "new 1\nnew ").unwrap();
+        assert_eq!(tail.next_line().unwrap().as_deref(), Some("new 1\n"));
+        assert_eq!(tail.next_line().unwrap(), None);
+        writeln!(file, "2").unwrap();
+        assert_eq!(tail.next_line().unwrap().as_deref(), Some("new 2\n"));
+
+        // A new `devrig start` truncates the file and writes fresh lines.
+        std::fs::write(&path, "run 2\n").unwrap();
+        assert_eq!(tail.next_line().unwrap(), None);
+        assert_eq!(tail.next_line().unwrap().as_deref(), Some("run 2\n"));
+
+        // Replaced outright (new inode) with a longer file.
+        let replacement = dir.path().join("current.jsonl.new");
+        std::fs::write(&replacement, "run 3 with a much longer first line\n").unwrap();
+        std::fs::rename(&replacement, &path).unwrap();
+        assert_eq!(tail.next_line().unwrap(), None);
+        assert_eq!(
+            tail.next_line().unwrap().as_deref(),
+            Some("run 3 with a much longer first line\n")
+        );
+    }
+
+    #[test]
+    fn load_phases_without_state_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(load_phases(dir.path()).is_empty());
+    }
+
+    #[test]
+    fn export_bundle_masks_secrets_and_writes_metadata() {
+        use std::io::Read;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bundle.zip");
+        let mut secrets = SecretRegistry::new();
+        secrets.track("hunter2");
+        let lines = vec![
+            file_line(10, "api", "connecting with password=hunter2"),
+            file_line(20, "api", "ready"),
+        ];
+        let metadata = BundleMetadata {
+            project: "myapp".to_string(),
+            devrig_version: env!("CARGO_PKG_VERSION"),
+            exported_at: Utc::now(),
+            from: Some(lines[0].timestamp),
+            to: Some(lines[1].timestamp),
+            services: vec!["api".to_string()],
+            line_count: lines.len(),
+        };
+        write_bundle(&path, &lines, &secrets, &metadata).unwrap();
+
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(&path).unwrap()).unwrap();
+        let mut logs = String::new();
+        archive
+            .by_name("logs.jsonl")
+            .unwrap()
+            .read_to_string(&mut logs)
+            .unwrap();
+        assert!(!logs.contains("hunter2"));
+        let first: LogLine = serde_json::from_str(logs.lines().next().unwrap()).unwrap();
+        assert_eq!(first.text, "connecting with password=****");
+        assert_eq!(logs.lines().count(), 2);
+
+        let mut meta = String::new();
+        archive
+            .by_name("metadata.json")
+            .unwrap()
+            .read_to_string(&mut meta)
+            .unwrap();
+        let meta: serde_json::Value = serde_json::from_str(&meta).unwrap();
+        assert_eq!(meta["project"], "myapp");
+        assert_eq!(meta["devrig_version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(meta["line_count"], 2);
+        assert!(meta["from"].is_string());
+        assert!(meta["to"].is_string());
+    }
+
+    #[test]
+    fn container_sources_resolve_docker_and_compose_names() {
+        let state: ProjectState = serde_json::from_value(serde_json::json!({
+            "slug": "test",
+            "config_path": "devrig.toml",
+            "services": {},
+            "started_at": "2026-03-01T12:00:00Z",
+            "docker": {
+                "postgres": {
+                    "container_id": "pg1",
+                    "container_name": "devrig-test-postgres",
+                    "port": 5432,
+                    "port_auto": false,
+                    "named_ports": {},
+                    "init_completed": true,
+                    "init_completed_at": null,
+                },
+            },
+            "compose_services": {
+                "redis": {
+                    "container_id": "rd1",
+                    "container_name": "test-redis-1",
+                    "port": null,
+                },
+            },
+        }))
+        .unwrap();
+
+        let all = container_sources(&state, &[]).unwrap();
+        assert_eq!(
+            all,
+            vec![
+                ("postgres".to_string(), "pg1".to_string()),
+                ("redis".to_string(), "rd1".to_string()),
+            ]
+        );
+        let picked = container_sources(&state, &["red*".to_string()]).unwrap();
+        assert_eq!(picked, vec![("redis".to_string(), "rd1".to_string())]);
+
+        let err = container_sources(&state, &["api".to_string()]).unwrap_err();
+        assert!(err.to_string().contains("available: postgres, redis"));
+    }
+
+    #[test]
+    fn container_lines_use_docker_timestamps() {
+        let output = LogOutput::StdErr {
+            message: "2026-03-01T12:00:00.250000000Z ERROR: relation missing\n\
+                      2026-03-01T12:00:01Z \n"
+                .into(),
+        };
+        let lines = container_lines("postgres", &output);
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].service, "postgres");
+        assert_eq!(lines[0].text, "ERROR: relation missing");
+        assert!(lines[0].is_stderr);
+        assert_eq!(lines[0].level, Some(LogLevel::Error));
+        assert_eq!(lines[0].timestamp.timestamp_millis(), 1772366400250);
+
+        let untimed = LogOutput::StdOut {
+            message: "ready to accept connections".into(),
+        };
+        let lines = container_lines("postgres", &untimed);
+        assert_eq!(lines[0].text, "ready to accept connections");
+        assert!(!lines[0].is_stderr);
+    }
+
+    #[test]
+    fn parse_size_units() {
+        assert_eq!(parse_size("512").unwrap(), 512);
+        assert_eq!(parse_size("10K").unwrap(), 10 * 1024);
+        assert_eq!(parse_size("10mb").unwrap(), 10 * 1024 * 1024);
+        assert_eq!(parse_size("1G").unwrap(), 1 << 30);
+        assert!(parse_size("0").is_err());
+        assert!(parse_size("ten").is_err());
+        let err = parse_size("99999999999999G").unwrap_err();
+        assert!(err.to_string().contains("too large"), "{err}");
+    }
+
+    #[test]
+    fn output_file_appends_and_rotates_on_line_boundaries() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run.log");
+        std::fs::write(&path, "old\n").unwrap();
+
+        let mut out = OutputFile::open(&path, Some(12)).unwrap();
+        out.write_all(b"first").unwrap();
+        out.write_all(b"\n").unwrap();
+        out.write_all(b"second\n").unwrap();
+        out.write_all(b"third\n").unwrap();
+        out.flush().unwrap();
+
+        // "old\nfirst\n" is 10 bytes, so "second" crosses the limit and rolls.
+        let rolled = std::fs::read_to_string(dir.path().join("run.log.1")).unwrap();
+        assert_eq!(rolled, "old\nfirst\nsecond\n");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "third\n");
+
+        for i in 0..6 {
+            writeln!(out, "line {} padding", i).unwrap();
+        }
+        out.flush().unwrap();
+        assert!(dir.path().join("run.log.5").exists());
+        assert!(!dir.path().join("run.log.6").exists());
+    }
 }
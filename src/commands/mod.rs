@@ -1,12 +1,22 @@
 pub mod cluster;
+pub mod config;
 pub mod doctor;
 pub mod env;
 pub mod exec;
 pub mod init;
 pub mod logs;
+pub mod open;
 pub mod ps;
 pub mod query;
+pub mod reload_env;
 pub mod reset;
+pub mod restart;
+pub mod scale;
+pub mod schema;
 pub mod skill;
+pub mod top;
 pub mod update;
 pub mod validate;
+pub mod version;
+pub mod wait;
+pub mod whoami;
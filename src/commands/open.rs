@@ -0,0 +1,141 @@
+use anyhow::{bail, Result};
+use std::path::Path;
+
+use crate::commands::ps::port_map;
+use crate::orchestrator::state::ProjectState;
+
+/// Open the dashboard, or the URL of the named resource, in the default
+/// browser. Ports come from the running project's state.json.
+pub fn run(config_path: Option<&Path>, name: Option<&str>) -> Result<()> {
+    let config_path = match config_path {
+        Some(p) => p.to_path_buf(),
+        None => crate::config::resolve::resolve_config(None)?,
+    };
+    let project_dir = config_path.parent().unwrap_or(Path::new("."));
+    let state_dir = ProjectState::state_dir_for(project_dir);
+
+    let running = std::fs::read_to_string(state_dir.join("pid"))
+        .ok()
+        .and_then(|pid| pid.trim().parse().ok())
+        .is_some_and(crate::platform::is_process_alive);
+    let state = match ProjectState::load(&state_dir) {
+        Some(state) if running => state,
+        _ => bail!("project is not running -- start it with `devrig start`"),
+    };
+
+    let url = target_url(&state, name)?;
+    println!("Opening {}", url);
+    crate::platform::open_url(&url)
+}
+
+/// The URL `devrig open [name]` opens. Resources without a web protocol
+/// are opened over `http://`.
+fn target_url(state: &ProjectState, name: Option<&str>) -> Result<String> {
+    let entries = port_map(state);
+    let name = name.unwrap_or("dashboard");
+    let Some(entry) = entries.iter().find(|e| e.name == name) else {
+        if name == "dashboard" {
+            bail!("the dashboard is not running (is [dashboard] enabled?)");
+        }
+        bail!(
+            "no port recorded for `{}` (available: {:?})",
+            name,
+            entries.iter().map(|e| e.name.as_str()).collect::<Vec<_>>()
+        );
+    };
+    if entry.url.contains("://") {
+        Ok(entry.url.clone())
+    } else {
+        Ok(format!("http://{}", entry.url))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orchestrator::state::{DashboardState, DockerState, ServiceState};
+    use chrono::Utc;
+    use std::collections::BTreeMap;
+
+    fn state() -> ProjectState {
+        let mut state = ProjectState {
+            slug: "test".to_string(),
+            config_path: "devrig.toml".to_string(),
+            services: BTreeMap::new(),
+            started_at: Utc::now(),
+            docker: BTreeMap::new(),
+            compose_services: BTreeMap::new(),
+            network_name: None,
+            cluster: None,
+            dashboard: None,
+        };
+        state.services.insert(
+            "web".to_string(),
+            ServiceState {
+                pid: 1,
+                port: Some(3000),
+                port_auto: false,
+                protocol: Some("https".to_string()),
+                phase: None,
+                exit_code: None,
+                ready: None,
+            },
+        );
+        state.docker.insert(
+            "mailpit".to_string(),
+            DockerState {
+                container_id: "abc".to_string(),
+                container_name: "devrig-test-mailpit".to_string(),
+                port: Some(8025),
+                port_auto: true,
+                protocol: None,
+                named_ports: BTreeMap::new(),
+                init_completed: false,
+                init_completed_at: None,
+                init_checksum: None,
+                image_digest: None,
+                phase: None,
+                health: None,
+                stop_timeout: None,
+            },
+        );
+        state
+    }
+
+    #[test]
+    fn opens_the_dashboard_by_default() {
+        let mut state = state();
+        let err = target_url(&state, None).unwrap_err();
+        assert!(
+            err.to_string().contains("dashboard is not running"),
+            "{err}"
+        );
+
+        state.dashboard = Some(DashboardState {
+            dashboard_port: 4000,
+            grpc_port: 4317,
+            http_port: 4318,
+            tls_cert: None,
+            otel_tls: false,
+        });
+        assert_eq!(target_url(&state, None).unwrap(), "http://localhost:4000");
+    }
+
+    #[test]
+    fn opens_a_resource_by_name() {
+        let state = state();
+        assert_eq!(
+            target_url(&state, Some("web")).unwrap(),
+            "https://localhost:3000"
+        );
+        assert_eq!(
+            target_url(&state, Some("mailpit")).unwrap(),
+            "http://localhost:8025"
+        );
+        let err = target_url(&state, Some("nope")).unwrap_err();
+        assert!(
+            err.to_string().contains("no port recorded for `nope`"),
+            "{err}"
+        );
+    }
+}
@@ -1,17 +1,63 @@
 use crate::orchestrator::registry::InstanceRegistry;
-use crate::orchestrator::state::ProjectState;
-use anyhow::Result;
+use crate::orchestrator::state::{DockerState, ProjectState, ServiceState};
+use crate::query::output::OutputFormat;
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
 use std::path::Path;
 
-pub fn run(config_path: Option<&Path>, all: bool) -> Result<()> {
-    if all {
-        run_all()
-    } else {
-        run_local(config_path)
+/// One resource of a project, as emitted by `devrig ps --format json`.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct PsResource {
+    pub name: String,
+    /// `service`, `docker`, `compose`, `cluster`, `addon`, or `dashboard`.
+    pub kind: &'static str,
+    pub port: Option<u16>,
+    pub port_auto: bool,
+    pub status: String,
+    pub pid: Option<u32>,
+    pub exit_code: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub container_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_digest: Option<String>,
+    /// Docker HEALTHCHECK status, for containers that have one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub health: Option<String>,
+}
+
+/// One registered instance, as emitted by `devrig ps --all --format json`.
+#[derive(Debug, Serialize)]
+pub struct PsInstance {
+    pub slug: String,
+    pub config_path: String,
+    pub state_dir: String,
+    pub started_at: DateTime<Utc>,
+    pub resources: Vec<PsResource>,
+}
+
+/// One entry of `devrig ps --ports`.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct PortEntry {
+    /// Resource name; docker named ports are `<docker>:<port name>`.
+    pub name: String,
+    /// Same kinds as [`PsResource::kind`].
+    pub kind: &'static str,
+    pub port: u16,
+    /// `http(s)://localhost:<port>` for web ports, `localhost:<port>` otherwise.
+    pub url: String,
+}
+
+pub fn run(config_path: Option<&Path>, all: bool, format: OutputFormat) -> Result<()> {
+    match (all, format) {
+        (true, OutputFormat::Table) => run_all(),
+        (true, _) => run_all_json(),
+        (false, OutputFormat::Table) => run_local(config_path),
+        (false, _) => run_local_json(config_path),
     }
 }
 
-fn run_local(config_path: Option<&Path>) -> Result<()> {
+fn load_local_state(config_path: Option<&Path>) -> Result<Option<ProjectState>> {
     // Resolve config path to find state dir
     let config_path = match config_path {
         Some(p) => p.to_path_buf(),
@@ -19,8 +65,286 @@ fn run_local(config_path: Option<&Path>) -> Result<()> {
     };
     let project_dir = config_path.parent().unwrap_or(Path::new("."));
     let state_dir = ProjectState::state_dir_for(project_dir);
+    Ok(ProjectState::load(&state_dir))
+}
+
+/// `devrig ps --ports [name]`: the port map, or just one resource's port.
+pub fn run_ports(
+    config_path: Option<&Path>,
+    name: Option<&str>,
+    format: OutputFormat,
+) -> Result<()> {
+    let entries = load_local_state(config_path)?
+        .map(|s| port_map(&s))
+        .unwrap_or_default();
+
+    if let Some(name) = name {
+        let Some(entry) = entries.iter().find(|e| e.name == name) else {
+            bail!("no port recorded for `{}` (is it running?)", name);
+        };
+        match format {
+            OutputFormat::Table => println!("{}", entry.port),
+            _ => println!("{}", serde_json::to_string_pretty(entry)?),
+        }
+        return Ok(());
+    }
+
+    match format {
+        OutputFormat::Table if entries.is_empty() => {
+            println!("No running services found.");
+            println!("Run `devrig start` to start services.");
+        }
+        OutputFormat::Table => {
+            let width = entries.iter().map(|e| e.name.len()).max().unwrap_or(0);
+            for entry in &entries {
+                println!("{:<width$} -> {} ({})", entry.name, entry.port, entry.url);
+            }
+        }
+        _ => println!("{}", serde_json::to_string_pretty(&entries)?),
+    }
+    Ok(())
+}
+
+/// Every resource with a recorded port, in the same order as
+/// [`project_resources`], with docker named ports after their container.
+pub fn port_map(state: &ProjectState) -> Vec<PortEntry> {
+    fn entry(name: String, kind: &'static str, port: u16, protocol: Option<&str>) -> PortEntry {
+        let url = match protocol {
+            Some(scheme @ ("http" | "https")) => format!("{}://localhost:{}", scheme, port),
+            _ => format!("localhost:{}", port),
+        };
+        PortEntry {
+            name,
+            kind,
+            port,
+            url,
+        }
+    }
+
+    let mut entries = Vec::new();
+
+    for (name, docker) in &state.docker {
+        if let Some(port) = docker.port {
+            entries.push(entry(
+                name.clone(),
+                "docker",
+                port,
+                docker.protocol.as_deref(),
+            ));
+        }
+        for (port_name, &port) in &docker.named_ports {
+            entries.push(entry(
+                format!("{}:{}", name, port_name),
+                "docker",
+                port,
+                None,
+            ));
+        }
+    }
+
+    for (name, cs) in &state.compose_services {
+        if let Some(port) = cs.port {
+            entries.push(entry(name.clone(), "compose", port, None));
+        }
+    }
+
+    if let Some(ref dash) = state.dashboard {
+        entries.push(entry(
+            "dashboard".to_string(),
+            "dashboard",
+            dash.dashboard_port,
+            Some(dash.scheme()),
+        ));
+        entries.push(entry(
+            "otel-grpc".to_string(),
+            "dashboard",
+            dash.grpc_port,
+            None,
+        ));
+        entries.push(entry(
+            "otel-http".to_string(),
+            "dashboard",
+            dash.http_port,
+            None,
+        ));
+    }
+
+    // Services are web ports unless they say otherwise.
+    for (name, svc) in &state.services {
+        if let Some(port) = svc.port {
+            let protocol = svc.protocol.as_deref().unwrap_or("http");
+            entries.push(entry(name.clone(), "service", port, Some(protocol)));
+        }
+    }
+
+    entries
+}
+
+fn run_local_json(config_path: Option<&Path>) -> Result<()> {
+    let resources = load_local_state(config_path)?
+        .map(|s| project_resources(&s))
+        .unwrap_or_default();
+    println!("{}", serde_json::to_string_pretty(&resources)?);
+    Ok(())
+}
+
+fn run_all_json() -> Result<()> {
+    let mut registry = InstanceRegistry::load();
+    registry.cleanup();
+    let _ = registry.save();
+
+    let instances: Vec<PsInstance> = registry
+        .list()
+        .iter()
+        .map(|entry| PsInstance {
+            slug: entry.slug.clone(),
+            config_path: entry.config_path.clone(),
+            state_dir: entry.state_dir.clone(),
+            started_at: entry.started_at,
+            resources: ProjectState::load(Path::new(&entry.state_dir))
+                .map(|s| project_resources(&s))
+                .unwrap_or_default(),
+        })
+        .collect();
+    println!("{}", serde_json::to_string_pretty(&instances)?);
+    Ok(())
+}
+
+/// Flatten a project's state into one entry per resource, in the same
+/// order as the table output.
+pub fn project_resources(state: &ProjectState) -> Vec<PsResource> {
+    let mut resources = Vec::new();
+
+    for (name, docker) in &state.docker {
+        resources.push(PsResource {
+            name: name.clone(),
+            kind: "docker",
+            port: docker.port,
+            port_auto: docker.port_auto,
+            status: docker_status(docker),
+            pid: None,
+            exit_code: None,
+            container_id: Some(docker.container_id.clone()),
+            image_digest: docker.image_digest.clone(),
+            health: docker.health.clone(),
+        });
+    }
+
+    for (name, cs) in &state.compose_services {
+        resources.push(PsResource {
+            name: name.clone(),
+            kind: "compose",
+            port: cs.port,
+            port_auto: false,
+            status: "running".to_string(),
+            pid: None,
+            exit_code: None,
+            container_id: Some(cs.container_id.clone()),
+            image_digest: None,
+            health: None,
+        });
+    }
+
+    if let Some(ref cluster) = state.cluster {
+        for name in cluster.deployed_services.keys() {
+            resources.push(PsResource {
+                name: name.clone(),
+                kind: "cluster",
+                port: None,
+                port_auto: false,
+                status: "deployed".to_string(),
+                pid: None,
+                exit_code: None,
+                container_id: None,
+                image_digest: None,
+                health: None,
+            });
+        }
+        for (name, addon) in &cluster.installed_addons {
+            let pf = addon.port_forwards.first();
+            resources.push(PsResource {
+                name: name.clone(),
+                kind: "addon",
+                port: pf.map(|f| f.local_port),
+                port_auto: pf.is_some_and(|f| f.port_auto),
+                status: "installed".to_string(),
+                pid: None,
+                exit_code: None,
+                container_id: None,
+                image_digest: None,
+                health: None,
+            });
+        }
+    }
+
+    if let Some(ref dash) = state.dashboard {
+        for (name, port) in [
+            ("dashboard", dash.dashboard_port),
+            ("otel-grpc", dash.grpc_port),
+            ("otel-http", dash.http_port),
+        ] {
+            resources.push(PsResource {
+                name: name.to_string(),
+                kind: "dashboard",
+                port: Some(port),
+                port_auto: false,
+                status: "running".to_string(),
+                pid: None,
+                exit_code: None,
+                container_id: None,
+                image_digest: None,
+                health: None,
+            });
+        }
+    }
+
+    for (name, svc) in &state.services {
+        resources.push(PsResource {
+            name: name.clone(),
+            kind: "service",
+            port: svc.port,
+            port_auto: svc.port_auto,
+            status: service_status(svc, is_process_alive(svc.pid)),
+            pid: (svc.pid != 0).then_some(svc.pid),
+            exit_code: svc.exit_code,
+            container_id: None,
+            image_digest: None,
+            health: None,
+        });
+    }
+
+    resources
+}
+
+/// Human status for a docker container: its watcher phase, with the
+/// HEALTHCHECK status appended while it runs, e.g. `running (starting)`.
+fn docker_status(docker: &DockerState) -> String {
+    let phase = docker.phase.as_deref().unwrap_or("running");
+    match docker.health.as_deref() {
+        Some(health) if phase == "running" => format!("{} ({})", phase, health),
+        _ => phase.to_string(),
+    }
+}
+
+/// Human status for a service given whether its recorded PID is alive.
+fn service_status(svc: &ServiceState, alive: bool) -> String {
+    let phase = svc.phase.as_deref().unwrap_or("");
+    if alive {
+        if phase.is_empty() { "running".to_string() } else { phase.to_string() }
+    } else if phase == "failed" {
+        match svc.exit_code {
+            Some(code) => format!("failed (exit {})", code),
+            None => "failed".to_string(),
+        }
+    } else if phase == "running" || phase == "starting" {
+        "stopped (stale)".to_string()
+    } else {
+        "stopped".to_string()
+    }
+}
 
-    let state = match ProjectState::load(&state_dir) {
+fn run_local(config_path: Option<&Path>) -> Result<()> {
+    let state = match load_local_state(config_path)? {
         Some(s) => s,
         None => {
             println!("No running services found.");
@@ -53,10 +377,11 @@ fn run_local(config_path: Option<&Path>) -> Result<()> {
             };
             let init_tag = if docker_svc.init_completed { " [init]" } else { "" };
             println!(
-                "  {:<20} {:<14} {:<24} running{}",
+                "  {:<20} {:<14} {:<24} {}{}",
                 name,
                 short_id,
                 format!("{}{}", url, auto_tag),
+                docker_status(docker_svc),
                 init_tag,
             );
         }
@@ -90,8 +415,8 @@ fn run_local(config_path: Option<&Path>) -> Result<()> {
         println!("  {:<20} {:<24}", "DASHBOARD", "URL");
         println!("  {}", "-".repeat(48));
         println!(
-            "  {:<20} http://localhost:{}",
-            "dashboard", dash.dashboard_port
+            "  {:<20} {}://localhost:{}",
+            "dashboard", dash.scheme(), dash.dashboard_port
         );
         println!(
             "  {:<20} localhost:{}",
@@ -114,20 +439,7 @@ fn run_local(config_path: Option<&Path>) -> Result<()> {
                 .map(|p| format!("http://localhost:{}", p))
                 .unwrap_or_else(|| "-".to_string());
             let auto_tag = if svc.port_auto { " (auto)" } else { "" };
-            let alive = is_process_alive(svc.pid);
-            let phase = svc.phase.as_deref().unwrap_or("");
-            let status = if alive {
-                if phase.is_empty() { "running".to_string() } else { phase.to_string() }
-            } else if phase == "failed" {
-                match svc.exit_code {
-                    Some(code) => format!("failed (exit {})", code),
-                    None => "failed".to_string(),
-                }
-            } else if phase == "running" || phase == "starting" {
-                "stopped (stale)".to_string()
-            } else {
-                "stopped".to_string()
-            };
+            let status = service_status(svc, is_process_alive(svc.pid));
             let pid_display = if svc.pid == 0 {
                 "-".to_string()
             } else {
@@ -204,7 +516,9 @@ pub fn build_status_parts(state: &ProjectState) -> Vec<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::orchestrator::state::{DashboardState, ServiceState};
+    use crate::orchestrator::state::{
+        ComposeServiceState, DashboardState, DockerState, ServiceState,
+    };
     use chrono::Utc;
     use std::collections::BTreeMap;
 
@@ -229,6 +543,8 @@ mod tests {
             dashboard_port: 4000,
             grpc_port: 4317,
             http_port: 4318,
+            tls_cert: None,
+            otel_tls: false,
         });
         assert_eq!(build_status_parts(&state), vec!["dashboard"]);
     }
@@ -245,19 +561,221 @@ mod tests {
                 protocol: None,
                 phase: None,
                 exit_code: None,
+                ready: None,
             },
         );
         state.dashboard = Some(DashboardState {
             dashboard_port: 4000,
             grpc_port: 4317,
             http_port: 4318,
+            tls_cert: None,
+            otel_tls: false,
         });
         assert_eq!(build_status_parts(&state), vec!["1 svc", "dashboard"]);
     }
 
+    #[test]
+    fn json_resources_cover_each_kind() {
+        let mut state = empty_state();
+        state.services.insert(
+            "api".to_string(),
+            ServiceState {
+                pid: 0,
+                port: Some(3000),
+                port_auto: true,
+                protocol: None,
+                phase: Some("failed".to_string()),
+                exit_code: Some(1),
+                ready: None,
+            },
+        );
+        state.docker.insert(
+            "postgres".to_string(),
+            DockerState {
+                container_id: "abc".to_string(),
+                container_name: "devrig-test-postgres".to_string(),
+                port: Some(5432),
+                port_auto: false,
+                protocol: None,
+                named_ports: BTreeMap::new(),
+                init_completed: true,
+                init_completed_at: None,
+                init_checksum: None,
+                image_digest: Some("sha256:1234".to_string()),
+                phase: None,
+                stop_timeout: None,
+                health: None,
+            },
+        );
+
+        let resources = project_resources(&state);
+        assert_eq!(resources.len(), 2);
+
+        let pg = &resources[0];
+        assert_eq!((pg.name.as_str(), pg.kind), ("postgres", "docker"));
+        assert_eq!(pg.port, Some(5432));
+        assert_eq!(pg.status, "running");
+        assert_eq!(pg.image_digest.as_deref(), Some("sha256:1234"));
+
+        let api = &resources[1];
+        assert_eq!((api.name.as_str(), api.kind), ("api", "service"));
+        assert!(api.port_auto);
+        assert_eq!(api.pid, None);
+        assert_eq!(api.status, "failed (exit 1)");
+        assert_eq!(api.exit_code, Some(1));
+
+        let json = serde_json::to_value(&resources).unwrap();
+        assert_eq!(json[1]["kind"], "service");
+        assert!(json[1].get("container_id").is_none());
+    }
+
+    #[test]
+    fn docker_status_includes_health_while_running() {
+        let docker = |phase: Option<&str>, health: Option<&str>| -> DockerState {
+            serde_json::from_value(serde_json::json!({
+                "container_id": "abc",
+                "container_name": "devrig-test-postgres",
+                "port": 5432,
+                "port_auto": false,
+                "named_ports": {},
+                "init_completed": false,
+                "init_completed_at": null,
+                "phase": phase,
+                "health": health,
+            }))
+            .unwrap()
+        };
+
+        assert_eq!(docker_status(&docker(None, None)), "running");
+        assert_eq!(
+            docker_status(&docker(None, Some("starting"))),
+            "running (starting)"
+        );
+        assert_eq!(
+            docker_status(&docker(Some("running"), Some("healthy"))),
+            "running (healthy)"
+        );
+        assert_eq!(
+            docker_status(&docker(Some("unhealthy"), Some("unhealthy"))),
+            "unhealthy"
+        );
+        assert_eq!(
+            docker_status(&docker(Some("exited"), Some("healthy"))),
+            "exited"
+        );
+    }
+
     #[test]
     fn no_dashboard_no_services_is_empty() {
         let state = empty_state();
         assert!(build_status_parts(&state).is_empty());
     }
+
+    #[test]
+    fn port_map_matches_recorded_state() {
+        let mut state = empty_state();
+        state.services.insert(
+            "api".to_string(),
+            ServiceState {
+                pid: 0,
+                port: Some(3000),
+                port_auto: true,
+                protocol: None,
+                phase: None,
+                exit_code: None,
+                ready: None,
+            },
+        );
+        state.services.insert(
+            "grpc".to_string(),
+            ServiceState {
+                pid: 0,
+                port: Some(50051),
+                port_auto: false,
+                protocol: Some("tcp".to_string()),
+                phase: None,
+                exit_code: None,
+                ready: None,
+            },
+        );
+        state.services.insert(
+            "worker".to_string(),
+            ServiceState {
+                pid: 0,
+                port: None,
+                port_auto: false,
+                protocol: None,
+                phase: None,
+                exit_code: None,
+                ready: None,
+            },
+        );
+        state.docker.insert(
+            "minio".to_string(),
+            DockerState {
+                container_id: "abc".to_string(),
+                container_name: "devrig-test-minio".to_string(),
+                port: None,
+                port_auto: false,
+                protocol: None,
+                named_ports: BTreeMap::from([
+                    ("api".to_string(), 9000),
+                    ("console".to_string(), 9001),
+                ]),
+                init_completed: false,
+                init_completed_at: None,
+                init_checksum: None,
+                image_digest: None,
+                phase: None,
+                stop_timeout: None,
+                health: None,
+            },
+        );
+        state.compose_services.insert(
+            "redis".to_string(),
+            ComposeServiceState {
+                container_id: "def".to_string(),
+                container_name: "redis-1".to_string(),
+                port: Some(6379),
+            },
+        );
+        state.dashboard = Some(DashboardState {
+            dashboard_port: 4000,
+            grpc_port: 4317,
+            http_port: 4318,
+            tls_cert: None,
+            otel_tls: false,
+        });
+
+        let map: Vec<(String, &str, u16, String)> = port_map(&state)
+            .into_iter()
+            .map(|e| (e.name, e.kind, e.port, e.url))
+            .collect();
+        let expected = [
+            ("minio:api", "docker", 9000, "localhost:9000"),
+            ("minio:console", "docker", 9001, "localhost:9001"),
+            ("redis", "compose", 6379, "localhost:6379"),
+            ("dashboard", "dashboard", 4000, "http://localhost:4000"),
+            ("otel-grpc", "dashboard", 4317, "localhost:4317"),
+            ("otel-http", "dashboard", 4318, "localhost:4318"),
+            ("api", "service", 3000, "http://localhost:3000"),
+            ("grpc", "service", 50051, "localhost:50051"),
+        ];
+        assert_eq!(
+            map,
+            expected
+                .iter()
+                .map(|&(n, k, p, u)| (n.to_string(), k, p, u.to_string()))
+                .collect::<Vec<_>>()
+        );
+
+        // Entries line up one-to-one with the ports templates resolve against.
+        let recorded = state.resolved_ports();
+        let listed: std::collections::HashMap<String, u16> = port_map(&state)
+            .into_iter()
+            .filter(|e| e.kind != "dashboard")
+            .map(|e| (format!("{}:{}", e.kind, e.name), e.port))
+            .collect();
+        assert_eq!(listed, recorded);
+    }
 }
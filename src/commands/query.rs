@@ -1,16 +1,55 @@
 use anyhow::{bail, Context, Result};
-use reqwest::Client;
+use chrono::{DateTime, SecondsFormat, Utc};
+use reqwest::{Client, Url};
 
+use crate::config::load_config;
 use crate::config::resolve::resolve_config;
-use crate::orchestrator::state::ProjectState;
-use crate::otel::query::{RelatedTelemetry, SystemStatus, TraceDetail, TraceSummary};
+use crate::orchestrator::state::{DashboardState, ProjectState};
+use crate::otel::query::{
+    time_window, LogQuery, MetricQuery, RelatedTelemetry, SystemStatus, TimeWindow, TraceDetail,
+    TraceQuery, TraceSummary,
+};
+use crate::otel::storage::TelemetryStore;
 use crate::otel::types::{StoredLog, StoredMetric};
+use crate::otel::wal;
 use crate::query::output::{self, OutputFormat};
 
-use std::path::Path;
+use std::net::{SocketAddr, TcpStream};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-/// Resolve dashboard HTTP base URL from project state.
-fn dashboard_url(config_path: Option<&Path>) -> Result<String> {
+/// A running dashboard's API, as recorded in project state.
+pub(crate) struct DashboardApi {
+    pub base_url: String,
+    /// Certificate the dashboard serves, when `[dashboard.tls]` is set.
+    pub tls_cert: Option<PathBuf>,
+}
+
+impl DashboardApi {
+    fn from_state(dash: &DashboardState) -> Self {
+        Self {
+            base_url: format!("{}://localhost:{}", dash.scheme(), dash.dashboard_port),
+            tls_cert: dash.tls_cert.clone(),
+        }
+    }
+
+    /// HTTP client for the dashboard API. With `[dashboard.tls]` it trusts
+    /// exactly the certificate the dashboard serves.
+    pub(crate) fn client(&self) -> Result<Client> {
+        let mut builder = Client::builder();
+        if let Some(path) = &self.tls_cert {
+            let pem = std::fs::read(path)
+                .with_context(|| format!("reading dashboard certificate {}", path.display()))?;
+            let tls = crate::tls::pinned_client_config(&pem)
+                .with_context(|| format!("loading dashboard certificate {}", path.display()))?;
+            builder = builder.use_preconfigured_tls(tls);
+        }
+        builder.build().context("building dashboard HTTP client")
+    }
+}
+
+/// Resolve the dashboard API from project state.
+pub(crate) fn dashboard_api(config_path: Option<&Path>) -> Result<DashboardApi> {
     let config_path = match config_path {
         Some(p) => p.to_path_buf(),
         None => resolve_config(None)?,
@@ -25,33 +64,132 @@ fn dashboard_url(config_path: Option<&Path>) -> Result<String> {
         .dashboard
         .ok_or_else(|| anyhow::anyhow!("dashboard is not enabled in this project"))?;
 
-    Ok(format!("http://localhost:{}", dash.dashboard_port))
+    Ok(DashboardApi::from_state(&dash))
+}
+
+/// Where `devrig query` reads telemetry from.
+enum TelemetrySource {
+    /// A running dashboard's API.
+    Live(DashboardApi),
+    /// Telemetry persisted by an earlier run, loaded from the WAL.
+    Persisted(Box<TelemetryStore>),
+}
+
+/// Use the running dashboard if it answers, otherwise the telemetry WAL
+/// (`dashboard.otel.wal`) if there is one. Without either, fail the way
+/// [`dashboard_api`] does.
+fn telemetry_source(config_path: Option<&Path>) -> Result<TelemetrySource> {
+    let config_path = match config_path {
+        Some(p) => p.to_path_buf(),
+        None => resolve_config(None)?,
+    };
+    let project_dir = config_path.parent().unwrap_or(Path::new("."));
+    let state_dir = ProjectState::state_dir_for(project_dir);
+
+    if let Some(dash) = ProjectState::load(&state_dir).and_then(|s| s.dashboard) {
+        let addr = SocketAddr::from(([127, 0, 0, 1], dash.dashboard_port));
+        if TcpStream::connect_timeout(&addr, Duration::from_millis(500)).is_ok() {
+            return Ok(TelemetrySource::Live(DashboardApi::from_state(&dash)));
+        }
+    }
+
+    let wal_dir = wal::wal_dir(&state_dir);
+    if !wal_dir.is_dir() {
+        return dashboard_api(Some(&config_path)).map(TelemetrySource::Live);
+    }
+    let otel_config = load_config(&config_path)
+        .ok()
+        .and_then(|(config, _)| config.dashboard.and_then(|d| d.otel))
+        .unwrap_or_default();
+    let mut store = crate::otel::new_store(&otel_config);
+    wal::replay(&wal_dir, &mut store);
+    eprintln!(
+        "devrig is not running; reading telemetry persisted in {}",
+        wal_dir.display()
+    );
+    Ok(TelemetrySource::Persisted(Box::new(store)))
+}
+
+/// The absolute window for `--last` or `--from`/`--to`, resolved against
+/// this machine's clock so live and persisted telemetry agree.
+fn resolve_window(
+    last: Option<&str>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+) -> Result<TimeWindow> {
+    time_window(last, from, to, Utc::now()).map_err(|e| anyhow::anyhow!(e))
+}
+
+/// A dashboard API URL under `base_url`. Each of `segments` is one
+/// percent-encoded path segment, so ids with `/` or `?` stay intact.
+pub(crate) fn api_url(base_url: &str, segments: &[&str]) -> Result<Url> {
+    let mut url =
+        Url::parse(base_url).with_context(|| format!("invalid dashboard URL '{}'", base_url))?;
+    url.path_segments_mut()
+        .map_err(|_| anyhow::anyhow!("invalid dashboard URL '{}'", base_url))?
+        .pop_if_empty()
+        .push("api")
+        .extend(segments);
+    Ok(url)
 }
 
+/// Append a query param when `value` is set.
+pub(crate) fn push_param(url: &mut Url, key: &str, value: Option<impl ToString>) {
+    if let Some(value) = value {
+        url.query_pairs_mut().append_pair(key, &value.to_string());
+    }
+}
+
+/// Append `since`/`until` query params for an absolute window.
+fn push_time_range(url: &mut Url, from: Option<DateTime<Utc>>, to: Option<DateTime<Utc>>) {
+    let rfc3339 = |t: DateTime<Utc>| t.to_rfc3339_opts(SecondsFormat::AutoSi, true);
+    push_param(url, "since", from.map(rfc3339));
+    push_param(url, "until", to.map(rfc3339));
+}
+
+#[allow(clippy::too_many_arguments)]
 pub async fn run_traces(
     config_path: Option<&Path>,
     service: Option<String>,
     status: Option<String>,
     min_duration: Option<u64>,
+    root_only: bool,
+    last: Option<String>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
     limit: usize,
     output: Option<String>,
 ) -> Result<()> {
-    let base_url = dashboard_url(config_path)?;
-    let client = Client::new();
+    let (from, to) = resolve_window(last.as_deref(), from, to)?;
+    let api = match telemetry_source(config_path)? {
+        TelemetrySource::Live(api) => api,
+        TelemetrySource::Persisted(store) => {
+            let traces = store.query_traces(&TraceQuery {
+                service,
+                status,
+                min_duration_ms: min_duration,
+                since: from,
+                until: to,
+                limit: Some(limit),
+                root_only,
+                ..Default::default()
+            });
+            output::print_traces(&traces, OutputFormat::from_str_opt(output.as_deref()));
+            return Ok(());
+        }
+    };
+    let client = api.client()?;
 
-    let mut url = format!("{}/api/traces?limit={}", base_url, limit);
-    if let Some(ref svc) = service {
-        url.push_str(&format!("&service={}", svc));
-    }
-    if let Some(ref s) = status {
-        url.push_str(&format!("&status={}", s));
-    }
-    if let Some(d) = min_duration {
-        url.push_str(&format!("&min_duration_ms={}", d));
-    }
+    let mut url = api_url(&api.base_url, &["traces"])?;
+    push_param(&mut url, "limit", Some(limit));
+    push_param(&mut url, "root_only", root_only.then_some(true));
+    push_param(&mut url, "service", service);
+    push_param(&mut url, "status", status);
+    push_param(&mut url, "min_duration_ms", min_duration);
+    push_time_range(&mut url, from, to);
 
     let resp = client
-        .get(&url)
+        .get(url)
         .send()
         .await
         .context("connecting to dashboard API")?;
@@ -71,12 +209,21 @@ pub async fn run_trace_detail(
     trace_id: String,
     output: Option<String>,
 ) -> Result<()> {
-    let base_url = dashboard_url(config_path)?;
-    let client = Client::new();
+    let api = match telemetry_source(config_path)? {
+        TelemetrySource::Live(api) => api,
+        TelemetrySource::Persisted(store) => {
+            let Some(detail) = store.get_trace(&trace_id) else {
+                bail!("trace '{}' not found", trace_id);
+            };
+            output::print_trace_detail(&detail, OutputFormat::from_str_opt(output.as_deref()));
+            return Ok(());
+        }
+    };
+    let client = api.client()?;
 
-    let url = format!("{}/api/traces/{}", base_url, trace_id);
+    let url = api_url(&api.base_url, &["traces", &trace_id])?;
     let resp = client
-        .get(&url)
+        .get(url)
         .send()
         .await
         .context("connecting to dashboard API")?;
@@ -90,38 +237,55 @@ pub async fn run_trace_detail(
 
     let detail: TraceDetail = resp.json().await.context("parsing trace detail")?;
     let format = OutputFormat::from_str_opt(output.as_deref());
-    output::print_spans(&detail.spans, format);
+    output::print_trace_detail(&detail, format);
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn run_logs(
     config_path: Option<&Path>,
     service: Option<String>,
     severity: Option<String>,
     search: Option<String>,
     trace_id: Option<String>,
+    last: Option<String>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
     limit: usize,
     output: Option<String>,
+    include_attributes: bool,
 ) -> Result<()> {
-    let base_url = dashboard_url(config_path)?;
-    let client = Client::new();
+    let (from, to) = resolve_window(last.as_deref(), from, to)?;
+    let api = match telemetry_source(config_path)? {
+        TelemetrySource::Live(api) => api,
+        TelemetrySource::Persisted(store) => {
+            let logs = store.query_logs(&LogQuery {
+                service,
+                severity,
+                search,
+                trace_id,
+                since: from,
+                until: to,
+                limit: Some(limit),
+                ..Default::default()
+            });
+            let format = OutputFormat::from_str_opt(output.as_deref());
+            output::print_logs(&logs, format, include_attributes);
+            return Ok(());
+        }
+    };
+    let client = api.client()?;
 
-    let mut url = format!("{}/api/logs?limit={}", base_url, limit);
-    if let Some(ref svc) = service {
-        url.push_str(&format!("&service={}", svc));
-    }
-    if let Some(ref sev) = severity {
-        url.push_str(&format!("&severity={}", sev));
-    }
-    if let Some(ref s) = search {
-        url.push_str(&format!("&search={}", s));
-    }
-    if let Some(ref tid) = trace_id {
-        url.push_str(&format!("&trace_id={}", tid));
-    }
+    let mut url = api_url(&api.base_url, &["logs"])?;
+    push_param(&mut url, "limit", Some(limit));
+    push_param(&mut url, "service", service);
+    push_param(&mut url, "severity", severity);
+    push_param(&mut url, "search", search);
+    push_param(&mut url, "trace_id", trace_id);
+    push_time_range(&mut url, from, to);
 
     let resp = client
-        .get(&url)
+        .get(url)
         .send()
         .await
         .context("connecting to dashboard API")?;
@@ -132,30 +296,47 @@ pub async fn run_logs(
 
     let logs: Vec<StoredLog> = resp.json().await.context("parsing log response")?;
     let format = OutputFormat::from_str_opt(output.as_deref());
-    output::print_logs(&logs, format);
+    output::print_logs(&logs, format, include_attributes);
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn run_metrics(
     config_path: Option<&Path>,
     name: Option<String>,
     service: Option<String>,
+    last: Option<String>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
     limit: usize,
     output: Option<String>,
 ) -> Result<()> {
-    let base_url = dashboard_url(config_path)?;
-    let client = Client::new();
+    let (from, to) = resolve_window(last.as_deref(), from, to)?;
+    let api = match telemetry_source(config_path)? {
+        TelemetrySource::Live(api) => api,
+        TelemetrySource::Persisted(store) => {
+            let metrics = store.query_metrics(&MetricQuery {
+                name,
+                service,
+                since: from,
+                until: to,
+                limit: Some(limit),
+                ..Default::default()
+            });
+            output::print_metrics(&metrics, OutputFormat::from_str_opt(output.as_deref()));
+            return Ok(());
+        }
+    };
+    let client = api.client()?;
 
-    let mut url = format!("{}/api/metrics?limit={}", base_url, limit);
-    if let Some(ref n) = name {
-        url.push_str(&format!("&name={}", n));
-    }
-    if let Some(ref svc) = service {
-        url.push_str(&format!("&service={}", svc));
-    }
+    let mut url = api_url(&api.base_url, &["metrics"])?;
+    push_param(&mut url, "limit", Some(limit));
+    push_param(&mut url, "name", name);
+    push_param(&mut url, "service", service);
+    push_time_range(&mut url, from, to);
 
     let resp = client
-        .get(&url)
+        .get(url)
         .send()
         .await
         .context("connecting to dashboard API")?;
@@ -171,12 +352,19 @@ pub async fn run_metrics(
 }
 
 pub async fn run_status(config_path: Option<&Path>, output: Option<String>) -> Result<()> {
-    let base_url = dashboard_url(config_path)?;
-    let client = Client::new();
+    let api = match telemetry_source(config_path)? {
+        TelemetrySource::Live(api) => api,
+        TelemetrySource::Persisted(store) => {
+            let status = store.get_status();
+            output::print_status(&status, OutputFormat::from_str_opt(output.as_deref()));
+            return Ok(());
+        }
+    };
+    let client = api.client()?;
 
-    let url = format!("{}/api/status", base_url);
+    let url = api_url(&api.base_url, &["status"])?;
     let resp = client
-        .get(&url)
+        .get(url)
         .send()
         .await
         .context("connecting to dashboard API")?;
@@ -196,12 +384,19 @@ pub async fn run_related(
     trace_id: String,
     output: Option<String>,
 ) -> Result<()> {
-    let base_url = dashboard_url(config_path)?;
-    let client = Client::new();
+    let api = match telemetry_source(config_path)? {
+        TelemetrySource::Live(api) => api,
+        TelemetrySource::Persisted(store) => {
+            let related = store.get_related(&trace_id);
+            output::print_related(&related, OutputFormat::from_str_opt(output.as_deref()));
+            return Ok(());
+        }
+    };
+    let client = api.client()?;
 
-    let url = format!("{}/api/traces/{}/related", base_url, trace_id);
+    let url = api_url(&api.base_url, &["traces", &trace_id, "related"])?;
     let resp = client
-        .get(&url)
+        .get(url)
         .send()
         .await
         .context("connecting to dashboard API")?;
@@ -215,3 +410,68 @@ pub async fn run_related(
     output::print_related(&related, format);
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::otel::types::{LogSeverity, StoredLog};
+
+    #[test]
+    fn api_url_percent_encodes_ids_and_params() {
+        let url = api_url("http://localhost:4000", &["traces", "a/b?c#d", "related"]).unwrap();
+        assert_eq!(
+            url.as_str(),
+            "http://localhost:4000/api/traces/a%2Fb%3Fc%23d/related"
+        );
+
+        let mut url = api_url("http://localhost:4000/", &["logs"]).unwrap();
+        push_param(&mut url, "trace_id", Some("x&limit=1"));
+        push_param(&mut url, "search", Some("a b+c"));
+        push_param(&mut url, "service", None::<String>);
+        assert_eq!(
+            url.as_str(),
+            "http://localhost:4000/api/logs?trace_id=x%26limit%3D1&search=a+b%2Bc"
+        );
+    }
+
+    #[test]
+    fn falls_back_to_persisted_telemetry_when_not_running() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("devrig.toml");
+        std::fs::write(
+            &config_path,
+            "[project]\nname = \"q\"\n\n[dashboard.otel]\nwal = true\n",
+        )
+        .unwrap();
+
+        // Nothing running and nothing persisted: the usual error.
+        let err = telemetry_source(Some(&config_path)).err().unwrap();
+        assert!(err.to_string().contains("no running project"), "{err}");
+
+        let wal_dir = wal::wal_dir(&ProjectState::state_dir_for(dir.path()));
+        let mut store = TelemetryStore::new(10, 10, 10, Duration::from_secs(3600));
+        store.attach_wal(wal::TelemetryWal::open(&wal_dir, 1 << 20).unwrap());
+        store.insert_log(StoredLog {
+            record_id: 0,
+            timestamp: Utc::now(),
+            service_name: "api".to_string(),
+            severity: LogSeverity::Error,
+            body: "boom".to_string(),
+            trace_id: None,
+            span_id: None,
+            attributes: vec![],
+        });
+        store.flush_wal();
+
+        match telemetry_source(Some(&config_path)).unwrap() {
+            TelemetrySource::Persisted(store) => {
+                let logs = store.query_logs(&LogQuery::default());
+                assert_eq!(logs.len(), 1);
+                assert_eq!(logs[0].body, "boom");
+            }
+            TelemetrySource::Live(api) => {
+                panic!("expected persisted telemetry, got {}", api.base_url)
+            }
+        }
+    }
+}
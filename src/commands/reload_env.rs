@@ -0,0 +1,45 @@
+use anyhow::{bail, Result};
+use std::path::Path;
+
+use crate::orchestrator::control::{self, ControlRequest};
+use crate::orchestrator::reload::ReloadAction;
+use crate::orchestrator::state::ProjectState;
+
+pub async fn run(config_path: Option<&Path>, services: Vec<String>) -> Result<()> {
+    let config_path = match config_path {
+        Some(p) => p.to_path_buf(),
+        None => crate::config::resolve::resolve_config(None)?,
+    };
+
+    let project_dir = config_path.parent().unwrap_or(Path::new("."));
+    let state_dir = ProjectState::state_dir_for(project_dir);
+
+    if ProjectState::load(&state_dir).is_none() {
+        bail!("project is not running -- start it with `devrig start`");
+    }
+
+    let response = control::send(&state_dir, &ControlRequest::ReloadEnv { services }).await?;
+    if !response.ok {
+        bail!(
+            "{}",
+            response
+                .error
+                .unwrap_or_else(|| "reload-env failed".to_string())
+        );
+    }
+
+    if response.reloaded.is_empty() {
+        println!("No env changes");
+        return Ok(());
+    }
+    for change in &response.reloaded {
+        let keys = change.keys.join(", ");
+        match &change.action {
+            ReloadAction::Restart => println!("Restarted {} ({})", change.service, keys),
+            ReloadAction::Signal(signal) => {
+                println!("Sent {} to {} ({})", signal, change.service, keys)
+            }
+        }
+    }
+    Ok(())
+}
@@ -1,9 +1,47 @@
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
+use is_terminal::IsTerminal;
+use std::io::Write;
 use std::path::Path;
 
+use crate::config;
+use crate::config::interpolate::{add_builtin_vars, build_template_vars};
+use crate::config::model::DevrigConfig;
+use crate::docker::DockerManager;
+use crate::orchestrator::graph::{DependencyResolver, ResourceKind};
+use crate::orchestrator::ports::AllocatedPorts;
+use crate::orchestrator::started_ports;
 use crate::orchestrator::state::ProjectState;
 
-pub fn run(config_path: Option<&Path>, docker_name: &str) -> Result<()> {
+/// What `devrig reset` does to the selected docker resources.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResetMode {
+    /// Clear the init-completed flag so init scripts re-run on next start.
+    InitFlag,
+    /// Remove the container and its volumes; the next start recreates them.
+    VolumesOnly,
+    /// Remove the container and its volumes, then recreate it right away.
+    Recreate,
+}
+
+impl ResetMode {
+    /// The mode for `devrig reset`'s `--all` and `--volumes-only` flags.
+    pub fn from_flags(all: bool, volumes_only: bool) -> Self {
+        if volumes_only {
+            ResetMode::VolumesOnly
+        } else if all {
+            ResetMode::Recreate
+        } else {
+            ResetMode::InitFlag
+        }
+    }
+}
+
+pub async fn run(
+    config_path: Option<&Path>,
+    docker_name: Option<&str>,
+    mode: ResetMode,
+    yes: bool,
+) -> Result<()> {
     let config_path = match config_path {
         Some(p) => p.to_path_buf(),
         None => crate::config::resolve::resolve_config(None)?,
@@ -16,19 +54,148 @@ pub fn run(config_path: Option<&Path>, docker_name: &str) -> Result<()> {
         anyhow::anyhow!("no project state found -- has the project been started?")
     })?;
 
-    if !state.reset_init(docker_name) {
-        bail!(
+    let targets: Vec<String> = match docker_name {
+        Some(name) if state.docker.contains_key(name) => vec![name.to_string()],
+        Some(name) => bail!(
             "docker '{}' not found in state (available: {:?})",
-            docker_name,
+            name,
             state.docker.keys().collect::<Vec<_>>()
-        );
+        ),
+        None => state.docker.keys().cloned().collect(),
+    };
+    if targets.is_empty() {
+        println!("No docker resources to reset.");
+        return Ok(());
+    }
+
+    if mode == ResetMode::InitFlag {
+        for name in &targets {
+            state.reset_init(name);
+            println!(
+                "Reset init flag for '{}'. Init scripts will run on next start.",
+                name
+            );
+        }
+        state.save(&state_dir)?;
+        return Ok(());
+    }
+
+    let prompt = match mode {
+        ResetMode::Recreate => "remove and recreate the containers and volumes of",
+        _ => "remove the containers and volumes of",
+    };
+    if !yes && !confirm(&format!("This will {} {}.", prompt, targets.join(", ")))? {
+        bail!("reset cancelled");
+    }
+
+    let (config, _source, _secrets) = config::load_config_with_secrets(&config_path)?;
+    let mgr = DockerManager::new(state.slug.clone()).await?;
+
+    for name in &targets {
+        mgr.delete_service(&state.docker[name])
+            .await
+            .with_context(|| format!("removing container of docker '{}'", name))?;
+        for volume in mgr.remove_service_volumes(name).await? {
+            println!("Removed volume {}", volume);
+        }
+        state.reset_init(name);
+        if let Some(docker) = state.docker.get_mut(name) {
+            docker.phase = Some("stopped".to_string());
+            docker.health = None;
+        }
+        ProjectState::update_docker_state(&state_dir, name, &state.docker[name]);
+        println!("Reset '{}'", name);
     }
 
-    state.save(&state_dir)?;
-    println!(
-        "Reset init flag for '{}'. Init scripts will run on next start.",
-        docker_name
-    );
+    if mode == ResetMode::Recreate {
+        mgr.ensure_network().await?;
+        recreate(&mgr, &config, &config_path, &state_dir, &mut state, &targets).await?;
+    } else {
+        println!("Containers and init scripts will be recreated on next start.");
+    }
+    Ok(())
+}
+
+/// Start `targets` again in dependency order, re-running their init scripts
+/// against fresh volumes.
+async fn recreate(
+    mgr: &DockerManager,
+    config: &DevrigConfig,
+    config_path: &Path,
+    state_dir: &Path,
+    state: &mut ProjectState,
+    targets: &[String],
+) -> Result<()> {
+    let config_dir = config_path.parent().unwrap_or(Path::new("."));
+    let order = DependencyResolver::from_config(config)
+        .and_then(|r| r.start_order())
+        .map_err(|e| anyhow::anyhow!(e))?;
+    let mut allocated_ports = AllocatedPorts::new();
 
+    for (name, kind) in order {
+        if kind != ResourceKind::Docker || !targets.contains(&name) {
+            continue;
+        }
+        let Some(docker_config) = config.docker.get(&name) else {
+            println!("Skipped '{}': no longer in the config", name);
+            continue;
+        };
+
+        let mut init_vars = build_template_vars(
+            config,
+            &started_ports(
+                state.dashboard.as_ref(),
+                &state.docker,
+                &state.compose_services,
+            ),
+        );
+        for (cs_name, cs_state) in &state.compose_services {
+            if let Some(port) = cs_state.port {
+                init_vars.insert(format!("compose.{}.port", cs_name), port.to_string());
+            }
+        }
+        add_builtin_vars(&mut init_vars);
+
+        let new_state = mgr
+            .start_service(
+                &name,
+                docker_config,
+                state.docker.get(&name),
+                &mut allocated_ports,
+                config_dir,
+                &init_vars,
+            )
+            .await
+            .with_context(|| format!("recreating docker '{}'", name))?;
+        ProjectState::update_docker_state(state_dir, &name, &new_state);
+        state.docker.insert(name.clone(), new_state);
+        println!("Recreated '{}'", name);
+    }
     Ok(())
 }
+
+/// Ask `question` on the terminal; without one, refuse so scripts must pass
+/// `--yes`.
+fn confirm(question: &str) -> Result<bool> {
+    if !std::io::stdin().is_terminal() {
+        bail!("{} Pass --yes to confirm.", question);
+    }
+    eprint!("{} Continue? [y/N] ", question);
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mode_from_flags() {
+        assert_eq!(ResetMode::from_flags(false, false), ResetMode::InitFlag);
+        assert_eq!(ResetMode::from_flags(true, false), ResetMode::Recreate);
+        assert_eq!(ResetMode::from_flags(false, true), ResetMode::VolumesOnly);
+        assert_eq!(ResetMode::from_flags(true, true), ResetMode::VolumesOnly);
+    }
+}
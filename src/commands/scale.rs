@@ -0,0 +1,78 @@
+use anyhow::{bail, Context, Result};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::orchestrator::control::{self, ControlRequest};
+use crate::orchestrator::state::ProjectState;
+
+pub async fn run(config_path: Option<&Path>, replicas: Vec<String>) -> Result<()> {
+    let counts = parse_counts(&replicas)?;
+
+    let config_path = match config_path {
+        Some(p) => p.to_path_buf(),
+        None => crate::config::resolve::resolve_config(None)?,
+    };
+
+    let project_dir = config_path.parent().unwrap_or(Path::new("."));
+    let state_dir = ProjectState::state_dir_for(project_dir);
+
+    if ProjectState::load(&state_dir).is_none() {
+        bail!("project is not running -- start it with `devrig start`");
+    }
+
+    let response = control::send(&state_dir, &ControlRequest::Scale { replicas: counts }).await?;
+    if !response.ok {
+        bail!(
+            "{}",
+            response.error.unwrap_or_else(|| "scale failed".to_string())
+        );
+    }
+
+    for replica in &response.replicas {
+        match replica.port {
+            Some(port) => println!("{}  port {}", replica.name, port),
+            None => println!("{}", replica.name),
+        }
+    }
+    Ok(())
+}
+
+/// Parse `name=count` arguments. A service named twice keeps the last count.
+fn parse_counts(args: &[String]) -> Result<BTreeMap<String, usize>> {
+    let mut counts = BTreeMap::new();
+    for arg in args {
+        let Some((name, count)) = arg.split_once('=') else {
+            bail!("expected <service>=<count>, got '{}'", arg);
+        };
+        if name.is_empty() {
+            bail!("expected <service>=<count>, got '{}'", arg);
+        }
+        let count: usize = count
+            .parse()
+            .with_context(|| format!("invalid replica count in '{}'", arg))?;
+        counts.insert(name.to_string(), count);
+    }
+    Ok(counts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_name_count_pairs() {
+        let counts = parse_counts(&args(&["api=3", "worker=1", "api=2"])).unwrap();
+        assert_eq!(
+            counts,
+            BTreeMap::from([("api".to_string(), 2), ("worker".to_string(), 1)])
+        );
+
+        for bad in ["api", "=3", "api=", "api=-1", "api=two"] {
+            assert!(parse_counts(&args(&[bad])).is_err(), "{bad} should fail");
+        }
+    }
+}
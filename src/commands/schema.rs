@@ -0,0 +1,9 @@
+use anyhow::Result;
+
+use crate::config::schema::config_schema;
+
+/// Print the JSON Schema for `devrig.toml` to stdout.
+pub fn run() -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(&config_schema())?);
+    Ok(())
+}
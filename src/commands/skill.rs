@@ -1,42 +1,197 @@
-use anyhow::{Context, Result};
-use std::path::Path;
+use anyhow::{bail, Context, Result};
+use std::path::{Path, PathBuf};
 
 const SKILL_MD: &str = include_str!("../../skill/claude-code/SKILL.md");
 const REFERENCE_CONFIGURATION_MD: &str =
     include_str!("../../skill/claude-code/reference/configuration.md");
 
+/// Short description used in the frontmatter of non-Claude targets.
+const DESCRIPTION: &str =
+    "Manage and debug the devrig local development environment: services, logs, traces, metrics and devrig.toml.";
+
+/// An editor or agent the devrig skill can be installed for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkillTarget {
+    /// Claude Code skill: `.claude/skills/devrig/SKILL.md`.
+    Claude,
+    /// Cursor project rule: `.cursor/rules/devrig.mdc`.
+    Cursor,
+    /// VS Code (GitHub Copilot) instructions file.
+    Vscode,
+}
+
+impl SkillTarget {
+    pub const ALL: [SkillTarget; 3] = [
+        SkillTarget::Claude,
+        SkillTarget::Cursor,
+        SkillTarget::Vscode,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SkillTarget::Claude => "claude",
+            SkillTarget::Cursor => "cursor",
+            SkillTarget::Vscode => "vscode",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|t| t.name() == name)
+    }
+
+    /// Where the skill file goes, relative to the project directory.
+    fn project_path(self) -> &'static str {
+        match self {
+            SkillTarget::Claude => ".claude/skills/devrig/SKILL.md",
+            SkillTarget::Cursor => ".cursor/rules/devrig.mdc",
+            SkillTarget::Vscode => ".github/instructions/devrig.instructions.md",
+        }
+    }
+
+    /// Where the skill file goes for a global install, if the target has a
+    /// file-based global location.
+    fn global_path(self) -> Result<Option<PathBuf>> {
+        Ok(match self {
+            SkillTarget::Claude => Some(
+                crate::platform::home_dir()
+                    .context("could not determine home directory")?
+                    .join(".claude/skills/devrig/SKILL.md"),
+            ),
+            SkillTarget::Cursor => None,
+            SkillTarget::Vscode => Some(
+                dirs::config_dir()
+                    .context("could not determine config directory")?
+                    .join("Code/User/prompts/devrig.instructions.md"),
+            ),
+        })
+    }
+
+    /// The file contents: the skill body under the target's own frontmatter.
+    fn contents(self) -> String {
+        match self {
+            SkillTarget::Claude => SKILL_MD.to_string(),
+            SkillTarget::Cursor => format!(
+                "---\ndescription: {}\nglobs:\nalwaysApply: false\n---\n{}",
+                DESCRIPTION,
+                skill_body()
+            ),
+            SkillTarget::Vscode => format!(
+                "---\ndescription: {}\napplyTo: \"**\"\n---\n{}",
+                DESCRIPTION,
+                skill_body()
+            ),
+        }
+    }
+}
+
+/// `SKILL.md` without its Claude-specific frontmatter.
+fn skill_body() -> &'static str {
+    SKILL_MD
+        .strip_prefix("---\n")
+        .and_then(|rest| rest.split_once("\n---\n"))
+        .map(|(_, body)| body)
+        .unwrap_or(SKILL_MD)
+}
+
+/// What installing a target did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstallOutcome {
+    Installed,
+    Updated,
+    UpToDate,
+}
+
+/// Write `target`'s skill file to `path` unless it already has the current
+/// contents.
+fn install_file(target: SkillTarget, path: &Path) -> Result<InstallOutcome> {
+    let contents = target.contents();
+    let outcome = match std::fs::read_to_string(path) {
+        Ok(existing) if existing == contents => return Ok(InstallOutcome::UpToDate),
+        Ok(_) => InstallOutcome::Updated,
+        Err(_) => InstallOutcome::Installed,
+    };
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("creating directory {}", dir.display()))?;
+    }
+    std::fs::write(path, contents).with_context(|| format!("writing {}", path.display()))?;
+    Ok(outcome)
+}
+
+/// The skill file path of `target` in `base` (a project directory), or its
+/// global location when `base` is `None`.
+fn target_path(target: SkillTarget, base: Option<&Path>) -> Result<PathBuf> {
+    match base {
+        Some(dir) => Ok(dir.join(target.project_path())),
+        None => match target.global_path()? {
+            Some(path) => Ok(path),
+            None => bail!(
+                "{} has no global install location; install it per project instead",
+                target.name()
+            ),
+        },
+    }
+}
+
+/// Targets whose skill file already exists in `base` (or globally).
+pub fn installed_targets(base: Option<&Path>) -> Vec<SkillTarget> {
+    SkillTarget::ALL
+        .into_iter()
+        .filter(|&t| target_path(t, base).is_ok_and(|p| p.exists()))
+        .collect()
+}
+
 pub fn run_reference() -> Result<()> {
     print!("{REFERENCE_CONFIGURATION_MD}");
     Ok(())
 }
 
-pub async fn run_install(global: bool, config_file: Option<&Path>) -> Result<()> {
-    let target = if global {
-        crate::platform::home_dir()
-            .context("could not determine home directory")?
-            .join(".claude/skills/devrig")
+/// Install the skill for `targets`. With no targets, refresh the ones
+/// already installed, or install for Claude Code when there are none.
+pub async fn run_install(
+    targets: &[SkillTarget],
+    global: bool,
+    config_file: Option<&Path>,
+) -> Result<()> {
+    let base = if global {
+        None
+    } else if let Some(cf) = config_file {
+        Some(cf.parent().unwrap_or_else(|| Path::new(".")).to_path_buf())
     } else {
-        // Find the config directory: use config_file's parent, or walk up from CWD
-        let config_dir = if let Some(cf) = config_file {
-            cf.parent().unwrap_or_else(|| Path::new(".")).to_path_buf()
-        } else {
-            // Walk up from CWD to find devrig.toml, use its parent
-            find_config_dir()?
-        };
-        config_dir.join(".claude/skills/devrig")
+        // Walk up from CWD to find devrig.toml, use its parent
+        Some(find_config_dir()?)
     };
+    let base = base.as_deref();
 
-    tokio::fs::create_dir_all(&target)
-        .await
-        .with_context(|| format!("creating directory {}", target.display()))?;
+    let targets = if targets.is_empty() {
+        match installed_targets(base) {
+            installed if installed.is_empty() => vec![SkillTarget::Claude],
+            installed => installed,
+        }
+    } else {
+        targets.to_vec()
+    };
 
-    tokio::fs::write(target.join("SKILL.md"), SKILL_MD)
-        .await
-        .with_context(|| format!("writing SKILL.md to {}", target.display()))?;
+    for target in &targets {
+        let path = target_path(*target, base)?;
+        let outcome = install_file(*target, &path)?;
+        let verb = match outcome {
+            InstallOutcome::Installed => "Installed",
+            InstallOutcome::Updated => "Updated",
+            InstallOutcome::UpToDate => "Already up to date:",
+        };
+        println!(
+            "{} devrig skill for {} at {}",
+            verb,
+            target.name(),
+            path.display()
+        );
+    }
 
-    println!("Installed devrig skill to {}", target.display());
-    println!();
-    println!("Try asking Claude: \"What services are running and are there any errors?\"");
+    if targets.contains(&SkillTarget::Claude) {
+        println!();
+        println!("Try asking Claude: \"What services are running and are there any errors?\"");
+    }
 
     Ok(())
 }
@@ -53,3 +208,57 @@ fn find_config_dir() -> Result<std::path::PathBuf> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn non_claude_targets_replace_the_frontmatter() {
+        for target in [SkillTarget::Cursor, SkillTarget::Vscode] {
+            let contents = target.contents();
+            assert!(
+                contents.starts_with("---\ndescription: "),
+                "{}",
+                target.name()
+            );
+            assert!(!contents.contains("allowed-tools"), "{}", target.name());
+            assert!(contents.contains("# devrig — Local Development Environment"));
+        }
+        assert!(SkillTarget::Vscode.contents().contains("applyTo: \"**\""));
+        assert_eq!(SkillTarget::Claude.contents(), SKILL_MD);
+    }
+
+    #[test]
+    fn install_is_idempotent_and_detected() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(installed_targets(Some(dir.path())).is_empty());
+
+        let path = target_path(SkillTarget::Cursor, Some(dir.path())).unwrap();
+        assert_eq!(path, dir.path().join(".cursor/rules/devrig.mdc"));
+        assert_eq!(
+            install_file(SkillTarget::Cursor, &path).unwrap(),
+            InstallOutcome::Installed
+        );
+        assert_eq!(
+            install_file(SkillTarget::Cursor, &path).unwrap(),
+            InstallOutcome::UpToDate
+        );
+        std::fs::write(&path, "stale").unwrap();
+        assert_eq!(
+            install_file(SkillTarget::Cursor, &path).unwrap(),
+            InstallOutcome::Updated
+        );
+
+        assert_eq!(
+            installed_targets(Some(dir.path())),
+            vec![SkillTarget::Cursor]
+        );
+    }
+
+    #[test]
+    fn cursor_has_no_global_location() {
+        let err = target_path(SkillTarget::Cursor, None).unwrap_err();
+        assert!(err.to_string().contains("no global install location"));
+    }
+}
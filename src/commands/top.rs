@@ -0,0 +1,368 @@
+//! `devrig top`: a live view of CPU and memory usage for a project's
+//! containers and service processes.
+
+use anyhow::Result;
+use bollard::models::{ContainerMemoryStats, ContainerStatsResponse};
+use bollard::query_parameters::StatsOptions;
+use bollard::Docker;
+use futures_util::StreamExt;
+use is_terminal::IsTerminal;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+
+use crate::config::resolve::resolve_config;
+use crate::orchestrator::state::ProjectState;
+use crate::platform;
+
+const REFRESH: Duration = Duration::from_secs(1);
+
+/// One line of the `devrig top` table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TopRow {
+    pub name: String,
+    /// `docker`, `compose`, or `service`.
+    pub kind: &'static str,
+    /// Short container ID or process ID.
+    pub id: String,
+    pub cpu_percent: Option<f64>,
+    pub memory_bytes: Option<u64>,
+}
+
+/// A cumulative CPU reading. CPU% is the share of `total` spent in `used`
+/// between two readings, scaled by `cpus`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct CpuSample {
+    used: u64,
+    total: u64,
+    cpus: u32,
+}
+
+fn cpu_percent(prev: CpuSample, cur: CpuSample) -> Option<f64> {
+    let used = cur.used.checked_sub(prev.used)?;
+    let total = cur.total.checked_sub(prev.total)?;
+    if total == 0 {
+        return None;
+    }
+    Some(used as f64 / total as f64 * cur.cpus as f64 * 100.0)
+}
+
+/// Container memory the way `docker stats` reports it: usage minus
+/// reclaimable page cache (`inactive_file` on cgroup v2,
+/// `total_inactive_file` on v1).
+fn container_memory(mem: &ContainerMemoryStats) -> Option<u64> {
+    let usage = mem.usage?;
+    let cache = mem
+        .stats
+        .as_ref()
+        .and_then(|s| {
+            s.get("inactive_file")
+                .or_else(|| s.get("total_inactive_file"))
+        })
+        .copied()
+        .unwrap_or(0);
+    Some(usage.saturating_sub(cache))
+}
+
+fn container_cpu(stats: &ContainerStatsResponse) -> Option<CpuSample> {
+    let cpu = stats.cpu_stats.as_ref()?;
+    Some(CpuSample {
+        used: cpu.cpu_usage.as_ref()?.total_usage?,
+        total: cpu.system_cpu_usage?,
+        cpus: cpu.online_cpus.unwrap_or(1).max(1),
+    })
+}
+
+/// Keeps the previous CPU reading per resource so each refresh can report
+/// usage over the last interval.
+struct Sampler {
+    docker: Option<Docker>,
+    started: Instant,
+    previous: HashMap<String, CpuSample>,
+}
+
+impl Sampler {
+    fn new() -> Self {
+        Self {
+            docker: None,
+            started: Instant::now(),
+            previous: HashMap::new(),
+        }
+    }
+
+    async fn sample(&mut self, state: &ProjectState) -> Vec<TopRow> {
+        let containers: Vec<(&String, &'static str, &String)> = state
+            .docker
+            .iter()
+            .map(|(name, d)| (name, "docker", &d.container_id))
+            .chain(
+                state
+                    .compose_services
+                    .iter()
+                    .map(|(name, c)| (name, "compose", &c.container_id)),
+            )
+            .collect();
+
+        if !containers.is_empty() && self.docker.is_none() {
+            self.docker = Docker::connect_with_local_defaults().ok();
+        }
+        let stats = match &self.docker {
+            Some(docker) => {
+                futures_util::future::join_all(
+                    containers
+                        .iter()
+                        .map(|(_, _, id)| one_shot_stats(docker, id.as_str())),
+                )
+                .await
+            }
+            None => vec![None; containers.len()],
+        };
+
+        let mut rows = Vec::new();
+        for ((name, kind, id), stats) in containers.into_iter().zip(stats) {
+            let cpu = stats.as_ref().and_then(container_cpu);
+            rows.push(TopRow {
+                name: name.clone(),
+                kind,
+                id: id.chars().take(12).collect(),
+                cpu_percent: self.cpu_since_last(&format!("{kind}/{name}"), cpu),
+                memory_bytes: stats
+                    .as_ref()
+                    .and_then(|s| s.memory_stats.as_ref())
+                    .and_then(container_memory),
+            });
+        }
+
+        for (name, svc) in &state.services {
+            let stats = (svc.pid != 0)
+                .then(|| platform::process_stats(svc.pid))
+                .flatten();
+            let cpu = stats.map(|s| CpuSample {
+                used: s.cpu_time.as_nanos() as u64,
+                total: self.started.elapsed().as_nanos() as u64,
+                cpus: 1,
+            });
+            rows.push(TopRow {
+                name: name.clone(),
+                kind: "service",
+                id: if svc.pid == 0 {
+                    "-".to_string()
+                } else {
+                    svc.pid.to_string()
+                },
+                cpu_percent: self.cpu_since_last(&format!("service/{name}"), cpu),
+                memory_bytes: stats.map(|s| s.rss_bytes),
+            });
+        }
+
+        rows
+    }
+
+    fn cpu_since_last(&mut self, key: &str, cur: Option<CpuSample>) -> Option<f64> {
+        let Some(cur) = cur else {
+            self.previous.remove(key);
+            return None;
+        };
+        self.previous
+            .insert(key.to_string(), cur)
+            .and_then(|prev| cpu_percent(prev, cur))
+    }
+}
+
+async fn one_shot_stats(docker: &Docker, container_id: &str) -> Option<ContainerStatsResponse> {
+    let options = StatsOptions {
+        stream: false,
+        one_shot: true,
+    };
+    docker.stats(container_id, Some(options)).next().await?.ok()
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// Render one frame of the table.
+pub fn render(slug: &str, rows: &[TopRow]) -> String {
+    let mut out = format!("  Project: {}  (q to quit)\n\n", slug);
+    out.push_str(&format!(
+        "  {:<20} {:<8} {:<14} {:>8} {:>12}\n",
+        "NAME", "KIND", "ID", "CPU%", "MEMORY"
+    ));
+    out.push_str(&format!("  {}\n", "-".repeat(66)));
+    for row in rows {
+        let cpu = row
+            .cpu_percent
+            .map(|p| format!("{:.1}", p))
+            .unwrap_or_else(|| "-".to_string());
+        let memory = row
+            .memory_bytes
+            .map(format_bytes)
+            .unwrap_or_else(|| "-".to_string());
+        out.push_str(&format!(
+            "  {:<20} {:<8} {:<14} {:>8} {:>12}\n",
+            row.name, row.kind, row.id, cpu, memory
+        ));
+    }
+    out
+}
+
+/// Refresh the usage table every second until `q` or Ctrl+C.
+pub async fn run(config_file: Option<&Path>) -> Result<()> {
+    let config_path = resolve_config(config_file)?;
+    let project_dir = config_path.parent().unwrap_or(Path::new("."));
+    let state_dir = ProjectState::state_dir_for(project_dir);
+    if ProjectState::load(&state_dir).is_none() {
+        println!("No running services found.");
+        println!("Run `devrig start` to start services.");
+        return Ok(());
+    }
+
+    let quit = Arc::new(Notify::new());
+    let _keypress = platform::keypress_input();
+    {
+        let quit = Arc::clone(&quit);
+        std::thread::spawn(move || {
+            for byte in std::io::stdin().lock().bytes() {
+                match byte {
+                    Ok(b'q') | Ok(b'Q') => {
+                        quit.notify_one();
+                        break;
+                    }
+                    Ok(_) => {}
+                    Err(_) => break,
+                }
+            }
+        });
+    }
+
+    let clear = std::io::stdout().is_terminal();
+    let mut sampler = Sampler::new();
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+    loop {
+        let Some(state) = ProjectState::load(&state_dir) else {
+            println!("Project stopped.");
+            break;
+        };
+        let rows = sampler.sample(&state).await;
+
+        let mut stdout = std::io::stdout().lock();
+        if clear {
+            write!(stdout, "\x1b[2J\x1b[H")?;
+        }
+        write!(stdout, "{}", render(&state.slug, &rows))?;
+        if !clear {
+            writeln!(stdout)?;
+        }
+        stdout.flush()?;
+        drop(stdout);
+
+        tokio::select! {
+            _ = &mut ctrl_c => break,
+            _ = quit.notified() => break,
+            _ = tokio::time::sleep(REFRESH) => {}
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cpu_percent_scales_by_cpus() {
+        let prev = CpuSample {
+            used: 1_000,
+            total: 10_000,
+            cpus: 4,
+        };
+        let cur = CpuSample {
+            used: 1_500,
+            total: 12_000,
+            cpus: 4,
+        };
+        assert_eq!(cpu_percent(prev, cur), Some(100.0));
+        // A restarted process reports less CPU time than before.
+        assert_eq!(cpu_percent(cur, prev), None);
+        assert_eq!(cpu_percent(cur, cur), None);
+    }
+
+    #[test]
+    fn container_memory_excludes_page_cache() {
+        let mut mem = ContainerMemoryStats {
+            usage: Some(100 * 1024 * 1024),
+            stats: Some(HashMap::from([(
+                "inactive_file".to_string(),
+                30 * 1024 * 1024,
+            )])),
+            ..Default::default()
+        };
+        assert_eq!(container_memory(&mem), Some(70 * 1024 * 1024));
+        mem.stats = None;
+        assert_eq!(container_memory(&mem), Some(100 * 1024 * 1024));
+        mem.usage = None;
+        assert_eq!(container_memory(&mem), None);
+    }
+
+    #[test]
+    fn first_sample_has_no_cpu_percent() {
+        let mut sampler = Sampler::new();
+        let sample = |used| {
+            Some(CpuSample {
+                used,
+                total: used * 2,
+                cpus: 1,
+            })
+        };
+        assert_eq!(sampler.cpu_since_last("service/api", sample(100)), None);
+        assert_eq!(
+            sampler.cpu_since_last("service/api", sample(200)),
+            Some(50.0)
+        );
+        assert_eq!(sampler.cpu_since_last("service/api", None), None);
+        assert_eq!(sampler.cpu_since_last("service/api", sample(300)), None);
+    }
+
+    #[test]
+    fn render_formats_usage() {
+        let rows = vec![
+            TopRow {
+                name: "postgres".to_string(),
+                kind: "docker",
+                id: "abc123def456".to_string(),
+                cpu_percent: Some(12.345),
+                memory_bytes: Some(3 * 1024 * 1024 / 2),
+            },
+            TopRow {
+                name: "api".to_string(),
+                kind: "service",
+                id: "4242".to_string(),
+                cpu_percent: None,
+                memory_bytes: Some(512),
+            },
+        ];
+        let out = render("myapp-1234", &rows);
+        assert!(out.contains("Project: myapp-1234"));
+        let postgres = out.lines().find(|l| l.contains("postgres")).unwrap();
+        assert!(postgres.contains("12.3"), "{postgres}");
+        assert!(postgres.contains("1.5 MiB"), "{postgres}");
+        let api = out.lines().find(|l| l.contains("api")).unwrap();
+        assert!(api.contains(" - "), "{api}");
+        assert!(api.ends_with("512 B"), "{api}");
+    }
+}
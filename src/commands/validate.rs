@@ -3,38 +3,79 @@ use owo_colors::OwoColorize;
 use std::path::Path;
 
 use crate::config;
+use crate::config::model::DevrigConfig;
 use crate::config::resolve::resolve_config;
-use crate::config::validate::validate;
+use crate::config::validate::{validate, validate_compose_files, DiagnosticReport};
 
-pub fn run(config_file: Option<&Path>) -> Result<()> {
+pub fn run(config_file: Option<&Path>, format: &str) -> Result<()> {
     let config_path = resolve_config(config_file)?;
+    let json = format == "json";
 
-    let (config, source) = config::load_config(&config_path)?;
+    let (config, source) = match config::load_config(&config_path) {
+        Ok(loaded) => loaded,
+        Err(e) if json => {
+            // Re-parse to recover the TOML error's span; anything else
+            // (an unreadable file) is not a diagnostic about the source.
+            let content = std::fs::read_to_string(&config_path)?;
+            match toml::from_str::<DevrigConfig>(&content) {
+                Err(toml_err) => {
+                    print_json(&[DiagnosticReport::from_toml_error(&toml_err)])?;
+                    std::process::exit(1);
+                }
+                Ok(_) => return Err(e),
+            }
+        }
+        Err(e) => return Err(e),
+    };
 
     let filename = config_path
         .file_name()
         .map(|f| f.to_string_lossy().to_string())
         .unwrap_or_else(|| "devrig.toml".to_string());
 
-    match validate(&config, &source, &filename) {
-        Ok(()) => {
-            let svc_count = config.services.len();
-            let docker_count = config.docker.len();
-            println!(
-                "  {} {} is valid ({} services, {} docker)",
-                "\u{2713}".green(),
-                filename,
-                svc_count,
-                docker_count,
-            );
-            Ok(())
-        }
-        Err(errors) => {
-            for err in errors {
-                let report: miette::Report = err.into();
-                eprintln!("{:?}", report);
-            }
+    let config_dir = config_path.parent().unwrap_or(Path::new("."));
+    let mut errors = validate(&config, &source, &filename)
+        .err()
+        .unwrap_or_default();
+    errors.extend(
+        validate_compose_files(&config, &source, &filename, config_dir)
+            .err()
+            .unwrap_or_default(),
+    );
+
+    if json {
+        let reports: Vec<DiagnosticReport> = errors
+            .iter()
+            .map(|err| DiagnosticReport::from_diagnostic(err))
+            .collect();
+        print_json(&reports)?;
+        if !errors.is_empty() {
             std::process::exit(1);
         }
+        return Ok(());
     }
+
+    if errors.is_empty() {
+        let svc_count = config.services.len();
+        let docker_count = config.docker.len();
+        println!(
+            "  {} {} is valid ({} services, {} docker)",
+            "\u{2713}".green(),
+            filename,
+            svc_count,
+            docker_count,
+        );
+        return Ok(());
+    }
+
+    for err in errors {
+        let report: miette::Report = err.into();
+        eprintln!("{:?}", report);
+    }
+    std::process::exit(1);
+}
+
+fn print_json(reports: &[DiagnosticReport]) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(reports)?);
+    Ok(())
 }
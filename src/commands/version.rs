@@ -0,0 +1,219 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+
+const DEFAULT_RELEASES_URL: &str =
+    "https://api.github.com/repos/steveyackey/devrig/releases/latest";
+
+/// How long a successful check is reused before hitting the endpoint again.
+const CACHE_TTL_HOURS: i64 = 24;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct VersionCache {
+    /// Release endpoint the answer came from; a cache for another URL is
+    /// not reused. Older caches don't record one.
+    #[serde(default)]
+    url: String,
+    checked_at: DateTime<Utc>,
+    latest: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct LatestRelease {
+    tag_name: String,
+}
+
+fn cache_path() -> PathBuf {
+    crate::platform::home_dir()
+        .unwrap_or_else(|| PathBuf::from("/tmp"))
+        .join(".devrig")
+        .join("version-check.json")
+}
+
+/// Release endpoint, overridable with `DEVRIG_UPDATE_URL`.
+fn releases_url() -> String {
+    std::env::var("DEVRIG_UPDATE_URL").unwrap_or_else(|_| DEFAULT_RELEASES_URL.to_string())
+}
+
+pub async fn run(check: bool) -> Result<()> {
+    let current = env!("CARGO_PKG_VERSION");
+    println!("devrig {}", current);
+
+    if !check {
+        return Ok(());
+    }
+
+    let url = releases_url();
+    let latest = latest_version(&cache_path(), &url, Utc::now(), || fetch_latest(&url)).await;
+    match latest {
+        Ok(latest) => match update_notice(current, &latest) {
+            Some(notice) => println!("{}", notice),
+            None => println!("You are running the latest version."),
+        },
+        Err(e) => eprintln!("Could not check for updates: {:#}", e),
+    }
+    Ok(())
+}
+
+async fn fetch_latest(url: &str) -> Result<String> {
+    let client = reqwest::Client::builder()
+        .user_agent(concat!("devrig/", env!("CARGO_PKG_VERSION")))
+        .timeout(std::time::Duration::from_secs(5))
+        .build()?;
+    let resp = client
+        .get(url)
+        .send()
+        .await
+        .context("connecting to release endpoint")?;
+    if !resp.status().is_success() {
+        anyhow::bail!("release endpoint returned {}", resp.status());
+    }
+    let release: LatestRelease = resp.json().await.context("parsing release response")?;
+    Ok(release.tag_name)
+}
+
+/// Return the latest released version, reusing the cached answer when it
+/// came from `url` and is younger than a day, and calling `fetch` otherwise.
+async fn latest_version<F, Fut>(
+    cache_path: &Path,
+    url: &str,
+    now: DateTime<Utc>,
+    fetch: F,
+) -> Result<String>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<String>>,
+{
+    if let Some(cache) = std::fs::read_to_string(cache_path)
+        .ok()
+        .and_then(|c| serde_json::from_str::<VersionCache>(&c).ok())
+    {
+        if cache.url == url && now - cache.checked_at < Duration::hours(CACHE_TTL_HOURS) {
+            return Ok(cache.latest);
+        }
+    }
+
+    let latest = fetch().await?;
+    let cache = VersionCache {
+        url: url.to_string(),
+        checked_at: now,
+        latest: latest.clone(),
+    };
+    if let Some(parent) = cache_path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    // A failed cache write only means the next check hits the network again.
+    let _ = std::fs::write(cache_path, serde_json::to_string_pretty(&cache)?);
+    Ok(latest)
+}
+
+/// Parse "v1.2.3" / "1.2.3" into comparable numeric components.
+fn parse_version(v: &str) -> Option<Vec<u64>> {
+    let v = v.trim().trim_start_matches('v');
+    let core = v.split(['-', '+']).next()?;
+    core.split('.').map(|p| p.parse().ok()).collect()
+}
+
+/// Build the "update available" message when `latest` is newer than `current`.
+fn update_notice(current: &str, latest: &str) -> Option<String> {
+    let (cur, new) = (parse_version(current)?, parse_version(latest)?);
+    if new > cur {
+        Some(format!(
+            "A new version is available: {} -> {}\nRun `devrig update` to upgrade.",
+            current,
+            latest.trim_start_matches('v')
+        ))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    const URL: &str = "https://releases.example.com/latest";
+
+    #[test]
+    fn notice_when_newer_release_exists() {
+        let notice = update_notice("0.29.0", "v0.30.1").unwrap();
+        assert!(notice.contains("0.29.0 -> 0.30.1"));
+        assert!(update_notice("0.29.0", "v0.29.0").is_none());
+        assert!(update_notice("0.29.0", "0.28.9").is_none());
+        assert!(update_notice("0.9.0", "0.10.0").is_some());
+        assert!(update_notice("0.29.0", "not-a-version").is_none());
+    }
+
+    #[tokio::test]
+    async fn fetches_then_uses_cache_for_a_day() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = dir.path().join("version-check.json");
+        let now = Utc::now();
+        let calls = Cell::new(0);
+
+        let fetch = || {
+            calls.set(calls.get() + 1);
+            async { Ok("v1.0.0".to_string()) }
+        };
+        assert_eq!(
+            latest_version(&cache, URL, now, fetch).await.unwrap(),
+            "v1.0.0"
+        );
+
+        let fetch = || {
+            calls.set(calls.get() + 1);
+            async { Ok("v2.0.0".to_string()) }
+        };
+        let later = now + Duration::hours(1);
+        assert_eq!(
+            latest_version(&cache, URL, later, fetch).await.unwrap(),
+            "v1.0.0"
+        );
+        assert_eq!(calls.get(), 1);
+
+        let fetch = || {
+            calls.set(calls.get() + 1);
+            async { Ok("v2.0.0".to_string()) }
+        };
+        let next_day = now + Duration::hours(25);
+        assert_eq!(
+            latest_version(&cache, URL, next_day, fetch).await.unwrap(),
+            "v2.0.0"
+        );
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[tokio::test]
+    async fn cache_for_another_url_is_not_reused() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = dir.path().join("version-check.json");
+        let now = Utc::now();
+        let fetch = || async { Ok("v1.0.0".to_string()) };
+        assert_eq!(
+            latest_version(&cache, URL, now, fetch).await.unwrap(),
+            "v1.0.0"
+        );
+
+        let mirror = "https://mirror.example.com/latest";
+        let fetch = || async { Ok("v2.0.0".to_string()) };
+        assert_eq!(
+            latest_version(&cache, mirror, now, fetch).await.unwrap(),
+            "v2.0.0"
+        );
+    }
+
+    #[tokio::test]
+    async fn fetch_error_is_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = dir.path().join("version-check.json");
+        let result = latest_version(&cache, URL, Utc::now(), || async {
+            Err(anyhow::anyhow!("offline"))
+        })
+        .await;
+        assert!(result.is_err());
+        assert!(!cache.exists());
+    }
+}
@@ -0,0 +1,299 @@
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::config::model::DevrigConfig;
+use crate::orchestrator::scale;
+use crate::orchestrator::state::ProjectState;
+
+/// How often state.json is re-read while waiting.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, PartialEq)]
+enum Readiness {
+    Ready,
+    /// Not ready yet; the current status, for the timeout message.
+    Pending(String),
+    /// Will not become ready without intervention.
+    Failed(String),
+}
+
+/// Block until every named resource of the running project is ready, or
+/// `timeout_secs` elapse. Reads the project's state.json, so it works
+/// against a `devrig start` running in another process.
+pub async fn run(config_path: Option<&Path>, names: Vec<String>, timeout_secs: u64) -> Result<()> {
+    let config_path = match config_path {
+        Some(p) => p.to_path_buf(),
+        None => crate::config::resolve::resolve_config(None)?,
+    };
+    let (config, _source) = crate::config::load_config(&config_path)?;
+    let known = waitable_names(&config);
+    for name in &names {
+        if !known.contains(scale::split_replica(name).0) {
+            bail!("unknown resource '{}' (available: {:?})", name, known);
+        }
+    }
+
+    let project_dir = config_path.parent().unwrap_or(Path::new("."));
+    let state_dir = ProjectState::state_dir_for(project_dir);
+
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(timeout_secs);
+    let mut pending = names;
+    loop {
+        let state = current_state(&state_dir)?;
+        let mut waiting = Vec::new();
+        for name in pending {
+            match state.as_ref().and_then(|s| readiness(s, &name)) {
+                Some(Readiness::Ready) => println!("{} is ready", name),
+                Some(Readiness::Failed(reason)) => bail!("`{}` {}", name, reason),
+                Some(Readiness::Pending(status)) => waiting.push((name, status)),
+                None => waiting.push((name, "not started".to_string())),
+            }
+        }
+        if waiting.is_empty() {
+            return Ok(());
+        }
+        if tokio::time::Instant::now() >= deadline {
+            let still: Vec<String> = waiting
+                .iter()
+                .map(|(name, status)| format!("{} ({})", name, status))
+                .collect();
+            bail!(
+                "timed out after {}s waiting for {}",
+                timeout_secs,
+                still.join(", ")
+            );
+        }
+        pending = waiting.into_iter().map(|(name, _)| name).collect();
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Names in `config` that [`readiness`] can report on: services, docker,
+/// compose services, cluster deploys and addons.
+fn waitable_names(config: &DevrigConfig) -> BTreeSet<&str> {
+    let mut names: BTreeSet<&str> = config.services.keys().map(String::as_str).collect();
+    names.extend(config.docker.keys().map(String::as_str));
+    if let Some(compose) = &config.compose {
+        names.extend(compose.services.iter().map(String::as_str));
+    }
+    if let Some(cluster) = &config.cluster {
+        names.extend(cluster.deploy.keys().map(String::as_str));
+        names.extend(cluster.addons.keys().map(String::as_str));
+    }
+    names
+}
+
+/// The state of the devrig currently starting or running, or `None` while
+/// it has not written any yet. state.json left behind by an earlier run is
+/// ignored: it predates the pid file of the current one.
+fn current_state(state_dir: &Path) -> Result<Option<ProjectState>> {
+    let pid_path = state_dir.join("pid");
+    let Ok(pid) = std::fs::read_to_string(&pid_path) else {
+        return Ok(None);
+    };
+    let alive = pid
+        .trim()
+        .parse()
+        .is_ok_and(crate::platform::is_process_alive);
+    if !alive {
+        bail!("project is not running -- start it with `devrig start`");
+    }
+    let started: Option<DateTime<Utc>> = std::fs::metadata(&pid_path)
+        .and_then(|m| m.modified())
+        .ok()
+        .map(DateTime::from);
+    Ok(ProjectState::load(state_dir).filter(|s| started.is_none_or(|t| s.started_at >= t)))
+}
+
+/// How far along resource `name` is, or `None` when the state does not
+/// list it (yet).
+fn readiness(state: &ProjectState, name: &str) -> Option<Readiness> {
+    if let Some(svc) = state.services.get(name) {
+        let phase = svc.phase.as_deref().unwrap_or("starting");
+        return Some(match (phase, svc.ready.as_deref()) {
+            ("failed" | "stopped", _) => Readiness::Failed(match svc.exit_code {
+                Some(code) => format!("exited with code {}", code),
+                None => "exited".to_string(),
+            }),
+            (_, Some("passed")) => Readiness::Ready,
+            (_, Some("failed")) => Readiness::Failed("failed its ready check".to_string()),
+            ("running", None) => Readiness::Ready,
+            (phase, _) => Readiness::Pending(phase.to_string()),
+        });
+    }
+    if let Some(docker) = state.docker.get(name) {
+        // Containers are recorded once their ready_check has passed; a
+        // HEALTHCHECK keeps being reported by the container watcher.
+        return Some(match (docker.phase.as_deref(), docker.health.as_deref()) {
+            (Some("failed"), _) => Readiness::Failed("failed".to_string()),
+            (_, Some("unhealthy")) => Readiness::Failed("is unhealthy".to_string()),
+            (Some("restarting"), _) => Readiness::Pending("restarting".to_string()),
+            (_, Some("starting")) => Readiness::Pending("health: starting".to_string()),
+            _ => Readiness::Ready,
+        });
+    }
+    let deployed = state.cluster.as_ref().is_some_and(|c| {
+        c.deployed_services.contains_key(name) || c.installed_addons.contains_key(name)
+    });
+    (state.compose_services.contains_key(name) || deployed).then_some(Readiness::Ready)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orchestrator::state::{DockerState, ServiceState};
+    use std::collections::BTreeMap;
+
+    fn service(phase: &str, ready: Option<&str>, exit_code: Option<i32>) -> ServiceState {
+        ServiceState {
+            pid: 4242,
+            port: None,
+            port_auto: false,
+            protocol: None,
+            phase: Some(phase.to_string()),
+            exit_code,
+            ready: ready.map(String::from),
+        }
+    }
+
+    fn docker(phase: Option<&str>, health: Option<&str>) -> DockerState {
+        DockerState {
+            container_id: "abc".to_string(),
+            container_name: "devrig-test-postgres".to_string(),
+            port: Some(5432),
+            port_auto: false,
+            protocol: None,
+            named_ports: BTreeMap::new(),
+            init_completed: false,
+            init_completed_at: None,
+            init_checksum: None,
+            image_digest: None,
+            phase: phase.map(String::from),
+            stop_timeout: None,
+            health: health.map(String::from),
+        }
+    }
+
+    fn state() -> ProjectState {
+        ProjectState {
+            slug: "test".to_string(),
+            config_path: "devrig.toml".to_string(),
+            services: BTreeMap::new(),
+            started_at: Utc::now(),
+            docker: BTreeMap::new(),
+            compose_services: BTreeMap::new(),
+            network_name: None,
+            cluster: None,
+            dashboard: None,
+        }
+    }
+
+    #[test]
+    fn waitable_names_cover_every_reported_kind() {
+        let config: DevrigConfig = toml::from_str(
+            r#"
+            [project]
+            name = "test"
+            [services.api]
+            command = "cargo run"
+            [docker.postgres]
+            image = "postgres:16"
+            [compose]
+            file = "docker-compose.yml"
+            services = ["redis"]
+            [cluster.image.builder]
+            context = "./builder"
+            [cluster.deploy.web]
+            context = "./web"
+            manifests = "./k8s/web"
+            "#,
+        )
+        .unwrap();
+        let names: Vec<&str> = waitable_names(&config).into_iter().collect();
+        assert_eq!(names, vec!["api", "postgres", "redis", "web"]);
+    }
+
+    #[tokio::test]
+    async fn unknown_names_fail_without_waiting() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("devrig.toml");
+        std::fs::write(
+            &config_path,
+            "[project]\nname = \"test\"\n[services.api]\ncommand = \"cargo run\"\n",
+        )
+        .unwrap();
+        let err = run(Some(&config_path), vec!["apii".to_string()], 60)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("unknown resource 'apii'"), "{err}");
+    }
+
+    fn readiness_of(svc: ServiceState) -> Option<Readiness> {
+        let mut state = state();
+        state.services.insert("api".to_string(), svc);
+        readiness(&state, "api")
+    }
+
+    #[test]
+    fn services_with_a_ready_check_wait_for_it() {
+        let pending = Readiness::Pending("running".to_string());
+        assert_eq!(
+            readiness_of(service("running", Some("pending"), None)),
+            Some(pending)
+        );
+        assert_eq!(
+            readiness_of(service("starting", Some("passed"), None)),
+            Some(Readiness::Ready)
+        );
+        assert!(matches!(
+            readiness_of(service("running", Some("failed"), None)),
+            Some(Readiness::Failed(_))
+        ));
+    }
+
+    #[test]
+    fn services_without_a_ready_check_wait_for_running() {
+        let pending = Readiness::Pending("starting".to_string());
+        assert_eq!(readiness_of(service("starting", None, None)), Some(pending));
+        assert_eq!(
+            readiness_of(service("running", None, None)),
+            Some(Readiness::Ready)
+        );
+        assert_eq!(
+            readiness_of(service("failed", None, Some(1))),
+            Some(Readiness::Failed("exited with code 1".to_string()))
+        );
+    }
+
+    #[test]
+    fn docker_uses_health_status() {
+        let mut state = state();
+        for (name, phase, health) in [
+            ("plain", Some("running"), None),
+            ("healthy", Some("running"), Some("healthy")),
+            ("booting", Some("running"), Some("starting")),
+            ("sick", Some("unhealthy"), Some("unhealthy")),
+            ("crashed", Some("failed"), None),
+        ] {
+            state.docker.insert(name.to_string(), docker(phase, health));
+        }
+        assert_eq!(readiness(&state, "plain"), Some(Readiness::Ready));
+        assert_eq!(readiness(&state, "healthy"), Some(Readiness::Ready));
+        assert!(matches!(
+            readiness(&state, "booting"),
+            Some(Readiness::Pending(_))
+        ));
+        assert!(matches!(
+            readiness(&state, "sick"),
+            Some(Readiness::Failed(_))
+        ));
+        assert!(matches!(
+            readiness(&state, "crashed"),
+            Some(Readiness::Failed(_))
+        ));
+        assert_eq!(readiness(&state, "missing"), None);
+    }
+}
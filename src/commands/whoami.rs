@@ -0,0 +1,93 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use crate::config::load_config;
+use crate::config::resolve::resolve_config;
+use crate::identity::ProjectIdentity;
+use crate::orchestrator::state::ProjectState;
+
+/// The names devrig derives for a project, as printed by `devrig whoami`.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Whoami {
+    pub name: String,
+    pub id: String,
+    pub slug: String,
+    pub network: String,
+    pub container_prefix: String,
+    pub cluster: String,
+    pub state_dir: PathBuf,
+    pub config_path: PathBuf,
+}
+
+impl Whoami {
+    pub fn from_identity(identity: &ProjectIdentity) -> Self {
+        let project_dir = identity.config_path.parent().unwrap_or(Path::new("."));
+        Self {
+            name: identity.name.clone(),
+            id: identity.id.clone(),
+            slug: identity.slug.clone(),
+            network: identity.network_name(),
+            container_prefix: identity.container_prefix(),
+            cluster: identity.cluster_name(),
+            state_dir: ProjectState::state_dir_for(project_dir),
+            config_path: identity.config_path.clone(),
+        }
+    }
+}
+
+/// Print the project's identity and derived resource names without
+/// starting anything.
+pub fn run(config_file: Option<&Path>, json: bool) -> Result<()> {
+    let config_path = resolve_config(config_file)?;
+    let (config, _source) = load_config(&config_path)?;
+    let identity = ProjectIdentity::from_config(&config, &config_path)?;
+    let whoami = Whoami::from_identity(&identity);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&whoami)?);
+        return Ok(());
+    }
+
+    println!("project           {}", whoami.name);
+    println!("id                {}", whoami.id);
+    println!("slug              {}", whoami.slug);
+    println!("network           {}", whoami.network);
+    println!("container prefix  {}", whoami.container_prefix);
+    println!("cluster           {}", whoami.cluster);
+    println!("state dir         {}", whoami.state_dir.display());
+    println!("config            {}", whoami.config_path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::identity::{cluster_name, compute_project_id, container_name, network_name};
+
+    #[test]
+    fn names_match_identity_computation() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("devrig.toml");
+        std::fs::write(&config_path, "[project]\nname = \"myapp\"\n").unwrap();
+        let (config, _) = load_config(&config_path).unwrap();
+        let identity = ProjectIdentity::from_config(&config, &config_path).unwrap();
+
+        let whoami = Whoami::from_identity(&identity);
+        let canonical = config_path.canonicalize().unwrap();
+        let slug = format!("myapp-{}", compute_project_id(&canonical));
+        assert_eq!(whoami.slug, slug);
+        assert_eq!(whoami.network, network_name(&slug));
+        assert_eq!(whoami.network, format!("devrig-{slug}-net"));
+        assert_eq!(whoami.cluster, cluster_name(&slug));
+        assert!(container_name(&slug, "postgres").starts_with(&whoami.container_prefix));
+        assert_eq!(
+            whoami.state_dir,
+            canonical.parent().unwrap().join(".devrig")
+        );
+
+        let json: serde_json::Value = serde_json::to_value(&whoami).unwrap();
+        assert_eq!(json["slug"], slug);
+        assert_eq!(json["container_prefix"], format!("devrig-{slug}-"));
+    }
+}
@@ -1,6 +1,6 @@
 use anyhow::{bail, Context, Result};
 use serde::Deserialize;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tracing::debug;
 
 /// Represents a service reported by `docker compose ps --format json`.
@@ -28,23 +28,30 @@ pub struct ComposePublisher {
     pub published_port: u16,
 }
 
-/// Run `docker compose up -d` for the specified services.
+/// `-f <file>` for each compose file, in merge order.
+fn file_args(compose_files: &[PathBuf]) -> Vec<String> {
+    compose_files
+        .iter()
+        .flat_map(|f| ["-f".to_string(), f.to_string_lossy().into_owned()])
+        .collect()
+}
+
+/// Run `docker compose up -d` for the specified services. With `no_pull`,
+/// images missing locally fail the command instead of being pulled.
 pub async fn compose_up(
-    compose_file: &Path,
+    compose_files: &[PathBuf],
     project_name: &str,
     services: &[String],
     env_file: Option<&str>,
+    no_pull: bool,
 ) -> Result<()> {
     let mut cmd = tokio::process::Command::new("docker");
-    cmd.args([
-        "compose",
-        "-f",
-        &compose_file.to_string_lossy(),
-        "-p",
-        project_name,
-        "up",
-        "-d",
-    ]);
+    cmd.arg("compose")
+        .args(file_args(compose_files))
+        .args(["-p", project_name, "up", "-d"]);
+    if no_pull {
+        cmd.args(["--pull", "never"]);
+    }
     if let Some(ef) = env_file {
         cmd.args(["--env-file", ef]);
     }
@@ -63,17 +70,11 @@ pub async fn compose_up(
 }
 
 /// Run `docker compose down --remove-orphans`.
-pub async fn compose_down(compose_file: &Path, project_name: &str) -> Result<()> {
+pub async fn compose_down(compose_files: &[PathBuf], project_name: &str) -> Result<()> {
     let output = tokio::process::Command::new("docker")
-        .args([
-            "compose",
-            "-f",
-            &compose_file.to_string_lossy(),
-            "-p",
-            project_name,
-            "down",
-            "--remove-orphans",
-        ])
+        .arg("compose")
+        .args(file_args(compose_files))
+        .args(["-p", project_name, "down", "--remove-orphans"])
         .output()
         .await
         .context("running docker compose down")?;
@@ -88,18 +89,14 @@ pub async fn compose_down(compose_file: &Path, project_name: &str) -> Result<()>
 }
 
 /// Run `docker compose ps --format json` and parse the output.
-pub async fn compose_ps(compose_file: &Path, project_name: &str) -> Result<Vec<ComposeService>> {
+pub async fn compose_ps(
+    compose_files: &[PathBuf],
+    project_name: &str,
+) -> Result<Vec<ComposeService>> {
     let output = tokio::process::Command::new("docker")
-        .args([
-            "compose",
-            "-f",
-            &compose_file.to_string_lossy(),
-            "-p",
-            project_name,
-            "ps",
-            "--format",
-            "json",
-        ])
+        .arg("compose")
+        .args(file_args(compose_files))
+        .args(["-p", project_name, "ps", "--format", "json"])
         .output()
         .await
         .context("running docker compose ps")?;
@@ -139,13 +136,28 @@ pub async fn compose_ps(compose_file: &Path, project_name: &str) -> Result<Vec<C
     Ok(services)
 }
 
-/// Discover service names from a docker-compose.yml file by parsing the
-/// top-level `services:` section. This avoids requiring Docker at config
-/// load time while still enabling auto-discovery of compose services as
-/// valid `depends_on` targets.
+/// Discover service names from docker-compose files by parsing each
+/// file's top-level `services:` section. This avoids requiring Docker at
+/// config load time while still enabling auto-discovery of compose services
+/// as valid `depends_on` targets.
 ///
-/// Returns an empty vec if the file cannot be read or has no services section.
-pub fn discover_compose_services(compose_file: &Path) -> Vec<String> {
+/// With override files, the result is the merged service set: every name
+/// from the base file, followed by names that only the overrides add.
+/// Files that cannot be read contribute nothing.
+pub fn discover_compose_services(compose_files: &[PathBuf]) -> Vec<String> {
+    let mut services: Vec<String> = Vec::new();
+    for compose_file in compose_files {
+        for name in discover_file_services(compose_file) {
+            if !services.contains(&name) {
+                services.push(name);
+            }
+        }
+    }
+    services
+}
+
+/// Service names declared in a single compose file.
+fn discover_file_services(compose_file: &Path) -> Vec<String> {
     let content = match std::fs::read_to_string(compose_file) {
         Ok(c) => c,
         Err(e) => {
@@ -225,7 +237,7 @@ mod tests {
         )
         .unwrap();
 
-        let services = discover_compose_services(&path);
+        let services = discover_compose_services(&[path]);
         assert_eq!(services, vec!["postgres", "redis"]);
     }
 
@@ -253,7 +265,7 @@ volumes:
 ";
         std::fs::write(&path, content).unwrap();
 
-        let services = discover_compose_services(&path);
+        let services = discover_compose_services(&[path]);
         assert_eq!(services, vec!["postgres", "mailpit"]);
     }
 
@@ -263,7 +275,7 @@ volumes:
         let path = dir.path().join("docker-compose.yml");
         std::fs::write(&path, "").unwrap();
 
-        let services = discover_compose_services(&path);
+        let services = discover_compose_services(&[path]);
         assert!(services.is_empty());
     }
 
@@ -273,14 +285,14 @@ volumes:
         let path = dir.path().join("docker-compose.yml");
         std::fs::write(&path, "version: '3.8'\nvolumes:\n  pgdata:\n").unwrap();
 
-        let services = discover_compose_services(&path);
+        let services = discover_compose_services(&[path]);
         assert!(services.is_empty());
     }
 
     #[test]
     fn discover_services_missing_file() {
-        let path = Path::new("/nonexistent/docker-compose.yml");
-        let services = discover_compose_services(path);
+        let path = PathBuf::from("/nonexistent/docker-compose.yml");
+        let services = discover_compose_services(&[path]);
         assert!(services.is_empty());
     }
 
@@ -291,7 +303,39 @@ volumes:
         let content = "services:\n\tdb:\n\t\timage: postgres\n\tcache:\n\t\timage: redis\n";
         std::fs::write(&path, content).unwrap();
 
-        let services = discover_compose_services(&path);
+        let services = discover_compose_services(&[path]);
         assert_eq!(services, vec!["db", "cache"]);
     }
+
+    #[test]
+    fn discover_services_merges_override_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path().join("compose.yml");
+        let local = dir.path().join("compose.local.yml");
+        std::fs::write(
+            &base,
+            "services:\n  postgres:\n    image: postgres:16\n  redis:\n    image: redis:7\n",
+        )
+        .unwrap();
+        std::fs::write(
+            &local,
+            "services:\n  redis:\n    ports:\n      - '6380:6379'\n  mailpit:\n    image: axllent/mailpit\n",
+        )
+        .unwrap();
+
+        let services = discover_compose_services(&[base, local]);
+        assert_eq!(services, vec!["postgres", "redis", "mailpit"]);
+    }
+
+    #[test]
+    fn file_args_keep_merge_order() {
+        let files = [
+            PathBuf::from("compose.yml"),
+            PathBuf::from("compose.local.yml"),
+        ];
+        assert_eq!(
+            file_args(&files),
+            vec!["-f", "compose.yml", "-f", "compose.local.yml"]
+        );
+    }
 }
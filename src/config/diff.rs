@@ -1,4 +1,7 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+use serde_json::Value;
 
 use crate::config::model::DevrigConfig;
 
@@ -93,6 +96,123 @@ pub fn diff_configs(old: &DevrigConfig, new: &DevrigConfig) -> ConfigDiff {
     }
 }
 
+/// One value that differs between two configs. `path` is dotted from the
+/// entry's name (`api.port`, `api.env.RUST_LOG`); a side is `None` when the
+/// value is unset there.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldChange {
+    pub path: String,
+    pub old: Option<Value>,
+    pub new: Option<Value>,
+}
+
+/// Added, removed and changed entries of one config table.
+#[derive(Debug, Default, Serialize)]
+pub struct SectionDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<FieldChange>,
+}
+
+impl SectionDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Field-level comparison of two configs, for `devrig config diff`.
+#[derive(Debug, Default, Serialize)]
+pub struct DetailedDiff {
+    pub services: SectionDiff,
+    pub docker: SectionDiff,
+    pub env: SectionDiff,
+    pub cluster_deploy: SectionDiff,
+}
+
+impl DetailedDiff {
+    pub fn is_empty(&self) -> bool {
+        self.sections()
+            .iter()
+            .all(|(_, section)| section.is_empty())
+    }
+
+    /// Each section with its config table name, in display order.
+    pub fn sections(&self) -> [(&'static str, &SectionDiff); 4] {
+        [
+            ("services", &self.services),
+            ("docker", &self.docker),
+            ("env", &self.env),
+            ("cluster.deploy", &self.cluster_deploy),
+        ]
+    }
+}
+
+/// Record every value under `path` that differs between `old` and `new`.
+/// Tables present on both sides are walked key by key; anything else
+/// compares as a whole, and `null` counts as unset.
+fn collect_changes(
+    path: &str,
+    old: Option<&Value>,
+    new: Option<&Value>,
+    out: &mut Vec<FieldChange>,
+) {
+    let old = old.filter(|v| !v.is_null());
+    let new = new.filter(|v| !v.is_null());
+    if let (Some(Value::Object(old_table)), Some(Value::Object(new_table))) = (old, new) {
+        let keys: BTreeSet<&String> = old_table.keys().chain(new_table.keys()).collect();
+        for key in keys {
+            collect_changes(
+                &format!("{}.{}", path, key),
+                old_table.get(key),
+                new_table.get(key),
+                out,
+            );
+        }
+    } else if old != new {
+        out.push(FieldChange {
+            path: path.to_string(),
+            old: old.cloned(),
+            new: new.cloned(),
+        });
+    }
+}
+
+fn diff_section<V: Serialize>(
+    old: &BTreeMap<String, V>,
+    new: &BTreeMap<String, V>,
+) -> serde_json::Result<SectionDiff> {
+    let to_values = |map: &BTreeMap<String, V>| -> serde_json::Result<BTreeMap<String, Value>> {
+        map.iter()
+            .map(|(k, v)| Ok((k.clone(), serde_json::to_value(v)?)))
+            .collect()
+    };
+    let (old, new) = (to_values(old)?, to_values(new)?);
+    let (added, removed, changed_names) = diff_map(&old, &new);
+    let mut changed = Vec::new();
+    for name in changed_names {
+        collect_changes(&name, old.get(&name), new.get(&name), &mut changed);
+    }
+    Ok(SectionDiff {
+        added,
+        removed,
+        changed,
+    })
+}
+
+/// Compare services, docker, top-level env and cluster deploys field by
+/// field.
+pub fn diff_details(old: &DevrigConfig, new: &DevrigConfig) -> serde_json::Result<DetailedDiff> {
+    let no_deploys = BTreeMap::new();
+    let old_deploys = old.cluster.as_ref().map_or(&no_deploys, |c| &c.deploy);
+    let new_deploys = new.cluster.as_ref().map_or(&no_deploys, |c| &c.deploy);
+    Ok(DetailedDiff {
+        services: diff_section(&old.services, &new.services)?,
+        docker: diff_section(&old.docker, &new.docker)?,
+        env: diff_section(&old.env, &new.env)?,
+        cluster_deploy: diff_section(old_deploys, new_deploys)?,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -100,9 +220,13 @@ mod tests {
 
     fn minimal_config() -> DevrigConfig {
         DevrigConfig {
+            disabled: Default::default(),
             project: ProjectConfig {
                 name: "test".to_string(),
                 env_file: None,
+                shell_env_passthrough: Default::default(),
+                vars: BTreeMap::new(),
+                env_prefix: None,
             },
             services: BTreeMap::new(),
             docker: BTreeMap::new(),
@@ -112,19 +236,30 @@ mod tests {
             env: BTreeMap::new(),
             network: None,
             links: BTreeMap::new(),
+            profiles: BTreeMap::new(),
+            secrets: BTreeMap::new(),
         }
     }
 
     fn make_service(command: &str, port: Option<u16>) -> ServiceConfig {
         ServiceConfig {
+            enabled: Default::default(),
             path: None,
             command: command.to_string(),
             port: port.map(Port::Fixed),
             protocol: None,
             env: BTreeMap::new(),
             env_file: None,
-            depends_on: vec![],
+            depends_on: Default::default(),
+            ready_check: None,
             restart: None,
+            shell: None,
+            reload_strategy: None,
+            reload_signal: None,
+            max_log_lines_per_sec: None,
+            pre_start: None,
+            post_stop: None,
+            log_format: None,
         }
     }
 
@@ -205,4 +340,36 @@ mod tests {
         assert_eq!(diff.services_removed, vec!["worker"]);
         assert_eq!(diff.services_changed, vec!["api"]);
     }
+
+    #[test]
+    fn details_list_changed_fields_by_path() {
+        let mut a = minimal_config();
+        a.services
+            .insert("api".to_string(), make_service("cargo run", Some(3000)));
+        a.env.insert("RUST_LOG".to_string(), "info".to_string());
+        let mut b = minimal_config();
+        let mut api = make_service("cargo run", Some(4000));
+        api.env.insert("DB".to_string(), "postgres://".to_string());
+        b.services.insert("api".to_string(), api);
+
+        let diff = diff_details(&a, &b).unwrap();
+        assert_eq!(
+            diff.services.changed,
+            vec![
+                FieldChange {
+                    path: "api.env.DB".to_string(),
+                    old: None,
+                    new: Some(Value::from("postgres://")),
+                },
+                FieldChange {
+                    path: "api.port".to_string(),
+                    old: Some(Value::from(3000)),
+                    new: Some(Value::from(4000)),
+                },
+            ]
+        );
+        assert_eq!(diff.env.removed, vec!["RUST_LOG"]);
+        assert!(diff.docker.is_empty() && diff.cluster_deploy.is_empty());
+        assert!(diff_details(&a, &a).unwrap().is_empty());
+    }
 }
@@ -1,8 +1,9 @@
+use chrono::{SecondsFormat, Utc};
 use regex::Regex;
 use std::collections::{BTreeMap, HashMap};
 use std::sync::LazyLock;
 
-use crate::config::model::DevrigConfig;
+use crate::config::model::{ClusterConfig, DevrigConfig, Enabled, ReadyCheck, StringOrList};
 use crate::orchestrator::state::ClusterDeployState;
 
 /// Minimum Jaro-Winkler score to consider a template variable a close match.
@@ -86,6 +87,7 @@ pub fn resolve_template(
 ///
 /// Produced keys:
 ///   - `project.name`
+///   - `var.{name}`                  (from `[project.vars]`)
 ///   - `services.{name}.port`       (from resolved_ports key `"service:{name}"`)
 ///   - `docker.{name}.port`          (from resolved_ports key `"docker:{name}"`)
 ///   - `docker.{name}.ports.{pname}` (from resolved_ports key `"docker:{name}:{pname}"`)
@@ -98,6 +100,11 @@ pub fn build_template_vars(
     // project.name
     vars.insert("project.name".to_string(), config.project.name.clone());
 
+    // var.{name}
+    for (name, value) in &config.project.vars {
+        vars.insert(format!("var.{name}"), value.clone());
+    }
+
     // services.{name}.port
     for name in config.services.keys() {
         let port_key = format!("service:{name}");
@@ -108,21 +115,16 @@ pub fn build_template_vars(
 
     // docker.{name}.port and docker.{name}.ports.{pname}
     for (name, docker_cfg) in &config.docker {
-        // Single port
-        let port_key = format!("docker:{name}");
-        if let Some(&port) = resolved_ports.get(&port_key) {
-            vars.insert(format!("docker.{name}.port"), port.to_string());
-        }
-
-        // Named ports (canonical + short alias)
-        for pname in docker_cfg.ports.keys() {
-            let port_key = format!("docker:{name}:{pname}");
-            if let Some(&port) = resolved_ports.get(&port_key) {
-                let val = port.to_string();
-                vars.insert(format!("docker.{name}.ports.{pname}"), val.clone());
-                vars.insert(format!("docker.{name}.port_{pname}"), val);
-            }
-        }
+        let port = resolved_ports.get(&format!("docker:{name}")).copied();
+        let named_ports: BTreeMap<String, u16> = docker_cfg
+            .ports
+            .keys()
+            .filter_map(|pname| {
+                let port = resolved_ports.get(&format!("docker:{name}:{pname}"))?;
+                Some((pname.clone(), *port))
+            })
+            .collect();
+        insert_docker_port_vars(&mut vars, name, port, &named_ports);
     }
 
     // cluster.name
@@ -151,6 +153,146 @@ pub fn build_template_vars(
     vars
 }
 
+/// Add `docker.{name}.port` and, for each named port, `docker.{name}.ports.{pname}`
+/// and its `docker.{name}.port_{pname}` alias to `vars`.
+pub fn insert_docker_port_vars(
+    vars: &mut HashMap<String, String>,
+    name: &str,
+    port: Option<u16>,
+    named_ports: &BTreeMap<String, u16>,
+) {
+    if let Some(port) = port {
+        vars.insert(format!("docker.{name}.port"), port.to_string());
+    }
+    for (pname, port) in named_ports {
+        let val = port.to_string();
+        vars.insert(format!("docker.{name}.ports.{pname}"), val.clone());
+        vars.insert(format!("docker.{name}.port_{pname}"), val);
+    }
+}
+
+/// Add the built-in template sources to `vars`:
+///   - `env.{NAME}`  the devrig process's environment variable `NAME`
+///   - `now`         the current time as an RFC 3339 timestamp (UTC)
+///   - `uuid`        a random version 4 UUID
+///
+/// Entries already in `vars` are kept, so a caller that resolves the config
+/// more than once can pin `now` and `uuid` by adding them up front.
+pub fn add_builtin_vars(vars: &mut HashMap<String, String>) {
+    for (key, value) in std::env::vars_os() {
+        if let (Some(key), Some(value)) = (key.to_str(), value.to_str()) {
+            vars.entry(format!("env.{key}"))
+                .or_insert_with(|| value.to_string());
+        }
+    }
+    vars.entry("now".to_string())
+        .or_insert_with(|| Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true));
+    vars.entry("uuid".to_string()).or_insert_with(uuid_v4);
+}
+
+/// Resolve a resource's `enabled = "{{ ... }}"` template to a bool. Only the
+/// built-in sources are available, since nothing has started yet. An unset
+/// `env.*` variable resolves to an empty string, i.e. disabled.
+pub fn resolve_enabled(template: &str) -> Result<bool, String> {
+    let mut vars = HashMap::new();
+    add_builtin_vars(&mut vars);
+    for cap in TEMPLATE_RE.captures_iter(template) {
+        if cap[1].starts_with("env.") {
+            vars.entry(cap[1].to_string()).or_default();
+        }
+    }
+    let resolved = resolve_template(template, &vars, "enabled").map_err(|errors| {
+        errors
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("; ")
+    })?;
+    Enabled::parse_resolved(&resolved)
+}
+
+/// Resolve `{{ var.NAME }}` references between `[project.vars]` entries in
+/// place, so every value is final before the rest of the config is
+/// interpolated. Vars may only reference other vars; a cycle is an error
+/// naming the chain, e.g. `var.a -> var.b -> var.a`.
+pub fn resolve_project_vars(vars: &mut BTreeMap<String, String>) -> Result<(), String> {
+    let mut resolved = HashMap::new();
+    for name in vars.keys() {
+        resolve_project_var(name, vars, &mut resolved, &mut Vec::new())?;
+    }
+    for (name, value) in vars.iter_mut() {
+        if let Some(v) = resolved.remove(&format!("var.{name}")) {
+            *value = v;
+        }
+    }
+    Ok(())
+}
+
+/// Resolve var `name` into `resolved` (keyed `var.{name}`) after the vars it
+/// references. `chain` holds the vars being resolved further up.
+fn resolve_project_var(
+    name: &str,
+    raw: &BTreeMap<String, String>,
+    resolved: &mut HashMap<String, String>,
+    chain: &mut Vec<String>,
+) -> Result<(), String> {
+    let key = format!("var.{name}");
+    if resolved.contains_key(&key) {
+        return Ok(());
+    }
+    if let Some(start) = chain.iter().position(|n| n == name) {
+        let cycle: Vec<String> = chain[start..]
+            .iter()
+            .chain(std::iter::once(&name.to_string()))
+            .map(|n| format!("var.{n}"))
+            .collect();
+        return Err(format!("cyclic reference: {}", cycle.join(" -> ")));
+    }
+
+    let value = &raw[name];
+    chain.push(name.to_string());
+    for cap in TEMPLATE_RE.captures_iter(value) {
+        if let Some(dep) = cap[1].strip_prefix("var.") {
+            if raw.contains_key(dep) {
+                resolve_project_var(dep, raw, resolved, chain)?;
+            }
+        }
+    }
+    chain.pop();
+
+    // Unknown references are suggested against every var, resolved or not.
+    let known: HashMap<String, String> = raw
+        .keys()
+        .map(|k| (format!("var.{k}"), String::new()))
+        .chain(resolved.iter().map(|(k, v)| (k.clone(), v.clone())))
+        .collect();
+    let value = resolve_template(value, &known, &format!("project.vars.{name}")).map_err(|errors| {
+        errors
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("; ")
+    })?;
+    resolved.insert(key, value);
+    Ok(())
+}
+
+/// A random version 4 UUID in its hyphenated form.
+fn uuid_v4() -> String {
+    let mut bytes: [u8; 16] = rand::random();
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex = hex::encode(bytes);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
 /// Build template variables from cluster image build results.
 ///
 /// Produced keys:
@@ -172,14 +314,104 @@ pub fn build_cluster_image_vars(
     vars
 }
 
-/// Walk every service env value and project-level `[env]` value in `config`
+/// Merge `[cluster].env` with `[cluster.deploy.<name>].env` (deploy wins) and
+/// resolve template expressions in the result.
+pub fn resolve_cluster_deploy_env(
+    cluster: &ClusterConfig,
+    deploy_name: &str,
+    vars: &HashMap<String, String>,
+) -> Result<BTreeMap<String, String>, Vec<TemplateError>> {
+    let mut merged: BTreeMap<String, (String, String)> = cluster
+        .env
+        .iter()
+        .map(|(k, v)| (k.clone(), (format!("cluster.env.{k}"), v.clone())))
+        .collect();
+    if let Some(deploy) = cluster.deploy.get(deploy_name) {
+        for (k, v) in &deploy.env {
+            merged.insert(
+                k.clone(),
+                (format!("cluster.deploy.{deploy_name}.env.{k}"), v.clone()),
+            );
+        }
+    }
+
+    let mut resolved = BTreeMap::new();
+    let mut all_errors: Vec<TemplateError> = Vec::new();
+    for (key, (field_context, value)) in merged {
+        match resolve_template(&value, vars, &field_context) {
+            Ok(v) => {
+                resolved.insert(key, v);
+            }
+            Err(mut errs) => all_errors.append(&mut errs),
+        }
+    }
+
+    if all_errors.is_empty() {
+        Ok(resolved)
+    } else {
+        Err(all_errors)
+    }
+}
+
+/// Resolve templates in the `url` of every `http` check in `check`,
+/// including checks nested in `all_of` / `any_of`.
+fn resolve_ready_check_urls(
+    check: &mut ReadyCheck,
+    vars: &HashMap<String, String>,
+    field_context: &str,
+    errors: &mut Vec<TemplateError>,
+) {
+    match check {
+        ReadyCheck::Http { url, .. } => match resolve_template(url, vars, field_context) {
+            Ok(resolved) => *url = resolved,
+            Err(mut errs) => errors.append(&mut errs),
+        },
+        ReadyCheck::AllOf { checks, .. } | ReadyCheck::AnyOf { checks, .. } => {
+            for child in checks {
+                resolve_ready_check_urls(child, vars, field_context, errors);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Resolve templates in each element of a docker `command` or `entrypoint`
+/// independently. Errors name the element as `{field_context}[i]` for lists.
+pub fn resolve_command_templates(
+    command: &mut StringOrList,
+    vars: &HashMap<String, String>,
+    field_context: &str,
+    errors: &mut Vec<TemplateError>,
+) {
+    let is_list = command.0.len() > 1;
+    for (i, arg) in command.0.iter_mut().enumerate() {
+        let field = if is_list {
+            format!("{field_context}[{i}]")
+        } else {
+            field_context.to_string()
+        };
+        match resolve_template(arg, vars, &field) {
+            Ok(resolved) => *arg = resolved,
+            Err(mut errs) => errors.append(&mut errs),
+        }
+    }
+}
+
+/// Walk every service env value, service `http` ready check URL, docker
+/// `command` and `entrypoint`, and project-level `[env]` value in `config`
 /// and resolve template expressions.
+/// The built-in sources from [`add_builtin_vars`] are available alongside
+/// `vars`; a reference to an unset `env.` variable is an error like any
+/// other unresolved variable.
 ///
 /// All errors across all fields are collected and returned together.
 pub fn resolve_config_templates(
     config: &mut DevrigConfig,
     vars: &HashMap<String, String>,
 ) -> Result<(), Vec<TemplateError>> {
+    let mut vars = vars.clone();
+    add_builtin_vars(&mut vars);
+    let vars = &vars;
     let mut all_errors: Vec<TemplateError> = Vec::new();
 
     // Resolve project-level [env] templates
@@ -200,6 +432,22 @@ pub fn resolve_config_templates(
                 Err(mut errs) => all_errors.append(&mut errs),
             }
         }
+        if let Some(check) = &mut svc.ready_check {
+            let field_context = format!("services.{svc_name}.ready_check");
+            resolve_ready_check_urls(check, vars, &field_context, &mut all_errors);
+        }
+    }
+
+    // Resolve docker command and entrypoint arguments
+    for (docker_name, docker) in &mut config.docker {
+        if let Some(command) = &mut docker.command {
+            let field_context = format!("docker.{docker_name}.command");
+            resolve_command_templates(command, vars, &field_context, &mut all_errors);
+        }
+        if let Some(entrypoint) = &mut docker.entrypoint {
+            let field_context = format!("docker.{docker_name}.entrypoint");
+            resolve_command_templates(entrypoint, vars, &field_context, &mut all_errors);
+        }
     }
 
     if all_errors.is_empty() {
@@ -310,25 +558,35 @@ mod tests {
         services.insert(
             "api".to_string(),
             ServiceConfig {
+                enabled: Default::default(),
                 path: None,
                 command: "cargo run".to_string(),
                 port: Some(Port::Auto),
                 protocol: None,
                 env: BTreeMap::new(),
                 env_file: None,
-                depends_on: vec![],
+                depends_on: Default::default(),
+                ready_check: None,
                 restart: None,
+                shell: None,
+                reload_strategy: None,
+                reload_signal: None,
+                max_log_lines_per_sec: None,
+                pre_start: None,
+                post_stop: None,
+                log_format: None,
             },
         );
 
         let mut mailpit_ports = BTreeMap::new();
-        mailpit_ports.insert("smtp".to_string(), Port::Fixed(1025));
-        mailpit_ports.insert("ui".to_string(), Port::Fixed(8025));
+        mailpit_ports.insert("smtp".to_string(), Port::Fixed(1025).into());
+        mailpit_ports.insert("ui".to_string(), Port::Fixed(8025).into());
 
         let mut docker_map = BTreeMap::new();
         docker_map.insert(
             "postgres".to_string(),
             DockerConfig {
+                enabled: Default::default(),
                 image: "postgres:16".to_string(),
                 port: Some(Port::Fixed(5432)),
                 container_port: None,
@@ -340,13 +598,22 @@ mod tests {
                 entrypoint: None,
                 ready_check: None,
                 init: vec![],
-                depends_on: vec![],
+                depends_on: Default::default(),
                 registry_auth: None,
+                pull_retries: None,
+                restart: None,
+                stop_timeout: None,
+                healthcheck: None,
+                mem_limit: None,
+                cpus: None,
+                network_aliases: Vec::new(),
+                extra_hosts: Vec::new(),
             },
         );
         docker_map.insert(
             "mailpit".to_string(),
             DockerConfig {
+                enabled: Default::default(),
                 image: "axllent/mailpit:latest".to_string(),
                 port: None,
                 container_port: None,
@@ -358,15 +625,27 @@ mod tests {
                 entrypoint: None,
                 ready_check: None,
                 init: vec![],
-                depends_on: vec![],
+                depends_on: Default::default(),
                 registry_auth: None,
+                pull_retries: None,
+                restart: None,
+                stop_timeout: None,
+                healthcheck: None,
+                mem_limit: None,
+                cpus: None,
+                network_aliases: Vec::new(),
+                extra_hosts: Vec::new(),
             },
         );
 
         let config = DevrigConfig {
+            disabled: Default::default(),
             project: ProjectConfig {
                 name: "myapp".to_string(),
                 env_file: None,
+                shell_env_passthrough: Default::default(),
+                vars: BTreeMap::new(),
+                env_prefix: None,
             },
             services,
             docker: docker_map,
@@ -376,6 +655,8 @@ mod tests {
             env: BTreeMap::new(),
             network: None,
             links: BTreeMap::new(),
+            profiles: BTreeMap::new(),
+            secrets: BTreeMap::new(),
         };
 
         let mut resolved_ports = HashMap::new();
@@ -400,9 +681,13 @@ mod tests {
     fn dashboard_template_vars() {
         use crate::config::model::{DashboardConfig, OtelConfig};
         let config = DevrigConfig {
+            disabled: Default::default(),
             project: ProjectConfig {
                 name: "myapp".to_string(),
                 env_file: None,
+                shell_env_passthrough: Default::default(),
+                vars: BTreeMap::new(),
+                env_prefix: None,
             },
             services: BTreeMap::new(),
             docker: BTreeMap::new(),
@@ -416,10 +701,14 @@ mod tests {
                     http_port: Port::Fixed(14318),
                     ..OtelConfig::default()
                 }),
+                title: None,
+                tls: None,
             }),
             env: BTreeMap::new(),
             network: None,
             links: BTreeMap::new(),
+            profiles: BTreeMap::new(),
+            secrets: BTreeMap::new(),
         };
 
         let mut resolved_ports = HashMap::new();
@@ -435,9 +724,13 @@ mod tests {
     #[test]
     fn cluster_name_template_var() {
         let mut config = DevrigConfig {
+            disabled: Default::default(),
             project: ProjectConfig {
                 name: "myapp".to_string(),
                 env_file: None,
+                shell_env_passthrough: Default::default(),
+                vars: BTreeMap::new(),
+                env_prefix: None,
             },
             services: BTreeMap::new(),
             docker: BTreeMap::new(),
@@ -454,11 +747,16 @@ mod tests {
                 logs: None,
                 registries: vec![],
                 k3s_args: vec![],
+                k3s_image: None,
+                env: BTreeMap::new(),
+                command_retries: 3,
             }),
             dashboard: None,
             env: BTreeMap::new(),
             network: None,
             links: BTreeMap::new(),
+            profiles: BTreeMap::new(),
+            secrets: BTreeMap::new(),
         };
 
         let resolved_ports = HashMap::new();
@@ -474,9 +772,13 @@ mod tests {
     #[test]
     fn resolve_config_templates_resolves_global_env() {
         let mut config = DevrigConfig {
+            disabled: Default::default(),
             project: ProjectConfig {
                 name: "myapp".to_string(),
                 env_file: None,
+                shell_env_passthrough: Default::default(),
+                vars: BTreeMap::new(),
+                env_prefix: None,
             },
             services: BTreeMap::new(),
             docker: BTreeMap::new(),
@@ -492,6 +794,8 @@ mod tests {
             ]),
             network: None,
             links: BTreeMap::new(),
+            profiles: BTreeMap::new(),
+            secrets: BTreeMap::new(),
         };
 
         let mut vars = HashMap::new();
@@ -505,4 +809,275 @@ mod tests {
         );
         assert_eq!(config.env.get("PLAIN").unwrap(), "no-templates-here");
     }
+
+    #[test]
+    fn resolve_config_templates_resolves_service_ready_check_url() {
+        let mut config: DevrigConfig = toml::from_str(
+            r#"
+            [project]
+            name = "myapp"
+
+            [services.api]
+            command = "cargo run"
+            port = "auto"
+            ready_check = { type = "any_of", checks = [
+                { type = "http", url = "http://localhost:{{ services.api.port }}/health" },
+                { type = "log", match = "listening" },
+            ] }
+            "#,
+        )
+        .unwrap();
+
+        let mut vars = HashMap::new();
+        vars.insert("services.api.port".to_string(), "41234".to_string());
+        resolve_config_templates(&mut config, &vars).unwrap();
+
+        let Some(ReadyCheck::AnyOf { checks, .. }) = &config.services["api"].ready_check else {
+            panic!("expected any_of ready check");
+        };
+        assert_eq!(
+            checks[0],
+            ReadyCheck::Http {
+                url: "http://localhost:41234/health".to_string(),
+                timeout: None,
+            }
+        );
+    }
+
+    #[test]
+    fn project_vars_resolve_through_each_other_into_templates() {
+        let mut config: DevrigConfig = toml::from_str(
+            r#"
+            [project]
+            name = "myapp"
+
+            [project.vars]
+            db_name = "{{ var.prefix }}_dev"
+            prefix = "shop"
+            db_url = "postgres://localhost/{{ var.db_name }}"
+
+            [services.api]
+            command = "cargo run"
+            env = { DATABASE_URL = "{{ var.db_url }}" }
+            "#,
+        )
+        .unwrap();
+        resolve_project_vars(&mut config.project.vars).unwrap();
+        assert_eq!(config.project.vars["db_name"], "shop_dev");
+
+        let vars = build_template_vars(&config, &HashMap::new());
+        resolve_config_templates(&mut config, &vars).unwrap();
+        assert_eq!(
+            config.services["api"].env["DATABASE_URL"],
+            "postgres://localhost/shop_dev"
+        );
+    }
+
+    #[test]
+    fn project_var_cycles_and_unknown_refs_are_errors() {
+        let vars = |pairs: &[(&str, &str)]| -> BTreeMap<String, String> {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect()
+        };
+
+        let mut cyclic = vars(&[
+            ("a", "{{ var.b }}"),
+            ("b", "x-{{ var.c }}"),
+            ("c", "{{ var.a }}"),
+        ]);
+        assert_eq!(
+            resolve_project_vars(&mut cyclic).unwrap_err(),
+            "cyclic reference: var.a -> var.b -> var.c -> var.a"
+        );
+
+        let mut selfref = vars(&[("a", "{{ var.a }}")]);
+        assert_eq!(
+            resolve_project_vars(&mut selfref).unwrap_err(),
+            "cyclic reference: var.a -> var.a"
+        );
+
+        let mut typo = vars(&[("db_name", "app"), ("url", "{{ var.db_nam }}")]);
+        let err = resolve_project_vars(&mut typo).unwrap_err();
+        assert!(err.contains("project.vars.url"), "{err}");
+        assert!(err.contains("did you mean `var.db_name`?"), "{err}");
+    }
+
+    #[test]
+    fn docker_command_and_entrypoint_resolved() {
+        let mut config: DevrigConfig = toml::from_str(
+            r#"
+[project]
+name = "myapp"
+
+[docker.api]
+image = "api:latest"
+port = 8080
+command = ["--port", "{{ docker.api.port }}", "--name={{ project.name }}"]
+entrypoint = "/bin/{{ project.name }}"
+"#,
+        )
+        .unwrap();
+        let ports = HashMap::from([("docker:api".to_string(), 8080u16)]);
+        let vars = build_template_vars(&config, &ports);
+        resolve_config_templates(&mut config, &vars).unwrap();
+
+        let api = &config.docker["api"];
+        assert_eq!(
+            api.command.as_ref().unwrap().as_slice(),
+            ["--port", "8080", "--name=myapp"]
+        );
+        assert_eq!(api.entrypoint.as_ref().unwrap().as_slice(), ["/bin/myapp"]);
+    }
+
+    #[test]
+    fn docker_command_errors_name_the_element() {
+        let mut config: DevrigConfig = toml::from_str(
+            r#"
+[project]
+name = "myapp"
+
+[docker.api]
+image = "api:latest"
+command = ["serve", "--db", "{{ docker.db.port }}"]
+entrypoint = "{{ docker.api.prot }}"
+"#,
+        )
+        .unwrap();
+        let errors = resolve_config_templates(&mut config, &HashMap::new()).unwrap_err();
+        let fields: Vec<String> = errors
+            .iter()
+            .map(|TemplateError::UnresolvedVariable { field, .. }| field.clone())
+            .collect();
+        assert_eq!(
+            fields,
+            vec!["docker.api.command[2]", "docker.api.entrypoint"]
+        );
+    }
+
+    fn config_with_env(env: &[(&str, &str)]) -> DevrigConfig {
+        let mut config: DevrigConfig = toml::from_str("[project]\nname = \"myapp\"\n").unwrap();
+        config.env = env
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        config
+    }
+
+    #[test]
+    fn resolves_process_env_vars() {
+        std::env::set_var("DEVRIG_TEMPLATE_TEST_HOME", "/home/dev");
+        let mut config =
+            config_with_env(&[("CACHE_DIR", "{{ env.DEVRIG_TEMPLATE_TEST_HOME }}/.cache")]);
+        resolve_config_templates(&mut config, &HashMap::new()).unwrap();
+        assert_eq!(config.env["CACHE_DIR"], "/home/dev/.cache");
+    }
+
+    #[test]
+    fn unset_env_var_is_reported_per_field() {
+        let mut config = config_with_env(&[
+            ("A", "{{ env.DEVRIG_TEMPLATE_TEST_UNSET_A }}"),
+            ("B", "x-{{ env.DEVRIG_TEMPLATE_TEST_UNSET_B }}"),
+        ]);
+        let errors = resolve_config_templates(&mut config, &HashMap::new()).unwrap_err();
+        let missing: Vec<(String, String)> = errors
+            .iter()
+            .map(|err| match err {
+                TemplateError::UnresolvedVariable {
+                    field, variable, ..
+                } => (field.clone(), variable.clone()),
+            })
+            .collect();
+        assert_eq!(
+            missing,
+            vec![
+                (
+                    "env.A".to_string(),
+                    "env.DEVRIG_TEMPLATE_TEST_UNSET_A".to_string()
+                ),
+                (
+                    "env.B".to_string(),
+                    "env.DEVRIG_TEMPLATE_TEST_UNSET_B".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn uuid_is_v4_and_shared_within_one_resolution() {
+        let mut config = config_with_env(&[("RUN_ID", "{{ uuid }}"), ("TAG", "run-{{uuid}}")]);
+        resolve_config_templates(&mut config, &HashMap::new()).unwrap();
+        let id = &config.env["RUN_ID"];
+        let groups: Vec<usize> = id.split('-').map(str::len).collect();
+        assert_eq!(groups, vec![8, 4, 4, 4, 12]);
+        assert!(id.chars().all(|c| c == '-' || c.is_ascii_hexdigit()));
+        assert_eq!(&id[14..15], "4");
+        assert!(matches!(&id[19..20], "8" | "9" | "a" | "b"));
+        assert_eq!(config.env["TAG"], format!("run-{id}"));
+
+        let mut again = config_with_env(&[("RUN_ID", "{{ uuid }}")]);
+        resolve_config_templates(&mut again, &HashMap::new()).unwrap();
+        assert_ne!(&again.env["RUN_ID"], id);
+    }
+
+    #[test]
+    fn now_is_an_rfc3339_timestamp() {
+        let mut config = config_with_env(&[("STARTED_AT", "{{ now }}")]);
+        let before = Utc::now() - chrono::Duration::seconds(1);
+        resolve_config_templates(&mut config, &HashMap::new()).unwrap();
+        let parsed = chrono::DateTime::parse_from_rfc3339(&config.env["STARTED_AT"]).unwrap();
+        assert!(parsed >= before && parsed <= Utc::now());
+    }
+
+    #[test]
+    fn pinned_builtins_are_kept() {
+        let mut vars = HashMap::from([
+            ("uuid".to_string(), "fixed-id".to_string()),
+            ("now".to_string(), "2026-01-01T00:00:00Z".to_string()),
+        ]);
+        add_builtin_vars(&mut vars);
+        assert_eq!(vars["uuid"], "fixed-id");
+        assert_eq!(vars["now"], "2026-01-01T00:00:00Z");
+
+        let mut config = config_with_env(&[("ID", "{{ uuid }}@{{ now }}")]);
+        resolve_config_templates(&mut config, &vars).unwrap();
+        assert_eq!(config.env["ID"], "fixed-id@2026-01-01T00:00:00Z");
+    }
+
+    #[test]
+    fn cluster_deploy_env_merges_and_resolves() {
+        let cluster: ClusterConfig = toml::from_str(
+            r#"
+            env = { LOG_LEVEL = "info", REGION = "local" }
+
+            [deploy.api]
+            context = "./api"
+            manifests = "./k8s"
+            env = { LOG_LEVEL = "debug", DB_PORT = "{{ docker.postgres.port }}" }
+            "#,
+        )
+        .unwrap();
+
+        let env = resolve_cluster_deploy_env(&cluster, "api", &make_vars()).unwrap();
+        assert_eq!(env["LOG_LEVEL"], "debug");
+        assert_eq!(env["REGION"], "local");
+        assert_eq!(env["DB_PORT"], "5432");
+
+        let errs = resolve_cluster_deploy_env(
+            &toml::from_str(
+                r#"
+                [deploy.api]
+                context = "./api"
+                manifests = "./k8s"
+                env = { X = "{{ nope.port }}" }
+                "#,
+            )
+            .unwrap(),
+            "api",
+            &make_vars(),
+        )
+        .unwrap_err();
+        assert!(errs[0].to_string().contains("cluster.deploy.api.env.X"));
+    }
 }
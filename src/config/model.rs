@@ -1,7 +1,8 @@
 use serde::{de, Deserialize, Deserializer, Serialize};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct DevrigConfig {
     pub project: ProjectConfig,
     #[serde(default)]
@@ -20,36 +21,222 @@ pub struct DevrigConfig {
     pub network: Option<NetworkConfig>,
     #[serde(default)]
     pub links: BTreeMap<String, String>,
+    /// Named subsets of resources for `devrig start --profile <name>`.
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Vec<String>>,
+    /// Values read from external commands at load time, referenced as
+    /// `$NAME` like any other env var.
+    #[serde(default)]
+    pub secrets: BTreeMap<String, SecretSource>,
+    /// Resources left out because they are not `enabled`, so a dependency
+    /// on one can be reported as such rather than as unknown.
+    #[serde(skip)]
+    pub disabled: BTreeSet<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct ProjectConfig {
     pub name: String,
     #[serde(default)]
     pub env_file: Option<String>,
+    /// Which host environment variables supervised services inherit.
+    /// Default: "all".
+    #[serde(default)]
+    pub shell_env_passthrough: EnvPassthrough,
+    /// User-defined template variables, referenced as `{{ var.NAME }}`.
+    /// Values may use `$VAR` and other vars; see
+    /// [`resolve_project_vars`](crate::config::interpolate::resolve_project_vars).
+    #[serde(default)]
+    pub vars: BTreeMap<String, String>,
+    /// Prefix of the injected discovery vars, e.g. `"APP_"` for
+    /// `APP_POSTGRES_URL`. Default: [`DEFAULT_ENV_PREFIX`].
+    #[serde(default)]
+    pub env_prefix: Option<String>,
+}
+
+/// A `[secrets]` entry, e.g. `db_password = { exec = "op read op://vault/db/password" }`.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct SecretSource {
+    /// Shell command whose stdout (minus the trailing newline) is the value.
+    pub exec: String,
+}
+
+/// Prefix of discovery vars when `project.env_prefix` is not set.
+pub const DEFAULT_ENV_PREFIX: &str = "DEVRIG_";
+
+impl ProjectConfig {
+    /// The prefix for injected discovery vars (`<PREFIX><NAME>_URL`, ...).
+    pub fn env_prefix(&self) -> &str {
+        self.env_prefix.as_deref().unwrap_or(DEFAULT_ENV_PREFIX)
+    }
+}
+
+/// Whether `prefix` can start an environment variable name: ASCII letters,
+/// digits and `_`, not starting with a digit.
+pub fn is_valid_env_prefix(prefix: &str) -> bool {
+    !prefix.is_empty()
+        && !prefix.starts_with(|c: char| c.is_ascii_digit())
+        && prefix.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Host environment inheritance for supervised services: `"all"`, `"none"`,
+/// or an allowlist of variable names.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum EnvPassthrough {
+    #[default]
+    All,
+    None,
+    Only(Vec<String>),
+}
+
+impl EnvPassthrough {
+    /// Whether the host variable `key` should be passed through to a service.
+    pub fn allows(&self, key: &str) -> bool {
+        match self {
+            EnvPassthrough::All => true,
+            EnvPassthrough::None => false,
+            EnvPassthrough::Only(keys) => keys.iter().any(|k| k == key),
+        }
+    }
+}
+
+impl Serialize for EnvPassthrough {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            EnvPassthrough::All => serializer.serialize_str("all"),
+            EnvPassthrough::None => serializer.serialize_str("none"),
+            EnvPassthrough::Only(keys) => keys.serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for EnvPassthrough {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct EnvPassthroughVisitor;
+
+        impl<'de> de::Visitor<'de> for EnvPassthroughVisitor {
+            type Value = EnvPassthrough;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(
+                    f,
+                    "\"all\", \"none\", or a list of environment variable names"
+                )
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                match v {
+                    "all" => Ok(EnvPassthrough::All),
+                    "none" => Ok(EnvPassthrough::None),
+                    other => Err(E::custom(format!(
+                        "expected \"all\" or \"none\" but got \"{}\"",
+                        other
+                    ))),
+                }
+            }
+
+            fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut keys = Vec::new();
+                while let Some(key) = seq.next_element::<String>()? {
+                    keys.push(key);
+                }
+                Ok(EnvPassthrough::Only(keys))
+            }
+        }
+
+        deserializer.deserialize_any(EnvPassthroughVisitor)
+    }
 }
 
-#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 pub struct ServiceConfig {
+    /// `false`, or a template that resolves to false, leaves this service
+    /// out of the project. See [`Enabled`].
+    #[serde(default, skip_serializing_if = "Enabled::is_true")]
+    pub enabled: Enabled,
     #[serde(default)]
     pub path: Option<String>,
     pub command: String,
     #[serde(default)]
     pub port: Option<Port>,
-    /// Protocol for this service's port: "http" (default), "https", "tcp", "udp".
-    /// Used by the dashboard to generate correct clickable links.
+    /// Protocol for this service's port: "http" (default), "https", "grpc",
+    /// "tcp", "udp". Used by the dashboard to generate correct clickable
+    /// links, and as the scheme of `DEVRIG_<NAME>_URL`.
     #[serde(default)]
     pub protocol: Option<String>,
     #[serde(default)]
     pub env: BTreeMap<String, String>,
+    /// One or more `.env` files, lowest precedence first. A trailing `?`
+    /// marks a file as optional.
     #[serde(default)]
-    pub env_file: Option<String>,
+    pub env_file: Option<StringOrList>,
     #[serde(default)]
-    pub depends_on: Vec<String>,
+    pub depends_on: DependsOn,
+    /// Readiness probe (`tcp`, `http`, `grpc` or `log`) that services
+    /// depending on this one wait for before starting.
+    #[serde(default)]
+    pub ready_check: Option<ReadyCheck>,
     #[serde(default)]
     pub restart: Option<RestartConfig>,
+    /// Interpreter used to run `command`, e.g. "bash -c", or "none" to exec
+    /// the command directly. Defaults to the platform login shell.
+    #[serde(default)]
+    pub shell: Option<String>,
+    /// How `devrig reload-env` applies env changes. Defaults to restarting
+    /// the service.
+    #[serde(default)]
+    pub reload_strategy: Option<ReloadStrategy>,
+    /// Signal sent by `devrig reload-env` with `reload_strategy = "signal"`.
+    /// Defaults to SIGHUP.
+    #[serde(default)]
+    pub reload_signal: Option<String>,
+    /// Log entries per second kept from this service's output; the rest are
+    /// dropped and counted. Overrides `devrig start --max-log-rate`.
+    #[serde(default)]
+    pub max_log_lines_per_sec: Option<u32>,
+    /// Command(s) run to completion before the service starts, in its
+    /// working dir and env. The service doesn't start unless all exit 0.
+    #[serde(default)]
+    pub pre_start: Option<StringOrList>,
+    /// Command(s) run after the service stops, best-effort.
+    #[serde(default)]
+    pub post_stop: Option<StringOrList>,
+    /// Parse output lines as JSON or logfmt so the dashboard gets each
+    /// line's real level, message and timestamp. Off unless set.
+    #[serde(default)]
+    pub log_format: Option<LogFormat>,
+}
+
+/// How a running service picks up a changed env.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReloadStrategy {
+    /// Stop the service and start it again with the new env.
+    #[default]
+    Restart,
+    /// Leave the process running and send it `reload_signal`.
+    Signal,
 }
 
+/// Structured log format of a service's output, for `log_format`.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// One JSON object per line, e.g. `{"level":"warn","msg":"slow"}`.
+    Json,
+    /// `key=value` pairs, e.g. `level=warn msg="slow query"`.
+    Logfmt,
+    /// JSON for lines that parse as an object, logfmt otherwise.
+    Auto,
+}
+
+/// Signals accepted for `reload_signal`.
+pub const RELOAD_SIGNALS: &[&str] = &["SIGHUP", "SIGUSR1", "SIGUSR2", "SIGWINCH"];
+
+/// Default `reload_signal`.
+pub const DEFAULT_RELOAD_SIGNAL: &str = "SIGHUP";
+
 fn default_restart_policy() -> String {
     "on-failure".to_string()
 }
@@ -74,7 +261,11 @@ fn default_max_delay_ms() -> u64 {
     30000
 }
 
-#[derive(Debug, Clone, Deserialize, PartialEq)]
+fn default_retries_reset_after_ms() -> u64 {
+    60000
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 pub struct RestartConfig {
     #[serde(default = "default_restart_policy")]
     pub policy: String,
@@ -88,10 +279,21 @@ pub struct RestartConfig {
     pub initial_delay_ms: u64,
     #[serde(default = "default_max_delay_ms")]
     pub max_delay_ms: u64,
+    /// Once a run stays up this long, the restart counters start over, so
+    /// `max_restarts` limits crashes in a row rather than over a lifetime.
+    #[serde(default = "default_retries_reset_after_ms")]
+    pub retries_reset_after_ms: u64,
 }
 
-#[derive(Debug, Clone, Deserialize, PartialEq)]
+/// Upper bound for `[docker.*].stop_timeout`, in seconds.
+pub const MAX_STOP_TIMEOUT_SECS: u64 = 600;
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 pub struct DockerConfig {
+    /// `false`, or a template that resolves to false, leaves this container
+    /// out of the project. See [`Enabled`].
+    #[serde(default, skip_serializing_if = "Enabled::is_true")]
+    pub enabled: Enabled,
     pub image: String,
     #[serde(default)]
     pub port: Option<Port>,
@@ -101,12 +303,16 @@ pub struct DockerConfig {
     /// internal port. Defaults to the same as `port`.
     #[serde(default)]
     pub container_port: Option<u16>,
-    /// Protocol for this service's port: "http" (default), "https", "tcp", "udp".
-    /// Used by the dashboard to generate correct clickable links.
+    /// Protocol for this service's port: "http" (default), "https", "grpc",
+    /// "tcp", "udp". Used by the dashboard to generate correct clickable
+    /// links and as the scheme of `DEVRIG_<NAME>_URL`. "udp" also publishes
+    /// `port` as a UDP port.
     #[serde(default)]
     pub protocol: Option<String>,
+    /// Extra named ports. Values are a port, "auto", or either with a
+    /// `/udp` or `/tcp` suffix (e.g. "8053/udp").
     #[serde(default)]
-    pub ports: BTreeMap<String, Port>,
+    pub ports: BTreeMap<String, DockerPort>,
     #[serde(default)]
     pub env: BTreeMap<String, String>,
     #[serde(default)]
@@ -117,12 +323,260 @@ pub struct DockerConfig {
     pub entrypoint: Option<StringOrList>,
     #[serde(default)]
     pub ready_check: Option<ReadyCheck>,
+    /// Run once after the container first becomes ready, in order. Re-run
+    /// when the scripts change.
     #[serde(default)]
-    pub init: Vec<String>,
+    pub init: Vec<InitScript>,
     #[serde(default)]
-    pub depends_on: Vec<String>,
+    pub depends_on: DependsOn,
     #[serde(default)]
     pub registry_auth: Option<RegistryAuth>,
+    /// Times to retry a failed image pull, with exponential backoff.
+    /// Defaults to 3.
+    #[serde(default)]
+    pub pull_retries: Option<u32>,
+    /// Recreate the container when it exits or turns unhealthy. Without
+    /// this, containers are not watched after startup.
+    #[serde(default)]
+    pub restart: Option<RestartConfig>,
+    /// Seconds to wait after SIGTERM before Docker kills the container on
+    /// stop. Defaults to 10 (5 during `devrig delete`).
+    #[serde(default)]
+    pub stop_timeout: Option<u64>,
+    /// Docker HEALTHCHECK for the container, replacing the image's own.
+    /// Its status is tracked in `devrig ps` for as long as devrig runs.
+    #[serde(default)]
+    pub healthcheck: Option<DockerHealthcheck>,
+    /// Memory cap, a byte count with an optional `b`, `k`, `m` or `g` unit
+    /// (e.g. "512m"). See [`parse_mem_limit`].
+    #[serde(default)]
+    pub mem_limit: Option<String>,
+    /// CPU cap as a number of CPUs, e.g. 1.5.
+    #[serde(default)]
+    pub cpus: Option<f64>,
+    /// Extra DNS names the container answers to on the project network,
+    /// e.g. "db.internal".
+    #[serde(default)]
+    pub network_aliases: Vec<String>,
+    /// Extra `/etc/hosts` entries as "HOST:IP"; IP may be "host-gateway".
+    #[serde(default)]
+    pub extra_hosts: Vec<String>,
+}
+
+/// Smallest `mem_limit` Docker accepts.
+pub const MIN_MEM_LIMIT_BYTES: i64 = 6 << 20;
+
+/// Parse a `mem_limit` like "512m", "2g" or "1073741824" into bytes. Units
+/// are powers of 1024 and case-insensitive, with an optional trailing `b`
+/// ("512MB"), as in `docker run --memory`.
+pub fn parse_mem_limit(s: &str) -> Result<i64, String> {
+    let lower = s.trim().to_ascii_lowercase();
+    let (num, unit) = match lower.find(|c: char| !c.is_ascii_digit() && c != '.') {
+        Some(i) => lower.split_at(i),
+        None => (lower.as_str(), ""),
+    };
+    let multiplier: f64 = match unit.trim() {
+        "" | "b" => 1.0,
+        "k" | "kb" => 1024.0,
+        "m" | "mb" => 1024.0 * 1024.0,
+        "g" | "gb" => 1024.0 * 1024.0 * 1024.0,
+        other => return Err(format!("unknown unit `{}`", other)),
+    };
+    let n: f64 = num
+        .parse()
+        .map_err(|_| format!("`{}` is not a number", num))?;
+    let bytes = (n * multiplier) as i64;
+    if bytes < MIN_MEM_LIMIT_BYTES {
+        return Err("must be at least 6m".to_string());
+    }
+    Ok(bytes)
+}
+
+/// A native Docker healthcheck, run by the Docker daemon inside the
+/// container. Unset durations use Docker's defaults.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct DockerHealthcheck {
+    /// Command to run: a string runs through the container's shell
+    /// (`CMD-SHELL`), a list is exec'd directly (`CMD`).
+    pub test: StringOrList,
+    /// Seconds between checks.
+    #[serde(default)]
+    pub interval: Option<u64>,
+    /// Seconds a single check may take.
+    #[serde(default)]
+    pub timeout: Option<u64>,
+    /// Consecutive failures before the container is unhealthy.
+    #[serde(default)]
+    pub retries: Option<u32>,
+    /// Seconds after start during which failures don't count.
+    #[serde(default)]
+    pub start_period: Option<u64>,
+}
+
+impl DockerConfig {
+    /// Protocol to publish the main `port` with, from `protocol`.
+    pub fn port_protocol(&self) -> PortProtocol {
+        match self.protocol.as_deref() {
+            Some("udp") => PortProtocol::Udp,
+            _ => PortProtocol::Tcp,
+        }
+    }
+
+    /// `mem_limit` in bytes, or `None` when unset or invalid (validation
+    /// reports the latter).
+    pub fn memory_bytes(&self) -> Option<i64> {
+        self.mem_limit
+            .as_deref()
+            .and_then(|m| parse_mem_limit(m).ok())
+    }
+
+    /// `cpus` in the billionths of a CPU Docker's `NanoCpus` takes.
+    pub fn nano_cpus(&self) -> Option<i64> {
+        self.cpus.map(|c| (c * 1e9).round() as i64)
+    }
+}
+
+/// One `[docker.*].init` entry: an inline command / SQL string, a file,
+/// or a directory whose files run in name order. Paths are relative to the
+/// config file. Written as `"CREATE DATABASE app;"`, `{ file = "seed.sql" }`
+/// or `{ dir = "migrations" }`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InitScript {
+    Inline(String),
+    File(String),
+    Dir(String),
+}
+
+/// The table form of [`InitScript`].
+#[derive(Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct InitSourceTable {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    file: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    dir: Option<String>,
+}
+
+impl From<&str> for InitScript {
+    fn from(script: &str) -> Self {
+        InitScript::Inline(script.to_string())
+    }
+}
+
+impl Serialize for InitScript {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let (file, dir) = match self {
+            InitScript::Inline(script) => return serializer.serialize_str(script),
+            InitScript::File(path) => (Some(path.clone()), None),
+            InitScript::Dir(path) => (None, Some(path.clone())),
+        };
+        InitSourceTable { file, dir }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for InitScript {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct InitScriptVisitor;
+
+        impl<'de> de::Visitor<'de> for InitScriptVisitor {
+            type Value = InitScript;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(
+                    f,
+                    "a command string, {{ file = \"...\" }} or {{ dir = \"...\" }}"
+                )
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                Ok(InitScript::Inline(v.to_string()))
+            }
+
+            fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+                let table =
+                    InitSourceTable::deserialize(de::value::MapAccessDeserializer::new(map))?;
+                match (table.file, table.dir) {
+                    (Some(file), None) => Ok(InitScript::File(file)),
+                    (None, Some(dir)) => Ok(InitScript::Dir(dir)),
+                    _ => Err(de::Error::custom(
+                        "init entry needs exactly one of `file` or `dir`",
+                    )),
+                }
+            }
+        }
+
+        deserializer.deserialize_any(InitScriptVisitor)
+    }
+}
+
+/// Whether a resource is part of the project: a bool, or a `{{ }}` template
+/// such as `"{{ env.ENABLE_MAILPIT }}"` that is resolved to one when the
+/// config is loaded. Defaults to enabled.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Enabled {
+    Bool(bool),
+    Template(String),
+}
+
+impl Default for Enabled {
+    fn default() -> Self {
+        Enabled::Bool(true)
+    }
+}
+
+impl Enabled {
+    pub fn is_true(&self) -> bool {
+        *self == Enabled::Bool(true)
+    }
+
+    /// Parse what a template resolved to. Unset variables resolve to an
+    /// empty string, which counts as false.
+    pub fn parse_resolved(value: &str) -> Result<bool, String> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "true" | "1" | "yes" | "on" => Ok(true),
+            "false" | "0" | "no" | "off" | "" => Ok(false),
+            other => Err(format!(
+                "expected true/false, 1/0, yes/no or on/off, got \"{}\"",
+                other
+            )),
+        }
+    }
+}
+
+impl Serialize for Enabled {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Enabled::Bool(enabled) => serializer.serialize_bool(*enabled),
+            Enabled::Template(template) => serializer.serialize_str(template),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Enabled {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct EnabledVisitor;
+
+        impl de::Visitor<'_> for EnabledVisitor {
+            type Value = Enabled;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(
+                    f,
+                    "a bool or a template string like \"{{{{ env.ENABLE_X }}}}\""
+                )
+            }
+
+            fn visit_bool<E: de::Error>(self, v: bool) -> Result<Self::Value, E> {
+                Ok(Enabled::Bool(v))
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                Ok(Enabled::Template(v.to_string()))
+            }
+        }
+
+        deserializer.deserialize_any(EnabledVisitor)
+    }
 }
 
 /// A value that can be either a single string or a list of strings.
@@ -140,6 +594,15 @@ impl StringOrList {
     }
 }
 
+impl Serialize for StringOrList {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.0.as_slice() {
+            [single] => serializer.serialize_str(single),
+            values => values.serialize(serializer),
+        }
+    }
+}
+
 impl<'de> Deserialize<'de> for StringOrList {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -171,13 +634,140 @@ impl<'de> Deserialize<'de> for StringOrList {
     }
 }
 
-#[derive(Debug, Clone, Deserialize, PartialEq)]
+/// What a dependent waits for before starting.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DependencyCondition {
+    /// Wait for the dependency's `ready_check` (or, for docker, its image's
+    /// `HEALTHCHECK`) to pass. Dependencies without either count as soon as
+    /// they are started.
+    #[default]
+    #[serde(alias = "service_healthy")]
+    Healthy,
+    /// Start as soon as the dependency has been started.
+    #[serde(alias = "service_started")]
+    Started,
+}
+
+/// One `depends_on` entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dependency {
+    pub name: String,
+    pub condition: DependencyCondition,
+}
+
+/// `depends_on` for services and docker: a list of names, or a map from
+/// name to `"healthy"` / `"started"`. List entries wait for readiness, as
+/// `"healthy"` does.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DependsOn(pub Vec<Dependency>);
+
+impl DependsOn {
+    pub fn iter(&self) -> std::slice::Iter<'_, Dependency> {
+        self.0.iter()
+    }
+
+    /// Dependency names, in declaration order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(|dep| dep.name.as_str())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Whether any entry on `name` waits for it to be healthy.
+    pub fn waits_for_health(&self, name: &str) -> bool {
+        self.0
+            .iter()
+            .any(|dep| dep.name == name && dep.condition == DependencyCondition::Healthy)
+    }
+}
+
+impl<S: Into<String>> FromIterator<S> for DependsOn {
+    /// Build the list form: every entry waits for readiness.
+    fn from_iter<I: IntoIterator<Item = S>>(names: I) -> Self {
+        DependsOn(
+            names
+                .into_iter()
+                .map(|name| Dependency {
+                    name: name.into(),
+                    condition: DependencyCondition::Healthy,
+                })
+                .collect(),
+        )
+    }
+}
+
+impl Serialize for DependsOn {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+
+        if self
+            .0
+            .iter()
+            .all(|dep| dep.condition == DependencyCondition::Healthy)
+        {
+            return serializer.collect_seq(self.names());
+        }
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for dep in &self.0 {
+            map.serialize_entry(&dep.name, &dep.condition)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for DependsOn {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct DependsOnVisitor;
+
+        impl<'de> de::Visitor<'de> for DependsOnVisitor {
+            type Value = DependsOn;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter
+                    .write_str("a list of names or a map of name to \"healthy\" or \"started\"")
+            }
+
+            fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<DependsOn, A::Error> {
+                let mut names = Vec::new();
+                while let Some(name) = seq.next_element::<String>()? {
+                    names.push(name);
+                }
+                Ok(names.into_iter().collect())
+            }
+
+            fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<DependsOn, A::Error> {
+                let mut deps = Vec::new();
+                while let Some((name, condition)) = map.next_entry()? {
+                    deps.push(Dependency { name, condition });
+                }
+                Ok(DependsOn(deps))
+            }
+        }
+
+        deserializer.deserialize_any(DependsOnVisitor)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 pub struct RegistryAuth {
     pub username: String,
+    /// Ignored when `password_command` is set.
+    #[serde(default)]
     pub password: String,
+    /// Shell command that prints the password, for registries with
+    /// short-lived tokens. Run before pulling and again when the registry
+    /// rejects the token.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password_command: Option<String>,
 }
 
-#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 #[serde(tag = "type")]
 pub enum ReadyCheck {
     #[serde(rename = "pg_isready")]
@@ -204,6 +794,16 @@ pub enum ReadyCheck {
         #[serde(default)]
         timeout: Option<u64>,
     },
+    /// Ready when the standard gRPC health service on the resolved port
+    /// (`grpc.health.v1.Health/Check`) reports `SERVING`. `service` names
+    /// the service to ask about; empty asks about the server as a whole.
+    #[serde(rename = "grpc")]
+    Grpc {
+        #[serde(default)]
+        service: Option<String>,
+        #[serde(default)]
+        timeout: Option<u64>,
+    },
     #[serde(rename = "log")]
     Log {
         #[serde(rename = "match")]
@@ -211,37 +811,136 @@ pub enum ReadyCheck {
         #[serde(default)]
         timeout: Option<u64>,
     },
+    /// Ready once every child check passes.
+    #[serde(rename = "all_of")]
+    AllOf {
+        checks: Vec<ReadyCheck>,
+        #[serde(default)]
+        timeout: Option<u64>,
+    },
+    /// Ready as soon as any child check passes.
+    #[serde(rename = "any_of")]
+    AnyOf {
+        checks: Vec<ReadyCheck>,
+        #[serde(default)]
+        timeout: Option<u64>,
+    },
 }
 
+/// Maximum nesting depth for `all_of` / `any_of` ready checks. A plain check
+/// has depth 1, a composite of plain checks depth 2.
+pub const MAX_READY_CHECK_DEPTH: usize = 3;
+
 impl ReadyCheck {
     /// Get the configured timeout or return the default for this check type.
+    ///
+    /// Composite checks default to the longest timeout among their children.
     pub fn timeout_secs(&self) -> u64 {
         let custom = match self {
             ReadyCheck::PgIsReady { timeout } => *timeout,
             ReadyCheck::Cmd { timeout, .. } => *timeout,
             ReadyCheck::Http { timeout, .. } => *timeout,
             ReadyCheck::Tcp { timeout } => *timeout,
+            ReadyCheck::Grpc { timeout, .. } => *timeout,
             ReadyCheck::Log { timeout, .. } => *timeout,
+            ReadyCheck::AllOf { timeout, .. } => *timeout,
+            ReadyCheck::AnyOf { timeout, .. } => *timeout,
         };
         custom.unwrap_or(match self {
             ReadyCheck::Log { .. } => 60,
+            ReadyCheck::AllOf { checks, .. } | ReadyCheck::AnyOf { checks, .. } => checks
+                .iter()
+                .map(ReadyCheck::timeout_secs)
+                .max()
+                .unwrap_or(30),
             _ => 30,
         })
     }
+
+    /// Override the timeout of this check and, for `all_of` / `any_of`, of
+    /// every nested child. Used by `devrig start --ready-timeout`.
+    pub fn set_timeout(&mut self, secs: u64) {
+        match self {
+            ReadyCheck::PgIsReady { timeout }
+            | ReadyCheck::Cmd { timeout, .. }
+            | ReadyCheck::Http { timeout, .. }
+            | ReadyCheck::Tcp { timeout }
+            | ReadyCheck::Grpc { timeout, .. }
+            | ReadyCheck::Log { timeout, .. } => *timeout = Some(secs),
+            ReadyCheck::AllOf { checks, timeout } | ReadyCheck::AnyOf { checks, timeout } => {
+                *timeout = Some(secs);
+                for check in checks {
+                    check.set_timeout(secs);
+                }
+            }
+        }
+    }
+
+    /// Nesting depth of this check: 1 for a plain check, one more than the
+    /// deepest child for `all_of` / `any_of`.
+    pub fn depth(&self) -> usize {
+        match self {
+            ReadyCheck::AllOf { checks, .. } | ReadyCheck::AnyOf { checks, .. } => {
+                1 + checks.iter().map(ReadyCheck::depth).max().unwrap_or(0)
+            }
+            _ => 1,
+        }
+    }
+
+    /// Whether this check, or any nested child, is a composite with no children.
+    pub fn has_empty_composite(&self) -> bool {
+        match self {
+            ReadyCheck::AllOf { checks, .. } | ReadyCheck::AnyOf { checks, .. } => {
+                checks.is_empty() || checks.iter().any(ReadyCheck::has_empty_composite)
+            }
+            _ => false,
+        }
+    }
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ComposeConfig {
-    pub file: String,
+    /// A compose file, or a base file followed by overrides. Later files
+    /// override earlier ones, as with repeated `docker compose -f`.
+    pub file: StringOrList,
+    /// Compose project name. Defaults to the devrig project slug.
+    #[serde(default)]
+    pub project_name: Option<String>,
     #[serde(default)]
     pub services: Vec<String>,
     #[serde(default)]
     pub env_file: Option<String>,
     #[serde(default)]
     pub ready_checks: BTreeMap<String, ReadyCheck>,
+    /// URL scheme per compose service for `DEVRIG_<NAME>_URL`, e.g.
+    /// `{ web = "https" }`. Services not listed use `http`.
+    #[serde(default)]
+    pub protocols: BTreeMap<String, String>,
+}
+
+impl ComposeConfig {
+    /// Compose files resolved against `config_dir`, in merge order.
+    pub fn files(&self, config_dir: &Path) -> Vec<PathBuf> {
+        self.file
+            .as_slice()
+            .iter()
+            .map(|f| config_dir.join(f))
+            .collect()
+    }
+
+    /// The name passed to `docker compose -p`: `project_name` when set,
+    /// otherwise `slug`.
+    pub fn project_name<'a>(&'a self, slug: &'a str) -> &'a str {
+        self.project_name.as_deref().unwrap_or(slug)
+    }
+
+    /// The configured protocol for compose service `name`, if any.
+    pub fn protocol(&self, name: &str) -> Option<&str> {
+        self.protocols.get(name).map(String::as_str)
+    }
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct NetworkConfig {
     #[serde(default)]
     pub name: Option<String>,
@@ -275,6 +974,10 @@ fn default_retention() -> String {
     "1h".to_string()
 }
 
+fn default_wal_max_mb() -> u64 {
+    64
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 pub struct DashboardConfig {
     #[serde(default = "default_dashboard_port")]
@@ -283,6 +986,38 @@ pub struct DashboardConfig {
     pub enabled: Option<bool>,
     #[serde(default)]
     pub otel: Option<OtelConfig>,
+    /// Label for the browser tab and dashboard header. Defaults to the project name.
+    #[serde(default)]
+    pub title: Option<String>,
+    /// Serve the dashboard (and optionally the OTLP receivers) over HTTPS.
+    #[serde(default)]
+    pub tls: Option<DashboardTlsConfig>,
+}
+
+/// `[dashboard.tls]`: a PEM certificate and key, relative to the config
+/// file. Both must be set; validation reports a missing one.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct DashboardTlsConfig {
+    #[serde(default)]
+    pub cert: Option<String>,
+    #[serde(default)]
+    pub key: Option<String>,
+    /// Also serve the OTLP gRPC and HTTP receivers over TLS.
+    #[serde(default)]
+    pub otel: bool,
+}
+
+impl DashboardTlsConfig {
+    /// The cert and key paths, if both are set.
+    pub fn files(&self) -> Option<(&str, &str)> {
+        Some((self.cert.as_deref()?, self.key.as_deref()?))
+    }
+
+    /// The cert and key paths resolved against `config_dir`.
+    pub fn resolved_files(&self, config_dir: &Path) -> Option<(PathBuf, PathBuf)> {
+        let (cert, key) = self.files()?;
+        Some((config_dir.join(cert), config_dir.join(key)))
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
@@ -299,6 +1034,39 @@ pub struct OtelConfig {
     pub log_buffer: usize,
     #[serde(default = "default_retention")]
     pub retention: String,
+    /// Append received telemetry to `.devrig/telemetry/` and reload it on
+    /// the next start, so `devrig query` works after devrig exits.
+    #[serde(default)]
+    pub wal: bool,
+    /// Disk cap for the telemetry WAL, in megabytes.
+    #[serde(default = "default_wal_max_mb")]
+    pub wal_max_mb: u64,
+    /// Re-export everything received to an upstream collector.
+    #[serde(default)]
+    pub forward: Option<OtelForwardConfig>,
+}
+
+/// `[dashboard.otel.forward]`: an upstream OTLP collector that receives a
+/// copy of all telemetry. Local storage is unaffected by upstream failures.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct OtelForwardConfig {
+    /// Base URL of the collector, e.g. `http://localhost:4317`. For `http`,
+    /// `/v1/traces`, `/v1/metrics` and `/v1/logs` are appended.
+    pub endpoint: String,
+    #[serde(default)]
+    pub protocol: OtlpProtocol,
+    /// Extra headers (or gRPC metadata) sent with every export.
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+}
+
+/// OTLP transport used for forwarding.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum OtlpProtocol {
+    #[default]
+    Grpc,
+    Http,
 }
 
 impl Default for DashboardConfig {
@@ -307,10 +1075,19 @@ impl Default for DashboardConfig {
             port: default_dashboard_port(),
             enabled: None,
             otel: None,
+            title: None,
+            tls: None,
         }
     }
 }
 
+impl DashboardConfig {
+    /// The configured title, falling back to `project_name`.
+    pub fn title_or<'a>(&'a self, project_name: &'a str) -> &'a str {
+        self.title.as_deref().unwrap_or(project_name)
+    }
+}
+
 impl Default for OtelConfig {
     fn default() -> Self {
         Self {
@@ -320,6 +1097,9 @@ impl Default for OtelConfig {
             metric_buffer: default_metric_buffer(),
             log_buffer: default_log_buffer(),
             retention: default_retention(),
+            wal: false,
+            wal_max_mb: default_wal_max_mb(),
+            forward: None,
         }
     }
 }
@@ -328,6 +1108,10 @@ fn default_agents() -> u32 {
     1
 }
 
+fn default_command_retries() -> u32 {
+    3
+}
+
 fn default_registry() -> bool {
     true
 }
@@ -360,6 +1144,17 @@ pub struct ClusterConfig {
     pub registries: Vec<ClusterRegistryAuth>,
     #[serde(default)]
     pub k3s_args: Vec<String>,
+    /// k3s node image passed to `k3d cluster create --image`, e.g.
+    /// `rancher/k3s:v1.31.4-k3s1`. Unset uses k3d's default.
+    #[serde(default)]
+    pub k3s_image: Option<String>,
+    /// Env injected into every `[cluster.deploy.*]`; per-deploy `env` wins.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Retries for read-only k3d/kubectl calls (list, get) that fail with a
+    /// transient API server error, common right after cluster creation.
+    #[serde(default = "default_command_retries")]
+    pub command_retries: u32,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
@@ -384,15 +1179,39 @@ pub struct ClusterLogsConfig {
     /// Which namespaces to collect logs from. Default: ["default"].
     #[serde(default)]
     pub namespaces: NamespaceFilter,
-    /// Namespaces to exclude (only valid when namespaces = "all").
+    /// Namespaces to exclude (only valid when namespaces = "all"). Entries
+    /// are matched as described in [`ClusterLogsConfig::exclude_pattern`].
     #[serde(default)]
     pub exclude_namespaces: Option<Vec<String>>,
-    /// Pod name patterns to exclude from log collection.
+    /// Pod names to exclude from log collection, matched the same way.
     #[serde(default)]
     pub exclude_pods: Option<Vec<String>>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+impl ClusterLogsConfig {
+    /// The regex for one `exclude_namespaces` / `exclude_pods` entry. A
+    /// plain name matches exactly; `*` and `?` make it a glob (`istio-*`);
+    /// an entry with regex syntax (`.*`, `\d`, `[..]`, `(a|b)`, ...) is used
+    /// as written.
+    pub fn exclude_pattern(entry: &str) -> String {
+        const REGEX_MARKERS: &[&str] =
+            &[".*", "\\", "^", "$", "+", "(", ")", "[", "]", "{", "}", "|"];
+        if REGEX_MARKERS.iter().any(|marker| entry.contains(marker)) {
+            return entry.to_string();
+        }
+        entry
+            .chars()
+            .map(|c| match c {
+                '*' => ".*".to_string(),
+                '?' => ".".to_string(),
+                '.' => "\\.".to_string(),
+                c => c.to_string(),
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
 pub enum NamespaceFilter {
     All,
     List(Vec<String>),
@@ -404,6 +1223,15 @@ impl Default for NamespaceFilter {
     }
 }
 
+impl Serialize for NamespaceFilter {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            NamespaceFilter::All => serializer.serialize_str("all"),
+            NamespaceFilter::List(names) => names.serialize(serializer),
+        }
+    }
+}
+
 impl<'de> Deserialize<'de> for NamespaceFilter {
     fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
         struct NamespaceFilterVisitor;
@@ -449,12 +1277,18 @@ pub enum AddonConfig {
         #[serde(default)]
         repo: Option<String>,
         namespace: String,
+        #[serde(default = "default_true")]
+        create_namespace: bool,
         #[serde(default)]
         version: Option<String>,
         #[serde(default)]
         values: BTreeMap<String, toml::Value>,
         #[serde(default)]
         values_files: Vec<String>,
+        /// Passed with `--set-string`, so values like an image tag of `1.0`
+        /// stay strings.
+        #[serde(default)]
+        set_string: BTreeMap<String, String>,
         #[serde(default)]
         port_forward: BTreeMap<String, String>,
         #[serde(default = "default_true")]
@@ -471,6 +1305,8 @@ pub enum AddonConfig {
         path: String,
         #[serde(default)]
         namespace: Option<String>,
+        #[serde(default = "default_true")]
+        create_namespace: bool,
         #[serde(default)]
         port_forward: BTreeMap<String, String>,
         #[serde(default)]
@@ -481,6 +1317,8 @@ pub enum AddonConfig {
         path: String,
         #[serde(default)]
         namespace: Option<String>,
+        #[serde(default = "default_true")]
+        create_namespace: bool,
         #[serde(default)]
         port_forward: BTreeMap<String, String>,
         #[serde(default)]
@@ -515,6 +1353,21 @@ impl AddonConfig {
         }
     }
 
+    /// Returns whether devrig should create the addon's namespace if missing.
+    pub fn create_namespace(&self) -> bool {
+        match self {
+            AddonConfig::Helm {
+                create_namespace, ..
+            } => *create_namespace,
+            AddonConfig::Manifest {
+                create_namespace, ..
+            } => *create_namespace,
+            AddonConfig::Kustomize {
+                create_namespace, ..
+            } => *create_namespace,
+        }
+    }
+
     /// Returns the addon type as a string.
     pub fn addon_type(&self) -> &str {
         match self {
@@ -536,6 +1389,10 @@ impl AddonConfig {
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ClusterImageConfig {
+    /// `false`, or a template that resolves to false, leaves this image
+    /// out of the project. See [`Enabled`].
+    #[serde(default, skip_serializing_if = "Enabled::is_true")]
+    pub enabled: Enabled,
     pub context: String,
     #[serde(default = "default_dockerfile")]
     pub dockerfile: String,
@@ -556,6 +1413,10 @@ pub struct ClusterImageConfig {
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ClusterDeployConfig {
+    /// `false`, or a template that resolves to false, leaves this deploy
+    /// out of the project. See [`Enabled`].
+    #[serde(default, skip_serializing_if = "Enabled::is_true")]
+    pub enabled: Enabled,
     pub context: String,
     #[serde(default = "default_dockerfile")]
     pub dockerfile: String,
@@ -568,12 +1429,33 @@ pub struct ClusterDeployConfig {
     /// Passed as `--secret id=<key>,src=<value>` to `docker build`.
     #[serde(default)]
     pub build_secrets: BTreeMap<String, String>,
+    /// Env for the deployment's pods, materialized as a ConfigMap.
+    /// Values support `$VAR` expansion and `{{ }}` templates.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Port {
     Fixed(u16),
     Auto,
+    /// The first free port in `start..=end`, trying `preferred` first.
+    /// Written as `"3000-3010"` or `{ start = 3000, end = 3010, preferred = 3005 }`.
+    Range {
+        preferred: Option<u16>,
+        start: u16,
+        end: u16,
+    },
+}
+
+/// The table form of [`Port::Range`].
+#[derive(Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct PortRangeTable {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    preferred: Option<u16>,
+    start: u16,
+    end: u16,
 }
 
 impl Serialize for Port {
@@ -581,6 +1463,21 @@ impl Serialize for Port {
         match self {
             Port::Fixed(p) => serializer.serialize_u16(*p),
             Port::Auto => serializer.serialize_str("auto"),
+            Port::Range {
+                preferred: None,
+                start,
+                end,
+            } => serializer.serialize_str(&format!("{start}-{end}")),
+            Port::Range {
+                preferred,
+                start,
+                end,
+            } => PortRangeTable {
+                preferred: *preferred,
+                start: *start,
+                end: *end,
+            }
+            .serialize(serializer),
         }
     }
 }
@@ -593,7 +1490,10 @@ impl<'de> Deserialize<'de> for Port {
             type Value = Port;
 
             fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-                write!(f, "a port number (1-65535) or the string \"auto\"")
+                write!(
+                    f,
+                    "a port number (1-65535), the string \"auto\", or a range like \"3000-3010\""
+                )
             }
 
             fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
@@ -611,10 +1511,22 @@ impl<'de> Deserialize<'de> for Port {
             fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
                 if v == "auto" {
                     Ok(Port::Auto)
+                } else if let Some(range) = Port::parse_range(v) {
+                    range.map_err(E::custom)
                 } else {
                     Err(E::custom(format!("expected \"auto\" but got \"{v}\"")))
                 }
             }
+
+            fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+                let table =
+                    PortRangeTable::deserialize(de::value::MapAccessDeserializer::new(map))?;
+                Ok(Port::Range {
+                    preferred: table.preferred,
+                    start: table.start,
+                    end: table.end,
+                })
+            }
         }
 
         deserializer.deserialize_any(PortVisitor)
@@ -625,12 +1537,155 @@ impl Port {
     pub fn as_fixed(&self) -> Option<u16> {
         match self {
             Port::Fixed(p) => Some(*p),
-            Port::Auto => None,
+            Port::Auto | Port::Range { .. } => None,
         }
     }
 
+    /// Whether devrig picks the port (`auto` or a range), so the choice is
+    /// remembered and reused on the next start.
     pub fn is_auto(&self) -> bool {
-        matches!(self, Port::Auto)
+        matches!(self, Port::Auto | Port::Range { .. })
+    }
+
+    /// Parse `"start-end"`. `None` if `v` isn't shaped like a range.
+    fn parse_range(v: &str) -> Option<Result<Port, String>> {
+        let (start, end) = v.split_once('-')?;
+        let parse = |p: &str| {
+            p.trim()
+                .parse::<u16>()
+                .map_err(|_| format!("invalid port range \"{v}\" (expected e.g. \"3000-3010\")"))
+        };
+        Some(parse(start).and_then(|start| {
+            Ok(Port::Range {
+                preferred: None,
+                start,
+                end: parse(end)?,
+            })
+        }))
+    }
+}
+
+/// Transport protocol for a published docker port.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PortProtocol {
+    #[default]
+    Tcp,
+    Udp,
+}
+
+impl PortProtocol {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PortProtocol::Tcp => "tcp",
+            PortProtocol::Udp => "udp",
+        }
+    }
+}
+
+impl std::fmt::Display for PortProtocol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A named `[docker.*.ports]` entry: a [`Port`] plus the protocol to publish
+/// it with. Written as `5353`, `"auto"`, `"5353-5360"`, `"5353/udp"` or
+/// `"auto/udp"`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DockerPort {
+    pub port: Port,
+    pub protocol: PortProtocol,
+}
+
+impl From<Port> for DockerPort {
+    fn from(port: Port) -> Self {
+        Self {
+            port,
+            protocol: PortProtocol::Tcp,
+        }
+    }
+}
+
+impl DockerPort {
+    pub fn as_fixed(&self) -> Option<u16> {
+        self.port.as_fixed()
+    }
+
+    pub fn is_auto(&self) -> bool {
+        self.port.is_auto()
+    }
+}
+
+impl Serialize for DockerPort {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match (&self.port, self.protocol) {
+            (port, PortProtocol::Tcp) => port.serialize(serializer),
+            (Port::Fixed(p), protocol) => serializer.serialize_str(&format!("{p}/{protocol}")),
+            (Port::Auto, protocol) => serializer.serialize_str(&format!("auto/{protocol}")),
+            (Port::Range { start, end, .. }, protocol) => {
+                serializer.serialize_str(&format!("{start}-{end}/{protocol}"))
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for DockerPort {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct DockerPortVisitor;
+
+        impl<'de> de::Visitor<'de> for DockerPortVisitor {
+            type Value = DockerPort;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(
+                    f,
+                    "a port number (1-65535), \"auto\", a range like \"3000-3010\", or any of those with a /tcp or /udp suffix"
+                )
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+                u16::try_from(v)
+                    .map(|p| Port::Fixed(p).into())
+                    .map_err(|_| E::custom(format!("port {v} out of range (1-65535)")))
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+                u16::try_from(v)
+                    .map(|p| Port::Fixed(p).into())
+                    .map_err(|_| E::custom(format!("port {v} out of range (1-65535)")))
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                let (port, protocol) = match v.rsplit_once('/') {
+                    Some((port, "tcp")) => (port, PortProtocol::Tcp),
+                    Some((port, "udp")) => (port, PortProtocol::Udp),
+                    Some((_, other)) => {
+                        return Err(E::custom(format!(
+                            "unknown port protocol \"{other}\" (expected tcp or udp)"
+                        )))
+                    }
+                    None => (v, PortProtocol::Tcp),
+                };
+                let port = if port == "auto" {
+                    Port::Auto
+                } else if let Some(range) = Port::parse_range(port) {
+                    range.map_err(E::custom)?
+                } else {
+                    port.parse::<u16>().map(Port::Fixed).map_err(|_| {
+                        E::custom(format!(
+                            "expected a port number or \"auto\" but got \"{v}\""
+                        ))
+                    })?
+                };
+                Ok(DockerPort { port, protocol })
+            }
+
+            fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+                Port::deserialize(de::value::MapAccessDeserializer::new(map)).map(Into::into)
+            }
+        }
+
+        deserializer.deserialize_any(DockerPortVisitor)
     }
 }
 
@@ -638,6 +1693,37 @@ impl Port {
 mod tests {
     use super::*;
 
+    #[test]
+    fn parse_mem_limit_units() {
+        assert_eq!(parse_mem_limit("512m"), Ok(512 << 20));
+        assert_eq!(parse_mem_limit("2G"), Ok(2 << 30));
+        assert_eq!(parse_mem_limit("1.5g"), Ok(3 << 29));
+        assert_eq!(parse_mem_limit("65536kb"), Ok(64 << 20));
+        assert_eq!(parse_mem_limit("10485760"), Ok(10 << 20));
+        assert!(parse_mem_limit("1k").is_err());
+        assert!(parse_mem_limit("512x").is_err());
+        assert!(parse_mem_limit("lots").is_err());
+        assert!(parse_mem_limit("").is_err());
+    }
+
+    #[test]
+    fn docker_resource_limits_translate_for_docker() {
+        let config: DockerConfig = toml::from_str(
+            r#"
+            image = "postgres:16"
+            mem_limit = "256m"
+            cpus = 1.5
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.memory_bytes(), Some(256 << 20));
+        assert_eq!(config.nano_cpus(), Some(1_500_000_000));
+
+        let config: DockerConfig = toml::from_str(r#"image = "redis:7""#).unwrap();
+        assert_eq!(config.memory_bytes(), None);
+        assert_eq!(config.nano_cpus(), None);
+    }
+
     #[test]
     fn parse_minimal_config() {
         let toml = r#"
@@ -687,7 +1773,7 @@ mod tests {
         assert_eq!(api.path.as_deref(), Some("./api"));
         assert_eq!(api.command, "cargo watch -x run");
         assert!(matches!(api.port, Some(Port::Fixed(3000))));
-        assert_eq!(api.depends_on, vec!["db"]);
+        assert_eq!(api.depends_on.names().collect::<Vec<_>>(), vec!["db"]);
         assert_eq!(api.env["API_KEY"], "secret");
 
         let web = &config.services["web"];
@@ -836,7 +1922,10 @@ mod tests {
         assert_eq!(api.path.as_deref(), Some("./backend"));
         assert_eq!(api.command, "cargo run");
         assert!(matches!(api.port, Some(Port::Fixed(8080))));
-        assert_eq!(api.depends_on, vec!["db", "cache"]);
+        assert_eq!(
+            api.depends_on.names().collect::<Vec<_>>(),
+            vec!["db", "cache"]
+        );
         assert_eq!(api.env.len(), 2);
     }
 
@@ -863,6 +1952,73 @@ mod tests {
         assert_eq!(Port::Auto.as_fixed(), None);
         assert!(!Port::Fixed(3000).is_auto());
         assert!(Port::Auto.is_auto());
+        let range = Port::Range {
+            preferred: None,
+            start: 3000,
+            end: 3010,
+        };
+        assert_eq!(range.as_fixed(), None);
+        assert!(range.is_auto());
+    }
+
+    #[test]
+    fn parse_port_range() {
+        let toml = r#"
+            [project]
+            name = "test"
+            [services.api]
+            command = "echo hi"
+            port = "3000-3010"
+            [services.web]
+            command = "echo hi"
+            port = { start = 8000, end = 8100, preferred = 8080 }
+            [docker.dns]
+            image = "coredns/coredns:latest"
+            [docker.dns.ports]
+            dns = "5353-5360/udp"
+        "#;
+        let config: DevrigConfig = toml::from_str(toml).unwrap();
+        assert_eq!(
+            config.services["api"].port,
+            Some(Port::Range {
+                preferred: None,
+                start: 3000,
+                end: 3010,
+            })
+        );
+        let web = Port::Range {
+            preferred: Some(8080),
+            start: 8000,
+            end: 8100,
+        };
+        assert_eq!(config.services["web"].port, Some(web.clone()));
+        assert_eq!(
+            config.docker["dns"].ports["dns"],
+            DockerPort {
+                port: Port::Range {
+                    preferred: None,
+                    start: 5353,
+                    end: 5360,
+                },
+                protocol: PortProtocol::Udp,
+            }
+        );
+
+        let serialized = toml::to_string(&config.services["api"]).unwrap();
+        assert!(serialized.contains("port = \"3000-3010\""), "{serialized}");
+        let roundtrip: ServiceConfig =
+            toml::from_str(&toml::to_string(&config.services["web"]).unwrap()).unwrap();
+        assert_eq!(roundtrip.port, Some(web));
+
+        let bad = r#"
+            [project]
+            name = "test"
+            [services.api]
+            command = "echo hi"
+            port = "3000-abc"
+        "#;
+        let err = toml::from_str::<DevrigConfig>(bad).unwrap_err();
+        assert!(err.to_string().contains("invalid port range"), "{err}");
     }
 
     // --- v0.2 DockerConfig tests ---
@@ -894,19 +2050,67 @@ mod tests {
             [project]
             name = "test"
 
-            [docker.mailpit]
-            image = "axllent/mailpit:latest"
-            [docker.mailpit.ports]
-            smtp = 1025
-            ui = 8025
+            [docker.mailpit]
+            image = "axllent/mailpit:latest"
+            [docker.mailpit.ports]
+            smtp = 1025
+            ui = 8025
+        "#;
+        let config: DevrigConfig = toml::from_str(toml).unwrap();
+        let mp = &config.docker["mailpit"];
+        assert_eq!(mp.image, "axllent/mailpit:latest");
+        assert!(mp.port.is_none());
+        assert_eq!(mp.ports.len(), 2);
+        assert!(matches!(mp.ports["smtp"].port, Port::Fixed(1025)));
+        assert!(matches!(mp.ports["ui"].port, Port::Fixed(8025)));
+    }
+
+    #[test]
+    fn parse_docker_udp_ports() {
+        let toml = r#"
+            [project]
+            name = "test"
+
+            [docker.dns]
+            image = "coredns/coredns:latest"
+            port = 5353
+            protocol = "udp"
+            [docker.dns.ports]
+            dns = "8053/udp"
+            metrics = "9153/tcp"
+            stun = "auto/udp"
+            health = 8080
+        "#;
+        let config: DevrigConfig = toml::from_str(toml).unwrap();
+        let dns = &config.docker["dns"];
+        assert_eq!(dns.port_protocol(), PortProtocol::Udp);
+        assert_eq!(
+            dns.ports["dns"],
+            DockerPort {
+                port: Port::Fixed(8053),
+                protocol: PortProtocol::Udp,
+            }
+        );
+        assert_eq!(dns.ports["metrics"], Port::Fixed(9153).into());
+        assert!(dns.ports["stun"].is_auto());
+        assert_eq!(dns.ports["stun"].protocol, PortProtocol::Udp);
+        assert_eq!(dns.ports["health"], Port::Fixed(8080).into());
+
+        let serialized = toml::to_string(&dns.ports).unwrap();
+        assert!(serialized.contains("dns = \"8053/udp\""), "{serialized}");
+        assert!(serialized.contains("health = 8080"), "{serialized}");
+
+        let bad = r#"
+            [project]
+            name = "test"
+
+            [docker.dns]
+            image = "coredns/coredns:latest"
+            [docker.dns.ports]
+            dns = "8053/sctp"
         "#;
-        let config: DevrigConfig = toml::from_str(toml).unwrap();
-        let mp = &config.docker["mailpit"];
-        assert_eq!(mp.image, "axllent/mailpit:latest");
-        assert!(mp.port.is_none());
-        assert_eq!(mp.ports.len(), 2);
-        assert!(matches!(mp.ports["smtp"], Port::Fixed(1025)));
-        assert!(matches!(mp.ports["ui"], Port::Fixed(8025)));
+        let err = toml::from_str::<DevrigConfig>(bad).unwrap_err();
+        assert!(err.to_string().contains("sctp"), "{err}");
     }
 
     #[test]
@@ -998,6 +2202,27 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn parse_ready_check_grpc() {
+        let toml = r#"
+            [project]
+            name = "test"
+            [docker.api]
+            image = "example/api"
+            port = 50051
+            ready_check = { type = "grpc", service = "api.v1.Orders", timeout = 20 }
+        "#;
+        let config: DevrigConfig = toml::from_str(toml).unwrap();
+        let check = config.docker["api"].ready_check.as_ref().unwrap();
+        match check {
+            ReadyCheck::Grpc { service, .. } => {
+                assert_eq!(service.as_deref(), Some("api.v1.Orders"));
+            }
+            other => panic!("expected ReadyCheck::Grpc, got {:?}", other),
+        }
+        assert_eq!(check.timeout_secs(), 20);
+    }
+
     #[test]
     fn parse_ready_check_log() {
         let toml = r#"
@@ -1019,6 +2244,95 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_ready_check_composite() {
+        let toml = r#"
+            [project]
+            name = "test"
+            [docker.api]
+            image = "api:dev"
+            port = 8080
+            [docker.api.ready_check]
+            type = "all_of"
+            checks = [
+                { type = "http", url = "http://localhost:8080/health", timeout = 45 },
+                { type = "any_of", checks = [
+                    { type = "log", match = "listening" },
+                    { type = "tcp" },
+                ] },
+            ]
+        "#;
+        let config: DevrigConfig = toml::from_str(toml).unwrap();
+        let check = config.docker["api"].ready_check.clone().unwrap();
+        match &check {
+            ReadyCheck::AllOf { checks, timeout } => {
+                assert_eq!(checks.len(), 2);
+                assert!(timeout.is_none());
+                assert!(matches!(checks[1], ReadyCheck::AnyOf { .. }));
+            }
+            other => panic!("expected ReadyCheck::AllOf, got {:?}", other),
+        }
+        assert_eq!(check.depth(), 3);
+        // Defaults to the longest child timeout (log check: 60s).
+        assert_eq!(check.timeout_secs(), 60);
+        assert!(!check.has_empty_composite());
+    }
+
+    #[test]
+    fn enabled_accepts_bool_or_template() {
+        let config: DevrigConfig = toml::from_str(
+            r#"
+            [project]
+            name = "test"
+            [services.api]
+            command = "cargo run"
+            [services.docs]
+            command = "mdbook serve"
+            enabled = false
+            [docker.mailpit]
+            image = "axllent/mailpit"
+            enabled = "{{ env.ENABLE_MAILPIT }}"
+            "#,
+        )
+        .unwrap();
+        assert!(config.services["api"].enabled.is_true());
+        assert_eq!(config.services["docs"].enabled, Enabled::Bool(false));
+        assert_eq!(
+            config.docker["mailpit"].enabled,
+            Enabled::Template("{{ env.ENABLE_MAILPIT }}".to_string())
+        );
+        assert_eq!(Enabled::parse_resolved(" Yes "), Ok(true));
+        assert_eq!(Enabled::parse_resolved(""), Ok(false));
+        assert!(Enabled::parse_resolved("2").is_err());
+    }
+
+    #[test]
+    fn set_timeout_overrides_nested_checks() {
+        let mut check = ReadyCheck::AllOf {
+            checks: vec![
+                ReadyCheck::Tcp { timeout: Some(45) },
+                ReadyCheck::AnyOf {
+                    checks: vec![ReadyCheck::Log {
+                        pattern: "listening".to_string(),
+                        timeout: None,
+                    }],
+                    timeout: None,
+                },
+            ],
+            timeout: None,
+        };
+        check.set_timeout(90);
+        assert_eq!(check.timeout_secs(), 90);
+        let ReadyCheck::AllOf { checks, .. } = &check else {
+            unreachable!()
+        };
+        assert_eq!(checks[0].timeout_secs(), 90);
+        let ReadyCheck::AnyOf { checks, .. } = &checks[1] else {
+            unreachable!()
+        };
+        assert_eq!(checks[0].timeout_secs(), 90);
+    }
+
     #[test]
     fn parse_compose_config() {
         let toml = r#"
@@ -1037,7 +2351,7 @@ mod tests {
         "#;
         let config: DevrigConfig = toml::from_str(toml).unwrap();
         let compose = config.compose.unwrap();
-        assert_eq!(compose.file, "docker-compose.yml");
+        assert_eq!(compose.file.as_slice(), ["docker-compose.yml"]);
         assert_eq!(compose.services, vec!["redis", "postgres"]);
         assert_eq!(compose.env_file.as_deref(), Some(".env"));
         assert_eq!(compose.ready_checks.len(), 1);
@@ -1047,6 +2361,23 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn compose_project_name_defaults_to_slug() {
+        let toml = r#"
+            [project]
+            name = "test"
+
+            [compose]
+            file = ["docker-compose.yml", "docker-compose.override.yml"]
+        "#;
+        let config: DevrigConfig = toml::from_str(toml).unwrap();
+        let mut compose = config.compose.unwrap();
+        assert_eq!(compose.project_name("test-1a2b3c4d"), "test-1a2b3c4d");
+
+        compose.project_name = Some("shop".to_string());
+        assert_eq!(compose.project_name("test-1a2b3c4d"), "shop");
+    }
+
     #[test]
     fn parse_config_with_infra_and_services() {
         let toml = r#"
@@ -1072,7 +2403,13 @@ mod tests {
         let config: DevrigConfig = toml::from_str(toml).unwrap();
         assert_eq!(config.docker.len(), 2);
         assert_eq!(config.services.len(), 1);
-        assert_eq!(config.services["api"].depends_on, vec!["postgres"]);
+        assert_eq!(
+            config.services["api"]
+                .depends_on
+                .names()
+                .collect::<Vec<_>>(),
+            vec!["postgres"]
+        );
     }
 
     #[test]
@@ -1123,7 +2460,7 @@ mod tests {
         assert!(matches!(pg.port, Some(Port::Fixed(5432))));
         assert_eq!(pg.volumes, vec!["pgdata:/var/lib/postgresql/data"]);
         assert_eq!(pg.init.len(), 2);
-        assert_eq!(pg.depends_on, vec!["redis"]);
+        assert_eq!(pg.depends_on.names().collect::<Vec<_>>(), vec!["redis"]);
         assert!(matches!(pg.ready_check, Some(ReadyCheck::PgIsReady { .. })));
         assert_eq!(pg.env.len(), 2);
     }
@@ -1153,6 +2490,7 @@ mod tests {
             registry = true
             agents = 2
             ports = ["8080:80@loadbalancer"]
+            command_retries = 5
 
             [cluster.deploy.api]
             context = "./api"
@@ -1170,6 +2508,7 @@ mod tests {
         assert!(cluster.registry);
         assert_eq!(cluster.agents, 2);
         assert_eq!(cluster.ports, vec!["8080:80@loadbalancer"]);
+        assert_eq!(cluster.command_retries, 5);
         assert_eq!(cluster.deploy.len(), 2);
 
         let api = &cluster.deploy["api"];
@@ -1199,9 +2538,32 @@ mod tests {
         let cluster = config.cluster.unwrap();
         assert!(cluster.name.is_none());
         assert_eq!(cluster.agents, 1);
+        assert_eq!(cluster.command_retries, 3);
         assert!(cluster.ports.is_empty());
         assert!(cluster.registry);
         assert!(cluster.deploy.is_empty());
+        assert!(cluster.k3s_image.is_none());
+    }
+
+    #[test]
+    fn parse_cluster_k3s_image() {
+        let toml = r#"
+            [project]
+            name = "test"
+
+            [cluster]
+            k3s_image = "rancher/k3s:v1.31.4-k3s1"
+
+            [cluster.image.api]
+            context = "./api"
+        "#;
+        let config: DevrigConfig = toml::from_str(toml).unwrap();
+        let cluster = config.cluster.unwrap();
+        assert_eq!(
+            cluster.k3s_image.as_deref(),
+            Some("rancher/k3s:v1.31.4-k3s1")
+        );
+        assert!(cluster.images.contains_key("api"));
     }
 
     #[test]
@@ -1401,6 +2763,7 @@ mod tests {
             startup_grace_ms = 3000
             initial_delay_ms = 1000
             max_delay_ms = 60000
+            retries_reset_after_ms = 300000
         "#;
         let config: DevrigConfig = toml::from_str(toml).unwrap();
         let restart = config.services["api"].restart.as_ref().unwrap();
@@ -1410,6 +2773,7 @@ mod tests {
         assert_eq!(restart.startup_grace_ms, 3000);
         assert_eq!(restart.initial_delay_ms, 1000);
         assert_eq!(restart.max_delay_ms, 60000);
+        assert_eq!(restart.retries_reset_after_ms, 300000);
     }
 
     #[test]
@@ -1431,6 +2795,7 @@ mod tests {
         assert_eq!(restart.startup_grace_ms, 2000);
         assert_eq!(restart.initial_delay_ms, 500);
         assert_eq!(restart.max_delay_ms, 30000);
+        assert_eq!(restart.retries_reset_after_ms, 60000);
     }
 
     #[test]
@@ -1449,14 +2814,23 @@ mod tests {
     #[test]
     fn service_config_partial_eq() {
         let a = ServiceConfig {
+            enabled: Default::default(),
             path: None,
             command: "echo hi".to_string(),
             port: Some(Port::Fixed(3000)),
             protocol: None,
             env: BTreeMap::new(),
             env_file: None,
-            depends_on: vec![],
+            depends_on: Default::default(),
+            ready_check: None,
             restart: None,
+            shell: None,
+            reload_strategy: None,
+            reload_signal: None,
+            max_log_lines_per_sec: None,
+            pre_start: None,
+            post_stop: None,
+            log_format: None,
         };
         let b = a.clone();
         assert_eq!(a, b);
@@ -1549,6 +2923,46 @@ mod tests {
         assert!(dash.otel.is_none());
     }
 
+    #[test]
+    fn parse_otel_forward() {
+        let toml_str = r#"
+            [project]
+            name = "test"
+
+            [dashboard.otel.forward]
+            endpoint = "http://tempo:4318"
+            protocol = "http"
+            headers = { authorization = "Bearer abc" }
+        "#;
+        let config: DevrigConfig = toml::from_str(toml_str).unwrap();
+        let forward = config.dashboard.unwrap().otel.unwrap().forward.unwrap();
+        assert_eq!(forward.endpoint, "http://tempo:4318");
+        assert_eq!(forward.protocol, OtlpProtocol::Http);
+        assert_eq!(forward.headers["authorization"], "Bearer abc");
+
+        let toml_str = r#"
+            [project]
+            name = "test"
+
+            [dashboard.otel.forward]
+            endpoint = "http://localhost:4317"
+        "#;
+        let config: DevrigConfig = toml::from_str(toml_str).unwrap();
+        let forward = config.dashboard.unwrap().otel.unwrap().forward.unwrap();
+        assert_eq!(forward.protocol, OtlpProtocol::Grpc);
+        assert!(forward.headers.is_empty());
+
+        let bad = r#"
+            [project]
+            name = "test"
+
+            [dashboard.otel.forward]
+            endpoint = "http://localhost:4317"
+            protocol = "thrift"
+        "#;
+        assert!(toml::from_str::<DevrigConfig>(bad).is_err());
+    }
+
     #[test]
     fn parse_dashboard_auto_ports() {
         let toml = r#"
@@ -1672,6 +3086,7 @@ mod tests {
             }
             other => panic!("expected Manifest addon, got {:?}", other),
         }
+        assert!(cluster.addons["my-tool"].create_namespace());
     }
 
     #[test]
@@ -1683,6 +3098,7 @@ mod tests {
             [cluster.addons.overlay]
             type = "kustomize"
             path = "./k8s/overlays/dev"
+            create_namespace = false
         "#;
         let config: DevrigConfig = toml::from_str(toml_str).unwrap();
         let cluster = config.cluster.unwrap();
@@ -1692,6 +3108,7 @@ mod tests {
             }
             other => panic!("expected Kustomize addon, got {:?}", other),
         }
+        assert!(!cluster.addons["overlay"].create_namespace());
     }
 
     #[test]
@@ -1769,9 +3186,11 @@ mod tests {
             chart: "test".to_string(),
             repo: Some("https://example.com".to_string()),
             namespace: "default".to_string(),
+            create_namespace: true,
             version: None,
             values: BTreeMap::new(),
             values_files: Vec::new(),
+            set_string: BTreeMap::new(),
             port_forward: BTreeMap::from([("8080".to_string(), "svc/test:80".to_string())]),
             wait: true,
             timeout: "5m".to_string(),
@@ -1786,6 +3205,7 @@ mod tests {
         let manifest = AddonConfig::Manifest {
             path: "./test.yaml".to_string(),
             namespace: None,
+            create_namespace: true,
             port_forward: BTreeMap::new(),
             depends_on: vec![],
         };
@@ -1861,6 +3281,29 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_addon_helm_set_string() {
+        let toml_str = r#"
+            [project]
+            name = "test"
+
+            [cluster.addons.myapp]
+            type = "helm"
+            chart = "./charts/myapp"
+            namespace = "myapp"
+
+            [cluster.addons.myapp.set_string]
+            "image.tag" = "1.0"
+        "#;
+        let config: DevrigConfig = toml::from_str(toml_str).unwrap();
+        match &config.cluster.unwrap().addons["myapp"] {
+            AddonConfig::Helm { set_string, .. } => {
+                assert_eq!(set_string["image.tag"], "1.0");
+            }
+            other => panic!("expected Helm addon, got {:?}", other),
+        }
+    }
+
     #[test]
     fn parse_addon_remote_helm_with_values_files() {
         let toml_str = r#"
@@ -1896,11 +3339,41 @@ mod tests {
             port: Port::Fixed(4000),
             enabled: Some(true),
             otel: Some(OtelConfig::default()),
+            title: None,
+            tls: None,
         };
         let b = a.clone();
         assert_eq!(a, b);
     }
 
+    #[test]
+    fn dashboard_title_defaults_to_project_name() {
+        let dash: DashboardConfig = toml::from_str("").unwrap();
+        assert_eq!(dash.title_or("myapp"), "myapp");
+
+        let dash: DashboardConfig = toml::from_str(r#"title = "billing rig""#).unwrap();
+        assert_eq!(dash.title_or("myapp"), "billing rig");
+    }
+
+    #[test]
+    fn dashboard_tls_needs_cert_and_key() {
+        let dash: DashboardConfig = toml::from_str(
+            r#"
+            [tls]
+            cert = "certs/dev.pem"
+            key = "certs/dev-key.pem"
+            otel = true
+            "#,
+        )
+        .unwrap();
+        let tls = dash.tls.unwrap();
+        assert_eq!(tls.files(), Some(("certs/dev.pem", "certs/dev-key.pem")));
+        assert!(tls.otel);
+
+        let dash: DashboardConfig = toml::from_str("[tls]\ncert = \"dev.pem\"").unwrap();
+        assert_eq!(dash.tls.unwrap().files(), None);
+    }
+
     // --- ClusterLogsConfig tests ---
 
     #[test]
@@ -2032,6 +3505,27 @@ mod tests {
         assert!(err.to_string().contains("expected \"all\""));
     }
 
+    #[test]
+    fn parse_shell_env_passthrough() {
+        let parse = |value: &str| {
+            let toml_str = format!("[project]\nname = \"test\"\n{}", value);
+            toml::from_str::<DevrigConfig>(&toml_str).map(|c| c.project.shell_env_passthrough)
+        };
+        assert_eq!(parse("").unwrap(), EnvPassthrough::All);
+        assert_eq!(
+            parse("shell_env_passthrough = \"none\"").unwrap(),
+            EnvPassthrough::None
+        );
+        let only = parse("shell_env_passthrough = [\"PATH\", \"HOME\"]").unwrap();
+        assert_eq!(
+            only,
+            EnvPassthrough::Only(vec!["PATH".to_string(), "HOME".to_string()])
+        );
+        assert!(only.allows("PATH"));
+        assert!(!only.allows("AWS_PROFILE"));
+        assert!(parse("shell_env_passthrough = \"some\"").is_err());
+    }
+
     // --- Secrets management config tests ---
 
     #[test]
@@ -2066,7 +3560,45 @@ mod tests {
             env_file = ".env.api"
         "#;
         let config: DevrigConfig = toml::from_str(toml_str).unwrap();
-        assert_eq!(config.services["api"].env_file.as_deref(), Some(".env.api"));
+        assert_eq!(
+            config.services["api"].env_file,
+            Some(StringOrList(vec![".env.api".to_string()]))
+        );
+    }
+
+    #[test]
+    fn parse_service_env_file_list() {
+        let toml_str = r#"
+            [project]
+            name = "test"
+
+            [services.api]
+            command = "cargo run"
+            env_file = [".env", ".env.local?"]
+        "#;
+        let config: DevrigConfig = toml::from_str(toml_str).unwrap();
+        let files = config.services["api"].env_file.as_ref().unwrap();
+        assert_eq!(files.as_slice(), [".env", ".env.local?"]);
+    }
+
+    #[test]
+    fn parse_service_hooks() {
+        let toml_str = r#"
+            [project]
+            name = "test"
+
+            [services.api]
+            command = "cargo run"
+            pre_start = ["cargo sqlx prepare", "./seed.sh"]
+            post_stop = "rm -rf tmp"
+        "#;
+        let config: DevrigConfig = toml::from_str(toml_str).unwrap();
+        let api = &config.services["api"];
+        assert_eq!(
+            api.pre_start.as_ref().unwrap().as_slice(),
+            ["cargo sqlx prepare", "./seed.sh"]
+        );
+        assert_eq!(api.post_stop.as_ref().unwrap().as_slice(), ["rm -rf tmp"]);
     }
 
     #[test]
@@ -2268,4 +3800,175 @@ mod tests {
         let sol = StringOrList(vec!["a".to_string(), "b".to_string()]);
         assert_eq!(sol.into_vec(), vec!["a".to_string(), "b".to_string()]);
     }
+
+    #[test]
+    fn serialize_round_trips_input_forms() {
+        let toml_str = r#"
+            [project]
+            name = "test"
+            shell_env_passthrough = ["PATH", "HOME"]
+
+            [docker.app]
+            image = "app:latest"
+            entrypoint = "python"
+            command = ["app.py", "--verbose"]
+
+            [compose]
+            file = "docker-compose.yml"
+
+            [cluster.logs]
+            namespaces = "all"
+        "#;
+        let config: DevrigConfig = toml::from_str(toml_str).unwrap();
+        let value = toml::Value::try_from(&config).unwrap();
+        assert_eq!(
+            value["project"]["shell_env_passthrough"][1].as_str(),
+            Some("HOME")
+        );
+        assert_eq!(
+            value["docker"]["app"]["entrypoint"].as_str(),
+            Some("python")
+        );
+        assert_eq!(
+            value["compose"]["file"].as_str(),
+            Some("docker-compose.yml")
+        );
+        assert_eq!(value["cluster"]["logs"]["namespaces"].as_str(), Some("all"));
+
+        let reparsed: DevrigConfig = toml::from_str(&toml::to_string(&config).unwrap()).unwrap();
+        assert_eq!(reparsed.docker["app"], config.docker["app"]);
+        assert_eq!(
+            reparsed.project.shell_env_passthrough,
+            config.project.shell_env_passthrough
+        );
+    }
+
+    #[test]
+    fn depends_on_map_form_sets_conditions() {
+        let toml_str = r#"
+            [project]
+            name = "test"
+
+            [docker.postgres]
+            image = "postgres:16"
+
+            [docker.redis]
+            image = "redis:7"
+
+            [services.api]
+            command = "cargo run"
+            depends_on = { postgres = "healthy", redis = "started" }
+
+            [services.worker]
+            command = "cargo run --bin worker"
+            depends_on = { api = "service_started", postgres = "service_healthy" }
+
+            [services.web]
+            command = "npm run dev"
+            depends_on = ["api"]
+        "#;
+        let config: DevrigConfig = toml::from_str(toml_str).unwrap();
+
+        let api = &config.services["api"].depends_on;
+        assert_eq!(api.names().collect::<Vec<_>>(), vec!["postgres", "redis"]);
+        assert!(api.waits_for_health("postgres"));
+        assert!(!api.waits_for_health("redis"));
+
+        let worker = &config.services["worker"].depends_on;
+        assert!(!worker.waits_for_health("api"));
+        assert!(worker.waits_for_health("postgres"));
+
+        // The list form waits for readiness.
+        let web = &config.services["web"].depends_on;
+        assert_eq!(web.0[0].condition, DependencyCondition::Healthy);
+    }
+
+    #[test]
+    fn depends_on_rejects_unknown_condition() {
+        let toml_str = r#"
+            [project]
+            name = "test"
+
+            [services.api]
+            command = "cargo run"
+            depends_on = { postgres = "ready" }
+        "#;
+        let err = toml::from_str::<DevrigConfig>(toml_str).unwrap_err();
+        assert!(err.to_string().contains("healthy"), "{err}");
+    }
+
+    #[test]
+    fn depends_on_serializes_in_input_form() {
+        let toml_str = r#"
+            [project]
+            name = "test"
+
+            [services.api]
+            command = "cargo run"
+            depends_on = { postgres = "healthy", redis = "started" }
+
+            [services.web]
+            command = "npm run dev"
+            depends_on = ["api"]
+        "#;
+        let config: DevrigConfig = toml::from_str(toml_str).unwrap();
+        let value = toml::Value::try_from(&config).unwrap();
+        assert_eq!(
+            value["services"]["api"]["depends_on"]["redis"].as_str(),
+            Some("started")
+        );
+        assert_eq!(
+            value["services"]["web"]["depends_on"][0].as_str(),
+            Some("api")
+        );
+
+        let reparsed: DevrigConfig = toml::from_str(&toml::to_string(&config).unwrap()).unwrap();
+        assert_eq!(reparsed.services["api"], config.services["api"]);
+        assert_eq!(reparsed.services["web"], config.services["web"]);
+    }
+
+    #[test]
+    fn init_accepts_strings_files_and_dirs() {
+        let toml_str = r#"
+            [project]
+            name = "test"
+
+            [docker.postgres]
+            image = "postgres:16"
+            init = [
+                "CREATE DATABASE app;",
+                { file = "seed.sql" },
+                { dir = "migrations" },
+            ]
+        "#;
+        let config: DevrigConfig = toml::from_str(toml_str).unwrap();
+        let pg = &config.docker["postgres"];
+        assert_eq!(
+            pg.init,
+            vec![
+                InitScript::Inline("CREATE DATABASE app;".to_string()),
+                InitScript::File("seed.sql".to_string()),
+                InitScript::Dir("migrations".to_string()),
+            ]
+        );
+
+        let reparsed: DevrigConfig = toml::from_str(&toml::to_string(&config).unwrap()).unwrap();
+        assert_eq!(reparsed.docker["postgres"].init, pg.init);
+
+        for bad in [
+            r#"{ file = "a.sql", dir = "m" }"#,
+            "{}",
+            r#"{ path = "a.sql" }"#,
+        ] {
+            let toml_str = format!(
+                "[project]\nname = \"t\"\n[docker.pg]\nimage = \"postgres\"\ninit = [{}]\n",
+                bad
+            );
+            assert!(
+                toml::from_str::<DevrigConfig>(&toml_str).is_err(),
+                "{}",
+                bad
+            );
+        }
+    }
 }
@@ -0,0 +1,738 @@
+//! JSON Schema for `devrig.toml`, printed by `devrig schema` so editors
+//! (e.g. Taplo / Even Better TOML) can offer completion and flag mistakes.
+//!
+//! The schema is written by hand next to the model rather than derived, so
+//! the shapes with custom (de)serializers — [`Port`](super::model::Port),
+//! `depends_on`, string-or-list fields and the `type`-tagged ready checks and
+//! addons — say exactly what the parser accepts. Keep it in step with
+//! `model.rs`; the tests fail when a model field is missing here.
+
+use serde_json::{json, Value};
+
+use super::model::{MAX_READY_CHECK_DEPTH, RELOAD_SIGNALS};
+
+const SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// The full schema, rooted at `DevrigConfig`.
+pub fn config_schema() -> Value {
+    let mut schema = object(
+        "devrig project configuration",
+        json!({
+            "project": reference("project"),
+            "services": map_of(reference("service")),
+            "docker": map_of(reference("docker")),
+            "compose": reference("compose"),
+            "cluster": reference("cluster"),
+            "dashboard": reference("dashboard"),
+            "env": string_map("Env injected into every service and docker container."),
+            "network": object("Docker network shared by `[docker.*]` containers.", json!({
+                "name": string("Network name. Defaults to `devrig-<slug>-net`."),
+            }), &[]),
+            "links": string_map("Extra links shown on the dashboard, name to URL."),
+            "profiles": map_of(json!({
+                "type": "array",
+                "items": { "type": "string" },
+                "description": "Resources started by `devrig start --profile <name>`.",
+            })),
+            "secrets": map_of(object("Value read from a command at load time, referenced as `$NAME`.", json!({
+                "exec": string("Shell command whose stdout is the value."),
+            }), &["exec"])),
+        }),
+        &["project"],
+    );
+    schema["$schema"] = json!(SCHEMA_DIALECT);
+    schema["title"] = json!("devrig.toml");
+    schema["$defs"] = definitions();
+    schema
+}
+
+fn definitions() -> Value {
+    json!({
+        "project": object("`[project]`", json!({
+            "name": string("Project name, used for the slug, container and network names."),
+            "env_file": string("`.env` file loaded for every service."),
+            "shell_env_passthrough": {
+                "description": "Which host environment variables services inherit: \"all\" (default), \"none\", or a list of names.",
+                "oneOf": [
+                    { "enum": ["all", "none"] },
+                    { "type": "array", "items": { "type": "string" } },
+                ],
+            },
+            "vars": string_map("Template variables, referenced as `{{ var.NAME }}`. Values may use `$VAR` and other vars."),
+            "env_prefix": {
+                "type": "string",
+                "description": "Prefix of injected discovery vars (default \"DEVRIG_\"); `OTEL_*` vars are not prefixed.",
+                "pattern": "^[A-Za-z_][A-Za-z0-9_]*$",
+            },
+        }), &["name"]),
+        "service": object("`[services.<name>]`: a local process.", json!({
+            "enabled": enabled(),
+            "path": string("Working directory, relative to the config file."),
+            "command": string("Command to run."),
+            "port": reference("port"),
+            "protocol": protocol(),
+            "env": string_map("Env for this service."),
+            "env_file": string_or_list("One or more `.env` files, lowest precedence first. A trailing `?` marks a file optional."),
+            "depends_on": reference("depends_on"),
+            "ready_check": reference("ready_check"),
+            "restart": reference("restart"),
+            "shell": string("Interpreter used to run `command`, e.g. \"bash -c\", or \"none\" to exec it directly."),
+            "reload_strategy": {
+                "description": "How `devrig reload-env` applies env changes.",
+                "enum": ["restart", "signal"],
+                "default": "restart",
+            },
+            "reload_signal": {
+                "description": "Signal sent by `devrig reload-env` with `reload_strategy = \"signal\"`.",
+                "enum": RELOAD_SIGNALS,
+                "default": "SIGHUP",
+            },
+            "max_log_lines_per_sec": integer("Log entries per second kept from this service's output."),
+            "pre_start": string_or_list("Command(s) run to completion before the service starts."),
+            "post_stop": string_or_list("Command(s) run after the service stops, best-effort."),
+            "log_format": {
+                "description": "Parse output lines as structured logs.",
+                "enum": ["json", "logfmt", "auto"],
+            },
+        }), &["command"]),
+        "docker": object("`[docker.<name>]`: a container.", json!({
+            "enabled": enabled(),
+            "image": string("Image to run."),
+            "port": reference("port"),
+            "container_port": port_number("Port the service listens on inside the container. Defaults to `port`."),
+            "protocol": protocol(),
+            "ports": map_of(reference("docker_port")),
+            "env": string_map("Env for the container."),
+            "volumes": string_list("Volume mounts, e.g. \"pgdata:/var/lib/postgresql/data\"."),
+            "command": string_or_list("Overrides the image's command. Supports `$VAR` and `{{ }}` templates per element."),
+            "entrypoint": string_or_list("Overrides the image's entrypoint. Supports `$VAR` and `{{ }}` templates per element."),
+            "ready_check": reference("ready_check"),
+            "init": {
+                "type": "array",
+                "description": "Run once after the container first becomes ready, and again when the scripts change.",
+                "items": {
+                    "oneOf": [
+                        string("Inline SQL (postgres images) or shell command."),
+                        object("A script file, relative to the config file.", json!({
+                            "file": string("Script file."),
+                        }), &["file"]),
+                        object("A directory whose files run in name order.", json!({
+                            "dir": string("Script directory."),
+                        }), &["dir"]),
+                    ],
+                },
+            },
+            "depends_on": reference("depends_on"),
+            "registry_auth": object("Credentials for pulling `image`.", json!({
+                "username": string("Registry username."),
+                "password": string("Registry password. Ignored when `password_command` is set."),
+                "password_command": string("Shell command that prints the password."),
+            }), &["username"]),
+            "pull_retries": integer("Times to retry a failed image pull. Defaults to 3."),
+            "restart": reference("restart"),
+            "stop_timeout": integer("Seconds to wait after SIGTERM before the container is killed."),
+            "healthcheck": object("Docker HEALTHCHECK, replacing the image's own.", json!({
+                "test": string_or_list("A string runs through the container's shell, a list is exec'd directly."),
+                "interval": integer("Seconds between checks."),
+                "timeout": integer("Seconds a single check may take."),
+                "retries": integer("Consecutive failures before the container is unhealthy."),
+                "start_period": integer("Seconds after start during which failures don't count."),
+            }), &["test"]),
+            "mem_limit": string("Memory cap: bytes with an optional b, k, m or g unit, e.g. \"512m\"."),
+            "cpus": json!({ "type": "number", "exclusiveMinimum": 0, "description": "CPU cap as a number of CPUs, e.g. 1.5." }),
+            "network_aliases": string_list("Extra DNS names for the container on the project network, e.g. \"db.internal\"."),
+            "extra_hosts": string_list("Extra /etc/hosts entries as \"HOST:IP\"; IP may be \"host-gateway\"."),
+        }), &["image"]),
+        "compose": object("`[compose]`: services from a docker-compose file.", json!({
+            "file": string_or_list("A compose file, or a base file followed by overrides."),
+            "project_name": string("Compose project name. Defaults to the devrig project slug."),
+            "services": string_list("Compose services to start. Empty starts them all."),
+            "env_file": string("`.env` file passed to `docker compose`."),
+            "ready_checks": map_of(reference("ready_check")),
+            "protocols": string_map("URL scheme per compose service for `DEVRIG_<NAME>_URL`."),
+        }), &["file"]),
+        "cluster": object("`[cluster]`: a local k3d cluster.", json!({
+            "name": string("Cluster name. Defaults to `<project name>-dev`."),
+            "agents": integer_default("Agent nodes.", 1),
+            "ports": string_list("Port mappings passed to `k3d cluster create -p`."),
+            "volumes": string_list("Volume mounts passed to `k3d cluster create -v`."),
+            "registry": { "type": "boolean", "default": true, "description": "Create a local image registry." },
+            "image": map_of(object("`[cluster.image.<name>]`: an image built and pushed to the cluster registry.", json!({
+                "enabled": enabled(),
+                "context": string("Build context."),
+                "dockerfile": string_default("Dockerfile, relative to `context`.", "Dockerfile"),
+                "watch": boolean("Rebuild when files in `context` change."),
+                "depends_on": string_list("Images or deploys built first."),
+                "build_secrets": string_map("BuildKit secrets, id to file."),
+                "build_args": string_map("Docker build arguments."),
+            }), &["context"])),
+            "deploy": map_of(object("`[cluster.deploy.<name>]`: an image built and applied with its manifests.", json!({
+                "enabled": enabled(),
+                "context": string("Build context."),
+                "dockerfile": string_default("Dockerfile, relative to `context`.", "Dockerfile"),
+                "manifests": string("Manifest file or directory applied with kubectl."),
+                "watch": boolean("Rebuild and redeploy when files in `context` change."),
+                "depends_on": string_list("Images or deploys built first."),
+                "build_secrets": string_map("BuildKit secrets, id to file."),
+                "env": string_map("Env for the deployment's pods, materialized as a ConfigMap."),
+            }), &["context", "manifests"])),
+            "addons": map_of(reference("addon")),
+            "logs": object("`[cluster.logs]`: pod log collection.", json!({
+                "enabled": { "type": "boolean", "default": true, "description": "Collect logs from the cluster." },
+                "collector": { "type": "boolean", "default": true, "description": "Deploy the built-in Fluent Bit collector." },
+                "namespaces": {
+                    "description": "Namespaces to collect from: \"all\" or a list. Default: [\"default\"].",
+                    "oneOf": [
+                        { "const": "all" },
+                        { "type": "array", "items": { "type": "string" } },
+                    ],
+                },
+                "exclude_namespaces": string_list("Namespaces to skip (with `namespaces = \"all\"`). Names, globs or regexes."),
+                "exclude_pods": string_list("Pods to skip. Names, globs or regexes."),
+            }), &[]),
+            "registries": {
+                "type": "array",
+                "description": "Credentials for private registries the cluster pulls from.",
+                "items": object("A registry login.", json!({
+                    "url": string("Registry host."),
+                    "username": string("Registry username."),
+                    "password": string("Registry password."),
+                }), &["url", "username", "password"]),
+            },
+            "k3s_args": string_list("Extra `--k3s-arg` values."),
+            "k3s_image": string("k3s node image, e.g. \"rancher/k3s:v1.31.4-k3s1\"."),
+            "env": string_map("Env injected into every `[cluster.deploy.*]`."),
+            "command_retries": integer_default("Retries for read-only k3d/kubectl calls.", 3),
+        }), &[]),
+        "dashboard": object("`[dashboard]`: the web dashboard and OTLP receivers.", json!({
+            "port": with_default(reference("port"), json!(4000)),
+            "enabled": boolean("Start the dashboard. Defaults to true when the section is present."),
+            "otel": object("`[dashboard.otel]`", json!({
+                "grpc_port": with_default(reference("port"), json!(4317)),
+                "http_port": with_default(reference("port"), json!(4318)),
+                "trace_buffer": integer_default("Spans kept in memory.", 10000),
+                "metric_buffer": integer_default("Metric points kept in memory.", 50000),
+                "log_buffer": integer_default("Log records kept in memory.", 100000),
+                "retention": string_default("How long telemetry is kept, e.g. \"1h\".", "1h"),
+                "wal": boolean("Persist telemetry to `.devrig/telemetry/` across restarts."),
+                "wal_max_mb": integer_default("Disk cap for the telemetry WAL, in megabytes.", 64),
+                "forward": object("`[dashboard.otel.forward]`: an upstream OTLP collector.", json!({
+                    "endpoint": string("Base URL of the collector."),
+                    "protocol": { "enum": ["grpc", "http"], "default": "grpc" },
+                    "headers": string_map("Extra headers sent with every export."),
+                }), &["endpoint"]),
+            }), &[]),
+            "title": string("Label for the browser tab and header. Defaults to the project name."),
+            "tls": object("`[dashboard.tls]`: serve over HTTPS.", json!({
+                "cert": string("PEM certificate, relative to the config file."),
+                "key": string("PEM private key, relative to the config file."),
+                "otel": boolean("Also serve the OTLP receivers over TLS."),
+            }), &[]),
+        }), &[]),
+        "port": {
+            "description": "A port number, \"auto\", or a range to pick a free port from.",
+            "oneOf": [
+                port_number("A fixed port."),
+                { "const": "auto", "description": "A free port, remembered across restarts." },
+                { "type": "string", "pattern": "^\\d+-\\d+$", "description": "The first free port in a range, e.g. \"3000-3010\"." },
+                reference("port_range"),
+            ],
+        },
+        "port_range": object("The first free port in `start..=end`, trying `preferred` first.", json!({
+            "start": port_number("First port of the range."),
+            "end": port_number("Last port of the range."),
+            "preferred": port_number("Port tried first."),
+        }), &["start", "end"]),
+        "docker_port": {
+            "description": "A port, \"auto\" or a range, optionally with a /tcp or /udp suffix, e.g. \"8053/udp\".",
+            "oneOf": [
+                port_number("A fixed TCP port."),
+                { "type": "string", "pattern": "^(\\d+|auto|\\d+-\\d+)(/(tcp|udp))?$" },
+                reference("port_range"),
+            ],
+        },
+        "depends_on": {
+            "description": "Names to wait for: a list (wait until ready) or a map of name to \"healthy\" / \"started\".",
+            "oneOf": [
+                { "type": "array", "items": { "type": "string" } },
+                {
+                    "type": "object",
+                    "additionalProperties": {
+                        "enum": ["healthy", "started", "service_healthy", "service_started"],
+                    },
+                },
+            ],
+        },
+        "restart": object("Restart policy.", json!({
+            "policy": {
+                "enum": ["always", "on-failure", "never"],
+                "default": "on-failure",
+            },
+            "max_restarts": integer_default("Restarts in a row before giving up.", 10),
+            "startup_max_restarts": integer_default("Restarts allowed while the service is still starting.", 3),
+            "startup_grace_ms": integer_default("How long a run must last to count as started.", 2000),
+            "initial_delay_ms": integer_default("First backoff delay.", 500),
+            "max_delay_ms": integer_default("Backoff cap.", 30000),
+            "retries_reset_after_ms": integer_default("Uptime after which the restart counters start over.", 60000),
+        }), &[]),
+        "ready_check": ready_check(),
+        "addon": addon(),
+    })
+}
+
+/// `ready_check`, tagged by `type`. Composites nest up to
+/// [`MAX_READY_CHECK_DEPTH`] levels, which validation enforces.
+fn ready_check() -> Value {
+    let timeout = || integer("Seconds to wait before giving up.");
+    let composite = |description: &str| {
+        json!({
+            "checks": {
+                "type": "array",
+                "items": reference("ready_check"),
+                "description": description,
+            },
+            "timeout": timeout(),
+        })
+    };
+    let variants = [
+        ("pg_isready", json!({ "timeout": timeout() }), vec![]),
+        (
+            "cmd",
+            json!({
+                "command": string("Command run until it exits 0."),
+                "expect": string("Text the output must contain."),
+                "timeout": timeout(),
+            }),
+            vec!["command"],
+        ),
+        (
+            "http",
+            json!({
+                "url": string("URL that must return a 2xx."),
+                "timeout": timeout(),
+            }),
+            vec!["url"],
+        ),
+        ("tcp", json!({ "timeout": timeout() }), vec![]),
+        (
+            "grpc",
+            json!({
+                "service": string("Service to ask the gRPC health service about."),
+                "timeout": timeout(),
+            }),
+            vec![],
+        ),
+        (
+            "log",
+            json!({
+                "match": string("Regex matched against the output."),
+                "timeout": timeout(),
+            }),
+            vec!["match"],
+        ),
+        (
+            "all_of",
+            composite("Checks that must all pass."),
+            vec!["checks"],
+        ),
+        (
+            "any_of",
+            composite("Checks of which one must pass."),
+            vec!["checks"],
+        ),
+    ];
+    tagged_union(
+        &format!(
+            "Readiness probe, selected by `type`. `all_of` / `any_of` nest up to {} levels.",
+            MAX_READY_CHECK_DEPTH
+        ),
+        variants,
+    )
+}
+
+/// `[cluster.addons.<name>]`, tagged by `type`.
+fn addon() -> Value {
+    let common = || {
+        json!({
+            "create_namespace": { "type": "boolean", "default": true },
+            "port_forward": string_map("Local port to `svc/<name>:<port>` forwarded while running."),
+            "depends_on": string_list("Addons installed first."),
+        })
+    };
+    let mut helm = common();
+    merge(
+        &mut helm,
+        json!({
+            "chart": string("Chart reference, e.g. \"bitnami/redis\" or a local path."),
+            "repo": string("Helm repository URL."),
+            "namespace": string("Release namespace."),
+            "version": string("Chart version."),
+            "values": { "type": "object", "description": "Values passed with `--set`." },
+            "values_files": string_list("Values files, relative to the config file."),
+            "set_string": string_map("Values passed with `--set-string`."),
+            "wait": { "type": "boolean", "default": true },
+            "timeout": string_default("Helm `--timeout`.", "5m"),
+            "skip_crds": boolean("Pass `--skip-crds`."),
+        }),
+    );
+    let mut manifest = common();
+    merge(
+        &mut manifest,
+        json!({
+            "path": string("Manifest file or directory."),
+            "namespace": string("Namespace to apply into."),
+        }),
+    );
+    let mut kustomize = common();
+    merge(
+        &mut kustomize,
+        json!({
+            "path": string("Kustomization directory."),
+            "namespace": string("Namespace to apply into."),
+        }),
+    );
+    tagged_union(
+        "Cluster addon, selected by `type`.",
+        [
+            ("helm", helm, vec!["chart", "namespace"]),
+            ("manifest", manifest, vec!["path"]),
+            ("kustomize", kustomize, vec!["path"]),
+        ],
+    )
+}
+
+/// A serde `#[serde(tag = "type")]` enum: one closed object per variant,
+/// each pinning `type` to its tag.
+fn tagged_union<const N: usize>(
+    description: &str,
+    variants: [(&str, Value, Vec<&str>); N],
+) -> Value {
+    let one_of: Vec<Value> = variants
+        .into_iter()
+        .map(|(tag, mut properties, mut required)| {
+            properties["type"] = json!({ "const": tag });
+            required.insert(0, "type");
+            object(&format!("`type = \"{}\"`", tag), properties, &required)
+        })
+        .collect();
+    json!({ "description": description, "oneOf": one_of })
+}
+
+fn object(description: &str, properties: Value, required: &[&str]) -> Value {
+    let mut schema = json!({
+        "type": "object",
+        "description": description,
+        "properties": properties,
+        "additionalProperties": false,
+    });
+    if !required.is_empty() {
+        schema["required"] = json!(required);
+    }
+    schema
+}
+
+fn merge(target: &mut Value, extra: Value) {
+    if let (Value::Object(target), Value::Object(extra)) = (target, extra) {
+        target.extend(extra);
+    }
+}
+
+fn reference(name: &str) -> Value {
+    json!({ "$ref": format!("#/$defs/{}", name) })
+}
+
+fn with_default(schema: Value, default: Value) -> Value {
+    json!({ "allOf": [schema], "default": default })
+}
+
+fn map_of(values: Value) -> Value {
+    json!({ "type": "object", "additionalProperties": values })
+}
+
+fn string(description: &str) -> Value {
+    json!({ "type": "string", "description": description })
+}
+
+fn string_default(description: &str, default: &str) -> Value {
+    json!({ "type": "string", "description": description, "default": default })
+}
+
+fn string_list(description: &str) -> Value {
+    json!({ "type": "array", "items": { "type": "string" }, "description": description })
+}
+
+fn string_map(description: &str) -> Value {
+    json!({
+        "type": "object",
+        "additionalProperties": { "type": "string" },
+        "description": description,
+    })
+}
+
+fn string_or_list(description: &str) -> Value {
+    json!({
+        "description": description,
+        "oneOf": [
+            { "type": "string" },
+            { "type": "array", "items": { "type": "string" } },
+        ],
+    })
+}
+
+fn integer(description: &str) -> Value {
+    json!({ "type": "integer", "minimum": 0, "description": description })
+}
+
+fn integer_default(description: &str, default: u64) -> Value {
+    json!({ "type": "integer", "minimum": 0, "description": description, "default": default })
+}
+
+fn port_number(description: &str) -> Value {
+    json!({ "type": "integer", "minimum": 1, "maximum": 65535, "description": description })
+}
+
+fn boolean(description: &str) -> Value {
+    json!({ "type": "boolean", "default": false, "description": description })
+}
+
+fn protocol() -> Value {
+    json!({
+        "description": "Scheme for dashboard links and `DEVRIG_<NAME>_URL`.",
+        "enum": ["http", "https", "grpc", "tcp", "udp"],
+        "default": "http",
+    })
+}
+
+fn enabled() -> Value {
+    json!({
+        "description": "`false` leaves this resource out. A string is a template such as \"{{ env.ENABLE_X }}\" resolved when the config is loaded.",
+        "oneOf": [{ "type": "boolean" }, { "type": "string" }],
+        "default": true,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::model::DevrigConfig;
+    use serde_json::Map;
+
+    fn properties<'a>(schema: &'a Value, pointer: &str) -> &'a Map<String, Value> {
+        schema
+            .pointer(&format!("{}/properties", pointer))
+            .and_then(Value::as_object)
+            .unwrap_or_else(|| panic!("no properties at {}", pointer))
+    }
+
+    /// Every key in `value` (a serialized model struct) is a property of the
+    /// object schema at `pointer`.
+    fn assert_covered(schema: &Value, pointer: &str, value: &Value) {
+        let known = properties(schema, pointer);
+        for key in value.as_object().unwrap().keys() {
+            assert!(
+                known.contains_key(key),
+                "`{}` missing from schema at {}",
+                key,
+                pointer
+            );
+        }
+    }
+
+    #[test]
+    fn schema_covers_every_model_field() {
+        // Every optional field set, so serialization emits every key.
+        let config: DevrigConfig = toml::from_str(
+            r#"
+            links = { docs = "http://localhost:3000/docs" }
+            profiles = { api = ["api"] }
+            secrets = { token = { exec = "cat token" } }
+            [project]
+            name = "demo"
+            env_file = ".env"
+            shell_env_passthrough = ["PATH"]
+            [env]
+            A = "1"
+            [network]
+            name = "net"
+            [services.api]
+            enabled = "{{ env.ENABLE_X }}"
+            path = "api"
+            command = "cargo run"
+            port = 3000
+            protocol = "http"
+            env_file = ".env.api"
+            depends_on = ["db"]
+            ready_check = { type = "http", url = "http://localhost:3000" }
+            restart = { policy = "always" }
+            shell = "bash -c"
+            reload_strategy = "signal"
+            reload_signal = "SIGHUP"
+            max_log_lines_per_sec = 100
+            pre_start = "make"
+            post_stop = "make clean"
+            log_format = "json"
+            [docker.db]
+            enabled = "{{ env.ENABLE_X }}"
+            image = "postgres:16"
+            port = 5432
+            container_port = 5432
+            protocol = "tcp"
+            ports = { admin = 8080 }
+            volumes = ["pg:/data"]
+            command = "postgres"
+            entrypoint = "docker-entrypoint.sh"
+            ready_check = { type = "pg_isready" }
+            init = ["select 1"]
+            registry_auth = { username = "u", password = "p", password_command = "echo" }
+            pull_retries = 2
+            stop_timeout = 5
+            healthcheck = { test = "true", interval = 1, timeout = 1, retries = 1, start_period = 1 }
+            [compose]
+            file = "docker-compose.yml"
+            project_name = "demo"
+            services = ["redis"]
+            env_file = ".env"
+            ready_checks = { redis = { type = "tcp" } }
+            protocols = { redis = "tcp" }
+            [cluster]
+            name = "demo"
+            registries = [{ url = "ghcr.io", username = "u", password = "p" }]
+            k3s_image = "rancher/k3s:v1.31.4-k3s1"
+            [cluster.image.job]
+            enabled = "{{ env.ENABLE_X }}"
+            context = "job"
+            [cluster.deploy.web]
+            enabled = "{{ env.ENABLE_X }}"
+            context = "web"
+            manifests = "k8s"
+            [cluster.addons.redis]
+            type = "helm"
+            chart = "bitnami/redis"
+            repo = "https://charts.bitnami.com/bitnami"
+            namespace = "redis"
+            version = "1.0"
+            [cluster.addons.extras]
+            type = "manifest"
+            path = "extras.yaml"
+            namespace = "extras"
+            [cluster.addons.overlay]
+            type = "kustomize"
+            path = "overlay"
+            namespace = "overlay"
+            [cluster.logs]
+            exclude_namespaces = ["kube-system"]
+            exclude_pods = ["noisy-*"]
+            [dashboard]
+            enabled = true
+            title = "Demo"
+            [dashboard.tls]
+            cert = "cert.pem"
+            key = "key.pem"
+            [dashboard.otel.forward]
+            endpoint = "http://localhost:4317"
+            "#,
+        )
+        .unwrap();
+        let value = serde_json::to_value(&config).unwrap();
+        let schema = config_schema();
+
+        assert_covered(&schema, "", &value);
+        assert_covered(&schema, "/$defs/project", &value["project"]);
+        assert_covered(&schema, "/properties/network", &value["network"]);
+        assert_covered(
+            &schema,
+            "/properties/secrets/additionalProperties",
+            &value["secrets"]["token"],
+        );
+        assert_covered(&schema, "/$defs/service", &value["services"]["api"]);
+        assert_covered(
+            &schema,
+            "/$defs/restart",
+            &value["services"]["api"]["restart"],
+        );
+        let db = &value["docker"]["db"];
+        assert_covered(&schema, "/$defs/docker", db);
+        assert_covered(
+            &schema,
+            "/$defs/docker/properties/registry_auth",
+            &db["registry_auth"],
+        );
+        assert_covered(
+            &schema,
+            "/$defs/docker/properties/healthcheck",
+            &db["healthcheck"],
+        );
+        assert_covered(&schema, "/$defs/compose", &value["compose"]);
+        let cluster = &value["cluster"];
+        assert_covered(&schema, "/$defs/cluster", cluster);
+        assert_covered(
+            &schema,
+            "/$defs/cluster/properties/image/additionalProperties",
+            &cluster["image"]["job"],
+        );
+        assert_covered(
+            &schema,
+            "/$defs/cluster/properties/deploy/additionalProperties",
+            &cluster["deploy"]["web"],
+        );
+        assert_covered(&schema, "/$defs/cluster/properties/logs", &cluster["logs"]);
+        assert_covered(
+            &schema,
+            "/$defs/cluster/properties/registries/items",
+            &cluster["registries"][0],
+        );
+        for (i, addon) in ["redis", "extras", "overlay"].iter().enumerate() {
+            assert_covered(
+                &schema,
+                &format!("/$defs/addon/oneOf/{}", i),
+                &cluster["addons"][addon],
+            );
+        }
+        let dashboard = &value["dashboard"];
+        assert_covered(&schema, "/$defs/dashboard", dashboard);
+        assert_covered(
+            &schema,
+            "/$defs/dashboard/properties/tls",
+            &dashboard["tls"],
+        );
+        assert_covered(
+            &schema,
+            "/$defs/dashboard/properties/otel",
+            &dashboard["otel"],
+        );
+        assert_covered(
+            &schema,
+            "/$defs/dashboard/properties/otel/properties/forward",
+            &dashboard["otel"]["forward"],
+        );
+    }
+
+    #[test]
+    fn ready_check_variants_match_their_tags() {
+        let checks = [
+            r#"{ type = "pg_isready", timeout = 1 }"#,
+            r#"{ type = "cmd", command = "true", expect = "ok", timeout = 1 }"#,
+            r#"{ type = "http", url = "http://x", timeout = 1 }"#,
+            r#"{ type = "tcp", timeout = 1 }"#,
+            r#"{ type = "grpc", service = "s", timeout = 1 }"#,
+            r#"{ type = "log", match = "ready", timeout = 1 }"#,
+            r#"{ type = "all_of", checks = [], timeout = 1 }"#,
+            r#"{ type = "any_of", checks = [], timeout = 1 }"#,
+        ];
+        let schema = config_schema();
+        for (i, check) in checks.iter().enumerate() {
+            let parsed: crate::config::model::ReadyCheck =
+                toml::from_str::<toml::Table>(&format!("c = {}", check)).unwrap()["c"]
+                    .clone()
+                    .try_into()
+                    .unwrap();
+            let value = serde_json::to_value(&parsed).unwrap();
+            let pointer = format!("/$defs/ready_check/oneOf/{}", i);
+            assert_eq!(
+                schema.pointer(&format!("{}/properties/type/const", pointer)),
+                Some(&value["type"])
+            );
+            assert_covered(&schema, &pointer, &value);
+        }
+    }
+}
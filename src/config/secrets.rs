@@ -5,7 +5,7 @@ use std::sync::LazyLock;
 use anyhow::{bail, Context, Result};
 use regex::Regex;
 
-use super::model::DevrigConfig;
+use super::model::{is_valid_env_prefix, DevrigConfig, SecretSource, StringOrList};
 
 /// Compiled pattern matching `$VAR`, `${VAR}`, and `$$` escape sequences.
 static ENV_VAR_RE: LazyLock<Regex> = LazyLock::new(|| {
@@ -35,6 +35,11 @@ impl SecretRegistry {
         }
     }
 
+    /// Register every value `other` tracks.
+    pub fn merge(&mut self, other: SecretRegistry) {
+        self.secret_values.extend(other.secret_values);
+    }
+
     /// Replace any known secret substrings in `value` with `****`.
     pub fn mask_value(&self, value: &str) -> String {
         let mut result = value.to_string();
@@ -181,6 +186,57 @@ pub fn expand_env_vars(
     Ok((result, had_expansion))
 }
 
+// ---------------------------------------------------------------------------
+// [secrets] — values read from external commands
+// ---------------------------------------------------------------------------
+
+/// Run each `[secrets]` command once, in the config directory, and return
+/// the values by name. A failing command aborts the load, naming the secret.
+/// Names are checked first, so no command runs for a config with a bad one.
+pub fn resolve_secrets(
+    secrets: &BTreeMap<String, SecretSource>,
+    config_dir: &Path,
+) -> Result<BTreeMap<String, String>> {
+    // A valid prefix is also a valid complete variable name.
+    if let Some(name) = secrets.keys().find(|name| !is_valid_env_prefix(name)) {
+        bail!(
+            "invalid secret {:?}: not a valid environment variable name",
+            name
+        );
+    }
+    let mut values = BTreeMap::new();
+    for (name, source) in secrets {
+        let value = run_secret_command(&source.exec, config_dir)
+            .with_context(|| format!("reading secret {:?}", name))?;
+        values.insert(name.clone(), value);
+    }
+    Ok(values)
+}
+
+/// Run `command` with the platform shell (`sh -c`, or `cmd /C` on Windows)
+/// and return its stdout without the trailing newline.
+fn run_secret_command(command: &str, dir: &Path) -> Result<String> {
+    let output = crate::platform::script_command(command)
+        .current_dir(dir)
+        .output()
+        .with_context(|| format!("running '{}'", command))?;
+    if !output.status.success() {
+        bail!(
+            "'{}' failed ({}): {}",
+            command,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let stdout = String::from_utf8(output.stdout)
+        .with_context(|| format!("'{}' printed non-UTF-8 output", command))?;
+    let value = stdout.trim_end_matches(['\n', '\r']);
+    if value.is_empty() {
+        bail!("'{}' printed nothing", command);
+    }
+    Ok(value.to_string())
+}
+
 // ---------------------------------------------------------------------------
 // Config walker — expand $VAR across config fields
 // ---------------------------------------------------------------------------
@@ -202,9 +258,8 @@ pub fn load_env_files(
 
     // Per-service env_file values
     for (name, svc) in &config.services {
-        if let Some(env_file) = &svc.env_file {
-            let path = config_dir.join(env_file);
-            let file_vars = parse_env_file(&path)
+        if let Some(env_files) = &svc.env_file {
+            let file_vars = load_service_env_files(env_files.as_slice(), config_dir)
                 .with_context(|| format!("loading env_file for service {:?}", name))?;
             vars.extend(file_vars);
         }
@@ -213,6 +268,34 @@ pub fn load_env_files(
     Ok(vars)
 }
 
+/// Load a service's `env_file` list in order, later files overriding
+/// earlier ones. Entries ending in `?` are skipped when the file is missing;
+/// any other missing file is an error.
+pub fn load_service_env_files(
+    env_files: &[String],
+    config_dir: &Path,
+) -> Result<BTreeMap<String, String>> {
+    let mut vars = BTreeMap::new();
+    for entry in env_files {
+        let (file, optional) = match entry.strip_suffix('?') {
+            Some(file) => (file, true),
+            None => (entry.as_str(), false),
+        };
+        let path = config_dir.join(file);
+        if !path.exists() {
+            if optional {
+                continue;
+            }
+            bail!(
+                "env_file {:?} not found (append `?` to make it optional)",
+                file
+            );
+        }
+        vars.extend(parse_env_file(&path)?);
+    }
+    Ok(vars)
+}
+
 /// Merge .env file values into config.env and per-service env maps.
 /// .env values have lower priority than explicit TOML values.
 pub fn merge_env_file_values(
@@ -230,9 +313,8 @@ pub fn merge_env_file_values(
 
     // Per-service env_file -> service.env (lower priority)
     for svc in config.services.values_mut() {
-        if let Some(env_file) = &svc.env_file {
-            let path = config_dir.join(env_file);
-            let file_vars = parse_env_file(&path)?;
+        if let Some(env_files) = &svc.env_file {
+            let file_vars = load_service_env_files(env_files.as_slice(), config_dir)?;
             for (key, value) in file_vars {
                 svc.env.entry(key).or_insert(value);
             }
@@ -242,6 +324,30 @@ pub fn merge_env_file_values(
     Ok(())
 }
 
+/// Expand `$VAR` in each argument of a docker `command` or `entrypoint`
+/// independently, naming list elements `{field_context}[i]` in errors.
+fn expand_args(
+    args: &mut StringOrList,
+    env_file_vars: &BTreeMap<String, String>,
+    field_context: &str,
+    registry: &mut SecretRegistry,
+) -> Result<()> {
+    let is_list = args.0.len() > 1;
+    for (i, arg) in args.0.iter_mut().enumerate() {
+        let context = if is_list {
+            format!("{field_context}[{i}]")
+        } else {
+            field_context.to_string()
+        };
+        let (expanded, was_secret) = expand_env_vars(arg, env_file_vars, &context)?;
+        if was_secret {
+            registry.track(&expanded);
+        }
+        *arg = expanded;
+    }
+    Ok(())
+}
+
 /// Walk the config and expand `$VAR` references in all supported string fields.
 /// Returns a `SecretRegistry` tracking which values came from expansion.
 pub fn expand_config_env_vars(
@@ -250,6 +356,17 @@ pub fn expand_config_env_vars(
 ) -> Result<SecretRegistry> {
     let mut registry = SecretRegistry::new();
 
+    // Project vars, already resolved against each other
+    let pairs: Vec<(String, String)> = config.project.vars.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+    for (key, value) in pairs {
+        let (expanded, was_secret) =
+            expand_env_vars(&value, env_file_vars, &format!("project.vars.{key}"))?;
+        if was_secret {
+            registry.track(&expanded);
+        }
+        config.project.vars.insert(key, expanded);
+    }
+
     // Global env values
     let pairs: Vec<(String, String)> = config.env.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
     for (key, value) in pairs {
@@ -320,6 +437,18 @@ pub fn expand_config_env_vars(
             docker.image = expanded;
         }
 
+        // docker.*.command and docker.*.entrypoint, each argument on its own
+        if let Some(docker) = config.docker.get_mut(&docker_name) {
+            if let Some(command) = &mut docker.command {
+                let context = format!("docker.{docker_name}.command");
+                expand_args(command, env_file_vars, &context, &mut registry)?;
+            }
+            if let Some(entrypoint) = &mut docker.entrypoint {
+                let context = format!("docker.{docker_name}.entrypoint");
+                expand_args(entrypoint, env_file_vars, &context, &mut registry)?;
+            }
+        }
+
         // docker.*.registry_auth
         if let Some(auth) = config.docker[&docker_name].registry_auth.as_ref() {
             let username = auth.username.clone();
@@ -384,6 +513,55 @@ pub fn expand_config_env_vars(
             reg.username = expanded_user;
             reg.password = expanded_pass;
         }
+
+        // cluster.env and cluster.deploy.*.env values
+        for (key, value) in cluster.env.iter_mut() {
+            let (expanded, was_secret) =
+                expand_env_vars(value, env_file_vars, &format!("cluster.env.{key}"))?;
+            if was_secret {
+                registry.track(&expanded);
+            }
+            *value = expanded;
+        }
+        for (deploy_name, deploy) in cluster.deploy.iter_mut() {
+            for (key, value) in deploy.env.iter_mut() {
+                let (expanded, was_secret) = expand_env_vars(
+                    value,
+                    env_file_vars,
+                    &format!("cluster.deploy.{deploy_name}.env.{key}"),
+                )?;
+                if was_secret {
+                    registry.track(&expanded);
+                }
+                *value = expanded;
+            }
+        }
+    }
+
+    // dashboard.otel.forward endpoint and headers
+    if let Some(forward) = config
+        .dashboard
+        .as_mut()
+        .and_then(|d| d.otel.as_mut())
+        .and_then(|o| o.forward.as_mut())
+    {
+        let (expanded, _) = expand_env_vars(
+            &forward.endpoint,
+            env_file_vars,
+            "dashboard.otel.forward.endpoint",
+        )?;
+        forward.endpoint = expanded;
+        for (key, value) in forward.headers.iter_mut() {
+            let (expanded, was_secret) = expand_env_vars(
+                value,
+                env_file_vars,
+                &format!("dashboard.otel.forward.headers.{key}"),
+            )?;
+            if was_secret {
+                registry.track(&expanded);
+            }
+            *value = expanded;
+        }
     }
 
     Ok(registry)
@@ -442,6 +620,51 @@ mod tests {
         assert!(vars.is_empty());
     }
 
+    #[test]
+    fn service_env_files_merge_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join(".env"), "A=base\nB=base\n").unwrap();
+        std::fs::write(dir.path().join(".env.local"), "B=local\n").unwrap();
+
+        let files = vec![
+            ".env".to_string(),
+            ".env.local".to_string(),
+            ".env.development?".to_string(),
+        ];
+        let vars = load_service_env_files(&files, dir.path()).unwrap();
+        assert_eq!(vars["A"], "base");
+        assert_eq!(vars["B"], "local");
+
+        let err = load_service_env_files(&[".env.missing".to_string()], dir.path()).unwrap_err();
+        assert!(err.to_string().contains(".env.missing"), "{err}");
+    }
+
+    #[test]
+    fn toml_service_env_wins_over_env_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join(".env"), "PORT=1\nDEBUG=1\n").unwrap();
+        std::fs::write(dir.path().join(".env.local"), "PORT=2\n").unwrap();
+
+        let mut config: DevrigConfig = toml::from_str(
+            r#"
+            [project]
+            name = "test"
+
+            [services.api]
+            command = "run"
+            env_file = [".env", ".env.local"]
+
+            [services.api.env]
+            DEBUG = "0"
+            "#,
+        )
+        .unwrap();
+        merge_env_file_values(&mut config, dir.path()).unwrap();
+        let env = &config.services["api"].env;
+        assert_eq!(env["PORT"], "2");
+        assert_eq!(env["DEBUG"], "0");
+    }
+
     // --- $VAR expansion tests ---
 
     #[test]
@@ -558,9 +781,13 @@ mod tests {
         use crate::config::model::*;
 
         let mut config = DevrigConfig {
+            disabled: Default::default(),
             project: ProjectConfig {
                 name: "test".to_string(),
                 env_file: None,
+                shell_env_passthrough: Default::default(),
+                vars: BTreeMap::new(),
+                env_prefix: None,
             },
             services: BTreeMap::new(),
             docker: BTreeMap::new(),
@@ -570,6 +797,8 @@ mod tests {
             env: BTreeMap::from([("KEY".to_string(), "$TEST_SECRET".to_string())]),
             network: None,
             links: BTreeMap::new(),
+            profiles: BTreeMap::new(),
+            secrets: BTreeMap::new(),
         };
 
         let env_file_vars =
@@ -586,9 +815,13 @@ mod tests {
         use crate::config::model::*;
 
         let mut config = DevrigConfig {
+            disabled: Default::default(),
             project: ProjectConfig {
                 name: "test".to_string(),
                 env_file: None,
+                shell_env_passthrough: Default::default(),
+                vars: BTreeMap::new(),
+                env_prefix: None,
             },
             services: BTreeMap::new(),
             docker: BTreeMap::new(),
@@ -598,6 +831,8 @@ mod tests {
             env: BTreeMap::from([("PLAIN".to_string(), "no-vars-here".to_string())]),
             network: None,
             links: BTreeMap::new(),
+            profiles: BTreeMap::new(),
+            secrets: BTreeMap::new(),
         };
 
         let env_file_vars = BTreeMap::new();
@@ -606,4 +841,137 @@ mod tests {
         assert_eq!(config.env["PLAIN"], "no-vars-here");
         assert!(!registry.contains_secret("no-vars-here"));
     }
+
+    #[test]
+    fn expand_config_otel_forward_headers() {
+        let mut config: crate::config::model::DevrigConfig = toml::from_str(
+            r#"
+            [project]
+            name = "test"
+
+            [dashboard.otel.forward]
+            endpoint = "http://${TEMPO_HOST}:4317"
+            headers = { authorization = "Bearer $OTEL_TOKEN" }
+            "#,
+        )
+        .unwrap();
+        let env_file_vars = BTreeMap::from([
+            ("TEMPO_HOST".to_string(), "tempo".to_string()),
+            ("OTEL_TOKEN".to_string(), "s3cret".to_string()),
+        ]);
+
+        let registry = expand_config_env_vars(&mut config, &env_file_vars).unwrap();
+
+        let forward = config.dashboard.unwrap().otel.unwrap().forward.unwrap();
+        assert_eq!(forward.endpoint, "http://tempo:4317");
+        assert_eq!(forward.headers["authorization"], "Bearer s3cret");
+        assert!(registry.contains_secret("Bearer s3cret"));
+    }
+
+    #[test]
+    fn expand_config_docker_command_and_entrypoint() {
+        let mut config: crate::config::model::DevrigConfig = toml::from_str(
+            r#"
+            [project]
+            name = "test"
+
+            [docker.redis]
+            image = "redis:7"
+            command = ["redis-server", "--requirepass", "$REDIS_PASSWORD", "--port=${REDIS_PORT}"]
+            entrypoint = "$$ENTRY"
+            "#,
+        )
+        .unwrap();
+        let env_file_vars = BTreeMap::from([
+            ("REDIS_PASSWORD".to_string(), "hunter2".to_string()),
+            ("REDIS_PORT".to_string(), "6380".to_string()),
+        ]);
+
+        let registry = expand_config_env_vars(&mut config, &env_file_vars).unwrap();
+
+        let redis = &config.docker["redis"];
+        assert_eq!(
+            redis.command.as_ref().unwrap().as_slice(),
+            ["redis-server", "--requirepass", "hunter2", "--port=6380"]
+        );
+        assert_eq!(redis.entrypoint.as_ref().unwrap().as_slice(), ["$ENTRY"]);
+        assert!(registry.contains_secret("hunter2"));
+    }
+
+    #[test]
+    fn expand_config_docker_command_error_names_element() {
+        let mut config: crate::config::model::DevrigConfig = toml::from_str(
+            r#"
+            [project]
+            name = "test"
+
+            [docker.redis]
+            image = "redis:7"
+            command = ["redis-server", "$DEFINITELY_UNDEFINED_VAR_67890"]
+            "#,
+        )
+        .unwrap();
+        let err = expand_config_env_vars(&mut config, &BTreeMap::new())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("docker.redis.command[1]"), "{err}");
+    }
+
+    // --- [secrets] tests ---
+
+    #[cfg(unix)]
+    #[test]
+    fn resolve_secrets_runs_commands_in_config_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("token"), "s3cr3t\n").unwrap();
+        let secrets = BTreeMap::from([(
+            "API_TOKEN".to_string(),
+            SecretSource {
+                exec: "cat token".to_string(),
+            },
+        )]);
+        let values = resolve_secrets(&secrets, dir.path()).unwrap();
+        assert_eq!(values["API_TOKEN"], "s3cr3t");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn resolve_secrets_failure_names_the_secret() {
+        let secrets = BTreeMap::from([(
+            "db_password".to_string(),
+            SecretSource {
+                exec: "echo 'not signed in' >&2; exit 3".to_string(),
+            },
+        )]);
+        let err = resolve_secrets(&secrets, Path::new(".")).unwrap_err();
+        let message = format!("{:#}", err);
+        assert!(message.contains("reading secret \"db_password\""), "{message}");
+        assert!(message.contains("not signed in"), "{message}");
+    }
+
+    #[test]
+    fn resolve_secrets_checks_names_before_running_anything() {
+        let dir = tempfile::tempdir().unwrap();
+        let marker = dir.path().join("ran");
+        let secrets = BTreeMap::from([
+            (
+                "API_TOKEN".to_string(),
+                SecretSource {
+                    exec: format!("echo ran > {}", marker.display()),
+                },
+            ),
+            (
+                "db-password".to_string(),
+                SecretSource {
+                    exec: "echo hunter2".to_string(),
+                },
+            ),
+        ]);
+        let err = resolve_secrets(&secrets, dir.path()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid secret \"db-password\": not a valid environment variable name"
+        );
+        assert!(!marker.exists());
+    }
 }
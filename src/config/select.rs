@@ -0,0 +1,90 @@
+//! Shell-style glob selection of resources by name, as accepted by
+//! `devrig start` and `devrig logs`.
+
+use anyhow::{bail, Context, Result};
+use glob::Pattern;
+
+/// Whether `name` is a glob pattern rather than a literal name.
+pub fn is_pattern(name: &str) -> bool {
+    name.contains(['*', '?', '['])
+}
+
+/// Expand glob patterns in `requested` (`api-*`, `*-worker`) against
+/// `available`. Literal names are passed through untouched, so callers keep
+/// their own handling of unknown names. Matches keep `available`'s order and
+/// duplicates are dropped. A pattern that matches nothing is an error.
+pub fn expand_patterns<'a>(
+    requested: &[String],
+    available: impl IntoIterator<Item = &'a String> + Clone,
+) -> Result<Vec<String>> {
+    let mut selected: Vec<String> = Vec::new();
+    for name in requested {
+        if !is_pattern(name) {
+            if !selected.contains(name) {
+                selected.push(name.clone());
+            }
+            continue;
+        }
+        let pattern = Pattern::new(name).with_context(|| format!("invalid pattern '{}'", name))?;
+        let mut matched = false;
+        for candidate in available.clone() {
+            if pattern.matches(candidate) {
+                matched = true;
+                if !selected.contains(candidate) {
+                    selected.push(candidate.clone());
+                }
+            }
+        }
+        if !matched {
+            bail!(
+                "pattern '{}' matches no service (available: {:?})",
+                name,
+                available.clone().into_iter().collect::<Vec<_>>()
+            );
+        }
+    }
+    Ok(selected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn globs_expand_in_available_order() {
+        let available = names(&["api-gateway", "api-users", "billing-worker", "web"]);
+        let selected =
+            expand_patterns(&names(&["*-worker", "api-*", "api-users"]), &available).unwrap();
+        assert_eq!(
+            selected,
+            names(&["billing-worker", "api-gateway", "api-users"])
+        );
+
+        let selected = expand_patterns(&names(&["we?", "[ab]*-users"]), &available).unwrap();
+        assert_eq!(selected, names(&["web", "api-users"]));
+    }
+
+    #[test]
+    fn literal_names_pass_through() {
+        let available = names(&["api", "web"]);
+        assert_eq!(
+            expand_patterns(&names(&["web", "missing"]), &available).unwrap(),
+            names(&["web", "missing"])
+        );
+    }
+
+    #[test]
+    fn unmatched_or_invalid_pattern_is_an_error() {
+        let available = names(&["api", "web"]);
+        let err = expand_patterns(&names(&["db-*"]), &available).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "pattern 'db-*' matches no service (available: [\"api\", \"web\"])"
+        );
+        assert!(expand_patterns(&names(&["[api"]), &available).is_err());
+    }
+}
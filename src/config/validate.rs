@@ -2,12 +2,18 @@
 // positive unused_assignments warnings on enum variant fields.
 #![allow(unused_assignments)]
 
-use std::collections::{BTreeMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::path::Path;
 
 use miette::{Diagnostic, NamedSource, SourceSpan};
+use serde::Serialize;
 use thiserror::Error;
 
-use crate::config::model::{DevrigConfig, Port};
+use crate::config::interpolate::TemplateError;
+use crate::config::model::{
+    ClusterLogsConfig, DevrigConfig, OtelForwardConfig, OtlpProtocol, Port, PortProtocol,
+    is_valid_env_prefix, parse_mem_limit, ReadyCheck, MAX_READY_CHECK_DEPTH, MAX_STOP_TIMEOUT_SECS, RELOAD_SIGNALS,
+};
 
 // ---------------------------------------------------------------------------
 // ConfigDiagnostic — miette-powered validation error
@@ -28,7 +34,33 @@ pub enum ConfigDiagnostic {
         dependency: String,
     },
 
-    #[error("port {port} is used by multiple resources: {services:?}")]
+    #[error("dependency `{dependency}` is disabled")]
+    #[diagnostic(code(devrig::disabled_dependency))]
+    DisabledDependency {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("`{service}` depends on `{dependency}`, which is not enabled")]
+        span: SourceSpan,
+        #[help]
+        advice: String,
+        service: String,
+        dependency: String,
+    },
+
+    #[error("unknown resource `{resource}` in profile `{profile}`")]
+    #[diagnostic(code(devrig::unknown_profile_resource))]
+    UnknownProfileResource {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("profile `{profile}` lists `{resource}`, which does not exist")]
+        span: SourceSpan,
+        #[help]
+        advice: String,
+        profile: String,
+        resource: String,
+    },
+
+    #[error("port {port}/{protocol} is used by multiple resources: {services:?}")]
     #[diagnostic(code(devrig::duplicate_port))]
     DuplicatePort {
         #[source_code]
@@ -36,9 +68,25 @@ pub enum ConfigDiagnostic {
         #[label("duplicate port")]
         span: SourceSpan,
         port: u16,
+        protocol: PortProtocol,
         services: Vec<String>,
     },
 
+    #[error("invalid port range `{value}` for {resource}: {reason}")]
+    #[diagnostic(
+        code(devrig::invalid_port_range),
+        help("write the lower port first, e.g. \"3000-3010\"")
+    )]
+    InvalidPortRange {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("invalid port range")]
+        span: SourceSpan,
+        resource: String,
+        value: String,
+        reason: String,
+    },
+
     #[error("dependency cycle detected involving `{node}`")]
     #[diagnostic(code(devrig::dependency_cycle))]
     DependencyCycle {
@@ -78,6 +126,44 @@ pub enum ConfigDiagnostic {
         span: SourceSpan,
     },
 
+    #[error("invalid compose project name `{name}`")]
+    #[diagnostic(
+        code(devrig::invalid_compose_project_name),
+        help("use lowercase letters, digits, dashes and underscores, starting with a letter or digit")
+    )]
+    InvalidComposeProjectName {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("invalid project name")]
+        span: SourceSpan,
+        name: String,
+    },
+
+    #[error("compose file `{path}` not found")]
+    #[diagnostic(
+        code(devrig::missing_compose_file),
+        help("paths in compose.file are relative to the directory containing devrig.toml")
+    )]
+    MissingComposeFile {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("no such file")]
+        span: SourceSpan,
+        path: String,
+    },
+
+    #[error("cluster.k3s_image is empty")]
+    #[diagnostic(
+        code(devrig::empty_k3s_image),
+        help("set a k3s image such as \"rancher/k3s:v1.31.4-k3s1\", or remove k3s_image to use the k3d default")
+    )]
+    EmptyK3sImage {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("image is empty")]
+        span: SourceSpan,
+    },
+
     #[error("cluster image `{image}` has an empty context")]
     #[diagnostic(code(devrig::empty_image_context))]
     EmptyImageContext {
@@ -143,6 +229,48 @@ pub enum ConfigDiagnostic {
         value: String,
     },
 
+    #[error("invalid reload signal `{value}` for service `{service}`")]
+    #[diagnostic(
+        code(devrig::invalid_reload_signal),
+        help("valid values are: SIGHUP, SIGUSR1, SIGUSR2, SIGWINCH")
+    )]
+    InvalidReloadSignal {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("invalid signal")]
+        span: SourceSpan,
+        service: String,
+        value: String,
+    },
+
+    #[error("max_log_lines_per_sec for service `{service}` must be at least 1")]
+    #[diagnostic(
+        code(devrig::invalid_max_log_rate),
+        help("remove the setting to keep every line")
+    )]
+    InvalidMaxLogRate {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("must be at least 1")]
+        span: SourceSpan,
+        service: String,
+    },
+
+    #[error("invalid shell `{value}` for service `{service}`: {reason}")]
+    #[diagnostic(
+        code(devrig::invalid_shell),
+        help("use an interpreter with its command flag (e.g. \"bash -c\") or \"none\" to exec directly")
+    )]
+    InvalidShell {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("invalid shell")]
+        span: SourceSpan,
+        service: String,
+        value: String,
+        reason: String,
+    },
+
     #[error("dashboard port {port} conflicts with {conflict_with}")]
     #[diagnostic(code(devrig::dashboard_port_conflict))]
     DashboardPortConflict {
@@ -167,6 +295,33 @@ pub enum ConfigDiagnostic {
         value: String,
     },
 
+    #[error("invalid dashboard.otel.forward endpoint `{value}`: {reason}")]
+    #[diagnostic(
+        code(devrig::invalid_otel_forward),
+        help("use a URL like \"http://localhost:4317\"; https endpoints need protocol = \"http\"")
+    )]
+    InvalidOtelForward {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("invalid endpoint")]
+        span: SourceSpan,
+        value: String,
+        reason: String,
+    },
+
+    #[error("[dashboard.tls] is missing `{missing}`")]
+    #[diagnostic(
+        code(devrig::incomplete_dashboard_tls),
+        help("set both `cert` and `key`, or remove [dashboard.tls] to serve plain HTTP")
+    )]
+    IncompleteDashboardTls {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("cert and key must be set together")]
+        span: SourceSpan,
+        missing: &'static str,
+    },
+
     #[error("dashboard/otel ports must all be distinct (port {port} used by {a} and {b})")]
     #[diagnostic(code(devrig::dashboard_ports_not_distinct))]
     DashboardPortsNotDistinct {
@@ -209,6 +364,20 @@ pub enum ConfigDiagnostic {
         addon: String,
     },
 
+    #[error("addon `{addon}` has an invalid namespace `{namespace}`")]
+    #[diagnostic(
+        code(devrig::invalid_addon_namespace),
+        help("namespaces must be at most 63 lowercase letters, digits or '-', starting and ending with a letter or digit")
+    )]
+    InvalidAddonNamespace {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("not a valid Kubernetes namespace")]
+        span: SourceSpan,
+        addon: String,
+        namespace: String,
+    },
+
     #[error("addon `{addon}` has an empty path")]
     #[diagnostic(code(devrig::empty_addon_path))]
     EmptyAddonPath {
@@ -230,6 +399,20 @@ pub enum ConfigDiagnostic {
         conflict_with: String,
     },
 
+    #[error("addon `{addon}` has invalid port_forward key `{key}`")]
+    #[diagnostic(
+        code(devrig::invalid_addon_port_forward),
+        help("use a local port number, or \"auto\" for a free one")
+    )]
+    InvalidAddonPortForward {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("invalid port_forward key")]
+        span: SourceSpan,
+        addon: String,
+        key: String,
+    },
+
     #[error("addon name `{name}` conflicts with a cluster.deploy name")]
     #[diagnostic(code(devrig::addon_name_conflict))]
     AddonNameConflict {
@@ -266,7 +449,7 @@ pub enum ConfigDiagnostic {
     #[error("docker `{service}` has empty registry_auth credentials after expansion")]
     #[diagnostic(
         code(devrig::empty_registry_auth),
-        help("ensure registry_auth username and password are non-empty (check $VAR references)")
+        help("ensure registry_auth username and password (or password_command) are non-empty (check $VAR references)")
     )]
     EmptyRegistryAuth {
         #[source_code]
@@ -292,7 +475,7 @@ pub enum ConfigDiagnostic {
     #[error("invalid volume spec `{spec}` on docker `{service}`")]
     #[diagnostic(
         code(devrig::invalid_volume_spec),
-        help("volumes must be \"name:/path\" (named) or \"/host/path:/container/path\" (bind mount)")
+        help("volumes must be \"name:/path\" (named) or \"/host/path:/container/path\" (bind mount; host path may be absolute, relative to the config, or start with ~), optionally followed by \":ro\" or \":rw\"")
     )]
     InvalidVolumeSpec {
         #[source_code]
@@ -303,6 +486,124 @@ pub enum ConfigDiagnostic {
         spec: String,
     },
 
+    #[error("stop_timeout {value} for docker `{service}` is out of range")]
+    #[diagnostic(
+        code(devrig::invalid_stop_timeout),
+        help("stop_timeout is in seconds and must be between 1 and {max}")
+    )]
+    InvalidStopTimeout {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("out of range")]
+        span: SourceSpan,
+        service: String,
+        value: u64,
+        max: u64,
+    },
+
+    #[error("invalid mem_limit `{value}` for docker `{service}`: {reason}")]
+    #[diagnostic(
+        code(devrig::invalid_mem_limit),
+        help("mem_limit is a byte count with an optional unit b, k, m or g (powers of 1024), e.g. \"512m\" or \"2g\"; Docker needs at least 6m")
+    )]
+    InvalidMemLimit {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("invalid memory limit")]
+        span: SourceSpan,
+        service: String,
+        value: String,
+        reason: String,
+    },
+
+    #[error("invalid {field} entry `{value}` for docker `{service}`: {reason}")]
+    #[diagnostic(
+        code(devrig::invalid_docker_host_name),
+        help("network_aliases are DNS names such as \"db.internal\"; extra_hosts entries are \"HOST:IP\", where IP may be \"host-gateway\"")
+    )]
+    InvalidDockerHostName {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("invalid host name entry")]
+        span: SourceSpan,
+        service: String,
+        field: String,
+        value: String,
+        reason: String,
+    },
+
+    #[error("cpus {value} for docker `{service}` must be greater than zero")]
+    #[diagnostic(
+        code(devrig::invalid_cpus),
+        help("cpus is a number of CPUs and may be fractional, e.g. 0.5 or 1.5")
+    )]
+    InvalidCpus {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("invalid CPU limit")]
+        span: SourceSpan,
+        service: String,
+        value: f64,
+    },
+
+    #[error("invalid env_prefix `{value}`")]
+    #[diagnostic(
+        code(devrig::invalid_env_prefix),
+        help("env_prefix must start with a letter or `_` and use only ASCII letters, digits and `_`, e.g. \"APP_\"")
+    )]
+    InvalidEnvPrefix {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("not a valid environment variable prefix")]
+        span: SourceSpan,
+        value: String,
+    },
+
+    #[error("invalid secret `{name}`: {reason}")]
+    #[diagnostic(
+        code(devrig::invalid_secret),
+        help("secrets are referenced as `$NAME`, so names use only ASCII letters, digits and `_`; `exec` is the command that prints the value")
+    )]
+    InvalidSecret {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("invalid secret")]
+        span: SourceSpan,
+        name: String,
+        reason: String,
+    },
+
+    #[error("invalid ready_check for service `{service}`: {reason}")]
+    #[diagnostic(
+        code(devrig::invalid_service_ready_check),
+        help("services support `tcp` (needs a `port`), `http` and `log` checks, optionally combined with all_of / any_of")
+    )]
+    InvalidServiceReadyCheck {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("unsupported ready_check")]
+        span: SourceSpan,
+        service: String,
+        reason: String,
+    },
+
+    #[error("invalid ready_check for `{service}`: {reason}")]
+    #[diagnostic(
+        code(devrig::invalid_ready_check),
+        help(
+            "all_of / any_of need at least one check and may nest at most {max_depth} levels deep"
+        )
+    )]
+    InvalidReadyCheck {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("invalid ready_check")]
+        span: SourceSpan,
+        service: String,
+        reason: String,
+        max_depth: usize,
+    },
+
     #[error("exclude_namespaces requires namespaces = \"all\"")]
     #[diagnostic(
         code(devrig::logs_exclude_requires_all),
@@ -315,6 +616,21 @@ pub enum ConfigDiagnostic {
         span: SourceSpan,
     },
 
+    #[error("invalid {field} pattern '{entry}': {reason}")]
+    #[diagnostic(
+        code(devrig::invalid_logs_exclude_pattern),
+        help("plain names match exactly, `*` and `?` are globs, anything with regex syntax is a regex")
+    )]
+    InvalidLogsExcludePattern {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("invalid pattern")]
+        span: SourceSpan,
+        field: String,
+        entry: String,
+        reason: String,
+    },
+
     #[error("[cluster.logs] is enabled but [dashboard] is not configured")]
     #[diagnostic(
         code(devrig::logs_without_dashboard),
@@ -326,6 +642,94 @@ pub enum ConfigDiagnostic {
         #[label("logs enabled here")]
         span: SourceSpan,
     },
+
+    #[error("unresolved variable `{variable}` in {field}")]
+    #[diagnostic(code(devrig::unresolved_template))]
+    UnresolvedTemplate {
+        #[source_code]
+        src: NamedSource<String>,
+        #[label("no value for `{variable}`")]
+        span: SourceSpan,
+        #[help]
+        advice: Option<String>,
+        field: String,
+        variable: String,
+    },
+}
+
+// ---------------------------------------------------------------------------
+// DiagnosticReport — machine-readable form for editors
+// ---------------------------------------------------------------------------
+
+/// A diagnostic flattened for `devrig validate --format json`, so editor
+/// integrations can place squiggles without parsing rendered output.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DiagnosticReport {
+    pub code: Option<String>,
+    pub message: String,
+    pub severity: &'static str,
+    /// Byte range in the config source of the primary label, if any.
+    pub span: Option<ReportSpan>,
+    /// The primary label's text, e.g. "cert and key must be set together".
+    pub label: Option<String>,
+    pub help: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ReportSpan {
+    pub start: usize,
+    pub len: usize,
+}
+
+impl From<SourceSpan> for ReportSpan {
+    fn from(span: SourceSpan) -> Self {
+        Self {
+            start: span.offset(),
+            len: span.len(),
+        }
+    }
+}
+
+impl DiagnosticReport {
+    pub fn from_diagnostic(diag: &dyn Diagnostic) -> Self {
+        let primary = diag.labels().and_then(|labels| {
+            let labels: Vec<_> = labels.collect();
+            labels
+                .iter()
+                .find(|l| l.primary())
+                .or(labels.first())
+                .cloned()
+        });
+        let severity = match diag.severity() {
+            Some(miette::Severity::Warning) => "warning",
+            Some(miette::Severity::Advice) => "advice",
+            Some(miette::Severity::Error) | None => "error",
+        };
+        Self {
+            code: diag.code().map(|c| c.to_string()),
+            message: diag.to_string(),
+            severity,
+            span: primary.as_ref().map(|l| (*l.inner()).into()),
+            label: primary.and_then(|l| l.label().map(str::to_string)),
+            help: diag.help().map(|h| h.to_string()),
+        }
+    }
+
+    /// A TOML syntax or type error, which stops validation before any
+    /// [`ConfigDiagnostic`] can be produced.
+    pub fn from_toml_error(err: &toml::de::Error) -> Self {
+        Self {
+            code: Some("devrig::invalid_toml".to_string()),
+            message: err.message().to_string(),
+            severity: "error",
+            span: err.span().map(|r| ReportSpan {
+                start: r.start,
+                len: r.len(),
+            }),
+            label: None,
+            help: None,
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -380,6 +784,100 @@ fn find_depends_on_value(source: &str, section: &str, service: &str, dep: &str)
     find_table_span(source, section, service)
 }
 
+/// Find the byte offset of a resource name listed in a `[profiles]` entry.
+fn find_profile_value(source: &str, profile: &str, resource: &str) -> SourceSpan {
+    let search_start = source.find("[profiles]").unwrap_or(0);
+    let after_header = &source[search_start..];
+    let profile_start = after_header
+        .find(&format!("{} =", profile))
+        .or_else(|| after_header.find(&format!("{}=", profile)))
+        .map(|p| search_start + p)
+        .unwrap_or(search_start);
+
+    let quoted = format!("\"{}\"", resource);
+    if let Some(rel_pos) = source[profile_start..].find(&quoted) {
+        return (profile_start + rel_pos + 1, resource.len()).into();
+    }
+
+    (profile_start, profile.len()).into()
+}
+
+/// Find the byte offset of an entry in `[secrets]`.
+fn find_secret_span(source: &str, name: &str) -> SourceSpan {
+    if let Some(pos) = source.find("[secrets]") {
+        if let Some(rel) = source[pos..].find(name) {
+            return (pos + rel, name.len()).into();
+        }
+    }
+    find_table_span(source, "secrets", name)
+}
+
+/// Why `check` cannot run against a local `[services]` process, if it can't.
+fn service_ready_check_problem(check: &ReadyCheck, has_port: bool) -> Option<String> {
+    match check {
+        ReadyCheck::PgIsReady { .. } => Some("`pg_isready` only works for docker".to_string()),
+        ReadyCheck::Cmd { .. } => Some("`cmd` only works for docker".to_string()),
+        ReadyCheck::Tcp { .. } if !has_port => {
+            Some("`tcp` check needs the service to have a `port`".to_string())
+        }
+        ReadyCheck::Grpc { .. } if !has_port => {
+            Some("`grpc` check needs the service to have a `port`".to_string())
+        }
+        ReadyCheck::AllOf { checks, .. } | ReadyCheck::AnyOf { checks, .. } => checks
+            .iter()
+            .find_map(|child| service_ready_check_problem(child, has_port)),
+        _ => None,
+    }
+}
+
+/// Whether `ns` is a valid Kubernetes namespace (an RFC 1123 label).
+fn is_valid_namespace(ns: &str) -> bool {
+    ns.len() <= 63
+        && ns
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        && !ns.starts_with('-')
+        && !ns.ends_with('-')
+}
+
+/// Why `name` is not a DNS-safe host name (RFC 1123 labels joined by `.`),
+/// if it isn't.
+fn dns_name_problem(name: &str) -> Option<String> {
+    if name.is_empty() {
+        return Some("name is empty".to_string());
+    }
+    if name.len() > 253 {
+        return Some("name is longer than 253 characters".to_string());
+    }
+    for label in name.split('.') {
+        if label.is_empty() || label.len() > 63 {
+            return Some("each dot-separated label must be 1-63 characters".to_string());
+        }
+        if !label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Some("only letters, digits, `-` and `.` are allowed".to_string());
+        }
+        if label.starts_with('-') || label.ends_with('-') {
+            return Some("labels cannot start or end with `-`".to_string());
+        }
+    }
+    None
+}
+
+/// Why an `extra_hosts` entry is not "HOST:IP" (or Docker's "HOST=IP"), if
+/// it isn't.
+fn extra_host_problem(entry: &str) -> Option<String> {
+    let Some((host, ip)) = entry.split_once(['=', ':']) else {
+        return Some("expected \"HOST:IP\"".to_string());
+    };
+    if let Some(problem) = dns_name_problem(host) {
+        return Some(format!("host {}", problem));
+    }
+    if ip != "host-gateway" && ip.parse::<std::net::IpAddr>().is_err() {
+        return Some(format!("`{}` is not an IP address or \"host-gateway\"", ip));
+    }
+    None
+}
+
 /// Find the byte offset of a specific field value in a TOML section.
 fn find_field_span(source: &str, section: &str, name: &str, field: &str) -> SourceSpan {
     let table_header = format!("[{}.{}]", section, name);
@@ -411,6 +909,22 @@ fn find_field_span(source: &str, section: &str, name: &str, field: &str) -> Sour
     find_table_span(source, section, name)
 }
 
+/// Span of the `file` key in the `[compose]` table, or the table header
+/// when the key is missing.
+fn compose_file_span(source: &str) -> SourceSpan {
+    compose_field_span(source, "file")
+}
+
+fn compose_field_span(source: &str, field: &str) -> SourceSpan {
+    let Some(pos) = source.find("[compose]") else {
+        return (0, 0).into();
+    };
+    match source[pos..].find(field) {
+        Some(rel) => (pos + rel, field.len()).into(),
+        None => (pos, 9).into(),
+    }
+}
+
 /// Find the byte offset of a port value for a given resource.
 fn find_port_span(source: &str, section: &str, name: &str) -> SourceSpan {
     find_field_span(source, section, name, "port")
@@ -431,18 +945,32 @@ fn find_closest_match<'a>(name: &str, candidates: &'a [String]) -> Option<&'a st
     best.map(|(name, _)| name)
 }
 
-/// Check that all `depends_on` entries for a given resource exist in `available`.
-fn check_deps_exist(
+/// Check that all `depends_on` entries for a given resource exist in
+/// `available`. Entries naming a resource in `disabled` get their own error.
+#[allow(clippy::too_many_arguments)]
+fn check_deps_exist<'a>(
     name: &str,
-    deps: &[String],
+    deps: impl IntoIterator<Item = &'a str>,
     section: &str,
     available: &[String],
+    disabled: &BTreeSet<String>,
     source: &str,
     src: &NamedSource<String>,
     errors: &mut Vec<ConfigDiagnostic>,
 ) {
     for dep in deps {
-        if !available.contains(dep) {
+        if disabled.contains(dep) {
+            errors.push(ConfigDiagnostic::DisabledDependency {
+                src: src.clone(),
+                span: find_depends_on_value(source, section, name, dep),
+                advice: format!(
+                    "enable `{}`, or remove it from `{}`'s depends_on",
+                    dep, name
+                ),
+                service: name.to_string(),
+                dependency: dep.to_string(),
+            });
+        } else if !available.iter().any(|a| a == dep) {
             let suggestion = find_closest_match(dep, available);
             let advice = match suggestion {
                 Some(s) => format!("did you mean `{}`?", s),
@@ -453,7 +981,7 @@ fn check_deps_exist(
                 span: find_depends_on_value(source, section, name, dep),
                 advice,
                 service: name.to_string(),
-                dependency: dep.clone(),
+                dependency: dep.to_string(),
             });
         }
     }
@@ -490,19 +1018,67 @@ pub fn validate(
         }
     }
 
+    if let Some(prefix) = &config.project.env_prefix {
+        if !is_valid_env_prefix(prefix) {
+            errors.push(ConfigDiagnostic::InvalidEnvPrefix {
+                src: src.clone(),
+                span: find_project_span(source, "env_prefix"),
+                value: prefix.clone(),
+            });
+        }
+    }
+
+    for (name, secret) in &config.secrets {
+        // A valid prefix is also a valid complete variable name.
+        let reason = if !is_valid_env_prefix(name) {
+            Some("not a valid environment variable name")
+        } else if secret.exec.trim().is_empty() {
+            Some("`exec` is empty")
+        } else {
+            None
+        };
+        if let Some(reason) = reason {
+            errors.push(ConfigDiagnostic::InvalidSecret {
+                src: src.clone(),
+                span: find_secret_span(source, name),
+                name: name.clone(),
+                reason: reason.to_string(),
+            });
+        }
+    }
+
     // Check all depends_on references exist
     for (name, svc) in &config.services {
-        check_deps_exist(name, &svc.depends_on, "services", &available, source, &src, &mut errors);
+        check_deps_exist(name, svc.depends_on.names(), "services", &available, &config.disabled, source, &src, &mut errors);
     }
     for (name, docker_cfg) in &config.docker {
-        check_deps_exist(name, &docker_cfg.depends_on, "docker", &available, source, &src, &mut errors);
+        check_deps_exist(name, docker_cfg.depends_on.names(), "docker", &available, &config.disabled, source, &src, &mut errors);
     }
     if let Some(cluster) = &config.cluster {
         for (name, image_cfg) in &cluster.images {
-            check_deps_exist(name, &image_cfg.depends_on, "cluster.image", &available, source, &src, &mut errors);
+            check_deps_exist(name, image_cfg.depends_on.iter().map(String::as_str), "cluster.image", &available, &config.disabled, source, &src, &mut errors);
         }
         for (name, deploy) in &cluster.deploy {
-            check_deps_exist(name, &deploy.depends_on, "cluster.deploy", &available, source, &src, &mut errors);
+            check_deps_exist(name, deploy.depends_on.iter().map(String::as_str), "cluster.deploy", &available, &config.disabled, source, &src, &mut errors);
+        }
+    }
+
+    // Check every profile entry names a known resource
+    for (profile, resources) in &config.profiles {
+        for resource in resources {
+            if !available.contains(resource) {
+                let advice = match find_closest_match(resource, &available) {
+                    Some(s) => format!("did you mean `{}`?", s),
+                    None => format!("available resources: {:?}", available),
+                };
+                errors.push(ConfigDiagnostic::UnknownProfileResource {
+                    src: src.clone(),
+                    span: find_profile_value(source, profile, resource),
+                    advice,
+                    profile: profile.clone(),
+                    resource: resource.clone(),
+                });
+            }
         }
     }
 
@@ -569,24 +1145,34 @@ pub fn validate(
         }
     }
 
-    // Check no two services/docker share the same fixed port
-    let mut port_map: BTreeMap<u16, Vec<String>> = BTreeMap::new();
+    // Check no two services/docker share the same fixed port. TCP and UDP
+    // ports don't collide with each other.
+    let mut port_map: BTreeMap<(u16, PortProtocol), Vec<String>> = BTreeMap::new();
     for (name, svc) in &config.services {
         if let Some(Port::Fixed(p)) = &svc.port {
-            port_map.entry(*p).or_default().push(name.clone());
+            port_map
+                .entry((*p, PortProtocol::Tcp))
+                .or_default()
+                .push(name.clone());
         }
     }
     for (name, docker_cfg) in &config.docker {
         if let Some(Port::Fixed(p)) = &docker_cfg.port {
-            port_map.entry(*p).or_default().push(name.clone());
+            port_map
+                .entry((*p, docker_cfg.port_protocol()))
+                .or_default()
+                .push(name.clone());
         }
         for port_val in docker_cfg.ports.values() {
-            if let Port::Fixed(p) = port_val {
-                port_map.entry(*p).or_default().push(name.clone());
+            if let Some(p) = port_val.as_fixed() {
+                port_map
+                    .entry((p, port_val.protocol))
+                    .or_default()
+                    .push(name.clone());
             }
         }
     }
-    for (port, services) in port_map {
+    for ((port, protocol), services) in port_map {
         if services.len() > 1 {
             // Find span of the first port declaration
             let first = &services[0];
@@ -599,25 +1185,93 @@ pub fn validate(
                 src: src.clone(),
                 span: find_port_span(source, section, first),
                 port,
+                protocol,
                 services,
             });
         }
     }
 
+    // Check port ranges are usable
+    let mut ranges: Vec<(String, &Port, SourceSpan)> = Vec::new();
+    for (name, svc) in &config.services {
+        if let Some(port) = &svc.port {
+            ranges.push((
+                format!("service `{name}`"),
+                port,
+                find_port_span(source, "services", name),
+            ));
+        }
+    }
+    for (name, docker_cfg) in &config.docker {
+        if let Some(port) = &docker_cfg.port {
+            ranges.push((
+                format!("docker `{name}`"),
+                port,
+                find_port_span(source, "docker", name),
+            ));
+        }
+        for (pname, port_val) in &docker_cfg.ports {
+            ranges.push((
+                format!("docker `{name}` port `{pname}`"),
+                &port_val.port,
+                find_field_span(source, "docker", name, "ports"),
+            ));
+        }
+    }
+    if let Some(dashboard) = &config.dashboard {
+        ranges.push((
+            "dashboard.port".to_string(),
+            &dashboard.port,
+            find_dashboard_span(source, "port"),
+        ));
+        if let Some(otel) = &dashboard.otel {
+            ranges.push((
+                "dashboard.otel.grpc_port".to_string(),
+                &otel.grpc_port,
+                find_dashboard_otel_span(source, "grpc_port"),
+            ));
+            ranges.push((
+                "dashboard.otel.http_port".to_string(),
+                &otel.http_port,
+                find_dashboard_otel_span(source, "http_port"),
+            ));
+        }
+    }
+    for (resource, port, span) in ranges {
+        let Port::Range { start, end, .. } = port else {
+            continue;
+        };
+        if let Some(reason) = port_range_problem(port) {
+            errors.push(ConfigDiagnostic::InvalidPortRange {
+                src: src.clone(),
+                span,
+                resource,
+                value: format!("{start}-{end}"),
+                reason,
+            });
+        }
+    }
+
     // Build a complete deps map from both services and docker for cycle detection
-    let mut deps_map: BTreeMap<&str, &Vec<String>> = BTreeMap::new();
+    let mut deps_map: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
     for (name, svc) in &config.services {
-        deps_map.insert(name.as_str(), &svc.depends_on);
+        deps_map.insert(name.as_str(), svc.depends_on.names().collect());
     }
     for (name, docker_cfg) in &config.docker {
-        deps_map.insert(name.as_str(), &docker_cfg.depends_on);
+        deps_map.insert(name.as_str(), docker_cfg.depends_on.names().collect());
     }
     if let Some(cluster) = &config.cluster {
         for (name, image_cfg) in &cluster.images {
-            deps_map.insert(name.as_str(), &image_cfg.depends_on);
+            deps_map.insert(
+                name.as_str(),
+                image_cfg.depends_on.iter().map(String::as_str).collect(),
+            );
         }
         for (name, deploy) in &cluster.deploy {
-            deps_map.insert(name.as_str(), &deploy.depends_on);
+            deps_map.insert(
+                name.as_str(),
+                deploy.depends_on.iter().map(String::as_str).collect(),
+            );
         }
     }
 
@@ -635,9 +1289,9 @@ pub fn validate(
             in_stack.insert(start);
 
             while let Some((node, idx)) = stack.last_mut() {
-                let deps = deps_map[*node];
+                let deps = &deps_map[*node];
                 if *idx < deps.len() {
-                    let dep = deps[*idx].as_str();
+                    let dep = deps[*idx];
                     *idx += 1;
 
                     if !deps_map.contains_key(dep) {
@@ -697,7 +1351,12 @@ pub fn validate(
     // Check registry_auth has non-empty credentials
     for (name, docker_cfg) in &config.docker {
         if let Some(auth) = &docker_cfg.registry_auth {
-            if auth.username.trim().is_empty() || auth.password.trim().is_empty() {
+            let no_password = auth.password.trim().is_empty()
+                && auth
+                    .password_command
+                    .as_deref()
+                    .is_none_or(|c| c.trim().is_empty());
+            if auth.username.trim().is_empty() || no_password {
                 errors.push(ConfigDiagnostic::EmptyRegistryAuth {
                     src: src.clone(),
                     span: find_field_span(source, "docker", name, "registry_auth"),
@@ -713,14 +1372,132 @@ pub fn validate(
             if crate::docker::volume::parse_volume_spec(vol_spec, "validate").is_none() {
                 errors.push(ConfigDiagnostic::InvalidVolumeSpec {
                     src: src.clone(),
-                    span: find_field_span(source, "docker", name, "volumes"),
+                    span: find_field_span(source, "docker", name, "volumes"),
+                    service: name.clone(),
+                    spec: vol_spec.clone(),
+                });
+            }
+        }
+    }
+
+    // Validate docker stop timeouts are within a sane range
+    for (name, docker_cfg) in &config.docker {
+        if let Some(timeout) = docker_cfg.stop_timeout {
+            if !(1..=MAX_STOP_TIMEOUT_SECS).contains(&timeout) {
+                errors.push(ConfigDiagnostic::InvalidStopTimeout {
+                    src: src.clone(),
+                    span: find_field_span(source, "docker", name, "stop_timeout"),
+                    service: name.clone(),
+                    value: timeout,
+                    max: MAX_STOP_TIMEOUT_SECS,
+                });
+            }
+        }
+    }
+
+    // Validate docker resource limits
+    for (name, docker_cfg) in &config.docker {
+        if let Some(mem_limit) = &docker_cfg.mem_limit {
+            if let Err(reason) = parse_mem_limit(mem_limit) {
+                errors.push(ConfigDiagnostic::InvalidMemLimit {
+                    src: src.clone(),
+                    span: find_field_span(source, "docker", name, "mem_limit"),
+                    service: name.clone(),
+                    value: mem_limit.clone(),
+                    reason,
+                });
+            }
+        }
+        if let Some(cpus) = docker_cfg.cpus {
+            if !(cpus.is_finite() && cpus > 0.0) {
+                errors.push(ConfigDiagnostic::InvalidCpus {
+                    src: src.clone(),
+                    span: find_field_span(source, "docker", name, "cpus"),
+                    service: name.clone(),
+                    value: cpus,
+                });
+            }
+        }
+    }
+
+    // Validate docker network aliases and extra hosts
+    for (name, docker_cfg) in &config.docker {
+        let aliases = docker_cfg
+            .network_aliases
+            .iter()
+            .map(|alias| ("network_aliases", alias, dns_name_problem(alias)));
+        let hosts = docker_cfg
+            .extra_hosts
+            .iter()
+            .map(|entry| ("extra_hosts", entry, extra_host_problem(entry)));
+        for (field, value, problem) in aliases.chain(hosts) {
+            if let Some(reason) = problem {
+                errors.push(ConfigDiagnostic::InvalidDockerHostName {
+                    src: src.clone(),
+                    span: find_field_span(source, "docker", name, field),
                     service: name.clone(),
-                    spec: vol_spec.clone(),
+                    field: field.to_string(),
+                    value: value.clone(),
+                    reason,
                 });
             }
         }
     }
 
+    // Validate composite ready checks are non-empty and not nested too deeply
+    let ready_checks = config
+        .docker
+        .iter()
+        .filter_map(|(name, d)| d.ready_check.as_ref().map(|c| ("docker", name, c)))
+        .chain(
+            config
+                .services
+                .iter()
+                .filter_map(|(name, svc)| svc.ready_check.as_ref().map(|c| ("services", name, c))),
+        )
+        .chain(config.compose.iter().flat_map(|c| {
+            c.ready_checks
+                .iter()
+                .map(|(name, check)| ("compose.ready_checks", name, check))
+        }));
+    for (section, name, check) in ready_checks {
+        let reason = if check.has_empty_composite() {
+            Some("all_of / any_of must list at least one check".to_string())
+        } else if check.depth() > MAX_READY_CHECK_DEPTH {
+            Some(format!(
+                "nested {} levels deep (max {})",
+                check.depth(),
+                MAX_READY_CHECK_DEPTH
+            ))
+        } else {
+            None
+        };
+        if let Some(reason) = reason {
+            errors.push(ConfigDiagnostic::InvalidReadyCheck {
+                src: src.clone(),
+                span: find_field_span(source, section, name, "ready_check"),
+                service: name.clone(),
+                reason,
+                max_depth: MAX_READY_CHECK_DEPTH,
+            });
+        }
+    }
+
+    // Services only support checks devrig can run from the host
+    for (name, svc) in &config.services {
+        let Some(check) = &svc.ready_check else {
+            continue;
+        };
+        if let Some(reason) = service_ready_check_problem(check, svc.port.is_some()) {
+            errors.push(ConfigDiagnostic::InvalidServiceReadyCheck {
+                src: src.clone(),
+                span: find_field_span(source, "services", name, "ready_check"),
+                service: name.clone(),
+                reason,
+            });
+        }
+    }
+
     // Check cluster registries have non-empty credentials
     if let Some(cluster) = &config.cluster {
         for reg in &cluster.registries {
@@ -741,29 +1518,38 @@ pub fn validate(
         }
     }
 
-    // Check compose.file is non-empty if compose is present
+    // Check compose.file names at least one file and none are blank
     if let Some(compose) = &config.compose {
-        if compose.file.trim().is_empty() {
-            // Find compose.file field
-            let span = if let Some(pos) = source.find("[compose]") {
-                let after = &source[pos..];
-                if let Some(rel) = after.find("file") {
-                    (pos + rel, 4).into()
-                } else {
-                    (pos, 9).into()
-                }
-            } else {
-                (0, 0).into()
-            };
+        let files = compose.file.as_slice();
+        if files.is_empty() || files.iter().any(|f| f.trim().is_empty()) {
             errors.push(ConfigDiagnostic::EmptyComposeFile {
                 src: src.clone(),
-                span,
+                span: compose_file_span(source),
             });
         }
+        if let Some(name) = &compose.project_name {
+            if !is_valid_compose_project_name(name) {
+                errors.push(ConfigDiagnostic::InvalidComposeProjectName {
+                    src: src.clone(),
+                    span: compose_field_span(source, "project_name"),
+                    name: name.clone(),
+                });
+            }
+        }
     }
 
     // Check cluster image entries have non-empty context
     if let Some(cluster) = &config.cluster {
+        if cluster
+            .k3s_image
+            .as_deref()
+            .is_some_and(|i| i.trim().is_empty())
+        {
+            errors.push(ConfigDiagnostic::EmptyK3sImage {
+                src: src.clone(),
+                span: find_cluster_span(source, "k3s_image"),
+            });
+        }
         for (name, image_cfg) in &cluster.images {
             if image_cfg.context.trim().is_empty() {
                 errors.push(ConfigDiagnostic::EmptyImageContext {
@@ -874,6 +1660,22 @@ pub fn validate(
                 }
             }
 
+            if let Some(ns) = addon.namespace() {
+                if !ns.trim().is_empty() && !is_valid_namespace(ns) {
+                    errors.push(ConfigDiagnostic::InvalidAddonNamespace {
+                        src: src.clone(),
+                        span: find_field_span(
+                            source,
+                            &format!("cluster.addons.{}", name),
+                            name,
+                            "namespace",
+                        ),
+                        addon: name.clone(),
+                        namespace: ns.to_string(),
+                    });
+                }
+            }
+
             // Check addon port_forward ports don't conflict with service/docker ports
             for port_str in addon.port_forward().keys() {
                 if let Ok(port) = port_str.parse::<u16>() {
@@ -893,7 +1695,7 @@ pub fn validate(
                     // Check against docker ports
                     for (docker_name, docker_cfg) in &config.docker {
                         if let Some(Port::Fixed(p)) = &docker_cfg.port {
-                            if *p == port {
+                            if *p == port && docker_cfg.port_protocol() == PortProtocol::Tcp {
                                 errors.push(ConfigDiagnostic::AddonPortConflict {
                                     src: src.clone(),
                                     span: find_table_span(source, "cluster.addons", name),
@@ -916,6 +1718,13 @@ pub fn validate(
                             }
                         }
                     }
+                } else if port_str != "auto" {
+                    errors.push(ConfigDiagnostic::InvalidAddonPortForward {
+                        src: src.clone(),
+                        span: find_table_span(source, "cluster.addons", name),
+                        addon: name.clone(),
+                        key: port_str.clone(),
+                    });
                 }
             }
 
@@ -1021,6 +1830,28 @@ pub fn validate(
                     }
                 }
 
+                // exclude_* entries must compile as regexes
+                let excludes = [
+                    ("exclude_namespaces", &logs.exclude_namespaces),
+                    ("exclude_pods", &logs.exclude_pods),
+                ];
+                for (field, entries) in excludes {
+                    for entry in entries.iter().flatten() {
+                        let pattern = ClusterLogsConfig::exclude_pattern(entry);
+                        if let Err(e) = regex::Regex::new(&format!("^(?:{})$", pattern)) {
+                            let message = e.to_string();
+                            let reason = message.lines().last().unwrap_or(&message);
+                            errors.push(ConfigDiagnostic::InvalidLogsExcludePattern {
+                                src: src.clone(),
+                                span: find_cluster_logs_entry(source, field, entry),
+                                field: field.to_string(),
+                                entry: entry.clone(),
+                                reason: reason.trim_start_matches("error: ").to_string(),
+                            });
+                        }
+                    }
+                }
+
                 // Warn if dashboard is missing (OTLP receiver won't be running)
                 if config.dashboard.is_none() {
                     let span = find_cluster_logs_span(source, "logs");
@@ -1034,6 +1865,19 @@ pub fn validate(
     }
 
     // Validate restart config policy values
+    for (name, docker_cfg) in &config.docker {
+        if let Some(restart) = &docker_cfg.restart {
+            let valid_policies = ["always", "on-failure", "never"];
+            if !valid_policies.contains(&restart.policy.as_str()) {
+                errors.push(ConfigDiagnostic::InvalidRestartPolicy {
+                    src: src.clone(),
+                    span: find_field_span(source, "docker", name, "policy"),
+                    service: name.clone(),
+                    value: restart.policy.clone(),
+                });
+            }
+        }
+    }
     for (name, svc) in &config.services {
         if let Some(restart) = &svc.restart {
             let valid_policies = ["always", "on-failure", "never"];
@@ -1046,6 +1890,52 @@ pub fn validate(
                 });
             }
         }
+
+        if let Some(shell) = &svc.shell {
+            let parsed = crate::platform::ServiceShell::parse(Some(shell)).and_then(|parsed| {
+                if parsed == crate::platform::ServiceShell::Direct {
+                    crate::platform::split_command(&svc.command)
+                        .map_err(|e| format!("command cannot be exec'd directly ({})", e))?;
+                    let hooks = [("pre_start", &svc.pre_start), ("post_stop", &svc.post_stop)];
+                    for (hook, commands) in hooks {
+                        for command in commands.iter().flat_map(|c| c.as_slice()) {
+                            crate::platform::split_command(command).map_err(|e| {
+                                format!("{} command cannot be exec'd directly ({})", hook, e)
+                            })?;
+                        }
+                    }
+                }
+                Ok(parsed)
+            });
+            if let Err(reason) = parsed {
+                errors.push(ConfigDiagnostic::InvalidShell {
+                    src: src.clone(),
+                    span: find_field_span(source, "services", name, "shell"),
+                    service: name.clone(),
+                    value: shell.clone(),
+                    reason,
+                });
+            }
+        }
+
+        if let Some(signal) = &svc.reload_signal {
+            if !RELOAD_SIGNALS.contains(&signal.as_str()) {
+                errors.push(ConfigDiagnostic::InvalidReloadSignal {
+                    src: src.clone(),
+                    span: find_field_span(source, "services", name, "reload_signal"),
+                    service: name.clone(),
+                    value: signal.clone(),
+                });
+            }
+        }
+
+        if svc.max_log_lines_per_sec == Some(0) {
+            errors.push(ConfigDiagnostic::InvalidMaxLogRate {
+                src: src.clone(),
+                span: find_field_span(source, "services", name, "max_log_lines_per_sec"),
+                service: name.clone(),
+            });
+        }
     }
 
     // Validate dashboard config
@@ -1114,9 +2004,10 @@ pub fn validate(
                     }
                 }
             }
+            // The dashboard listens on TCP, so UDP docker ports can't clash.
             for (docker_name, docker_cfg) in &config.docker {
                 if let Some(Port::Fixed(p)) = &docker_cfg.port {
-                    if p == dport {
+                    if p == dport && docker_cfg.port_protocol() == PortProtocol::Tcp {
                         errors.push(ConfigDiagnostic::DashboardPortConflict {
                             src: src.clone(),
                             span: find_dashboard_span(source, "port"),
@@ -1126,8 +2017,8 @@ pub fn validate(
                     }
                 }
                 for (pname, port_val) in &docker_cfg.ports {
-                    if let Port::Fixed(p) = port_val {
-                        if p == dport {
+                    if let Some(p) = port_val.as_fixed() {
+                        if p == *dport && port_val.protocol == PortProtocol::Tcp {
                             errors.push(ConfigDiagnostic::DashboardPortConflict {
                                 src: src.clone(),
                                 span: find_dashboard_span(source, "port"),
@@ -1152,6 +2043,32 @@ pub fn validate(
                     value: otel.retention.clone(),
                 });
             }
+
+            if let Some(forward) = &otel.forward {
+                if let Some(reason) = otel_forward_problem(forward) {
+                    errors.push(ConfigDiagnostic::InvalidOtelForward {
+                        src: src.clone(),
+                        span: find_otel_forward_span(source),
+                        value: forward.endpoint.clone(),
+                        reason,
+                    });
+                }
+            }
+        }
+
+        if let Some(tls) = &dashboard.tls {
+            let missing = match (&tls.cert, &tls.key) {
+                (None, _) => Some("cert"),
+                (_, None) => Some("key"),
+                _ => None,
+            };
+            if let Some(missing) = missing {
+                errors.push(ConfigDiagnostic::IncompleteDashboardTls {
+                    src: src.clone(),
+                    span: find_dashboard_tls_span(source),
+                    missing,
+                });
+            }
         }
     }
 
@@ -1162,6 +2079,88 @@ pub fn validate(
     }
 }
 
+/// Whether `docker compose -p` accepts `name`: lowercase letters, digits,
+/// dashes and underscores, starting with a letter or digit.
+fn is_valid_compose_project_name(name: &str) -> bool {
+    name.chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+}
+
+/// Check that every file in `compose.file` exists relative to `config_dir`.
+///
+/// Kept out of [`validate`] because it touches the filesystem.
+pub fn validate_compose_files(
+    config: &DevrigConfig,
+    source: &str,
+    filename: &str,
+    config_dir: &Path,
+) -> Result<(), Vec<ConfigDiagnostic>> {
+    let Some(compose) = &config.compose else {
+        return Ok(());
+    };
+    let src = NamedSource::new(filename, source.to_string());
+    let mut errors = Vec::new();
+    for file in compose.file.as_slice() {
+        if file.trim().is_empty() || config_dir.join(file).is_file() {
+            continue;
+        }
+        let quoted = format!("\"{}\"", file);
+        let span = source
+            .find("[compose]")
+            .and_then(|pos| source[pos..].find(&quoted).map(|rel| pos + rel))
+            .map(|pos| (pos, quoted.len()).into())
+            .unwrap_or_else(|| compose_file_span(source));
+        errors.push(ConfigDiagnostic::MissingComposeFile {
+            src: src.clone(),
+            span,
+            path: file.clone(),
+        });
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Turn template resolution errors into diagnostics pointing at the first
+/// `{{ variable }}` reference in the source.
+pub fn template_diagnostics(
+    errors: &[TemplateError],
+    source: &str,
+    filename: &str,
+) -> Vec<ConfigDiagnostic> {
+    let src = NamedSource::new(filename, source.to_string());
+    errors
+        .iter()
+        .map(|err| {
+            let TemplateError::UnresolvedVariable {
+                field,
+                variable,
+                suggestion,
+            } = err;
+            let pattern = format!(r"\{{\{{\s*{}\s*\}}\}}", regex::escape(variable));
+            let span = regex::Regex::new(&pattern)
+                .ok()
+                .and_then(|re| re.find(source))
+                .map(|m| (m.start(), m.len()).into())
+                .unwrap_or_else(|| (0, 0).into());
+            ConfigDiagnostic::UnresolvedTemplate {
+                src: src.clone(),
+                span,
+                advice: suggestion.as_ref().map(|s| format!("did you mean `{s}`?")),
+                field: field.clone(),
+                variable: variable.clone(),
+            }
+        })
+        .collect()
+}
+
 /// Find the byte offset of a field in the [dashboard] section.
 fn find_dashboard_span(source: &str, field: &str) -> SourceSpan {
     if let Some(pos) = source.find("[dashboard]") {
@@ -1177,6 +2176,30 @@ fn find_dashboard_span(source: &str, field: &str) -> SourceSpan {
     (0, 0).into()
 }
 
+/// Find the byte offset of a field in the [project] section.
+fn find_project_span(source: &str, field: &str) -> SourceSpan {
+    if let Some(pos) = source.find("[project]") {
+        let after = &source[pos..];
+        if let Some(rel) = after.find(field) {
+            return (pos + rel, field.len()).into();
+        }
+        return (pos, 9).into();
+    }
+    (0, 0).into()
+}
+
+/// Find the byte offset of a field in the [cluster] section.
+fn find_cluster_span(source: &str, field: &str) -> SourceSpan {
+    if let Some(pos) = source.find("[cluster]") {
+        let after = &source[pos..];
+        if let Some(rel) = after.find(field) {
+            return (pos + rel, field.len()).into();
+        }
+        return (pos, 9).into();
+    }
+    (0, 0).into()
+}
+
 /// Find the byte offset of a field in the [cluster.logs] section.
 fn find_cluster_logs_span(source: &str, field: &str) -> SourceSpan {
     if let Some(pos) = source.find("[cluster.logs]") {
@@ -1196,6 +2219,18 @@ fn find_cluster_logs_span(source: &str, field: &str) -> SourceSpan {
     (0, 0).into()
 }
 
+/// Find an entry of a `[cluster.logs]` list field, falling back to the
+/// field itself when the entry is written with escapes.
+fn find_cluster_logs_entry(source: &str, field: &str, entry: &str) -> SourceSpan {
+    let field_span = find_cluster_logs_span(source, field);
+    let after = &source[field_span.offset()..];
+    [format!("\"{}\"", entry), format!("'{}'", entry)]
+        .iter()
+        .find_map(|quoted| after.find(quoted.as_str()))
+        .map(|rel| (field_span.offset() + rel + 1, entry.len()).into())
+        .unwrap_or(field_span)
+}
+
 /// Find the byte offset of a field in the [dashboard.otel] section.
 fn find_dashboard_otel_span(source: &str, field: &str) -> SourceSpan {
     if let Some(pos) = source.find("[dashboard.otel]") {
@@ -1207,12 +2242,74 @@ fn find_dashboard_otel_span(source: &str, field: &str) -> SourceSpan {
     find_dashboard_span(source, field)
 }
 
+/// Why a port range can't be used, if it can't.
+fn port_range_problem(port: &Port) -> Option<String> {
+    let Port::Range {
+        preferred,
+        start,
+        end,
+    } = port
+    else {
+        return None;
+    };
+    if *start == 0 {
+        return Some("ports start at 1".to_string());
+    }
+    if start > end {
+        return Some("the range is inverted".to_string());
+    }
+    match preferred {
+        Some(p) if !(*start..=*end).contains(p) => {
+            Some(format!("preferred port {p} is outside the range"))
+        }
+        _ => None,
+    }
+}
+
+/// Why `forward.endpoint` can't be used, if it can't. The gRPC client is
+/// built without TLS, so it only takes plain `http://` endpoints.
+fn otel_forward_problem(forward: &OtelForwardConfig) -> Option<String> {
+    let Some((scheme, rest)) = forward.endpoint.split_once("://") else {
+        return Some("missing http:// or https:// scheme".to_string());
+    };
+    if rest.trim_matches('/').is_empty() {
+        return Some("missing host".to_string());
+    }
+    match (scheme, forward.protocol) {
+        ("http", _) | ("https", OtlpProtocol::Http) => None,
+        ("https", OtlpProtocol::Grpc) => {
+            Some("TLS is not supported for gRPC forwarding".to_string())
+        }
+        _ => Some(format!("unsupported scheme `{scheme}`")),
+    }
+}
+
+/// Find the byte offset of `endpoint` in the [dashboard.otel.forward] section.
+fn find_otel_forward_span(source: &str) -> SourceSpan {
+    if let Some(pos) = source.find("[dashboard.otel.forward]") {
+        let after = &source[pos..];
+        if let Some(rel) = after.find("endpoint") {
+            return (pos + rel, "endpoint".len()).into();
+        }
+        return (pos, "[dashboard.otel.forward]".len()).into();
+    }
+    find_dashboard_otel_span(source, "forward")
+}
+
+/// Find the [dashboard.tls] section header.
+fn find_dashboard_tls_span(source: &str) -> SourceSpan {
+    if let Some(pos) = source.find("[dashboard.tls]") {
+        return (pos, "[dashboard.tls]".len()).into();
+    }
+    find_dashboard_span(source, "tls")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::config::model::{
         ClusterConfig, ClusterDeployConfig, ClusterImageConfig, ComposeConfig, DockerConfig,
-        ProjectConfig, ServiceConfig,
+        ProjectConfig, RegistryAuth, ServiceConfig, StringOrList,
     };
 
     const TEST_FILENAME: &str = "devrig.toml";
@@ -1224,6 +2321,7 @@ mod tests {
             svc_map.insert(
                 name.to_string(),
                 ServiceConfig {
+                    enabled: Default::default(),
                     path: None,
                     command: command.to_string(),
                     port,
@@ -1231,14 +2329,26 @@ mod tests {
                     env: BTreeMap::new(),
                     env_file: None,
                     depends_on: deps.into_iter().map(|s| s.to_string()).collect(),
+                    ready_check: None,
                     restart: None,
+                    shell: None,
+                    reload_strategy: None,
+                    reload_signal: None,
+                    max_log_lines_per_sec: None,
+                    pre_start: None,
+                    post_stop: None,
+                    log_format: None,
                 },
             );
         }
         DevrigConfig {
+            disabled: Default::default(),
             project: ProjectConfig {
                 name: "test".to_string(),
                 env_file: None,
+                shell_env_passthrough: Default::default(),
+                vars: BTreeMap::new(),
+                env_prefix: None,
             },
             services: svc_map,
             docker: BTreeMap::new(),
@@ -1248,6 +2358,8 @@ mod tests {
             env: BTreeMap::new(),
             network: None,
             links: BTreeMap::new(),
+            profiles: BTreeMap::new(),
+            secrets: BTreeMap::new(),
         }
     }
 
@@ -1274,6 +2386,7 @@ mod tests {
     /// Helper to build an DockerConfig with minimal fields.
     fn make_infra(image: &str, port: Option<Port>, deps: Vec<&str>) -> DockerConfig {
         DockerConfig {
+            enabled: Default::default(),
             image: image.to_string(),
             port,
             container_port: None,
@@ -1287,6 +2400,14 @@ mod tests {
             init: Vec::new(),
             depends_on: deps.into_iter().map(|s| s.to_string()).collect(),
             registry_auth: None,
+            pull_retries: None,
+            restart: None,
+            stop_timeout: None,
+            healthcheck: None,
+            mem_limit: None,
+            cpus: None,
+            network_aliases: Vec::new(),
+            extra_hosts: Vec::new(),
         }
     }
 
@@ -1316,6 +2437,36 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn diagnostic_report_carries_code_span_and_help() {
+        let source = "[project]\nname = \"test\"\n\n[services.api]\ncommand = \"cargo run\"\ndepends_on = [\"postres\"]\n\n[docker.postgres]\nimage = \"postgres:16\"\n";
+        let config: DevrigConfig = toml::from_str(source).unwrap();
+        let errs = validate(&config, source, TEST_FILENAME).unwrap_err();
+        let report = DiagnosticReport::from_diagnostic(&errs[0]);
+
+        assert_eq!(report.code.as_deref(), Some("devrig::missing_dependency"));
+        assert_eq!(report.message, "unknown dependency `postres`");
+        assert_eq!(report.severity, "error");
+        let span = report.span.unwrap();
+        assert_eq!(&source[span.start..span.start + span.len], "postres");
+        assert!(report.label.unwrap().contains("depends on `postres`"));
+        assert!(report.help.unwrap().contains("postgres"));
+
+        let json = serde_json::to_value(DiagnosticReport::from_diagnostic(&errs[0])).unwrap();
+        assert_eq!(json["span"]["start"], span.start);
+        assert_eq!(json["span"]["len"], span.len);
+    }
+
+    #[test]
+    fn diagnostic_report_from_toml_error_points_at_the_bad_value() {
+        let source = "[project]\nname = \"test\"\n\n[services.api]\ncommand = 42\n";
+        let err = toml::from_str::<DevrigConfig>(source).unwrap_err();
+        let report = DiagnosticReport::from_toml_error(&err);
+        assert_eq!(report.code.as_deref(), Some("devrig::invalid_toml"));
+        let span = report.span.unwrap();
+        assert_eq!(&source[span.start..span.start + span.len], "42");
+    }
+
     #[test]
     fn missing_dependency_with_suggestion() {
         let mut config = make_config(vec![(
@@ -1512,6 +2663,52 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn depends_on_disabled_resource_errors() {
+        let source = "[project]\nname = \"test\"\n\n[services.api]\ncommand = \"cargo run\"\ndepends_on = [\"mailpit\"]\n";
+        let mut config: DevrigConfig = toml::from_str(source).unwrap();
+        config.disabled.insert("mailpit".to_string());
+        let errs = validate(&config, source, TEST_FILENAME).unwrap_err();
+        assert_eq!(errs.len(), 1);
+        let ConfigDiagnostic::DisabledDependency {
+            service,
+            dependency,
+            span,
+            ..
+        } = &errs[0]
+        else {
+            panic!("expected DisabledDependency, got {:?}", errs[0]);
+        };
+        assert_eq!((service.as_str(), dependency.as_str()), ("api", "mailpit"));
+        assert_eq!(
+            &source[span.offset()..span.offset() + span.len()],
+            "mailpit"
+        );
+    }
+
+    #[test]
+    fn map_form_unknown_dependency_points_at_key() {
+        let source = "[project]\nname = \"test\"\n\n[services.api]\ncommand = \"cargo run\"\ndepends_on = { postgress = \"healthy\" }\n\n[docker.postgres]\nimage = \"postgres:16-alpine\"\n";
+        let config: DevrigConfig = toml::from_str(source).unwrap();
+        let errs = validate(&config, source, TEST_FILENAME).unwrap_err();
+        assert_eq!(errs.len(), 1);
+        let ConfigDiagnostic::MissingDependency {
+            span,
+            advice,
+            dependency,
+            ..
+        } = &errs[0]
+        else {
+            panic!("unexpected diagnostic: {:?}", errs[0]);
+        };
+        assert_eq!(dependency, "postgress");
+        assert_eq!(advice, "did you mean `postgres`?");
+        assert_eq!(
+            &source[span.offset()..span.offset() + span.len()],
+            "postgress"
+        );
+    }
+
     #[test]
     fn infra_and_service_share_fixed_port_errors() {
         let mut config = make_config(vec![("api", "cargo run", Some(Port::Fixed(5432)), vec![])]);
@@ -1528,6 +2725,28 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn registry_auth_needs_password_or_password_command() {
+        let source = "[project]\nname = \"test\"\n\n[docker.app]\nimage = \"ghcr.io/org/app\"\nregistry_auth = { username = \"ci\" }\n";
+        let mut config = make_config(vec![]);
+        let mut app = make_infra("ghcr.io/org/app", None, vec![]);
+        app.registry_auth = Some(RegistryAuth {
+            username: "ci".to_string(),
+            password: String::new(),
+            password_command: None,
+        });
+        config.docker.insert("app".to_string(), app);
+        let errs = validate(&config, source, TEST_FILENAME).unwrap_err();
+        assert!(matches!(
+            &errs[0],
+            ConfigDiagnostic::EmptyRegistryAuth { service, .. } if service == "app"
+        ));
+
+        let auth = config.docker.get_mut("app").unwrap().registry_auth.as_mut();
+        auth.unwrap().password_command = Some("gh auth token".to_string());
+        assert!(validate(&config, source, TEST_FILENAME).is_ok());
+    }
+
     #[test]
     fn infra_with_empty_image_errors() {
         let mut config = make_config(vec![]);
@@ -1571,10 +2790,12 @@ mod tests {
     fn compose_with_empty_file_errors() {
         let mut config = make_config(vec![]);
         config.compose = Some(ComposeConfig {
-            file: "".to_string(),
+            file: StringOrList(vec!["".to_string()]),
+            project_name: None,
             services: vec![],
             env_file: None,
             ready_checks: BTreeMap::new(),
+            protocols: BTreeMap::new(),
         });
         let source = "[project]\nname = \"test\"\n\n[compose]\nfile = \"\"\n";
         let errs = validate(&config, source, TEST_FILENAME).unwrap_err();
@@ -1585,12 +2806,99 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn compose_with_empty_file_list_errors() {
+        let mut config = make_config(vec![]);
+        config.compose = Some(ComposeConfig {
+            file: StringOrList(vec![]),
+            project_name: None,
+            services: vec![],
+            env_file: None,
+            ready_checks: BTreeMap::new(),
+            protocols: BTreeMap::new(),
+        });
+        let source = "[project]\nname = \"test\"\n\n[compose]\nfile = []\n";
+        let errs = validate(&config, source, TEST_FILENAME).unwrap_err();
+        assert_eq!(errs.len(), 1);
+        assert!(matches!(
+            &errs[0],
+            ConfigDiagnostic::EmptyComposeFile { .. }
+        ));
+    }
+
+    #[test]
+    fn compose_project_name_must_be_valid() {
+        for (name, ok) in [
+            ("shop", true),
+            ("shop_dev-2", true),
+            ("2shop", true),
+            ("", false),
+            ("Shop", false),
+            ("-shop", false),
+            ("shop.dev", false),
+        ] {
+            let source = format!(
+                "[project]\nname = \"test\"\n\n[compose]\nfile = \"compose.yml\"\nproject_name = \"{name}\"\n"
+            );
+            let config: DevrigConfig = toml::from_str(&source).unwrap();
+            let result = validate(&config, &source, TEST_FILENAME);
+            if ok {
+                assert!(result.is_ok(), "{name:?} should be accepted");
+            } else {
+                let errs = result.unwrap_err();
+                assert_eq!(errs.len(), 1, "{name:?} should be rejected");
+                assert!(matches!(
+                    &errs[0],
+                    ConfigDiagnostic::InvalidComposeProjectName { .. }
+                ));
+            }
+        }
+    }
+
+    #[test]
+    fn compose_override_files_must_exist() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("compose.yml"), "services: {}\n").unwrap();
+        let mut config = make_config(vec![]);
+        config.compose = Some(ComposeConfig {
+            file: StringOrList(vec![
+                "compose.yml".to_string(),
+                "compose.local.yml".to_string(),
+            ]),
+            project_name: None,
+            services: vec![],
+            env_file: None,
+            ready_checks: BTreeMap::new(),
+            protocols: BTreeMap::new(),
+        });
+        let source = "[project]\nname = \"test\"\n\n[compose]\nfile = [\"compose.yml\", \"compose.local.yml\"]\n";
+        let errs = validate_compose_files(&config, source, TEST_FILENAME, dir.path()).unwrap_err();
+        assert_eq!(errs.len(), 1);
+        match &errs[0] {
+            ConfigDiagnostic::MissingComposeFile { path, span, .. } => {
+                assert_eq!(path, "compose.local.yml");
+                assert_eq!(
+                    &source[span.offset()..span.offset() + span.len()],
+                    "\"compose.local.yml\""
+                );
+            }
+            other => panic!("unexpected diagnostic: {other:?}"),
+        }
+
+        std::fs::write(dir.path().join("compose.local.yml"), "services: {}\n").unwrap();
+        assert!(validate_compose_files(&config, source, TEST_FILENAME, dir.path()).is_ok());
+    }
+
     #[test]
     fn docker_named_ports_conflict_detected() {
         let mut config = make_config(vec![("api", "cargo run", Some(Port::Fixed(8025)), vec![])]);
         let mut mailpit = make_infra("axllent/mailpit:latest", None, vec![]);
-        mailpit.ports.insert("smtp".to_string(), Port::Fixed(1025));
-        mailpit.ports.insert("ui".to_string(), Port::Fixed(8025));
+        mailpit
+            .ports
+            .insert("smtp".to_string(), Port::Fixed(1025).into());
+        mailpit
+            .ports
+            .insert("ui".to_string(), Port::Fixed(8025).into());
         config.docker.insert("mailpit".to_string(), mailpit);
         let source = "[project]\nname = \"test\"\n\n[services.api]\ncommand = \"cargo run\"\nport = 8025\n\n[docker.mailpit]\nimage = \"axllent/mailpit:latest\"\n[docker.mailpit.ports]\nsmtp = 1025\nui = 8025\n";
         let errs = validate(&config, source, TEST_FILENAME).unwrap_err();
@@ -1601,6 +2909,46 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn tcp_and_udp_on_same_port_do_not_collide() {
+        let source = r#"
+[project]
+name = "test"
+
+[services.api]
+command = "cargo run"
+port = 8053
+
+[docker.dns]
+image = "coredns/coredns:latest"
+[docker.dns.ports]
+dns = "8053/udp"
+also-dns = "8053/udp"
+"#;
+        let config: DevrigConfig = toml::from_str(source).unwrap();
+        let errs = validate(&config, source, TEST_FILENAME).unwrap_err();
+        let dups: Vec<_> = errs
+            .iter()
+            .filter_map(|e| match e {
+                ConfigDiagnostic::DuplicatePort {
+                    port,
+                    protocol,
+                    services,
+                    ..
+                } => Some((*port, *protocol, services.clone())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            dups,
+            vec![(
+                8053,
+                PortProtocol::Udp,
+                vec!["dns".to_string(), "dns".to_string()]
+            )]
+        );
+    }
+
     #[test]
     fn infra_cycle_detected() {
         let mut config = make_config(vec![]);
@@ -1630,10 +2978,12 @@ mod tests {
             vec!["redis"],
         )]);
         config.compose = Some(ComposeConfig {
-            file: "docker-compose.yml".to_string(),
+            file: StringOrList(vec!["docker-compose.yml".to_string()]),
+            project_name: None,
             services: vec!["redis".to_string(), "postgres".to_string()],
             env_file: None,
             ready_checks: BTreeMap::new(),
+            protocols: BTreeMap::new(),
         });
         let source = "[project]\nname = \"test\"\n\n[services.api]\ncommand = \"cargo run\"\nport = 3000\ndepends_on = [\"redis\"]\n\n[compose]\nfile = \"docker-compose.yml\"\nservices = [\"redis\", \"postgres\"]\n";
         assert!(validate(&config, source, TEST_FILENAME).is_ok());
@@ -1643,6 +2993,7 @@ mod tests {
 
     fn make_image(context: &str, deps: Vec<&str>) -> ClusterImageConfig {
         ClusterImageConfig {
+            enabled: Default::default(),
             context: context.to_string(),
             dockerfile: "Dockerfile".to_string(),
             watch: false,
@@ -1654,12 +3005,14 @@ mod tests {
 
     fn make_deploy(context: &str, manifests: &str, deps: Vec<&str>) -> ClusterDeployConfig {
         ClusterDeployConfig {
+            enabled: Default::default(),
             context: context.to_string(),
             dockerfile: "Dockerfile".to_string(),
             manifests: manifests.to_string(),
             watch: false,
             depends_on: deps.into_iter().map(|s| s.to_string()).collect(),
             build_secrets: BTreeMap::new(),
+            env: BTreeMap::new(),
         }
     }
 
@@ -1683,6 +3036,9 @@ mod tests {
             logs: None,
             registries: vec![],
             k3s_args: vec![],
+            k3s_image: None,
+            env: BTreeMap::new(),
+            command_retries: 3,
         });
         let source = "[project]\nname = \"test\"\n\n[services.web]\ncommand = \"npm run dev\"\nport = 3000\ndepends_on = [\"api\"]\n\n[cluster]\nregistry = true\n\n[cluster.deploy.api]\ncontext = \"./api\"\nmanifests = \"./k8s\"\n";
         assert!(validate(&config, source, TEST_FILENAME).is_ok());
@@ -1710,6 +3066,9 @@ mod tests {
             logs: None,
             registries: vec![],
             k3s_args: vec![],
+            k3s_image: None,
+            env: BTreeMap::new(),
+            command_retries: 3,
         });
         let source = "[project]\nname = \"test\"\n\n[docker.postgres]\nimage = \"postgres:16-alpine\"\nport = 5432\n\n[cluster]\nregistry = true\n\n[cluster.deploy.api]\ncontext = \"./api\"\nmanifests = \"./k8s\"\ndepends_on = [\"postgres\"]\n";
         assert!(validate(&config, source, TEST_FILENAME).is_ok());
@@ -1730,6 +3089,9 @@ mod tests {
             logs: None,
             registries: vec![],
             k3s_args: vec![],
+            k3s_image: None,
+            env: BTreeMap::new(),
+            command_retries: 3,
         });
         let source = "[project]\nname = \"test\"\n\n[cluster.deploy.api]\ncontext = \"\"\nmanifests = \"./k8s\"\n";
         let errs = validate(&config, source, TEST_FILENAME).unwrap_err();
@@ -1754,6 +3116,9 @@ mod tests {
             logs: None,
             registries: vec![],
             k3s_args: vec![],
+            k3s_image: None,
+            env: BTreeMap::new(),
+            command_retries: 3,
         });
         let source = "[project]\nname = \"test\"\n\n[cluster.deploy.api]\ncontext = \"./api\"\nmanifests = \"\"\n";
         let errs = validate(&config, source, TEST_FILENAME).unwrap_err();
@@ -1785,6 +3150,9 @@ mod tests {
             logs: None,
             registries: vec![],
             k3s_args: vec![],
+            k3s_image: None,
+            env: BTreeMap::new(),
+            command_retries: 3,
         });
         let source = "[project]\nname = \"test\"\n\n[docker.postgres]\nimage = \"postgres:16-alpine\"\nport = 5432\n\n[cluster.deploy.postgres]\ncontext = \"./pg\"\nmanifests = \"./k8s\"\n";
         let errs = validate(&config, source, TEST_FILENAME).unwrap_err();
@@ -1812,6 +3180,9 @@ mod tests {
             logs: None,
             registries: vec![],
             k3s_args: vec![],
+            k3s_image: None,
+            env: BTreeMap::new(),
+            command_retries: 3,
         });
         let source = "[project]\nname = \"test\"\n\n[cluster.deploy.api]\ncontext = \"./api\"\nmanifests = \"./k8s\"\ndepends_on = [\"nonexistent\"]\n";
         let errs = validate(&config, source, TEST_FILENAME).unwrap_err();
@@ -1863,6 +3234,391 @@ policy = "invalid"
         )));
     }
 
+    #[test]
+    fn empty_k3s_image_rejected() {
+        let source = "[project]\nname = \"test\"\n\n[cluster]\nk3s_image = \" \"\n";
+        let config: DevrigConfig = toml::from_str(source).unwrap();
+        let errs = validate(&config, source, TEST_FILENAME).unwrap_err();
+        assert_eq!(errs.len(), 1);
+        match &errs[0] {
+            ConfigDiagnostic::EmptyK3sImage { span, .. } => {
+                assert_eq!(
+                    &source[span.offset()..span.offset() + span.len()],
+                    "k3s_image"
+                );
+            }
+            other => panic!("unexpected diagnostic: {other:?}"),
+        }
+
+        let source = source.replace("\" \"", "\"rancher/k3s:v1.31.4-k3s1\"");
+        let config: DevrigConfig = toml::from_str(&source).unwrap();
+        assert!(validate(&config, &source, TEST_FILENAME).is_ok());
+    }
+
+    #[test]
+    fn docker_stop_timeout_range_validated() {
+        let source = r#"
+[project]
+name = "test"
+
+[docker.postgres]
+image = "postgres:16"
+stop_timeout = 30
+
+[docker.kafka]
+image = "kafka:3"
+stop_timeout = 0
+
+[docker.es]
+image = "elasticsearch:8"
+stop_timeout = 7200
+"#;
+        let config: DevrigConfig = toml::from_str(source).unwrap();
+        let errs = validate(&config, source, TEST_FILENAME).unwrap_err();
+        let mut flagged: Vec<(&str, u64)> = errs
+            .iter()
+            .filter_map(|e| match e {
+                ConfigDiagnostic::InvalidStopTimeout { service, value, .. } => {
+                    Some((service.as_str(), *value))
+                }
+                _ => None,
+            })
+            .collect();
+        flagged.sort();
+        assert_eq!(flagged, vec![("es", 7200), ("kafka", 0)]);
+    }
+
+    #[test]
+    fn docker_resource_limits_validated() {
+        let source = r#"
+[project]
+name = "test"
+
+[docker.postgres]
+image = "postgres:16"
+mem_limit = "512m"
+cpus = 1.5
+
+[docker.kafka]
+image = "kafka:3"
+mem_limit = "lots"
+
+[docker.es]
+image = "elasticsearch:8"
+mem_limit = "1k"
+cpus = 0
+"#;
+        let config: DevrigConfig = toml::from_str(source).unwrap();
+        let errs = validate(&config, source, TEST_FILENAME).unwrap_err();
+        let mut flagged: Vec<(&str, &str)> = errs
+            .iter()
+            .filter_map(|e| match e {
+                ConfigDiagnostic::InvalidMemLimit { service, .. } => {
+                    Some((service.as_str(), "mem_limit"))
+                }
+                ConfigDiagnostic::InvalidCpus { service, .. } => Some((service.as_str(), "cpus")),
+                _ => None,
+            })
+            .collect();
+        flagged.sort();
+        assert_eq!(
+            flagged,
+            vec![("es", "cpus"), ("es", "mem_limit"), ("kafka", "mem_limit")]
+        );
+    }
+
+    #[test]
+    fn docker_network_aliases_and_extra_hosts_validated() {
+        let source = r#"
+[project]
+name = "test"
+
+[docker.postgres]
+image = "postgres:16"
+network_aliases = ["db.internal", "postgres-primary"]
+extra_hosts = ["api.example.com:10.0.0.5", "host.docker.internal:host-gateway", "v6.local:::1"]
+
+[docker.redis]
+image = "redis:7"
+network_aliases = ["cache_internal"]
+
+[docker.mailpit]
+image = "axllent/mailpit"
+network_aliases = ["-mail.internal"]
+extra_hosts = ["smtp.example.com:not-an-ip"]
+"#;
+        let config: DevrigConfig = toml::from_str(source).unwrap();
+        let errs = validate(&config, source, TEST_FILENAME).unwrap_err();
+        let mut flagged: Vec<(&str, &str)> = errs
+            .iter()
+            .filter_map(|e| match e {
+                ConfigDiagnostic::InvalidDockerHostName { service, field, .. } => {
+                    Some((service.as_str(), field.as_str()))
+                }
+                _ => None,
+            })
+            .collect();
+        flagged.sort();
+        assert_eq!(
+            flagged,
+            vec![
+                ("mailpit", "extra_hosts"),
+                ("mailpit", "network_aliases"),
+                ("redis", "network_aliases"),
+            ]
+        );
+    }
+
+    #[test]
+    fn env_prefix_must_be_a_valid_env_name_prefix() {
+        for (prefix, valid) in [
+            ("APP_", true),
+            ("_X", true),
+            ("MyApp", true),
+            ("", false),
+            ("1APP_", false),
+            ("APP-", false),
+            ("MY APP_", false),
+        ] {
+            let source = format!("[project]\nname = \"test\"\nenv_prefix = \"{}\"\n", prefix);
+            let config: DevrigConfig = toml::from_str(&source).unwrap();
+            let result = validate(&config, &source, TEST_FILENAME);
+            let flagged = result.is_err_and(|errs| {
+                errs.iter()
+                    .any(|e| matches!(e, ConfigDiagnostic::InvalidEnvPrefix { .. }))
+            });
+            assert_eq!(flagged, !valid, "env_prefix = {:?}", prefix);
+        }
+    }
+
+    #[test]
+    fn secrets_need_a_var_name_and_a_command() {
+        for (entry, valid) in [
+            (r#"db_password = { exec = "op read op://vault/db/password" }"#, true),
+            (r#""db-password" = { exec = "op read op://vault/db/password" }"#, false),
+            (r#"db_password = { exec = " " }"#, false),
+        ] {
+            let source = format!("[project]\nname = \"test\"\n\n[secrets]\n{}\n", entry);
+            let config: DevrigConfig = toml::from_str(&source).unwrap();
+            let result = validate(&config, &source, TEST_FILENAME);
+            let flagged = result.is_err_and(|errs| {
+                errs.iter()
+                    .any(|e| matches!(e, ConfigDiagnostic::InvalidSecret { .. }))
+            });
+            assert_eq!(flagged, !valid, "{}", entry);
+        }
+    }
+
+    #[test]
+    fn profile_unknown_resource_suggests_closest() {
+        let source = r#"[project]
+name = "test"
+
+[services.api]
+command = "cargo run"
+
+[docker.postgres]
+image = "postgres:16"
+
+[profiles]
+backend = ["api", "postgress"]
+"#;
+        let config: DevrigConfig = toml::from_str(source).unwrap();
+        let errs = validate(&config, source, TEST_FILENAME).unwrap_err();
+        assert_eq!(errs.len(), 1);
+        match &errs[0] {
+            ConfigDiagnostic::UnknownProfileResource {
+                profile,
+                resource,
+                advice,
+                span,
+                ..
+            } => {
+                assert_eq!(profile, "backend");
+                assert_eq!(resource, "postgress");
+                assert!(advice.contains("did you mean `postgres`?"));
+                assert_eq!(
+                    &source[span.offset()..span.offset() + span.len()],
+                    "postgress"
+                );
+            }
+            other => panic!("expected UnknownProfileResource, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn profile_with_known_resources_passes() {
+        let source = "[project]\nname = \"test\"\n\n[services.api]\ncommand = \"cargo run\"\n\n[docker.redis]\nimage = \"redis:7\"\n\n[profiles]\nbackend = [\"api\", \"redis\"]\n";
+        let config: DevrigConfig = toml::from_str(source).unwrap();
+        assert!(validate(&config, source, TEST_FILENAME).is_ok());
+    }
+
+    #[test]
+    fn composite_ready_check_depth_and_emptiness_validated() {
+        let source = r#"[project]
+name = "test"
+
+[docker.api]
+image = "api:dev"
+ready_check = { type = "all_of", checks = [{ type = "any_of", checks = [{ type = "all_of", checks = [{ type = "tcp" }] }] }] }
+
+[docker.web]
+image = "web:dev"
+ready_check = { type = "any_of", checks = [] }
+
+[docker.worker]
+image = "worker:dev"
+ready_check = { type = "all_of", checks = [{ type = "tcp" }, { type = "any_of", checks = [{ type = "tcp" }] }] }
+"#;
+        let config: DevrigConfig = toml::from_str(source).unwrap();
+        let errs = validate(&config, source, TEST_FILENAME).unwrap_err();
+        let invalid: Vec<&str> = errs
+            .iter()
+            .filter_map(|e| match e {
+                ConfigDiagnostic::InvalidReadyCheck { service, .. } => Some(service.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(invalid, vec!["api", "web"]);
+    }
+
+    #[test]
+    fn service_ready_check_types_validated() {
+        let source = r#"[project]
+name = "test"
+
+[services.api]
+command = "cargo run"
+port = 3000
+ready_check = { type = "http", url = "http://localhost:{{ services.api.port }}/health" }
+
+[services.db-proxy]
+command = "proxy"
+ready_check = { type = "any_of", checks = [{ type = "log", match = "ready" }, { type = "tcp" }] }
+
+[services.migrate]
+command = "migrate"
+ready_check = { type = "cmd", command = "true" }
+
+[services.orders]
+command = "orders"
+port = 50051
+ready_check = { type = "grpc" }
+
+[services.stream]
+command = "stream"
+ready_check = { type = "grpc", service = "stream.v1.Events" }
+
+[services.worker]
+command = "worker"
+ready_check = { type = "log", match = "consuming" }
+"#;
+        let config: DevrigConfig = toml::from_str(source).unwrap();
+        let errs = validate(&config, source, TEST_FILENAME).unwrap_err();
+        let invalid: Vec<&str> = errs
+            .iter()
+            .filter_map(|e| match e {
+                ConfigDiagnostic::InvalidServiceReadyCheck { service, .. } => {
+                    Some(service.as_str())
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(invalid, vec!["db-proxy", "migrate", "stream"]);
+    }
+
+    #[test]
+    fn invalid_shell_rejected() {
+        let source = "[project]\nname = \"test\"\n\n[services.api]\ncommand = \"cargo run\"\nshell = \"  \"\n";
+        let config: DevrigConfig = toml::from_str(source).unwrap();
+        let errs = validate(&config, source, TEST_FILENAME).unwrap_err();
+        assert!(errs.iter().any(|e| matches!(
+            e,
+            ConfigDiagnostic::InvalidShell { service, .. } if service == "api"
+        )));
+    }
+
+    #[test]
+    fn invalid_reload_signal_rejected() {
+        let source = "[project]\nname = \"test\"\n\n[services.api]\ncommand = \"cargo run\"\nreload_strategy = \"signal\"\nreload_signal = \"SIGKILL\"\n";
+        let config: DevrigConfig = toml::from_str(source).unwrap();
+        let errs = validate(&config, source, TEST_FILENAME).unwrap_err();
+        assert!(errs.iter().any(|e| matches!(
+            e,
+            ConfigDiagnostic::InvalidReloadSignal { value, .. } if value == "SIGKILL"
+        )));
+
+        let source = "[project]\nname = \"test\"\n\n[services.api]\ncommand = \"cargo run\"\nreload_strategy = \"signal\"\nreload_signal = \"SIGUSR1\"\n";
+        let config: DevrigConfig = toml::from_str(source).unwrap();
+        assert!(validate(&config, source, TEST_FILENAME).is_ok());
+    }
+
+    #[test]
+    fn zero_max_log_rate_rejected() {
+        let source = "[project]\nname = \"test\"\n\n[services.api]\ncommand = \"cargo run\"\nmax_log_lines_per_sec = 0\n";
+        let config: DevrigConfig = toml::from_str(source).unwrap();
+        let errs = validate(&config, source, TEST_FILENAME).unwrap_err();
+        assert!(errs.iter().any(|e| matches!(
+            e,
+            ConfigDiagnostic::InvalidMaxLogRate { service, .. } if service == "api"
+        )));
+
+        let source = "[project]\nname = \"test\"\n\n[services.api]\ncommand = \"cargo run\"\nmax_log_lines_per_sec = 200\n";
+        let config: DevrigConfig = toml::from_str(source).unwrap();
+        assert!(validate(&config, source, TEST_FILENAME).is_ok());
+    }
+
+    #[test]
+    fn invalid_port_ranges_rejected() {
+        let source = "[project]\nname = \"test\"\n\n[services.api]\ncommand = \"cargo run\"\nport = \"3010-3000\"\n\n[services.web]\ncommand = \"npm start\"\nport = { start = 8000, end = 8010, preferred = 9000 }\n\n[dashboard]\nport = \"0-10\"\n";
+        let config: DevrigConfig = toml::from_str(source).unwrap();
+        let errs = validate(&config, source, TEST_FILENAME).unwrap_err();
+        let reasons: Vec<(&str, &str)> = errs
+            .iter()
+            .filter_map(|e| match e {
+                ConfigDiagnostic::InvalidPortRange {
+                    resource, reason, ..
+                } => Some((resource.as_str(), reason.as_str())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                ("service `api`", "the range is inverted"),
+                ("service `web`", "preferred port 9000 is outside the range"),
+                ("dashboard.port", "ports start at 1"),
+            ]
+        );
+
+        let source = "[project]\nname = \"test\"\n\n[services.api]\ncommand = \"cargo run\"\nport = \"3000-3000\"\n";
+        let config: DevrigConfig = toml::from_str(source).unwrap();
+        assert!(validate(&config, source, TEST_FILENAME).is_ok());
+    }
+
+    #[test]
+    fn shell_none_requires_splittable_command() {
+        let source = "[project]\nname = \"test\"\n\n[services.api]\ncommand = \"echo 'oops\"\nshell = \"none\"\n";
+        let config: DevrigConfig = toml::from_str(source).unwrap();
+        let errs = validate(&config, source, TEST_FILENAME).unwrap_err();
+        assert!(errs.iter().any(|e| matches!(
+            e,
+            ConfigDiagnostic::InvalidShell { value, .. } if value == "none"
+        )));
+
+        let source = "[project]\nname = \"test\"\n\n[services.api]\ncommand = \"node server.js\"\nshell = \"none\"\npre_start = [\"npm ci\", \"echo 'oops\"]\n";
+        let config: DevrigConfig = toml::from_str(source).unwrap();
+        let errs = validate(&config, source, TEST_FILENAME).unwrap_err();
+        assert!(errs.iter().any(|e| matches!(
+            e,
+            ConfigDiagnostic::InvalidShell { reason, .. } if reason.contains("pre_start")
+        )));
+
+        let source = "[project]\nname = \"test\"\n\n[services.api]\ncommand = \"node server.js\"\nshell = \"none\"\n";
+        let config: DevrigConfig = toml::from_str(source).unwrap();
+        assert!(validate(&config, source, TEST_FILENAME).is_ok());
+    }
+
     #[test]
     fn valid_restart_policies_accepted() {
         for policy in &["always", "on-failure", "never"] {
@@ -1943,6 +3699,69 @@ retention = "not-a-duration"
             .any(|e| matches!(e, ConfigDiagnostic::InvalidRetention { .. })));
     }
 
+    #[test]
+    fn otel_forward_endpoint_must_be_usable() {
+        let cases = [
+            ("http://localhost:4317", "grpc", true),
+            ("https://otel.example.com", "http", true),
+            ("https://otel.example.com", "grpc", false),
+            ("localhost:4317", "grpc", false),
+            ("http://", "http", false),
+            ("udp://localhost:4317", "http", false),
+        ];
+        for (endpoint, protocol, ok) in cases {
+            let source = format!(
+                r#"
+[project]
+name = "test"
+
+[dashboard.otel.forward]
+endpoint = "{endpoint}"
+protocol = "{protocol}"
+"#
+            );
+            let config: DevrigConfig = toml::from_str(&source).unwrap();
+            let result = validate(&config, &source, TEST_FILENAME);
+            if ok {
+                assert!(result.is_ok(), "{endpoint} ({protocol}) should pass");
+            } else {
+                let errs = result.unwrap_err();
+                assert!(
+                    errs.iter()
+                        .any(|e| matches!(e, ConfigDiagnostic::InvalidOtelForward { .. })),
+                    "{endpoint} ({protocol}) should fail"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn dashboard_tls_needs_both_cert_and_key() {
+        let cases = [
+            ("cert = \"dev.pem\"\nkey = \"dev-key.pem\"", None),
+            ("cert = \"dev.pem\"", Some("key")),
+            ("key = \"dev-key.pem\"\notel = true", Some("cert")),
+        ];
+        for (body, expected) in cases {
+            let source = format!("[project]\nname = \"test\"\n\n[dashboard.tls]\n{body}\n");
+            let config: DevrigConfig = toml::from_str(&source).unwrap();
+            let result = validate(&config, &source, TEST_FILENAME);
+            match expected {
+                None => assert!(result.is_ok(), "{body} should pass"),
+                Some(field) => {
+                    let errs = result.unwrap_err();
+                    assert!(
+                        errs.iter().any(|e| matches!(
+                            e,
+                            ConfigDiagnostic::IncompleteDashboardTls { missing, .. } if *missing == field
+                        )),
+                        "{body} should report missing {field}"
+                    );
+                }
+            }
+        }
+    }
+
     #[test]
     fn valid_dashboard_config_passes() {
         let source = r#"
@@ -2008,6 +3827,32 @@ namespace = "traefik"
         )));
     }
 
+    #[test]
+    fn validate_addon_invalid_namespace() {
+        let source = r#"
+[project]
+name = "test"
+
+[cluster.addons.tools]
+type = "manifest"
+path = "./k8s/tools.yaml"
+namespace = "My_Tools"
+"#;
+        let config: DevrigConfig = toml::from_str(source).unwrap();
+        let errs = validate(&config, source, TEST_FILENAME).unwrap_err();
+        assert!(errs.iter().any(|e| matches!(
+            e,
+            ConfigDiagnostic::InvalidAddonNamespace { addon, namespace, .. }
+                if addon == "tools" && namespace == "My_Tools"
+        )));
+
+        assert!(is_valid_namespace("cert-manager"));
+        assert!(is_valid_namespace("ns1"));
+        assert!(!is_valid_namespace("-tools"));
+        assert!(!is_valid_namespace("tools-"));
+        assert!(!is_valid_namespace(&"a".repeat(64)));
+    }
+
     #[test]
     fn validate_addon_port_conflict_with_service() {
         let source = r#"
@@ -2032,6 +3877,28 @@ port_forward = { 9000 = "svc/traefik:9000" }
             .any(|e| matches!(e, ConfigDiagnostic::AddonPortConflict { port: 9000, .. })));
     }
 
+    #[test]
+    fn validate_addon_port_forward_keys() {
+        let source = r#"
+[project]
+name = "test"
+
+[cluster.addons.traefik]
+type = "helm"
+chart = "traefik/traefik"
+repo = "https://traefik.github.io/charts"
+namespace = "traefik"
+port_forward = { auto = "svc/traefik:9000", dashbaord = "svc/traefik:80" }
+"#;
+        let config: DevrigConfig = toml::from_str(source).unwrap();
+        let errs = validate(&config, source, TEST_FILENAME).unwrap_err();
+        assert_eq!(errs.len(), 1, "{:?}", errs);
+        assert!(matches!(
+            &errs[0],
+            ConfigDiagnostic::InvalidAddonPortForward { key, .. } if key == "dashbaord"
+        ));
+    }
+
     #[test]
     fn validate_addon_name_conflict_with_deploy() {
         let source = r#"
@@ -2164,6 +4031,36 @@ exclude_namespaces = ["kube-system"]
         )));
     }
 
+    #[test]
+    fn validate_logs_exclude_patterns() {
+        let source = r#"
+[project]
+name = "test"
+
+[dashboard]
+
+[cluster.logs]
+namespaces = "all"
+exclude_namespaces = ["istio-*", "kube-system"]
+exclude_pods = [".*-canary", "web-(v1"]
+"#;
+        let config: DevrigConfig = toml::from_str(source).unwrap();
+        let errs = validate(&config, source, TEST_FILENAME).unwrap_err();
+        assert_eq!(errs.len(), 1);
+        let ConfigDiagnostic::InvalidLogsExcludePattern {
+            span, field, entry, ..
+        } = &errs[0]
+        else {
+            panic!("unexpected diagnostic: {:?}", errs[0]);
+        };
+        assert_eq!(field, "exclude_pods");
+        assert_eq!(entry, "web-(v1");
+        assert_eq!(
+            &source[span.offset()..span.offset() + span.len()],
+            "web-(v1"
+        );
+    }
+
     #[test]
     fn validate_logs_exclude_with_all_is_valid() {
         let source = r#"
@@ -2258,6 +4155,9 @@ context = "./tools/job-runner"
             logs: None,
             registries: vec![],
             k3s_args: vec![],
+            k3s_image: None,
+            env: BTreeMap::new(),
+            command_retries: 3,
         });
         let source = "[project]\nname = \"test\"\n\n[cluster.image.job-runner]\ncontext = \"\"\n";
         let errs = validate(&config, source, TEST_FILENAME).unwrap_err();
@@ -2285,6 +4185,9 @@ context = "./tools/job-runner"
             logs: None,
             registries: vec![],
             k3s_args: vec![],
+            k3s_image: None,
+            env: BTreeMap::new(),
+            command_retries: 3,
         });
         let source = "[project]\nname = \"test\"\n\n[cluster.image.api]\ncontext = \"./tools/api\"\n\n[cluster.deploy.api]\ncontext = \"./api\"\nmanifests = \"./k8s\"\n";
         let errs = validate(&config, source, TEST_FILENAME).unwrap_err();
@@ -2316,6 +4219,9 @@ context = "./tools/job-runner"
             logs: None,
             registries: vec![],
             k3s_args: vec![],
+            k3s_image: None,
+            env: BTreeMap::new(),
+            command_retries: 3,
         });
         let source = "[project]\nname = \"test\"\n\n[docker.postgres]\nimage = \"postgres:16-alpine\"\nport = 5432\n\n[cluster.image.postgres]\ncontext = \"./tools/pg\"\n";
         let errs = validate(&config, source, TEST_FILENAME).unwrap_err();
@@ -2343,6 +4249,9 @@ context = "./tools/job-runner"
             logs: None,
             registries: vec![],
             k3s_args: vec![],
+            k3s_image: None,
+            env: BTreeMap::new(),
+            command_retries: 3,
         });
         let source = "[project]\nname = \"test\"\n\n[cluster.image.job-runner]\ncontext = \"./tools/job-runner\"\ndepends_on = [\"nonexistent\"]\n";
         let errs = validate(&config, source, TEST_FILENAME).unwrap_err();
@@ -2378,6 +4287,9 @@ context = "./tools/job-runner"
             logs: None,
             registries: vec![],
             k3s_args: vec![],
+            k3s_image: None,
+            env: BTreeMap::new(),
+            command_retries: 3,
         });
         let source = "[project]\nname = \"test\"\n\n[docker.postgres]\nimage = \"postgres:16-alpine\"\nport = 5432\n\n[cluster.image.job-runner]\ncontext = \"./tools/job-runner\"\ndepends_on = [\"postgres\"]\n";
         assert!(validate(&config, source, TEST_FILENAME).is_ok());
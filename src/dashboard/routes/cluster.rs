@@ -2,7 +2,7 @@ use axum::extract::State;
 use axum::Json;
 use serde::Serialize;
 
-use crate::orchestrator::state::ProjectState;
+use crate::orchestrator::state::{PortForwardState, ProjectState};
 
 use super::DashboardState;
 
@@ -34,6 +34,7 @@ pub struct AddonInfo {
     pub addon_type: String,
     pub namespace: String,
     pub installed_at: String,
+    pub port_forwards: Vec<PortForwardState>,
 }
 
 pub async fn get_cluster(
@@ -77,6 +78,7 @@ pub async fn get_cluster(
             addon_type: addon.addon_type.clone(),
             namespace: addon.namespace.clone(),
             installed_at: addon.installed_at.to_rfc3339(),
+            port_forwards: addon.port_forwards.clone(),
         })
         .collect();
 
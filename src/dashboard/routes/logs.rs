@@ -1,16 +1,69 @@
 use axum::extract::{Query, State};
+use axum::http::{header, HeaderMap, StatusCode};
 use axum::response::IntoResponse;
 use axum::Json;
+use chrono::Utc;
 
-use crate::otel::query::LogQuery;
+use crate::otel::loki::{push_to_stored, PushRequest};
+use crate::otel::query::{LogQuery, time_window};
+use crate::otel::types::TelemetryEvent;
 
 use super::DashboardState;
 
 pub async fn list_logs(
     State(state): State<DashboardState>,
-    Query(query): Query<LogQuery>,
+    Query(mut query): Query<LogQuery>,
 ) -> impl IntoResponse {
+    match time_window(query.last.as_deref(), query.since, query.until, Utc::now()) {
+        Ok((since, until)) => (query.since, query.until) = (since, until),
+        Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+    }
     let store = state.store.read().await;
     let logs = store.query_logs(&query);
     Json(logs).into_response()
 }
+
+/// Loki push API (JSON payloads only). Protobuf pushes, the default for
+/// some shippers, are rejected with 415.
+pub async fn loki_push(
+    State(state): State<DashboardState>,
+    headers: HeaderMap,
+    body: axum::body::Bytes,
+) -> impl IntoResponse {
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("application/json");
+    if !content_type.starts_with("application/json") {
+        return (
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "only JSON pushes (Content-Type: application/json) are supported",
+        )
+            .into_response();
+    }
+    let logs = match serde_json::from_slice::<PushRequest>(&body)
+        .map_err(|e| e.to_string())
+        .and_then(|req| push_to_stored(&req))
+    {
+        Ok(logs) => logs,
+        Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+    };
+
+    let mut events = Vec::with_capacity(logs.len());
+    {
+        let mut store = state.store.write().await;
+        for stored in logs {
+            events.push(TelemetryEvent::LogRecord {
+                trace_id: None,
+                severity: format!("{:?}", stored.severity),
+                body: stored.body.clone(),
+                service: stored.service_name.clone(),
+            });
+            store.insert_log(stored);
+        }
+    }
+    for event in events {
+        let _ = state.events_tx.send(event);
+    }
+    StatusCode::NO_CONTENT.into_response()
+}
@@ -0,0 +1,20 @@
+use axum::extract::State;
+use axum::response::IntoResponse;
+use axum::Json;
+use serde::Serialize;
+
+use super::DashboardState;
+
+#[derive(Debug, Serialize)]
+pub struct DashboardMeta {
+    pub title: String,
+    pub version: &'static str,
+}
+
+pub async fn get_meta(State(state): State<DashboardState>) -> impl IntoResponse {
+    Json(DashboardMeta {
+        title: state.title.clone(),
+        version: env!("CARGO_PKG_VERSION"),
+    })
+    .into_response()
+}
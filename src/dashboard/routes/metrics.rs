@@ -1,15 +1,22 @@
 use axum::extract::{Query, State};
+use axum::http::{header, StatusCode};
 use axum::response::IntoResponse;
 use axum::Json;
+use chrono::Utc;
 
-use crate::otel::query::{MetricQuery, MetricSeriesQuery};
+use crate::otel::prometheus;
+use crate::otel::query::{time_window, MetricQuery, MetricSeriesQuery};
 
 use super::DashboardState;
 
 pub async fn list_metrics(
     State(state): State<DashboardState>,
-    Query(query): Query<MetricQuery>,
+    Query(mut query): Query<MetricQuery>,
 ) -> impl IntoResponse {
+    match time_window(query.last.as_deref(), query.since, query.until, Utc::now()) {
+        Ok((since, until)) => (query.since, query.until) = (since, until),
+        Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+    }
     let store = state.store.read().await;
     let metrics = store.query_metrics(&query);
     Json(metrics).into_response()
@@ -19,7 +26,22 @@ pub async fn get_metric_series(
     State(state): State<DashboardState>,
     Query(query): Query<MetricSeriesQuery>,
 ) -> impl IntoResponse {
+    if let Err(e) = query.window() {
+        return (StatusCode::BAD_REQUEST, e).into_response();
+    }
     let store = state.store.read().await;
     let response = store.query_metric_series(&query);
     Json(response).into_response()
 }
+
+/// Prometheus scrape endpoint: the latest value of every stored series.
+pub async fn prometheus_metrics(State(state): State<DashboardState>) -> impl IntoResponse {
+    let store = state.store.read().await;
+    (
+        [(
+            header::CONTENT_TYPE,
+            "text/plain; version=0.0.4; charset=utf-8",
+        )],
+        prometheus::render(store.metrics()),
+    )
+}
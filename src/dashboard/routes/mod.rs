@@ -2,6 +2,7 @@ pub mod cluster;
 pub mod config;
 pub mod env;
 pub mod logs;
+pub mod meta;
 pub mod metrics;
 pub mod services;
 pub mod status;
@@ -10,10 +11,12 @@ pub mod traces;
 use std::path::PathBuf;
 use std::sync::Arc;
 
+use axum::middleware;
 use axum::routing::{get, post};
 use axum::Router;
-use tokio::sync::{broadcast, RwLock};
+use tokio::sync::{broadcast, mpsc, RwLock};
 
+use crate::orchestrator::control::ControlMessage;
 use crate::otel::storage::TelemetryStore;
 use crate::otel::types::TelemetryEvent;
 
@@ -23,6 +26,11 @@ pub struct DashboardState {
     pub events_tx: broadcast::Sender<TelemetryEvent>,
     pub config_path: Option<PathBuf>,
     pub state_dir: Option<PathBuf>,
+    /// Dashboard title (`[dashboard] title`, or the project name).
+    pub title: String,
+    /// Requests to the orchestrator that started this dashboard; `None`
+    /// when it isn't serving a live `devrig start`.
+    pub control: Option<mpsc::Sender<ControlMessage>>,
 }
 
 pub fn api_router(state: DashboardState) -> Router {
@@ -34,6 +42,7 @@ pub fn api_router(state: DashboardState) -> Router {
         .route("/api/metrics", get(metrics::list_metrics))
         .route("/api/metrics/series", get(metrics::get_metric_series))
         .route("/api/status", get(status::get_status))
+        .route("/api/meta", get(meta::get_meta))
         .route(
             "/api/config",
             get(config::get_config).put(config::update_config),
@@ -41,5 +50,21 @@ pub fn api_router(state: DashboardState) -> Router {
         .route("/api/services", get(services::get_services))
         .route("/api/cluster", get(cluster::get_cluster))
         .route("/api/config/validate", post(config::validate_config))
+        .route("/metrics", get(metrics::prometheus_metrics))
+        .route("/loki/api/v1/push", post(logs::loki_push))
+        .with_state(state)
+}
+
+/// Routes that act on the running project. They only answer loopback peers
+/// and refuse requests sent from another origin's page, so they are served
+/// without the permissive CORS layer of the read-only API.
+pub fn control_router(state: DashboardState) -> Router {
+    Router::new()
+        .route(
+            "/api/services/{name}/restart",
+            post(services::restart_service),
+        )
+        .route("/api/services/{name}/stop", post(services::stop_service))
+        .route_layer(middleware::from_fn(services::require_local))
         .with_state(state)
 }
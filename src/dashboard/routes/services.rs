@@ -1,8 +1,18 @@
-use axum::extract::State;
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+
+use axum::extract::{ConnectInfo, Path, Request, State};
+use axum::http::{header, HeaderMap, StatusCode, Uri};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
 use axum::Json;
 use serde::Serialize;
 
 use crate::config::model::DevrigConfig;
+use crate::dashboard::server::PeerAddr;
+use crate::orchestrator::control::{
+    self, ControlRequest, ControlResponse, ServiceControlErrorKind,
+};
 use crate::orchestrator::state::ProjectState;
 
 use super::DashboardState;
@@ -25,10 +35,10 @@ pub struct ServiceInfo {
     pub url: Option<String>,
 }
 
-pub async fn get_services(
-    State(state): State<DashboardState>,
-) -> Json<Vec<ServiceInfo>> {
+pub async fn get_services(State(state): State<DashboardState>) -> Json<Vec<ServiceInfo>> {
     let mut services = Vec::new();
+    // Addon port-forwards as started, including ones on an auto port
+    let mut addon_ports = HashMap::new();
 
     // Load runtime state (services, docker, compose)
     if let Some(project) = state
@@ -36,6 +46,14 @@ pub async fn get_services(
         .as_ref()
         .and_then(|dir| ProjectState::load(dir))
     {
+        if let Some(cluster) = &project.cluster {
+            for (name, addon) in &cluster.installed_addons {
+                if let Some(pf) = addon.port_forwards.first() {
+                    addon_ports.insert(name.clone(), (pf.local_port, pf.port_auto));
+                }
+            }
+        }
+
         for (name, svc) in &project.services {
             services.push(ServiceInfo {
                 name: name.clone(),
@@ -102,13 +120,18 @@ pub async fn get_services(
                 // Cluster addons and ports
                 if let Some(cluster) = &config.cluster {
                     for (name, addon) in &cluster.addons {
-                        let port_forwards = addon.parsed_port_forwards();
-                        let port = port_forwards.first().map(|(p, _)| *p);
+                        let (port, port_auto) = match addon_ports.get(name) {
+                            Some(&(port, auto)) => (Some(port), auto),
+                            None => {
+                                let port_forwards = addon.parsed_port_forwards();
+                                (port_forwards.first().map(|(p, _)| *p), false)
+                            }
+                        };
                         services.push(ServiceInfo {
                             name: name.clone(),
                             port,
                             kind: "addon".to_string(),
-                            port_auto: false,
+                            port_auto,
                             protocol: None,
                             phase: None,
                             exit_code: None,
@@ -120,8 +143,7 @@ pub async fn get_services(
                     // Cluster port mappings (e.g. "8080:30080")
                     for mapping in &cluster.ports {
                         let parts: Vec<&str> = mapping.split(':').collect();
-                        if let Some(host_port) = parts.first().and_then(|p| p.parse::<u16>().ok())
-                        {
+                        if let Some(host_port) = parts.first().and_then(|p| p.parse::<u16>().ok()) {
                             services.push(ServiceInfo {
                                 name: format!("cluster:{}", mapping),
                                 port: Some(host_port),
@@ -146,6 +168,92 @@ pub async fn get_services(
     Json(services)
 }
 
+/// `POST /api/services/{name}/restart`: restart a service, or start one
+/// stopped earlier.
+pub async fn restart_service(
+    State(state): State<DashboardState>,
+    Path(name): Path<String>,
+) -> Response {
+    let services = vec![name];
+    control_service(&state, ControlRequest::Restart { services }).await
+}
+
+/// `POST /api/services/{name}/stop`: stop a running service.
+pub async fn stop_service(
+    State(state): State<DashboardState>,
+    Path(name): Path<String>,
+) -> Response {
+    let services = vec![name];
+    control_service(&state, ControlRequest::Stop { services }).await
+}
+
+/// Hand `request` to the orchestrator and map its answer to a status:
+/// 404 for an unknown service, 409 for one that isn't running, and 503
+/// when there is no live orchestrator to ask.
+async fn control_service(state: &DashboardState, request: ControlRequest) -> Response {
+    let tx = match &state.control {
+        Some(tx) if !tx.is_closed() => tx,
+        _ => {
+            let response = ControlResponse::error("service control needs a running `devrig start`");
+            return (StatusCode::SERVICE_UNAVAILABLE, Json(response)).into_response();
+        }
+    };
+    let response = control::send_local(tx, request).await;
+    let status = match (response.ok, response.error_kind) {
+        (true, _) => StatusCode::OK,
+        (false, Some(ServiceControlErrorKind::Unknown)) => StatusCode::NOT_FOUND,
+        (
+            false,
+            Some(ServiceControlErrorKind::NotRunning | ServiceControlErrorKind::NotStarted),
+        ) => StatusCode::CONFLICT,
+        (false, None) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, Json(response)).into_response()
+}
+
+/// Middleware for the control routes: only serve requests from this
+/// machine, and none a browser sent on behalf of a page from another
+/// origin. Answers 403 otherwise.
+pub async fn require_local(
+    ConnectInfo(PeerAddr(peer)): ConnectInfo<PeerAddr>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !is_local_request(peer, request.headers()) {
+        let response =
+            ControlResponse::error("service control is only available from localhost");
+        return (StatusCode::FORBIDDEN, Json(response)).into_response();
+    }
+    next.run(request).await
+}
+
+/// Whether a request comes from a loopback peer and, when a browser sent it,
+/// from a page served on a loopback host. Checking the `Origin` host rather
+/// than comparing it with `Host` also turns away DNS-rebound pages.
+fn is_local_request(peer: SocketAddr, headers: &HeaderMap) -> bool {
+    if !peer.ip().to_canonical().is_loopback() {
+        return false;
+    }
+    let Some(origin) = headers.get(header::ORIGIN) else {
+        return true;
+    };
+    origin
+        .to_str()
+        .ok()
+        .and_then(|origin| origin.parse::<Uri>().ok())
+        .and_then(|uri| uri.host().map(is_loopback_host))
+        .unwrap_or(false)
+}
+
+fn is_loopback_host(host: &str) -> bool {
+    host.eq_ignore_ascii_case("localhost")
+        || host
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse::<IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback())
+}
+
 fn parse_port_from_url(url: &str) -> Option<u16> {
     // Extract port from URLs like "http://localhost:8080" or "http://localhost:8080/path"
     let after_scheme = url.split("://").nth(1).unwrap_or(url);
@@ -153,3 +261,111 @@ fn parse_port_from_url(url: &str) -> Option<u16> {
     let port_str = host_port.rsplit(':').next()?;
     port_str.parse::<u16>().ok()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orchestrator::control::{ControlMessage, ServiceControlError};
+    use crate::otel::storage::TelemetryStore;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::sync::{broadcast, mpsc, RwLock};
+
+    fn dashboard_state(control: Option<mpsc::Sender<ControlMessage>>) -> DashboardState {
+        DashboardState {
+            store: Arc::new(RwLock::new(TelemetryStore::new(
+                10,
+                10,
+                10,
+                Duration::from_secs(60),
+            ))),
+            events_tx: broadcast::channel(1).0,
+            config_path: None,
+            state_dir: None,
+            title: "test".to_string(),
+            control,
+        }
+    }
+
+    /// A stand-in orchestrator: `api` runs, `worker` is configured but
+    /// stopped, anything else is unknown.
+    fn fake_orchestrator() -> mpsc::Sender<ControlMessage> {
+        let (tx, mut rx) = mpsc::channel::<ControlMessage>(8);
+        tokio::spawn(async move {
+            while let Some((request, reply)) = rx.recv().await {
+                let (ControlRequest::Restart { services } | ControlRequest::Stop { services }) =
+                    &request
+                else {
+                    panic!("unexpected request {:?}", request);
+                };
+                let services = services.clone();
+                let name = services[0].clone();
+                let err = match name.as_str() {
+                    "api" => None,
+                    "worker" => Some(ServiceControlError::NotRunning(name.clone())),
+                    _ => Some(ServiceControlError::Unknown {
+                        name: name.clone(),
+                        running: vec!["api".to_string()],
+                    }),
+                };
+                let response = match (err, request) {
+                    (Some(e), _) => ControlResponse::from_error(&e.into()),
+                    (None, ControlRequest::Stop { .. }) => ControlResponse::stopped(services),
+                    (None, _) => ControlResponse::restarted(services),
+                };
+                let _ = reply.send(response);
+            }
+        });
+        tx
+    }
+
+    #[tokio::test]
+    async fn service_control_status_codes() {
+        let state = dashboard_state(Some(fake_orchestrator()));
+        let call = |name: &str, stop: bool| {
+            let state = state.clone();
+            let name = Path(name.to_string());
+            async move {
+                if stop {
+                    stop_service(State(state), name).await.status()
+                } else {
+                    restart_service(State(state), name).await.status()
+                }
+            }
+        };
+        assert_eq!(call("api", false).await, StatusCode::OK);
+        assert_eq!(call("api", true).await, StatusCode::OK);
+        assert_eq!(call("worker", true).await, StatusCode::CONFLICT);
+        assert_eq!(call("nope", false).await, StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn service_control_only_for_local_requests() {
+        let local: SocketAddr = "127.0.0.1:50000".parse().unwrap();
+        let mapped: SocketAddr = "[::ffff:127.0.0.1]:50000".parse().unwrap();
+        let remote: SocketAddr = "192.168.1.20:50000".parse().unwrap();
+        let origin = |value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(header::ORIGIN, value.parse().unwrap());
+            headers
+        };
+
+        assert!(is_local_request(local, &HeaderMap::new()));
+        assert!(is_local_request(mapped, &HeaderMap::new()));
+        assert!(is_local_request(local, &origin("http://localhost:4000")));
+        assert!(is_local_request(local, &origin("https://127.0.0.1:4000")));
+        assert!(is_local_request(local, &origin("http://[::1]:5173")));
+
+        assert!(!is_local_request(remote, &HeaderMap::new()));
+        assert!(!is_local_request(remote, &origin("http://localhost:4000")));
+        assert!(!is_local_request(local, &origin("https://evil.example")));
+        assert!(!is_local_request(local, &origin("null")));
+    }
+
+    #[tokio::test]
+    async fn service_control_needs_a_live_project() {
+        let state = dashboard_state(None);
+        let response = restart_service(State(state), Path("api".to_string())).await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+}
@@ -2,15 +2,20 @@ use axum::extract::{Path, Query, State};
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
 use axum::Json;
+use chrono::Utc;
 
-use crate::otel::query::TraceQuery;
+use crate::otel::query::{TraceQuery, time_window};
 
 use super::DashboardState;
 
 pub async fn list_traces(
     State(state): State<DashboardState>,
-    Query(query): Query<TraceQuery>,
+    Query(mut query): Query<TraceQuery>,
 ) -> impl IntoResponse {
+    match time_window(query.last.as_deref(), query.since, query.until, Utc::now()) {
+        Ok((since, until)) => (query.since, query.until) = (since, until),
+        Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+    }
     let store = state.store.read().await;
     let traces = store.query_traces(&query);
     Json(traces).into_response()
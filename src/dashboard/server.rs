@@ -1,17 +1,43 @@
+use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::sync::Arc;
 
-use tokio::sync::{broadcast, RwLock};
+use axum::extract::connect_info::Connected;
+use axum::serve::IncomingStream;
+use tokio::net::TcpListener;
+use tokio::sync::{broadcast, mpsc, RwLock};
 use tokio_util::sync::CancellationToken;
 use tower_http::cors::CorsLayer;
 
+use crate::orchestrator::control::ControlMessage;
 use crate::otel::storage::TelemetryStore;
 use crate::otel::types::TelemetryEvent;
+use crate::tls::{TlsIdentity, TlsListener, ALPN_HTTP1};
 
 use super::routes::{self, DashboardState};
 use super::static_files;
 use super::ws;
 
+/// Address of the peer a dashboard request came in from, over plain TCP or
+/// TLS.
+#[derive(Debug, Clone, Copy)]
+pub struct PeerAddr(pub SocketAddr);
+
+impl Connected<IncomingStream<'_, TcpListener>> for PeerAddr {
+    fn connect_info(stream: IncomingStream<'_, TcpListener>) -> Self {
+        Self(*stream.remote_addr())
+    }
+}
+
+impl Connected<IncomingStream<'_, TlsListener>> for PeerAddr {
+    fn connect_info(stream: IncomingStream<'_, TlsListener>) -> Self {
+        Self(*stream.remote_addr())
+    }
+}
+
+/// Serve the dashboard on `port`, over HTTPS when `tls` is given. `control`
+/// enables the service restart/stop routes, which answer loopback peers only.
+#[allow(clippy::too_many_arguments)]
 pub async fn start_dashboard_server(
     port: u16,
     store: Arc<RwLock<TelemetryStore>>,
@@ -19,25 +45,42 @@ pub async fn start_dashboard_server(
     cancel: CancellationToken,
     config_path: Option<PathBuf>,
     state_dir: Option<PathBuf>,
+    title: String,
+    tls: Option<TlsIdentity>,
+    control: Option<mpsc::Sender<ControlMessage>>,
 ) -> anyhow::Result<()> {
     let state = DashboardState {
         store,
         events_tx,
         config_path,
         state_dir,
+        title,
+        control,
     };
 
     let app = routes::api_router(state.clone())
-        .merge(ws::ws_router(state))
+        .merge(ws::ws_router(state.clone()))
         .merge(static_files::static_router())
-        .layer(CorsLayer::permissive());
+        .layer(CorsLayer::permissive())
+        .merge(routes::control_router(state))
+        .into_make_service_with_connect_info::<PeerAddr>();
 
     let addr = format!("0.0.0.0:{}", port);
-    let listener = tokio::net::TcpListener::bind(&addr).await?;
+    let listener = TcpListener::bind(&addr).await?;
 
-    axum::serve(listener, app)
-        .with_graceful_shutdown(cancel.cancelled_owned())
-        .await?;
+    match tls {
+        Some(identity) => {
+            let listener = TlsListener::new(listener, identity.acceptor(ALPN_HTTP1)?)?;
+            axum::serve(listener, app)
+                .with_graceful_shutdown(cancel.cancelled_owned())
+                .await?;
+        }
+        None => {
+            axum::serve(listener, app)
+                .with_graceful_shutdown(cancel.cancelled_owned())
+                .await?;
+        }
+    }
 
     Ok(())
 }
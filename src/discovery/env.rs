@@ -1,39 +1,43 @@
 use std::collections::{BTreeMap, HashMap};
 
 use crate::config::model::DevrigConfig;
-use crate::discovery::url::generate_url;
+use crate::discovery::url::{generate_url, service_url};
 
 /// Build the full environment variable map for a given service.
 ///
 /// The layering order (later overrides earlier):
 /// 1. Global env from config.env
-/// 2. Auto-generated DEVRIG_* vars for all docker services
-/// 3. Auto-generated DEVRIG_* vars for all other services
+/// 2. Auto-generated discovery vars for all docker services
+/// 3. Auto-generated discovery vars for all other services
 /// 4. PORT and HOST for the service itself
 /// 5. Service-specific env (explicit overrides)
+///
+/// Discovery vars are named `<PREFIX><NAME>_HOST` etc. with the prefix from
+/// `project.env_prefix` (`DEVRIG_` by default); `OTEL_*` vars are not prefixed.
 pub fn build_service_env(
     service_name: &str,
     config: &DevrigConfig,
     resolved_ports: &HashMap<String, u16>,
 ) -> BTreeMap<String, String> {
     let mut env = BTreeMap::new();
+    let prefix = config.project.env_prefix();
 
     // 1. Start with global env
     for (k, v) in &config.env {
         env.insert(k.clone(), v.clone());
     }
 
-    // 2. Add DEVRIG_* vars for all docker services
+    // 2. Add discovery vars for all docker services
     for (docker_name, docker_config) in &config.docker {
         let upper = docker_name.to_uppercase();
         let port_key = format!("docker:{}", docker_name);
 
-        env.insert(format!("DEVRIG_{}_HOST", upper), "localhost".to_string());
+        env.insert(format!("{}{}_HOST", prefix, upper), "localhost".to_string());
 
         if let Some(&port) = resolved_ports.get(&port_key) {
-            env.insert(format!("DEVRIG_{}_PORT", upper), port.to_string());
+            env.insert(format!("{}{}_PORT", prefix, upper), port.to_string());
             let url = generate_url(docker_name, docker_config, port);
-            env.insert(format!("DEVRIG_{}_URL", upper), url);
+            env.insert(format!("{}{}_URL", prefix, upper), url);
         }
 
         // Named ports
@@ -42,28 +46,28 @@ pub fn build_service_env(
             if let Some(&port) = resolved_ports.get(&named_key) {
                 let upper_port_name = port_name.to_uppercase();
                 env.insert(
-                    format!("DEVRIG_{}_PORT_{}", upper, upper_port_name),
+                    format!("{}{}_PORT_{}", prefix, upper, upper_port_name),
                     port.to_string(),
                 );
             }
         }
     }
 
-    // 3. Add DEVRIG_* vars for all other services
-    for svc_name in config.services.keys() {
+    // 3. Add discovery vars for all other services
+    for (svc_name, svc_config) in &config.services {
         if svc_name == service_name {
             continue;
         }
         let upper = svc_name.to_uppercase();
         let svc_key = format!("service:{}", svc_name);
 
-        env.insert(format!("DEVRIG_{}_HOST", upper), "localhost".to_string());
+        env.insert(format!("{}{}_HOST", prefix, upper), "localhost".to_string());
 
         if let Some(&port) = resolved_ports.get(&svc_key) {
-            env.insert(format!("DEVRIG_{}_PORT", upper), port.to_string());
+            env.insert(format!("{}{}_PORT", prefix, upper), port.to_string());
             env.insert(
-                format!("DEVRIG_{}_URL", upper),
-                format!("http://localhost:{}", port),
+                format!("{}{}_URL", prefix, upper),
+                service_url(svc_config.protocol.as_deref(), port),
             );
         }
     }
@@ -78,17 +82,23 @@ pub fn build_service_env(
     // 5. Inject OTel env vars when dashboard is enabled (use resolved ports)
     if let Some(ref dash) = config.dashboard {
         if dash.enabled.unwrap_or(true) {
+            let tls = dash.tls.as_ref().filter(|tls| tls.files().is_some());
+            let scheme = |on: bool| if on { "https" } else { "http" };
             if let Some(&http_port) = resolved_ports.get("otel-http") {
                 env.insert(
                     "OTEL_EXPORTER_OTLP_ENDPOINT".to_string(),
-                    format!("http://localhost:{}", http_port),
+                    format!(
+                        "{}://localhost:{}",
+                        scheme(tls.is_some_and(|tls| tls.otel)),
+                        http_port
+                    ),
                 );
             }
             env.insert("OTEL_SERVICE_NAME".to_string(), service_name.to_string());
             if let Some(&dash_port) = resolved_ports.get("dashboard") {
                 env.insert(
-                    "DEVRIG_DASHBOARD_URL".to_string(),
-                    format!("http://localhost:{}", dash_port),
+                    format!("{}DASHBOARD_URL", prefix),
+                    format!("{}://localhost:{}", scheme(tls.is_some()), dash_port),
                 );
             }
         }
@@ -111,9 +121,13 @@ mod tests {
 
     fn minimal_config() -> DevrigConfig {
         DevrigConfig {
+            disabled: Default::default(),
             project: ProjectConfig {
                 name: "test".to_string(),
                 env_file: None,
+                shell_env_passthrough: Default::default(),
+                vars: BTreeMap::new(),
+                env_prefix: None,
             },
             services: BTreeMap::new(),
             docker: BTreeMap::new(),
@@ -123,11 +137,14 @@ mod tests {
             env: BTreeMap::new(),
             network: None,
             links: BTreeMap::new(),
+            profiles: BTreeMap::new(),
+            secrets: BTreeMap::new(),
         }
     }
 
     fn make_infra(image: &str, env: Vec<(&str, &str)>) -> DockerConfig {
         DockerConfig {
+            enabled: Default::default(),
             image: image.to_string(),
             port: None,
             container_port: None,
@@ -142,21 +159,38 @@ mod tests {
             entrypoint: None,
             ready_check: None,
             init: Vec::new(),
-            depends_on: Vec::new(),
+            depends_on: Default::default(),
             registry_auth: None,
+            pull_retries: None,
+            restart: None,
+            stop_timeout: None,
+            healthcheck: None,
+            mem_limit: None,
+            cpus: None,
+            network_aliases: Vec::new(),
+            extra_hosts: Vec::new(),
         }
     }
 
     fn make_service(command: &str, port: Option<u16>) -> ServiceConfig {
         ServiceConfig {
+            enabled: Default::default(),
             path: None,
             command: command.to_string(),
             port: port.map(Port::Fixed),
             protocol: None,
             env: BTreeMap::new(),
             env_file: None,
-            depends_on: Vec::new(),
+            depends_on: Default::default(),
+            ready_check: None,
             restart: None,
+            shell: None,
+            reload_strategy: None,
+            reload_signal: None,
+            max_log_lines_per_sec: None,
+            pre_start: None,
+            post_stop: None,
+            log_format: None,
         }
     }
 
@@ -190,8 +224,10 @@ mod tests {
     fn named_port_vars() {
         let mut config = minimal_config();
         let mut mailpit = make_infra("axllent/mailpit:latest", vec![]);
-        mailpit.ports.insert("smtp".into(), Port::Fixed(1025));
-        mailpit.ports.insert("ui".into(), Port::Fixed(8025));
+        mailpit
+            .ports
+            .insert("smtp".into(), Port::Fixed(1025).into());
+        mailpit.ports.insert("ui".into(), Port::Fixed(8025).into());
         config.docker.insert("mailpit".into(), mailpit);
         config
             .services
@@ -271,4 +307,77 @@ mod tests {
         assert_eq!(env2["DEVRIG_WEB_URL"], "http://localhost:4000");
         assert!(!env2.contains_key("DEVRIG_API_HOST"));
     }
+
+    #[test]
+    fn url_scheme_follows_protocol() {
+        let mut config = minimal_config();
+        for (name, protocol, port) in [
+            ("web", None, 3000),
+            ("secure", Some("https"), 3443),
+            ("rpc", Some("grpc"), 50051),
+        ] {
+            let mut svc = make_service("run", Some(port));
+            svc.protocol = protocol.map(str::to_string);
+            config.services.insert(name.into(), svc);
+        }
+        let mut tls_proxy = make_infra("caddy:2", vec![]);
+        tls_proxy.protocol = Some("https".to_string());
+        config.docker.insert("proxy".into(), tls_proxy);
+        config
+            .services
+            .insert("client".into(), make_service("run", None));
+
+        let ports = HashMap::from([
+            ("service:web".to_string(), 3000u16),
+            ("service:secure".to_string(), 3443u16),
+            ("service:rpc".to_string(), 50051u16),
+            ("docker:proxy".to_string(), 8443u16),
+        ]);
+
+        let env = build_service_env("client", &config, &ports);
+        assert_eq!(env["DEVRIG_WEB_URL"], "http://localhost:3000");
+        assert_eq!(env["DEVRIG_SECURE_URL"], "https://localhost:3443");
+        assert_eq!(env["DEVRIG_RPC_URL"], "grpc://localhost:50051");
+        assert_eq!(env["DEVRIG_PROXY_URL"], "https://localhost:8443");
+    }
+
+    #[test]
+    fn custom_env_prefix_applies_to_all_discovery_vars() {
+        let mut config = minimal_config();
+        config.project.env_prefix = Some("APP_".to_string());
+        config.dashboard = Some(toml::from_str("").unwrap());
+        let mut mailpit = make_infra("axllent/mailpit", vec![]);
+        mailpit.port = Some(Port::Fixed(1025));
+        mailpit.ports.insert("ui".into(), Port::Fixed(8025).into());
+        config.docker.insert("mailpit".into(), mailpit);
+        config
+            .services
+            .insert("api".into(), make_service("cargo run", Some(3000)));
+        config
+            .services
+            .insert("web".into(), make_service("npm run dev", Some(4000)));
+
+        let ports = HashMap::from([
+            ("docker:mailpit".to_string(), 1025u16),
+            ("docker:mailpit:ui".to_string(), 8025u16),
+            ("service:api".to_string(), 3000u16),
+            ("service:web".to_string(), 4000u16),
+            ("otel-http".to_string(), 4318u16),
+            ("dashboard".to_string(), 4000u16),
+        ]);
+
+        let env = build_service_env("web", &config, &ports);
+        assert_eq!(env["APP_MAILPIT_HOST"], "localhost");
+        assert_eq!(env["APP_MAILPIT_PORT"], "1025");
+        assert_eq!(env["APP_MAILPIT_PORT_UI"], "8025");
+        assert_eq!(env["APP_API_URL"], "http://localhost:3000");
+        assert_eq!(env["APP_DASHBOARD_URL"], "http://localhost:4000");
+        assert_eq!(env["OTEL_EXPORTER_OTLP_ENDPOINT"], "http://localhost:4318");
+        assert_eq!(env["OTEL_SERVICE_NAME"], "web");
+        assert!(
+            env.keys().all(|k| !k.starts_with("DEVRIG_")),
+            "unexpected default-prefixed vars: {:?}",
+            env.keys().collect::<Vec<_>>()
+        );
+    }
 }
@@ -1,11 +1,17 @@
 use crate::config::model::DockerConfig;
 
+/// `<protocol>://localhost:<port>`, using `http` when no protocol is set.
+pub fn service_url(protocol: Option<&str>, port: impl std::fmt::Display) -> String {
+    format!("{}://localhost:{}", protocol.unwrap_or("http"), port)
+}
+
 /// Generate a connection URL for an infrastructure service based on its image type
 /// and resolved port.
 ///
 /// Rules:
 /// - postgres:// for Postgres images (with optional user:pass from env)
 /// - redis:// for Redis images
+/// - The configured `protocol` as the scheme, when set
 /// - No protocol (just localhost:port) when the docker service has named ports
 /// - http:// as the default fallback
 pub fn generate_url(name: &str, docker_config: &DockerConfig, port: u16) -> String {
@@ -28,11 +34,15 @@ pub fn generate_url(name: &str, docker_config: &DockerConfig, port: u16) -> Stri
         return format!("redis://localhost:{}", port);
     }
 
+    if let Some(protocol) = &docker_config.protocol {
+        return service_url(Some(protocol), port);
+    }
+
     if !docker_config.ports.is_empty() {
         return format!("localhost:{}", port);
     }
 
-    format!("http://localhost:{}", port)
+    service_url(None, port)
 }
 
 #[cfg(test)]
@@ -43,6 +53,7 @@ mod tests {
 
     fn base_infra(image: &str) -> DockerConfig {
         DockerConfig {
+            enabled: Default::default(),
             image: image.to_string(),
             port: None,
             container_port: None,
@@ -54,8 +65,16 @@ mod tests {
             entrypoint: None,
             ready_check: None,
             init: Vec::new(),
-            depends_on: Vec::new(),
+            depends_on: Default::default(),
             registry_auth: None,
+            pull_retries: None,
+            restart: None,
+            stop_timeout: None,
+            healthcheck: None,
+            mem_limit: None,
+            cpus: None,
+            network_aliases: Vec::new(),
+            extra_hosts: Vec::new(),
         }
     }
 
@@ -99,11 +118,23 @@ mod tests {
         assert_eq!(url, "http://localhost:9000");
     }
 
+    #[test]
+    fn protocol_sets_the_scheme() {
+        let mut cfg = base_infra("envoyproxy/envoy:v1.31");
+        cfg.protocol = Some("grpc".into());
+        assert_eq!(generate_url("envoy", &cfg, 9901), "grpc://localhost:9901");
+
+        // Image-specific schemes still win.
+        let mut cfg = base_infra("redis:7-alpine");
+        cfg.protocol = Some("tcp".into());
+        assert_eq!(generate_url("redis", &cfg, 6379), "redis://localhost:6379");
+    }
+
     #[test]
     fn multi_port_no_protocol() {
         let mut cfg = base_infra("axllent/mailpit:latest");
-        cfg.ports.insert("smtp".into(), Port::Fixed(1025));
-        cfg.ports.insert("ui".into(), Port::Fixed(8025));
+        cfg.ports.insert("smtp".into(), Port::Fixed(1025).into());
+        cfg.ports.insert("ui".into(), Port::Fixed(8025).into());
         let url = generate_url("mailpit", &cfg, 1025);
         assert_eq!(url, "localhost:1025");
     }
@@ -1,5 +1,7 @@
 use anyhow::{Context, Result};
-use bollard::models::{ContainerCreateBody, HostConfig, PortBinding};
+use bollard::models::{
+    ContainerCreateBody, EndpointSettings, HealthConfig, HostConfig, NetworkingConfig, PortBinding,
+};
 use bollard::query_parameters::{
     CreateContainerOptions, ListContainersOptions, RemoveContainerOptions, StartContainerOptions,
     StopContainerOptions,
@@ -7,21 +9,61 @@ use bollard::query_parameters::{
 use bollard::Docker;
 use std::collections::HashMap;
 
+use crate::config::model::{DockerHealthcheck, PortProtocol};
 use crate::docker::network::resource_labels;
+use crate::docker::ready::NativeHealth;
 
-/// Port mapping: (container_port, host_port).
+/// Port mapping: (container_port, host_port) over `protocol`.
 pub struct PortMap {
     pub container_port: u16,
     pub host_port: u16,
+    pub protocol: PortProtocol,
 }
 
-/// Options for overriding a container's command and entrypoint.
+/// Label recording a container's configured stop timeout, so label-based
+/// cleanup can honor it without the config.
+pub const STOP_TIMEOUT_LABEL: &str = "devrig.stop-timeout";
+
+/// Options for overriding a container's command, entrypoint, stop timeout,
+/// healthcheck, resource limits and host names.
 #[derive(Default)]
 pub struct ContainerCmdOptions {
     /// Override the container's CMD (command to run).
     pub cmd: Option<Vec<String>>,
     /// Override the container's ENTRYPOINT.
     pub entrypoint: Option<Vec<String>>,
+    /// Seconds Docker waits after SIGTERM before killing the container.
+    pub stop_timeout: Option<u64>,
+    /// Replaces the image's HEALTHCHECK.
+    pub healthcheck: Option<HealthConfig>,
+    /// Memory limit in bytes.
+    pub memory: Option<i64>,
+    /// CPU limit in units of 10^-9 CPUs.
+    pub nano_cpus: Option<i64>,
+    /// DNS aliases on the project network.
+    pub network_aliases: Vec<String>,
+    /// `/etc/hosts` entries, "HOST:IP".
+    pub extra_hosts: Vec<String>,
+}
+
+/// Translate a `healthcheck` table into Docker's create-time form. A single
+/// command string runs through the shell, a list is exec'd as is.
+pub fn health_config(healthcheck: &DockerHealthcheck) -> HealthConfig {
+    let secs_to_nanos = |secs: Option<u64>| secs.map(|s| s as i64 * 1_000_000_000);
+    let test = match healthcheck.test.as_slice() {
+        [command] => vec!["CMD-SHELL".to_string(), command.clone()],
+        args => std::iter::once("CMD".to_string())
+            .chain(args.iter().cloned())
+            .collect(),
+    };
+    HealthConfig {
+        test: Some(test),
+        interval: secs_to_nanos(healthcheck.interval),
+        timeout: secs_to_nanos(healthcheck.timeout),
+        retries: healthcheck.retries.map(i64::from),
+        start_period: secs_to_nanos(healthcheck.start_period),
+        ..Default::default()
+    }
 }
 
 /// Create a Docker container with the specified configuration.
@@ -33,12 +75,15 @@ pub async fn create_container(
     image: &str,
     env_vars: &[(String, String)],
     port_maps: &[PortMap],
-    volumes: &[(String, String)],
+    binds: &[String],
     network_name: &str,
     cmd_options: &ContainerCmdOptions,
 ) -> Result<String> {
-    let container_name = format!("devrig-{}-{}", slug, service_name);
-    let labels = resource_labels(slug, service_name);
+    let container_name = crate::identity::container_name(slug, service_name);
+    let mut labels = resource_labels(slug, service_name);
+    if let Some(timeout) = cmd_options.stop_timeout {
+        labels.insert(STOP_TIMEOUT_LABEL.to_string(), timeout.to_string());
+    }
 
     let env: Vec<String> = env_vars
         .iter()
@@ -48,7 +93,7 @@ pub async fn create_container(
     let mut port_bindings: HashMap<String, Option<Vec<PortBinding>>> = HashMap::new();
     let mut exposed_ports: Vec<String> = Vec::new();
     for pm in port_maps {
-        let container_port_key = format!("{}/tcp", pm.container_port);
+        let container_port_key = format!("{}/{}", pm.container_port, pm.protocol);
         port_bindings.insert(
             container_port_key.clone(),
             Some(vec![PortBinding {
@@ -59,18 +104,28 @@ pub async fn create_container(
         exposed_ports.push(container_port_key);
     }
 
-    let binds: Vec<String> = volumes
-        .iter()
-        .map(|(vol, path)| format!("{}:{}", vol, path))
-        .collect();
-
     let host_config = HostConfig {
         port_bindings: Some(port_bindings),
-        binds: Some(binds),
+        binds: Some(binds.to_vec()),
         network_mode: Some(network_name.to_string()),
+        memory: cmd_options.memory,
+        nano_cpus: cmd_options.nano_cpus,
+        extra_hosts: (!cmd_options.extra_hosts.is_empty()).then(|| cmd_options.extra_hosts.clone()),
         ..Default::default()
     };
 
+    // Aliases are set on the endpoint for the network the container joins
+    // at creation, so they resolve from its first start.
+    let networking_config = (!cmd_options.network_aliases.is_empty()).then(|| NetworkingConfig {
+        endpoints_config: Some(HashMap::from([(
+            network_name.to_string(),
+            EndpointSettings {
+                aliases: Some(cmd_options.network_aliases.clone()),
+                ..Default::default()
+            },
+        )])),
+    });
+
     let config = ContainerCreateBody {
         image: Some(image.to_string()),
         env: Some(env),
@@ -79,6 +134,9 @@ pub async fn create_container(
         labels: Some(labels),
         cmd: cmd_options.cmd.clone(),
         entrypoint: cmd_options.entrypoint.clone(),
+        stop_timeout: cmd_options.stop_timeout.map(|t| t as i64),
+        healthcheck: cmd_options.healthcheck.clone(),
+        networking_config,
         ..Default::default()
     };
 
@@ -165,3 +223,78 @@ pub async fn list_project_containers(
         .await
         .context("listing project containers")
 }
+
+/// Observed state of a container, as seen by the restart watcher.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContainerStatus {
+    /// Running, and healthy or without a HEALTHCHECK.
+    Running,
+    /// Running, but its HEALTHCHECK reports unhealthy.
+    Unhealthy,
+    /// No longer running; carries the exit code.
+    Exited(i64),
+    /// The container no longer exists.
+    Missing,
+}
+
+/// Inspect a container and classify its current state, along with its
+/// HEALTHCHECK status.
+pub async fn container_status_and_health(
+    docker: &Docker,
+    container_id: &str,
+) -> Result<(ContainerStatus, NativeHealth)> {
+    use bollard::models::HealthStatusEnum;
+
+    let info = match docker.inspect_container(container_id, None).await {
+        Ok(info) => info,
+        Err(bollard::errors::Error::DockerResponseServerError {
+            status_code: 404, ..
+        }) => return Ok((ContainerStatus::Missing, NativeHealth::None)),
+        Err(e) => return Err(e).context("inspecting container"),
+    };
+    let state = info.state.unwrap_or_default();
+    let health = match state.health.as_ref().and_then(|h| h.status) {
+        Some(HealthStatusEnum::STARTING) => NativeHealth::Starting,
+        Some(HealthStatusEnum::HEALTHY) => NativeHealth::Healthy,
+        Some(HealthStatusEnum::UNHEALTHY) => NativeHealth::Unhealthy,
+        _ => NativeHealth::None,
+    };
+    if !state.running.unwrap_or(false) {
+        return Ok((
+            ContainerStatus::Exited(state.exit_code.unwrap_or(0)),
+            health,
+        ));
+    }
+    let status = if health == NativeHealth::Unhealthy {
+        ContainerStatus::Unhealthy
+    } else {
+        ContainerStatus::Running
+    };
+    Ok((status, health))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn healthcheck_translates_to_docker_form() {
+        let shell: DockerHealthcheck =
+            toml::from_str("test = \"pg_isready -U postgres\"\ninterval = 5\nretries = 3").unwrap();
+        let config = health_config(&shell);
+        assert_eq!(
+            config.test.unwrap(),
+            vec!["CMD-SHELL", "pg_isready -U postgres"]
+        );
+        assert_eq!(config.interval, Some(5_000_000_000));
+        assert_eq!(config.retries, Some(3));
+        assert_eq!(config.timeout, None);
+
+        let exec: DockerHealthcheck =
+            toml::from_str(r#"test = ["curl", "-f", "http://localhost/health"]"#).unwrap();
+        assert_eq!(
+            health_config(&exec).test.unwrap(),
+            vec!["CMD", "curl", "-f", "http://localhost/health"]
+        );
+    }
+}
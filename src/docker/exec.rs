@@ -3,8 +3,12 @@ use bollard::exec::{StartExecOptions, StartExecResults};
 use bollard::models::ExecConfig;
 use bollard::Docker;
 use futures_util::StreamExt;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
-use crate::config::model::DockerConfig;
+use crate::config::interpolate::resolve_template;
+use crate::config::model::{DockerConfig, InitScript};
 
 /// Execute a command in a container and return (exit_code, combined_output).
 pub async fn exec_in_container(
@@ -53,19 +57,103 @@ pub async fn exec_in_container(
     Ok((exit_code, output))
 }
 
-/// Run init scripts for a docker service.
+/// An init script read from its source, ready to run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadedInitScript {
+    /// Where the script came from, for logs and errors: `init[0]` for an
+    /// inline entry, the path for a file.
+    pub source: String,
+    pub text: String,
+}
+
+/// Read `init` entries in order. A `dir` entry expands to its files sorted
+/// by name, skipping dotfiles and subdirectories. Paths are relative to
+/// `config_dir`.
+pub fn load_init_scripts(init: &[InitScript], config_dir: &Path) -> Result<Vec<LoadedInitScript>> {
+    let read = |path: &Path| -> Result<LoadedInitScript> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("reading init script {}", path.display()))?;
+        Ok(LoadedInitScript {
+            source: path.display().to_string(),
+            text,
+        })
+    };
+
+    let mut scripts = Vec::new();
+    for (i, entry) in init.iter().enumerate() {
+        match entry {
+            InitScript::Inline(text) => scripts.push(LoadedInitScript {
+                source: format!("init[{}]", i),
+                text: text.clone(),
+            }),
+            InitScript::File(file) => scripts.push(read(&config_dir.join(file))?),
+            InitScript::Dir(dir) => {
+                let dir = config_dir.join(dir);
+                let mut files: Vec<PathBuf> = std::fs::read_dir(&dir)
+                    .with_context(|| format!("reading init directory {}", dir.display()))?
+                    .filter_map(|entry| entry.ok().map(|e| e.path()))
+                    .filter(|path| {
+                        path.is_file()
+                            && !path
+                                .file_name()
+                                .is_some_and(|n| n.to_string_lossy().starts_with('.'))
+                    })
+                    .collect();
+                files.sort();
+                for file in files {
+                    scripts.push(read(&file)?);
+                }
+            }
+        }
+    }
+    Ok(scripts)
+}
+
+/// SHA-256 over the scripts as written (before `{{ }}` resolution), stored
+/// in `DockerState` so editing, adding or removing a script re-runs init.
+pub fn init_checksum(scripts: &[LoadedInitScript]) -> String {
+    let mut hasher = Sha256::new();
+    for script in scripts {
+        hasher.update(script.text.as_bytes());
+        hasher.update([0]);
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// Run init scripts for a docker service, resolving `{{ }}` templates in
+/// each against `vars` first. Nothing runs if any template is unresolved.
 pub async fn run_init_scripts(
     docker: &Docker,
     container_id: &str,
     docker_name: &str,
     docker_config: &DockerConfig,
+    scripts: &[LoadedInitScript],
+    vars: &HashMap<String, String>,
 ) -> Result<()> {
-    for (i, script) in docker_config.init.iter().enumerate() {
+    let mut resolved = Vec::with_capacity(scripts.len());
+    let mut errors = Vec::new();
+    for script in scripts {
+        let field = format!("docker.{}.{}", docker_name, script.source);
+        match resolve_template(&script.text, vars, &field) {
+            Ok(text) => resolved.push(text),
+            Err(mut errs) => errors.append(&mut errs),
+        }
+    }
+    if !errors.is_empty() {
+        let mut msg = String::from("Template resolution errors:\n");
+        for err in &errors {
+            msg.push_str(&format!("  - {}\n", err));
+        }
+        bail!("{}", msg.trim_end());
+    }
+
+    for (i, (script, text)) in scripts.iter().zip(resolved).enumerate() {
         tracing::debug!(
             docker = %docker_name,
+            source = %script.source,
             "running init script {}/{}",
             i + 1,
-            docker_config.init.len()
+            scripts.len()
         );
 
         let cmd = if docker_config.image.starts_with("postgres") {
@@ -79,10 +167,10 @@ pub async fn run_init_scripts(
                 "-U".to_string(),
                 user.to_string(),
                 "-c".to_string(),
-                script.clone(),
+                text,
             ]
         } else {
-            vec!["sh".to_string(), "-c".to_string(), script.clone()]
+            vec!["sh".to_string(), "-c".to_string(), text]
         };
 
         let (exit_code, output) = exec_in_container(docker, container_id, cmd).await?;
@@ -93,9 +181,10 @@ pub async fn run_init_scripts(
 
         if exit_code != 0 {
             bail!(
-                "init script {}/{} for '{}' failed with exit code {} — output: {}",
+                "init script {}/{} ({}) for '{}' failed with exit code {} — output: {}",
                 i + 1,
-                docker_config.init.len(),
+                scripts.len(),
+                script.source,
                 docker_name,
                 exit_code,
                 output.trim()
@@ -133,3 +222,58 @@ pub async fn exec_ready_check(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn load_init_scripts_reads_files_and_sorted_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("seed.sql"), "INSERT INTO t VALUES (1);").unwrap();
+        let migrations = dir.path().join("migrations");
+        std::fs::create_dir(&migrations).unwrap();
+        std::fs::write(migrations.join("002_users.sql"), "CREATE TABLE users();").unwrap();
+        std::fs::write(migrations.join("001_init.sql"), "CREATE TABLE t();").unwrap();
+        std::fs::write(migrations.join(".DS_Store"), "junk").unwrap();
+        std::fs::create_dir(migrations.join("archive")).unwrap();
+
+        let init = vec![
+            InitScript::from("CREATE DATABASE app;"),
+            InitScript::Dir("migrations".to_string()),
+            InitScript::File("seed.sql".to_string()),
+        ];
+        let scripts = load_init_scripts(&init, dir.path()).unwrap();
+        let texts: Vec<&str> = scripts.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            vec![
+                "CREATE DATABASE app;",
+                "CREATE TABLE t();",
+                "CREATE TABLE users();",
+                "INSERT INTO t VALUES (1);",
+            ]
+        );
+        assert_eq!(scripts[0].source, "init[0]");
+        assert!(scripts[1].source.ends_with("001_init.sql"));
+
+        let missing = load_init_scripts(&[InitScript::File("nope.sql".to_string())], dir.path());
+        assert!(missing.unwrap_err().to_string().contains("nope.sql"));
+    }
+
+    #[test]
+    fn init_checksum_changes_with_script_contents() {
+        let script = |text: &str| LoadedInitScript {
+            source: "init[0]".to_string(),
+            text: text.to_string(),
+        };
+        let base = init_checksum(&[script("CREATE TABLE t();")]);
+        assert_eq!(base, init_checksum(&[script("CREATE TABLE t();")]));
+        assert_ne!(base, init_checksum(&[script("CREATE TABLE t(id int);")]));
+        // Splitting one script in two is a change too
+        assert_ne!(
+            init_checksum(&[script("ab")]),
+            init_checksum(&[script("a"), script("b")])
+        );
+    }
+}
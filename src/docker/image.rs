@@ -4,14 +4,36 @@ use bollard::models::CreateImageInfo;
 use bollard::query_parameters::CreateImageOptions;
 use bollard::Docker;
 use futures_util::StreamExt;
+use std::time::Duration;
 
 use crate::config::model::RegistryAuth;
 
+/// Split a pinned digest off an image reference.
+/// "postgres:16@sha256:abc" -> ("postgres:16", Some("sha256:abc"))
+/// "postgres:16" -> ("postgres:16", None)
+pub fn split_digest(image: &str) -> (&str, Option<&str>) {
+    match image.split_once('@') {
+        Some((reference, digest)) if !digest.is_empty() => (reference, Some(digest)),
+        _ => (image, None),
+    }
+}
+
 /// Parse an image reference into (name, tag).
 /// "postgres:16" -> ("postgres", "16")
 /// "redis" -> ("redis", "latest")
 /// "axllent/mailpit:latest" -> ("axllent/mailpit", "latest")
+/// "postgres:16@sha256:abc" -> ("postgres", "sha256:abc")
+///
+/// When a digest is pinned it takes the place of the tag, since the Docker
+/// API accepts a digest in the tag position when pulling.
 pub fn parse_image_ref(image: &str) -> (&str, &str) {
+    if let (reference, Some(digest)) = split_digest(image) {
+        let name = match reference.rsplit_once(':') {
+            Some((name, tag)) if !name.is_empty() && !tag.contains('/') => name,
+            _ => reference,
+        };
+        return (name, digest);
+    }
     match image.rsplit_once(':') {
         Some((name, tag)) if !name.is_empty() && !tag.is_empty() => (name, tag),
         _ => (image, "latest"),
@@ -23,56 +45,197 @@ pub async fn check_image_exists(docker: &Docker, image: &str) -> bool {
     docker.inspect_image(image).await.is_ok()
 }
 
-/// Pull a single Docker image with progress logging.
-pub async fn pull_image(docker: &Docker, image: &str) -> Result<()> {
-    let (name, tag) = parse_image_ref(image);
-    tracing::debug!(image = %image, "pulling image");
+/// Look up the repo digests of the image a container was created from.
+pub async fn container_repo_digests(docker: &Docker, container_id: &str) -> Result<Vec<String>> {
+    let info = docker
+        .inspect_container(container_id, None)
+        .await
+        .context("inspecting container")?;
+    let Some(image_id) = info.image else {
+        return Ok(Vec::new());
+    };
+    let image = docker
+        .inspect_image(&image_id)
+        .await
+        .context("inspecting container image")?;
+    Ok(image.repo_digests.unwrap_or_default())
+}
+
+/// Extract the digest (e.g. "sha256:...") from the first repo digest entry.
+/// Locally built images have no repo digests and resolve to `None`.
+pub fn resolved_digest(repo_digests: &[String]) -> Option<String> {
+    repo_digests
+        .iter()
+        .find_map(|d| d.split_once('@').map(|(_, digest)| digest.to_string()))
+}
 
+/// Verify that a container's image matches a pinned digest.
+pub fn verify_digest(name: &str, repo_digests: &[String], pinned: &str) -> Result<()> {
+    let matches = repo_digests
+        .iter()
+        .filter_map(|d| d.split_once('@').map(|(_, digest)| digest))
+        .any(|digest| digest.eq_ignore_ascii_case(pinned));
+    if !matches {
+        let actual = resolved_digest(repo_digests).unwrap_or_else(|| "<none>".to_string());
+        bail!(
+            "image digest mismatch for docker '{}': pinned {} but container is running {}",
+            name,
+            pinned,
+            actual
+        );
+    }
+    Ok(())
+}
+
+/// Retries for a failed pull when `pull_retries` is unset.
+pub const DEFAULT_PULL_RETRIES: u32 = 3;
+
+/// Delay before retry number `retry` (1-based): 1s, 2s, 4s, ... up to 30s.
+fn pull_backoff(retry: u32) -> Duration {
+    Duration::from_secs(1 << retry.saturating_sub(1).min(5)).min(Duration::from_secs(30))
+}
+
+/// Why a pull attempt failed, which decides whether another attempt helps.
+#[derive(Debug, PartialEq)]
+enum PullFailure {
+    /// The registry rejected the credentials.
+    Unauthorized,
+    /// The image or tag does not exist.
+    NotFound,
+    /// Network errors, timeouts and registry hiccups.
+    Transient,
+}
+
+fn classify_pull_error(message: &str) -> PullFailure {
+    let message = message.to_ascii_lowercase();
+    if message.contains("unauthorized")
+        || message.contains("authentication required")
+        || message.contains("401")
+    {
+        PullFailure::Unauthorized
+    } else if message.contains("manifest unknown")
+        || message.contains("not found")
+        || message.contains("does not exist")
+        || message.contains("404")
+    {
+        PullFailure::NotFound
+    } else {
+        PullFailure::Transient
+    }
+}
+
+/// Credentials for a pull. A `password_command` is run each time, so a
+/// short-lived registry token is fetched fresh.
+async fn registry_credentials(auth: &RegistryAuth) -> Result<DockerCredentials> {
+    let password = match &auth.password_command {
+        Some(command) => {
+            let output = tokio::process::Command::from(crate::platform::script_command(command))
+                .output()
+                .await
+                .with_context(|| format!("running registry_auth password_command '{}'", command))?;
+            if !output.status.success() {
+                bail!(
+                    "registry_auth password_command '{}' failed: {}",
+                    command,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+            }
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        }
+        None => auth.password.clone(),
+    };
+    Ok(DockerCredentials {
+        username: Some(auth.username.clone()),
+        password: Some(password),
+        ..Default::default()
+    })
+}
+
+/// Pull an image once, logging progress at debug.
+async fn pull_once(
+    docker: &Docker,
+    image: &str,
+    credentials: Option<DockerCredentials>,
+) -> Result<()> {
+    let (name, tag) = parse_image_ref(image);
     let options = CreateImageOptions {
         from_image: Some(name.to_string()),
         tag: Some(tag.to_string()),
         ..Default::default()
     };
 
-    let mut stream = docker.create_image(Some(options), None, None);
+    let mut stream = docker.create_image(Some(options), None, credentials);
     while let Some(result) = stream.next().await {
         let info: CreateImageInfo = result.context("pulling image")?;
         if let Some(err) = &info.error_detail {
             bail!("image pull failed for {}: {:?}", image, err);
         }
+        if let Some(status) = &info.status {
+            let progress = info.progress_detail.as_ref();
+            tracing::debug!(
+                image = %image,
+                layer = info.id.as_deref().unwrap_or(""),
+                current = progress.and_then(|p| p.current),
+                total = progress.and_then(|p| p.total),
+                "{}",
+                status
+            );
+        }
     }
-
-    tracing::debug!(image = %image, "image pulled successfully");
     Ok(())
 }
 
-/// Pull a single Docker image with optional registry authentication.
+/// Pull a single Docker image with progress logging.
+pub async fn pull_image(docker: &Docker, image: &str) -> Result<()> {
+    pull_image_with_auth(docker, image, None, DEFAULT_PULL_RETRIES).await
+}
+
+/// Pull a single Docker image with optional registry authentication,
+/// retrying up to `retries` times with exponential backoff. When the
+/// registry rejects credentials that come from a `password_command`, the
+/// command is run again before the next attempt; a missing image or a
+/// rejected static password fails straight away.
 pub async fn pull_image_with_auth(
     docker: &Docker,
     image: &str,
     auth: Option<&RegistryAuth>,
+    retries: u32,
 ) -> Result<()> {
-    let (name, tag) = parse_image_ref(image);
     tracing::debug!(image = %image, "pulling image");
 
-    let options = CreateImageOptions {
-        from_image: Some(name.to_string()),
-        tag: Some(tag.to_string()),
-        ..Default::default()
+    let mut credentials = match auth {
+        Some(auth) => Some(registry_credentials(auth).await?),
+        None => None,
     };
-
-    let credentials = auth.map(|a| DockerCredentials {
-        username: Some(a.username.clone()),
-        password: Some(a.password.clone()),
-        ..Default::default()
-    });
-
-    let mut stream = docker.create_image(Some(options), None, credentials);
-    while let Some(result) = stream.next().await {
-        let info: CreateImageInfo = result.context("pulling image")?;
-        if let Some(err) = &info.error_detail {
-            bail!("image pull failed for {}: {:?}", image, err);
+    let mut retry = 0;
+    loop {
+        let err = match pull_once(docker, image, credentials.clone()).await {
+            Ok(()) => break,
+            Err(e) => e,
+        };
+        let failure = classify_pull_error(&format!("{:#}", err));
+        if retry >= retries || failure == PullFailure::NotFound {
+            return Err(err);
         }
+        if failure == PullFailure::Unauthorized {
+            match auth {
+                Some(auth) if auth.password_command.is_some() => {
+                    tracing::debug!(image = %image, "registry rejected credentials, refreshing");
+                    credentials = Some(registry_credentials(auth).await?);
+                }
+                _ => return Err(err),
+            }
+        }
+        retry += 1;
+        let delay = pull_backoff(retry);
+        tracing::debug!(
+            image = %image,
+            retry,
+            delay = ?delay,
+            error = %format!("{:#}", err),
+            "image pull failed, retrying"
+        );
+        tokio::time::sleep(delay).await;
     }
 
     tracing::debug!(image = %image, "image pulled successfully");
@@ -124,6 +287,43 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_image_with_digest() {
+        let digest = "sha256:4c8a0e3bbd2f6e1d0a0b6f6c6d1e2f3a4b5c6d7e8f90123456789abcdef0123";
+        let image = format!("postgres:16@{}", digest);
+        assert_eq!(parse_image_ref(&image), ("postgres", digest));
+        assert_eq!(split_digest(&image), ("postgres:16", Some(digest)));
+
+        let untagged = format!("ghcr.io/org/app@{}", digest);
+        assert_eq!(parse_image_ref(&untagged), ("ghcr.io/org/app", digest));
+    }
+
+    #[test]
+    fn split_digest_without_pin() {
+        assert_eq!(split_digest("postgres:16"), ("postgres:16", None));
+    }
+
+    #[test]
+    fn verify_digest_pinned() {
+        let repo_digests = vec![
+            "postgres@sha256:aaaa".to_string(),
+            "docker.io/library/postgres@sha256:bbbb".to_string(),
+        ];
+        assert!(verify_digest("postgres", &repo_digests, "sha256:bbbb").is_ok());
+        let err = verify_digest("postgres", &repo_digests, "sha256:cccc").unwrap_err();
+        assert!(err.to_string().contains("pinned sha256:cccc"));
+        assert!(verify_digest("postgres", &[], "sha256:aaaa").is_err());
+    }
+
+    #[test]
+    fn resolved_digest_from_repo_digests() {
+        assert_eq!(
+            resolved_digest(&["redis@sha256:abcd".to_string()]),
+            Some("sha256:abcd".to_string())
+        );
+        assert_eq!(resolved_digest(&[]), None);
+    }
+
     #[test]
     fn parse_image_alpine() {
         assert_eq!(
@@ -131,4 +331,49 @@ mod tests {
             ("postgres", "16-alpine")
         );
     }
+
+    #[test]
+    fn pull_backoff_doubles_up_to_a_cap() {
+        assert_eq!(pull_backoff(1), Duration::from_secs(1));
+        assert_eq!(pull_backoff(2), Duration::from_secs(2));
+        assert_eq!(pull_backoff(4), Duration::from_secs(8));
+        assert_eq!(pull_backoff(10), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn classify_pull_errors() {
+        assert_eq!(
+            classify_pull_error(
+                "pulling image: Docker responded with status code 401: unauthorized"
+            ),
+            PullFailure::Unauthorized
+        );
+        assert_eq!(
+            classify_pull_error("image pull failed for ghcr.io/org/app:1: manifest unknown"),
+            PullFailure::NotFound
+        );
+        assert_eq!(
+            classify_pull_error("pulling image: error trying to connect: connection reset by peer"),
+            PullFailure::Transient
+        );
+    }
+
+    #[tokio::test]
+    async fn password_command_supplies_a_fresh_password() {
+        let auth = RegistryAuth {
+            username: "ci".to_string(),
+            password: String::new(),
+            password_command: Some("echo token-123".to_string()),
+        };
+        let credentials = registry_credentials(&auth).await.unwrap();
+        assert_eq!(credentials.username.as_deref(), Some("ci"));
+        assert_eq!(credentials.password.as_deref(), Some("token-123"));
+
+        let failing = RegistryAuth {
+            password_command: Some("echo nope >&2; exit 1".to_string()),
+            ..auth
+        };
+        let err = registry_credentials(&failing).await.unwrap_err();
+        assert!(err.to_string().contains("nope"));
+    }
 }
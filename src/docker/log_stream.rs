@@ -10,7 +10,7 @@ use tokio_util::task::TaskTracker;
 use tracing::warn;
 
 use crate::otel::storage::TelemetryStore;
-use crate::otel::types::{LogSeverity, StoredLog, TelemetryEvent};
+use crate::otel::types::{extract_trace_context, LogSeverity, StoredLog, TelemetryEvent};
 use crate::ui::logs::detect_log_level;
 
 /// Spawn a background task that streams Docker container logs into the
@@ -64,6 +64,7 @@ pub fn spawn_docker_log_stream(
 
                             let level = detect_log_level(&text);
                             let severity = LogSeverity::from_log_level(level, is_stderr);
+                            let (trace_id, span_id) = extract_trace_context(&text);
 
                             let stored = StoredLog {
                                 record_id: 0,
@@ -71,15 +72,15 @@ pub fn spawn_docker_log_stream(
                                 service_name: svc.clone(),
                                 severity,
                                 body: text.clone(),
-                                trace_id: None,
-                                span_id: None,
+                                trace_id,
+                                span_id,
                                 attributes: vec![
                                     ("log.source".to_string(), "docker".to_string()),
                                 ],
                             };
 
                             let event = TelemetryEvent::LogRecord {
-                                trace_id: None,
+                                trace_id: stored.trace_id.clone(),
                                 severity: format!("{:?}", stored.severity),
                                 body: stored.body.clone(),
                                 service: stored.service_name.clone(),
@@ -6,20 +6,136 @@ pub mod network;
 pub mod ready;
 pub mod volume;
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use bollard::Docker;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
 
-use crate::config::model::{DockerConfig, Port};
+use crate::config::interpolate::{insert_docker_port_vars, resolve_command_templates};
+use crate::config::model::{DockerConfig, Port, StringOrList};
 use crate::docker::container::{ContainerCmdOptions, PortMap};
 use crate::docker::network::resource_labels;
-use crate::orchestrator::ports::resolve_port;
+use crate::orchestrator::ports::{resolve_port, AllocatedPorts, PortRangeExhausted};
 use crate::orchestrator::state::DockerState;
 
+/// Stop timeout used by `devrig stop` and shutdown when none is configured.
+pub const DEFAULT_STOP_TIMEOUT_SECS: u64 = 10;
+/// Stop timeout used by `devrig delete` cleanup when none is configured.
+pub const CLEANUP_STOP_TIMEOUT_SECS: u64 = 5;
+
+/// Convert a configured stop timeout (or `default`) to bollard's `i32` seconds.
+fn stop_timeout_secs(configured: Option<u64>, default: u64) -> i32 {
+    i32::try_from(configured.unwrap_or(default)).unwrap_or(i32::MAX)
+}
+
+/// Stop timeout recorded in a listed container's labels, or `default`.
+fn labelled_stop_timeout(c: &bollard::models::ContainerSummary, default: u64) -> i32 {
+    let configured = c
+        .labels
+        .as_ref()
+        .and_then(|l| l.get(container::STOP_TIMEOUT_LABEL))
+        .and_then(|t| t.parse().ok());
+    stop_timeout_secs(configured, default)
+}
+
+/// Host ports a docker resource publishes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedDockerPorts {
+    pub port: Option<u16>,
+    /// Whether `port` was auto-assigned (sticky across restarts).
+    pub port_auto: bool,
+    pub named_ports: BTreeMap<String, u16>,
+}
+
+/// Resolve the host ports for docker resource `name`, reusing auto-assigned
+/// ports from `prev_state` when they are still free.
+pub fn resolve_docker_ports(
+    name: &str,
+    config: &DockerConfig,
+    prev_state: Option<&DockerState>,
+    allocated_ports: &mut AllocatedPorts,
+) -> Result<ResolvedDockerPorts, PortRangeExhausted> {
+    let mut port: Option<u16> = None;
+    let mut port_auto = false;
+    let mut named_ports = BTreeMap::new();
+
+    if let Some(port_config) = &config.port {
+        let prev_port = prev_state.and_then(|s| s.port);
+        let prev_auto = prev_state.map(|s| s.port_auto).unwrap_or(false);
+        let resolved = resolve_port(
+            &format!("docker:{}", name),
+            port_config,
+            config.port_protocol(),
+            prev_port,
+            prev_auto,
+            allocated_ports,
+        )?;
+        port = Some(resolved);
+        port_auto = port_config.is_auto();
+    }
+
+    for (port_name, port_config) in &config.ports {
+        let prev_port = prev_state
+            .and_then(|s| s.named_ports.get(port_name))
+            .copied();
+        let prev_auto = port_config.is_auto();
+        let resolved = resolve_port(
+            &format!("docker:{}:{}", name, port_name),
+            &port_config.port,
+            port_config.protocol,
+            prev_port,
+            prev_auto,
+            allocated_ports,
+        )?;
+        named_ports.insert(port_name.clone(), resolved);
+    }
+
+    Ok(ResolvedDockerPorts {
+        port,
+        port_auto,
+        named_ports,
+    })
+}
+
+/// The `command` and `entrypoint` of docker `name` with templates resolved
+/// against `template_vars` plus the container's own `port` and `named_ports`.
+fn resolve_cmd_templates(
+    name: &str,
+    config: &DockerConfig,
+    port: Option<u16>,
+    named_ports: &BTreeMap<String, u16>,
+    template_vars: &HashMap<String, String>,
+) -> Result<(Option<StringOrList>, Option<StringOrList>)> {
+    let mut vars = template_vars.clone();
+    insert_docker_port_vars(&mut vars, name, port, named_ports);
+    let mut command = config.command.clone();
+    let mut entrypoint = config.entrypoint.clone();
+    let mut errors = Vec::new();
+    if let Some(command) = &mut command {
+        let field = format!("docker.{}.command", name);
+        resolve_command_templates(command, &vars, &field, &mut errors);
+    }
+    if let Some(entrypoint) = &mut entrypoint {
+        let field = format!("docker.{}.entrypoint", name);
+        resolve_command_templates(entrypoint, &vars, &field, &mut errors);
+    }
+    if !errors.is_empty() {
+        let mut msg = String::from("Template resolution errors:\n");
+        for err in &errors {
+            msg.push_str(&format!("  - {}\n", err));
+        }
+        bail!("{}", msg.trim_end());
+    }
+    Ok((command, entrypoint))
+}
+
 /// Manages Docker infrastructure containers for a devrig project.
+#[derive(Clone)]
 pub struct DockerManager {
     docker: Docker,
     slug: String,
+    /// Fail instead of pulling images that are not present locally.
+    no_pull: bool,
 }
 
 impl DockerManager {
@@ -31,7 +147,17 @@ impl DockerManager {
             .ping()
             .await
             .context("Cannot connect to Docker daemon. Is Docker running?")?;
-        Ok(Self { docker, slug })
+        Ok(Self {
+            docker,
+            slug,
+            no_pull: false,
+        })
+    }
+
+    /// Use only images already present locally (`--no-pull`).
+    pub fn with_no_pull(mut self, no_pull: bool) -> Self {
+        self.no_pull = no_pull;
+        self
     }
 
     /// Get a reference to the Docker client.
@@ -46,7 +172,7 @@ impl DockerManager {
 
     /// Get the project network name.
     pub fn network_name(&self) -> String {
-        format!("devrig-{}-net", self.slug)
+        crate::identity::network_name(&self.slug)
     }
 
     /// Ensure the project Docker network exists.
@@ -63,48 +189,37 @@ impl DockerManager {
         name: &str,
         config: &DockerConfig,
         prev_state: Option<&DockerState>,
-        allocated_ports: &mut HashSet<u16>,
+        allocated_ports: &mut AllocatedPorts,
         config_dir: &std::path::Path,
+        template_vars: &HashMap<String, String>,
     ) -> Result<DockerState> {
         // Pull image if needed (with optional registry auth)
         if !image::check_image_exists(&self.docker, &config.image).await {
-            image::pull_image_with_auth(&self.docker, &config.image, config.registry_auth.as_ref())
-                .await?;
+            if self.no_pull {
+                bail!(
+                    "image {} for docker '{}' is not present locally and --no-pull is set",
+                    config.image,
+                    name
+                );
+            }
+            image::pull_image_with_auth(
+                &self.docker,
+                &config.image,
+                config.registry_auth.as_ref(),
+                config.pull_retries.unwrap_or(image::DEFAULT_PULL_RETRIES),
+            )
+            .await?;
         }
 
-        // Resolve ports
-        let mut port: Option<u16> = None;
-        let mut port_auto = false;
-        let mut named_ports = std::collections::BTreeMap::new();
-
-        if let Some(port_config) = &config.port {
-            let prev_port = prev_state.and_then(|s| s.port);
-            let prev_auto = prev_state.map(|s| s.port_auto).unwrap_or(false);
-            let resolved = resolve_port(
-                &format!("docker:{}", name),
-                port_config,
-                prev_port,
-                prev_auto,
-                allocated_ports,
-            );
-            port = Some(resolved);
-            port_auto = port_config.is_auto();
-        }
+        let ResolvedDockerPorts {
+            port,
+            port_auto,
+            named_ports,
+        } = resolve_docker_ports(name, config, prev_state, allocated_ports)?;
 
-        for (port_name, port_config) in &config.ports {
-            let prev_port = prev_state
-                .and_then(|s| s.named_ports.get(port_name))
-                .copied();
-            let prev_auto = port_config.is_auto();
-            let resolved = resolve_port(
-                &format!("docker:{}:{}", name, port_name),
-                port_config,
-                prev_port,
-                prev_auto,
-                allocated_ports,
-            );
-            named_ports.insert(port_name.clone(), resolved);
-        }
+        // Resolve templates in command/entrypoint, including this container's ports
+        let (command, entrypoint) =
+            resolve_cmd_templates(name, config, port, &named_ports, template_vars)?;
 
         // Create volumes / resolve bind mounts
         let mut volume_binds = Vec::new();
@@ -113,17 +228,23 @@ impl DockerManager {
                 Some(volume::VolumeSpec::Named {
                     volume_name,
                     container_path,
+                    read_only,
                 }) => {
                     let labels = resource_labels(&self.slug, name);
                     volume::ensure_volume(&self.docker, &volume_name, labels).await?;
-                    volume_binds.push((volume_name, container_path));
+                    volume_binds.push(volume::bind_string(
+                        &volume_name,
+                        &container_path,
+                        read_only,
+                    ));
                 }
                 Some(volume::VolumeSpec::Bind {
                     host_path,
                     container_path,
+                    read_only,
                 }) => {
                     // Resolve relative paths to absolute (Docker requires absolute paths)
-                    let resolved = if host_path.starts_with('/') {
+                    let resolved = if Path::new(&host_path).is_absolute() {
                         host_path
                     } else {
                         config_dir
@@ -139,7 +260,7 @@ impl DockerManager {
                             .to_string_lossy()
                             .into_owned()
                     };
-                    volume_binds.push((resolved, container_path));
+                    volume_binds.push(volume::bind_string(&resolved, &container_path, read_only));
                 }
                 None => {}
             }
@@ -157,17 +278,16 @@ impl DockerManager {
             port_maps.push(PortMap {
                 container_port,
                 host_port,
+                protocol: config.port_protocol(),
             });
         }
         for (port_name, port_config) in &config.ports {
             if let Some(&host_port) = named_ports.get(port_name) {
-                let container_port = match port_config {
-                    Port::Fixed(p) => *p,
-                    Port::Auto => host_port,
-                };
+                let container_port = port_config.as_fixed().unwrap_or(host_port);
                 port_maps.push(PortMap {
                     container_port,
                     host_port,
+                    protocol: port_config.protocol,
                 });
             }
         }
@@ -183,12 +303,18 @@ impl DockerManager {
 
         // Build command/entrypoint overrides
         let cmd_options = ContainerCmdOptions {
-            cmd: config.command.as_ref().map(|s| s.as_slice().to_vec()),
-            entrypoint: config.entrypoint.as_ref().map(|s| s.as_slice().to_vec()),
+            cmd: command.map(StringOrList::into_vec),
+            entrypoint: entrypoint.map(StringOrList::into_vec),
+            stop_timeout: config.stop_timeout,
+            healthcheck: config.healthcheck.as_ref().map(container::health_config),
+            memory: config.memory_bytes(),
+            nano_cpus: config.nano_cpus(),
+            network_aliases: config.network_aliases.clone(),
+            extra_hosts: config.extra_hosts.clone(),
         };
 
         // Create and start container
-        let container_name = format!("devrig-{}-{}", self.slug, name);
+        let container_name = crate::identity::container_name(&self.slug, name);
         let container_id = container::create_container(
             &self.docker,
             &self.slug,
@@ -205,20 +331,54 @@ impl DockerManager {
         container::start_container(&self.docker, &container_id).await?;
         tracing::debug!(docker = %name, container = %container_name, "container started");
 
-        // Run ready check
+        // Verify a pinned digest, or record the resolved one
+        let repo_digests = image::container_repo_digests(&self.docker, &container_id).await?;
+        let image_digest = match image::split_digest(&config.image).1 {
+            Some(pinned) => {
+                if let Err(e) = image::verify_digest(name, &repo_digests, pinned) {
+                    let timeout = stop_timeout_secs(config.stop_timeout, DEFAULT_STOP_TIMEOUT_SECS);
+                    let _ = container::stop_container(&self.docker, &container_id, timeout).await;
+                    return Err(e);
+                }
+                Some(pinned.to_string())
+            }
+            None => image::resolved_digest(&repo_digests),
+        };
+
+        // Run ready check. A container that never became ready is stopped
+        // rather than left running behind the error.
         if let Some(check) = &config.ready_check {
             tracing::debug!(docker = %name, "waiting for ready check");
-            ready::run_ready_check(&self.docker, &container_id, check, port, name).await?;
+            if let Err(e) =
+                ready::run_ready_check(&self.docker, &container_id, check, port, name).await
+            {
+                let timeout = stop_timeout_secs(config.stop_timeout, DEFAULT_STOP_TIMEOUT_SECS);
+                let _ = container::stop_container(&self.docker, &container_id, timeout).await;
+                return Err(e);
+            }
             tracing::debug!(docker = %name, "ready");
         }
 
-        // Run init scripts (only if not already completed)
-        let already_init = prev_state.map(|s| s.init_completed).unwrap_or(false);
+        // Run init scripts, unless these exact scripts already ran. State
+        // from before checksums were recorded counts as a match.
+        let scripts = exec::load_init_scripts(&config.init, config_dir)?;
+        let checksum = (!scripts.is_empty()).then(|| exec::init_checksum(&scripts));
+        let already_init = prev_state.is_some_and(|s| {
+            s.init_completed && (s.init_checksum.is_none() || s.init_checksum == checksum)
+        });
         let mut init_completed = already_init;
         let mut init_completed_at = prev_state.and_then(|s| s.init_completed_at);
 
-        if !already_init && !config.init.is_empty() {
-            exec::run_init_scripts(&self.docker, &container_id, name, config).await?;
+        if !already_init && !scripts.is_empty() {
+            exec::run_init_scripts(
+                &self.docker,
+                &container_id,
+                name,
+                config,
+                &scripts,
+                template_vars,
+            )
+            .await?;
             init_completed = true;
             init_completed_at = Some(chrono::Utc::now());
             tracing::debug!(docker = %name, "init scripts completed");
@@ -233,31 +393,85 @@ impl DockerManager {
             named_ports,
             init_completed,
             init_completed_at,
+            init_checksum: checksum,
+            image_digest,
+            phase: None,
+            stop_timeout: config.stop_timeout,
+            health: None,
         })
     }
 
+    /// Wait for a started container's native HEALTHCHECK to report healthy.
+    /// Returns immediately for images without a healthcheck.
+    pub async fn wait_for_native_health(&self, name: &str, state: &DockerState) -> Result<()> {
+        ready::wait_for_native_health(
+            &self.docker,
+            &state.container_id,
+            name,
+            std::time::Duration::from_secs(60),
+        )
+        .await
+    }
+
     /// Stop a single docker service container.
     pub async fn stop_service(&self, state: &DockerState) -> Result<()> {
-        container::stop_container(&self.docker, &state.container_id, 10).await?;
+        let timeout = stop_timeout_secs(state.stop_timeout, DEFAULT_STOP_TIMEOUT_SECS);
+        container::stop_container(&self.docker, &state.container_id, timeout).await?;
         tracing::debug!(container = %state.container_name, "container stopped");
         Ok(())
     }
 
     /// Stop and remove a single docker service container.
     pub async fn delete_service(&self, state: &DockerState) -> Result<()> {
-        container::stop_container(&self.docker, &state.container_id, 10).await?;
+        let timeout = stop_timeout_secs(state.stop_timeout, DEFAULT_STOP_TIMEOUT_SECS);
+        container::stop_container(&self.docker, &state.container_id, timeout).await?;
         container::remove_container(&self.docker, &state.container_id, true).await?;
         tracing::debug!(container = %state.container_name, "container removed");
         Ok(())
     }
 
+    /// Remove the named volumes of docker service `name`, returning their
+    /// names. The container must already be removed.
+    pub async fn remove_service_volumes(&self, name: &str) -> Result<Vec<String>> {
+        volume::remove_resource_volumes(&self.docker, &self.slug, name).await
+    }
+
+    /// Stop every container labelled with this project's slug, keeping the
+    /// containers and their volumes. Used by `devrig stop` when state.json
+    /// can't be read, so containers aren't left running.
+    pub async fn stop_project_containers(&self) -> Result<()> {
+        let containers = container::list_project_containers(&self.docker, &self.slug).await?;
+        for c in &containers {
+            if let Some(id) = &c.id {
+                let timeout = labelled_stop_timeout(c, DEFAULT_STOP_TIMEOUT_SECS);
+                container::stop_container(&self.docker, id, timeout).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Stop and remove every container labelled with this project's slug,
+    /// keeping volumes. The label fallback of `devrig stop --remove-containers`.
+    pub async fn remove_project_containers(&self) -> Result<()> {
+        let containers = container::list_project_containers(&self.docker, &self.slug).await?;
+        for c in &containers {
+            if let Some(id) = &c.id {
+                let timeout = labelled_stop_timeout(c, DEFAULT_STOP_TIMEOUT_SECS);
+                container::stop_container(&self.docker, id, timeout).await?;
+                container::remove_container(&self.docker, id, true).await?;
+            }
+        }
+        Ok(())
+    }
+
     /// Remove all Docker resources (containers, volumes, networks) for this project.
     pub async fn cleanup_all(&self) -> Result<()> {
         // Remove containers by label
         let containers = container::list_project_containers(&self.docker, &self.slug).await?;
         for c in &containers {
             if let Some(id) = &c.id {
-                container::stop_container(&self.docker, id, 5).await?;
+                let timeout = labelled_stop_timeout(c, CLEANUP_STOP_TIMEOUT_SECS);
+                container::stop_container(&self.docker, id, timeout).await?;
                 container::remove_container(&self.docker, id, true).await?;
             }
         }
@@ -281,3 +495,72 @@ impl DockerManager {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn stop_uses_configured_timeout_or_ten_seconds() {
+        assert_eq!(stop_timeout_secs(Some(45), DEFAULT_STOP_TIMEOUT_SECS), 45);
+        assert_eq!(stop_timeout_secs(None, DEFAULT_STOP_TIMEOUT_SECS), 10);
+        assert_eq!(
+            stop_timeout_secs(Some(u64::MAX), DEFAULT_STOP_TIMEOUT_SECS),
+            i32::MAX
+        );
+    }
+
+    #[test]
+    fn command_templates_see_own_ports() {
+        let config: DockerConfig = toml::from_str(
+            r#"
+image = "mailpit"
+command = ["--smtp", "0.0.0.0:{{ docker.mail.ports.smtp }}", "--listen={{ docker.mail.port }}"]
+entrypoint = "{{ project.name }}-entry"
+"#,
+        )
+        .unwrap();
+        let vars = HashMap::from([("project.name".to_string(), "myapp".to_string())]);
+        let named = BTreeMap::from([("smtp".to_string(), 1025)]);
+        let (command, entrypoint) =
+            resolve_cmd_templates("mail", &config, Some(8025), &named, &vars).unwrap();
+        assert_eq!(
+            command.unwrap().as_slice(),
+            ["--smtp", "0.0.0.0:1025", "--listen=8025"]
+        );
+        assert_eq!(entrypoint.unwrap().as_slice(), ["myapp-entry"]);
+
+        let err = resolve_cmd_templates("mail", &config, None, &named, &vars).unwrap_err();
+        assert!(
+            err.to_string().contains("docker.mail.command[2]"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn cleanup_timeout_prefers_label_value() {
+        let listed = |label: Option<&str>| bollard::models::ContainerSummary {
+            labels: Some(
+                label
+                    .map(|t| (container::STOP_TIMEOUT_LABEL.to_string(), t.to_string()))
+                    .into_iter()
+                    .collect(),
+            ),
+            ..Default::default()
+        };
+        assert_eq!(
+            labelled_stop_timeout(&listed(Some("90")), CLEANUP_STOP_TIMEOUT_SECS),
+            90
+        );
+        assert_eq!(
+            labelled_stop_timeout(&listed(None), CLEANUP_STOP_TIMEOUT_SECS),
+            5
+        );
+        assert_eq!(
+            labelled_stop_timeout(&listed(Some("soon")), CLEANUP_STOP_TIMEOUT_SECS),
+            5
+        );
+    }
+}
@@ -2,15 +2,23 @@ use anyhow::{bail, Context, Result};
 use backon::{ExponentialBuilder, Retryable};
 use bollard::query_parameters::LogsOptions;
 use bollard::Docker;
+use futures_util::future::BoxFuture;
 use futures_util::StreamExt;
 use std::time::Duration;
 
 use crate::config::model::ReadyCheck;
 use crate::docker::exec::exec_in_container;
 
+/// How often a pending ready check reports how long it has been waiting.
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Container log lines included in the error when a ready check fails.
+const FAILURE_LOG_LINES: usize = 20;
+
 /// Run a ready check for a container, retrying with exponential backoff.
 ///
-/// Dispatches to the appropriate strategy based on the ReadyCheck variant.
+/// Reports progress every few seconds while waiting. On failure the last
+/// lines of the container's output are added to the error.
 pub async fn run_ready_check(
     docker: &Docker,
     container_id: &str,
@@ -19,11 +27,112 @@ pub async fn run_ready_check(
     docker_name: &str,
 ) -> Result<()> {
     let total_timeout = Duration::from_secs(check.timeout_secs());
+    let check_fut = check_ready(docker, container_id, check, host_port, docker_name);
+    let result = with_progress(check_fut, PROGRESS_INTERVAL, |waited| {
+        tracing::info!(
+            docker = %docker_name,
+            "waiting for {} ready check, {}s/{}s",
+            docker_name,
+            waited.as_secs(),
+            total_timeout.as_secs()
+        );
+    })
+    .await;
+    match result {
+        Ok(()) => Ok(()),
+        Err(e) => {
+            let lines = container_log_tail(docker, container_id, FAILURE_LOG_LINES).await;
+            Err(with_log_tail(e, docker_name, &lines))
+        }
+    }
+}
+
+/// Drive `fut` to completion, calling `report` with the time waited so far
+/// every `interval`.
+async fn with_progress<F, R>(fut: F, interval: Duration, mut report: R) -> F::Output
+where
+    F: std::future::Future,
+    R: FnMut(Duration),
+{
+    let started = tokio::time::Instant::now();
+    let mut ticker = tokio::time::interval_at(started + interval, interval);
+    tokio::pin!(fut);
+    loop {
+        tokio::select! {
+            output = &mut fut => return output,
+            _ = ticker.tick() => report(started.elapsed()),
+        }
+    }
+}
+
+/// The last `lines` lines of a container's stdout and stderr. Empty when
+/// the logs can't be read.
+async fn container_log_tail(docker: &Docker, container_id: &str, lines: usize) -> Vec<String> {
+    let options = LogsOptions {
+        follow: false,
+        stdout: true,
+        stderr: true,
+        tail: lines.to_string(),
+        ..Default::default()
+    };
+    let mut stream = docker.logs(container_id, Some(options));
+    let mut output = Vec::new();
+    while let Some(Ok(chunk)) = stream.next().await {
+        output.extend(chunk.to_string().lines().map(str::to_string));
+    }
+    let skip = output.len().saturating_sub(lines);
+    output.split_off(skip)
+}
+
+/// `err` followed by the container output in `lines`, indented, so the
+/// reason for a failed ready check is visible without `docker logs`.
+fn with_log_tail(err: anyhow::Error, docker_name: &str, lines: &[String]) -> anyhow::Error {
+    if lines.is_empty() {
+        return err;
+    }
+    let mut msg = format!(
+        "{:#}\nlast {} lines of '{}' output:",
+        err,
+        lines.len(),
+        docker_name
+    );
+    for line in lines {
+        msg.push_str("\n  ");
+        msg.push_str(line);
+    }
+    anyhow::anyhow!(msg)
+}
+
+/// Run a ready check without progress or diagnostics. Dispatches to the
+/// appropriate strategy based on the ReadyCheck variant.
+async fn check_ready(
+    docker: &Docker,
+    container_id: &str,
+    check: &ReadyCheck,
+    host_port: Option<u16>,
+    docker_name: &str,
+) -> Result<()> {
+    let total_timeout = Duration::from_secs(check.timeout_secs());
 
     match check {
         ReadyCheck::Log { pattern, .. } => {
             run_log_check(docker, container_id, pattern, total_timeout, docker_name).await
         }
+        ReadyCheck::AllOf { .. } | ReadyCheck::AnyOf { .. } => {
+            let result = tokio::time::timeout(
+                total_timeout,
+                run_composite_check(docker, container_id, check, host_port, docker_name),
+            )
+            .await;
+            match result {
+                Ok(inner) => inner,
+                Err(_) => bail!(
+                    "ready check for '{}' timed out after {:?}",
+                    docker_name,
+                    total_timeout
+                ),
+            }
+        }
         _ => {
             let docker = docker.clone();
             let container_id = container_id.to_string();
@@ -64,6 +173,97 @@ pub async fn run_ready_check(
     }
 }
 
+/// Native Docker health state of a container, as reported by `State.Health`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NativeHealth {
+    /// The image defines no HEALTHCHECK.
+    None,
+    Starting,
+    Healthy,
+    Unhealthy,
+}
+
+impl NativeHealth {
+    /// Docker's name for the status, or `None` without a HEALTHCHECK.
+    pub fn as_str(&self) -> Option<&'static str> {
+        match self {
+            NativeHealth::None => None,
+            NativeHealth::Starting => Some("starting"),
+            NativeHealth::Healthy => Some("healthy"),
+            NativeHealth::Unhealthy => Some("unhealthy"),
+        }
+    }
+}
+
+/// Inspect a container's native Docker health status.
+pub async fn container_health(docker: &Docker, container_id: &str) -> Result<NativeHealth> {
+    use bollard::models::HealthStatusEnum;
+
+    let info = docker
+        .inspect_container(container_id, None)
+        .await
+        .context("inspecting container health")?;
+    let status = info.state.and_then(|s| s.health).and_then(|h| h.status);
+    Ok(match status {
+        Some(HealthStatusEnum::STARTING) => NativeHealth::Starting,
+        Some(HealthStatusEnum::HEALTHY) => NativeHealth::Healthy,
+        Some(HealthStatusEnum::UNHEALTHY) => NativeHealth::Unhealthy,
+        _ => NativeHealth::None,
+    })
+}
+
+/// Wait until a container's native HEALTHCHECK reports healthy.
+///
+/// Used to gate dependents of containers that have no devrig `ready_check`.
+/// Returns immediately when the image defines no healthcheck.
+pub async fn wait_for_native_health(
+    docker: &Docker,
+    container_id: &str,
+    docker_name: &str,
+    timeout: Duration,
+) -> Result<()> {
+    poll_native_health(docker_name, timeout, Duration::from_millis(500), || {
+        container_health(docker, container_id)
+    })
+    .await
+}
+
+async fn poll_native_health<P, Fut>(
+    docker_name: &str,
+    timeout: Duration,
+    interval: Duration,
+    mut probe: P,
+) -> Result<()>
+where
+    P: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<NativeHealth>>,
+{
+    let result = tokio::time::timeout(timeout, async {
+        loop {
+            match probe().await? {
+                NativeHealth::None | NativeHealth::Healthy => return Ok(()),
+                NativeHealth::Unhealthy => {
+                    bail!("docker '{}' reported unhealthy", docker_name)
+                }
+                NativeHealth::Starting => {
+                    tracing::debug!(docker = %docker_name, "waiting for healthcheck");
+                    tokio::time::sleep(interval).await;
+                }
+            }
+        }
+    })
+    .await;
+
+    match result {
+        Ok(inner) => inner,
+        Err(_) => bail!(
+            "healthcheck for '{}' did not report healthy after {:?}",
+            docker_name,
+            timeout
+        ),
+    }
+}
+
 /// Run a single (non-retrying) check based on the strategy.
 async fn run_single_check(
     docker: &Docker,
@@ -91,32 +291,161 @@ async fn run_single_check(
             crate::docker::exec::exec_ready_check(docker, container_id, command, expect.as_deref())
                 .await
         }
-        ReadyCheck::Http { url, .. } => {
-            let client = reqwest::Client::builder()
-                .timeout(Duration::from_secs(2))
-                .build()
-                .context("building HTTP client")?;
-            let response = client.get(url).send().await.context("HTTP ready check")?;
-            if !response.status().is_success() {
-                bail!("HTTP ready check returned status {}", response.status());
-            }
-            Ok(())
-        }
-        ReadyCheck::Tcp { .. } => {
-            let port = host_port.context("TCP ready check requires a port")?;
-            tokio::time::timeout(
-                Duration::from_secs(2),
-                tokio::net::TcpStream::connect(format!("127.0.0.1:{}", port)),
-            )
-            .await
-            .context("TCP connect timed out")?
-            .context("TCP connect failed")?;
-            Ok(())
-        }
+        ReadyCheck::Http { url, .. } => probe_http(url).await,
+        ReadyCheck::Tcp { .. } => probe_tcp(host_port).await,
+        ReadyCheck::Grpc { service, .. } => probe_grpc(host_port, service.as_deref()).await,
         ReadyCheck::Log { .. } => {
             unreachable!("log check handled separately")
         }
+        ReadyCheck::AllOf { .. } | ReadyCheck::AnyOf { .. } => {
+            unreachable!("composite checks handled separately")
+        }
+    }
+}
+
+/// One HTTP probe: succeeds when `url` answers with a 2xx status.
+pub(crate) async fn probe_http(url: &str) -> Result<()> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(2))
+        .build()
+        .context("building HTTP client")?;
+    let response = client.get(url).send().await.context("HTTP ready check")?;
+    if !response.status().is_success() {
+        bail!("HTTP ready check returned status {}", response.status());
+    }
+    Ok(())
+}
+
+/// One TCP probe: succeeds when something accepts connections on `port`.
+pub(crate) async fn probe_tcp(port: Option<u16>) -> Result<()> {
+    let port = port.context("TCP ready check requires a port")?;
+    tokio::time::timeout(
+        Duration::from_secs(2),
+        tokio::net::TcpStream::connect(format!("127.0.0.1:{}", port)),
+    )
+    .await
+    .context("TCP connect timed out")?
+    .context("TCP connect failed")?;
+    Ok(())
+}
+
+/// Messages of the standard gRPC health checking protocol
+/// (`grpc/health/v1/health.proto`).
+mod health {
+    pub const CHECK_PATH: &str = "/grpc.health.v1.Health/Check";
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct HealthCheckRequest {
+        #[prost(string, tag = "1")]
+        pub service: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct HealthCheckResponse {
+        #[prost(int32, tag = "1")]
+        pub status: i32,
+    }
+
+    pub const SERVING: i32 = 1;
+
+    pub fn status_name(status: i32) -> &'static str {
+        match status {
+            1 => "SERVING",
+            2 => "NOT_SERVING",
+            3 => "SERVICE_UNKNOWN",
+            _ => "UNKNOWN",
+        }
+    }
+}
+
+/// One gRPC probe: succeeds when `grpc.health.v1.Health/Check` on `port`
+/// reports `SERVING` for `service` (the whole server when `None`).
+pub(crate) async fn probe_grpc(port: Option<u16>, service: Option<&str>) -> Result<()> {
+    use tonic::codec::ProstCodec;
+    use tonic::codegen::http::uri::PathAndQuery;
+    use tonic::transport::Endpoint;
+
+    let port = port.context("gRPC ready check requires a port")?;
+    let channel = Endpoint::from_shared(format!("http://127.0.0.1:{}", port))?
+        .connect_timeout(Duration::from_secs(2))
+        .timeout(Duration::from_secs(2))
+        .connect()
+        .await
+        .context("gRPC connect failed")?;
+    let mut client = tonic::client::Grpc::new(channel);
+    client.ready().await.context("gRPC channel not ready")?;
+
+    let request = health::HealthCheckRequest {
+        service: service.unwrap_or_default().to_string(),
+    };
+    let response = client
+        .unary(
+            tonic::Request::new(request),
+            PathAndQuery::from_static(health::CHECK_PATH),
+            ProstCodec::<health::HealthCheckRequest, health::HealthCheckResponse>::default(),
+        )
+        .await
+        .context("gRPC health check")?;
+    let status = response.into_inner().status;
+    if status != health::SERVING {
+        bail!("gRPC health check reported {}", health::status_name(status));
+    }
+    Ok(())
+}
+
+/// Run the children of an `all_of` / `any_of` check concurrently and combine
+/// their results.
+///
+/// Boxed so that [`check_ready`] can recurse through it.
+fn run_composite_check<'a>(
+    docker: &'a Docker,
+    container_id: &'a str,
+    check: &'a ReadyCheck,
+    host_port: Option<u16>,
+    docker_name: &'a str,
+) -> BoxFuture<'a, Result<()>> {
+    Box::pin(async move {
+        let (ReadyCheck::AllOf { checks, .. } | ReadyCheck::AnyOf { checks, .. }) = check else {
+            unreachable!("not a composite ready check")
+        };
+        let children: Vec<BoxFuture<'a, Result<()>>> = checks
+            .iter()
+            .map(|child| {
+                Box::pin(check_ready(
+                    docker,
+                    container_id,
+                    child,
+                    host_port,
+                    docker_name,
+                )) as BoxFuture<'a, Result<()>>
+            })
+            .collect();
+        match check {
+            ReadyCheck::AllOf { .. } => all_ready(children).await,
+            _ => any_ready(children).await,
+        }
+    })
+}
+
+/// Run child checks concurrently; succeeds once all pass, fails as soon as
+/// any one fails.
+async fn all_ready(children: Vec<BoxFuture<'_, Result<()>>>) -> Result<()> {
+    futures_util::future::try_join_all(children)
+        .await
+        .map(|_| ())
+        .context("all_of ready check failed")
+}
+
+/// Run child checks concurrently; succeeds as soon as any passes, fails only
+/// once every child has failed.
+async fn any_ready(children: Vec<BoxFuture<'_, Result<()>>>) -> Result<()> {
+    if children.is_empty() {
+        bail!("any_of ready check has no checks");
     }
+    futures_util::future::select_ok(children)
+        .await
+        .map(|_| ())
+        .context("no any_of ready check passed")
 }
 
 /// Run a log-based ready check by streaming container logs and scanning for
@@ -167,3 +496,237 @@ async fn run_log_check(
         ),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    fn pass() -> BoxFuture<'static, Result<()>> {
+        Box::pin(async { Ok(()) })
+    }
+
+    fn fail(msg: &'static str) -> BoxFuture<'static, Result<()>> {
+        Box::pin(async move { bail!(msg) })
+    }
+
+    fn never() -> BoxFuture<'static, Result<()>> {
+        Box::pin(std::future::pending())
+    }
+
+    #[tokio::test]
+    async fn all_of_fails_if_any_child_fails() {
+        assert!(all_ready(vec![pass(), pass()]).await.is_ok());
+
+        // Fails fast even while another child is still waiting.
+        let err = all_ready(vec![pass(), fail("http 503"), never()])
+            .await
+            .unwrap_err();
+        assert!(format!("{:#}", err).contains("http 503"));
+    }
+
+    #[tokio::test]
+    async fn any_of_passes_if_one_child_passes() {
+        assert!(any_ready(vec![fail("no log line"), never(), pass()])
+            .await
+            .is_ok());
+
+        let err = any_ready(vec![fail("tcp refused"), fail("no log line")])
+            .await
+            .unwrap_err();
+        assert!(format!("{:#}", err).contains("no any_of ready check passed"));
+        assert!(any_ready(vec![]).await.is_err());
+    }
+
+    /// A `grpc.health.v1.Health` server that answers every Check with `status`.
+    #[derive(Clone)]
+    struct FakeHealth(i32);
+
+    impl tonic::server::NamedService for FakeHealth {
+        const NAME: &'static str = "grpc.health.v1.Health";
+    }
+
+    impl tonic::server::UnaryService<health::HealthCheckRequest> for FakeHealth {
+        type Response = health::HealthCheckResponse;
+        type Future =
+            std::future::Ready<std::result::Result<tonic::Response<Self::Response>, tonic::Status>>;
+
+        fn call(&mut self, _: tonic::Request<health::HealthCheckRequest>) -> Self::Future {
+            std::future::ready(Ok(tonic::Response::new(health::HealthCheckResponse {
+                status: self.0,
+            })))
+        }
+    }
+
+    impl tonic::codegen::Service<tonic::codegen::http::Request<tonic::body::BoxBody>> for FakeHealth {
+        type Response = tonic::codegen::http::Response<tonic::body::BoxBody>;
+        type Error = std::convert::Infallible;
+        type Future = BoxFuture<'static, std::result::Result<Self::Response, Self::Error>>;
+
+        fn poll_ready(
+            &mut self,
+            _: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<std::result::Result<(), Self::Error>> {
+            std::task::Poll::Ready(Ok(()))
+        }
+
+        fn call(
+            &mut self,
+            req: tonic::codegen::http::Request<tonic::body::BoxBody>,
+        ) -> Self::Future {
+            let svc = self.clone();
+            Box::pin(async move {
+                let mut grpc = tonic::server::Grpc::new(tonic::codec::ProstCodec::default());
+                Ok(grpc.unary(svc, req).await)
+            })
+        }
+    }
+
+    async fn serve_health(status: i32) -> u16 {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(FakeHealth(status))
+                .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener)),
+        );
+        port
+    }
+
+    #[tokio::test]
+    async fn grpc_probe_needs_serving() {
+        let port = serve_health(health::SERVING).await;
+        probe_grpc(Some(port), None).await.unwrap();
+        probe_grpc(Some(port), Some("api.v1.Orders")).await.unwrap();
+
+        let port = serve_health(2).await;
+        let err = probe_grpc(Some(port), None).await.unwrap_err();
+        assert!(err.to_string().contains("NOT_SERVING"));
+
+        assert!(probe_grpc(None, None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn dependent_starts_only_after_slow_dependency_is_healthy() {
+        use crate::config::model::{Dependency, DependencyCondition};
+        use crate::orchestrator::graph::ResourceKind;
+        use crate::orchestrator::readiness::ReadinessGates;
+
+        let events = Arc::new(Mutex::new(Vec::<String>::new()));
+        let polls = Arc::new(Mutex::new(0u32));
+
+        let probe = {
+            let events = Arc::clone(&events);
+            let polls = Arc::clone(&polls);
+            move || {
+                let events = Arc::clone(&events);
+                let polls = Arc::clone(&polls);
+                async move {
+                    let mut n = polls.lock().unwrap();
+                    *n += 1;
+                    if *n < 4 {
+                        Ok(NativeHealth::Starting)
+                    } else {
+                        events.lock().unwrap().push("a healthy".to_string());
+                        Ok(NativeHealth::Healthy)
+                    }
+                }
+            }
+        };
+
+        // The orchestrator's path: `a` has no ready_check, so its native
+        // healthcheck settles the gate that `b` waits on.
+        let mut gates = ReadinessGates::default();
+        gates.register("a");
+        gates.started("a");
+        gates.track("a", async move {
+            poll_native_health(
+                "a",
+                Duration::from_secs(5),
+                Duration::from_millis(10),
+                probe,
+            )
+            .await
+        });
+        let depends_on = [Dependency {
+            name: "a".to_string(),
+            condition: DependencyCondition::Healthy,
+        }];
+        gates
+            .wait(ResourceKind::Docker, "b", &depends_on)
+            .await
+            .unwrap();
+        events.lock().unwrap().push("b created".to_string());
+
+        assert_eq!(*polls.lock().unwrap(), 4);
+        assert_eq!(*events.lock().unwrap(), vec!["a healthy", "b created"]);
+    }
+
+    #[tokio::test]
+    async fn no_healthcheck_does_not_block() {
+        poll_native_health(
+            "a",
+            Duration::from_millis(50),
+            Duration::from_secs(10),
+            || async { Ok(NativeHealth::None) },
+        )
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn unhealthy_dependency_errors() {
+        let err = poll_native_health(
+            "a",
+            Duration::from_secs(1),
+            Duration::from_millis(10),
+            || async { Ok(NativeHealth::Unhealthy) },
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("unhealthy"));
+    }
+
+    #[tokio::test]
+    async fn stuck_starting_times_out() {
+        let err = poll_native_health(
+            "a",
+            Duration::from_millis(50),
+            Duration::from_millis(10),
+            || async { Ok(NativeHealth::Starting) },
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("did not report healthy"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn progress_is_reported_every_interval() {
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let seen = reports.clone();
+        let output = with_progress(
+            async {
+                tokio::time::sleep(Duration::from_secs(12)).await;
+                7
+            },
+            Duration::from_secs(5),
+            move |waited| seen.lock().unwrap().push(waited.as_secs()),
+        )
+        .await;
+        assert_eq!(output, 7);
+        assert_eq!(*reports.lock().unwrap(), vec![5, 10]);
+    }
+
+    #[test]
+    fn failure_includes_container_output() {
+        let lines = vec!["FATAL: role \"app\" does not exist".to_string()];
+        let err = with_log_tail(anyhow::anyhow!("timed out"), "postgres", &lines);
+        assert_eq!(
+            err.to_string(),
+            "timed out\nlast 1 lines of 'postgres' output:\n  FATAL: role \"app\" does not exist"
+        );
+
+        let err = with_log_tail(anyhow::anyhow!("timed out"), "postgres", &[]);
+        assert_eq!(err.to_string(), "timed out");
+    }
+}
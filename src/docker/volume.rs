@@ -13,45 +13,72 @@ pub enum VolumeSpec {
     Named {
         volume_name: String,
         container_path: String,
+        read_only: bool,
     },
     /// Bind mount: `"/host/path:/container/path"` — mounts a host directory
-    /// directly into the container. No Docker volume is created.
+    /// directly into the container. No Docker volume is created. A leading
+    /// `~` or `$HOME` is already expanded; relative paths are left for the
+    /// caller to resolve against the config directory.
     Bind {
         host_path: String,
         container_path: String,
+        read_only: bool,
     },
 }
 
 /// Parse a volume spec into a [`VolumeSpec`].
 ///
-/// Bind mounts are detected when the source starts with `/`, `./`, or `../`.
-/// Everything else is treated as a named volume and scoped to the project.
+/// Specs are `source:/container/path`, optionally followed by a `:ro` or
+/// `:rw` mode (default `rw`). Bind mounts are detected when the source is
+/// `.` or `..`, or starts with `/`, `./`, `../`, `~` or `$HOME`. Everything
+/// else is treated as a named volume and scoped to the project.
 pub fn parse_volume_spec(spec: &str, slug: &str) -> Option<VolumeSpec> {
-    let (source, path) = spec.split_once(':')?;
+    let (source, rest) = spec.split_once(':')?;
+    let (path, read_only) = match rest.rsplit_once(':') {
+        Some((path, "ro")) => (path, true),
+        Some((path, "rw")) => (path, false),
+        Some(_) => return None,
+        None => (rest, false),
+    };
     if source.is_empty() || path.is_empty() {
         return None;
     }
 
     if is_bind_mount(source) {
         Some(VolumeSpec::Bind {
-            host_path: source.to_string(),
+            host_path: crate::platform::expand_home(source),
             container_path: path.to_string(),
+            read_only,
         })
     } else {
         let scoped_name = format!("devrig-{}-{}", slug, source);
         Some(VolumeSpec::Named {
             volume_name: scoped_name,
             container_path: path.to_string(),
+            read_only,
         })
     }
 }
 
 /// Returns true if the source portion of a volume spec looks like a host path
-/// (absolute or relative) rather than a named volume.
+/// (absolute, relative or home-relative) rather than a named volume.
 fn is_bind_mount(source: &str) -> bool {
-    source.starts_with('/')
+    matches!(source, "." | ".." | "~" | "$HOME")
+        || source.starts_with('/')
         || source.starts_with("./")
         || source.starts_with("../")
+        || source.starts_with("~/")
+        || source.starts_with("$HOME/")
+}
+
+/// Docker `Binds` entry mounting `source` (a volume name or absolute host
+/// path) at `container_path`.
+pub fn bind_string(source: &str, container_path: &str, read_only: bool) -> String {
+    if read_only {
+        format!("{}:{}:ro", source, container_path)
+    } else {
+        format!("{}:{}", source, container_path)
+    }
 }
 
 /// Create a Docker volume if it doesn't already exist.
@@ -121,6 +148,25 @@ pub async fn remove_project_volumes(docker: &Docker, slug: &str) -> Result<()> {
     Ok(())
 }
 
+/// Remove the volumes devrig created for one resource of the project,
+/// returning their names.
+pub async fn remove_resource_volumes(
+    docker: &Docker,
+    slug: &str,
+    resource: &str,
+) -> Result<Vec<String>> {
+    let mut removed = Vec::new();
+    for vol in list_project_volumes(docker, slug).await? {
+        if vol.labels.get("devrig.service").map(String::as_str) != Some(resource) {
+            continue;
+        }
+        tracing::debug!(volume = %vol.name, "removing volume");
+        remove_volume(docker, &vol.name).await?;
+        removed.push(vol.name);
+    }
+    Ok(removed)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -134,6 +180,7 @@ mod tests {
             VolumeSpec::Named {
                 volume_name: "devrig-myapp-abc123-pgdata".to_string(),
                 container_path: "/var/lib/postgresql/data".to_string(),
+                read_only: false,
             }
         );
     }
@@ -147,6 +194,7 @@ mod tests {
             VolumeSpec::Bind {
                 host_path: "/home/user/data".to_string(),
                 container_path: "/var/lib/postgresql/data".to_string(),
+                read_only: false,
             }
         );
     }
@@ -159,6 +207,7 @@ mod tests {
             VolumeSpec::Bind {
                 host_path: "./data".to_string(),
                 container_path: "/app/data".to_string(),
+                read_only: false,
             }
         );
     }
@@ -171,10 +220,63 @@ mod tests {
             VolumeSpec::Bind {
                 host_path: "../shared".to_string(),
                 container_path: "/app/shared".to_string(),
+                read_only: false,
+            }
+        );
+    }
+
+    #[test]
+    fn parse_bind_mount_home_read_only() {
+        let spec = parse_volume_spec("~/.aws:/root/.aws:ro", "slug").unwrap();
+        assert_eq!(
+            spec,
+            VolumeSpec::Bind {
+                host_path: crate::platform::expand_home("~/.aws"),
+                container_path: "/root/.aws".to_string(),
+                read_only: true,
+            }
+        );
+    }
+
+    #[test]
+    fn parse_bind_mount_config_dir() {
+        let spec = parse_volume_spec(".:/workspace:rw", "slug").unwrap();
+        assert_eq!(
+            spec,
+            VolumeSpec::Bind {
+                host_path: ".".to_string(),
+                container_path: "/workspace".to_string(),
+                read_only: false,
             }
         );
     }
 
+    #[test]
+    fn parse_named_volume_read_only() {
+        let spec = parse_volume_spec("seed:/docker-entrypoint-initdb.d:ro", "slug").unwrap();
+        assert_eq!(
+            spec,
+            VolumeSpec::Named {
+                volume_name: "devrig-slug-seed".to_string(),
+                container_path: "/docker-entrypoint-initdb.d".to_string(),
+                read_only: true,
+            }
+        );
+    }
+
+    #[test]
+    fn parse_volume_spec_invalid_mode() {
+        assert!(parse_volume_spec("./data:/app/data:rx", "slug").is_none());
+        assert!(parse_volume_spec("./data:/app/data:", "slug").is_none());
+        assert!(parse_volume_spec("./data::ro", "slug").is_none());
+    }
+
+    #[test]
+    fn bind_string_appends_ro() {
+        assert_eq!(bind_string("/a", "/b", true), "/a:/b:ro");
+        assert_eq!(bind_string("vol", "/b", false), "vol:/b");
+    }
+
     #[test]
     fn parse_volume_spec_empty_name() {
         assert!(parse_volume_spec(":/var/lib", "slug").is_none());
@@ -43,6 +43,36 @@ impl ProjectIdentity {
             config_path: canonical,
         })
     }
+
+    /// Docker network shared by this project's containers.
+    pub fn network_name(&self) -> String {
+        network_name(&self.slug)
+    }
+
+    /// Prefix of every container devrig creates for this project.
+    pub fn container_prefix(&self) -> String {
+        container_name(&self.slug, "")
+    }
+
+    /// k3d cluster name used when `[cluster]` is configured.
+    pub fn cluster_name(&self) -> String {
+        cluster_name(&self.slug)
+    }
+}
+
+/// Docker network name for a project slug.
+pub fn network_name(slug: &str) -> String {
+    format!("devrig-{slug}-net")
+}
+
+/// Container name for a `[docker.*]` resource in a project.
+pub fn container_name(slug: &str, resource: &str) -> String {
+    format!("devrig-{slug}-{resource}")
+}
+
+/// k3d cluster name for a project slug.
+pub fn cluster_name(slug: &str) -> String {
+    format!("devrig-{slug}")
 }
 
 #[cfg(test)]
@@ -11,4 +11,5 @@ pub mod orchestrator;
 pub mod otel;
 pub mod platform;
 pub mod query;
+pub mod tls;
 pub mod ui;
@@ -2,8 +2,10 @@ use clap::{CommandFactory, Parser};
 use clap_complete::aot::generate;
 use devrig::cli::{Cli, Commands};
 use devrig::commands;
+use devrig::commands::reset::ResetMode;
 use devrig::config::resolve::resolve_config;
 use devrig::orchestrator::Orchestrator;
+use devrig::ui::summary::SummaryFormat;
 
 #[tokio::main]
 async fn main() {
@@ -21,43 +23,174 @@ async fn main() {
     let result = match cli.command {
         Commands::Start {
             services,
+            profile,
+            summary_format,
+            max_log_rate,
+            log_buffer,
+            dry_run,
+            force_rebuild,
+            ready_timeout,
+            detach,
+            exit_on_failure,
+            foreground,
             #[cfg(debug_assertions)]
             dev,
         } => {
             let dev_mode = { #[cfg(debug_assertions)] { dev } #[cfg(not(debug_assertions))] { false } };
-            run_start(cli.global.config_file, services, dev_mode).await
+            let summary_format = SummaryFormat::from_str_opt(Some(&summary_format));
+            if dry_run {
+                run_start_dry_run(cli.global.config_file, services, profile, summary_format)
+            } else if detach && !foreground {
+                run_start_detached(cli.global.config_file)
+            } else {
+                run_start(
+                    cli.global.config_file,
+                    services,
+                    profile,
+                    summary_format,
+                    max_log_rate,
+                    log_buffer,
+                    force_rebuild,
+                    ready_timeout,
+                    cli.global.no_pull,
+                    exit_on_failure,
+                    foreground,
+                    dev_mode,
+                )
+                .await
+            }
+        }
+        Commands::Stop {
+            all,
+            remove_containers,
+            ..
+        } if all => run_stop_all(remove_containers).await,
+        Commands::Stop {
+            remove_containers, ..
+        } => run_stop(cli.global.config_file, remove_containers).await,
+        Commands::Restart { services } => {
+            commands::restart::run(cli.global.config_file.as_deref(), services).await
+        }
+        Commands::Scale { replicas } => {
+            commands::scale::run(cli.global.config_file.as_deref(), replicas).await
+        }
+        Commands::ReloadEnv { services } => {
+            commands::reload_env::run(cli.global.config_file.as_deref(), services).await
         }
-        Commands::Stop { all, .. } if all => run_stop_all().await,
-        Commands::Stop { .. } => run_stop(cli.global.config_file).await,
         Commands::Delete { all } if all => run_delete_all().await,
         Commands::Delete { .. } => run_delete(cli.global.config_file).await,
-        Commands::Ps { all } => commands::ps::run(cli.global.config_file.as_deref(), all),
-        Commands::Init => commands::init::run(),
-        Commands::Doctor => commands::doctor::run(),
-        Commands::Env { service } => {
-            commands::env::run(cli.global.config_file.as_deref(), &service)
+        Commands::Ps {
+            ports: Some(name),
+            format,
+            ..
+        } => commands::ps::run_ports(
+            cli.global.config_file.as_deref(),
+            name.as_deref(),
+            devrig::query::output::OutputFormat::from_str_opt(format.as_deref()),
+        ),
+        Commands::Ps {
+            all,
+            ports: None,
+            format,
+        } => commands::ps::run(
+            cli.global.config_file.as_deref(),
+            all,
+            devrig::query::output::OutputFormat::from_str_opt(format.as_deref()),
+        ),
+        Commands::Top => commands::top::run(cli.global.config_file.as_deref()).await,
+        Commands::Open { name } => {
+            commands::open::run(cli.global.config_file.as_deref(), name.as_deref())
         }
-        Commands::Exec { docker, command } => {
-            commands::exec::run(cli.global.config_file.as_deref(), &docker, command).await
+        Commands::Wait { names, timeout } => {
+            commands::wait::run(cli.global.config_file.as_deref(), names, timeout).await
         }
-        Commands::Reset { docker } => {
-            commands::reset::run(cli.global.config_file.as_deref(), &docker)
+        Commands::Init {
+            minimal,
+            full,
+            template,
+            force,
+        } => {
+            let scaffold = if let Some(template) =
+                template.as_deref().and_then(commands::init::Template::from_name)
+            {
+                commands::init::Scaffold::Template(template)
+            } else if minimal {
+                commands::init::Scaffold::Minimal
+            } else if full {
+                commands::init::Scaffold::Full
+            } else {
+                commands::init::Scaffold::Default
+            };
+            commands::init::run(scaffold, force)
         }
-        Commands::Validate => commands::validate::run(cli.global.config_file.as_deref()),
-        Commands::Logs {
-            services,
-            follow: _,
-            tail,
-            since,
-            grep,
-            exclude,
-            level,
+        Commands::Doctor { fix } => {
+            commands::doctor::run(cli.global.config_file.as_deref(), fix)
+        }
+        Commands::Env {
+            service,
+            export,
             format,
-            output,
-            timestamps,
-        } => commands::logs::run(
-            cli.global.config_file.as_deref(),
+        } => {
+            let format = if export {
+                commands::env::EnvFormat::Export
+            } else {
+                commands::env::EnvFormat::from_str_opt(format.as_deref())
+            };
+            commands::env::run(cli.global.config_file.as_deref(), &service, format)
+        }
+        Commands::Exec {
+            name,
+            service: true,
+            command,
+            ..
+        } => commands::exec::run_service(cli.global.config_file.as_deref(), &name, command).await,
+        Commands::Exec {
+            name,
+            docker,
+            compose,
+            service: false,
+            command,
+        } => {
+            let target = if docker {
+                commands::exec::ExecTarget::Docker
+            } else if compose {
+                commands::exec::ExecTarget::Compose
+            } else {
+                commands::exec::ExecTarget::Any
+            };
+            commands::exec::run(cli.global.config_file.as_deref(), &name, target, command).await
+        }
+        Commands::Reset {
+            docker,
+            all,
+            volumes_only,
+            yes,
+        } => {
+            commands::reset::run(
+                cli.global.config_file.as_deref(),
+                docker.as_deref(),
+                ResetMode::from_flags(all, volumes_only),
+                yes,
+            )
+            .await
+        }
+        Commands::Validate { format } => {
+            commands::validate::run(cli.global.config_file.as_deref(), &format)
+        }
+        Commands::Config { command } => match command {
+            devrig::cli::ConfigCommands::Show { format } => {
+                commands::config::run_show(cli.global.config_file.as_deref(), &format)
+            }
+            devrig::cli::ConfigCommands::Diff { old, new, format } => {
+                commands::config::run_diff(&old, &new, &format)
+            }
+        },
+        Commands::Whoami { json } => {
+            commands::whoami::run(cli.global.config_file.as_deref(), json)
+        }
+        Commands::Logs {
             services,
+            follow,
             tail,
             since,
             grep,
@@ -65,8 +198,40 @@ async fn main() {
             level,
             format,
             output,
+            quiet,
+            rotate,
             timestamps,
-        ),
+            narrow,
+            with_state,
+            export_bundle,
+            count,
+            expect_zero,
+            container,
+        } => {
+            commands::logs::run(
+                cli.global.config_file.as_deref(),
+                services,
+                follow,
+                tail,
+                since,
+                grep,
+                exclude,
+                level,
+                format,
+                output,
+                quiet,
+                rotate,
+                timestamps,
+                narrow,
+                with_state,
+                export_bundle,
+                count,
+                expect_zero,
+                container,
+            )
+            .await
+        }
+        Commands::Schema => commands::schema::run(),
         Commands::Completions { shell } => {
             generate(shell, &mut Cli::command(), "devrig", &mut std::io::stdout());
             Ok(())
@@ -89,14 +254,55 @@ async fn main() {
                 )
                 .await
             }
+            devrig::cli::ClusterCommands::Events {
+                namespace,
+                since,
+                include_normal,
+            } => {
+                commands::cluster::run_events(
+                    cli.global.config_file.as_deref(),
+                    namespace,
+                    since,
+                    include_normal,
+                )
+                .await
+            }
+            devrig::cli::ClusterCommands::Diff { deploy } => {
+                commands::cluster::run_diff(cli.global.config_file.as_deref(), deploy).await
+            }
+            devrig::cli::ClusterCommands::Node {
+                action,
+                node,
+                ignore_daemonsets,
+                force,
+            } => {
+                commands::cluster::run_node(
+                    cli.global.config_file.as_deref(),
+                    &action,
+                    node,
+                    ignore_daemonsets,
+                    force,
+                )
+                .await
+            }
+            devrig::cli::ClusterCommands::ImportImage { images } => {
+                commands::cluster::run_import_image(cli.global.config_file.as_deref(), &images)
+                    .await
+            }
         },
         Commands::Kubectl { args } => {
             commands::cluster::run_kubectl(cli.global.config_file.as_deref(), args).await
         }
         Commands::Update => commands::update::run(),
+        Commands::Version { check } => commands::version::run(check).await,
         Commands::Skill { command } => match command {
-            devrig::cli::SkillCommands::Install { global } => {
-                commands::skill::run_install(global, cli.global.config_file.as_deref()).await
+            devrig::cli::SkillCommands::Install { targets, global } => {
+                let targets: Vec<_> = targets
+                    .iter()
+                    .filter_map(|t| commands::skill::SkillTarget::from_name(t))
+                    .collect();
+                commands::skill::run_install(&targets, global, cli.global.config_file.as_deref())
+                    .await
             }
             devrig::cli::SkillCommands::Reference => commands::skill::run_reference(),
         },
@@ -105,7 +311,10 @@ async fn main() {
                 service,
                 status,
                 min_duration,
-                last: _,
+                root_only,
+                last,
+                from,
+                to,
                 limit,
                 format,
             } => {
@@ -114,6 +323,10 @@ async fn main() {
                     service,
                     status,
                     min_duration,
+                    root_only,
+                    last,
+                    from,
+                    to,
                     limit,
                     format,
                 )
@@ -132,9 +345,12 @@ async fn main() {
                 level,
                 search,
                 trace_id,
-                last: _,
+                last,
+                from,
+                to,
                 limit,
                 format,
+                include_attributes,
             } => {
                 commands::query::run_logs(
                     cli.global.config_file.as_deref(),
@@ -142,15 +358,21 @@ async fn main() {
                     level,
                     search,
                     trace_id,
+                    last,
+                    from,
+                    to,
                     limit,
                     format,
+                    include_attributes,
                 )
                 .await
             }
             devrig::cli::QueryCommands::Metrics {
                 name,
                 service,
-                last: _,
+                last,
+                from,
+                to,
                 limit,
                 format,
             } => {
@@ -158,6 +380,9 @@ async fn main() {
                     cli.global.config_file.as_deref(),
                     name,
                     service,
+                    last,
+                    from,
+                    to,
                     limit,
                     format,
                 )
@@ -175,27 +400,77 @@ async fn main() {
 
     if let Err(e) = result {
         eprintln!("Error: {:#}", e);
-        std::process::exit(1);
+        // `devrig start --exit-on-failure` passes on the failed service's code
+        let code = e
+            .downcast_ref::<devrig::orchestrator::ServiceFailure>()
+            .map_or(1, |f| f.process_exit_code());
+        std::process::exit(code);
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn run_start(
     config_file: Option<std::path::PathBuf>,
     services: Vec<String>,
+    profile: Option<String>,
+    summary_format: SummaryFormat,
+    max_log_rate: Option<u32>,
+    log_buffer: Option<u32>,
+    force_rebuild: bool,
+    ready_timeout: Option<u64>,
+    no_pull: bool,
+    exit_on_failure: bool,
+    detached_child: bool,
     dev_mode: bool,
 ) -> anyhow::Result<()> {
     let config_path = resolve_config(config_file.as_deref())?;
-    let mut orchestrator = Orchestrator::from_config(config_path)?;
-    orchestrator.start(services, dev_mode).await
+    let mut orchestrator = Orchestrator::from_config(config_path)?
+        .with_summary_format(summary_format)
+        .with_max_log_rate(max_log_rate)
+        .with_log_buffer(log_buffer.map(|n| n as usize))
+        .with_force_rebuild(force_rebuild)
+        .with_no_pull(no_pull)
+        .with_ready_timeout(ready_timeout)
+        .with_exit_on_failure(exit_on_failure)
+        .with_detached_child(detached_child);
+    orchestrator.start(services, profile, dev_mode).await
 }
 
-async fn run_stop(config_file: Option<std::path::PathBuf>) -> anyhow::Result<()> {
+/// Validate the config here so mistakes are reported directly, then hand
+/// the same command line to a background `devrig start --foreground`.
+fn run_start_detached(config_file: Option<std::path::PathBuf>) -> anyhow::Result<()> {
+    let config_path = resolve_config(config_file.as_deref())?;
+    Orchestrator::from_config(config_path.clone())?;
+    let project_dir = config_path.parent().unwrap_or(std::path::Path::new("."));
+    let state_dir = devrig::orchestrator::state::ProjectState::state_dir_for(project_dir);
+    devrig::orchestrator::detach::spawn_detached(&state_dir)
+}
+
+fn run_start_dry_run(
+    config_file: Option<std::path::PathBuf>,
+    services: Vec<String>,
+    profile: Option<String>,
+    summary_format: SummaryFormat,
+) -> anyhow::Result<()> {
+    let config_path = resolve_config(config_file.as_deref())?;
+    let plan = Orchestrator::from_config(config_path)?.plan(services, profile)?;
+    match summary_format {
+        SummaryFormat::Json => println!("{}", serde_json::to_string(&plan)?),
+        SummaryFormat::Table => print!("{}", devrig::orchestrator::plan::render_plan(&plan)),
+    }
+    Ok(())
+}
+
+async fn run_stop(
+    config_file: Option<std::path::PathBuf>,
+    remove_containers: bool,
+) -> anyhow::Result<()> {
     let config_path = resolve_config(config_file.as_deref())?;
     let orchestrator = Orchestrator::from_config(config_path)?;
-    orchestrator.stop().await
+    orchestrator.stop(remove_containers).await
 }
 
-async fn run_stop_all() -> anyhow::Result<()> {
+async fn run_stop_all(remove_containers: bool) -> anyhow::Result<()> {
     use devrig::orchestrator::registry::InstanceRegistry;
 
     let mut registry = InstanceRegistry::load();
@@ -216,7 +491,7 @@ async fn run_stop_all() -> anyhow::Result<()> {
         }
         eprint!("  Stopping {} ... ", entry.slug);
         match Orchestrator::from_config(config_path) {
-            Ok(o) => match o.stop().await {
+            Ok(o) => match o.stop(remove_containers).await {
                 Ok(()) => eprintln!("done"),
                 Err(e) => eprintln!("error: {:#}", e),
             },
@@ -0,0 +1,414 @@
+//! Control channel between a running `devrig start` and other devrig
+//! commands, over a unix socket in the project's state directory.
+//!
+//! The protocol is one JSON [`ControlRequest`] line per connection, answered
+//! by one JSON [`ControlResponse`] line.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, oneshot};
+use tokio_util::sync::CancellationToken;
+
+use crate::orchestrator::reload::EnvChange;
+use crate::orchestrator::scale::Replica;
+
+/// Socket file name inside the state directory.
+pub const SOCKET_FILENAME: &str = "control.sock";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum ControlRequest {
+    /// Tear down and respawn the named `[services]` supervisors.
+    Restart { services: Vec<String> },
+    /// Stop the named `[services]` supervisors without restarting them.
+    Stop { services: Vec<String> },
+    /// Rebuild the env of the named services (all running ones when empty)
+    /// and restart or signal those whose env changed.
+    ReloadEnv { services: Vec<String> },
+    /// Start or stop replicas so each named service runs the given number
+    /// of instances.
+    Scale { replicas: BTreeMap<String, usize> },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ControlResponse {
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub restarted: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stopped: Vec<String>,
+    /// Services whose env changed during a `reload_env` request.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reloaded: Vec<EnvChange>,
+    /// Every instance of the services named in a `scale` request.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub replicas: Vec<Replica>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Why a request naming services was refused, when that is known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_kind: Option<ServiceControlErrorKind>,
+}
+
+/// A service named in a control request that can't be acted on.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ServiceControlError {
+    #[error("unknown service '{name}' (running: {running:?})")]
+    Unknown { name: String, running: Vec<String> },
+    #[error("service '{0}' is not running")]
+    NotRunning(String),
+    #[error("service '{0}' was not started in this session")]
+    NotStarted(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ServiceControlErrorKind {
+    Unknown,
+    NotRunning,
+    NotStarted,
+}
+
+impl ServiceControlError {
+    pub fn kind(&self) -> ServiceControlErrorKind {
+        match self {
+            ServiceControlError::Unknown { .. } => ServiceControlErrorKind::Unknown,
+            ServiceControlError::NotRunning(_) => ServiceControlErrorKind::NotRunning,
+            ServiceControlError::NotStarted(_) => ServiceControlErrorKind::NotStarted,
+        }
+    }
+}
+
+impl ControlResponse {
+    pub fn restarted(services: Vec<String>) -> Self {
+        Self {
+            ok: true,
+            restarted: services,
+            stopped: Vec::new(),
+            reloaded: Vec::new(),
+            replicas: Vec::new(),
+            error: None,
+            error_kind: None,
+        }
+    }
+
+    pub fn reloaded(changes: Vec<EnvChange>) -> Self {
+        Self {
+            ok: true,
+            restarted: Vec::new(),
+            stopped: Vec::new(),
+            reloaded: changes,
+            replicas: Vec::new(),
+            error: None,
+            error_kind: None,
+        }
+    }
+
+    pub fn scaled(replicas: Vec<Replica>) -> Self {
+        Self {
+            ok: true,
+            restarted: Vec::new(),
+            stopped: Vec::new(),
+            reloaded: Vec::new(),
+            replicas,
+            error: None,
+            error_kind: None,
+        }
+    }
+
+    pub fn stopped(services: Vec<String>) -> Self {
+        Self {
+            ok: true,
+            restarted: Vec::new(),
+            stopped: services,
+            reloaded: Vec::new(),
+            replicas: Vec::new(),
+            error: None,
+            error_kind: None,
+        }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            ok: false,
+            restarted: Vec::new(),
+            stopped: Vec::new(),
+            reloaded: Vec::new(),
+            replicas: Vec::new(),
+            error: Some(message.into()),
+            error_kind: None,
+        }
+    }
+
+    /// An error response for a failed request, tagged with its
+    /// [`ServiceControlErrorKind`] when it is a [`ServiceControlError`].
+    pub fn from_error(err: &anyhow::Error) -> Self {
+        Self {
+            error_kind: err
+                .downcast_ref::<ServiceControlError>()
+                .map(ServiceControlError::kind),
+            ..Self::error(format!("{:#}", err))
+        }
+    }
+}
+
+/// A request received by the server, with the channel to answer it on.
+pub type ControlMessage = (ControlRequest, oneshot::Sender<ControlResponse>);
+
+pub fn socket_path(state_dir: &Path) -> PathBuf {
+    state_dir.join(SOCKET_FILENAME)
+}
+
+/// Listen on the control socket until `cancel` fires, forwarding each
+/// request to `tx`. The socket file is removed on shutdown.
+///
+/// The listener is deliberately not tracked: an idle control socket must not
+/// keep devrig alive once every service has exited.
+#[cfg(unix)]
+pub fn spawn_server(
+    state_dir: &Path,
+    cancel: CancellationToken,
+    tx: mpsc::Sender<ControlMessage>,
+) -> Result<()> {
+    use anyhow::Context;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::UnixListener;
+    use tracing::debug;
+
+    let path = socket_path(state_dir);
+    // A previous run that was killed leaves a stale socket behind.
+    let _ = std::fs::remove_file(&path);
+    let listener = UnixListener::bind(&path)
+        .with_context(|| format!("binding control socket {}", path.display()))?;
+
+    tokio::spawn(async move {
+        loop {
+            let stream = tokio::select! {
+                _ = cancel.cancelled() => break,
+                accepted = listener.accept() => match accepted {
+                    Ok((stream, _)) => stream,
+                    Err(e) => {
+                        debug!(error = %e, "control socket accept failed");
+                        continue;
+                    }
+                },
+            };
+
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                let (read, mut write) = stream.into_split();
+                let mut line = String::new();
+                if BufReader::new(read).read_line(&mut line).await.is_err() {
+                    return;
+                }
+                let response = match serde_json::from_str::<ControlRequest>(&line) {
+                    Ok(request) => send_local(&tx, request).await,
+                    Err(e) => ControlResponse::error(format!("invalid control request: {}", e)),
+                };
+                if let Ok(mut json) = serde_json::to_string(&response) {
+                    json.push('\n');
+                    let _ = write.write_all(json.as_bytes()).await;
+                }
+            });
+        }
+        let _ = std::fs::remove_file(&path);
+    });
+
+    Ok(())
+}
+
+/// Control sockets need unix domain sockets; elsewhere only in-process
+/// senders such as the dashboard reach the orchestrator.
+#[cfg(not(unix))]
+pub fn spawn_server(
+    _state_dir: &Path,
+    _cancel: CancellationToken,
+    _tx: mpsc::Sender<ControlMessage>,
+) -> Result<()> {
+    Ok(())
+}
+
+/// Send `request` over an in-process control channel, as the dashboard does.
+pub async fn send_local(
+    tx: &mpsc::Sender<ControlMessage>,
+    request: ControlRequest,
+) -> ControlResponse {
+    let (reply_tx, reply_rx) = oneshot::channel();
+    if tx.send((request, reply_tx)).await.is_err() {
+        return ControlResponse::error("devrig is shutting down");
+    }
+    reply_rx
+        .await
+        .unwrap_or_else(|_| ControlResponse::error("devrig is shutting down"))
+}
+
+/// Send `request` to the devrig process running from `state_dir`.
+#[cfg(unix)]
+pub async fn send(state_dir: &Path, request: &ControlRequest) -> Result<ControlResponse> {
+    use anyhow::{bail, Context};
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::UnixStream;
+
+    let path = socket_path(state_dir);
+    let stream = match UnixStream::connect(&path).await {
+        Ok(stream) => stream,
+        Err(e)
+            if matches!(
+                e.kind(),
+                std::io::ErrorKind::NotFound | std::io::ErrorKind::ConnectionRefused
+            ) =>
+        {
+            bail!("project is not running -- start it with `devrig start`")
+        }
+        Err(e) => {
+            return Err(e).with_context(|| format!("connecting to {}", path.display()));
+        }
+    };
+
+    let (read, mut write) = stream.into_split();
+    let mut json = serde_json::to_string(request)?;
+    json.push('\n');
+    write
+        .write_all(json.as_bytes())
+        .await
+        .context("sending control request")?;
+
+    let mut line = String::new();
+    BufReader::new(read)
+        .read_line(&mut line)
+        .await
+        .context("reading control response")?;
+    if line.is_empty() {
+        bail!("devrig closed the control connection without responding");
+    }
+    serde_json::from_str(&line).context("parsing control response")
+}
+
+#[cfg(not(unix))]
+pub async fn send(_state_dir: &Path, _request: &ControlRequest) -> Result<ControlResponse> {
+    anyhow::bail!("controlling a running devrig is only supported on unix platforms")
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn request_round_trips_over_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let cancel = CancellationToken::new();
+        let (tx, mut rx) = mpsc::channel(8);
+        spawn_server(dir.path(), cancel.clone(), tx).unwrap();
+
+        tokio::spawn(async move {
+            let (request, reply) = rx.recv().await.unwrap();
+            let ControlRequest::Restart { services } = request else {
+                panic!("expected a restart request");
+            };
+            let _ = reply.send(ControlResponse::restarted(services));
+        });
+
+        let request = ControlRequest::Restart {
+            services: vec!["api".to_string()],
+        };
+        let response = send(dir.path(), &request).await.unwrap();
+        assert_eq!(
+            response,
+            ControlResponse::restarted(vec!["api".to_string()])
+        );
+
+        cancel.cancel();
+        for _ in 0..50 {
+            if !socket_path(dir.path()).exists() {
+                return;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        panic!("control socket not removed after cancel");
+    }
+
+    #[test]
+    fn reload_response_round_trips() {
+        use crate::orchestrator::reload::ReloadAction;
+
+        let response = ControlResponse::reloaded(vec![
+            EnvChange {
+                service: "api".to_string(),
+                keys: vec!["GREETING".to_string()],
+                action: ReloadAction::Signal("SIGUSR1".to_string()),
+            },
+            EnvChange {
+                service: "worker".to_string(),
+                keys: vec!["QUEUE".to_string()],
+                action: ReloadAction::Restart,
+            },
+        ]);
+        let json = serde_json::to_string(&response).unwrap();
+        assert_eq!(
+            serde_json::from_str::<ControlResponse>(&json).unwrap(),
+            response
+        );
+
+        let request: ControlRequest =
+            serde_json::from_str(r#"{"command":"reload_env","services":[]}"#).unwrap();
+        assert_eq!(request, ControlRequest::ReloadEnv { services: vec![] });
+    }
+
+    #[test]
+    fn scale_round_trips() {
+        let request: ControlRequest =
+            serde_json::from_str(r#"{"command":"scale","replicas":{"api":3}}"#).unwrap();
+        assert_eq!(
+            request,
+            ControlRequest::Scale {
+                replicas: BTreeMap::from([("api".to_string(), 3)]),
+            }
+        );
+
+        let response = ControlResponse::scaled(vec![Replica {
+            service: "api".to_string(),
+            name: "api#1".to_string(),
+            index: 1,
+            port: Some(41001),
+        }]);
+        let json = serde_json::to_string(&response).unwrap();
+        assert_eq!(
+            serde_json::from_str::<ControlResponse>(&json).unwrap(),
+            response
+        );
+    }
+
+    #[tokio::test]
+    async fn send_without_server_reports_not_running() {
+        let dir = tempfile::tempdir().unwrap();
+        let request = ControlRequest::Restart {
+            services: vec!["api".to_string()],
+        };
+        let err = send(dir.path(), &request).await.unwrap_err();
+        assert!(err.to_string().contains("not running"), "{err}");
+    }
+
+    #[test]
+    fn error_kind_comes_from_service_control_errors() {
+        let err = anyhow::Error::from(ServiceControlError::NotRunning("api".to_string()));
+        let response = ControlResponse::from_error(&err);
+        assert!(!response.ok);
+        assert_eq!(response.error_kind, Some(ServiceControlErrorKind::NotRunning));
+        assert_eq!(response.error.as_deref(), Some("service 'api' is not running"));
+
+        let other = ControlResponse::from_error(&anyhow::anyhow!("pre_start failed"));
+        assert_eq!(other.error_kind, None);
+
+        let request: ControlRequest =
+            serde_json::from_str(r#"{"command":"stop","services":["api"]}"#).unwrap();
+        assert_eq!(
+            request,
+            ControlRequest::Stop {
+                services: vec!["api".to_string()]
+            }
+        );
+    }
+}
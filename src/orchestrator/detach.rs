@@ -0,0 +1,101 @@
+//! `devrig start --detach`: run devrig as a background child of the same
+//! binary and return once its startup summary has been printed.
+//!
+//! The child is started with the hidden `--foreground` flag and its stdout
+//! connected to a pipe. The parent relays that output until the child writes
+//! [`READY_LINE`], which it does right after the startup summary, then exits.
+//! The child moves its stdout over to [`LOG_FILE`] and keeps running until
+//! `devrig stop` signals it through the pid file like any other `devrig start`.
+
+use anyhow::{bail, Context, Result};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::platform;
+
+/// Output of a detached devrig, in the state directory.
+pub const LOG_FILE: &str = "devrig.log";
+
+/// Written by the child once startup completes; never shown to the user.
+const READY_LINE: &[u8] = b"\0devrig-detached-ready\n";
+
+/// Lines of the child's output shown when it fails to start.
+const FAILURE_TAIL_LINES: usize = 20;
+
+/// Re-run this command as a detached `devrig start --foreground` and wait
+/// until it is up. Fails, with the end of its log, if the child exits first.
+pub fn spawn_detached(state_dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(state_dir)
+        .with_context(|| format!("creating state dir {}", state_dir.display()))?;
+    let log_path = state_dir.join(LOG_FILE);
+    File::create(&log_path).with_context(|| format!("creating {}", log_path.display()))?;
+    // Appending, as stdout joins stderr in the file once startup completes.
+    let log = open_log(&log_path)?;
+
+    let (reader, writer) = std::io::pipe().context("creating startup pipe")?;
+    let exe = std::env::current_exe().context("locating the devrig binary")?;
+    let mut cmd = Command::new(exe);
+    cmd.args(std::env::args_os().skip(1))
+        .arg("--foreground")
+        .stdin(Stdio::null())
+        .stdout(writer)
+        .stderr(log);
+    platform::configure_detached(&mut cmd);
+    let mut child = cmd.spawn().context("starting devrig in the background")?;
+    // Drop our copy of the write end so a child that dies reaches EOF.
+    drop(cmd);
+
+    let mut reader = BufReader::new(reader);
+    let mut stdout = std::io::stdout();
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            break;
+        }
+        if line == READY_LINE {
+            eprintln!(
+                "devrig is running in the background (pid {}); output goes to {}",
+                child.id(),
+                log_path.display()
+            );
+            return Ok(());
+        }
+        stdout.write_all(&line)?;
+        stdout.flush()?;
+    }
+
+    let status = child.wait().context("waiting for devrig")?;
+    let output = std::fs::read_to_string(&log_path).unwrap_or_default();
+    let lines: Vec<&str> = output.lines().collect();
+    for l in &lines[lines.len().saturating_sub(FAILURE_TAIL_LINES)..] {
+        eprintln!("{}", l);
+    }
+    bail!(
+        "devrig exited during startup ({}); full output in {}",
+        status,
+        log_path.display()
+    );
+}
+
+/// Called by the detached child once startup completes: tell the waiting
+/// parent, then send the rest of stdout to [`LOG_FILE`].
+pub fn signal_ready(state_dir: &Path) -> Result<()> {
+    let log_path = state_dir.join(LOG_FILE);
+    let log = open_log(&log_path)?;
+    let mut stdout = std::io::stdout().lock();
+    stdout.write_all(READY_LINE)?;
+    stdout.flush()?;
+    platform::redirect_stdout(log)
+        .with_context(|| format!("redirecting output to {}", log_path.display()))
+}
+
+fn open_log(path: &Path) -> Result<File> {
+    OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(path)
+        .with_context(|| format!("opening {}", path.display()))
+}
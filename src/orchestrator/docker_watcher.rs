@@ -0,0 +1,311 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, warn};
+
+use crate::config::model::DockerConfig;
+use crate::docker::container::{container_status_and_health, ContainerStatus};
+use crate::docker::ready::NativeHealth;
+use crate::docker::DockerManager;
+use crate::orchestrator::state::{DockerState, ProjectState};
+use crate::orchestrator::supervisor::{RestartMode, RestartPolicy, ServiceSupervisor};
+use crate::otel::types::TelemetryEvent;
+
+/// How often a watched container is inspected.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Callback invoked with the new state after a container is recreated.
+pub type OnRestart = Box<dyn Fn(&DockerState) + Send + Sync>;
+
+/// Whether a container in `status` should be recreated under `mode`.
+fn needs_restart(mode: &RestartMode, status: ContainerStatus) -> bool {
+    match status {
+        ContainerStatus::Running => false,
+        ContainerStatus::Exited(0) => *mode == RestartMode::Always,
+        ContainerStatus::Exited(_) | ContainerStatus::Missing | ContainerStatus::Unhealthy => {
+            *mode != RestartMode::Never
+        }
+    }
+}
+
+/// Restart budget for a watched container, mirroring the process
+/// supervisor: `max_restarts` attempts with exponential backoff, reset after
+/// the container stays up for `reset_after`.
+#[derive(Default)]
+struct RestartBudget {
+    count: u32,
+    last_restart: Option<Instant>,
+}
+
+impl RestartBudget {
+    /// Record a restart at `now`, returning the backoff to wait first, or
+    /// `None` once `max_restarts` is exhausted.
+    fn next_delay(&mut self, policy: &RestartPolicy, now: Instant) -> Option<Duration> {
+        if self
+            .last_restart
+            .is_some_and(|t| now.duration_since(t) >= policy.reset_after)
+        {
+            self.count = 0;
+        }
+        if self.count >= policy.max_restarts {
+            return None;
+        }
+        let delay = ServiceSupervisor::backoff_delay(policy, self.count);
+        self.count += 1;
+        self.last_restart = Some(now);
+        Some(delay)
+    }
+}
+
+/// Watches a docker container started by [`DockerManager::start_service`]
+/// and recreates it when it exits or turns unhealthy, according to its
+/// `restart` policy. Without one the container is only observed: its phase
+/// and HEALTHCHECK status are recorded but it is never recreated.
+pub struct DockerWatcher {
+    name: String,
+    config: DockerConfig,
+    state: DockerState,
+    policy: RestartPolicy,
+    manager: DockerManager,
+    config_dir: PathBuf,
+    cancel: CancellationToken,
+    state_dir: Option<PathBuf>,
+    events_tx: Option<broadcast::Sender<TelemetryEvent>>,
+    on_restart: Option<OnRestart>,
+    template_vars: HashMap<String, String>,
+}
+
+impl DockerWatcher {
+    pub fn new(
+        name: String,
+        config: DockerConfig,
+        state: DockerState,
+        manager: DockerManager,
+        config_dir: PathBuf,
+        cancel: CancellationToken,
+    ) -> Self {
+        let policy = match &config.restart {
+            Some(restart) => RestartPolicy::from_config(restart),
+            None => RestartPolicy {
+                mode: RestartMode::Never,
+                ..RestartPolicy::default()
+            },
+        };
+        Self {
+            name,
+            config,
+            state,
+            policy,
+            manager,
+            config_dir,
+            cancel,
+            state_dir: None,
+            events_tx: None,
+            on_restart: None,
+            template_vars: HashMap::new(),
+        }
+    }
+
+    /// Record phase changes and recreated containers in `state.json`.
+    pub fn with_state_dir(mut self, state_dir: PathBuf) -> Self {
+        self.state_dir = Some(state_dir);
+        self
+    }
+
+    /// Broadcast phase changes as service status events.
+    pub fn with_events(mut self, events_tx: Option<broadcast::Sender<TelemetryEvent>>) -> Self {
+        self.events_tx = events_tx;
+        self
+    }
+
+    /// Template variables for init scripts that run again on a recreate.
+    pub fn with_template_vars(mut self, vars: HashMap<String, String>) -> Self {
+        self.template_vars = vars;
+        self
+    }
+
+    /// Run `callback` after each successful recreate, e.g. to reattach log streams.
+    pub fn on_restart(mut self, callback: OnRestart) -> Self {
+        self.on_restart = Some(callback);
+        self
+    }
+
+    /// Poll the container until cancelled, giving up once the restart
+    /// budget is exhausted or the container exits without needing a restart.
+    pub async fn run(mut self) {
+        let mut budget = RestartBudget::default();
+        let mut unhealthy = false;
+        let mut health = None;
+
+        loop {
+            tokio::select! {
+                _ = self.cancel.cancelled() => return,
+                _ = tokio::time::sleep(POLL_INTERVAL) => {}
+            }
+
+            let (status, native_health) =
+                match container_status_and_health(self.manager.docker(), &self.state.container_id)
+                    .await
+                {
+                    Ok(observed) => observed,
+                    Err(e) => {
+                        debug!(docker = %self.name, error = %e, "container inspect failed");
+                        continue;
+                    }
+                };
+
+            // Shutdown cancels before stopping containers; never fight it.
+            if self.cancel.is_cancelled() {
+                return;
+            }
+
+            if health != Some(native_health) {
+                health = Some(native_health);
+                self.set_health(native_health);
+            }
+
+            if status == ContainerStatus::Unhealthy && !unhealthy {
+                unhealthy = true;
+                self.set_phase("unhealthy");
+            } else if status == ContainerStatus::Running && unhealthy {
+                unhealthy = false;
+                self.set_phase("running");
+            }
+
+            if !needs_restart(&self.policy.mode, status) {
+                if matches!(
+                    status,
+                    ContainerStatus::Exited(_) | ContainerStatus::Missing
+                ) {
+                    debug!(docker = %self.name, ?status, "container exited; not restarting");
+                    self.set_phase("exited");
+                    return;
+                }
+                continue;
+            }
+
+            let Some(delay) = budget.next_delay(&self.policy, Instant::now()) else {
+                error!(
+                    docker = %self.name,
+                    max_restarts = self.policy.max_restarts,
+                    "exceeded max restarts; giving up"
+                );
+                self.set_phase("failed");
+                return;
+            };
+
+            warn!(
+                docker = %self.name,
+                ?status,
+                attempt = budget.count,
+                delay_ms = delay.as_millis() as u64,
+                "container needs restart; recreating after backoff"
+            );
+            self.set_phase("restarting");
+
+            tokio::select! {
+                _ = self.cancel.cancelled() => return,
+                _ = tokio::time::sleep(delay) => {}
+            }
+
+            let result = self
+                .manager
+                .start_service(
+                    &self.name,
+                    &self.config,
+                    Some(&self.state),
+                    &mut HashSet::new(),
+                    &self.config_dir,
+                    &self.template_vars,
+                )
+                .await;
+            match result {
+                Ok(new_state) => {
+                    self.state = new_state;
+                    self.state.phase = Some("running".to_string());
+                    unhealthy = false;
+                    health = None;
+                    if let Some(ref dir) = self.state_dir {
+                        ProjectState::update_docker_state(dir, &self.name, &self.state);
+                    }
+                    if let Some(ref callback) = self.on_restart {
+                        callback(&self.state);
+                    }
+                    self.set_phase("running");
+                }
+                Err(e) => {
+                    // The next poll sees the container missing or exited and
+                    // spends another attempt from the budget.
+                    warn!(docker = %self.name, error = %e, "failed to recreate container");
+                }
+            }
+        }
+    }
+
+    fn set_health(&self, health: NativeHealth) {
+        if let Some(ref dir) = self.state_dir {
+            ProjectState::update_docker_health(dir, &self.name, health.as_str());
+        }
+    }
+
+    fn set_phase(&self, phase: &str) {
+        if let Some(ref dir) = self.state_dir {
+            ProjectState::update_docker_phase(dir, &self.name, phase);
+        }
+        if let Some(ref tx) = self.events_tx {
+            let _ = tx.send(TelemetryEvent::ServiceStatusChange {
+                service: self.name.clone(),
+                status: phase.to_string(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn needs_restart_respects_mode() {
+        use ContainerStatus::*;
+
+        let always = RestartMode::Always;
+        let on_failure = RestartMode::OnFailure;
+        let never = RestartMode::Never;
+
+        assert!(!needs_restart(&always, Running));
+        assert!(needs_restart(&always, Exited(0)));
+        assert!(!needs_restart(&on_failure, Exited(0)));
+        assert!(needs_restart(&on_failure, Exited(137)));
+        assert!(needs_restart(&on_failure, Missing));
+        assert!(needs_restart(&on_failure, Unhealthy));
+        assert!(!needs_restart(&never, Exited(1)));
+        assert!(!needs_restart(&never, Unhealthy));
+    }
+
+    #[test]
+    fn restart_budget_stops_at_max_and_resets_after_stable_run() {
+        let policy = RestartPolicy {
+            max_restarts: 2,
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(1),
+            reset_after: Duration::from_secs(60),
+            ..RestartPolicy::default()
+        };
+        let mut budget = RestartBudget::default();
+        let start = Instant::now();
+
+        let first = budget.next_delay(&policy, start).unwrap();
+        assert!(first <= Duration::from_millis(100));
+        assert!(budget.next_delay(&policy, start).is_some());
+        assert!(budget.next_delay(&policy, start).is_none());
+
+        // Up for longer than reset_after since the last restart: budget refills.
+        let later = start + Duration::from_secs(61);
+        assert!(budget.next_delay(&policy, later).is_some());
+        assert_eq!(budget.count, 1);
+    }
+}
@@ -14,6 +14,19 @@ pub enum ResourceKind {
     ClusterDeploy,
 }
 
+impl ResourceKind {
+    /// Name used for this kind in CLI output.
+    pub fn label(self) -> &'static str {
+        match self {
+            ResourceKind::Service => "service",
+            ResourceKind::Docker => "docker",
+            ResourceKind::Compose => "compose",
+            ResourceKind::ClusterImage => "cluster-image",
+            ResourceKind::ClusterDeploy => "cluster-deploy",
+        }
+    }
+}
+
 /// A node in the unified dependency graph.
 #[derive(Debug, Clone)]
 pub struct ResourceNode {
@@ -102,7 +115,7 @@ impl DependencyResolver {
         // Add edges for docker depends_on
         for (name, docker_cfg) in &config.docker {
             let dependent_idx = node_map[name];
-            for dep in &docker_cfg.depends_on {
+            for dep in docker_cfg.depends_on.names() {
                 let dep_idx = node_map.get(dep).ok_or_else(|| {
                     format!(
                         "docker '{}' depends on '{}', which is not defined",
@@ -148,7 +161,7 @@ impl DependencyResolver {
         // Add edges for service depends_on
         for (name, svc) in &config.services {
             let dependent_idx = node_map[name];
-            for dep in &svc.depends_on {
+            for dep in svc.depends_on.names() {
                 let dep_idx = node_map.get(dep).ok_or_else(|| {
                     format!(
                         "service '{}' depends on '{}', which is not defined",
@@ -184,6 +197,64 @@ impl DependencyResolver {
         }
     }
 
+    /// Return a valid shutdown order: every service first, then the remaining
+    /// resources with dependents stopped before their dependencies.
+    ///
+    /// Services are always drained as a group before any infrastructure is
+    /// touched, so an app never loses its database mid-flush.
+    pub fn stop_order(&self) -> Result<Vec<(String, ResourceKind)>, String> {
+        let mut order = self.start_order()?;
+        order.reverse();
+        let (services, infra): (Vec<_>, Vec<_>) = order
+            .into_iter()
+            .partition(|(_, kind)| *kind == ResourceKind::Service);
+        Ok(services.into_iter().chain(infra).collect())
+    }
+
+    /// Group services into shutdown tiers: the first tier holds services
+    /// nothing else depends on, and each later tier holds services whose
+    /// dependents have all stopped in an earlier one. Dependencies through
+    /// docker, compose or cluster resources count too.
+    pub fn service_stop_tiers(&self) -> Result<Vec<Vec<String>>, String> {
+        let order = toposort(&self.graph, None).map_err(|cycle| {
+            format!(
+                "dependency cycle detected involving '{}'",
+                self.graph[cycle.node_id()].name
+            )
+        })?;
+
+        // Walk dependents before dependencies, so each node's tier is
+        // known by the time its dependencies look at it.
+        let mut tier: BTreeMap<NodeIndex, usize> = BTreeMap::new();
+        for &idx in order.iter().rev() {
+            let t = self
+                .graph
+                .neighbors(idx)
+                .map(|dependent| {
+                    let is_service = self.graph[dependent].kind == ResourceKind::Service;
+                    tier[&dependent] + usize::from(is_service)
+                })
+                .max()
+                .unwrap_or(0);
+            tier.insert(idx, t);
+        }
+
+        let mut tiers: Vec<Vec<String>> = Vec::new();
+        for &idx in &order {
+            let node = &self.graph[idx];
+            if node.kind != ResourceKind::Service {
+                continue;
+            }
+            let t = tier[&idx];
+            if tiers.len() <= t {
+                tiers.resize_with(t + 1, Vec::new);
+            }
+            tiers[t].push(node.name.clone());
+        }
+        tiers.retain(|t| !t.is_empty());
+        Ok(tiers)
+    }
+
     /// Return just the names in startup order (for backward compatibility).
     pub fn start_order_names(&self) -> Result<Vec<String>, String> {
         self.start_order()
@@ -201,7 +272,7 @@ mod tests {
     use super::*;
     use crate::config::model::{
         ClusterConfig, ClusterDeployConfig, ClusterImageConfig, ComposeConfig, DevrigConfig,
-        DockerConfig, ProjectConfig, ServiceConfig,
+        DockerConfig, ProjectConfig, ServiceConfig, StringOrList,
     };
 
     fn make_config(services: Vec<(&str, Vec<&str>)>) -> DevrigConfig {
@@ -210,6 +281,7 @@ mod tests {
             svc_map.insert(
                 name.to_string(),
                 ServiceConfig {
+                    enabled: Default::default(),
                     path: None,
                     command: "echo test".to_string(),
                     port: None,
@@ -217,14 +289,26 @@ mod tests {
                     env: BTreeMap::new(),
                     env_file: None,
                     depends_on: deps.into_iter().map(|d| d.to_string()).collect(),
+                    ready_check: None,
                     restart: None,
+                    shell: None,
+                    reload_strategy: None,
+                    reload_signal: None,
+                    max_log_lines_per_sec: None,
+                    pre_start: None,
+                    post_stop: None,
+                    log_format: None,
                 },
             );
         }
         DevrigConfig {
+            disabled: Default::default(),
             project: ProjectConfig {
                 name: "test".to_string(),
                 env_file: None,
+                shell_env_passthrough: Default::default(),
+                vars: BTreeMap::new(),
+                env_prefix: None,
             },
             services: svc_map,
             docker: BTreeMap::new(),
@@ -234,11 +318,14 @@ mod tests {
             env: BTreeMap::new(),
             network: None,
             links: BTreeMap::new(),
+            profiles: BTreeMap::new(),
+            secrets: BTreeMap::new(),
         }
     }
 
     fn make_infra(image: &str, deps: Vec<&str>) -> DockerConfig {
         DockerConfig {
+            enabled: Default::default(),
             image: image.to_string(),
             port: None,
             container_port: None,
@@ -252,6 +339,14 @@ mod tests {
             init: Vec::new(),
             depends_on: deps.into_iter().map(|s| s.to_string()).collect(),
             registry_auth: None,
+            pull_retries: None,
+            restart: None,
+            stop_timeout: None,
+            healthcheck: None,
+            mem_limit: None,
+            cpus: None,
+            network_aliases: Vec::new(),
+            extra_hosts: Vec::new(),
         }
     }
 
@@ -387,6 +482,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn map_form_depends_on_orders_every_condition() {
+        let config: DevrigConfig = toml::from_str(
+            r#"
+            [project]
+            name = "test"
+
+            [docker.postgres]
+            image = "postgres:16"
+
+            [docker.redis]
+            image = "redis:7"
+
+            [services.api]
+            command = "cargo run"
+            depends_on = { postgres = "healthy", redis = "started" }
+            "#,
+        )
+        .unwrap();
+        let resolver = DependencyResolver::from_config(&config).unwrap();
+        let order = resolver.start_order().unwrap();
+        assert_before(&order, "postgres", "api");
+        assert_before(&order, "redis", "api");
+    }
+
     #[test]
     fn service_depends_on_infra() {
         let mut config = make_config(vec![("api", vec!["postgres", "redis"])]);
@@ -409,10 +529,12 @@ mod tests {
     fn compose_nodes_before_dependent_services() {
         let mut config = make_config(vec![("api", vec!["redis"])]);
         config.compose = Some(ComposeConfig {
-            file: "docker-compose.yml".to_string(),
+            file: StringOrList(vec!["docker-compose.yml".to_string()]),
+            project_name: None,
             services: vec!["redis".to_string()],
             env_file: None,
             ready_checks: BTreeMap::new(),
+            protocols: BTreeMap::new(),
         });
 
         let resolver = DependencyResolver::from_config(&config).unwrap();
@@ -440,6 +562,55 @@ mod tests {
         assert_before(&order, "postgres", "api");
     }
 
+    #[test]
+    fn stop_order_drains_services_before_infra() {
+        let mut config = make_config(vec![("api", vec!["postgres"]), ("worker", vec!["api"])]);
+        config
+            .docker
+            .insert("postgres".to_string(), make_infra("postgres:16", vec!["vault"]));
+        config
+            .docker
+            .insert("vault".to_string(), make_infra("vault:1", vec![]));
+        config
+            .docker
+            .insert("redis".to_string(), make_infra("redis:7", vec![]));
+
+        let resolver = DependencyResolver::from_config(&config).unwrap();
+        let order = resolver.stop_order().unwrap();
+        assert_before(&order, "worker", "api");
+        assert_before(&order, "api", "postgres");
+        assert_before(&order, "postgres", "vault");
+        // Unrelated infra still waits for every service
+        assert_before(&order, "worker", "redis");
+        assert_before(&order, "api", "redis");
+    }
+
+    #[test]
+    fn service_stop_tiers_put_dependents_first() {
+        let mut config = make_config(vec![
+            ("api", vec!["postgres"]),
+            ("worker", vec!["api"]),
+            ("web", vec!["api"]),
+            ("auth", vec![]),
+            ("migrator", vec![]),
+        ]);
+        config.docker.insert(
+            "postgres".to_string(),
+            make_infra("postgres:16", vec!["migrator"]),
+        );
+
+        let resolver = DependencyResolver::from_config(&config).unwrap();
+        let mut tiers = resolver.service_stop_tiers().unwrap();
+        for tier in &mut tiers {
+            tier.sort();
+        }
+        // migrator only backs api through postgres, but still outlives it
+        assert_eq!(
+            tiers,
+            vec![vec!["auth", "web", "worker"], vec!["api"], vec!["migrator"]]
+        );
+    }
+
     #[test]
     fn infra_cycle_detected() {
         let mut config = make_config(vec![]);
@@ -457,12 +628,14 @@ mod tests {
 
     fn make_deploy(context: &str, manifests: &str, deps: Vec<&str>) -> ClusterDeployConfig {
         ClusterDeployConfig {
+            enabled: Default::default(),
             context: context.to_string(),
             dockerfile: "Dockerfile".to_string(),
             manifests: manifests.to_string(),
             watch: false,
             depends_on: deps.into_iter().map(|s| s.to_string()).collect(),
             build_secrets: BTreeMap::new(),
+            env: BTreeMap::new(),
         }
     }
 
@@ -481,6 +654,9 @@ mod tests {
             logs: None,
             registries: vec![],
             k3s_args: vec![],
+            k3s_image: None,
+            env: BTreeMap::new(),
+            command_retries: 3,
         });
 
         let resolver = DependencyResolver::from_config(&config).unwrap();
@@ -505,6 +681,9 @@ mod tests {
             logs: None,
             registries: vec![],
             k3s_args: vec![],
+            k3s_image: None,
+            env: BTreeMap::new(),
+            command_retries: 3,
         });
 
         let resolver = DependencyResolver::from_config(&config).unwrap();
@@ -538,6 +717,9 @@ mod tests {
             logs: None,
             registries: vec![],
             k3s_args: vec![],
+            k3s_image: None,
+            env: BTreeMap::new(),
+            command_retries: 3,
         });
 
         let resolver = DependencyResolver::from_config(&config).unwrap();
@@ -552,10 +734,12 @@ mod tests {
             .docker
             .insert("postgres".into(), make_infra("postgres:16", vec![]));
         config.compose = Some(ComposeConfig {
-            file: "docker-compose.yml".to_string(),
+            file: StringOrList(vec!["docker-compose.yml".to_string()]),
+            project_name: None,
             services: vec!["cache".to_string()],
             env_file: None,
             ready_checks: BTreeMap::new(),
+            protocols: BTreeMap::new(),
         });
         config.cluster = Some(ClusterConfig {
             name: None,
@@ -572,6 +756,9 @@ mod tests {
             logs: None,
             registries: vec![],
             k3s_args: vec![],
+            k3s_image: None,
+            env: BTreeMap::new(),
+            command_retries: 3,
         });
 
         let resolver = DependencyResolver::from_config(&config).unwrap();
@@ -611,6 +798,9 @@ mod tests {
             logs: None,
             registries: vec![],
             k3s_args: vec![],
+            k3s_image: None,
+            env: BTreeMap::new(),
+            command_retries: 3,
         });
 
         let resolver = DependencyResolver::from_config(&config).unwrap();
@@ -636,6 +826,9 @@ mod tests {
             logs: None,
             registries: vec![],
             k3s_args: vec![],
+            k3s_image: None,
+            env: BTreeMap::new(),
+            command_retries: 3,
         });
 
         let err = DependencyResolver::from_config(&config).unwrap_err();
@@ -653,10 +846,12 @@ mod tests {
             .docker
             .insert("postgres".into(), make_infra("postgres:16", vec![]));
         config.compose = Some(ComposeConfig {
-            file: "docker-compose.yml".to_string(),
+            file: StringOrList(vec!["docker-compose.yml".to_string()]),
+            project_name: None,
             services: vec!["cache".to_string()],
             env_file: None,
             ready_checks: BTreeMap::new(),
+            protocols: BTreeMap::new(),
         });
 
         let resolver = DependencyResolver::from_config(&config).unwrap();
@@ -674,6 +869,7 @@ mod tests {
 
     fn make_image(context: &str, deps: Vec<&str>) -> ClusterImageConfig {
         ClusterImageConfig {
+            enabled: Default::default(),
             context: context.to_string(),
             dockerfile: "Dockerfile".to_string(),
             watch: false,
@@ -698,6 +894,9 @@ mod tests {
             logs: None,
             registries: vec![],
             k3s_args: vec![],
+            k3s_image: None,
+            env: BTreeMap::new(),
+            command_retries: 3,
         });
 
         let resolver = DependencyResolver::from_config(&config).unwrap();
@@ -725,6 +924,9 @@ mod tests {
             logs: None,
             registries: vec![],
             k3s_args: vec![],
+            k3s_image: None,
+            env: BTreeMap::new(),
+            command_retries: 3,
         });
 
         let resolver = DependencyResolver::from_config(&config).unwrap();
@@ -761,6 +963,9 @@ mod tests {
             logs: None,
             registries: vec![],
             k3s_args: vec![],
+            k3s_image: None,
+            env: BTreeMap::new(),
+            command_retries: 3,
         });
 
         let resolver = DependencyResolver::from_config(&config).unwrap();
@@ -786,6 +991,9 @@ mod tests {
             logs: None,
             registries: vec![],
             k3s_args: vec![],
+            k3s_image: None,
+            env: BTreeMap::new(),
+            command_retries: 3,
         });
 
         let err = DependencyResolver::from_config(&config).unwrap_err();
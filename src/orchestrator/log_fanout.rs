@@ -0,0 +1,171 @@
+//! Receiving end of the supervisor log broadcast. A consumer that falls
+//! behind loses the oldest lines; rather than leaving silent gaps in the
+//! JSONL file and the dashboard, the loss is counted per service and
+//! reported as a `[devrig] dropped N log lines from <svc>` line.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::time::Duration;
+
+use tokio::sync::broadcast;
+use tokio::time::{interval_at, Instant, Interval, MissedTickBehavior};
+
+use crate::ui::logs::{LogLevel, LogLine};
+
+/// Lines the supervisor log broadcast holds before slow consumers lag.
+pub const DEFAULT_LOG_BUFFER: usize = 4096;
+
+/// How often lost lines are reported while the stream keeps flowing.
+const REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A broadcast receiver that turns `Lagged` errors into drop markers.
+pub struct LogFanoutReceiver {
+    rx: broadcast::Receiver<LogLine>,
+    /// Lines lost in the latest lag, not yet attributed to a service.
+    lagged: u64,
+    dropped: BTreeMap<String, u64>,
+    markers: VecDeque<LogLine>,
+    ticker: Interval,
+    closed: bool,
+}
+
+impl LogFanoutReceiver {
+    pub fn new(rx: broadcast::Receiver<LogLine>) -> Self {
+        let mut ticker = interval_at(Instant::now() + REPORT_INTERVAL, REPORT_INTERVAL);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        Self {
+            rx,
+            lagged: 0,
+            dropped: BTreeMap::new(),
+            markers: VecDeque::new(),
+            ticker,
+            closed: false,
+        }
+    }
+
+    /// The next log line or drop marker; `None` once every sender is gone
+    /// and outstanding drops have been reported.
+    pub async fn recv(&mut self) -> Option<LogLine> {
+        loop {
+            if let Some(marker) = self.markers.pop_front() {
+                return Some(marker);
+            }
+            if self.closed {
+                return None;
+            }
+            tokio::select! {
+                result = self.rx.recv() => match result {
+                    Ok(line) => {
+                        // The lost lines are the ones just ahead of this one;
+                        // under a flood they come from the service flooding.
+                        if self.lagged > 0 {
+                            *self.dropped.entry(line.service.clone()).or_default() +=
+                                std::mem::take(&mut self.lagged);
+                        }
+                        return Some(line);
+                    }
+                    Err(broadcast::error::RecvError::Lagged(n)) => self.lagged += n,
+                    Err(broadcast::error::RecvError::Closed) => {
+                        self.closed = true;
+                        self.queue_markers();
+                    }
+                },
+                _ = self.ticker.tick() => self.queue_markers(),
+            }
+        }
+    }
+
+    fn queue_markers(&mut self) {
+        for (service, n) in std::mem::take(&mut self.dropped) {
+            self.markers.push_back(drop_marker(service, n));
+        }
+        if self.lagged > 0 {
+            let n = std::mem::take(&mut self.lagged);
+            self.markers.push_back(drop_marker("devrig".to_string(), n));
+        }
+    }
+}
+
+fn drop_marker(service: String, n: u64) -> LogLine {
+    LogLine {
+        timestamp: chrono::Utc::now(),
+        text: format!(
+            "[devrig] dropped {} log lines from {} (log buffer full, see --log-buffer)",
+            n, service
+        ),
+        service,
+        is_stderr: true,
+        level: Some(LogLevel::Warn),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(service: &str, text: String) -> LogLine {
+        LogLine {
+            timestamp: chrono::Utc::now(),
+            service: service.to_string(),
+            text,
+            is_stderr: false,
+            level: None,
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn flooding_the_channel_reports_dropped_lines() {
+        let (tx, rx) = broadcast::channel(8);
+        let mut logs = LogFanoutReceiver::new(rx);
+        for i in 0..100 {
+            tx.send(line("noisy", i.to_string())).unwrap();
+        }
+        tx.send(line("quiet", "hello".to_string())).unwrap();
+
+        // The oldest lines are gone; what is left still arrives in order.
+        let first = logs.recv().await.unwrap();
+        assert_eq!(first.text, "93");
+
+        // Nothing more is sent, but the marker still shows up on the next
+        // report tick rather than waiting for the channel to close.
+        let mut received = vec![first];
+        loop {
+            let line = logs.recv().await.unwrap();
+            let is_marker = line.text.starts_with("[devrig] dropped");
+            received.push(line);
+            if is_marker {
+                break;
+            }
+        }
+        let marker = received.last().unwrap();
+        assert_eq!(marker.service, "noisy");
+        assert_eq!(marker.level, Some(LogLevel::Warn));
+        assert!(
+            marker
+                .text
+                .starts_with("[devrig] dropped 93 log lines from noisy"),
+            "{}",
+            marker.text
+        );
+        assert_eq!(received.len(), 9);
+
+        drop(tx);
+        assert!(logs.recv().await.is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn drops_are_reported_before_close() {
+        let (tx, rx) = broadcast::channel(2);
+        let mut logs = LogFanoutReceiver::new(rx);
+        for i in 0..5 {
+            tx.send(line("api", i.to_string())).unwrap();
+        }
+        drop(tx);
+
+        let mut texts = Vec::new();
+        while let Some(line) = logs.recv().await {
+            texts.push(line.text);
+        }
+        assert_eq!(texts.len(), 3, "{:?}", texts);
+        assert!(texts[2].starts_with("[devrig] dropped 3 log lines from api"));
+    }
+}
@@ -1,6 +1,15 @@
+pub mod control;
+pub mod detach;
+pub mod docker_watcher;
 pub mod graph;
+pub mod log_fanout;
+pub mod plan;
 pub mod ports;
+pub mod readiness;
 pub mod registry;
+pub mod reload;
+pub mod scale;
+pub mod service_ready;
 pub mod state;
 pub mod supervisor;
 
@@ -10,7 +19,8 @@ use std::sync::Arc;
 
 use anyhow::{bail, Context, Result};
 use chrono::Utc;
-use tokio::sync::broadcast;
+use futures_util::stream::{FuturesUnordered, StreamExt};
+use tokio::sync::{broadcast, mpsc};
 use tokio_util::sync::CancellationToken;
 use tokio_util::task::TaskTracker;
 use tracing::{debug, error, warn};
@@ -19,44 +29,90 @@ use crate::cluster::addon::PortForwardManager;
 use crate::cluster::K3dManager;
 use crate::compose;
 use crate::config;
-use crate::config::interpolate::{build_template_vars, resolve_config_templates};
-use crate::config::model::{DevrigConfig, Port};
-use crate::config::validate::validate;
+use crate::config::interpolate::{add_builtin_vars, build_template_vars, resolve_config_templates};
+use crate::config::model::{
+    DependencyCondition, DependsOn, DevrigConfig, LogFormat, Port, PortProtocol, ServiceConfig,
+    StringOrList,
+};
+use crate::config::validate::{validate, validate_compose_files};
 use crate::discovery::env::build_service_env;
+use crate::discovery::url::service_url;
 use crate::platform;
 use crate::identity::ProjectIdentity;
 use crate::docker::DockerManager;
 use crate::ui::logs::LogLine;
-use crate::ui::summary::{print_startup_banner, print_startup_summary, RunningService, StartupBannerInfo};
+use crate::ui::summary::{
+    print_startup_banner, print_startup_summary, print_startup_summary_json, startup_summary_json,
+    write_summary_file, RunningService, StartupBannerInfo, SummaryFormat, SUMMARY_FILE,
+};
 
 use graph::{DependencyResolver, ResourceKind};
-use ports::{check_all_ports_unified, check_port_available, find_free_port_excluding, format_port_conflicts, resolve_port};
+use log_fanout::{LogFanoutReceiver, DEFAULT_LOG_BUFFER};
+use ports::{check_all_ports_unified, check_port_available, find_free_port_excluding, find_free_port_in_range, format_port_conflicts, resolve_port, AllocatedPorts, PortRangeExhausted};
+use readiness::ReadinessGates;
 use registry::{InstanceEntry, InstanceRegistry};
 use state::{
-    ClusterDeployState, ClusterState, ComposeServiceState, DockerState, ProjectState, ServiceState,
+    ClusterDeployState, ClusterState, ComposeServiceState, DockerState, LoadedState, ProjectState,
+    ServiceState,
 };
-use supervisor::{RestartPolicy, ServiceSupervisor};
+use control::ServiceControlError;
+use supervisor::{RestartPolicy, ServiceSupervisor, POST_STOP_TIMEOUT};
+
+/// Ports of the dashboard, docker and compose resources started so far,
+/// keyed the way [`build_template_vars`] expects.
+pub(crate) fn started_ports(
+    dashboard: Option<&state::DashboardState>,
+    docker: &BTreeMap<String, DockerState>,
+    compose: &BTreeMap<String, ComposeServiceState>,
+) -> HashMap<String, u16> {
+    let mut ports = HashMap::new();
+    if let Some(ds) = dashboard {
+        ports.insert("dashboard".to_string(), ds.dashboard_port);
+        ports.insert("otel-grpc".to_string(), ds.grpc_port);
+        ports.insert("otel-http".to_string(), ds.http_port);
+    }
+    for (name, state) in docker {
+        if let Some(port) = state.port {
+            ports.insert(format!("docker:{}", name), port);
+        }
+        for (pname, &port) in &state.named_ports {
+            ports.insert(format!("docker:{}:{}", name, pname), port);
+        }
+    }
+    for (name, state) in compose {
+        if let Some(port) = state.port {
+            ports.insert(format!("compose:{}", name), port);
+        }
+    }
+    ports
+}
 
 /// Resolve a dashboard/OTel port: use the configured port if available,
 /// otherwise auto-assign a free one. Tracks in `allocated` to avoid collisions.
-fn resolve_dashboard_port(port_config: &Port, label: &str, allocated: &mut HashSet<u16>) -> u16 {
+fn resolve_dashboard_port(
+    port_config: &Port,
+    label: &str,
+    allocated: &mut AllocatedPorts,
+) -> Result<u16, PortRangeExhausted> {
+    let tcp = PortProtocol::Tcp;
     match port_config {
         Port::Fixed(preferred) => {
-            if !allocated.contains(preferred) && check_port_available(*preferred) {
-                allocated.insert(*preferred);
-                *preferred
+            if !allocated.contains(&(*preferred, tcp)) && check_port_available(*preferred) {
+                allocated.insert((*preferred, tcp));
+                Ok(*preferred)
             } else {
-                let port = find_free_port_excluding(allocated);
+                let port = find_free_port_excluding(allocated, tcp);
                 warn!("{label}: port {preferred} in use, using {port} instead");
-                allocated.insert(port);
-                port
+                allocated.insert((port, tcp));
+                Ok(port)
             }
         }
         Port::Auto => {
-            let port = find_free_port_excluding(allocated);
-            allocated.insert(port);
-            port
+            let port = find_free_port_excluding(allocated, tcp);
+            allocated.insert((port, tcp));
+            Ok(port)
         }
+        Port::Range { .. } => resolve_port(label, port_config, tcp, None, false, allocated),
     }
 }
 
@@ -80,6 +136,18 @@ pub struct Orchestrator {
     cancel: CancellationToken,
     tracker: TaskTracker,
     port_forward_mgr: Option<PortForwardManager>,
+    summary_format: SummaryFormat,
+    max_log_rate: Option<u32>,
+    log_buffer: usize,
+    force_rebuild: bool,
+    no_pull: bool,
+    /// Running as the background child of `devrig start --detach`.
+    detached_child: bool,
+    /// Shut everything down when a service fails for good.
+    exit_on_failure: bool,
+    /// Values that came from `.env` files, masked wherever config is printed.
+    /// `devrig reload-env` adds the values it reads.
+    secrets: std::sync::Mutex<config::secrets::SecretRegistry>,
 }
 
 impl Orchestrator {
@@ -93,7 +161,7 @@ impl Orchestrator {
         let config_path = config_path
             .canonicalize()
             .with_context(|| format!("canonicalizing config path {}", config_path.display()))?;
-        let (config, source, _secret_registry) = config::load_config_with_secrets(&config_path)
+        let (config, source, secrets) = config::load_config_with_secrets(&config_path)
             .with_context(|| format!("loading config from {}", config_path.display()))?;
 
         let filename = config_path
@@ -101,7 +169,16 @@ impl Orchestrator {
             .map(|f| f.to_string_lossy().to_string())
             .unwrap_or_else(|| "devrig.toml".to_string());
 
-        if let Err(errors) = validate(&config, &source, &filename) {
+        let config_dir = config_path.parent().unwrap_or_else(|| std::path::Path::new("."));
+        let mut errors = validate(&config, &source, &filename)
+            .err()
+            .unwrap_or_default();
+        errors.extend(
+            validate_compose_files(&config, &source, &filename, config_dir)
+                .err()
+                .unwrap_or_default(),
+        );
+        if !errors.is_empty() {
             let mut msg = String::from("Configuration errors:\n");
             for err in &errors {
                 msg.push_str(&format!("  - {}\n", err));
@@ -125,39 +202,143 @@ impl Orchestrator {
             cancel: CancellationToken::new(),
             tracker: TaskTracker::new(),
             port_forward_mgr: None,
+            summary_format: SummaryFormat::default(),
+            max_log_rate: None,
+            log_buffer: DEFAULT_LOG_BUFFER,
+            force_rebuild: false,
+            no_pull: false,
+            detached_child: false,
+            exit_on_failure: false,
+            secrets: std::sync::Mutex::new(secrets),
         })
     }
 
-    /// Start services according to the configuration.
-    ///
-    /// If `service_filter` is non-empty, only the named services (plus their
-    /// transitive dependencies including docker/compose) are started.
-    pub async fn start(&mut self, service_filter: Vec<String>, dev_mode: bool) -> Result<()> {
-        // ================================================================
-        // Phase 0: Parse, validate, resolve dependencies, load prev state
-        // ================================================================
+    /// Choose how the startup summary is printed once everything is running.
+    pub fn with_summary_format(mut self, format: SummaryFormat) -> Self {
+        self.summary_format = format;
+        self
+    }
+
+    /// Cap log entries per second for services that don't set
+    /// `max_log_lines_per_sec` themselves.
+    pub fn with_max_log_rate(mut self, max_per_sec: Option<u32>) -> Self {
+        self.max_log_rate = max_per_sec;
+        self
+    }
+
+    /// Lines the service log stream buffers for the JSONL writer and the
+    /// dashboard before the oldest are dropped.
+    pub fn with_log_buffer(mut self, lines: Option<usize>) -> Self {
+        self.log_buffer = lines.unwrap_or(DEFAULT_LOG_BUFFER);
+        self
+    }
+
+    /// Rebuild every `[cluster.image]` on start, even when its build context
+    /// is unchanged since the last start.
+    pub fn with_force_rebuild(mut self, force: bool) -> Self {
+        self.force_rebuild = force;
+        self
+    }
+
+    /// Use only locally present images for docker and compose resources.
+    pub fn with_no_pull(mut self, no_pull: bool) -> Self {
+        self.no_pull = no_pull;
+        self
+    }
+
+    /// Mark this as the child spawned by `devrig start --detach`: once
+    /// startup completes it releases the waiting parent and writes the rest of
+    /// its output to `.devrig/devrig.log`.
+    pub fn with_detached_child(mut self, detached_child: bool) -> Self {
+        self.detached_child = detached_child;
+        self
+    }
+
+    /// Stop everything once a service has failed and will not be restarted;
+    /// `start` then returns that service's [`ServiceFailure`].
+    pub fn with_exit_on_failure(mut self, exit_on_failure: bool) -> Self {
+        self.exit_on_failure = exit_on_failure;
+        self
+    }
+
+    /// Override the timeout of every service and docker ready check, e.g. to
+    /// give slow machines more headroom.
+    pub fn with_ready_timeout(mut self, secs: Option<u64>) -> Self {
+        let Some(secs) = secs else {
+            return self;
+        };
+        let checks = self
+            .config
+            .services
+            .values_mut()
+            .filter_map(|svc| svc.ready_check.as_mut())
+            .chain(
+                self.config
+                    .docker
+                    .values_mut()
+                    .filter_map(|docker| docker.ready_check.as_mut()),
+            );
+        for check in checks {
+            check.set_timeout(secs);
+        }
+        self
+    }
+
+    /// Resolve what `start` launches, in dependency order: every resource,
+    /// or the requested services / `profile` resources plus their transitive
+    /// dependencies.
+    fn resolve_launch_order(
+        &self,
+        service_filter: Vec<String>,
+        profile: Option<String>,
+    ) -> Result<Vec<(String, ResourceKind)>> {
         let resolver =
             DependencyResolver::from_config(&self.config).map_err(|e| anyhow::anyhow!("{}", e))?;
         let full_order = resolver
             .start_order()
             .map_err(|e| anyhow::anyhow!("{}", e))?;
 
-        let prev_state = ProjectState::load(&self.state_dir);
+        let service_filter = match profile {
+            Some(profile) => {
+                if !service_filter.is_empty() {
+                    bail!("--profile cannot be combined with an explicit service list");
+                }
+                match self.config.profiles.get(&profile) {
+                    Some(resources) if resources.is_empty() => {
+                        bail!("profile '{}' lists no resources", profile)
+                    }
+                    Some(resources) => resources.clone(),
+                    None => bail!(
+                        "unknown profile '{}' (available: {:?})",
+                        profile,
+                        self.config.profiles.keys().collect::<Vec<_>>()
+                    ),
+                }
+            }
+            None => {
+                // Globs expand first; dependencies are pulled in below.
+                let service_filter =
+                    config::select::expand_patterns(&service_filter, self.config.services.keys())?;
+                for name in &service_filter {
+                    if self.config.disabled.contains(name) {
+                        bail!("service '{}' is disabled by its `enabled` setting", name);
+                    }
+                    if !self.config.services.contains_key(name) {
+                        bail!(
+                            "unknown service '{}' (available: {:?})",
+                            name,
+                            self.config.services.keys().collect::<Vec<_>>()
+                        );
+                    }
+                }
+                service_filter
+            }
+        };
 
-        // Filter to requested services + transitive deps (across all resource types)
+        // Filter to requested resources + transitive deps (across all resource types)
         let launch_order = if service_filter.is_empty() {
             full_order
         } else {
-            for name in &service_filter {
-                if !self.config.services.contains_key(name) {
-                    bail!(
-                        "unknown service '{}' (available: {:?})",
-                        name,
-                        self.config.services.keys().collect::<Vec<_>>()
-                    );
-                }
-            }
-
             let mut needed: HashSet<String> = service_filter.iter().cloned().collect();
             let mut changed = true;
             while changed {
@@ -165,15 +346,15 @@ impl Orchestrator {
                 let snapshot: Vec<String> = needed.iter().cloned().collect();
                 for name in &snapshot {
                     if let Some(svc) = self.config.services.get(name) {
-                        for dep in &svc.depends_on {
-                            if needed.insert(dep.clone()) {
+                        for dep in svc.depends_on.names() {
+                            if needed.insert(dep.to_string()) {
                                 changed = true;
                             }
                         }
                     }
                     if let Some(docker_cfg) = self.config.docker.get(name) {
-                        for dep in &docker_cfg.depends_on {
-                            if needed.insert(dep.clone()) {
+                        for dep in docker_cfg.depends_on.names() {
+                            if needed.insert(dep.to_string()) {
                                 changed = true;
                             }
                         }
@@ -202,6 +383,27 @@ impl Orchestrator {
                 .filter(|(name, _)| needed.contains(name))
                 .collect()
         };
+        Ok(launch_order)
+    }
+
+    /// Start services according to the configuration.
+    ///
+    /// If `service_filter` is non-empty, only the named services (plus their
+    /// transitive dependencies including docker/compose) are started. Names
+    /// may be globs like `api-*`, matched against `[services]`. A
+    /// `profile` selects the resources listed under `[profiles.<name>]`
+    /// instead and cannot be combined with `service_filter`.
+    pub async fn start(
+        &mut self,
+        service_filter: Vec<String>,
+        profile: Option<String>,
+        dev_mode: bool,
+    ) -> Result<()> {
+        // ================================================================
+        // Phase 0: Parse, validate, resolve dependencies, load prev state
+        // ================================================================
+        let launch_order = self.resolve_launch_order(service_filter, profile)?;
+        let prev_state = ProjectState::load(&self.state_dir);
 
         let dashboard_enabled = self
             .config
@@ -228,8 +430,12 @@ impl Orchestrator {
         std::fs::write(&pid_path, std::process::id().to_string())
             .with_context(|| format!("writing PID file {}", pid_path.display()))?;
 
-        // Print startup banner
-        {
+        // Register before any container is created, so `devrig doctor`
+        // never takes this project's containers for orphans.
+        self.register_instance();
+
+        // Print startup banner (JSON summaries keep stdout machine-readable)
+        if self.summary_format == SummaryFormat::Table {
             let banner_services: Vec<String> = launch_order
                 .iter()
                 .filter(|(_, k)| matches!(k, ResourceKind::Service))
@@ -241,7 +447,7 @@ impl Orchestrator {
                 .map(|(n, _)| n.clone())
                 .collect();
             let banner_compose = self.config.compose.as_ref().map(|c| {
-                c.file.clone()
+                c.file.as_slice().join(", ")
             });
             let banner_addons: Vec<String> = self
                 .config
@@ -254,6 +460,12 @@ impl Orchestrator {
                 services: banner_services,
                 docker: banner_docker,
                 compose: banner_compose,
+                cluster: self.config.cluster.is_some(),
+                cluster_image: self
+                    .config
+                    .cluster
+                    .as_ref()
+                    .and_then(|c| c.k3s_image.clone()),
                 cluster_addons: banner_addons,
                 dashboard_enabled,
             };
@@ -273,13 +485,17 @@ impl Orchestrator {
         // ================================================================
         // Phase 0.5: Dashboard + OTel collector (start early to capture all telemetry)
         // ================================================================
-        let mut allocated_ports: HashSet<u16> = HashSet::new();
+        let mut allocated_ports = AllocatedPorts::new();
         let mut dashboard_state: Option<state::DashboardState> = None;
         let mut _otel_collector: Option<crate::otel::OtelCollector> = None;
         // Clones of store/events for the log bridge (used later for docker + service logs)
         let mut bridge_store: Option<Arc<tokio::sync::RwLock<crate::otel::storage::TelemetryStore>>> = None;
         let mut bridge_events_tx: Option<broadcast::Sender<crate::otel::types::TelemetryEvent>> = None;
 
+        // Requests from `devrig restart` & co. (via the control socket) and
+        // from the dashboard, served once startup completes.
+        let (control_tx, mut control_rx) = mpsc::channel::<control::ControlMessage>(8);
+
         if dashboard_enabled {
             let dash_config = self.config.dashboard.as_ref().unwrap();
             let otel_config = dash_config.otel.clone().unwrap_or_default();
@@ -287,16 +503,46 @@ impl Orchestrator {
             // Auto-resolve dashboard/OTel ports: use configured port if free,
             // otherwise find an available one. This lets multiple devrig instances
             // run without port conflicts.
-            let dash_port = resolve_dashboard_port(&dash_config.port, "dashboard", &mut allocated_ports);
-            let otel_grpc = resolve_dashboard_port(&otel_config.grpc_port, "otel-grpc", &mut allocated_ports);
-            let otel_http = resolve_dashboard_port(&otel_config.http_port, "otel-http", &mut allocated_ports);
+            let dash_port = resolve_dashboard_port(&dash_config.port, "dashboard", &mut allocated_ports)?;
+            let otel_grpc = resolve_dashboard_port(&otel_config.grpc_port, "otel-grpc", &mut allocated_ports)?;
+            let otel_http = resolve_dashboard_port(&otel_config.http_port, "otel-http", &mut allocated_ports)?;
 
             // Use resolved ports for the collector
             let mut resolved_otel = otel_config;
             resolved_otel.grpc_port = Port::Fixed(otel_grpc);
             resolved_otel.http_port = Port::Fixed(otel_http);
 
-            let collector = crate::otel::OtelCollector::new(&resolved_otel);
+            // `[dashboard.tls]`: a bad cert or key fails startup rather than
+            // quietly serving plain HTTP.
+            let tls_files = dash_config.tls.as_ref().and_then(|tls| {
+                tls.resolved_files(
+                    self.config_path
+                        .parent()
+                        .unwrap_or_else(|| std::path::Path::new(".")),
+                )
+            });
+            let tls_identity = tls_files
+                .as_ref()
+                .map(|(cert, key)| crate::tls::TlsIdentity::load(cert, key))
+                .transpose()
+                .context("loading [dashboard.tls]")?;
+            let otel_tls =
+                tls_identity.is_some() && dash_config.tls.as_ref().is_some_and(|tls| tls.otel);
+
+            let mut collector = crate::otel::OtelCollector::new(&resolved_otel);
+            if otel_tls {
+                collector.enable_tls(tls_identity.clone().unwrap());
+            }
+            if resolved_otel.wal {
+                let wal_dir = crate::otel::wal::wal_dir(&self.state_dir);
+                let max_bytes = resolved_otel.wal_max_mb.saturating_mul(1024 * 1024);
+                match collector.enable_wal(&wal_dir, max_bytes).await {
+                    Ok(loaded) => debug!(records = loaded, "telemetry WAL replayed"),
+                    Err(e) => {
+                        warn!(error = %e, "telemetry WAL unavailable; not persisting telemetry")
+                    }
+                }
+            }
             collector
                 .start(self.cancel.clone())
                 .await
@@ -317,6 +563,10 @@ impl Orchestrator {
             let dash_cancel = self.cancel.clone();
             let dash_config_path = Some(self.config_path.clone());
             let dash_state_dir = Some(self.state_dir.clone());
+            let dash_title = dash_config
+                .title_or(&self.config.project.name)
+                .to_string();
+            let dash_control = control_tx.clone();
             self.tracker.spawn(async move {
                 if let Err(e) = crate::dashboard::server::start_dashboard_server(
                     dash_port,
@@ -325,6 +575,9 @@ impl Orchestrator {
                     dash_cancel,
                     dash_config_path,
                     dash_state_dir,
+                    dash_title,
+                    tls_identity,
+                    Some(dash_control),
                 )
                 .await
                 {
@@ -337,6 +590,8 @@ impl Orchestrator {
                 dashboard_port: dash_port,
                 grpc_port: otel_grpc,
                 http_port: otel_http,
+                tls_cert: tls_files.map(|(cert, _)| cert),
+                otel_tls,
             });
             _otel_collector = Some(collector);
         }
@@ -345,7 +600,9 @@ impl Orchestrator {
         // Phase 1: Docker network
         // ================================================================
         let docker_mgr = if has_docker {
-            let mgr = DockerManager::new(self.identity.slug.clone()).await?;
+            let mgr = DockerManager::new(self.identity.slug.clone())
+                .await?
+                .with_no_pull(self.no_pull);
             mgr.ensure_network().await?;
             debug!(network = %mgr.network_name(), "Docker network ensured");
             Some(mgr)
@@ -361,11 +618,11 @@ impl Orchestrator {
         let mut compose_states: BTreeMap<String, ComposeServiceState> = BTreeMap::new();
 
         if let Some(compose_config) = &self.config.compose {
-            let compose_file = self
-                .config_path
-                .parent()
-                .unwrap_or_else(|| std::path::Path::new("."))
-                .join(&compose_config.file);
+            let compose_files = compose_config.files(
+                self.config_path
+                    .parent()
+                    .unwrap_or_else(|| std::path::Path::new(".")),
+            );
 
             let compose_services: Vec<String> = launch_order
                 .iter()
@@ -375,16 +632,18 @@ impl Orchestrator {
 
             if !compose_services.is_empty() {
                 debug!(services = ?compose_services, "starting compose services");
+                let project_name = compose_config.project_name(&self.identity.slug);
                 compose::lifecycle::compose_up(
-                    &compose_file,
-                    &self.identity.slug,
+                    &compose_files,
+                    project_name,
                     &compose_services,
                     compose_config.env_file.as_deref(),
+                    self.no_pull,
                 )
                 .await?;
 
                 let containers =
-                    compose::lifecycle::compose_ps(&compose_file, &self.identity.slug).await?;
+                    compose::lifecycle::compose_ps(&compose_files, project_name).await?;
 
                 // Bridge compose containers to the devrig network
                 if let Some(mgr) = &docker_mgr {
@@ -438,10 +697,40 @@ impl Orchestrator {
         // Pre-populate allocated ports from compose services
         for cs in compose_states.values() {
             if let Some(port) = cs.port {
-                allocated_ports.insert(port);
+                allocated_ports.insert((port, PortProtocol::Tcp));
             }
         }
 
+        // Every docker container and service gets a readiness gate that its
+        // dependents wait on, whatever their kind.
+        let mut gates = ReadinessGates::default();
+
+        // Docker resources that something else waits on to be healthy. When
+        // these have no ready_check, their native HEALTHCHECK settles their
+        // gate instead. `started` dependencies don't wait for it.
+        let docker_dependencies: HashSet<&str> = self
+            .config
+            .services
+            .values()
+            .flat_map(|s| s.depends_on.iter())
+            .chain(
+                self.config
+                    .docker
+                    .values()
+                    .flat_map(|d| d.depends_on.iter()),
+            )
+            .filter(|dep| dep.condition == DependencyCondition::Healthy)
+            .map(|dep| dep.name.as_str())
+            .chain(self.config.cluster.iter().flat_map(|c| {
+                c.images
+                    .values()
+                    .flat_map(|i| i.depends_on.iter())
+                    .chain(c.deploy.values().flat_map(|d| d.depends_on.iter()))
+                    .map(String::as_str)
+            }))
+            .filter(|dep| self.config.docker.contains_key(*dep))
+            .collect();
+
         for (name, kind) in &launch_order {
             if *kind != ResourceKind::Docker {
                 continue;
@@ -456,16 +745,64 @@ impl Orchestrator {
 
             let prev_docker = prev_state.as_ref().and_then(|s| s.docker.get(name));
 
-            debug!(docker = %name, image = %docker_config.image, "starting docker service");
-
-            let state = docker_mgr
+            let mgr = docker_mgr
                 .as_ref()
-                .expect("docker_mgr must exist when docker resources are present")
-                .start_service(name, &docker_config, prev_docker, &mut allocated_ports, &config_dir)
+                .expect("docker_mgr must exist when docker resources are present");
+            if let Err(e) = gates
+                .wait(ResourceKind::Docker, name, docker_config.depends_on.iter())
                 .await
-                .with_context(|| format!("starting docker service '{}'", name))?;
+            {
+                self.stop_started_containers(mgr, &docker_states).await;
+                return Err(e);
+            }
+
+            debug!(docker = %name, image = %docker_config.image, "starting docker service");
 
-            docker_states.insert(name.clone(), state);
+            // Init scripts see the project, the built-ins and the ports of
+            // everything started before this container.
+            let mut init_vars = build_template_vars(
+                &self.config,
+                &started_ports(dashboard_state.as_ref(), &docker_states, &compose_states),
+            );
+            for (name, state) in &compose_states {
+                if let Some(port) = state.port {
+                    init_vars.insert(format!("compose.{}.port", name), port.to_string());
+                }
+            }
+            add_builtin_vars(&mut init_vars);
+
+            let state = match mgr
+                .start_service(
+                    name,
+                    &docker_config,
+                    prev_docker,
+                    &mut allocated_ports,
+                    &config_dir,
+                    &init_vars,
+                )
+                .await
+                .with_context(|| format!("starting docker service '{}'", name))
+            {
+                Ok(state) => state,
+                Err(e) => {
+                    self.stop_started_containers(mgr, &docker_states).await;
+                    return Err(e);
+                }
+            };
+            docker_states.insert(name.clone(), state.clone());
+
+            // start_service already blocked on ready_check; without one, the
+            // image's own healthcheck settles the gate in the background.
+            gates.register(name);
+            gates.started(name);
+            if docker_config.ready_check.is_none() && docker_dependencies.contains(name.as_str()) {
+                let (mgr, docker_name) = (mgr.clone(), name.clone());
+                gates.track(name, async move {
+                    mgr.wait_for_native_health(&docker_name, &state).await
+                });
+            } else {
+                gates.ready(name);
+            }
 
             // Docker service passed ready checks — broadcast "running"
             if let Some(tx) = &bridge_events_tx {
@@ -498,6 +835,27 @@ impl Orchestrator {
         // ================================================================
 
         if let Some(cluster_config) = &self.config.cluster {
+            // Images and deploys wait for the docker containers they depend on.
+            let cluster_waits = cluster_config
+                .images
+                .iter()
+                .map(|(name, image)| (ResourceKind::ClusterImage, name, &image.depends_on))
+                .chain(
+                    cluster_config
+                        .deploy
+                        .iter()
+                        .map(|(name, deploy)| (ResourceKind::ClusterDeploy, name, &deploy.depends_on)),
+                );
+            for (kind, name, depends_on) in cluster_waits {
+                let depends_on: DependsOn = depends_on.iter().cloned().collect();
+                if let Err(e) = gates.wait(kind, name, depends_on.iter()).await {
+                    if let Some(mgr) = &docker_mgr {
+                        self.stop_started_containers(mgr, &docker_states).await;
+                    }
+                    return Err(e);
+                }
+            }
+
             let network = network_name
                 .as_deref()
                 .expect("network must exist when cluster is configured");
@@ -552,6 +910,13 @@ impl Orchestrator {
                     .get(name)
                     .ok_or_else(|| anyhow::anyhow!("cluster image '{}' not in config", name))?;
 
+                // Reuse the image from the last start if its context is unchanged
+                let previous = prev_state
+                    .as_ref()
+                    .and_then(|s| s.cluster.as_ref())
+                    .and_then(|c| c.deployed_services.get(name))
+                    .filter(|_| !self.force_rebuild);
+
                 debug!(image = %name, "building cluster image");
                 let state = crate::cluster::deploy::run_image_build(
                     name,
@@ -559,6 +924,7 @@ impl Orchestrator {
                     registry_port,
                     &config_dir,
                     &deployed,
+                    previous,
                     &self.cancel,
                 )
                 .await
@@ -567,16 +933,37 @@ impl Orchestrator {
                 deployed.insert(name.clone(), state);
             }
 
-            // Deploy cluster services in dependency order
+            // Deploy cluster services in dependency order. Each deploy's env
+            // is merged with [cluster].env and template-resolved here so the
+            // watchers below rebuild with the same values.
+            let deploy_template_vars = self.cluster_template_vars(
+                &deployed,
+                dashboard_state.as_ref(),
+                &docker_states,
+                &compose_states,
+            );
+            let mut resolved_deploys = cluster_config.deploy.clone();
             for (name, kind) in &launch_order {
                 if *kind != ResourceKind::ClusterDeploy {
                     continue;
                 }
 
-                let deploy_config = cluster_config
-                    .deploy
-                    .get(name)
+                let deploy_config = resolved_deploys
+                    .get_mut(name)
                     .ok_or_else(|| anyhow::anyhow!("cluster deploy '{}' not in config", name))?;
+                deploy_config.env = crate::config::interpolate::resolve_cluster_deploy_env(
+                    cluster_config,
+                    name,
+                    &deploy_template_vars,
+                )
+                .map_err(|errors| {
+                    let mut msg = String::from("Template resolution errors:\n");
+                    for err in &errors {
+                        msg.push_str(&format!("  - {}\n", err));
+                    }
+                    anyhow::anyhow!("{}", msg.trim_end())
+                })?;
+                let deploy_config = &*deploy_config;
 
                 debug!(deploy = %name, "deploying to cluster");
                 let state = crate::cluster::deploy::run_deploy(
@@ -585,6 +972,7 @@ impl Orchestrator {
                     registry_port,
                     k3d_mgr.kubeconfig_path(),
                     &config_dir,
+                    &self.state_dir,
                     &self.cancel,
                 )
                 .await
@@ -593,12 +981,51 @@ impl Orchestrator {
                 deployed.insert(name.clone(), state);
             }
 
+            // Remove env ConfigMaps left by deploys that were dropped from
+            // the config, no longer set env, or moved out of a namespace.
+            let stale_config_maps: Vec<(String, Vec<String>)> = prev_state
+                .as_ref()
+                .and_then(|s| s.cluster.as_ref())
+                .into_iter()
+                .flat_map(|cs| cs.deployed_services.iter())
+                .filter_map(|(name, prev)| {
+                    let config_map = prev.config_map.clone()?;
+                    let kept = deployed
+                        .get(name)
+                        .filter(|state| state.config_map.is_some())
+                        .map_or(&[][..], |state| state.namespaces.as_slice());
+                    // Older states did not record namespaces; their
+                    // ConfigMaps were created in the default one.
+                    let previous = if prev.namespaces.is_empty() {
+                        vec!["default".to_string()]
+                    } else {
+                        prev.namespaces.clone()
+                    };
+                    let stale: Vec<String> =
+                        previous.into_iter().filter(|ns| !kept.contains(ns)).collect();
+                    (!stale.is_empty()).then_some((config_map, stale))
+                })
+                .collect();
+            for (config_map, namespaces) in stale_config_maps {
+                if let Err(e) = crate::cluster::deploy::delete_env_config_map(
+                    &config_map,
+                    &namespaces,
+                    k3d_mgr.kubeconfig_path(),
+                    &self.cancel,
+                )
+                .await
+                {
+                    warn!(config_map = %config_map, error = %e, "failed to delete stale ConfigMap");
+                }
+            }
+
             // Start file watchers for watch=true deploys
             crate::cluster::watcher::start_watchers(
-                &cluster_config.deploy,
+                &resolved_deploys,
                 registry_port,
                 k3d_mgr.kubeconfig_path().to_path_buf(),
                 config_dir.clone(),
+                self.state_dir.clone(),
                 self.cancel.clone(),
                 &self.tracker,
             )
@@ -643,6 +1070,7 @@ impl Orchestrator {
                         crate::config::model::AddonConfig::Manifest {
                             path: manifest_path.to_string_lossy().to_string(),
                             namespace: None,
+                            create_namespace: true,
                             port_forward: BTreeMap::new(),
                             depends_on: vec![],
                         },
@@ -652,75 +1080,19 @@ impl Orchestrator {
             }
 
             // Install addons (helm charts, manifests, kustomize)
-            let installed_addons = if !combined_addons.is_empty() {
+            let mut installed_addons = if !combined_addons.is_empty() {
                 debug!(
                     count = combined_addons.len(),
                     "installing cluster addons"
                 );
 
-                // Build template vars available at addon-install time:
-                // cluster images, registry, project name, and any ports
-                // already resolved (dashboard, docker, compose, fixed
-                // service ports).
-                let mut addon_template_vars =
-                    crate::config::interpolate::build_cluster_image_vars(&deployed);
-                if cluster_config.registry {
-                    addon_template_vars.insert(
-                        "cluster.registry".to_string(),
-                        format!("k3d-devrig-{}-reg:5000", self.identity.slug),
-                    );
-                }
-
-                addon_template_vars.insert(
-                    "project.name".to_string(),
-                    self.config.project.name.clone(),
+                let addon_template_vars = self.cluster_template_vars(
+                    &deployed,
+                    dashboard_state.as_ref(),
+                    &docker_states,
+                    &compose_states,
                 );
 
-                // Fixed service ports (available before service launch)
-                for (name, svc) in &self.config.services {
-                    if let Some(crate::config::model::Port::Fixed(port)) = &svc.port {
-                        addon_template_vars
-                            .insert(format!("services.{name}.port"), port.to_string());
-                    }
-                }
-
-                // Dashboard / OTel ports
-                if let Some(ref ds) = dashboard_state {
-                    addon_template_vars
-                        .insert("dashboard.port".to_string(), ds.dashboard_port.to_string());
-                    addon_template_vars.insert(
-                        "dashboard.otel.grpc_port".to_string(),
-                        ds.grpc_port.to_string(),
-                    );
-                    addon_template_vars.insert(
-                        "dashboard.otel.http_port".to_string(),
-                        ds.http_port.to_string(),
-                    );
-                }
-
-                // Docker ports
-                for (name, state) in &docker_states {
-                    if let Some(port) = state.port {
-                        addon_template_vars
-                            .insert(format!("docker.{name}.port"), port.to_string());
-                    }
-                    for (pname, &port) in &state.named_ports {
-                        let val = port.to_string();
-                        addon_template_vars
-                            .insert(format!("docker.{name}.ports.{pname}"), val.clone());
-                        addon_template_vars
-                            .insert(format!("docker.{name}.port_{pname}"), val);
-                    }
-                }
-
-                // Compose ports
-                for (name, state) in &compose_states {
-                    if let Some(port) = state.port {
-                        addon_template_vars
-                            .insert(format!("compose.{name}.port"), port.to_string());
-                    }
-                }
-
                 crate::cluster::addon::install_addons(
                     &combined_addons,
                     &addon_template_vars,
@@ -736,7 +1108,16 @@ impl Orchestrator {
 
             // Start port-forwards for addons
             let pf_mgr = PortForwardManager::new();
-            pf_mgr.start_port_forwards(&cluster_config.addons, k3d_mgr.kubeconfig_path());
+            let port_forwards = pf_mgr.start_port_forwards(
+                &cluster_config.addons,
+                k3d_mgr.kubeconfig_path(),
+                &mut allocated_ports,
+            );
+            for (name, forwards) in port_forwards {
+                if let Some(addon_state) = installed_addons.get_mut(&name) {
+                    addon_state.port_forwards = forwards;
+                }
+            }
             self.port_forward_mgr = Some(pf_mgr);
 
             let registry_name = if cluster_config.registry {
@@ -774,31 +1155,9 @@ impl Orchestrator {
         // ================================================================
         // Phase 4: Resolve ports, templates, and env vars
         // ================================================================
-        let mut resolved_ports: HashMap<String, u16> = HashMap::new();
-
-        // Dashboard/OTel resolved ports (for template interpolation)
-        if let Some(ref ds) = dashboard_state {
-            resolved_ports.insert("dashboard".to_string(), ds.dashboard_port);
-            resolved_ports.insert("otel-grpc".to_string(), ds.grpc_port);
-            resolved_ports.insert("otel-http".to_string(), ds.http_port);
-        }
-
-        // Docker ports
-        for (name, state) in &docker_states {
-            if let Some(port) = state.port {
-                resolved_ports.insert(format!("docker:{}", name), port);
-            }
-            for (pname, &port) in &state.named_ports {
-                resolved_ports.insert(format!("docker:{}:{}", name, pname), port);
-            }
-        }
-
-        // Compose service ports
-        for (name, state) in &compose_states {
-            if let Some(port) = state.port {
-                resolved_ports.insert(format!("compose:{}", name), port);
-            }
-        }
+        // Dashboard/OTel, docker and compose ports (for template interpolation)
+        let mut resolved_ports =
+            started_ports(dashboard_state.as_ref(), &docker_states, &compose_states);
 
         // Service ports (with sticky auto-port support)
         for (name, kind) in &launch_order {
@@ -821,10 +1180,11 @@ impl Orchestrator {
                 let port = resolve_port(
                     &format!("service:{}", name),
                     port_config,
+                    PortProtocol::Tcp,
                     prev_port,
                     prev_auto,
                     &mut allocated_ports,
-                );
+                )?;
                 resolved_ports.insert(format!("service:{}", name), port);
             }
         }
@@ -862,6 +1222,9 @@ impl Orchestrator {
             }
         }
 
+        // Pin `now` and `uuid` so `devrig reload-env` resolves the same values
+        add_builtin_vars(&mut template_vars);
+
         if let Err(errors) = resolve_config_templates(&mut self.config, &template_vars) {
             let mut msg = String::from("Template resolution errors:\n");
             for err in &errors {
@@ -962,7 +1325,7 @@ impl Orchestrator {
         for name in &service_names {
             let svc = &self.config.services[name];
             let port = resolved_ports.get(&format!("service:{}", name)).copied();
-            let port_auto = matches!(&svc.port, Some(Port::Auto));
+            let port_auto = svc.port.as_ref().is_some_and(Port::is_auto);
             service_states.insert(
                 name.clone(),
                 ServiceState {
@@ -972,6 +1335,7 @@ impl Orchestrator {
                     protocol: svc.protocol.clone(),
                     phase: Some("starting".to_string()),
                     exit_code: None,
+                    ready: svc.ready_check.as_ref().map(|_| "pending".to_string()),
                 },
             );
         }
@@ -991,26 +1355,72 @@ impl Orchestrator {
             .save(&self.state_dir)
             .context("saving project state")?;
 
-        let mut registry = InstanceRegistry::load();
-        registry.register(InstanceEntry {
-            slug: self.identity.slug.clone(),
-            config_path: self.config_path.to_string_lossy().to_string(),
-            state_dir: self.state_dir.to_string_lossy().to_string(),
-            started_at: Utc::now(),
-        });
-        if let Err(e) = registry.save() {
-            warn!(error = %e, "failed to save instance registry");
+        // Again, as `devrig ps --all` drops entries without a state file
+        // and may have run while this project was starting.
+        self.register_instance();
+
+        // ================================================================
+        // Phase 4.95: Docker restart / health watchers
+        // ================================================================
+        if let Some(ref mgr) = docker_mgr {
+            for (name, state) in &docker_states {
+                let docker_config = &self.config.docker[name];
+                if docker_config.restart.is_none() && docker_config.healthcheck.is_none() {
+                    continue;
+                }
+                let mut watcher = docker_watcher::DockerWatcher::new(
+                    name.clone(),
+                    docker_config.clone(),
+                    state.clone(),
+                    mgr.clone(),
+                    config_dir.clone(),
+                    self.cancel.clone(),
+                )
+                .with_state_dir(self.state_dir.clone())
+                .with_events(bridge_events_tx.clone())
+                .with_template_vars(template_vars.clone());
+                if let (Some(b_store), Some(b_events)) = (&bridge_store, &bridge_events_tx) {
+                    // Recreated containers get a new id; reattach the dashboard log stream.
+                    let docker = mgr.docker().clone();
+                    let name = name.clone();
+                    let store = Arc::clone(b_store);
+                    let events = b_events.clone();
+                    let cancel = self.cancel.clone();
+                    let tracker = self.tracker.clone();
+                    watcher = watcher.on_restart(Box::new(move |state| {
+                        crate::docker::log_stream::spawn_docker_log_stream(
+                            docker.clone(),
+                            state.container_id.clone(),
+                            name.clone(),
+                            Arc::clone(&store),
+                            events.clone(),
+                            cancel.clone(),
+                            &tracker,
+                        );
+                    }));
+                }
+                self.tracker.spawn(watcher.run());
+            }
         }
 
         // ================================================================
         // Phase 5: Spawn service supervisors
         // ================================================================
+        // Supervisors get their own tracker so shutdown can wait for them to
+        // drain before stopping the docker containers they depend on.
+        let service_tracker = TaskTracker::new();
+        let mut launcher: Option<ServiceLauncher> = None;
+        let mut running_supervisors: BTreeMap<String, RunningSupervisor> = BTreeMap::new();
+        // Supervisors that give up report here; without --exit-on-failure
+        // the sender is dropped and nothing is ever received.
+        let (failure_tx, mut failure_rx) = mpsc::unbounded_channel::<ServiceFailure>();
+        let failure_tx = self.exit_on_failure.then_some(failure_tx);
         if !service_names.is_empty() {
             // Supervisors send to log_tx (broadcast). A fan-out task distributes
             // to the JSONL file writer and the OTel log bridge. Logs are NOT
             // printed to the terminal — use the dashboard or `devrig query logs`
             // to view them.
-            let (log_tx, _) = broadcast::channel::<LogLine>(4096);
+            let (log_tx, _) = broadcast::channel::<LogLine>(self.log_buffer);
 
             // JSONL log file writer
             let logs_dir = self.state_dir.join("logs");
@@ -1018,23 +1428,18 @@ impl Orchestrator {
             let jsonl_path = logs_dir.join("current.jsonl");
             let jsonl_file = std::fs::File::create(&jsonl_path).ok();
 
-            // Fan-out task: subscribes to broadcast, forwards to JSONL
-            let mut fan_rx = log_tx.subscribe();
+            // Fan-out task: subscribes to broadcast, forwards to JSONL. Lines
+            // lost to lag are written as drop markers.
+            let mut fan_rx = LogFanoutReceiver::new(log_tx.subscribe());
             self.tracker.spawn(async move {
                 let mut jsonl_writer = jsonl_file.map(std::io::BufWriter::new);
-                loop {
-                    match fan_rx.recv().await {
-                        Ok(line) => {
-                            if let Some(ref mut w) = jsonl_writer {
-                                use std::io::Write;
-                                if let Ok(json) = serde_json::to_string(&line) {
-                                    let _ = writeln!(w, "{}", json);
-                                    let _ = w.flush();
-                                }
-                            }
+                while let Some(line) = fan_rx.recv().await {
+                    if let Some(ref mut w) = jsonl_writer {
+                        use std::io::Write;
+                        if let Ok(json) = serde_json::to_string(&line) {
+                            let _ = writeln!(w, "{}", json);
+                            let _ = w.flush();
                         }
-                        Err(broadcast::error::RecvError::Closed) => break,
-                        Err(broadcast::error::RecvError::Lagged(_)) => continue,
                     }
                 }
             });
@@ -1042,137 +1447,117 @@ impl Orchestrator {
             // Log bridge: forwards supervisor LogLine → TelemetryStore so
             // process stdout/stderr appears in the dashboard Logs view.
             if let (Some(b_store), Some(b_events)) = (bridge_store.clone(), bridge_events_tx.clone()) {
-                let mut bridge_rx = log_tx.subscribe();
+                let mut bridge_rx = LogFanoutReceiver::new(log_tx.subscribe());
+                let log_formats: HashMap<String, LogFormat> = self
+                    .config
+                    .services
+                    .iter()
+                    .filter_map(|(name, svc)| Some((name.clone(), svc.log_format?)))
+                    .collect();
                 self.tracker.spawn(async move {
-                    loop {
-                        match bridge_rx.recv().await {
-                            Ok(line) => {
-                                let stored = crate::otel::types::logline_to_stored(&line);
-                                let event = crate::otel::types::TelemetryEvent::LogRecord {
-                                    trace_id: None,
-                                    severity: format!("{:?}", stored.severity),
-                                    body: stored.body.clone(),
-                                    service: stored.service_name.clone(),
-                                };
-                                { b_store.write().await.insert_log(stored); }
-                                let _ = b_events.send(event);
-                            }
-                            Err(broadcast::error::RecvError::Closed) => break,
-                            Err(broadcast::error::RecvError::Lagged(n)) => {
-                                warn!(skipped = n, "log bridge lagged");
-                                continue;
-                            }
-                        }
+                    while let Some(line) = bridge_rx.recv().await {
+                        let format = log_formats
+                            .get(scale::split_replica(&line.service).0)
+                            .copied();
+                        let stored = crate::otel::types::logline_to_stored(&line, format);
+                        let event = crate::otel::types::TelemetryEvent::LogRecord {
+                            trace_id: stored.trace_id.clone(),
+                            severity: format!("{:?}", stored.severity),
+                            body: stored.body.clone(),
+                            service: stored.service_name.clone(),
+                        };
+                        { b_store.write().await.insert_log(stored); }
+                        let _ = b_events.send(event);
                     }
                 });
             }
 
+            let service_launcher = ServiceLauncher {
+                config: &self.config,
+                config_path: &self.config_path,
+                state_dir: &self.state_dir,
+                resolved_ports: &resolved_ports,
+                template_vars: &template_vars,
+                secrets: &self.secrets,
+                compose_states: &compose_states,
+                dashboard_state: dashboard_state.as_ref(),
+                log_tx,
+                events_tx: bridge_events_tx.clone(),
+                cancel: &self.cancel,
+                tracker: &service_tracker,
+                max_log_rate: self.max_log_rate,
+                failure_tx: failure_tx.clone(),
+            };
+            // Every service starts as soon as its dependencies allow: a
+            // `started` dependency once it is spawned, a `healthy` one once
+            // its ready_check passes. Services without a ready_check count
+            // as ready once spawned.
             for name in &service_names {
-                let svc = &self.config.services[name];
-
-                // Build env using the discovery module (global + DEVRIG_* + service overrides)
-                let mut env = build_service_env(name, &self.config, &resolved_ports);
-
-                // Add compose service discovery vars (build_service_env doesn't handle compose)
-                for (cs_name, cs_state) in &compose_states {
-                    let upper = cs_name.to_uppercase();
-                    env.insert(format!("DEVRIG_{}_HOST", upper), "localhost".to_string());
-                    if let Some(port) = cs_state.port {
-                        env.insert(format!("DEVRIG_{}_PORT", upper), port.to_string());
-                        env.insert(
-                            format!("DEVRIG_{}_URL", upper),
-                            format!("http://localhost:{}", port),
-                        );
-                    }
-                }
-
-                // Inject OTel env vars with resolved ports (overrides build_service_env defaults)
-                if let Some(ref ds) = dashboard_state {
-                    env.insert(
-                        "OTEL_EXPORTER_OTLP_ENDPOINT".to_string(),
-                        format!("http://localhost:{}", ds.http_port),
-                    );
-                    env.insert("OTEL_SERVICE_NAME".to_string(), name.clone());
-                    env.insert(
-                        "DEVRIG_DASHBOARD_URL".to_string(),
-                        format!("http://localhost:{}", ds.dashboard_port),
-                    );
-                }
-
-                let working_dir = svc.path.as_ref().map(|p| {
-                    let expanded = platform::expand_home(p);
-                    let expanded_path = std::path::Path::new(&expanded);
-                    if expanded_path.is_absolute() {
-                        expanded_path.to_path_buf()
-                    } else {
-                        let base = self
-                            .config_path
-                            .parent()
-                            .unwrap_or_else(|| std::path::Path::new("."));
-                        base.join(&expanded)
-                    }
-                });
-
-                let policy = match &svc.restart {
-                    Some(cfg) => RestartPolicy::from_config(cfg),
-                    None => RestartPolicy::default(),
-                };
-
-                let command = platform::expand_home(&svc.command);
-
-                let supervisor = ServiceSupervisor::new(
-                    name.clone(),
-                    command,
-                    working_dir,
-                    env,
-                    policy,
-                    log_tx.clone(),
-                    self.cancel.clone(),
-                    bridge_events_tx.clone(),
-                    Some(self.state_dir.clone()),
-                );
-
-                let svc_name = name.clone();
-                let state_dir_clone = self.state_dir.clone();
-                let exit_events_tx = bridge_events_tx.clone();
-                self.tracker.spawn(async move {
-                    let (phase, exit_code) = match supervisor.run().await {
-                        Ok(status) => {
-                            debug!(service = %svc_name, %status, "supervisor finished");
-                            let code = status.code();
-                            let phase = if code == Some(0) { "stopped" } else { "failed" };
-                            (phase.to_string(), code)
+                gates.register(name);
+            }
+            let startup = {
+                let gates = &gates;
+                let launcher = &service_launcher;
+                let mut launches: FuturesUnordered<_> = service_names
+                    .iter()
+                    .map(|name| async move {
+                        let svc = &launcher.config.services[name];
+                        gates
+                            .wait(ResourceKind::Service, name, svc.depends_on.iter())
+                            .await?;
+                        let logs = launcher.log_tx.subscribe();
+                        let spawned = launcher.spawn(name).await?;
+                        gates.started(name);
+                        match svc.ready_check.clone() {
+                            Some(check) => service_ready::spawn_wait(
+                                gates,
+                                name.clone(),
+                                check,
+                                launcher
+                                    .resolved_ports
+                                    .get(&format!("service:{}", name))
+                                    .copied(),
+                                logs,
+                                launcher.state_dir.to_path_buf(),
+                            ),
+                            None => gates.ready(name),
                         }
-                        Err(e) => {
-                            if !e.to_string().contains("cancelled") {
-                                error!(service = %svc_name, error = %e, "supervisor failed");
-                                ("failed".to_string(), None)
-                            } else {
-                                ("stopped".to_string(), None)
-                            }
+                        Ok::<_, anyhow::Error>((name, spawned))
+                    })
+                    .collect();
+                loop {
+                    let launched = tokio::select! {
+                        launched = launches.next() => launched,
+                        _ = self.cancel.cancelled() => {
+                            Some(Err(anyhow::anyhow!("startup cancelled")))
                         }
                     };
-
-                    // Broadcast exit event to dashboard
-                    if let Some(tx) = &exit_events_tx {
-                        let _ = tx.send(crate::otel::types::TelemetryEvent::ServiceStatusChange {
-                            service: svc_name.clone(),
-                            status: phase.clone(),
-                        });
+                    match launched {
+                        Some(Ok((name, spawned))) => {
+                            running_supervisors.insert(name.clone(), spawned);
+                        }
+                        Some(Err(e)) => break Err(e),
+                        None => break Ok(()),
                     }
-
-                    // Update state.json with exit info (file-locked)
-                    ProjectState::update_service_exit(
-                        &state_dir_clone,
-                        &svc_name,
-                        &phase,
-                        exit_code,
-                    );
-                });
+                }
+            };
+            if let Err(e) = startup {
+                // Stop what was already spawned before giving up.
+                self.cancel.cancel();
+                service_tracker.close();
+                service_tracker.wait().await;
+                if let Some(mgr) = &docker_mgr {
+                    self.stop_started_containers(mgr, &docker_states).await;
+                }
+                return Err(e);
             }
+            // The launcher keeps a log_tx copy for `devrig restart`; it is
+            // dropped before shutdown so fan-out tasks see the channel close.
+            launcher = Some(service_launcher);
+        }
 
-            // Drop our copy so fan-out tasks detect when all supervisors are done
-            drop(log_tx);
+        if let Err(e) = control::spawn_server(&self.state_dir, self.cancel.clone(), control_tx) {
+            warn!(error = %e, "control socket unavailable; `devrig restart` will not work");
         }
 
         // (State and registry already saved in Phase 4.9 above)
@@ -1182,13 +1567,19 @@ impl Orchestrator {
         // ================================================================
         let mut summary_services: BTreeMap<String, RunningService> = BTreeMap::new();
 
+        // Restart watchers may have moved containers on since Phase 4.9.
+        let live_docker = ProjectState::load(&self.state_dir)
+            .map(|s| s.docker)
+            .unwrap_or_default();
         for (name, state) in &project_state.docker {
+            let phase = live_docker.get(name).and_then(|s| s.phase.clone());
             summary_services.insert(
                 format!("[docker] {}", name),
                 RunningService {
                     port: state.port,
                     port_auto: state.port_auto,
-                    status: "running".to_string(),
+                    status: phase.unwrap_or_else(|| "running".to_string()),
+                    tls: false,
                 },
             );
         }
@@ -1200,6 +1591,7 @@ impl Orchestrator {
                     port: state.port,
                     port_auto: false,
                     status: "running".to_string(),
+                    tls: false,
                 },
             );
         }
@@ -1232,6 +1624,7 @@ impl Orchestrator {
                             port: None,
                             port_auto: false,
                             status: format!("{} [{}]", status, deploy_state.image_tag),
+                            tls: false,
                         },
                     );
                 } else {
@@ -1253,6 +1646,7 @@ impl Orchestrator {
                             port: None,
                             port_auto: false,
                             status: format!("{} [{}]", status, deploy_state.image_tag),
+                            tls: false,
                         },
                     );
                 }
@@ -1260,23 +1654,14 @@ impl Orchestrator {
 
             // Addon summary entries
             for (name, addon_state) in &cs.installed_addons {
-                let pf_port = self
-                    .config
-                    .cluster
-                    .as_ref()
-                    .and_then(|c| c.addons.get(name))
-                    .and_then(|a| {
-                        a.port_forward()
-                            .keys()
-                            .next()
-                            .and_then(|p| p.parse::<u16>().ok())
-                    });
+                let pf = addon_state.port_forwards.first();
                 summary_services.insert(
                     format!("[addon] {}", name),
                     RunningService {
-                        port: pf_port,
-                        port_auto: false,
+                        port: pf.map(|f| f.local_port),
+                        port_auto: pf.is_some_and(|f| f.port_auto),
                         status: format!("installed ({})", addon_state.addon_type),
+                        tls: false,
                     },
                 );
             }
@@ -1291,6 +1676,7 @@ impl Orchestrator {
                     port: Some(ds.dashboard_port),
                     port_auto: dash_config.port.is_auto(),
                     status: "running".to_string(),
+                    tls: ds.tls_cert.is_some(),
                 },
             );
             summary_services.insert(
@@ -1299,6 +1685,7 @@ impl Orchestrator {
                     port: Some(ds.grpc_port),
                     port_auto: otel_config.grpc_port.is_auto(),
                     status: "running".to_string(),
+                    tls: ds.otel_tls,
                 },
             );
             summary_services.insert(
@@ -1307,6 +1694,7 @@ impl Orchestrator {
                     port: Some(ds.http_port),
                     port_auto: otel_config.http_port.is_auto(),
                     status: "running".to_string(),
+                    tls: ds.otel_tls,
                 },
             );
         }
@@ -1314,13 +1702,14 @@ impl Orchestrator {
         for name in &service_names {
             let svc = &self.config.services[name];
             let port = resolved_ports.get(&format!("service:{}", name)).copied();
-            let port_auto = matches!(&svc.port, Some(Port::Auto));
+            let port_auto = svc.port.as_ref().is_some_and(Port::is_auto);
             summary_services.insert(
                 name.clone(),
                 RunningService {
                     port,
                     port_auto,
                     status: "running".to_string(),
+                    tls: false,
                 },
             );
         }
@@ -1332,11 +1721,26 @@ impl Orchestrator {
                     port: Some(port),
                     port_auto: false,
                     status: "running".to_string(),
+                    tls: false,
                 },
             );
         }
 
-        print_startup_summary(&self.identity, &summary_services);
+        let summary_json =
+            startup_summary_json(&self.identity, &summary_services, cluster_state.as_ref());
+        match self.summary_format {
+            SummaryFormat::Table => {
+                print_startup_summary(&self.identity, &summary_services, self.detached_child)
+            }
+            SummaryFormat::Json => print_startup_summary_json(&summary_json),
+        }
+        // Tooling reads the same summary from the state dir
+        if let Err(e) = write_summary_file(&self.state_dir, &summary_json) {
+            warn!(error = %e, "failed to write {}", SUMMARY_FILE);
+        }
+        if self.detached_child {
+            detach::signal_ready(&self.state_dir)?;
+        }
 
         // ================================================================
         // Wait for shutdown signal (SIGINT/SIGTERM) or all tasks to exit
@@ -1358,46 +1762,163 @@ impl Orchestrator {
             }
         };
 
-        if service_names.is_empty() {
-            wait_for_signal.await;
-            eprintln!("\nShutting down...");
-        } else {
-            tokio::select! {
-                _ = wait_for_signal => {
-                    eprintln!("\nShutting down...");
-                }
-                _ = async {
-                    self.tracker.close();
-                    self.tracker.wait().await;
-                } => {
+        // Serve `devrig restart` and dashboard requests until shutdown.
+        let no_services = || control::ControlResponse {
+            error_kind: Some(control::ServiceControlErrorKind::Unknown),
+            ..control::ControlResponse::error("no [services] are running in this project")
+        };
+        let control_loop = async {
+            while let Some((request, reply)) = control_rx.recv().await {
+                let response = match request {
+                    control::ControlRequest::Restart { services } => match &launcher {
+                        Some(l) => match l.restart(&mut running_supervisors, &services).await {
+                            Ok(()) => control::ControlResponse::restarted(services),
+                            Err(e) => control::ControlResponse::from_error(&e),
+                        },
+                        None => no_services(),
+                    },
+                    control::ControlRequest::Stop { services } => match &launcher {
+                        Some(l) => match l.stop(&mut running_supervisors, &services).await {
+                            Ok(()) => control::ControlResponse::stopped(services),
+                            Err(e) => control::ControlResponse::from_error(&e),
+                        },
+                        None => no_services(),
+                    },
+                    control::ControlRequest::Scale { replicas } => match &launcher {
+                        Some(l) => match l.scale(&mut running_supervisors, &replicas).await {
+                            Ok(scaled) => control::ControlResponse::scaled(scaled),
+                            Err(e) => control::ControlResponse::error(format!("{:#}", e)),
+                        },
+                        None => control::ControlResponse::error(
+                            "no [services] are running in this project",
+                        ),
+                    },
+                    control::ControlRequest::ReloadEnv { services } => match &launcher {
+                        Some(l) => match l.reload_env(&mut running_supervisors, &services).await {
+                            Ok(changes) => control::ControlResponse::reloaded(changes),
+                            Err(e) => control::ControlResponse::error(format!("{:#}", e)),
+                        },
+                        None => control::ControlResponse::error(
+                            "no [services] are running in this project",
+                        ),
+                    },
+                };
+                let _ = reply.send(response);
+            }
+            std::future::pending::<()>().await
+        };
+
+        drop(failure_tx);
+        let mut failure = None;
+        if service_names.is_empty() {
+            tokio::select! {
+                _ = wait_for_signal => {}
+                _ = control_loop => {}
+            }
+            eprintln!("\nShutting down...");
+        } else {
+            tokio::select! {
+                _ = wait_for_signal => {
+                    eprintln!("\nShutting down...");
+                }
+                Some(failed) = failure_rx.recv() => {
+                    eprintln!("\n{} -- shutting down", failed);
+                    failure = Some(failed);
+                }
+                _ = async {
+                    service_tracker.close();
+                    self.tracker.close();
+                    service_tracker.wait().await;
+                    self.tracker.wait().await;
+                } => {
                     eprintln!("All services exited");
+                    failure = failure_rx.try_recv().ok();
                 }
+                _ = control_loop => {}
             }
         }
+        drop(launcher);
 
-        // Graceful shutdown: cancel supervisors, with second Ctrl+C for force exit
-        self.cancel.cancel();
+        // Graceful shutdown: cancel supervisors, with second Ctrl+C for force exit.
+        // Services stop a tier at a time, dependents before their dependencies,
+        // and drain fully before any docker container they depend on is
+        // stopped; containers then stop in reverse dependency order.
         self.tracker.close();
+        let resolver =
+            DependencyResolver::from_config(&self.config).map_err(|e| anyhow::anyhow!("{}", e))?;
+        let service_tiers = resolver
+            .service_stop_tiers()
+            .map_err(|e| anyhow::anyhow!("{}", e))?;
+        let docker_stop_order: Vec<String> = resolver
+            .stop_order()
+            .map_err(|e| anyhow::anyhow!("{}", e))?
+            .into_iter()
+            .filter(|(name, kind)| *kind == ResourceKind::Docker && docker_states.contains_key(name))
+            .map(|(name, _)| name)
+            .collect();
+        // Restart watchers record recreated containers in state.json; stop
+        // whichever container is current rather than the one first started.
+        let mut live_docker_states = docker_states.clone();
+        if let Some(live) = ProjectState::load(&self.state_dir) {
+            for (name, state) in live.docker {
+                if let Some(entry) = live_docker_states.get_mut(&name) {
+                    *entry = state;
+                }
+            }
+        }
+        // Services get longer to drain when they have post_stop hooks to run.
+        let mut drain_timeout = std::time::Duration::from_secs(10);
+        if service_names
+            .iter()
+            .any(|name| self.config.services[name].post_stop.is_some())
+        {
+            drain_timeout += POST_STOP_TIMEOUT;
+        }
         let shutdown_fut = async {
-            match tokio::time::timeout(std::time::Duration::from_secs(10), self.tracker.wait())
-                .await
-            {
-                Ok(()) => debug!("All services stopped cleanly"),
-                Err(_) => warn!("Shutdown timed out -- some processes may have been force-killed"),
+            let deadline = tokio::time::Instant::now() + drain_timeout;
+            let tiered = stop_in_tiers(&mut running_supervisors, &service_tiers, |tier| {
+                if tier
+                    .iter()
+                    .any(|name| self.config.services[name].post_stop.is_some())
+                {
+                    TIER_STOP_TIMEOUT + POST_STOP_TIMEOUT
+                } else {
+                    TIER_STOP_TIMEOUT
+                }
+            });
+            if tokio::time::timeout_at(deadline, tiered).await.is_err() {
+                warn!("Shutdown timed out -- stopping remaining services at once");
             }
+            self.cancel.cancel();
+            shutdown_in_order(
+                &service_tracker,
+                deadline.saturating_duration_since(tokio::time::Instant::now()),
+                &docker_stop_order,
+                |name| {
+                    let docker_mgr = docker_mgr.as_ref();
+                    let docker_state = live_docker_states.get(&name);
+                    async move {
+                        if let (Some(mgr), Some(docker_state)) = (docker_mgr, docker_state) {
+                            if let Err(e) = mgr.stop_service(docker_state).await {
+                                warn!(docker = %name, error = %e, "failed to stop docker container");
+                            }
+                        }
+                    }
+                },
+            )
+            .await;
 
             // Stop addon port-forwards
             if let Some(pf_mgr) = &self.port_forward_mgr {
                 pf_mgr.stop().await;
             }
 
-            // Stop docker containers on shutdown (preserve state for restart)
-            for (name, docker_state) in &docker_states {
-                if let Some(mgr) = &docker_mgr {
-                    if let Err(e) = mgr.stop_service(docker_state).await {
-                        warn!(docker = %name, error = %e, "failed to stop docker container");
-                    }
-                }
+            // Let remaining background tasks (log fan-out, dashboard) finish
+            if tokio::time::timeout(std::time::Duration::from_secs(5), self.tracker.wait())
+                .await
+                .is_err()
+            {
+                debug!("background tasks did not finish before shutdown");
             }
         };
 
@@ -1410,18 +1931,33 @@ impl Orchestrator {
             }
         }
 
-        // Clean up PID file
+        // Persist whatever the telemetry WAL still has buffered
+        if let Some(store) = &bridge_store {
+            store.write().await.flush_wal();
+        }
+
+        // Clean up PID file, control socket and startup summary
         let _ = std::fs::remove_file(self.state_dir.join("pid"));
+        let _ = std::fs::remove_file(control::socket_path(&self.state_dir));
+        let _ = std::fs::remove_file(self.state_dir.join(SUMMARY_FILE));
 
-        Ok(())
+        match failure {
+            Some(failure) => Err(failure.into()),
+            None => Ok(()),
+        }
     }
 
     /// Stop a running project: signal the running devrig process via PID file,
-    /// or stop docker containers directly.
-    pub async fn stop(&self) -> Result<()> {
-        let _state = ProjectState::load(&self.state_dir).ok_or_else(|| {
-            anyhow::anyhow!("no running project state found -- is the project running?")
-        })?;
+    /// or stop docker containers directly. If state.json is corrupt, the
+    /// project's containers are found by label instead.
+    pub async fn stop(&self, remove_containers: bool) -> Result<()> {
+        let state = match ProjectState::load_checked(&self.state_dir) {
+            LoadedState::Loaded(state) => Some(*state),
+            LoadedState::Corrupt { .. } => None,
+            LoadedState::Missing => {
+                bail!("no running project state found -- is the project running?")
+            }
+        };
 
         // Signal the running devrig process via PID file
         let pid_path = self.state_dir.join("pid");
@@ -1441,7 +1977,11 @@ impl Orchestrator {
                                 }
                                 tokio::time::sleep(std::time::Duration::from_millis(100)).await;
                             }
-                            return Ok(());
+                            // The running devrig stopped its containers on
+                            // the way out; removing them is left to us.
+                            if !remove_containers {
+                                return Ok(());
+                            }
                         }
                         Err(nix::errno::Errno::ESRCH) => {
                             // Process doesn't exist — stale PID file
@@ -1463,20 +2003,45 @@ impl Orchestrator {
             eprintln!("No PID file found — the project may have been started in a previous version.");
         }
 
-        // Fallback: stop docker containers directly (preserve volumes/data)
-        if !_state.docker.is_empty() {
-            match DockerManager::new(_state.slug.clone()).await {
-                Ok(mgr) => {
-                    for (name, docker_state) in &_state.docker {
-                        if let Err(e) = mgr.stop_service(docker_state).await {
-                            warn!(docker = %name, error = %e, "failed to stop docker container");
+        // Fallback: stop docker containers directly (preserve volumes/data).
+        // With `remove_containers` they are removed too, so the next start
+        // creates them afresh on the same volumes.
+        match &state {
+            Some(state) if !state.docker.is_empty() => {
+                match DockerManager::new(state.slug.clone()).await {
+                    Ok(mgr) => {
+                        for (name, docker_state) in &state.docker {
+                            let result = if remove_containers {
+                                mgr.delete_service(docker_state).await
+                            } else {
+                                mgr.stop_service(docker_state).await
+                            };
+                            if let Err(e) = result {
+                                warn!(docker = %name, error = %e, "failed to stop docker container");
+                            }
                         }
                     }
+                    Err(e) => {
+                        warn!(error = %e, "could not connect to Docker to stop docker containers");
+                    }
+                }
+            }
+            Some(_) => {}
+            None => match DockerManager::new(self.identity.slug.clone()).await {
+                Ok(mgr) => {
+                    let result = if remove_containers {
+                        mgr.remove_project_containers().await
+                    } else {
+                        mgr.stop_project_containers().await
+                    };
+                    if let Err(e) = result {
+                        warn!(error = %e, "failed to stop project containers by label");
+                    }
                 }
                 Err(e) => {
                     warn!(error = %e, "could not connect to Docker to stop docker containers");
                 }
-            }
+            },
         }
 
         Ok(())
@@ -1485,7 +2050,7 @@ impl Orchestrator {
     /// Stop the project, remove all Docker resources, and unregister.
     pub async fn delete(&self) -> Result<()> {
         // Stop first (ignore errors if nothing is running)
-        let _ = self.stop().await;
+        let _ = self.stop(false).await;
 
         // Delete k3d cluster if it exists.
         // Attempt cleanup even without state.json — the cluster name is
@@ -1521,6 +2086,7 @@ impl Orchestrator {
                     crate::config::model::AddonConfig::Manifest {
                         path: log_collector_manifest.to_string_lossy().to_string(),
                         namespace: None,
+                        create_namespace: true,
                         port_forward: BTreeMap::new(),
                         depends_on: vec![],
                     },
@@ -1545,7 +2111,7 @@ impl Orchestrator {
 
             // Try to delete regardless of whether we have state — cluster may
             // have been created before the failure that prevented state save.
-            let cluster_name = format!("devrig-{}", self.identity.slug);
+            let cluster_name = self.identity.cluster_name();
             debug!(cluster = %cluster_name, "deleting k3d cluster");
             if let Err(e) = k3d_mgr.delete_cluster().await {
                 warn!(error = %e, "failed to delete k3d cluster");
@@ -1568,14 +2134,13 @@ impl Orchestrator {
 
         // Compose down
         if let Some(compose_config) = &self.config.compose {
-            let compose_file = self
-                .config_path
-                .parent()
-                .unwrap_or_else(|| std::path::Path::new("."))
-                .join(&compose_config.file);
-            if let Err(e) =
-                compose::lifecycle::compose_down(&compose_file, &self.identity.slug).await
-            {
+            let compose_files = compose_config.files(
+                self.config_path
+                    .parent()
+                    .unwrap_or_else(|| std::path::Path::new(".")),
+            );
+            let project_name = compose_config.project_name(&self.identity.slug);
+            if let Err(e) = compose::lifecycle::compose_down(&compose_files, project_name).await {
                 warn!(error = %e, "failed to run compose down");
             }
         }
@@ -1601,4 +2166,972 @@ impl Orchestrator {
 
         Ok(())
     }
+
+    /// Record this run in the global instance registry.
+    fn register_instance(&self) {
+        let mut registry = InstanceRegistry::load();
+        registry.register(InstanceEntry {
+            slug: self.identity.slug.clone(),
+            config_path: self.config_path.to_string_lossy().to_string(),
+            state_dir: self.state_dir.to_string_lossy().to_string(),
+            started_at: Utc::now(),
+        });
+        if let Err(e) = registry.save() {
+            warn!(error = %e, "failed to save instance registry");
+        }
+    }
+
+    /// Stop the docker containers this run started, in reverse dependency
+    /// order, after startup fails part way so the stack isn't left half up.
+    /// Volumes, containers and state.json are kept for the next start.
+    async fn stop_started_containers(
+        &self,
+        mgr: &DockerManager,
+        docker_states: &BTreeMap<String, DockerState>,
+    ) {
+        if docker_states.is_empty() {
+            return;
+        }
+        // Without a dependency order, stop in reverse name order.
+        let order: Vec<String> = DependencyResolver::from_config(&self.config)
+            .map_err(|e| anyhow::anyhow!("{}", e))
+            .and_then(|resolver| resolver.stop_order().map_err(|e| anyhow::anyhow!("{}", e)))
+            .map(|order| {
+                order
+                    .into_iter()
+                    .filter(|(name, kind)| {
+                        *kind == ResourceKind::Docker && docker_states.contains_key(name)
+                    })
+                    .map(|(name, _)| name)
+                    .collect()
+            })
+            .unwrap_or_else(|_| docker_states.keys().rev().cloned().collect());
+        for name in order {
+            debug!(docker = %name, "stopping container after failed start");
+            if let Err(e) = mgr.stop_service(&docker_states[&name]).await {
+                warn!(docker = %name, error = %e, "failed to stop docker container");
+            }
+        }
+    }
+
+    /// Build template vars available at cluster time (deploy env, addon
+    /// install): cluster images, registry, project name, and any ports
+    /// already resolved (dashboard, docker, compose, fixed service ports).
+    fn cluster_template_vars(
+        &self,
+        deployed: &BTreeMap<String, ClusterDeployState>,
+        dashboard_state: Option<&state::DashboardState>,
+        docker_states: &BTreeMap<String, DockerState>,
+        compose_states: &BTreeMap<String, ComposeServiceState>,
+    ) -> HashMap<String, String> {
+        let mut vars = crate::config::interpolate::build_cluster_image_vars(deployed);
+        if self.config.cluster.as_ref().is_some_and(|c| c.registry) {
+            vars.insert(
+                "cluster.registry".to_string(),
+                format!("k3d-devrig-{}-reg:5000", self.identity.slug),
+            );
+        }
+
+        vars.insert("project.name".to_string(), self.config.project.name.clone());
+
+        // Fixed service ports (available before service launch)
+        for (name, svc) in &self.config.services {
+            if let Some(Port::Fixed(port)) = &svc.port {
+                vars.insert(format!("services.{name}.port"), port.to_string());
+            }
+        }
+
+        // Dashboard / OTel ports
+        if let Some(ds) = dashboard_state {
+            vars.insert("dashboard.port".to_string(), ds.dashboard_port.to_string());
+            vars.insert("dashboard.otel.grpc_port".to_string(), ds.grpc_port.to_string());
+            vars.insert("dashboard.otel.http_port".to_string(), ds.http_port.to_string());
+        }
+
+        // Docker ports
+        for (name, state) in docker_states {
+            if let Some(port) = state.port {
+                vars.insert(format!("docker.{name}.port"), port.to_string());
+            }
+            for (pname, &port) in &state.named_ports {
+                let val = port.to_string();
+                vars.insert(format!("docker.{name}.ports.{pname}"), val.clone());
+                vars.insert(format!("docker.{name}.port_{pname}"), val);
+            }
+        }
+
+        // Compose ports
+        for (name, state) in compose_states {
+            if let Some(port) = state.port {
+                vars.insert(format!("compose.{name}.port"), port.to_string());
+            }
+        }
+
+        vars
+    }
+}
+
+/// How long one shutdown tier gets to stop before the next tier is cancelled
+/// anyway: long enough for a supervisor's SIGTERM grace period and SIGKILL.
+const TIER_STOP_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(6);
+
+/// Cancel services one tier at a time (see
+/// [`DependencyResolver::service_stop_tiers`]), waiting up to
+/// `tier_timeout(tier)` for a tier's supervisors to finish before moving on
+/// to the next. Replicas stop with their service; names that are not running
+/// are skipped.
+async fn stop_in_tiers<F>(
+    running: &mut BTreeMap<String, RunningSupervisor>,
+    tiers: &[Vec<String>],
+    tier_timeout: F,
+) where
+    F: Fn(&[String]) -> std::time::Duration,
+{
+    for tier in tiers {
+        let names: Vec<String> = running
+            .keys()
+            .filter(|name| tier.iter().any(|t| t == scale::split_replica(name).0))
+            .cloned()
+            .collect();
+        if names.is_empty() {
+            continue;
+        }
+        let stopping: Vec<RunningSupervisor> = names
+            .iter()
+            .filter_map(|name| running.remove(name))
+            .collect();
+        for sup in &stopping {
+            sup.cancel.cancel();
+        }
+        let drained = futures_util::future::join_all(stopping.into_iter().map(|sup| sup.handle));
+        match tokio::time::timeout(tier_timeout(tier), drained).await {
+            Ok(_) => debug!(services = ?names, "shutdown tier stopped"),
+            Err(_) => warn!(services = ?names, "shutdown tier timed out -- moving on"),
+        }
+    }
+}
+
+/// Wait for all service supervisors to drain, then stop docker containers one
+/// at a time in `docker_order` (dependents before their dependencies).
+async fn shutdown_in_order<F, Fut>(
+    services: &TaskTracker,
+    timeout: std::time::Duration,
+    docker_order: &[String],
+    mut stop_docker: F,
+) where
+    F: FnMut(String) -> Fut,
+    Fut: std::future::Future<Output = ()>,
+{
+    services.close();
+    match tokio::time::timeout(timeout, services.wait()).await {
+        Ok(()) => debug!("All services stopped cleanly"),
+        Err(_) => warn!("Shutdown timed out -- some processes may have been force-killed"),
+    }
+
+    for name in docker_order {
+        stop_docker(name.clone()).await;
+    }
+}
+
+/// Builds and spawns `[services]` supervisors. Kept alive after startup so
+/// `devrig restart` can respawn individual services with freshly built env.
+struct ServiceLauncher<'a> {
+    config: &'a DevrigConfig,
+    config_path: &'a std::path::Path,
+    state_dir: &'a std::path::Path,
+    /// Ports resolved at startup; auto ports here match the sticky ports
+    /// recorded in state.json, so restarted services keep them.
+    resolved_ports: &'a HashMap<String, u16>,
+    /// Template variables resolved at startup, reapplied when
+    /// `devrig reload-env` re-reads the config. They include the `now` and
+    /// `uuid` pinned at startup, so those don't count as env changes.
+    template_vars: &'a HashMap<String, String>,
+    /// The project's secret registry; `devrig reload-env` adds new or
+    /// rotated values to it.
+    secrets: &'a std::sync::Mutex<config::secrets::SecretRegistry>,
+    compose_states: &'a BTreeMap<String, ComposeServiceState>,
+    dashboard_state: Option<&'a state::DashboardState>,
+    log_tx: broadcast::Sender<LogLine>,
+    events_tx: Option<broadcast::Sender<crate::otel::types::TelemetryEvent>>,
+    cancel: &'a CancellationToken,
+    tracker: &'a TaskTracker,
+    /// `devrig start --max-log-rate`, for services without their own limit.
+    max_log_rate: Option<u32>,
+    /// Set with `devrig start --exit-on-failure`; supervisors that finish
+    /// as failed report on it.
+    failure_tx: Option<mpsc::UnboundedSender<ServiceFailure>>,
+}
+
+/// A service whose supervisor finished as failed: it exited non-zero, or
+/// could not be run, and its restart policy gave up.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("service '{service}' failed{}", .exit_code.map(|c| format!(" with exit code {}", c)).unwrap_or_default())]
+pub struct ServiceFailure {
+    pub service: String,
+    pub exit_code: Option<i32>,
+}
+
+impl ServiceFailure {
+    /// The code `devrig start` exits with: the service's own, or 1 when it
+    /// has none (killed by a signal, or never started).
+    pub fn process_exit_code(&self) -> i32 {
+        self.exit_code.filter(|&c| c != 0).unwrap_or(1)
+    }
+}
+
+/// A spawned supervisor task, the token that stops just that service, and
+/// the env it was given.
+struct RunningSupervisor {
+    cancel: CancellationToken,
+    handle: tokio::task::JoinHandle<()>,
+    env: BTreeMap<String, String>,
+}
+
+/// A service's `path`, resolved relative to the config file. `None` when
+/// it has no `path`.
+pub fn service_working_dir(
+    config_path: &std::path::Path,
+    svc: &ServiceConfig,
+) -> Option<std::path::PathBuf> {
+    svc.path.as_ref().map(|p| {
+        let expanded = platform::expand_home(p);
+        let expanded_path = std::path::Path::new(&expanded);
+        if expanded_path.is_absolute() {
+            expanded_path.to_path_buf()
+        } else {
+            let base = config_path
+                .parent()
+                .unwrap_or_else(|| std::path::Path::new("."));
+            base.join(&expanded)
+        }
+    })
+}
+
+/// A service hook's commands, with `~` expanded like `command`.
+fn hook_commands(hook: &Option<StringOrList>) -> Vec<String> {
+    hook.iter()
+        .flat_map(|commands| commands.as_slice())
+        .map(|c| platform::expand_home(c))
+        .collect()
+}
+
+/// The full env for service `name`: `build_service_env` plus discovery vars
+/// for compose services and the OTel/dashboard endpoints.
+pub fn full_service_env(
+    config: &DevrigConfig,
+    name: &str,
+    resolved_ports: &HashMap<String, u16>,
+    compose_states: &BTreeMap<String, ComposeServiceState>,
+    dashboard_state: Option<&state::DashboardState>,
+) -> BTreeMap<String, String> {
+    // Build env using the discovery module (global + discovery vars + service overrides)
+    let mut env = build_service_env(name, config, resolved_ports);
+    let prefix = config.project.env_prefix();
+
+    // Add compose service discovery vars (build_service_env doesn't handle compose)
+    for (cs_name, cs_state) in compose_states {
+        let upper = cs_name.to_uppercase();
+        env.insert(format!("{}{}_HOST", prefix, upper), "localhost".to_string());
+        if let Some(port) = cs_state.port {
+            env.insert(format!("{}{}_PORT", prefix, upper), port.to_string());
+            let protocol = config.compose.as_ref().and_then(|c| c.protocol(cs_name));
+            env.insert(format!("{}{}_URL", prefix, upper), service_url(protocol, port));
+        }
+    }
+
+    // Inject OTel env vars with resolved ports (overrides build_service_env defaults)
+    if let Some(ds) = dashboard_state {
+        env.insert(
+            "OTEL_EXPORTER_OTLP_ENDPOINT".to_string(),
+            format!("{}://localhost:{}", ds.otel_scheme(), ds.http_port),
+        );
+        if let Some(cert) = ds.otlp_certificate() {
+            env.insert(
+                "OTEL_EXPORTER_OTLP_CERTIFICATE".to_string(),
+                cert.display().to_string(),
+            );
+        }
+        env.insert("OTEL_SERVICE_NAME".to_string(), name.to_string());
+        env.insert(
+            format!("{}DASHBOARD_URL", prefix),
+            format!("{}://localhost:{}", ds.scheme(), ds.dashboard_port),
+        );
+    }
+    env
+}
+
+impl ServiceLauncher<'_> {
+    /// The full env for service `name` under `config`.
+    fn service_env(&self, config: &DevrigConfig, name: &str) -> BTreeMap<String, String> {
+        full_service_env(
+            config,
+            name,
+            self.resolved_ports,
+            self.compose_states,
+            self.dashboard_state,
+        )
+    }
+
+    async fn spawn(&self, name: &str) -> Result<RunningSupervisor> {
+        self.spawn_with_env(name, self.service_env(self.config, name))
+            .await
+    }
+
+    /// Run the service's `pre_start` commands, then start its supervisor.
+    /// `name` may be a replica (`api#1`), which runs its service's config.
+    async fn spawn_with_env(
+        &self,
+        name: &str,
+        env: BTreeMap<String, String>,
+    ) -> Result<RunningSupervisor> {
+        let svc = &self.config.services[scale::split_replica(name).0];
+
+        let working_dir = service_working_dir(self.config_path, svc);
+
+        let policy = match &svc.restart {
+            Some(cfg) => RestartPolicy::from_config(cfg),
+            None => RestartPolicy::default(),
+        };
+
+        let command = platform::expand_home(&svc.command);
+        let shell = platform::ServiceShell::parse(svc.shell.as_deref())
+            .map_err(|e| anyhow::anyhow!("service {}: invalid shell: {}", name, e))?;
+
+        let cancel = self.cancel.child_token();
+        let supervisor = ServiceSupervisor::new(
+            name.to_string(),
+            command,
+            working_dir,
+            env.clone(),
+            policy,
+            self.log_tx.clone(),
+            cancel.clone(),
+            self.events_tx.clone(),
+            Some(self.state_dir.to_path_buf()),
+        )
+        .with_shell(shell)
+        .with_env_passthrough(self.config.project.shell_env_passthrough.clone())
+        .with_max_log_rate(svc.max_log_lines_per_sec.or(self.max_log_rate))
+        .with_hooks(hook_commands(&svc.pre_start), hook_commands(&svc.post_stop));
+        supervisor.pre_start().await?;
+
+        let svc_name = name.to_string();
+        let state_dir_clone = self.state_dir.to_path_buf();
+        let exit_events_tx = self.events_tx.clone();
+        let failure_tx = self.failure_tx.clone();
+        let stopped = cancel.clone();
+        let handle = self.tracker.spawn(async move {
+            let result = supervisor.run().await;
+            // A cancelled supervisor was stopped on purpose (stop, restart,
+            // reload-env, scale-down or shutdown). Its last run may have
+            // crashed, but that is not a failure.
+            let (phase, exit_code) = match result {
+                _ if stopped.is_cancelled() => {
+                    debug!(service = %svc_name, "supervisor stopped");
+                    ("stopped".to_string(), None)
+                }
+                Ok(status) => {
+                    debug!(service = %svc_name, %status, "supervisor finished");
+                    let code = status.code();
+                    let phase = if code == Some(0) { "stopped" } else { "failed" };
+                    (phase.to_string(), code)
+                }
+                Err(e) => {
+                    if !e.to_string().contains("cancelled") {
+                        error!(service = %svc_name, error = %e, "supervisor failed");
+                        ("failed".to_string(), None)
+                    } else {
+                        ("stopped".to_string(), None)
+                    }
+                }
+            };
+
+            // Broadcast exit event to dashboard
+            if let Some(tx) = &exit_events_tx {
+                let _ = tx.send(crate::otel::types::TelemetryEvent::ServiceStatusChange {
+                    service: svc_name.clone(),
+                    status: phase.clone(),
+                });
+            }
+
+            // Update state.json with exit info (file-locked)
+            ProjectState::update_service_exit(&state_dir_clone, &svc_name, &phase, exit_code);
+
+            if phase == "failed" {
+                if let Some(tx) = &failure_tx {
+                    let _ = tx.send(ServiceFailure {
+                        service: svc_name,
+                        exit_code,
+                    });
+                }
+            }
+        });
+
+        Ok(RunningSupervisor {
+            cancel,
+            handle,
+            env,
+        })
+    }
+
+    fn unknown_service(
+        &self,
+        running: &BTreeMap<String, RunningSupervisor>,
+        name: &str,
+    ) -> ServiceControlError {
+        ServiceControlError::Unknown {
+            name: name.to_string(),
+            running: running.keys().cloned().collect(),
+        }
+    }
+
+    /// Stop the named services without restarting them; `restart` starts
+    /// them again. Everything else keeps running.
+    async fn stop(
+        &self,
+        running: &mut BTreeMap<String, RunningSupervisor>,
+        services: &[String],
+    ) -> Result<()> {
+        if services.is_empty() {
+            bail!("no services given to stop");
+        }
+        for name in services {
+            if running.contains_key(name) {
+                continue;
+            }
+            if self.config.services.contains_key(name) {
+                return Err(ServiceControlError::NotRunning(name.clone()).into());
+            }
+            return Err(self.unknown_service(running, name).into());
+        }
+
+        for name in services {
+            if let Some(old) = running.remove(name) {
+                debug!(service = %name, "stopping service");
+                old.cancel.cancel();
+                let _ = old.handle.await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Stop the named services and respawn them, leaving everything else
+    /// (other services, docker, compose, cluster) untouched.
+    async fn restart(
+        &self,
+        running: &mut BTreeMap<String, RunningSupervisor>,
+        services: &[String],
+    ) -> Result<()> {
+        if services.is_empty() {
+            bail!("no services given to restart");
+        }
+        // Services stopped with `stop` are still in state.json and can be
+        // started again.
+        let session = ProjectState::load(self.state_dir)
+            .map(|s| s.services)
+            .unwrap_or_default();
+        for name in services {
+            if running.contains_key(name) {
+                continue;
+            }
+            if self.config.services.contains_key(name) {
+                if session.contains_key(name) {
+                    continue;
+                }
+                return Err(ServiceControlError::NotStarted(name.clone()).into());
+            }
+            return Err(self.unknown_service(running, name).into());
+        }
+
+        // Hold the tracker open while swapping so the gap between stopping
+        // and respawning doesn't look like every service exited.
+        let _guard = self.tracker.token();
+        let mut seen = HashSet::new();
+        for name in services {
+            if !seen.insert(name) {
+                continue;
+            }
+            // Keep the env the service is running with, which may have been
+            // updated by `devrig reload-env` since startup.
+            let env = match running.remove(name) {
+                Some(old) => {
+                    old.cancel.cancel();
+                    let _ = old.handle.await;
+                    old.env
+                }
+                None => self.service_env(self.config, name),
+            };
+            debug!(service = %name, "restarting service");
+            ProjectState::update_service_phase(self.state_dir, name, "starting");
+            running.insert(name.clone(), self.spawn_with_env(name, env).await?);
+        }
+        Ok(())
+    }
+
+    /// Re-read the config and `.env` files, rebuild the env of `services`
+    /// (every running service when empty), and restart or signal the ones
+    /// whose env changed, per their `reload_strategy`.
+    async fn reload_env(
+        &self,
+        running: &mut BTreeMap<String, RunningSupervisor>,
+        services: &[String],
+    ) -> Result<Vec<reload::EnvChange>> {
+        for name in services {
+            let (service, index) = scale::split_replica(name);
+            if index > 0 {
+                bail!(
+                    "'{}' is a replica; reload '{}' and scale it again instead",
+                    name,
+                    service
+                );
+            }
+            if !running.contains_key(name) {
+                bail!(
+                    "unknown service '{}' (running: {:?})",
+                    name,
+                    running.keys().collect::<Vec<_>>()
+                );
+            }
+        }
+
+        let (mut fresh, _source, secrets) = config::load_config_with_secrets(self.config_path)
+            .with_context(|| format!("reloading config from {}", self.config_path.display()))?;
+        self.secrets.lock().unwrap().merge(secrets);
+        if let Err(errors) = resolve_config_templates(&mut fresh, self.template_vars) {
+            let msgs: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+            bail!("template resolution errors: {}", msgs.join("; "));
+        }
+
+        let targets: Vec<&String> = if services.is_empty() {
+            running
+                .keys()
+                .filter(|name| scale::split_replica(name).1 == 0)
+                .collect()
+        } else {
+            services.iter().collect()
+        };
+        let mut current = BTreeMap::new();
+        let mut rebuilt = BTreeMap::new();
+        for name in targets {
+            if !fresh.services.contains_key(name) {
+                warn!(service = %name, "service no longer in config, leaving it running");
+                continue;
+            }
+            current.insert(name.clone(), running[name].env.clone());
+            rebuilt.insert(name.clone(), self.service_env(&fresh, name));
+        }
+
+        let changes = reload::plan_env_reload(&fresh, &current, &rebuilt);
+        let _guard = self.tracker.token();
+        for change in &changes {
+            let name = &change.service;
+            let env = rebuilt.remove(name).unwrap_or_default();
+            match &change.action {
+                reload::ReloadAction::Restart => {
+                    if let Some(old) = running.remove(name) {
+                        old.cancel.cancel();
+                        let _ = old.handle.await;
+                    }
+                    debug!(service = %name, "restarting service with new env");
+                    ProjectState::update_service_phase(self.state_dir, name, "starting");
+                    running.insert(name.clone(), self.spawn_with_env(name, env).await?);
+                }
+                reload::ReloadAction::Signal(signal) => {
+                    let pid = ProjectState::load(self.state_dir)
+                        .and_then(|s| s.services.get(name).map(|svc| svc.pid))
+                        .with_context(|| format!("no pid recorded for service '{}'", name))?;
+                    platform::signal_process_group(pid, signal)
+                        .with_context(|| format!("signaling service '{}'", name))?;
+                    debug!(service = %name, %signal, "signaled service with new env");
+                    if let Some(entry) = running.get_mut(name) {
+                        entry.env = env;
+                    }
+                }
+            }
+        }
+        Ok(changes)
+    }
+
+    /// Start or stop replicas so each service in `counts` runs that many
+    /// instances. Replicas get their own port when the service's port is
+    /// `auto` or a range. Returns every instance of the scaled services.
+    async fn scale(
+        &self,
+        running: &mut BTreeMap<String, RunningSupervisor>,
+        counts: &BTreeMap<String, usize>,
+    ) -> Result<Vec<scale::Replica>> {
+        if counts.is_empty() {
+            bail!("no services given to scale");
+        }
+        for (name, &count) in counts {
+            let Some(svc) = self.config.services.get(name) else {
+                bail!(
+                    "unknown service '{}' (running: {:?})",
+                    name,
+                    running.keys().collect::<Vec<_>>()
+                );
+            };
+            if !running.contains_key(name) {
+                bail!("service '{}' was not started in this session", name);
+            }
+            if count == 0 {
+                bail!("cannot scale '{}' to 0 -- use `devrig stop` instead", name);
+            }
+            if let Some(Port::Fixed(port)) = svc.port {
+                bail!(
+                    "service '{}' has a fixed port ({}); set port = \"auto\" to scale it",
+                    name,
+                    port
+                );
+            }
+        }
+
+        // Hold the tracker open while replicas stop so it doesn't look like
+        // every service exited.
+        let _guard = self.tracker.token();
+        let mut scaled = Vec::new();
+        for (service, &count) in counts {
+            let plan = scale::plan_scale(running.keys(), service, count);
+            for name in &plan.stop {
+                if let Some(old) = running.remove(name) {
+                    old.cancel.cancel();
+                    let _ = old.handle.await;
+                }
+                ProjectState::remove_service_state(self.state_dir, name);
+                debug!(replica = %name, "stopped replica");
+            }
+
+            let svc = &self.config.services[service];
+            let tcp = PortProtocol::Tcp;
+            let mut taken: AllocatedPorts =
+                self.resolved_ports.values().map(|&port| (port, tcp)).collect();
+            taken.extend(running.values().filter_map(supervisor_port).map(|port| (port, tcp)));
+            let mut ports: BTreeMap<usize, u16> = running
+                .iter()
+                .filter(|(name, _)| scale::split_replica(name).0 == service)
+                .filter_map(|(name, sup)| {
+                    Some((scale::split_replica(name).1, supervisor_port(sup)?))
+                })
+                .collect();
+            for &index in &plan.start {
+                let port = match svc.port {
+                    None | Some(Port::Fixed(_)) => continue,
+                    Some(Port::Auto) => find_free_port_excluding(&taken, tcp),
+                    Some(Port::Range { start, end, .. }) => {
+                        find_free_port_in_range(None, start, end, tcp, &taken).with_context(|| {
+                            format!(
+                                "no free port left in {}-{} for another '{}' replica",
+                                start, end, service
+                            )
+                        })?
+                    }
+                };
+                taken.insert((port, tcp));
+                ports.insert(index, port);
+            }
+
+            for &index in &plan.start {
+                let name = scale::replica_name(service, index);
+                let env = scale::replica_env(
+                    &running[service].env,
+                    self.config.project.env_prefix(),
+                    service,
+                    index,
+                    &ports,
+                );
+                ProjectState::update_service_state(
+                    self.state_dir,
+                    &name,
+                    &ServiceState {
+                        pid: 0,
+                        port: ports.get(&index).copied(),
+                        port_auto: true,
+                        protocol: svc.protocol.clone(),
+                        phase: Some("starting".to_string()),
+                        exit_code: None,
+                        ready: None,
+                    },
+                );
+                debug!(replica = %name, "starting replica");
+                let supervisor = self.spawn_with_env(&name, env).await?;
+                running.insert(name, supervisor);
+            }
+
+            scaled.extend(
+                running
+                    .iter()
+                    .filter(|(name, _)| scale::split_replica(name).0 == service)
+                    .map(|(name, sup)| scale::Replica {
+                        service: service.clone(),
+                        name: name.clone(),
+                        index: scale::split_replica(name).1,
+                        port: supervisor_port(sup),
+                    }),
+            );
+        }
+        scaled.sort_by(|a, b| (&a.service, a.index).cmp(&(&b.service, b.index)));
+        Ok(scaled)
+    }
+}
+
+/// The port a supervisor was started on, from the `PORT` in its env.
+fn supervisor_port(sup: &RunningSupervisor) -> Option<u16> {
+    sup.env.get("PORT")?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn service_failure_exit_code() {
+        let failure = ServiceFailure {
+            service: "api".to_string(),
+            exit_code: Some(3),
+        };
+        assert_eq!(failure.process_exit_code(), 3);
+        assert_eq!(failure.to_string(), "service 'api' failed with exit code 3");
+
+        let killed = ServiceFailure {
+            service: "api".to_string(),
+            exit_code: None,
+        };
+        assert_eq!(killed.process_exit_code(), 1);
+        assert_eq!(killed.to_string(), "service 'api' failed");
+    }
+
+    #[tokio::test]
+    async fn services_stop_before_docker_dependencies() {
+        let events = Arc::new(Mutex::new(Vec::<String>::new()));
+        let cancel = CancellationToken::new();
+        let services = TaskTracker::new();
+
+        for (name, flush_ms) in [("api", 80u64), ("worker", 20)] {
+            let events = Arc::clone(&events);
+            let cancel = cancel.clone();
+            services.spawn(async move {
+                cancel.cancelled().await;
+                // Simulate flushing in-flight work to the database
+                tokio::time::sleep(std::time::Duration::from_millis(flush_ms)).await;
+                events.lock().unwrap().push(format!("{} stopped", name));
+            });
+        }
+
+        cancel.cancel();
+        let order = vec!["postgres".to_string(), "vault".to_string()];
+        let docker_events = Arc::clone(&events);
+        shutdown_in_order(
+            &services,
+            std::time::Duration::from_secs(5),
+            &order,
+            |name| {
+                let events = Arc::clone(&docker_events);
+                async move {
+                    events.lock().unwrap().push(format!("{} stopped", name));
+                }
+            },
+        )
+        .await;
+
+        let events = events.lock().unwrap().clone();
+        assert_eq!(
+            events,
+            vec!["worker stopped", "api stopped", "postgres stopped", "vault stopped"]
+        );
+    }
+
+    #[tokio::test]
+    async fn tiers_stop_dependents_before_dependencies() {
+        let events = Arc::new(Mutex::new(Vec::<String>::new()));
+        let mut running = BTreeMap::new();
+        // The api flushes fastest, but must still wait for its dependents
+        for (name, flush_ms) in [("api", 5u64), ("api#1", 10), ("worker", 60), ("web", 30)] {
+            let events = Arc::clone(&events);
+            let cancel = CancellationToken::new();
+            let token = cancel.clone();
+            let handle = tokio::spawn(async move {
+                token.cancelled().await;
+                tokio::time::sleep(std::time::Duration::from_millis(flush_ms)).await;
+                events.lock().unwrap().push(name.to_string());
+            });
+            running.insert(
+                name.to_string(),
+                RunningSupervisor {
+                    cancel,
+                    handle,
+                    env: BTreeMap::new(),
+                },
+            );
+        }
+
+        let tiers = vec![
+            vec!["web".to_string(), "worker".to_string()],
+            vec!["api".to_string(), "not-running".to_string()],
+        ];
+        stop_in_tiers(&mut running, &tiers, |_| std::time::Duration::from_secs(5)).await;
+
+        assert!(running.is_empty());
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec!["web", "worker", "api", "api#1"]
+        );
+    }
+
+    #[tokio::test]
+    async fn cancelled_service_is_stopped_not_failed() {
+        let dir = tempfile::tempdir().unwrap();
+        let config: DevrigConfig = toml::from_str(
+            r#"
+[project]
+name = "test"
+
+[services.crash]
+command = "echo crashed; exit 3"
+shell = "sh -c"
+restart = { initial_delay_ms = 60000, max_delay_ms = 60000 }
+"#,
+        )
+        .unwrap();
+        let (log_tx, mut log_rx) = broadcast::channel::<LogLine>(16);
+        let (events_tx, mut events_rx) = broadcast::channel(16);
+        let (failure_tx, mut failure_rx) = mpsc::unbounded_channel::<ServiceFailure>();
+        let cancel = CancellationToken::new();
+        let tracker = TaskTracker::new();
+        let launcher = ServiceLauncher {
+            config: &config,
+            config_path: &dir.path().join("devrig.toml"),
+            state_dir: dir.path(),
+            resolved_ports: &HashMap::new(),
+            template_vars: &HashMap::new(),
+            secrets: &std::sync::Mutex::default(),
+            compose_states: &BTreeMap::new(),
+            dashboard_state: None,
+            log_tx,
+            events_tx: Some(events_tx),
+            cancel: &cancel,
+            tracker: &tracker,
+            max_log_rate: None,
+            failure_tx: Some(failure_tx),
+        };
+
+        let running = launcher.spawn("crash").await.unwrap();
+        // Crashed once; the supervisor is now waiting out its backoff.
+        let line = tokio::time::timeout(std::time::Duration::from_secs(5), log_rx.recv())
+            .await
+            .expect("service should log before crashing")
+            .unwrap();
+        assert_eq!(line.text.trim(), "crashed");
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+
+        running.cancel.cancel();
+        running.handle.await.unwrap();
+
+        let mut statuses = Vec::new();
+        while let Ok(event) = events_rx.try_recv() {
+            if let crate::otel::types::TelemetryEvent::ServiceStatusChange { status, .. } = event {
+                statuses.push(status);
+            }
+        }
+        assert_eq!(statuses.last().map(String::as_str), Some("stopped"));
+        assert!(failure_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn reload_env_keeps_pinned_builtins_and_tracks_new_secrets() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("devrig.toml");
+        std::fs::write(
+            &config_path,
+            r#"
+[project]
+name = "test"
+env_file = ".env"
+
+[services.api]
+command = "sleep 30"
+shell = "sh -c"
+env = { RUN_ID = "{{ uuid }}", TOKEN = "$API_TOKEN" }
+"#,
+        )
+        .unwrap();
+        std::fs::write(dir.path().join(".env"), "API_TOKEN=first-token\n").unwrap();
+        let (mut config, _, secrets) = config::load_config_with_secrets(&config_path).unwrap();
+        let mut template_vars = HashMap::new();
+        add_builtin_vars(&mut template_vars);
+        resolve_config_templates(&mut config, &template_vars).unwrap();
+
+        let secrets = std::sync::Mutex::new(secrets);
+        let (log_tx, _log_rx) = broadcast::channel::<LogLine>(16);
+        let cancel = CancellationToken::new();
+        let tracker = TaskTracker::new();
+        let launcher = ServiceLauncher {
+            config: &config,
+            config_path: &config_path,
+            state_dir: dir.path(),
+            resolved_ports: &HashMap::new(),
+            template_vars: &template_vars,
+            secrets: &secrets,
+            compose_states: &BTreeMap::new(),
+            dashboard_state: None,
+            log_tx,
+            events_tx: None,
+            cancel: &cancel,
+            tracker: &tracker,
+            max_log_rate: None,
+            failure_tx: None,
+        };
+        let mut running = BTreeMap::new();
+        running.insert("api".to_string(), launcher.spawn("api").await.unwrap());
+
+        let changes = launcher.reload_env(&mut running, &[]).await.unwrap();
+        assert!(changes.is_empty(), "{changes:?}");
+
+        std::fs::write(dir.path().join(".env"), "API_TOKEN=rotated-token\n").unwrap();
+        let changes = launcher.reload_env(&mut running, &[]).await.unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].keys, vec!["API_TOKEN", "TOKEN"]);
+        assert_eq!(
+            secrets.lock().unwrap().mask_value("rotated-token"),
+            "****"
+        );
+
+        cancel.cancel();
+        for (_, sup) in running {
+            sup.handle.await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn slow_tier_times_out_and_next_tier_still_stops() {
+        let mut running = BTreeMap::new();
+        let stuck = CancellationToken::new();
+        running.insert(
+            "stuck".to_string(),
+            RunningSupervisor {
+                cancel: stuck.clone(),
+                handle: tokio::spawn(std::future::pending::<()>()),
+                env: BTreeMap::new(),
+            },
+        );
+        let db = CancellationToken::new();
+        let db_token = db.clone();
+        running.insert(
+            "db".to_string(),
+            RunningSupervisor {
+                cancel: db.clone(),
+                handle: tokio::spawn(async move { db_token.cancelled().await }),
+                env: BTreeMap::new(),
+            },
+        );
+
+        let tiers = vec![vec!["stuck".to_string()], vec!["db".to_string()]];
+        stop_in_tiers(&mut running, &tiers, |_| {
+            std::time::Duration::from_millis(50)
+        })
+        .await;
+
+        assert!(stuck.is_cancelled());
+        assert!(db.is_cancelled());
+    }
 }
@@ -0,0 +1,618 @@
+//! `devrig start --dry-run`: what `start` would do, resolved without touching
+//! Docker, k3d or any service processes.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+
+use anyhow::{bail, Result};
+use serde::Serialize;
+
+use super::graph::ResourceKind;
+use super::ports::{check_all_ports_unified, format_port_conflicts, resolve_port, AllocatedPorts};
+use super::state::{DashboardState, ProjectState};
+use super::{full_service_env, resolve_dashboard_port, Orchestrator};
+use crate::config::interpolate::{build_template_vars, resolve_config_templates};
+use crate::config::model::{AddonConfig, PortProtocol};
+use crate::discovery::url::service_url;
+use crate::docker::{resolve_docker_ports, ResolvedDockerPorts};
+
+/// Everything `devrig start` would do for one invocation.
+#[derive(Debug, Clone, Serialize)]
+pub struct StartPlan {
+    pub project: String,
+    /// Resources in the order they start.
+    pub launch_order: Vec<PlannedResource>,
+    /// Host ports keyed like `service:api`, `docker:postgres` or
+    /// `docker:redis:admin`, plus `dashboard`, `otel-grpc` and `otel-http`.
+    pub ports: BTreeMap<String, u16>,
+    pub images: Vec<PlannedImage>,
+    pub cluster: Option<PlannedCluster>,
+    /// Addons in install order.
+    pub addons: Vec<PlannedAddon>,
+    /// Final env per service, after template resolution, with secrets
+    /// masked.
+    pub env: BTreeMap<String, BTreeMap<String, String>>,
+    /// Problems `start` would hit, such as fixed ports already in use.
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlannedResource {
+    pub name: String,
+    pub kind: &'static str,
+}
+
+/// An image pulled for a `[docker.*]` resource or built for the cluster.
+#[derive(Debug, Clone, Serialize)]
+pub struct PlannedImage {
+    pub resource: String,
+    /// `pull` or `build`.
+    pub action: &'static str,
+    /// The image reference to pull, or the build context and Dockerfile.
+    pub source: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlannedCluster {
+    pub name: String,
+    pub agents: u32,
+    pub registry: bool,
+    pub k3s_image: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlannedAddon {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub addon_type: String,
+    pub namespace: Option<String>,
+    /// Helm chart, or the manifest / kustomize path.
+    pub source: String,
+}
+
+/// Stand-in for a template value that only exists once its resource is
+/// running (compose ports, built image tags, the registry's host port).
+fn pending(key: &str) -> String {
+    format!("<{key}>")
+}
+
+impl Orchestrator {
+    /// Resolve what [`Orchestrator::start`] would do for the same arguments:
+    /// launch order, ports, images, addons and each service's env. Ports are
+    /// probed for availability but nothing is started, created or written.
+    /// Like `start`, this resolves `{{ }}` templates in the loaded config.
+    pub fn plan(
+        &mut self,
+        service_filter: Vec<String>,
+        profile: Option<String>,
+    ) -> Result<StartPlan> {
+        let launch_order = self.resolve_launch_order(service_filter, profile)?;
+        let prev_state = ProjectState::load(&self.state_dir);
+
+        let dashboard_config = self
+            .config
+            .dashboard
+            .as_ref()
+            .filter(|d| d.enabled.unwrap_or(true));
+        if launch_order.is_empty() && dashboard_config.is_none() {
+            bail!("no resources to start");
+        }
+
+        let mut warnings = Vec::new();
+        let conflicts = check_all_ports_unified(&self.config);
+        if !conflicts.is_empty() {
+            warnings.push(format_port_conflicts(&conflicts));
+        }
+
+        let mut allocated_ports = AllocatedPorts::new();
+        let mut resolved_ports: HashMap<String, u16> = HashMap::new();
+
+        let dashboard_state = dashboard_config.map(|dash_config| -> Result<_> {
+            let otel_config = dash_config.otel.clone().unwrap_or_default();
+            let tls_cert = dash_config.tls.as_ref().and_then(|tls| {
+                let config_dir = self
+                    .config_path
+                    .parent()
+                    .unwrap_or(std::path::Path::new("."));
+                tls.resolved_files(config_dir).map(|(cert, _)| cert)
+            });
+            let otel_tls =
+                tls_cert.is_some() && dash_config.tls.as_ref().is_some_and(|tls| tls.otel);
+            Ok(DashboardState {
+                dashboard_port: resolve_dashboard_port(
+                    &dash_config.port,
+                    "dashboard",
+                    &mut allocated_ports,
+                )?,
+                grpc_port: resolve_dashboard_port(
+                    &otel_config.grpc_port,
+                    "otel-grpc",
+                    &mut allocated_ports,
+                )?,
+                http_port: resolve_dashboard_port(
+                    &otel_config.http_port,
+                    "otel-http",
+                    &mut allocated_ports,
+                )?,
+                tls_cert,
+                otel_tls,
+            })
+        });
+        let dashboard_state = dashboard_state.transpose()?;
+        if let Some(ref ds) = dashboard_state {
+            resolved_ports.insert("dashboard".to_string(), ds.dashboard_port);
+            resolved_ports.insert("otel-grpc".to_string(), ds.grpc_port);
+            resolved_ports.insert("otel-http".to_string(), ds.http_port);
+        }
+
+        let resources_of = |kind: ResourceKind| {
+            launch_order
+                .iter()
+                .filter(move |(_, k)| *k == kind)
+                .map(|(name, _)| name.as_str())
+        };
+
+        for name in resources_of(ResourceKind::Docker) {
+            let prev_docker = prev_state.as_ref().and_then(|s| s.docker.get(name));
+            let ResolvedDockerPorts {
+                port, named_ports, ..
+            } = resolve_docker_ports(
+                name,
+                &self.config.docker[name],
+                prev_docker,
+                &mut allocated_ports,
+            )?;
+            if let Some(port) = port {
+                resolved_ports.insert(format!("docker:{}", name), port);
+            }
+            for (pname, port) in named_ports {
+                resolved_ports.insert(format!("docker:{}:{}", name, pname), port);
+            }
+        }
+
+        for name in resources_of(ResourceKind::Service) {
+            let Some(port_config) = &self.config.services[name].port else {
+                continue;
+            };
+            let prev_service = prev_state.as_ref().and_then(|s| s.services.get(name));
+            let port = resolve_port(
+                &format!("service:{}", name),
+                port_config,
+                PortProtocol::Tcp,
+                prev_service.and_then(|s| s.port),
+                prev_service.is_some_and(|s| s.port_auto),
+                &mut allocated_ports,
+            )?;
+            resolved_ports.insert(format!("service:{}", name), port);
+        }
+
+        let mut template_vars = build_template_vars(&self.config, &resolved_ports);
+        let compose_services: Vec<&str> = resources_of(ResourceKind::Compose).collect();
+        for name in &compose_services {
+            let key = format!("compose.{}.port", name);
+            template_vars.insert(key.clone(), pending(&key));
+        }
+        if let Some(cluster) = &self.config.cluster {
+            for name in resources_of(ResourceKind::ClusterImage)
+                .chain(resources_of(ResourceKind::ClusterDeploy))
+            {
+                let key = format!("cluster.image.{}.tag", name);
+                template_vars.insert(key.clone(), pending(&key));
+            }
+            template_vars.insert(
+                "cluster.kubeconfig".to_string(),
+                self.state_dir.join("kubeconfig").display().to_string(),
+            );
+            if cluster.registry {
+                template_vars.insert(
+                    "cluster.registry".to_string(),
+                    format!("k3d-devrig-{}-reg:5000", self.identity.slug),
+                );
+                template_vars.insert(
+                    "cluster.registry_host".to_string(),
+                    pending("cluster.registry_host"),
+                );
+            }
+        }
+
+        if let Err(errors) = resolve_config_templates(&mut self.config, &template_vars) {
+            let mut msg = String::from("Template resolution errors:\n");
+            for err in &errors {
+                msg.push_str(&format!("  - {}\n", err));
+            }
+            bail!("{}", msg.trim_end());
+        }
+
+        let mut env = BTreeMap::new();
+        for name in resources_of(ResourceKind::Service) {
+            let mut service_env = full_service_env(
+                &self.config,
+                name,
+                &resolved_ports,
+                &BTreeMap::new(),
+                dashboard_state.as_ref(),
+            );
+            let prefix = self.config.project.env_prefix();
+            for cs_name in &compose_services {
+                let upper = cs_name.to_uppercase();
+                let port = pending(&format!("compose.{}.port", cs_name));
+                service_env.insert(format!("{}{}_HOST", prefix, upper), "localhost".to_string());
+                let protocol = self
+                    .config
+                    .compose
+                    .as_ref()
+                    .and_then(|c| c.protocol(cs_name));
+                service_env.insert(
+                    format!("{}{}_URL", prefix, upper),
+                    service_url(protocol, &port),
+                );
+                service_env.insert(format!("{}{}_PORT", prefix, upper), port);
+            }
+            let secrets = self.secrets.lock().unwrap();
+            for value in service_env.values_mut() {
+                *value = secrets.mask_value(value);
+            }
+            env.insert(name.to_string(), service_env);
+        }
+
+        let mut images: Vec<PlannedImage> = resources_of(ResourceKind::Docker)
+            .map(|name| PlannedImage {
+                resource: name.to_string(),
+                action: "pull",
+                source: self.config.docker[name].image.clone(),
+            })
+            .collect();
+
+        let mut cluster = None;
+        let mut addons = Vec::new();
+        if let Some(cluster_config) = &self.config.cluster {
+            let builds = resources_of(ResourceKind::ClusterImage)
+                .map(|name| {
+                    let image = &cluster_config.images[name];
+                    (name, &image.context, &image.dockerfile)
+                })
+                .chain(resources_of(ResourceKind::ClusterDeploy).map(|name| {
+                    let deploy = &cluster_config.deploy[name];
+                    (name, &deploy.context, &deploy.dockerfile)
+                }));
+            for (name, context, dockerfile) in builds {
+                images.push(PlannedImage {
+                    resource: name.to_string(),
+                    action: "build",
+                    source: format!("{} ({})", context, dockerfile),
+                });
+            }
+
+            cluster = Some(PlannedCluster {
+                name: self.identity.cluster_name(),
+                agents: cluster_config.agents,
+                registry: cluster_config.registry,
+                k3s_image: cluster_config.k3s_image.clone(),
+            });
+
+            for name in crate::cluster::addon::topo_sort_addons(&cluster_config.addons)? {
+                let addon = &cluster_config.addons[&name];
+                let source = match addon {
+                    AddonConfig::Helm { chart, .. } => chart.clone(),
+                    AddonConfig::Manifest { path, .. } | AddonConfig::Kustomize { path, .. } => {
+                        path.clone()
+                    }
+                };
+                addons.push(PlannedAddon {
+                    name,
+                    addon_type: addon.addon_type().to_string(),
+                    namespace: addon.namespace().map(str::to_string),
+                    source,
+                });
+            }
+            if cluster_config
+                .logs
+                .as_ref()
+                .is_some_and(|l| l.enabled && l.collector)
+            {
+                addons.push(PlannedAddon {
+                    name: crate::cluster::log_collector::ADDON_KEY.to_string(),
+                    addon_type: "manifest".to_string(),
+                    namespace: None,
+                    source: "generated Fluent Bit log collector".to_string(),
+                });
+            }
+        }
+
+        Ok(StartPlan {
+            project: self.identity.name.clone(),
+            launch_order: launch_order
+                .iter()
+                .map(|(name, kind)| PlannedResource {
+                    name: name.clone(),
+                    kind: kind.label(),
+                })
+                .collect(),
+            ports: resolved_ports.into_iter().collect(),
+            images,
+            cluster,
+            addons,
+            env,
+            warnings,
+        })
+    }
+}
+
+/// Render a plan as the human-readable text `devrig start --dry-run` prints.
+pub fn render_plan(plan: &StartPlan) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "Plan for {} (dry run, nothing was started)",
+        plan.project
+    );
+
+    let _ = writeln!(out, "\nLaunch order:");
+    if plan.launch_order.is_empty() {
+        let _ = writeln!(out, "  (dashboard only)");
+    }
+    for (i, resource) in plan.launch_order.iter().enumerate() {
+        let _ = writeln!(out, "  {:>2}. {} ({})", i + 1, resource.name, resource.kind);
+    }
+
+    if !plan.ports.is_empty() {
+        let _ = writeln!(out, "\nPorts:");
+        let width = plan.ports.keys().map(String::len).max().unwrap_or(0);
+        for (key, port) in &plan.ports {
+            let _ = writeln!(out, "  {:<width$}  {}", key, port);
+        }
+    }
+
+    if !plan.images.is_empty() {
+        let _ = writeln!(out, "\nImages:");
+        let width = plan
+            .images
+            .iter()
+            .map(|i| i.resource.len())
+            .max()
+            .unwrap_or(0);
+        for image in &plan.images {
+            let _ = writeln!(
+                out,
+                "  {:<width$}  {:<5}  {}",
+                image.resource, image.action, image.source
+            );
+        }
+    }
+
+    if let Some(cluster) = &plan.cluster {
+        let _ = writeln!(out, "\nCluster:");
+        let _ = writeln!(
+            out,
+            "  {} ({} agent(s){}{})",
+            cluster.name,
+            cluster.agents,
+            if cluster.registry {
+                ", local registry"
+            } else {
+                ""
+            },
+            cluster
+                .k3s_image
+                .as_deref()
+                .map(|image| format!(", {}", image))
+                .unwrap_or_default()
+        );
+    }
+
+    if !plan.addons.is_empty() {
+        let _ = writeln!(out, "\nAddons:");
+        for addon in &plan.addons {
+            let namespace = addon
+                .namespace
+                .as_deref()
+                .map(|ns| format!(" -n {}", ns))
+                .unwrap_or_default();
+            let _ = writeln!(
+                out,
+                "  {} ({}{}): {}",
+                addon.name, addon.addon_type, namespace, addon.source
+            );
+        }
+    }
+
+    for (service, env) in &plan.env {
+        let _ = writeln!(out, "\nEnv for {}:", service);
+        for (key, value) in env {
+            let _ = writeln!(out, "  {}={}", key, value);
+        }
+    }
+
+    for warning in &plan.warnings {
+        let _ = writeln!(out, "\nwarning: {}", warning);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn orchestrator(dir: &Path, toml: &str) -> Orchestrator {
+        let path = dir.join("devrig.toml");
+        std::fs::write(&path, toml).unwrap();
+        Orchestrator::from_config(path).unwrap()
+    }
+
+    #[test]
+    fn plan_resolves_order_ports_and_env_without_side_effects() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut orch = orchestrator(
+            dir.path(),
+            r#"
+[project]
+name = "planned"
+
+[docker.postgres]
+image = "postgres:16"
+port = "auto"
+
+[services.api]
+command = "cargo run"
+port = "auto"
+depends_on = ["postgres"]
+
+[services.api.env]
+DATABASE_URL = "postgres://localhost:{{ docker.postgres.port }}/app"
+
+[services.web]
+command = "npm run dev"
+depends_on = ["api"]
+"#,
+        );
+
+        let plan = orch.plan(vec!["api".to_string()], None).unwrap();
+
+        let order: Vec<(&str, &str)> = plan
+            .launch_order
+            .iter()
+            .map(|r| (r.name.as_str(), r.kind))
+            .collect();
+        assert_eq!(order, vec![("postgres", "docker"), ("api", "service")]);
+
+        let pg_port = plan.ports["docker:postgres"];
+        let api_port = plan.ports["service:api"];
+        assert_ne!(pg_port, api_port);
+
+        let api_env = &plan.env["api"];
+        assert_eq!(
+            api_env["DATABASE_URL"],
+            format!("postgres://localhost:{}/app", pg_port)
+        );
+        assert_eq!(api_env["PORT"], api_port.to_string());
+        assert_eq!(api_env["DEVRIG_POSTGRES_PORT"], pg_port.to_string());
+        assert!(!plan.env.contains_key("web"));
+
+        assert_eq!(plan.images.len(), 1);
+        assert_eq!(plan.images[0].action, "pull");
+        assert_eq!(plan.images[0].source, "postgres:16");
+
+        // Dry runs leave no state behind.
+        assert!(!dir.path().join(".devrig").exists());
+
+        let text = render_plan(&plan);
+        assert!(text.contains("   1. postgres (docker)\n"), "{text}");
+        assert!(text.contains("Env for api:\n"), "{text}");
+    }
+
+    #[test]
+    fn service_globs_expand_before_dependencies() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut orch = orchestrator(
+            dir.path(),
+            r#"
+[project]
+name = "planned"
+
+[docker.redis]
+image = "redis:7"
+
+[services.api-users]
+command = "cargo run"
+depends_on = ["redis"]
+
+[services.api-orders]
+command = "cargo run"
+
+[services.billing-worker]
+command = "cargo run"
+depends_on = ["api-orders"]
+
+[services.web]
+command = "npm run dev"
+"#,
+        );
+
+        let plan = orch
+            .plan(vec!["api-u*".to_string(), "*-worker".to_string()], None)
+            .unwrap();
+        let mut names: Vec<&str> = plan.launch_order.iter().map(|r| r.name.as_str()).collect();
+        names.sort();
+        assert_eq!(
+            names,
+            vec!["api-orders", "api-users", "billing-worker", "redis"]
+        );
+
+        let err = orch.plan(vec!["db-*".to_string()], None).unwrap_err();
+        assert!(err.to_string().contains("matches no service"), "{err}");
+        assert!(err.to_string().contains("billing-worker"), "{err}");
+
+        // Globs only match services, so docker names are left out.
+        assert!(orch.plan(vec!["red*".to_string()], None).is_err());
+    }
+
+    #[test]
+    fn plan_masks_secrets_in_env() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join(".env"), "API_TOKEN=s3cr3t-value\n").unwrap();
+        let mut orch = orchestrator(
+            dir.path(),
+            r#"
+[project]
+name = "planned"
+env_file = ".env"
+
+[services.api]
+command = "cargo run"
+
+[services.api.env]
+TOKEN = "$API_TOKEN"
+"#,
+        );
+
+        let plan = orch.plan(Vec::new(), None).unwrap();
+        assert_eq!(plan.env["api"]["TOKEN"], "****");
+    }
+
+    #[test]
+    fn plan_marks_values_only_known_at_runtime() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut orch = orchestrator(
+            dir.path(),
+            r#"
+[project]
+name = "planned"
+
+[services.worker]
+command = "cargo run"
+
+[services.worker.env]
+API_IMAGE = "{{ cluster.registry }}/api:{{ cluster.image.api.tag }}"
+
+[cluster]
+registry = true
+
+[cluster.deploy.api]
+context = "./api"
+manifests = "./k8s"
+
+[cluster.addons.traefik]
+type = "helm"
+chart = "traefik/traefik"
+namespace = "traefik"
+"#,
+        );
+
+        let plan = orch.plan(Vec::new(), None).unwrap();
+
+        let api_image = &plan.env["worker"]["API_IMAGE"];
+        assert!(
+            api_image.ends_with("/api:<cluster.image.api.tag>"),
+            "{api_image}"
+        );
+        assert!(plan
+            .images
+            .iter()
+            .any(|i| i.resource == "api" && i.action == "build"));
+        assert_eq!(plan.addons.len(), 1);
+        assert_eq!(plan.addons[0].addon_type, "helm");
+        assert_eq!(plan.addons[0].namespace.as_deref(), Some("traefik"));
+        assert!(plan.cluster.as_ref().unwrap().registry);
+    }
+}
@@ -1,75 +1,142 @@
-use crate::config::model::{DevrigConfig, Port, ServiceConfig};
+use crate::config::model::{DevrigConfig, Port, PortProtocol, ServiceConfig};
 use std::collections::{BTreeMap, HashSet};
-use std::net::TcpListener;
+use std::net::{TcpListener, UdpSocket};
 
 #[derive(Debug)]
 pub struct PortConflict {
     pub service: String,
     pub port: u16,
+    pub protocol: PortProtocol,
     pub owner: Option<String>,
 }
 
 impl std::fmt::Display for PortConflict {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let port = match self.protocol {
+            PortProtocol::Tcp => self.port.to_string(),
+            PortProtocol::Udp => format!("{}/udp", self.port),
+        };
         match &self.owner {
             Some(owner) => write!(
                 f,
                 "Port {} required by '{}' is already in use by {}",
-                self.port, self.service, owner
+                port, self.service, owner
             ),
             None => write!(
                 f,
                 "Port {} required by '{}' is already in use",
-                self.port, self.service
+                port, self.service
             ),
         }
     }
 }
 
+/// Every port in a [`Port::Range`] is taken, either by another process or
+/// by a resource that resolved its port earlier in the same run.
+#[derive(Debug)]
+pub struct PortRangeExhausted {
+    pub resource: String,
+    pub start: u16,
+    pub end: u16,
+    pub protocol: PortProtocol,
+}
+
+impl std::fmt::Display for PortRangeExhausted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let suffix = match self.protocol {
+            PortProtocol::Tcp => "",
+            PortProtocol::Udp => "/udp",
+        };
+        write!(
+            f,
+            "No free port in range {}-{}{} required by '{}': every port in it is already in use",
+            self.start, self.end, suffix, self.resource
+        )
+    }
+}
+
+impl std::error::Error for PortRangeExhausted {}
+
 pub fn check_port_available(port: u16) -> bool {
     TcpListener::bind(("127.0.0.1", port)).is_ok()
 }
 
+/// Like [`check_port_available`], but for either protocol. TCP and UDP ports
+/// are separate: a bound TCP port doesn't make the same UDP port unavailable.
+pub fn check_protocol_port_available(port: u16, protocol: PortProtocol) -> bool {
+    match protocol {
+        PortProtocol::Tcp => check_port_available(port),
+        PortProtocol::Udp => UdpSocket::bind(("127.0.0.1", port)).is_ok(),
+    }
+}
+
 pub fn find_free_port() -> u16 {
-    TcpListener::bind(("127.0.0.1", 0))
-        .expect("failed to bind ephemeral port")
-        .local_addr()
-        .unwrap()
-        .port()
+    find_free_protocol_port(PortProtocol::Tcp)
+}
+
+/// A free ephemeral port for `protocol`, as picked by the OS.
+fn find_free_protocol_port(protocol: PortProtocol) -> u16 {
+    let addr = match protocol {
+        PortProtocol::Tcp => TcpListener::bind(("127.0.0.1", 0)).and_then(|l| l.local_addr()),
+        PortProtocol::Udp => UdpSocket::bind(("127.0.0.1", 0)).and_then(|s| s.local_addr()),
+    };
+    addr.expect("failed to bind ephemeral port").port()
 }
 
-/// Find a free port that is not already in the allocated set.
-pub fn find_free_port_excluding(allocated: &HashSet<u16>) -> u16 {
+/// Host ports handed out during one startup. TCP and UDP ports of the same
+/// number are separate, so entries carry their protocol.
+pub type AllocatedPorts = HashSet<(u16, PortProtocol)>;
+
+/// Find a free `protocol` port that is not already in the allocated set.
+pub fn find_free_port_excluding(allocated: &AllocatedPorts, protocol: PortProtocol) -> u16 {
     for _ in 0..100 {
-        let port = find_free_port();
-        if !allocated.contains(&port) {
+        let port = find_free_protocol_port(protocol);
+        if !allocated.contains(&(port, protocol)) {
             return port;
         }
     }
     panic!("failed to find a free port after 100 attempts");
 }
 
-/// Resolve a single port from its config, respecting sticky auto-ports from
-/// previous state.
+/// The first free `protocol` port in `start..=end` that is not in the
+/// allocated set, trying `preferred` first.
+pub fn find_free_port_in_range(
+    preferred: Option<u16>,
+    start: u16,
+    end: u16,
+    protocol: PortProtocol,
+    allocated: &AllocatedPorts,
+) -> Option<u16> {
+    preferred.into_iter().chain(start..=end).find(|port| {
+        !allocated.contains(&(*port, protocol)) && check_protocol_port_available(*port, protocol)
+    })
+}
+
+/// Resolve a single `protocol` port from its config, respecting sticky
+/// auto-ports from previous state. Fails only when every port of a range
+/// is taken.
 pub fn resolve_port(
     resource_key: &str,
     port_config: &Port,
+    protocol: PortProtocol,
     prev_port: Option<u16>,
     prev_auto: bool,
-    allocated: &mut HashSet<u16>,
-) -> u16 {
+    allocated: &mut AllocatedPorts,
+) -> Result<u16, PortRangeExhausted> {
     match port_config {
         Port::Fixed(p) => {
-            allocated.insert(*p);
-            *p
+            allocated.insert((*p, protocol));
+            Ok(*p)
         }
         Port::Auto => {
             // Try to reuse previously assigned auto port
             if prev_auto {
                 if let Some(prev) = prev_port {
-                    if !allocated.contains(&prev) && check_port_available(prev) {
-                        allocated.insert(prev);
-                        return prev;
+                    if !allocated.contains(&(prev, protocol))
+                        && check_protocol_port_available(prev, protocol)
+                    {
+                        allocated.insert((prev, protocol));
+                        return Ok(prev);
                     }
                     tracing::debug!(
                         "{}: previously assigned port {} no longer available",
@@ -78,9 +145,35 @@ pub fn resolve_port(
                     );
                 }
             }
-            let port = find_free_port_excluding(allocated);
-            allocated.insert(port);
-            port
+            let port = find_free_port_excluding(allocated, protocol);
+            allocated.insert((port, protocol));
+            Ok(port)
+        }
+        Port::Range {
+            preferred,
+            start,
+            end,
+        } => {
+            // Keep the port picked last time while it's in range and free
+            if prev_auto {
+                if let Some(prev) = prev_port.filter(|p| (*start..=*end).contains(p)) {
+                    if !allocated.contains(&(prev, protocol))
+                        && check_protocol_port_available(prev, protocol)
+                    {
+                        allocated.insert((prev, protocol));
+                        return Ok(prev);
+                    }
+                }
+            }
+            let port = find_free_port_in_range(*preferred, *start, *end, protocol, allocated)
+                .ok_or_else(|| PortRangeExhausted {
+                    resource: resource_key.to_string(),
+                    start: *start,
+                    end: *end,
+                    protocol,
+                })?;
+            allocated.insert((port, protocol));
+            Ok(port)
         }
     }
 }
@@ -90,6 +183,14 @@ pub fn identify_port_owner(port: u16) -> Option<String> {
     crate::platform::identify_port_owner(port)
 }
 
+/// Owner lookup only understands TCP sockets.
+fn identify_owner(port: u16, protocol: PortProtocol) -> Option<String> {
+    match protocol {
+        PortProtocol::Tcp => identify_port_owner(port),
+        PortProtocol::Udp => None,
+    }
+}
+
 /// Check all fixed ports (services + docker) for conflicts with already-bound
 /// ports on the system.
 pub fn check_all_ports_unified(config: &DevrigConfig) -> Vec<PortConflict> {
@@ -101,6 +202,7 @@ pub fn check_all_ports_unified(config: &DevrigConfig) -> Vec<PortConflict> {
                 conflicts.push(PortConflict {
                     service: name.clone(),
                     port: *port,
+                    protocol: PortProtocol::Tcp,
                     owner: identify_port_owner(*port),
                 });
             }
@@ -109,21 +211,24 @@ pub fn check_all_ports_unified(config: &DevrigConfig) -> Vec<PortConflict> {
 
     for (name, docker_cfg) in &config.docker {
         if let Some(Port::Fixed(port)) = &docker_cfg.port {
-            if !check_port_available(*port) {
+            let protocol = docker_cfg.port_protocol();
+            if !check_protocol_port_available(*port, protocol) {
                 conflicts.push(PortConflict {
                     service: format!("docker:{}", name),
                     port: *port,
-                    owner: identify_port_owner(*port),
+                    protocol,
+                    owner: identify_owner(*port, protocol),
                 });
             }
         }
         for (port_name, port_val) in &docker_cfg.ports {
-            if let Port::Fixed(port) = port_val {
-                if !check_port_available(*port) {
+            if let Some(port) = port_val.as_fixed() {
+                if !check_protocol_port_available(port, port_val.protocol) {
                     conflicts.push(PortConflict {
                         service: format!("docker:{}:{}", name, port_name),
-                        port: *port,
-                        owner: identify_port_owner(*port),
+                        port,
+                        protocol: port_val.protocol,
+                        owner: identify_owner(port, port_val.protocol),
                     });
                 }
             }
@@ -137,6 +242,7 @@ pub fn check_all_ports_unified(config: &DevrigConfig) -> Vec<PortConflict> {
                 conflicts.push(PortConflict {
                     service: "dashboard".to_string(),
                     port: *dash_port,
+                    protocol: PortProtocol::Tcp,
                     owner: identify_port_owner(*dash_port),
                 });
             }
@@ -148,6 +254,7 @@ pub fn check_all_ports_unified(config: &DevrigConfig) -> Vec<PortConflict> {
                 conflicts.push(PortConflict {
                     service: "otel-grpc".to_string(),
                     port: grpc_port,
+                    protocol: PortProtocol::Tcp,
                     owner: identify_port_owner(grpc_port),
                 });
             }
@@ -159,6 +266,7 @@ pub fn check_all_ports_unified(config: &DevrigConfig) -> Vec<PortConflict> {
                 conflicts.push(PortConflict {
                     service: "otel-http".to_string(),
                     port: http_port,
+                    protocol: PortProtocol::Tcp,
                     owner: identify_port_owner(http_port),
                 });
             }
@@ -177,6 +285,7 @@ pub fn check_all_ports(services: &BTreeMap<String, ServiceConfig>) -> Vec<PortCo
                 conflicts.push(PortConflict {
                     service: name.clone(),
                     port: *port,
+                    protocol: PortProtocol::Tcp,
                     owner: identify_port_owner(*port),
                 });
             }
@@ -193,3 +302,182 @@ pub fn format_port_conflicts(conflicts: &[PortConflict]) -> String {
     msg.push_str("\nFree the ports or change your devrig.toml configuration.");
     msg
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tcp_and_udp_ports_are_checked_separately() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        assert!(!check_protocol_port_available(port, PortProtocol::Tcp));
+
+        // The UDP port of the same number may still be taken by something
+        // else on a busy host, so only assert when it is actually free.
+        if let Ok(socket) = UdpSocket::bind(("127.0.0.1", port)) {
+            drop(socket);
+            assert!(check_protocol_port_available(port, PortProtocol::Udp));
+        }
+
+        let socket = UdpSocket::bind(("127.0.0.1", 0)).unwrap();
+        let udp_port = socket.local_addr().unwrap().port();
+        assert!(!check_protocol_port_available(udp_port, PortProtocol::Udp));
+    }
+
+    fn free_port() -> u16 {
+        TcpListener::bind(("127.0.0.1", 0))
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port()
+    }
+
+    #[test]
+    fn range_prefers_sticky_then_preferred_port() {
+        let port = free_port();
+        let range = |preferred| Port::Range {
+            preferred,
+            start: port - 5,
+            end: port,
+        };
+
+        let mut allocated = AllocatedPorts::new();
+        assert_eq!(
+            resolve_port(
+                "service:api",
+                &range(None),
+                PortProtocol::Tcp,
+                Some(port),
+                true,
+                &mut allocated
+            )
+            .unwrap(),
+            port
+        );
+        assert!(allocated.contains(&(port, PortProtocol::Tcp)));
+
+        let mut allocated = AllocatedPorts::new();
+        assert_eq!(
+            resolve_port(
+                "service:api",
+                &range(Some(port)),
+                PortProtocol::Tcp,
+                None,
+                false,
+                &mut allocated
+            )
+            .unwrap(),
+            port
+        );
+
+        // A remembered port outside the range is not reused
+        let mut allocated = AllocatedPorts::new();
+        let resolved = resolve_port(
+            "service:api",
+            &range(None),
+            PortProtocol::Tcp,
+            Some(port + 1),
+            true,
+            &mut allocated,
+        )
+        .unwrap();
+        assert!((port - 5..=port).contains(&resolved));
+    }
+
+    #[test]
+    fn full_range_is_a_conflict_naming_the_range() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let taken = listener.local_addr().unwrap().port();
+        let range = Port::Range {
+            preferred: None,
+            start: taken,
+            end: taken,
+        };
+        assert_eq!(
+            find_free_port_in_range(
+                None,
+                taken,
+                taken,
+                PortProtocol::Tcp,
+                &AllocatedPorts::new()
+            ),
+            None
+        );
+
+        let mut allocated = AllocatedPorts::new();
+        let err = resolve_port(
+            "service:api",
+            &range,
+            PortProtocol::Tcp,
+            Some(taken),
+            true,
+            &mut allocated,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "No free port in range {taken}-{taken} required by 'service:api': \
+                 every port in it is already in use"
+            )
+        );
+        assert!(allocated.is_empty());
+
+        // Ports already handed out in this run are skipped too
+        drop(listener);
+        let allocated = AllocatedPorts::from([(taken, PortProtocol::Tcp)]);
+        assert_eq!(
+            find_free_port_in_range(Some(taken), taken, taken, PortProtocol::Tcp, &allocated),
+            None
+        );
+    }
+
+    #[test]
+    fn udp_auto_ports_are_checked_and_allocated_as_udp() {
+        // A sticky UDP port still held over UDP is not reused
+        let socket = UdpSocket::bind(("127.0.0.1", 0)).unwrap();
+        let held = socket.local_addr().unwrap().port();
+        let mut allocated = AllocatedPorts::new();
+        let resolved = resolve_port(
+            "docker:dns",
+            &Port::Auto,
+            PortProtocol::Udp,
+            Some(held),
+            true,
+            &mut allocated,
+        )
+        .unwrap();
+        assert_ne!(resolved, held);
+        assert!(allocated.contains(&(resolved, PortProtocol::Udp)));
+        assert!(!allocated.contains(&(resolved, PortProtocol::Tcp)));
+
+        // Nor is one already handed out over UDP in this run, while the
+        // same number over TCP doesn't block it
+        drop(socket);
+        if UdpSocket::bind(("127.0.0.1", held)).is_ok() {
+            let mut allocated = AllocatedPorts::from([(held, PortProtocol::Tcp)]);
+            let range = Port::Range {
+                preferred: None,
+                start: held,
+                end: held,
+            };
+            assert_eq!(
+                resolve_port(
+                    "docker:dns",
+                    &range,
+                    PortProtocol::Udp,
+                    None,
+                    false,
+                    &mut allocated
+                )
+                .unwrap(),
+                held
+            );
+            assert_eq!(
+                find_free_port_in_range(None, held, held, PortProtocol::Udp, &allocated),
+                None
+            );
+        }
+    }
+}
@@ -0,0 +1,237 @@
+//! Dependency gating for `depends_on`.
+//!
+//! Each resource that dependents can wait on gets a gate. The gate moves to
+//! started once the resource is up, then to ready or failed once its ready
+//! check settles. A gate keeps its last state, so every dependent sees the
+//! outcome, however many there are and whenever they start waiting.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use tokio::sync::watch;
+
+use crate::config::model::{Dependency, DependencyCondition};
+use crate::orchestrator::graph::ResourceKind;
+
+/// Where a gated resource is in its startup.
+#[derive(Debug, Clone, PartialEq)]
+enum Readiness {
+    Pending,
+    Started,
+    Ready,
+    Failed(Arc<str>),
+}
+
+impl Readiness {
+    /// Whether a dependent waiting for `condition` can stop waiting.
+    fn settles(&self, condition: DependencyCondition) -> bool {
+        match condition {
+            DependencyCondition::Started => *self != Readiness::Pending,
+            DependencyCondition::Healthy => {
+                matches!(self, Readiness::Ready | Readiness::Failed(_))
+            }
+        }
+    }
+}
+
+/// Readiness gates, by resource name.
+#[derive(Debug, Default)]
+pub struct ReadinessGates {
+    gates: HashMap<String, watch::Sender<Readiness>>,
+}
+
+impl ReadinessGates {
+    /// Add a pending gate for `name`. Dependents wait on it until it is
+    /// started, ready or failed.
+    pub fn register(&mut self, name: &str) {
+        self.gates
+            .insert(name.to_string(), watch::Sender::new(Readiness::Pending));
+    }
+
+    /// Mark `name` as started.
+    pub fn started(&self, name: &str) {
+        self.set(name, Readiness::Started);
+    }
+
+    /// Mark `name` as ready, for resources with nothing left to check.
+    pub fn ready(&self, name: &str) {
+        self.set(name, Readiness::Ready);
+    }
+
+    /// Run `check` in the background and settle `name`'s gate with its
+    /// outcome.
+    pub fn track<F>(&self, name: &str, check: F)
+    where
+        F: Future<Output = Result<()>> + Send + 'static,
+    {
+        let Some(gate) = self.gates.get(name).cloned() else {
+            return;
+        };
+        tokio::spawn(async move {
+            gate.send_replace(match check.await {
+                Ok(()) => Readiness::Ready,
+                Err(e) => Readiness::Failed(format!("{:#}", e).into()),
+            });
+        });
+    }
+
+    fn set(&self, name: &str, readiness: Readiness) {
+        if let Some(gate) = self.gates.get(name) {
+            gate.send_replace(readiness);
+        }
+    }
+
+    /// Wait until every entry of `depends_on` meets its condition. Errors
+    /// when a dependency waited on for health fails its check. Dependencies
+    /// without a gate, such as compose services, do not block.
+    pub async fn wait<'a>(
+        &self,
+        kind: ResourceKind,
+        name: &str,
+        depends_on: impl IntoIterator<Item = &'a Dependency>,
+    ) -> Result<()> {
+        for dep in depends_on {
+            let Some(gate) = self.gates.get(&dep.name) else {
+                continue;
+            };
+            let mut rx = gate.subscribe();
+            if !rx.borrow().settles(dep.condition) {
+                tracing::debug!(
+                    resource = %name,
+                    dependency = %dep.name,
+                    "waiting for dependency"
+                );
+            }
+            let state = rx
+                .wait_for(|state| state.settles(dep.condition))
+                .await
+                .expect("gates outlive their waiters")
+                .clone();
+            if let (DependencyCondition::Healthy, Readiness::Failed(reason)) =
+                (dep.condition, state)
+            {
+                return Err(anyhow!("{}", reason).context(format!(
+                    "{} '{}' depends on '{}', which did not become ready",
+                    kind.label(),
+                    name,
+                    dep.name
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    fn dep(name: &str, condition: DependencyCondition) -> Dependency {
+        Dependency {
+            name: name.to_string(),
+            condition,
+        }
+    }
+
+    #[tokio::test]
+    async fn every_dependent_of_a_slow_service_waits_for_it() {
+        let mut gates = ReadinessGates::default();
+        gates.register("db");
+        let gates = Arc::new(gates);
+        let events = Arc::new(Mutex::new(Vec::<String>::new()));
+
+        let dependents: Vec<_> = ["api", "worker"]
+            .into_iter()
+            .map(|name| {
+                let gates = Arc::clone(&gates);
+                let events = Arc::clone(&events);
+                tokio::spawn(async move {
+                    let deps = [dep("db", DependencyCondition::Healthy)];
+                    gates
+                        .wait(ResourceKind::Service, name, &deps)
+                        .await
+                        .unwrap();
+                    events.lock().unwrap().push(format!("{} started", name));
+                })
+            })
+            .collect();
+
+        gates.started("db");
+        let slow = Arc::clone(&events);
+        gates.track("db", async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            slow.lock().unwrap().push("db ready".to_string());
+            Ok(())
+        });
+        for dependent in dependents {
+            dependent.await.unwrap();
+        }
+
+        let events = events.lock().unwrap();
+        assert_eq!(events[0], "db ready");
+        assert_eq!(events.len(), 3);
+        assert!(events.contains(&"api started".to_string()));
+        assert!(events.contains(&"worker started".to_string()));
+    }
+
+    #[tokio::test]
+    async fn failed_check_fails_every_healthy_dependent() {
+        let mut gates = ReadinessGates::default();
+        gates.register("db");
+        gates.started("db");
+        gates.track("db", async { Err(anyhow!("connection refused")) });
+
+        for name in ["api", "worker"] {
+            let err = gates
+                .wait(
+                    ResourceKind::Service,
+                    name,
+                    &[dep("db", DependencyCondition::Healthy)],
+                )
+                .await
+                .unwrap_err();
+            assert_eq!(
+                format!("{:#}", err),
+                format!(
+                    "service '{}' depends on 'db', which did not become ready: connection refused",
+                    name
+                )
+            );
+        }
+        gates
+            .wait(
+                ResourceKind::Service,
+                "sidecar",
+                &[dep("db", DependencyCondition::Started)],
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn started_dependents_do_not_wait_for_the_check() {
+        let mut gates = ReadinessGates::default();
+        gates.register("db");
+        gates.started("db");
+        gates.track("db", std::future::pending());
+
+        tokio::time::timeout(
+            Duration::from_secs(1),
+            gates.wait(
+                ResourceKind::Service,
+                "api",
+                &[
+                    dep("db", DependencyCondition::Started),
+                    dep("compose-only", DependencyCondition::Healthy),
+                ],
+            ),
+        )
+        .await
+        .expect("a started dependency should not block")
+        .unwrap();
+    }
+}
@@ -0,0 +1,151 @@
+//! Planning for `devrig reload-env`: which running services saw their env
+//! change, and how each one should pick the change up.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::model::{DevrigConfig, ReloadStrategy, DEFAULT_RELOAD_SIGNAL};
+
+/// How a changed service is told about its new env.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", content = "signal", rename_all = "snake_case")]
+pub enum ReloadAction {
+    Restart,
+    Signal(String),
+}
+
+/// A service whose env changed, with the names (not values) of the
+/// variables that were added, removed or modified.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnvChange {
+    pub service: String,
+    pub keys: Vec<String>,
+    #[serde(flatten)]
+    pub action: ReloadAction,
+}
+
+/// Names of variables that differ between `old` and `new`, sorted.
+pub fn changed_env_keys(
+    old: &BTreeMap<String, String>,
+    new: &BTreeMap<String, String>,
+) -> Vec<String> {
+    let mut keys: Vec<String> = old
+        .iter()
+        .filter(|(k, v)| new.get(*k) != Some(*v))
+        .map(|(k, _)| k.clone())
+        .collect();
+    keys.extend(new.keys().filter(|k| !old.contains_key(*k)).cloned());
+    keys.sort();
+    keys
+}
+
+/// Compare each service's `current` env with its freshly built env and
+/// decide what to do with the ones that changed. Services missing from
+/// `fresh` are left alone.
+pub fn plan_env_reload(
+    config: &DevrigConfig,
+    current: &BTreeMap<String, BTreeMap<String, String>>,
+    fresh: &BTreeMap<String, BTreeMap<String, String>>,
+) -> Vec<EnvChange> {
+    let mut changes = Vec::new();
+    for (service, old_env) in current {
+        let Some(new_env) = fresh.get(service) else {
+            continue;
+        };
+        let keys = changed_env_keys(old_env, new_env);
+        if keys.is_empty() {
+            continue;
+        }
+        let svc = config.services.get(service);
+        let action = match svc.and_then(|s| s.reload_strategy).unwrap_or_default() {
+            ReloadStrategy::Restart => ReloadAction::Restart,
+            ReloadStrategy::Signal => ReloadAction::Signal(
+                svc.and_then(|s| s.reload_signal.clone())
+                    .unwrap_or_else(|| DEFAULT_RELOAD_SIGNAL.to_string()),
+            ),
+        };
+        changes.push(EnvChange {
+            service: service.clone(),
+            keys,
+            action,
+        });
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn changed_keys_cover_added_removed_and_modified() {
+        let old = env(&[("A", "1"), ("B", "2"), ("C", "3")]);
+        let new = env(&[("A", "1"), ("B", "20"), ("D", "4")]);
+        assert_eq!(changed_env_keys(&old, &new), vec!["B", "C", "D"]);
+        assert!(changed_env_keys(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn only_services_with_changed_env_are_reloaded() {
+        let config: DevrigConfig = toml::from_str(
+            r#"
+            [project]
+            name = "test"
+
+            [services.api]
+            command = "api"
+            reload_strategy = "signal"
+            reload_signal = "SIGUSR1"
+
+            [services.worker]
+            command = "worker"
+
+            [services.web]
+            command = "web"
+            reload_strategy = "signal"
+            "#,
+        )
+        .unwrap();
+
+        let current = BTreeMap::from([
+            ("api".to_string(), env(&[("GREETING", "hello")])),
+            ("worker".to_string(), env(&[("QUEUE", "jobs")])),
+            ("web".to_string(), env(&[("THEME", "dark")])),
+        ]);
+        let mut fresh = current.clone();
+        fresh.insert("api".to_string(), env(&[("GREETING", "hi")]));
+
+        assert_eq!(
+            plan_env_reload(&config, &current, &fresh),
+            vec![EnvChange {
+                service: "api".to_string(),
+                keys: vec!["GREETING".to_string()],
+                action: ReloadAction::Signal("SIGUSR1".to_string()),
+            }]
+        );
+
+        fresh.insert("worker".to_string(), env(&[("QUEUE", "jobs"), ("X", "1")]));
+        fresh.insert("web".to_string(), env(&[]));
+        let plan = plan_env_reload(&config, &current, &fresh);
+        let actions: Vec<_> = plan
+            .iter()
+            .map(|c| (c.service.as_str(), c.action.clone()))
+            .collect();
+        assert_eq!(
+            actions,
+            vec![
+                ("api", ReloadAction::Signal("SIGUSR1".to_string())),
+                ("web", ReloadAction::Signal("SIGHUP".to_string())),
+                ("worker", ReloadAction::Restart),
+            ]
+        );
+    }
+}
@@ -0,0 +1,153 @@
+//! Planning for `devrig scale`: what extra replicas of a service are called,
+//! which ones to start or stop, and the env each one gets.
+//!
+//! Replica 0 is the service itself. Replica `n` runs as a separate supervisor
+//! named `<service>#<n>`, which is also its key in state.json and its
+//! service name in logs.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Separates a service name from a replica index, as in `api#2`.
+pub const REPLICA_SEPARATOR: char = '#';
+
+/// Env var holding a replica's index (`0` for the service itself), after
+/// the project's discovery var prefix.
+pub const REPLICA_INDEX_VAR: &str = "REPLICA_INDEX";
+
+/// One instance of a scaled service, as reported back to `devrig scale`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Replica {
+    pub service: String,
+    pub name: String,
+    pub index: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+}
+
+/// The supervisor name of replica `index` of `service`.
+pub fn replica_name(service: &str, index: usize) -> String {
+    if index == 0 {
+        service.to_string()
+    } else {
+        format!("{}{}{}", service, REPLICA_SEPARATOR, index)
+    }
+}
+
+/// The service a supervisor name belongs to, and its replica index.
+pub fn split_replica(name: &str) -> (&str, usize) {
+    match name.rsplit_once(REPLICA_SEPARATOR) {
+        Some((service, index)) => match index.parse() {
+            Ok(index) if index > 0 => (service, index),
+            _ => (name, 0),
+        },
+        None => (name, 0),
+    }
+}
+
+/// Replicas to start and stop so that `service` runs `count` instances.
+#[derive(Debug, Default, PartialEq)]
+pub struct ScalePlan {
+    /// Indexes of replicas to start, lowest first.
+    pub start: Vec<usize>,
+    /// Supervisor names of replicas to stop, highest index first.
+    pub stop: Vec<String>,
+}
+
+/// Plan scaling `service` to `count` instances, given the supervisor names
+/// currently running. The service itself is never stopped; `count` must be
+/// at least 1.
+pub fn plan_scale<'a>(
+    running: impl IntoIterator<Item = &'a String>,
+    service: &str,
+    count: usize,
+) -> ScalePlan {
+    let current: Vec<usize> = running
+        .into_iter()
+        .map(|name| split_replica(name))
+        .filter(|(base, index)| *base == service && *index > 0)
+        .map(|(_, index)| index)
+        .collect();
+
+    let start = (1..count).filter(|i| !current.contains(i)).collect();
+    let mut stop: Vec<usize> = current.into_iter().filter(|i| *i >= count).collect();
+    stop.sort_unstable_by(|a, b| b.cmp(a));
+    ScalePlan {
+        start,
+        stop: stop.into_iter().map(|i| replica_name(service, i)).collect(),
+    }
+}
+
+/// The env for replica `index` of `service`: the service's own env with
+/// `PORT` set to the replica's port, `<PREFIX><SERVICE>_PORT_<n>` for every
+/// replica in `ports`, and `<PREFIX>`[`REPLICA_INDEX_VAR`].
+pub fn replica_env(
+    service_env: &BTreeMap<String, String>,
+    prefix: &str,
+    service: &str,
+    index: usize,
+    ports: &BTreeMap<usize, u16>,
+) -> BTreeMap<String, String> {
+    let mut env = service_env.clone();
+    let upper = service.to_uppercase();
+    for (i, port) in ports {
+        env.insert(format!("{}{}_PORT_{}", prefix, upper, i), port.to_string());
+    }
+    if let Some(port) = ports.get(&index) {
+        env.insert("PORT".to_string(), port.to_string());
+    }
+    env.insert(format!("{}{}", prefix, REPLICA_INDEX_VAR), index.to_string());
+    env
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replica_names_round_trip() {
+        assert_eq!(replica_name("api", 0), "api");
+        assert_eq!(replica_name("api", 2), "api#2");
+        assert_eq!(split_replica("api#2"), ("api", 2));
+        assert_eq!(split_replica("api"), ("api", 0));
+        assert_eq!(split_replica("api#0"), ("api#0", 0));
+        assert_eq!(split_replica("api#x"), ("api#x", 0));
+    }
+
+    #[test]
+    fn plan_adds_missing_and_drops_highest_replicas() {
+        let running: Vec<String> = ["api", "api#1", "api#3", "web", "web#1"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+
+        let up = plan_scale(&running, "api", 4);
+        assert_eq!(up.start, vec![2]);
+        assert!(up.stop.is_empty());
+
+        let down = plan_scale(&running, "api", 1);
+        assert!(down.start.is_empty());
+        assert_eq!(down.stop, vec!["api#3", "api#1"]);
+
+        let other = plan_scale(&running, "worker", 3);
+        assert_eq!(other.start, vec![1, 2]);
+        assert!(other.stop.is_empty());
+    }
+
+    #[test]
+    fn replica_env_exposes_every_replica_port() {
+        let base = BTreeMap::from([
+            ("PORT".to_string(), "3000".to_string()),
+            ("DEVRIG_DB_PORT".to_string(), "5432".to_string()),
+        ]);
+        let ports = BTreeMap::from([(0, 3000), (1, 41001), (2, 41002)]);
+        let env = replica_env(&base, "DEVRIG_", "api", 2, &ports);
+        assert_eq!(env["PORT"], "41002");
+        assert_eq!(env["DEVRIG_API_PORT_0"], "3000");
+        assert_eq!(env["DEVRIG_API_PORT_1"], "41001");
+        assert_eq!(env["DEVRIG_API_PORT_2"], "41002");
+        assert_eq!(env["DEVRIG_REPLICA_INDEX"], "2");
+        assert_eq!(env["DEVRIG_DB_PORT"], "5432");
+    }
+}
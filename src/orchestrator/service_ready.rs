@@ -0,0 +1,247 @@
+//! Ready checks for local `[services]` processes.
+//!
+//! Services support the `tcp`, `http`, `grpc` and `log` ready check types,
+//! combined with `all_of` / `any_of`. The outcome settles the service's
+//! [`ReadinessGates`] entry, which gates services that `depends_on` it.
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+use backon::{ExponentialBuilder, Retryable};
+use futures_util::future::BoxFuture;
+use tokio::sync::broadcast;
+
+use crate::config::model::ReadyCheck;
+use crate::docker::ready::{probe_grpc, probe_http, probe_tcp};
+use crate::orchestrator::readiness::ReadinessGates;
+use crate::orchestrator::state::ProjectState;
+use crate::ui::logs::LogLine;
+
+/// Log patterns seen so far in a service's output.
+type SeenPatterns = Arc<Mutex<HashSet<String>>>;
+
+/// Wait until `check` passes for service `name`, or its timeout elapses.
+///
+/// `port` is the service's resolved port, used by `tcp` checks. `logs` must
+/// be subscribed before the service is spawned so `log` checks see its
+/// first lines.
+pub async fn wait_for_service(
+    name: &str,
+    check: &ReadyCheck,
+    port: Option<u16>,
+    logs: broadcast::Receiver<LogLine>,
+) -> Result<()> {
+    let total_timeout = Duration::from_secs(check.timeout_secs());
+
+    let mut patterns = Vec::new();
+    collect_log_patterns(check, &mut patterns);
+    let seen: SeenPatterns = Arc::default();
+    let log_watcher = (!patterns.is_empty()).then(|| {
+        tokio::spawn(watch_logs(
+            name.to_string(),
+            patterns,
+            logs,
+            Arc::clone(&seen),
+        ))
+    });
+
+    let result = tokio::time::timeout(total_timeout, async {
+        (|| probe(check, port, &seen))
+            .retry(
+                ExponentialBuilder::default()
+                    .with_min_delay(Duration::from_millis(250))
+                    .with_max_delay(Duration::from_secs(1))
+                    .without_max_times(),
+            )
+            .notify(|err: &anyhow::Error, dur: Duration| {
+                tracing::debug!(
+                    service = %name,
+                    "ready check failed: {}, retrying in {:?}",
+                    err,
+                    dur
+                );
+            })
+            .await
+    })
+    .await;
+
+    if let Some(watcher) = log_watcher {
+        watcher.abort();
+    }
+
+    match result {
+        Ok(inner) => inner,
+        Err(_) => bail!(
+            "ready check for service '{}' timed out after {:?}",
+            name,
+            total_timeout
+        ),
+    }
+}
+
+/// Run [`wait_for_service`] in the background, settling `name`'s gate in
+/// `gates` with the outcome, logging it and recording it in `state_dir`'s
+/// state.json.
+pub fn spawn_wait(
+    gates: &ReadinessGates,
+    name: String,
+    check: ReadyCheck,
+    port: Option<u16>,
+    logs: broadcast::Receiver<LogLine>,
+    state_dir: PathBuf,
+) {
+    let gate = name.clone();
+    gates.track(&gate, async move {
+        let result = wait_for_service(&name, &check, port, logs).await;
+        match &result {
+            Ok(()) => tracing::debug!(service = %name, "ready"),
+            Err(e) => tracing::warn!(service = %name, error = %e, "ready check failed"),
+        }
+        ProjectState::update_service_ready(&state_dir, &name, result.is_ok());
+        result
+    });
+}
+
+/// Collect every `log` pattern in `check`, including nested ones.
+fn collect_log_patterns(check: &ReadyCheck, patterns: &mut Vec<String>) {
+    match check {
+        ReadyCheck::Log { pattern, .. } => patterns.push(pattern.clone()),
+        ReadyCheck::AllOf { checks, .. } | ReadyCheck::AnyOf { checks, .. } => {
+            for child in checks {
+                collect_log_patterns(child, patterns);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Record which `patterns` have appeared in service `name`'s output.
+async fn watch_logs(
+    name: String,
+    patterns: Vec<String>,
+    mut logs: broadcast::Receiver<LogLine>,
+    seen: SeenPatterns,
+) {
+    loop {
+        match logs.recv().await {
+            Ok(line) if line.service == name => {
+                let mut seen = seen.lock().unwrap();
+                for pattern in &patterns {
+                    if line.text.contains(pattern.as_str()) {
+                        seen.insert(pattern.clone());
+                    }
+                }
+            }
+            Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
+
+/// Probe `check` once. Boxed so composite checks can recurse.
+fn probe<'a>(
+    check: &'a ReadyCheck,
+    port: Option<u16>,
+    seen: &'a SeenPatterns,
+) -> BoxFuture<'a, Result<()>> {
+    Box::pin(async move {
+        match check {
+            ReadyCheck::Tcp { .. } => probe_tcp(port).await,
+            ReadyCheck::Http { url, .. } => probe_http(url).await,
+            ReadyCheck::Grpc { service, .. } => probe_grpc(port, service.as_deref()).await,
+            ReadyCheck::Log { pattern, .. } => {
+                if seen.lock().unwrap().contains(pattern) {
+                    Ok(())
+                } else {
+                    bail!("no log line matching '{}' yet", pattern)
+                }
+            }
+            ReadyCheck::AllOf { checks, .. } => {
+                futures_util::future::try_join_all(
+                    checks.iter().map(|child| probe(child, port, seen)),
+                )
+                .await?;
+                Ok(())
+            }
+            ReadyCheck::AnyOf { checks, .. } => {
+                if checks.is_empty() {
+                    bail!("any_of ready check has no checks");
+                }
+                futures_util::future::select_ok(
+                    checks.iter().map(|child| probe(child, port, seen)),
+                )
+                .await?;
+                Ok(())
+            }
+            ReadyCheck::PgIsReady { .. } | ReadyCheck::Cmd { .. } => {
+                bail!("pg_isready and cmd ready checks are only supported for docker")
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn log_line(service: &str, text: &str) -> LogLine {
+        LogLine {
+            timestamp: Utc::now(),
+            service: service.to_string(),
+            text: text.to_string(),
+            is_stderr: false,
+            level: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn waits_for_port_and_log_line() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (tx, rx) = broadcast::channel(16);
+        let check = ReadyCheck::AllOf {
+            checks: vec![
+                ReadyCheck::Tcp { timeout: None },
+                ReadyCheck::Log {
+                    pattern: "listening".to_string(),
+                    timeout: None,
+                },
+            ],
+            timeout: Some(5),
+        };
+
+        let sender = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            let _ = tx.send(log_line("worker", "listening on 9000"));
+            let _ = tx.send(log_line("api", "starting up"));
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            let _ = tx.send(log_line("api", "listening on 3000"));
+            tx
+        });
+
+        let started = std::time::Instant::now();
+        wait_for_service("api", &check, Some(port), rx)
+            .await
+            .unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(200));
+        drop(sender.await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn closed_port_times_out() {
+        let port = {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            listener.local_addr().unwrap().port()
+        };
+        let (_tx, rx) = broadcast::channel(1);
+        let check = ReadyCheck::Tcp { timeout: Some(1) };
+        let err = wait_for_service("api", &check, Some(port), rx)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("timed out"), "{err}");
+    }
+}
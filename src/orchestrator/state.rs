@@ -1,7 +1,9 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
-use std::path::Path;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use crate::ui::summary::update_summary_status;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProjectState {
@@ -36,6 +38,17 @@ pub struct ClusterState {
 pub struct ClusterDeployState {
     pub image_tag: String,
     pub last_deployed: DateTime<Utc>,
+    /// ConfigMap holding the deploy's `env`, tracked for cleanup.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config_map: Option<String>,
+    /// Namespaces of the workloads its manifests applied, where the env
+    /// ConfigMap lives.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub namespaces: Vec<String>,
+    /// Checksum of the build context the image was built from, so an
+    /// unchanged `[cluster.image]` is not rebuilt on the next start.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_checksum: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -43,6 +56,16 @@ pub struct AddonState {
     pub addon_type: String,
     pub namespace: String,
     pub installed_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub port_forwards: Vec<PortForwardState>,
+}
+
+/// A `port_forward` entry and the local port it was given.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PortForwardState {
+    pub local_port: u16,
+    pub port_auto: bool,
+    pub target: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -56,6 +79,10 @@ pub struct ServiceState {
     pub phase: Option<String>,
     #[serde(default)]
     pub exit_code: Option<i32>,
+    /// Ready check outcome: "pending", "passed" or "failed". Unset for
+    /// services without a ready_check.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ready: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -69,6 +96,24 @@ pub struct DockerState {
     pub named_ports: BTreeMap<String, u16>,
     pub init_completed: bool,
     pub init_completed_at: Option<DateTime<Utc>>,
+    /// Checksum of the init scripts that last ran, so edited scripts run
+    /// again. Unset for state written before it was recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub init_checksum: Option<String>,
+    /// Image digest the container is running (pinned or resolved at start).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image_digest: Option<String>,
+    /// Lifecycle phase reported by the restart watcher ("running",
+    /// "restarting", "unhealthy", "failed"). Unset for unwatched containers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phase: Option<String>,
+    /// Configured `stop_timeout`, so `devrig stop` honors it without the config.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_timeout: Option<u64>,
+    /// Docker HEALTHCHECK status ("starting", "healthy", "unhealthy") last
+    /// seen by the container watcher. Unset without a healthcheck.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -83,6 +128,51 @@ pub struct DashboardState {
     pub dashboard_port: u16,
     pub grpc_port: u16,
     pub http_port: u16,
+    /// Certificate the dashboard serves, when `[dashboard.tls]` is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls_cert: Option<PathBuf>,
+    /// Whether the OTLP receivers serve TLS too.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub otel_tls: bool,
+}
+
+impl DashboardState {
+    /// `https` when the dashboard serves TLS, otherwise `http`.
+    pub fn scheme(&self) -> &'static str {
+        if self.tls_cert.is_some() {
+            "https"
+        } else {
+            "http"
+        }
+    }
+
+    /// `https` when the OTLP receivers serve TLS, otherwise `http`.
+    pub fn otel_scheme(&self) -> &'static str {
+        if self.otel_tls {
+            "https"
+        } else {
+            "http"
+        }
+    }
+
+    /// The certificate OTLP exporters should trust, when the receivers
+    /// serve TLS.
+    pub fn otlp_certificate(&self) -> Option<&Path> {
+        self.tls_cert.as_deref().filter(|_| self.otel_tls)
+    }
+}
+
+/// Result of [`ProjectState::load_checked`].
+#[derive(Debug)]
+pub enum LoadedState {
+    /// No state.json: the project was never started or has been deleted.
+    Missing,
+    Loaded(Box<ProjectState>),
+    /// state.json could not be read or parsed. `moved_to` is where the bad
+    /// file now lives, if moving it succeeded.
+    Corrupt {
+        moved_to: Option<PathBuf>,
+    },
 }
 
 impl ProjectState {
@@ -97,10 +187,55 @@ impl ProjectState {
         Ok(())
     }
 
+    /// Load state.json, or `None` if it is missing or corrupt. A corrupt
+    /// file is moved aside (see [`ProjectState::load_checked`]).
     pub fn load(state_dir: &Path) -> Option<Self> {
+        match Self::load_checked(state_dir) {
+            LoadedState::Loaded(state) => Some(*state),
+            LoadedState::Missing | LoadedState::Corrupt { .. } => None,
+        }
+    }
+
+    /// Load state.json, telling a missing file apart from one that can't be
+    /// read or parsed. A corrupt file is logged and renamed to
+    /// `state.json.corrupt-<timestamp>` so the next start writes a fresh one.
+    pub fn load_checked(state_dir: &Path) -> LoadedState {
         let path = state_dir.join("state.json");
-        let content = std::fs::read_to_string(&path).ok()?;
-        serde_json::from_str(&content).ok()
+        let parsed = match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).map_err(anyhow::Error::from),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return LoadedState::Missing,
+            Err(e) => Err(e.into()),
+        };
+        match parsed {
+            Ok(state) => LoadedState::Loaded(Box::new(state)),
+            Err(e) => {
+                let aside = state_dir.join(format!(
+                    "state.json.corrupt-{}",
+                    Utc::now().format("%Y%m%dT%H%M%S%.3fZ")
+                ));
+                let moved_to = match std::fs::rename(&path, &aside) {
+                    Ok(()) => {
+                        tracing::warn!(
+                            "{} is corrupt ({}); moved it to {}",
+                            path.display(),
+                            e,
+                            aside.display()
+                        );
+                        Some(aside)
+                    }
+                    Err(rename_err) => {
+                        tracing::warn!(
+                            "{} is corrupt ({}) and could not be moved aside: {}",
+                            path.display(),
+                            e,
+                            rename_err
+                        );
+                        None
+                    }
+                };
+                LoadedState::Corrupt { moved_to }
+            }
+        }
     }
 
     pub fn remove(state_dir: &Path) -> anyhow::Result<()> {
@@ -117,6 +252,32 @@ impl ProjectState {
         project_dir.join(".devrig")
     }
 
+    /// Assigned ports keyed the way `build_template_vars` expects:
+    /// `service:{name}`, `docker:{name}`, `docker:{name}:{port}` and
+    /// `compose:{name}`.
+    pub fn resolved_ports(&self) -> HashMap<String, u16> {
+        let mut ports = HashMap::new();
+        for (name, svc_state) in &self.services {
+            if let Some(port) = svc_state.port {
+                ports.insert(format!("service:{}", name), port);
+            }
+        }
+        for (name, docker_state) in &self.docker {
+            if let Some(port) = docker_state.port {
+                ports.insert(format!("docker:{}", name), port);
+            }
+            for (pname, &port) in &docker_state.named_ports {
+                ports.insert(format!("docker:{}:{}", name, pname), port);
+            }
+        }
+        for (name, cs_state) in &self.compose_services {
+            if let Some(port) = cs_state.port {
+                ports.insert(format!("compose:{}", name), port);
+            }
+        }
+        ports
+    }
+
     /// Acquire an exclusive file lock on state.json.lock.
     /// Returns the lock file handle (lock released on drop).
     fn lock_state(state_dir: &Path) -> Option<std::fs::File> {
@@ -147,6 +308,19 @@ impl ProjectState {
             }
             let _ = state.save(state_dir);
         }
+        update_summary_status(state_dir, "service", service, phase);
+    }
+
+    /// Atomically record the outcome of a service's ready check.
+    pub fn update_service_ready(state_dir: &Path, service: &str, passed: bool) {
+        let _lock = Self::lock_state(state_dir);
+        if let Some(mut state) = Self::load(state_dir) {
+            if let Some(svc) = state.services.get_mut(service) {
+                let outcome = if passed { "passed" } else { "failed" };
+                svc.ready = Some(outcome.to_string());
+            }
+            let _ = state.save(state_dir);
+        }
     }
 
     /// Atomically update a single service's PID in state.json.
@@ -175,12 +349,79 @@ impl ProjectState {
             }
             let _ = state.save(state_dir);
         }
+        update_summary_status(state_dir, "service", service, phase);
+    }
+
+    /// Atomically update a docker container's phase in state.json.
+    pub fn update_docker_phase(state_dir: &Path, docker_name: &str, phase: &str) {
+        let _lock = Self::lock_state(state_dir);
+        if let Some(mut state) = Self::load(state_dir) {
+            if let Some(docker) = state.docker.get_mut(docker_name) {
+                docker.phase = Some(phase.to_string());
+            }
+            let _ = state.save(state_dir);
+        }
+        update_summary_status(state_dir, "docker", docker_name, phase);
+    }
+
+    /// Atomically update a docker container's HEALTHCHECK status in state.json.
+    pub fn update_docker_health(state_dir: &Path, docker_name: &str, health: Option<&str>) {
+        let _lock = Self::lock_state(state_dir);
+        if let Some(mut state) = Self::load(state_dir) {
+            if let Some(docker) = state.docker.get_mut(docker_name) {
+                docker.health = health.map(str::to_string);
+            }
+            let _ = state.save(state_dir);
+        }
+    }
+
+    /// Atomically add or replace a service's entry in state.json, as for a
+    /// replica started by `devrig scale`.
+    pub fn update_service_state(state_dir: &Path, service: &str, svc: &ServiceState) {
+        let _lock = Self::lock_state(state_dir);
+        if let Some(mut state) = Self::load(state_dir) {
+            state.services.insert(service.to_string(), svc.clone());
+            let _ = state.save(state_dir);
+        }
+    }
+
+    /// Atomically drop a service's entry from state.json.
+    pub fn remove_service_state(state_dir: &Path, service: &str) {
+        let _lock = Self::lock_state(state_dir);
+        if let Some(mut state) = Self::load(state_dir) {
+            if state.services.remove(service).is_some() {
+                let _ = state.save(state_dir);
+            }
+        }
+    }
+
+    /// Atomically replace a cluster deploy's state after it was rebuilt.
+    pub fn update_cluster_deploy_state(state_dir: &Path, name: &str, deploy: &ClusterDeployState) {
+        let _lock = Self::lock_state(state_dir);
+        if let Some(mut state) = Self::load(state_dir) {
+            if let Some(cluster) = state.cluster.as_mut() {
+                cluster
+                    .deployed_services
+                    .insert(name.to_string(), deploy.clone());
+                let _ = state.save(state_dir);
+            }
+        }
+    }
+
+    /// Atomically replace a docker container's state after it was recreated.
+    pub fn update_docker_state(state_dir: &Path, docker_name: &str, docker: &DockerState) {
+        let _lock = Self::lock_state(state_dir);
+        if let Some(mut state) = Self::load(state_dir) {
+            state.docker.insert(docker_name.to_string(), docker.clone());
+            let _ = state.save(state_dir);
+        }
     }
 
     pub fn reset_init(&mut self, docker_name: &str) -> bool {
         if let Some(state) = self.docker.get_mut(docker_name) {
             state.init_completed = false;
             state.init_completed_at = None;
+            state.init_checksum = None;
             true
         } else {
             false
@@ -205,6 +446,7 @@ mod tests {
                 protocol: None,
                 phase: None,
                 exit_code: None,
+                ready: None,
             },
         );
         ProjectState {
@@ -239,4 +481,33 @@ mod tests {
         let loaded = ProjectState::load(state_dir).unwrap();
         assert_eq!(loaded.services["api"].pid, 12345);
     }
+
+    #[test]
+    fn corrupt_state_is_moved_aside() {
+        let dir = tempdir().unwrap();
+        let state_dir = dir.path();
+
+        assert!(matches!(
+            ProjectState::load_checked(state_dir),
+            LoadedState::Missing
+        ));
+
+        std::fs::write(state_dir.join("state.json"), "{ not json").unwrap();
+        let moved_to = match ProjectState::load_checked(state_dir) {
+            LoadedState::Corrupt { moved_to } => moved_to.unwrap(),
+            other => panic!("expected corrupt state, got {other:?}"),
+        };
+        assert!(!state_dir.join("state.json").exists());
+        assert_eq!(std::fs::read_to_string(&moved_to).unwrap(), "{ not json");
+        assert!(moved_to
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .starts_with("state.json.corrupt-"));
+
+        // The bad file no longer shadows a fresh start.
+        assert!(ProjectState::load(state_dir).is_none());
+        test_state().save(state_dir).unwrap();
+        assert!(ProjectState::load(state_dir).is_some());
+    }
 }
@@ -1,6 +1,7 @@
 use std::collections::{BTreeMap, VecDeque};
 use std::path::PathBuf;
 use std::process::ExitStatus;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result};
@@ -10,11 +11,11 @@ use tokio::task::JoinHandle;
 use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, warn};
 
-use crate::config::model::RestartConfig;
+use crate::config::model::{EnvPassthrough, RestartConfig};
 use crate::otel::types::TelemetryEvent;
 use crate::orchestrator::state::ProjectState;
 use crate::platform;
-use crate::ui::logs::LogLine;
+use crate::ui::logs::{LogLevel, LogLine};
 
 // ---------------------------------------------------------------------------
 // ServicePhase — explicit state tracking for supervisor lifecycle
@@ -92,7 +93,7 @@ impl RestartPolicy {
             startup_grace: Duration::from_millis(cfg.startup_grace_ms),
             initial_delay: Duration::from_millis(cfg.initial_delay_ms),
             max_delay: Duration::from_millis(cfg.max_delay_ms),
-            reset_after: Duration::from_secs(60),
+            reset_after: Duration::from_millis(cfg.retries_reset_after_ms),
             mode: RestartMode::from_policy_str(&cfg.policy),
         }
     }
@@ -105,6 +106,9 @@ impl RestartPolicy {
 /// How long to wait for the next line before flushing a multiline buffer.
 const MULTILINE_FLUSH_TIMEOUT: Duration = Duration::from_millis(250);
 
+/// How long `post_stop` commands get to finish during shutdown.
+pub const POST_STOP_TIMEOUT: Duration = Duration::from_secs(30);
+
 /// Returns `true` if this line starts a new log entry (i.e. it does NOT
 /// begin with whitespace).  Continuation lines (stack traces, indented
 /// JSON, etc.) start with a space or tab.
@@ -112,18 +116,63 @@ fn is_log_entry_start(line: &str) -> bool {
     !line.starts_with(' ') && !line.starts_with('\t')
 }
 
-/// Flush the multiline buffer as a single `LogLine`.
-fn flush_multiline_buffer(
-    buffer: &mut Vec<String>,
+/// Caps how many log entries a service sends per one-second window. Entries
+/// over the cap are dropped and counted until the window ends; each window's
+/// count is summarized as it ends, not held until the stream closes.
+struct LogRateLimiter {
+    max_per_sec: u32,
+    window_start: Instant,
+    sent: u32,
+    dropped: u64,
+}
+
+impl LogRateLimiter {
+    fn new(max_per_sec: u32, now: Instant) -> Self {
+        Self {
+            max_per_sec,
+            window_start: now,
+            sent: 0,
+            dropped: 0,
+        }
+    }
+
+    /// Start a new window if the current one is over, returning how many
+    /// entries the finished window dropped (if any).
+    fn roll(&mut self, now: Instant) -> Option<u64> {
+        if now.duration_since(self.window_start) < Duration::from_secs(1) {
+            return None;
+        }
+        self.window_start = now;
+        self.sent = 0;
+        (self.dropped > 0).then(|| std::mem::take(&mut self.dropped))
+    }
+
+    /// Whether an entry at `now` may be sent, plus the drop count of a
+    /// window that just ended.
+    fn admit(&mut self, now: Instant) -> (bool, Option<u64>) {
+        let dropped = self.roll(now);
+        if self.sent < self.max_per_sec {
+            self.sent += 1;
+            (true, dropped)
+        } else {
+            self.dropped += 1;
+            (false, dropped)
+        }
+    }
+
+    /// Drops not yet reported, regardless of the window.
+    fn take_dropped(&mut self) -> Option<u64> {
+        (self.dropped > 0).then(|| std::mem::take(&mut self.dropped))
+    }
+}
+
+fn send_log_line(
     tx: &broadcast::Sender<LogLine>,
     service_name: &str,
+    text: String,
     is_stderr: bool,
+    level: Option<LogLevel>,
 ) {
-    if buffer.is_empty() {
-        return;
-    }
-    let text = buffer.join("\n");
-    let level = crate::ui::logs::detect_log_level(&text);
     let _ = tx.send(LogLine {
         timestamp: chrono::Utc::now(),
         service: service_name.to_string(),
@@ -131,7 +180,70 @@ fn flush_multiline_buffer(
         is_stderr,
         level,
     });
+}
+
+fn send_drop_summary(
+    tx: &broadcast::Sender<LogLine>,
+    service_name: &str,
+    dropped: Option<u64>,
+    max_per_sec: u32,
+) {
+    if let Some(n) = dropped {
+        send_log_line(
+            tx,
+            service_name,
+            format!(
+                "[devrig] dropped {} log lines (over {} lines/sec)",
+                n, max_per_sec
+            ),
+            true,
+            Some(LogLevel::Warn),
+        );
+    }
+}
+
+/// Report drops that are due: those of a finished window, or all of them
+/// when `finished` (the stream hit EOF).
+fn report_dropped(
+    limiter: Option<&Mutex<LogRateLimiter>>,
+    tx: &broadcast::Sender<LogLine>,
+    service_name: &str,
+    finished: bool,
+) {
+    let Some(limiter) = limiter else { return };
+    let mut limiter = limiter.lock().unwrap();
+    let dropped = if finished {
+        limiter.take_dropped()
+    } else {
+        limiter.roll(Instant::now())
+    };
+    send_drop_summary(tx, service_name, dropped, limiter.max_per_sec);
+}
+
+/// Flush the multiline buffer as a single `LogLine`, unless the service is
+/// over its log rate.
+fn flush_multiline_buffer(
+    buffer: &mut Vec<String>,
+    tx: &broadcast::Sender<LogLine>,
+    service_name: &str,
+    is_stderr: bool,
+    limiter: Option<&Mutex<LogRateLimiter>>,
+) {
+    if buffer.is_empty() {
+        return;
+    }
+    let text = buffer.join("\n");
     buffer.clear();
+    if let Some(limiter) = limiter {
+        let mut limiter = limiter.lock().unwrap();
+        let (admitted, dropped) = limiter.admit(Instant::now());
+        send_drop_summary(tx, service_name, dropped, limiter.max_per_sec);
+        if !admitted {
+            return;
+        }
+    }
+    let level = crate::ui::logs::detect_log_level(&text);
+    send_log_line(tx, service_name, text, is_stderr, level);
 }
 
 /// Spawn a task that reads lines from a stream, groups multiline entries
@@ -141,26 +253,29 @@ fn spawn_stream_reader(
     tx: broadcast::Sender<LogLine>,
     service_name: String,
     is_stderr: bool,
+    limiter: Option<Arc<Mutex<LogRateLimiter>>>,
 ) -> JoinHandle<()> {
     tokio::spawn(async move {
         let Some(stream) = stream else { return };
         let mut reader = BufReader::new(stream);
         let mut line = String::new();
         let mut buffer: Vec<String> = Vec::new();
+        let limiter = limiter.as_deref();
 
         loop {
             line.clear();
             match tokio::time::timeout(MULTILINE_FLUSH_TIMEOUT, reader.read_line(&mut line)).await {
                 Ok(Ok(0)) => {
                     // EOF — flush remaining buffer and exit.
-                    flush_multiline_buffer(&mut buffer, &tx, &service_name, is_stderr);
+                    flush_multiline_buffer(&mut buffer, &tx, &service_name, is_stderr, limiter);
+                    report_dropped(limiter, &tx, &service_name, true);
                     break;
                 }
                 Ok(Ok(_)) => {
                     let text = line.trim_end_matches(['\r', '\n']).to_string();
                     if is_log_entry_start(&text) {
                         // New entry starts — flush the previous group.
-                        flush_multiline_buffer(&mut buffer, &tx, &service_name, is_stderr);
+                        flush_multiline_buffer(&mut buffer, &tx, &service_name, is_stderr, limiter);
                     }
                     buffer.push(text);
                 }
@@ -171,12 +286,15 @@ fn spawn_stream_reader(
                         "{} read error",
                         if is_stderr { "stderr" } else { "stdout" }
                     );
-                    flush_multiline_buffer(&mut buffer, &tx, &service_name, is_stderr);
+                    flush_multiline_buffer(&mut buffer, &tx, &service_name, is_stderr, limiter);
+                    report_dropped(limiter, &tx, &service_name, true);
                     break;
                 }
                 Err(_) => {
-                    // Timeout — flush the buffer so the last group isn't stuck.
-                    flush_multiline_buffer(&mut buffer, &tx, &service_name, is_stderr);
+                    // Timeout — flush the buffer so the last group isn't stuck,
+                    // and report drops from a window that has since ended.
+                    flush_multiline_buffer(&mut buffer, &tx, &service_name, is_stderr, limiter);
+                    report_dropped(limiter, &tx, &service_name, false);
                 }
             }
         }
@@ -205,6 +323,11 @@ pub struct ServiceSupervisor {
     cancel: CancellationToken,
     events_tx: Option<broadcast::Sender<TelemetryEvent>>,
     state_dir: Option<PathBuf>,
+    shell: platform::ServiceShell,
+    env_passthrough: EnvPassthrough,
+    max_log_rate: Option<u32>,
+    pre_start: Vec<String>,
+    post_stop: Vec<String>,
 }
 
 impl ServiceSupervisor {
@@ -230,13 +353,131 @@ impl ServiceSupervisor {
             cancel,
             events_tx,
             state_dir,
+            shell: platform::ServiceShell::Default,
+            env_passthrough: EnvPassthrough::All,
+            max_log_rate: None,
+            pre_start: Vec::new(),
+            post_stop: Vec::new(),
         }
     }
 
+    /// Run the command with the given interpreter instead of the platform default.
+    pub fn with_shell(mut self, shell: platform::ServiceShell) -> Self {
+        self.shell = shell;
+        self
+    }
+
+    /// Restrict which host environment variables the child inherits.
+    pub fn with_env_passthrough(mut self, passthrough: EnvPassthrough) -> Self {
+        self.env_passthrough = passthrough;
+        self
+    }
+
+    /// Keep at most `max_per_sec` log entries per second from the child's
+    /// stdout and stderr combined; the rest are dropped and summarized.
+    pub fn with_max_log_rate(mut self, max_per_sec: Option<u32>) -> Self {
+        self.max_log_rate = max_per_sec;
+        self
+    }
+
+    /// Commands to run before the service first starts and after it stops
+    /// for good.
+    pub fn with_hooks(mut self, pre_start: Vec<String>, post_stop: Vec<String>) -> Self {
+        self.pre_start = pre_start;
+        self.post_stop = post_stop;
+        self
+    }
+
+    /// Run the `pre_start` commands in order, failing on the first one that
+    /// doesn't exit 0. Cancellation stops the running command.
+    pub async fn pre_start(&self) -> Result<()> {
+        self.run_hook("pre_start", &self.pre_start, &self.cancel)
+            .await
+    }
+
+    /// Run the `post_stop` commands, logging rather than returning failures.
+    /// They run after cancellation too, so they get their own time limit.
+    async fn post_stop(&self) {
+        if self.post_stop.is_empty() {
+            return;
+        }
+        let never = CancellationToken::new();
+        let hook = self.run_hook("post_stop", &self.post_stop, &never);
+        match tokio::time::timeout(POST_STOP_TIMEOUT, hook).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => warn!(service = %self.name, error = %e, "post_stop failed"),
+            Err(_) => warn!(
+                service = %self.name,
+                timeout_secs = POST_STOP_TIMEOUT.as_secs(),
+                "post_stop timed out"
+            ),
+        }
+    }
+
+    /// Run hook `commands` one at a time like the service itself (same
+    /// shell, working dir and env), with output going to the service's logs.
+    async fn run_hook(
+        &self,
+        hook: &str,
+        commands: &[String],
+        cancel: &CancellationToken,
+    ) -> Result<()> {
+        for command in commands {
+            debug!(service = %self.name, hook, command = %command, "running hook");
+            let mut child = self
+                .build_command(command)?
+                .spawn()
+                .with_context(|| format!("failed to spawn {} for service {}", hook, self.name))?;
+            let child_pid = child.id();
+            let group_handle = platform::post_spawn_setup(child_pid);
+            let stdout_handle = spawn_stream_reader(
+                child.stdout.take(),
+                self.log_tx.clone(),
+                self.name.clone(),
+                false,
+                None,
+            );
+            let stderr_handle = spawn_stream_reader(
+                child.stderr.take(),
+                self.log_tx.clone(),
+                self.name.clone(),
+                true,
+                None,
+            );
+            let status = tokio::select! {
+                result = child.wait() => result
+                    .with_context(|| format!("waiting on {} for service {}", hook, self.name))?,
+                _ = cancel.cancelled() => {
+                    platform::terminate_child(&mut child, child_pid, group_handle.as_ref()).await;
+                    anyhow::bail!("{} for service {} cancelled", hook, self.name);
+                }
+            };
+            let _ = stdout_handle.await;
+            let _ = stderr_handle.await;
+            if !status.success() {
+                anyhow::bail!(
+                    "{} `{}` for service {} failed ({})",
+                    hook,
+                    command,
+                    self.name,
+                    status
+                );
+            }
+        }
+        Ok(())
+    }
+
     /// Runs the supervised process in a loop, restarting on failure according
-    /// to the configured [`RestartPolicy`].  Returns the final [`ExitStatus`]
-    /// if the process exited, or an error if spawning failed irrecoverably.
+    /// to the configured [`RestartPolicy`], then runs `post_stop`.  Returns
+    /// the final [`ExitStatus`] if the process exited, or an error if
+    /// spawning failed irrecoverably.
     pub async fn run(self) -> Result<ExitStatus> {
+        let result = self.supervise().await;
+        self.post_stop().await;
+        result
+    }
+
+    async fn supervise(&self) -> Result<ExitStatus> {
         let mut restart_count: u32 = 0;
         let mut startup_restart_count: u32 = 0;
         let mut last_status: Option<ExitStatus> = None;
@@ -251,6 +492,12 @@ impl ServiceSupervisor {
         // Track recent crash timestamps for crash rate detection
         let mut recent_crashes: VecDeque<Instant> = VecDeque::new();
 
+        // Shared by stdout and stderr, and kept across restarts so a
+        // crash-looping service can't reset its budget.
+        let log_limiter = self
+            .max_log_rate
+            .map(|max| Arc::new(Mutex::new(LogRateLimiter::new(max, Instant::now()))));
+
         loop {
             // Check cancellation before (re)spawning.
             if self.cancel.is_cancelled() {
@@ -266,22 +513,15 @@ impl ServiceSupervisor {
                 service = %self.name,
                 attempt = restart_count + 1,
                 "spawning: {} {:?}",
-                platform::shell_name(),
+                if self.uses_plain_shell() {
+                    "plain shell".to_string()
+                } else {
+                    self.shell.display_name()
+                },
                 self.command,
             );
 
-            let mut cmd = platform::shell_command(&self.command);
-
-            if let Some(ref dir) = self.working_dir {
-                cmd.current_dir(dir);
-            }
-
-            cmd.envs(&self.env);
-            cmd.stdout(std::process::Stdio::piped());
-            cmd.stderr(std::process::Stdio::piped());
-            cmd.kill_on_drop(true);
-
-            platform::configure_process_group(&mut cmd);
+            let mut cmd = self.build_command(&self.command)?;
 
             let spawn_time = Instant::now();
 
@@ -305,12 +545,14 @@ impl ServiceSupervisor {
                 self.log_tx.clone(),
                 self.name.clone(),
                 false,
+                log_limiter.clone(),
             );
             let stderr_handle = spawn_stream_reader(
                 child.stderr.take(),
                 self.log_tx.clone(),
                 self.name.clone(),
                 true,
+                log_limiter.clone(),
             );
 
             // -----------------------------------------------------------
@@ -497,6 +739,9 @@ impl ServiceSupervisor {
                 startup_failure = is_startup_failure,
                 "restarting after backoff",
             );
+            if let Some(ref dir) = self.state_dir {
+                ProjectState::update_service_phase(dir, &self.name, "restarting");
+            }
 
             // Sleep with cancellation awareness.
             tokio::select! {
@@ -518,8 +763,47 @@ impl ServiceSupervisor {
     // Helpers
     // -----------------------------------------------------------------------
 
+    /// Whether `command` runs under the plain, non-login shell: a login
+    /// shell sources the user's profile and would bring back the host env a
+    /// restricted `shell_env_passthrough` keeps out.
+    fn uses_plain_shell(&self) -> bool {
+        self.shell == platform::ServiceShell::Default && self.env_passthrough != EnvPassthrough::All
+    }
+
+    /// `command` under the service's shell, in its working dir and env, with
+    /// output piped and its own process group.
+    fn build_command(&self, command: &str) -> Result<tokio::process::Command> {
+        let mut cmd = if self.uses_plain_shell() {
+            tokio::process::Command::from(platform::script_command(command))
+        } else {
+            platform::service_command(command, &self.shell)
+                .with_context(|| format!("failed to build command for service {}", self.name))?
+        };
+
+        if let Some(ref dir) = self.working_dir {
+            cmd.current_dir(dir);
+        }
+
+        if self.env_passthrough == EnvPassthrough::All {
+            cmd.envs(&self.env);
+        } else {
+            let mut env = child_env(&self.env_passthrough, std::env::vars(), &self.env);
+            // Without the host PATH, programs still need somewhere to be found.
+            env.entry("PATH".to_string())
+                .or_insert_with(|| platform::MINIMAL_PATH.to_string());
+            cmd.env_clear();
+            cmd.envs(env);
+        }
+        cmd.stdout(std::process::Stdio::piped());
+        cmd.stderr(std::process::Stdio::piped());
+        cmd.kill_on_drop(true);
+
+        platform::configure_process_group(&mut cmd);
+        Ok(cmd)
+    }
+
     /// Computes a backoff duration using equal-jitter exponential backoff.
-    fn backoff_delay(policy: &RestartPolicy, restart_count: u32) -> Duration {
+    pub(crate) fn backoff_delay(policy: &RestartPolicy, restart_count: u32) -> Duration {
         let base_ms = policy.initial_delay.as_millis() as f64 * 2_f64.powi(restart_count as i32);
         let capped_ms = base_ms.min(policy.max_delay.as_millis() as f64);
         let half = capped_ms / 2.0;
@@ -529,6 +813,21 @@ impl ServiceSupervisor {
 
 }
 
+/// Composes a child's environment from the allowed host variables plus the
+/// devrig-injected and configured `env`, which always wins on conflict.
+fn child_env(
+    passthrough: &EnvPassthrough,
+    host: impl IntoIterator<Item = (String, String)>,
+    env: &BTreeMap<String, String>,
+) -> BTreeMap<String, String> {
+    let mut merged: BTreeMap<String, String> = host
+        .into_iter()
+        .filter(|(key, _)| passthrough.allows(key))
+        .collect();
+    merged.extend(env.iter().map(|(k, v)| (k.clone(), v.clone())));
+    merged
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -695,6 +994,81 @@ mod tests {
         );
     }
 
+    fn drain_text(rx: &mut broadcast::Receiver<LogLine>) -> Vec<String> {
+        std::iter::from_fn(|| rx.try_recv().ok())
+            .map(|line| line.text)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn pre_start_stops_at_first_failure() {
+        let (tx, mut rx) = broadcast::channel::<LogLine>(64);
+        let supervisor = ServiceSupervisor::new(
+            "test-pre-start".into(),
+            platform::test_commands::sleep_long().into(),
+            None,
+            BTreeMap::new(),
+            RestartPolicy::default(),
+            tx,
+            CancellationToken::new(),
+            None,
+            None,
+        )
+        .with_hooks(
+            vec![
+                platform::test_commands::echo_two_lines().into(),
+                platform::test_commands::exit_failure().into(),
+                "echo unreachable".into(),
+            ],
+            vec![],
+        );
+
+        let err = supervisor.pre_start().await.unwrap_err().to_string();
+        assert!(err.contains("pre_start"), "unexpected error: {err}");
+        assert!(err.contains("test-pre-start"), "unexpected error: {err}");
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let text = drain_text(&mut rx).join("\n");
+        assert!(text.contains("hello") && text.contains("world"));
+        assert!(!text.contains("unreachable"));
+    }
+
+    #[tokio::test]
+    async fn post_stop_runs_after_cancel() {
+        let (tx, mut rx) = broadcast::channel::<LogLine>(64);
+        let cancel = CancellationToken::new();
+        let supervisor = ServiceSupervisor::new(
+            "test-post-stop".into(),
+            platform::test_commands::sleep_long().into(),
+            None,
+            BTreeMap::new(),
+            RestartPolicy::default(),
+            tx,
+            cancel.clone(),
+            None,
+            None,
+        )
+        .with_hooks(
+            vec![],
+            vec![platform::test_commands::echo_two_lines().into()],
+        );
+
+        let handle = tokio::spawn(supervisor.run());
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        cancel.cancel();
+        tokio::time::timeout(Duration::from_secs(10), handle)
+            .await
+            .expect("should complete within timeout")
+            .expect("task should not panic")
+            .unwrap_err();
+
+        let text = drain_text(&mut rx).join("\n");
+        assert!(
+            text.contains("hello") && text.contains("world"),
+            "got: {text}"
+        );
+    }
+
     #[tokio::test]
     async fn exit_code_zero_with_on_failure_no_restart() {
         let (tx, _rx) = broadcast::channel::<LogLine>(64);
@@ -721,6 +1095,49 @@ mod tests {
         // Should not restart — should return immediately on exit 0
     }
 
+    /// Runs of `echo_then_fail` in a supervisor with `max_restarts = 1`.
+    async fn runs_before_giving_up(reset_after: Duration) -> usize {
+        let (tx, mut rx) = broadcast::channel::<LogLine>(64);
+        let supervisor = ServiceSupervisor::new(
+            "test-reset".into(),
+            platform::test_commands::echo_then_fail().into(),
+            None,
+            BTreeMap::new(),
+            RestartPolicy {
+                max_restarts: 1,
+                startup_grace: Duration::from_millis(10),
+                initial_delay: Duration::from_millis(1),
+                max_delay: Duration::from_millis(1),
+                reset_after,
+                ..RestartPolicy::default()
+            },
+            tx,
+            CancellationToken::new(),
+            None,
+            None,
+        );
+
+        let status = supervisor.run().await.expect("run should succeed");
+        assert!(!status.success());
+
+        let mut runs = 0;
+        while let Ok(line) = rx.try_recv() {
+            if line.text.trim() == "run" {
+                runs += 1;
+            }
+        }
+        runs
+    }
+
+    #[tokio::test]
+    async fn restart_count_resets_after_stable_run() {
+        // Each run outlives the reset window, so max_restarts never trips;
+        // the supervisor only stops at the 5-crashes-in-30s loop guard.
+        assert_eq!(runs_before_giving_up(Duration::from_millis(50)).await, 5);
+        // Without a reset the single allowed restart is spent on run two.
+        assert_eq!(runs_before_giving_up(Duration::from_secs(60)).await, 2);
+    }
+
     #[tokio::test]
     async fn restart_mode_never_no_restart() {
         let (tx, _rx) = broadcast::channel::<LogLine>(64);
@@ -852,6 +1269,189 @@ mod tests {
         assert!(lines[0].text.contains("at MyApp.Main()"));
     }
 
+    #[test]
+    fn log_rate_limiter_resets_each_second() {
+        let start = Instant::now();
+        let mut limiter = LogRateLimiter::new(2, start);
+        assert_eq!(limiter.admit(start), (true, None));
+        assert_eq!(limiter.admit(start), (true, None));
+        assert_eq!(limiter.admit(start), (false, None));
+        assert_eq!(
+            limiter.admit(start + Duration::from_millis(999)),
+            (false, None)
+        );
+        assert_eq!(limiter.roll(start + Duration::from_millis(500)), None);
+
+        // The next window admits again and reports the previous drops once.
+        let next = start + Duration::from_secs(1);
+        assert_eq!(limiter.admit(next), (true, Some(2)));
+        assert_eq!(limiter.admit(next), (true, None));
+        assert_eq!(limiter.admit(next), (false, None));
+        assert_eq!(limiter.take_dropped(), Some(1));
+        assert_eq!(limiter.take_dropped(), None);
+        assert_eq!(limiter.roll(next + Duration::from_secs(1)), None);
+    }
+
+    #[tokio::test]
+    async fn drops_are_summarized_every_second_while_flooding() {
+        use tokio::io::AsyncWriteExt;
+
+        let (tx, mut rx) = broadcast::channel::<LogLine>(1024);
+        let (mut writer, reader) = tokio::io::duplex(1024);
+        let limiter = Arc::new(Mutex::new(LogRateLimiter::new(10, Instant::now())));
+        let handle = spawn_stream_reader(Some(reader), tx, "noisy".into(), false, Some(limiter));
+
+        // Flood for a bit over two seconds and look at what arrived before
+        // the stream ends.
+        let flood_until = Instant::now() + Duration::from_millis(2300);
+        let mut i = 0u64;
+        while Instant::now() < flood_until {
+            writer.write_all(format!("{i}\n").as_bytes()).await.unwrap();
+            i += 1;
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        let mut summaries = 0;
+        while let Ok(line) = rx.try_recv() {
+            if line.text.starts_with("[devrig] dropped") {
+                summaries += 1;
+            }
+        }
+        assert!(
+            summaries >= 2,
+            "expected a summary per second, got {summaries}"
+        );
+
+        drop(writer);
+        handle.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn log_burst_is_rate_limited_and_summarized() {
+        let (tx, mut rx) = broadcast::channel::<LogLine>(1024);
+        let cancel = CancellationToken::new();
+
+        let supervisor = ServiceSupervisor::new(
+            "test-noisy".into(),
+            platform::test_commands::print_500_lines().into(),
+            None,
+            BTreeMap::new(),
+            RestartPolicy {
+                max_restarts: 0,
+                ..RestartPolicy::default()
+            },
+            tx,
+            cancel.clone(),
+            None,
+            None,
+        )
+        .with_max_log_rate(Some(50));
+
+        let status = supervisor.run().await.expect("run should succeed");
+        assert!(status.success());
+
+        let mut lines = Vec::new();
+        while let Ok(line) = rx.try_recv() {
+            lines.push(line);
+        }
+        let (summaries, kept): (Vec<_>, Vec<_>) = lines
+            .into_iter()
+            .partition(|l| l.text.starts_with("[devrig] dropped"));
+
+        // The burst finishes well within a second, so one window's worth
+        // gets through. A login shell may add a stray stderr line or two.
+        assert_eq!(kept.len(), 50, "{:?}", kept);
+        let numbers: Vec<u32> = kept.iter().filter_map(|l| l.text.parse().ok()).collect();
+        assert!(numbers.windows(2).all(|w| w[0] < w[1]), "{:?}", numbers);
+        let dropped: u64 = summaries
+            .iter()
+            .map(|l| {
+                l.text
+                    .split_whitespace()
+                    .nth(2)
+                    .and_then(|n| n.parse::<u64>().ok())
+                    .expect("summary should carry a count")
+            })
+            .sum();
+        assert!(dropped >= 450, "{:?}", summaries);
+        assert!(dropped + numbers.len() as u64 >= 500, "{:?}", summaries);
+        assert!(summaries.iter().all(|l| l.level == Some(LogLevel::Warn)));
+    }
+
+    #[test]
+    fn child_env_passthrough_filters_host_vars() {
+        let host = || {
+            vec![
+                ("PATH".to_string(), "/usr/bin".to_string()),
+                ("HOME".to_string(), "/home/dev".to_string()),
+                ("AWS_PROFILE".to_string(), "prod".to_string()),
+            ]
+        };
+        let mut env = BTreeMap::new();
+        env.insert("DEVRIG_SERVICE_NAME".to_string(), "api".to_string());
+        env.insert("HOME".to_string(), "/override".to_string());
+
+        let none = child_env(&EnvPassthrough::None, host(), &env);
+        assert_eq!(
+            none.keys().collect::<Vec<_>>(),
+            vec!["DEVRIG_SERVICE_NAME", "HOME"]
+        );
+        assert_eq!(none["HOME"], "/override");
+
+        let only = child_env(
+            &EnvPassthrough::Only(vec!["PATH".to_string(), "MISSING".to_string()]),
+            host(),
+            &BTreeMap::new(),
+        );
+        assert_eq!(only.len(), 1);
+        assert_eq!(only["PATH"], "/usr/bin");
+
+        let all = child_env(&EnvPassthrough::All, host(), &env);
+        assert_eq!(all.len(), 4);
+        assert_eq!(all["AWS_PROFILE"], "prod");
+    }
+
+    /// `$0`, a piped `tr` and `$PATH` from `command`'s output under
+    /// `passthrough`.
+    #[cfg(unix)]
+    async fn restricted_shell_output(passthrough: EnvPassthrough) -> String {
+        let (tx, _rx) = broadcast::channel::<LogLine>(4);
+        let supervisor = ServiceSupervisor::new(
+            "test-plain".into(),
+            "echo \"$0 $(echo a | tr a b) $PATH\"".into(),
+            None,
+            BTreeMap::new(),
+            RestartPolicy::default(),
+            tx,
+            CancellationToken::new(),
+            None,
+            None,
+        )
+        .with_env_passthrough(passthrough);
+
+        let output = supervisor
+            .build_command(&supervisor.command)
+            .unwrap()
+            .output()
+            .await
+            .expect("failed to spawn");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn restricted_env_passthrough_runs_under_plain_shell() {
+        // A plain `sh -c`, so shell syntax works, with a minimal PATH to
+        // find programs on.
+        assert_eq!(
+            restricted_shell_output(EnvPassthrough::None).await,
+            format!("sh b {}", platform::MINIMAL_PATH)
+        );
+        assert_eq!(
+            restricted_shell_output(EnvPassthrough::Only(vec!["HOME".to_string()])).await,
+            format!("sh b {}", platform::MINIMAL_PATH)
+        );
+    }
+
     #[test]
     fn restart_policy_from_config() {
         let cfg = RestartConfig {
@@ -861,6 +1461,7 @@ mod tests {
             startup_grace_ms: 3000,
             initial_delay_ms: 1000,
             max_delay_ms: 60000,
+            retries_reset_after_ms: 120000,
         };
         let policy = RestartPolicy::from_config(&cfg);
         assert_eq!(policy.max_restarts, 5);
@@ -868,6 +1469,7 @@ mod tests {
         assert_eq!(policy.startup_grace, Duration::from_millis(3000));
         assert_eq!(policy.initial_delay, Duration::from_millis(1000));
         assert_eq!(policy.max_delay, Duration::from_millis(60000));
+        assert_eq!(policy.reset_after, Duration::from_millis(120000));
         assert_eq!(policy.mode, RestartMode::Always);
     }
 }
@@ -0,0 +1,456 @@
+//! Forwarding of received telemetry to an upstream OTLP collector.
+//!
+//! Receivers store each export request locally and then hand its resources
+//! to a [`Forwarder`]. The hand-off goes through a bounded queue and never
+//! waits: when the queue is full the upstream copy is dropped, so a slow or
+//! unreachable collector can't hold up local ingestion. A background task
+//! drains the queue, batches by signal and exports upstream.
+
+use std::mem;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use prost::Message;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+use tonic::metadata::{MetadataKey, MetadataMap, MetadataValue};
+use tonic::transport::{Channel, Endpoint};
+use tracing::{debug, info, warn};
+
+use opentelemetry_proto::tonic::collector::logs::v1::logs_service_client::LogsServiceClient;
+use opentelemetry_proto::tonic::collector::logs::v1::ExportLogsServiceRequest;
+use opentelemetry_proto::tonic::collector::metrics::v1::metrics_service_client::MetricsServiceClient;
+use opentelemetry_proto::tonic::collector::metrics::v1::ExportMetricsServiceRequest;
+use opentelemetry_proto::tonic::collector::trace::v1::trace_service_client::TraceServiceClient;
+use opentelemetry_proto::tonic::collector::trace::v1::ExportTraceServiceRequest;
+use opentelemetry_proto::tonic::logs::v1::ResourceLogs;
+use opentelemetry_proto::tonic::metrics::v1::ResourceMetrics;
+use opentelemetry_proto::tonic::trace::v1::ResourceSpans;
+
+use crate::config::model::{OtelForwardConfig, OtlpProtocol};
+
+/// Export requests waiting for the forwarding task.
+const QUEUE_CAPACITY: usize = 1024;
+/// Resource entries (per signal) that trigger an immediate export.
+const MAX_BATCH: usize = 512;
+/// How often a partial batch is exported.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+/// Upper bound on a single upstream export.
+const EXPORT_TIMEOUT: Duration = Duration::from_secs(10);
+
+enum ForwardItem {
+    Traces(Vec<ResourceSpans>),
+    Metrics(Vec<ResourceMetrics>),
+    Logs(Vec<ResourceLogs>),
+}
+
+/// Handle the receivers use to queue telemetry for the upstream collector.
+#[derive(Clone)]
+pub struct Forwarder {
+    tx: mpsc::Sender<ForwardItem>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl Forwarder {
+    pub fn traces(&self, resource_spans: Vec<ResourceSpans>) {
+        if !resource_spans.is_empty() {
+            self.send(ForwardItem::Traces(resource_spans));
+        }
+    }
+
+    pub fn metrics(&self, resource_metrics: Vec<ResourceMetrics>) {
+        if !resource_metrics.is_empty() {
+            self.send(ForwardItem::Metrics(resource_metrics));
+        }
+    }
+
+    pub fn logs(&self, resource_logs: Vec<ResourceLogs>) {
+        if !resource_logs.is_empty() {
+            self.send(ForwardItem::Logs(resource_logs));
+        }
+    }
+
+    fn send(&self, item: ForwardItem) {
+        if let Err(mpsc::error::TrySendError::Full(_)) = self.tx.try_send(item) {
+            let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+            if dropped == 1 || dropped.is_multiple_of(1000) {
+                warn!(dropped, "OTLP forward queue full, dropping upstream copy");
+            }
+        }
+    }
+}
+
+/// Telemetry accumulated between exports.
+#[derive(Default)]
+struct Batch {
+    spans: Vec<ResourceSpans>,
+    metrics: Vec<ResourceMetrics>,
+    logs: Vec<ResourceLogs>,
+}
+
+impl Batch {
+    fn push(&mut self, item: ForwardItem) {
+        match item {
+            ForwardItem::Traces(mut spans) => self.spans.append(&mut spans),
+            ForwardItem::Metrics(mut metrics) => self.metrics.append(&mut metrics),
+            ForwardItem::Logs(mut logs) => self.logs.append(&mut logs),
+        }
+    }
+
+    fn is_full(&self) -> bool {
+        self.spans.len() >= MAX_BATCH
+            || self.metrics.len() >= MAX_BATCH
+            || self.logs.len() >= MAX_BATCH
+    }
+
+    fn is_empty(&self) -> bool {
+        self.spans.is_empty() && self.metrics.is_empty() && self.logs.is_empty()
+    }
+}
+
+enum Exporter {
+    Grpc {
+        channel: Channel,
+        metadata: MetadataMap,
+    },
+    Http {
+        client: reqwest::Client,
+        endpoint: String,
+    },
+}
+
+impl Exporter {
+    fn new(config: &OtelForwardConfig) -> Result<Self> {
+        match config.protocol {
+            OtlpProtocol::Grpc => {
+                let channel = Endpoint::from_shared(config.endpoint.clone())
+                    .with_context(|| format!("invalid OTLP endpoint `{}`", config.endpoint))?
+                    .timeout(EXPORT_TIMEOUT)
+                    .connect_timeout(EXPORT_TIMEOUT)
+                    .connect_lazy();
+                let mut metadata = MetadataMap::new();
+                for (name, value) in &config.headers {
+                    let key = MetadataKey::from_bytes(name.to_ascii_lowercase().as_bytes())
+                        .with_context(|| format!("invalid OTLP header name `{name}`"))?;
+                    let value = MetadataValue::try_from(value.as_str())
+                        .with_context(|| format!("invalid value for OTLP header `{name}`"))?;
+                    metadata.insert(key, value);
+                }
+                Ok(Exporter::Grpc { channel, metadata })
+            }
+            OtlpProtocol::Http => {
+                let mut headers = reqwest::header::HeaderMap::new();
+                for (name, value) in &config.headers {
+                    let key = reqwest::header::HeaderName::from_bytes(name.as_bytes())
+                        .with_context(|| format!("invalid OTLP header name `{name}`"))?;
+                    let value = reqwest::header::HeaderValue::from_str(value)
+                        .with_context(|| format!("invalid value for OTLP header `{name}`"))?;
+                    headers.insert(key, value);
+                }
+                let client = reqwest::Client::builder()
+                    .default_headers(headers)
+                    .timeout(EXPORT_TIMEOUT)
+                    .build()
+                    .context("building OTLP HTTP client")?;
+                Ok(Exporter::Http {
+                    client,
+                    endpoint: config.endpoint.trim_end_matches('/').to_string(),
+                })
+            }
+        }
+    }
+
+    /// Export every non-empty signal in `batch`. All signals are attempted;
+    /// the first failure is returned.
+    async fn export(&self, batch: Batch) -> Result<()> {
+        let mut result = Ok(());
+        if !batch.spans.is_empty() {
+            let req = ExportTraceServiceRequest {
+                resource_spans: batch.spans,
+            };
+            result = result.and(self.export_traces(req).await.context("exporting traces"));
+        }
+        if !batch.metrics.is_empty() {
+            let req = ExportMetricsServiceRequest {
+                resource_metrics: batch.metrics,
+            };
+            result = result.and(self.export_metrics(req).await.context("exporting metrics"));
+        }
+        if !batch.logs.is_empty() {
+            let req = ExportLogsServiceRequest {
+                resource_logs: batch.logs,
+            };
+            result = result.and(self.export_logs(req).await.context("exporting logs"));
+        }
+        result
+    }
+
+    async fn export_traces(&self, req: ExportTraceServiceRequest) -> Result<()> {
+        match self {
+            Exporter::Grpc { channel, metadata } => {
+                TraceServiceClient::new(channel.clone())
+                    .export(grpc_request(req, metadata))
+                    .await?;
+                Ok(())
+            }
+            Exporter::Http { client, endpoint } => post(client, endpoint, "traces", &req).await,
+        }
+    }
+
+    async fn export_metrics(&self, req: ExportMetricsServiceRequest) -> Result<()> {
+        match self {
+            Exporter::Grpc { channel, metadata } => {
+                MetricsServiceClient::new(channel.clone())
+                    .export(grpc_request(req, metadata))
+                    .await?;
+                Ok(())
+            }
+            Exporter::Http { client, endpoint } => post(client, endpoint, "metrics", &req).await,
+        }
+    }
+
+    async fn export_logs(&self, req: ExportLogsServiceRequest) -> Result<()> {
+        match self {
+            Exporter::Grpc { channel, metadata } => {
+                LogsServiceClient::new(channel.clone())
+                    .export(grpc_request(req, metadata))
+                    .await?;
+                Ok(())
+            }
+            Exporter::Http { client, endpoint } => post(client, endpoint, "logs", &req).await,
+        }
+    }
+}
+
+fn grpc_request<T>(message: T, metadata: &MetadataMap) -> tonic::Request<T> {
+    let mut request = tonic::Request::new(message);
+    *request.metadata_mut() = metadata.clone();
+    request
+}
+
+async fn post<T: Message>(
+    client: &reqwest::Client,
+    endpoint: &str,
+    signal: &str,
+    message: &T,
+) -> Result<()> {
+    let response = client
+        .post(format!("{endpoint}/v1/{signal}"))
+        .header(reqwest::header::CONTENT_TYPE, "application/x-protobuf")
+        .body(message.encode_to_vec())
+        .send()
+        .await?;
+    let status = response.status();
+    if !status.is_success() {
+        anyhow::bail!("upstream returned {status}");
+    }
+    Ok(())
+}
+
+/// Start the forwarding task for `config` and return the handle receivers
+/// queue telemetry through. On cancellation, whatever is queued is exported
+/// once more before the task exits.
+pub fn spawn(config: &OtelForwardConfig, cancel: CancellationToken) -> Result<Forwarder> {
+    let exporter = Exporter::new(config)?;
+    let (tx, rx) = mpsc::channel(QUEUE_CAPACITY);
+    let endpoint = config.endpoint.clone();
+    tokio::spawn(run(rx, exporter, endpoint, cancel));
+    Ok(Forwarder {
+        tx,
+        dropped: Arc::new(AtomicU64::new(0)),
+    })
+}
+
+async fn run(
+    mut rx: mpsc::Receiver<ForwardItem>,
+    exporter: Exporter,
+    endpoint: String,
+    cancel: CancellationToken,
+) {
+    let mut batch = Batch::default();
+    let mut healthy = true;
+    let mut interval =
+        tokio::time::interval_at(tokio::time::Instant::now() + FLUSH_INTERVAL, FLUSH_INTERVAL);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        tokio::select! {
+            item = rx.recv() => {
+                let Some(item) = item else { break };
+                batch.push(item);
+                if !batch.is_full() {
+                    continue;
+                }
+            }
+            _ = interval.tick() => {}
+            _ = cancel.cancelled() => {
+                while let Ok(item) = rx.try_recv() {
+                    batch.push(item);
+                }
+                break;
+            }
+        }
+        flush(&exporter, &mut batch, &endpoint, &mut healthy).await;
+    }
+    flush(&exporter, &mut batch, &endpoint, &mut healthy).await;
+    debug!(endpoint = %endpoint, "OTLP forwarder stopped");
+}
+
+/// Export and clear `batch`. A failed batch is dropped; only the change
+/// between working and failing is logged, to avoid a warning per batch
+/// while the upstream is down.
+async fn flush(exporter: &Exporter, batch: &mut Batch, endpoint: &str, healthy: &mut bool) {
+    if batch.is_empty() {
+        return;
+    }
+    match exporter.export(mem::take(batch)).await {
+        Ok(()) => {
+            if !*healthy {
+                info!(endpoint, "OTLP forwarding recovered");
+                *healthy = true;
+            }
+        }
+        Err(e) => {
+            if *healthy {
+                warn!(endpoint, error = format!("{e:#}"), "OTLP forwarding failed");
+                *healthy = false;
+            } else {
+                debug!(
+                    endpoint,
+                    error = format!("{e:#}"),
+                    "OTLP forwarding still failing"
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    use axum::extract::State;
+    use axum::http::HeaderMap;
+    use axum::routing::post;
+    use axum::Router;
+    use opentelemetry_proto::tonic::trace::v1::{ScopeSpans, Span};
+
+    fn resource_spans(name: &str) -> ResourceSpans {
+        ResourceSpans {
+            scope_spans: vec![ScopeSpans {
+                spans: vec![Span {
+                    name: name.to_string(),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn batch_merges_by_signal_and_fills_up() {
+        let mut batch = Batch::default();
+        assert!(batch.is_empty());
+
+        batch.push(ForwardItem::Traces(vec![resource_spans("a")]));
+        batch.push(ForwardItem::Traces(vec![resource_spans("b")]));
+        batch.push(ForwardItem::Logs(vec![ResourceLogs::default()]));
+        assert_eq!(batch.spans.len(), 2);
+        assert_eq!(batch.logs.len(), 1);
+        assert!(batch.metrics.is_empty());
+        assert!(!batch.is_full());
+
+        batch.push(ForwardItem::Metrics(vec![
+            ResourceMetrics::default();
+            MAX_BATCH
+        ]));
+        assert!(batch.is_full());
+        assert!(mem::take(&mut batch).spans.len() == 2 && batch.is_empty());
+    }
+
+    #[test]
+    fn full_queue_drops_instead_of_blocking() {
+        let (tx, mut rx) = mpsc::channel(1);
+        let forwarder = Forwarder {
+            tx,
+            dropped: Arc::new(AtomicU64::new(0)),
+        };
+
+        forwarder.traces(vec![resource_spans("kept")]);
+        forwarder.traces(vec![resource_spans("dropped")]);
+        forwarder.logs(Vec::new());
+
+        assert_eq!(forwarder.dropped.load(Ordering::Relaxed), 1);
+        match rx.try_recv() {
+            Ok(ForwardItem::Traces(spans)) => {
+                assert_eq!(spans[0].scope_spans[0].spans[0].name, "kept")
+            }
+            _ => panic!("expected the first trace batch"),
+        }
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn http_forwarding_posts_protobuf_with_headers() {
+        type Received = mpsc::UnboundedSender<(Option<String>, ExportTraceServiceRequest)>;
+        async fn receive(State(tx): State<Received>, headers: HeaderMap, body: axum::body::Bytes) {
+            let auth = headers
+                .get("authorization")
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string);
+            let req = ExportTraceServiceRequest::decode(body).unwrap();
+            let _ = tx.send((auth, req));
+        }
+
+        let (tx, mut received) = mpsc::unbounded_channel();
+        let app = Router::new()
+            .route("/v1/traces", post(receive))
+            .with_state(tx);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let config = OtelForwardConfig {
+            endpoint: format!("http://{addr}/"),
+            protocol: OtlpProtocol::Http,
+            headers: BTreeMap::from([("authorization".to_string(), "Bearer t".to_string())]),
+        };
+        let cancel = CancellationToken::new();
+        let forwarder = spawn(&config, cancel.clone()).unwrap();
+        forwarder.traces(vec![resource_spans("first")]);
+        forwarder.traces(vec![resource_spans("second")]);
+
+        let (auth, req) = tokio::time::timeout(Duration::from_secs(5), received.recv())
+            .await
+            .expect("upstream received nothing")
+            .unwrap();
+        cancel.cancel();
+
+        assert_eq!(auth.as_deref(), Some("Bearer t"));
+        let names: Vec<&str> = req
+            .resource_spans
+            .iter()
+            .map(|rs| rs.scope_spans[0].spans[0].name.as_str())
+            .collect();
+        assert_eq!(names, vec!["first", "second"]);
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_header_names() {
+        for protocol in [OtlpProtocol::Grpc, OtlpProtocol::Http] {
+            let config = OtelForwardConfig {
+                endpoint: "http://localhost:4317".to_string(),
+                protocol,
+                headers: BTreeMap::from([("bad header".to_string(), "x".to_string())]),
+            };
+            let err = Exporter::new(&config)
+                .err()
+                .expect("header should be rejected");
+            assert!(err.to_string().contains("bad header"), "{err}");
+        }
+    }
+}
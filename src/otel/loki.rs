@@ -0,0 +1,140 @@
+//! A minimal Loki push endpoint (`POST /loki/api/v1/push`, JSON only) so
+//! log shippers already pointed at Loki can send logs into devrig.
+
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+
+use super::types::{nanos_to_datetime, LogSeverity, StoredLog};
+use crate::ui::logs::detect_log_level;
+
+/// Body of a JSON push request.
+#[derive(Debug, Deserialize)]
+pub struct PushRequest {
+    pub streams: Vec<PushStream>,
+}
+
+/// Log lines sharing one label set. Each entry of `values` is
+/// `[timestamp_ns, line]` or `[timestamp_ns, line, structured_metadata]`.
+#[derive(Debug, Deserialize)]
+pub struct PushStream {
+    #[serde(default)]
+    pub stream: BTreeMap<String, String>,
+    pub values: Vec<PushEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum PushEntry {
+    Line(String, String),
+    WithMetadata(String, String, BTreeMap<String, String>),
+}
+
+/// Labels that name the sending service, in order of preference.
+const SERVICE_LABELS: &[&str] = &["service_name", "service", "app", "job"];
+
+/// Labels that carry the log level, in order of preference.
+const LEVEL_LABELS: &[&str] = &["level", "detected_level", "severity"];
+
+/// Convert a push request into stored logs. Labels and structured metadata
+/// become attributes. An entry whose timestamp is not a nanosecond integer
+/// fails the whole request, as it does in Loki.
+pub fn push_to_stored(req: &PushRequest) -> Result<Vec<StoredLog>, String> {
+    let mut logs = Vec::new();
+    for stream in &req.streams {
+        let service_name = SERVICE_LABELS
+            .iter()
+            .find_map(|l| stream.stream.get(*l))
+            .cloned()
+            .unwrap_or_else(|| "unknown".to_string());
+        let level = LEVEL_LABELS.iter().find_map(|l| stream.stream.get(*l));
+
+        for entry in &stream.values {
+            let (ts, line, metadata) = match entry {
+                PushEntry::Line(ts, line) => (ts, line, None),
+                PushEntry::WithMetadata(ts, line, metadata) => (ts, line, Some(metadata)),
+            };
+            let nanos: u64 = ts
+                .parse()
+                .map_err(|_| format!("invalid timestamp {:?}: expected nanoseconds", ts))?;
+
+            let mut attributes: Vec<(String, String)> = stream
+                .stream
+                .iter()
+                .chain(metadata.into_iter().flatten())
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect();
+            attributes.push(("log.source".to_string(), "loki".to_string()));
+
+            let detected = detect_log_level(level.map(String::as_str).unwrap_or(line));
+            logs.push(StoredLog {
+                record_id: 0,
+                timestamp: nanos_to_datetime(nanos),
+                service_name: service_name.clone(),
+                severity: LogSeverity::from_log_level(detected, false),
+                body: line.clone(),
+                trace_id: None,
+                span_id: None,
+                attributes,
+            });
+        }
+    }
+    Ok(logs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_streams_to_logs() {
+        let req: PushRequest = serde_json::from_str(
+            r#"{
+                "streams": [
+                    {
+                        "stream": { "job": "nginx", "level": "error" },
+                        "values": [
+                            ["1700000000000000000", "upstream timed out"],
+                            ["1700000001000000000", "retrying", { "trace": "abc" }]
+                        ]
+                    },
+                    {
+                        "stream": { "service_name": "api", "job": "backend" },
+                        "values": [["1700000002000000000", "WARN slow query"]]
+                    },
+                    { "values": [["1700000003000000000", "plain line"]] }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let logs = push_to_stored(&req).unwrap();
+        assert_eq!(logs.len(), 4);
+
+        assert_eq!(logs[0].service_name, "nginx");
+        assert_eq!(logs[0].severity, LogSeverity::Error);
+        assert_eq!(logs[0].body, "upstream timed out");
+        assert_eq!(logs[0].timestamp.timestamp(), 1_700_000_000);
+        assert!(logs[0]
+            .attributes
+            .contains(&("log.source".to_string(), "loki".to_string())));
+        assert!(logs[1]
+            .attributes
+            .contains(&("trace".to_string(), "abc".to_string())));
+
+        assert_eq!(logs[2].service_name, "api");
+        assert_eq!(logs[2].severity, LogSeverity::Warn);
+
+        assert_eq!(logs[3].service_name, "unknown");
+        assert_eq!(logs[3].severity, LogSeverity::Info);
+    }
+
+    #[test]
+    fn rejects_non_numeric_timestamps() {
+        let req: PushRequest = serde_json::from_str(
+            r#"{ "streams": [{ "stream": {}, "values": [["yesterday", "line"]] }] }"#,
+        )
+        .unwrap();
+        assert!(push_to_stored(&req).unwrap_err().contains("yesterday"));
+    }
+}
@@ -1,17 +1,26 @@
+pub mod forward;
+pub mod loki;
+pub mod prometheus;
 pub mod query;
 pub mod receiver_grpc;
 pub mod receiver_http;
 pub mod storage;
+pub mod structured;
 pub mod types;
+pub mod wal;
 
+use std::path::Path;
 use std::sync::Arc;
 use std::time::Duration;
 
+use anyhow::Context;
+
 use tokio::sync::{broadcast, RwLock};
 use tokio_util::sync::CancellationToken;
 use tracing::{debug, warn};
 
-use crate::config::model::OtelConfig;
+use crate::config::model::{OtelConfig, OtelForwardConfig};
+use crate::tls::TlsIdentity;
 use types::TelemetryEvent;
 
 use self::storage::TelemetryStore;
@@ -22,19 +31,26 @@ pub struct OtelCollector {
     events_tx: broadcast::Sender<TelemetryEvent>,
     grpc_port: u16,
     http_port: u16,
+    forward: Option<OtelForwardConfig>,
+    wal_enabled: bool,
+    tls: Option<TlsIdentity>,
+}
+
+/// An empty store with the buffer sizes and retention from `otel_config`.
+pub fn new_store(otel_config: &OtelConfig) -> TelemetryStore {
+    let retention = humantime::parse_duration(&otel_config.retention)
+        .unwrap_or_else(|_| Duration::from_secs(3600));
+    TelemetryStore::new(
+        otel_config.trace_buffer,
+        otel_config.log_buffer,
+        otel_config.metric_buffer,
+        retention,
+    )
 }
 
 impl OtelCollector {
     pub fn new(otel_config: &OtelConfig) -> Self {
-        let retention = humantime::parse_duration(&otel_config.retention)
-            .unwrap_or_else(|_| Duration::from_secs(3600));
-
-        let store = Arc::new(RwLock::new(TelemetryStore::new(
-            otel_config.trace_buffer,
-            otel_config.log_buffer,
-            otel_config.metric_buffer,
-            retention,
-        )));
+        let store = Arc::new(RwLock::new(new_store(otel_config)));
 
         let (events_tx, _) = broadcast::channel(1024);
 
@@ -43,9 +59,28 @@ impl OtelCollector {
             events_tx,
             grpc_port: otel_config.grpc_port.as_fixed().expect("otel grpc_port must be resolved before creating collector"),
             http_port: otel_config.http_port.as_fixed().expect("otel http_port must be resolved before creating collector"),
+            forward: otel_config.forward.clone(),
+            wal_enabled: false,
+            tls: None,
         }
     }
 
+    /// Serve both receivers over TLS with `identity`.
+    pub fn enable_tls(&mut self, identity: TlsIdentity) {
+        self.tls = Some(identity);
+    }
+
+    /// Reload telemetry persisted in `dir` by earlier runs, then persist
+    /// everything stored from now on there too. Returns the number of
+    /// records reloaded.
+    pub async fn enable_wal(&mut self, dir: &Path, max_bytes: u64) -> std::io::Result<usize> {
+        let mut store = self.store.write().await;
+        let loaded = wal::replay(dir, &mut store);
+        store.attach_wal(wal::TelemetryWal::open(dir, max_bytes)?);
+        self.wal_enabled = true;
+        Ok(loaded)
+    }
+
     pub fn store(&self) -> Arc<RwLock<TelemetryStore>> {
         Arc::clone(&self.store)
     }
@@ -55,16 +90,36 @@ impl OtelCollector {
     }
 
     /// Start the OTLP gRPC and HTTP receivers as background tasks.
-    /// Also starts a background sweeper for expired telemetry.
+    /// Also starts a background sweeper for expired telemetry, and the
+    /// upstream forwarder when `forward` is configured.
     pub async fn start(&self, cancel: CancellationToken) -> anyhow::Result<()> {
+        let forwarder = match &self.forward {
+            Some(config) => {
+                let forwarder = forward::spawn(config, cancel.clone())
+                    .context("configuring OTLP forwarding")?;
+                debug!(endpoint = %config.endpoint, "OTLP forwarding enabled");
+                Some(forwarder)
+            }
+            None => None,
+        };
+
         // Start gRPC OTLP receiver
         let grpc_store = Arc::clone(&self.store);
         let grpc_tx = self.events_tx.clone();
         let grpc_port = self.grpc_port;
+        let grpc_forwarder = forwarder.clone();
+        let grpc_tls = self.tls.clone();
         let grpc_cancel = cancel.clone();
         tokio::spawn(async move {
-            if let Err(e) =
-                receiver_grpc::start_grpc_server(grpc_port, grpc_store, grpc_tx, grpc_cancel).await
+            if let Err(e) = receiver_grpc::start_grpc_server(
+                grpc_port,
+                grpc_store,
+                grpc_tx,
+                grpc_forwarder,
+                grpc_tls,
+                grpc_cancel,
+            )
+            .await
             {
                 warn!(error = %e, "OTLP gRPC server failed");
             }
@@ -75,11 +130,18 @@ impl OtelCollector {
         let http_store = Arc::clone(&self.store);
         let http_tx = self.events_tx.clone();
         let http_port = self.http_port;
+        let http_tls = self.tls.clone();
         let http_cancel = cancel.clone();
         tokio::spawn(async move {
-            if let Err(e) =
-                receiver_http::start_http_otlp_server(http_port, http_store, http_tx, http_cancel)
-                    .await
+            if let Err(e) = receiver_http::start_http_otlp_server(
+                http_port,
+                http_store,
+                http_tx,
+                forwarder,
+                http_tls,
+                http_cancel,
+            )
+            .await
             {
                 warn!(error = %e, "OTLP HTTP server failed");
             }
@@ -101,6 +163,25 @@ impl OtelCollector {
             }
         });
 
+        // Flush the WAL every second, and once more on shutdown
+        if self.wal_enabled {
+            let wal_store = Arc::clone(&self.store);
+            let wal_cancel = cancel.clone();
+            tokio::spawn(async move {
+                loop {
+                    tokio::select! {
+                        _ = tokio::time::sleep(Duration::from_secs(1)) => {
+                            wal_store.write().await.flush_wal();
+                        }
+                        _ = wal_cancel.cancelled() => {
+                            wal_store.write().await.flush_wal();
+                            break;
+                        }
+                    }
+                }
+            });
+        }
+
         Ok(())
     }
 }
@@ -0,0 +1,420 @@
+//! Prometheus text exposition of stored metrics, served at `/metrics` so
+//! existing scrapers can read what devrig collected over OTLP.
+
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use super::types::{HistogramBuckets, MetricType, StoredMetric};
+
+/// Replace characters Prometheus doesn't allow in metric names (OTel names
+/// use dots) with `_`.
+fn metric_name(name: &str) -> String {
+    let mut out: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == ':' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if out.is_empty() || out.starts_with(|c: char| c.is_ascii_digit()) {
+        out.insert(0, '_');
+    }
+    out
+}
+
+/// Like [`metric_name`], minus `:`, which label names may not contain.
+/// Names starting with `__` are reserved, so they get an extra prefix.
+fn label_name(name: &str) -> String {
+    let mut out = metric_name(name).replace(':', "_");
+    if out.starts_with("__") {
+        out.insert_str(0, "attr");
+    }
+    out
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn format_value(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// Family name and `# TYPE` for a stored metric. Counters get the
+/// conventional `_total` suffix. Histograms with bucket data are exposed as
+/// histograms; for those without, only the sum is known, so it becomes an
+/// untyped `_sum` series.
+fn family(metric: &StoredMetric) -> (String, &'static str) {
+    let name = metric_name(&metric.metric_name);
+    match metric.metric_type {
+        MetricType::Gauge => (name, "gauge"),
+        MetricType::Counter if name.ends_with("_total") => (name, "counter"),
+        MetricType::Counter => (format!("{name}_total"), "counter"),
+        MetricType::Histogram if metric.histogram.is_some() => (name, "histogram"),
+        MetricType::Histogram => (format!("{name}_sum"), "untyped"),
+    }
+}
+
+/// Rendered `key="value"` pairs for a series, sorted by label name.
+fn labels(metric: &StoredMetric) -> Vec<String> {
+    let mut pairs: BTreeMap<String, &str> = BTreeMap::new();
+    pairs.insert("service_name".to_string(), &metric.service_name);
+    for (key, value) in &metric.attributes {
+        pairs.entry(label_name(key)).or_insert(value);
+    }
+    pairs
+        .iter()
+        .map(|(k, v)| format!("{}=\"{}\"", k, escape_label_value(v)))
+        .collect()
+}
+
+/// The exposed value of one series.
+enum Sample {
+    Value(f64),
+    Histogram { sum: f64, buckets: HistogramBuckets },
+}
+
+impl Sample {
+    /// Fold a newer point of the same series into this one. Delta
+    /// histograms add up, since Prometheus expects cumulative buckets;
+    /// everything else keeps the latest value.
+    fn update(&mut self, metric: &StoredMetric) {
+        if let (Sample::Histogram { sum, buckets }, Some(next)) = (&mut *self, &metric.histogram) {
+            if !next.cumulative
+                && buckets.bounds == next.bounds
+                && buckets.counts.len() == next.counts.len()
+            {
+                *sum += metric.value;
+                buckets.count += next.count;
+                for (total, n) in buckets.counts.iter_mut().zip(&next.counts) {
+                    *total += n;
+                }
+                return;
+            }
+        }
+        *self = Sample::from(metric);
+    }
+}
+
+impl From<&StoredMetric> for Sample {
+    fn from(metric: &StoredMetric) -> Self {
+        match &metric.histogram {
+            Some(buckets) => Sample::Histogram {
+                sum: metric.value,
+                buckets: buckets.clone(),
+            },
+            None => Sample::Value(metric.value),
+        }
+    }
+}
+
+/// Write one series: a single sample, or the `_bucket` / `_sum` / `_count`
+/// samples of a histogram with cumulative `le` buckets.
+fn write_series(out: &mut String, name: &str, labels: &[String], sample: &Sample) {
+    let braced = |extra: Option<String>| {
+        let pairs: Vec<&str> = labels
+            .iter()
+            .map(String::as_str)
+            .chain(extra.as_deref())
+            .collect();
+        format!("{{{}}}", pairs.join(","))
+    };
+    match sample {
+        Sample::Value(value) => {
+            let _ = writeln!(out, "{}{} {}", name, braced(None), format_value(*value));
+        }
+        Sample::Histogram { sum, buckets } => {
+            let mut cumulative = 0;
+            for (i, count) in buckets.counts.iter().enumerate() {
+                cumulative += count;
+                let le = buckets
+                    .bounds
+                    .get(i)
+                    .map_or("+Inf".to_string(), |b| format_value(*b));
+                let le = Some(format!("le=\"{}\"", le));
+                let _ = writeln!(out, "{}_bucket{} {}", name, braced(le), cumulative);
+            }
+            // Exporters may omit the unbounded bucket; Prometheus requires it.
+            if buckets.counts.len() <= buckets.bounds.len() {
+                let le = Some("le=\"+Inf\"".to_string());
+                let _ = writeln!(out, "{}_bucket{} {}", name, braced(le), buckets.count);
+            }
+            let _ = writeln!(out, "{}_sum{} {}", name, braced(None), format_value(*sum));
+            let _ = writeln!(out, "{}_count{} {}", name, braced(None), buckets.count);
+        }
+    }
+}
+
+/// Render the latest value of every metric series in the text exposition
+/// format (version 0.0.4). A series is a metric name plus its service and
+/// attributes. If two metrics map to the same family with different types,
+/// the first type seen wins and the others are dropped; so is an untyped
+/// `_sum` that would repeat a histogram's own `_sum` samples.
+pub fn render<'a>(metrics: impl IntoIterator<Item = &'a StoredMetric>) -> String {
+    type Series = BTreeMap<Vec<String>, Sample>;
+    let mut families: BTreeMap<String, (&'static str, Series)> = BTreeMap::new();
+    for metric in metrics {
+        let (name, kind) = family(metric);
+        let (family_kind, series) = families.entry(name).or_insert((kind, BTreeMap::new()));
+        if *family_kind == kind {
+            match series.entry(labels(metric)) {
+                Entry::Occupied(mut entry) => entry.get_mut().update(metric),
+                Entry::Vacant(entry) => {
+                    entry.insert(Sample::from(metric));
+                }
+            }
+        }
+    }
+
+    let mut out = String::new();
+    for (name, (kind, series)) in &families {
+        let shadowed = name
+            .strip_suffix("_sum")
+            .and_then(|base| families.get(base))
+            .is_some_and(|(kind, _)| *kind == "histogram");
+        if *kind == "untyped" && shadowed {
+            continue;
+        }
+        let _ = writeln!(out, "# TYPE {} {}", name, kind);
+        for (labels, sample) in series {
+            write_series(&mut out, name, labels, sample);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use regex::Regex;
+    use std::collections::HashSet;
+
+    fn metric(
+        service: &str,
+        name: &str,
+        metric_type: MetricType,
+        value: f64,
+        attributes: &[(&str, &str)],
+    ) -> StoredMetric {
+        StoredMetric {
+            record_id: 0,
+            timestamp: Utc::now(),
+            service_name: service.to_string(),
+            metric_name: name.to_string(),
+            metric_type,
+            value,
+            attributes: attributes
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            unit: None,
+            histogram: None,
+        }
+    }
+
+    /// Check `text` against the text exposition grammar: comment lines are
+    /// `# TYPE`/`# HELP`, each family is typed once before its samples,
+    /// names and labels are well-formed, and values parse as floats.
+    fn assert_valid_exposition(text: &str) {
+        let type_re = Regex::new(
+            r"^# TYPE ([a-zA-Z_:][a-zA-Z0-9_:]*) (counter|gauge|histogram|summary|untyped)$",
+        )
+        .unwrap();
+        let sample_re = Regex::new(
+            r#"^([a-zA-Z_:][a-zA-Z0-9_:]*)(\{(?:[a-zA-Z_][a-zA-Z0-9_]*="(?:[^"\\\n]|\\["\\n])*"(?:,[a-zA-Z_][a-zA-Z0-9_]*="(?:[^"\\\n]|\\["\\n])*")*)?\})? (\S+)$"#,
+        )
+        .unwrap();
+        let mut typed = HashSet::new();
+        let mut current = None;
+        for line in text.lines() {
+            if let Some(caps) = type_re.captures(line) {
+                let name = caps[1].to_string();
+                assert!(typed.insert(name.clone()), "family typed twice: {line}");
+                current = Some(name);
+                continue;
+            }
+            assert!(!line.starts_with('#'), "unexpected comment: {line}");
+            let caps = sample_re
+                .captures(line)
+                .unwrap_or_else(|| panic!("malformed sample: {line:?}"));
+            let family = ["_bucket", "_sum", "_count"]
+                .iter()
+                .find_map(|suffix| caps[1].strip_suffix(suffix))
+                .filter(|base| current.as_deref() == Some(*base))
+                .unwrap_or(&caps[1]);
+            assert_eq!(
+                Some(family),
+                current.as_deref(),
+                "sample outside its family: {line}"
+            );
+            let value = &caps[3];
+            assert!(
+                matches!(value, "NaN" | "+Inf" | "-Inf") || value.parse::<f64>().is_ok(),
+                "bad value: {line}"
+            );
+        }
+        assert!(text.ends_with('\n'));
+    }
+
+    #[test]
+    fn renders_valid_exposition_for_synthetic_metrics() {
+        let metrics = vec![
+            metric(
+                "api",
+                "http.server.request.count",
+                MetricType::Counter,
+                3.0,
+                &[("http.method", "GET"), ("http.route", "/users/\"{id}\"")],
+            ),
+            metric(
+                "api",
+                "http.server.request.count",
+                MetricType::Counter,
+                7.0,
+                &[("http.method", "GET"), ("http.route", "/users/\"{id}\"")],
+            ),
+            metric("api", "process.memory.usage", MetricType::Gauge, 1.5e8, &[]),
+            metric(
+                "worker",
+                "process.memory.usage",
+                MetricType::Gauge,
+                f64::NAN,
+                &[("__name__", "x"), ("path", "C:\\tmp\nnext")],
+            ),
+            metric(
+                "api",
+                "http.server.duration",
+                MetricType::Histogram,
+                42.5,
+                &[],
+            ),
+            metric("api", "jobs_total", MetricType::Counter, f64::INFINITY, &[]),
+            metric("api", "9lives", MetricType::Gauge, -1.0, &[]),
+        ];
+
+        let out = render(&metrics);
+        assert_valid_exposition(&out);
+
+        // Later samples of a series replace earlier ones.
+        assert!(out.contains(
+            "http_server_request_count_total{http_method=\"GET\",http_route=\"/users/\\\"{id}\\\"\",service_name=\"api\"} 7\n"
+        ), "{out}");
+        assert!(!out.contains("} 3\n"), "{out}");
+        assert!(out.contains("# TYPE process_memory_usage gauge\n"), "{out}");
+        assert!(
+            out.contains("process_memory_usage{service_name=\"api\"} 150000000\n"),
+            "{out}"
+        );
+        assert!(out.contains("attr__name__=\"x\""), "{out}");
+        assert!(out.contains("path=\"C:\\\\tmp\\nnext\""), "{out}");
+        assert!(
+            out.contains("# TYPE http_server_duration_sum untyped\n"),
+            "{out}"
+        );
+        assert!(
+            out.contains("jobs_total{service_name=\"api\"} +Inf\n"),
+            "{out}"
+        );
+        assert!(out.contains("_9lives{service_name=\"api\"} -1\n"), "{out}");
+    }
+
+    #[test]
+    fn conflicting_types_keep_the_first() {
+        let metrics = vec![
+            metric("api", "queue.depth_total", MetricType::Gauge, 1.0, &[]),
+            metric("worker", "queue.depth", MetricType::Counter, 2.0, &[]),
+            metric("worker", "queue.depth", MetricType::Histogram, 3.0, &[]),
+        ];
+        let out = render(&metrics);
+        assert_valid_exposition(&out);
+        assert!(out.contains("# TYPE queue_depth_total gauge\n"), "{out}");
+        assert!(!out.contains("worker\"} 2"), "{out}");
+        assert!(
+            out.contains("queue_depth_sum{service_name=\"worker\"} 3\n"),
+            "{out}"
+        );
+    }
+
+    fn histogram(
+        service: &str,
+        sum: f64,
+        bounds: &[f64],
+        counts: &[u64],
+        cumulative: bool,
+    ) -> StoredMetric {
+        let mut m = metric(
+            service,
+            "http.server.duration",
+            MetricType::Histogram,
+            sum,
+            &[],
+        );
+        m.histogram = Some(HistogramBuckets {
+            count: counts.iter().sum(),
+            bounds: bounds.to_vec(),
+            counts: counts.to_vec(),
+            cumulative,
+        });
+        m
+    }
+
+    #[test]
+    fn histograms_with_buckets_render_as_histograms() {
+        let metrics = vec![
+            histogram("api", 10.0, &[5.0, 10.0], &[1, 1, 0], true),
+            histogram("api", 42.5, &[5.0, 10.0], &[2, 3, 1], true),
+            // Delta points add up; the exporter left out the +Inf bucket.
+            histogram("worker", 4.0, &[5.0, 10.0], &[1, 0], false),
+            histogram("worker", 6.0, &[5.0, 10.0], &[1, 1], false),
+            // The bucketless sum would repeat http_server_duration_sum.
+            metric(
+                "db",
+                "http.server.duration",
+                MetricType::Histogram,
+                1.0,
+                &[],
+            ),
+        ];
+        let out = render(&metrics);
+        assert_valid_exposition(&out);
+        assert!(
+            out.starts_with("# TYPE http_server_duration histogram\n"),
+            "{out}"
+        );
+        for line in [
+            "http_server_duration_bucket{service_name=\"api\",le=\"5\"} 2\n",
+            "http_server_duration_bucket{service_name=\"api\",le=\"10\"} 5\n",
+            "http_server_duration_bucket{service_name=\"api\",le=\"+Inf\"} 6\n",
+            "http_server_duration_sum{service_name=\"api\"} 42.5\n",
+            "http_server_duration_count{service_name=\"api\"} 6\n",
+            "http_server_duration_bucket{service_name=\"worker\",le=\"5\"} 2\n",
+            "http_server_duration_bucket{service_name=\"worker\",le=\"10\"} 3\n",
+            "http_server_duration_bucket{service_name=\"worker\",le=\"+Inf\"} 3\n",
+            "http_server_duration_sum{service_name=\"worker\"} 10\n",
+            "http_server_duration_count{service_name=\"worker\"} 3\n",
+        ] {
+            assert!(out.contains(line), "missing {line:?} in {out}");
+        }
+        assert!(!out.contains("untyped"), "{out}");
+        assert!(!out.contains("db"), "{out}");
+    }
+
+    #[test]
+    fn empty_store_renders_nothing() {
+        assert_eq!(render(&[]), "");
+    }
+}
@@ -1,10 +1,17 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 use super::storage::TelemetryStore;
-use super::types::{LogSeverity, MetricType, SpanStatus, StoredLog, StoredMetric, StoredSpan};
+use super::types::{
+    HistogramBuckets, LogSeverity, MetricType, SpanKind, SpanStatus, StoredLog, StoredMetric,
+    StoredSpan,
+};
+
+/// Orphans younger than this are not reported: children usually export
+/// before their parent span ends, so a missing parent may still arrive.
+const ORPHAN_GRACE_SECS: i64 = 30;
 
 // -----------------------------------------------------------------------
 // Query parameters
@@ -17,7 +24,13 @@ pub struct TraceQuery {
     pub min_duration_ms: Option<u64>,
     pub search: Option<String>,
     pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    /// Relative window ending now, e.g. "15m"; excludes `since`/`until`.
+    pub last: Option<String>,
     pub limit: Option<usize>,
+    /// Only traces with a root span; `service` and `search` match the root.
+    #[serde(default)]
+    pub root_only: bool,
 }
 
 #[derive(Debug, Default, Deserialize)]
@@ -27,6 +40,9 @@ pub struct LogQuery {
     pub search: Option<String>,
     pub trace_id: Option<String>,
     pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    /// Relative window ending now, e.g. "15m"; excludes `since`/`until`.
+    pub last: Option<String>,
     pub limit: Option<usize>,
     /// Filter by log source: "process" (stdout+stderr), "stdout", "stderr", "docker", "otlp", or omit for all.
     pub source: Option<String>,
@@ -38,6 +54,9 @@ pub struct MetricQuery {
     pub metric_type: Option<String>,
     pub service: Option<String>,
     pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    /// Relative window ending now, e.g. "15m"; excludes `since`/`until`.
+    pub last: Option<String>,
     pub limit: Option<usize>,
 }
 
@@ -46,8 +65,58 @@ pub struct MetricSeriesQuery {
     pub name: String,
     pub service: Option<String>,
     pub since: Option<DateTime<Utc>>,
+    /// Aggregate points over `window` instead of returning them raw.
+    pub agg: Option<MetricAgg>,
+    /// Aggregation window, e.g. "30s" or "1m". Defaults to 30 seconds.
+    pub window: Option<String>,
+}
+
+impl MetricSeriesQuery {
+    /// The aggregation window, checked to be a positive duration.
+    pub fn window(&self) -> Result<chrono::Duration, String> {
+        let Some(window) = &self.window else {
+            return Ok(chrono::Duration::seconds(DEFAULT_AGG_WINDOW_SECS));
+        };
+        if self.agg.is_none() {
+            return Err("window requires agg".to_string());
+        }
+        match humantime::parse_duration(window) {
+            Ok(d) if d.as_millis() > 0 => {
+                chrono::Duration::from_std(d).map_err(|e| format!("invalid window: {}", e))
+            }
+            Ok(_) => Err("invalid window: must be at least 1ms".to_string()),
+            Err(e) => Err(format!("invalid window '{}': {}", window, e)),
+        }
+    }
 }
 
+/// Server-side aggregation for `/api/metrics/series`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MetricAgg {
+    /// Per-second increase. Counts observations for histograms.
+    Rate,
+    /// Mean value; mean observation for histograms.
+    Avg,
+    P50,
+    P95,
+    P99,
+}
+
+impl MetricAgg {
+    fn quantile(self) -> Option<f64> {
+        match self {
+            MetricAgg::P50 => Some(0.5),
+            MetricAgg::P95 => Some(0.95),
+            MetricAgg::P99 => Some(0.99),
+            MetricAgg::Rate | MetricAgg::Avg => None,
+        }
+    }
+}
+
+/// Default aggregation window, in seconds.
+const DEFAULT_AGG_WINDOW_SECS: i64 = 30;
+
 #[derive(Debug, Clone, Serialize)]
 pub struct MetricSeriesPoint {
     pub t: i64,  // unix milliseconds
@@ -68,6 +137,299 @@ pub struct MetricSeriesResponse {
     pub series: Vec<MetricSeries>,
 }
 
+/// A root span has no parent; exporters send either no parent or an empty id.
+pub fn is_root_span(span: &StoredSpan) -> bool {
+    span.parent_span_id.as_deref().is_none_or(str::is_empty)
+}
+
+/// Check that an absolute `since`..`until` window is not inverted.
+pub fn check_time_range(
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+) -> Result<(), String> {
+    match (since, until) {
+        (Some(from), Some(to)) if from > to => Err(format!(
+            "invalid time range: start {} is after end {}",
+            from.to_rfc3339(),
+            to.to_rfc3339()
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// An absolute `since`..`until` window; `None` leaves that side open.
+pub type TimeWindow = (Option<DateTime<Utc>>, Option<DateTime<Utc>>);
+
+/// The absolute window a query covers. `last` (e.g. "15m") starts the
+/// window that long before `now` and leaves it open-ended; it can't be
+/// combined with `since`/`until`.
+pub fn time_window(
+    last: Option<&str>,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Result<TimeWindow, String> {
+    let Some(last) = last else {
+        check_time_range(since, until)?;
+        return Ok((since, until));
+    };
+    if since.is_some() || until.is_some() {
+        return Err("last can't be combined with since/until".to_string());
+    }
+    let duration = humantime::parse_duration(last)
+        .map_err(|e| format!("invalid last '{}': {}", last, e))
+        .and_then(|d| {
+            chrono::Duration::from_std(d).map_err(|e| format!("invalid last '{}': {}", last, e))
+        })?;
+    Ok((Some(now - duration), None))
+}
+
+/// Whether `ts` falls within the inclusive `since`..`until` window.
+fn in_window(ts: DateTime<Utc>, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>) -> bool {
+    since.is_none_or(|s| ts >= s) && until.is_none_or(|u| ts <= u)
+}
+
+// -----------------------------------------------------------------------
+// Metric aggregation
+// -----------------------------------------------------------------------
+
+/// Aggregate one series' points (sorted by time) over consecutive windows
+/// aligned to multiples of `window`. Each output point is stamped with the
+/// end of its window. Points with different attributes are aggregated
+/// separately and then combined: rates add up, histogram buckets merge,
+/// and raw values are pooled.
+///
+/// Counters and histograms are treated as cumulative; a drop in value is a
+/// reset. The last point before a window is its baseline, so a window's
+/// increase includes what happened between that point and its first one.
+pub fn aggregate_points(
+    points: &[&StoredMetric],
+    agg: MetricAgg,
+    window: chrono::Duration,
+) -> Vec<MetricSeriesPoint> {
+    let window_ms = window.num_milliseconds().max(1);
+
+    let mut by_attributes: BTreeMap<&[(String, String)], Vec<&StoredMetric>> = BTreeMap::new();
+    for point in points {
+        by_attributes
+            .entry(point.attributes.as_slice())
+            .or_default()
+            .push(point);
+    }
+
+    let windows: BTreeSet<i64> = points
+        .iter()
+        .map(|p| p.timestamp.timestamp_millis().div_euclid(window_ms))
+        .collect();
+
+    windows
+        .into_iter()
+        .filter_map(|idx| {
+            let start = idx * window_ms;
+            let end = start + window_ms;
+            let slices: Vec<WindowSlice> = by_attributes
+                .values()
+                .map(|series| WindowSlice::new(series, start, end))
+                .filter(|slice| !slice.points.is_empty())
+                .collect();
+            aggregate_window(&slices, agg).map(|v| MetricSeriesPoint { t: end, v })
+        })
+        .collect()
+}
+
+/// One attribute set's points within a window, plus the point before it.
+struct WindowSlice<'a> {
+    baseline: Option<&'a StoredMetric>,
+    points: &'a [&'a StoredMetric],
+}
+
+impl<'a> WindowSlice<'a> {
+    fn new(series: &'a [&'a StoredMetric], start: i64, end: i64) -> Self {
+        let first = series.partition_point(|p| p.timestamp.timestamp_millis() < start);
+        let last = series.partition_point(|p| p.timestamp.timestamp_millis() < end);
+        WindowSlice {
+            baseline: first.checked_sub(1).map(|i| series[i]),
+            points: &series[first..last],
+        }
+    }
+
+    /// Baseline followed by the window's points.
+    fn with_baseline(&self) -> impl Iterator<Item = &'a StoredMetric> + '_ {
+        self.baseline.into_iter().chain(self.points.iter().copied())
+    }
+
+    fn is_histogram(&self) -> bool {
+        self.points.iter().all(|p| p.histogram.is_some())
+    }
+
+    /// Buckets observed during the window: the sum of delta points, or the
+    /// last cumulative point minus the baseline.
+    fn histogram_delta(&self) -> Option<(f64, HistogramBuckets)> {
+        let last = self.points.last()?;
+        let last_buckets = last.histogram.as_ref()?;
+        if !last_buckets.cumulative {
+            let mut merged = last_buckets.clone();
+            merged.count = 0;
+            merged.counts.iter_mut().for_each(|c| *c = 0);
+            let mut sum = 0.0;
+            for point in self.points {
+                let buckets = point.histogram.as_ref()?;
+                if buckets.bounds != merged.bounds {
+                    continue;
+                }
+                merged.count += buckets.count;
+                for (total, c) in merged.counts.iter_mut().zip(&buckets.counts) {
+                    *total += c;
+                }
+                sum += point.value;
+            }
+            return Some((sum, merged));
+        }
+
+        let base = self
+            .baseline
+            .and_then(|b| b.histogram.as_ref().map(|h| (b.value, h)));
+        match base {
+            Some((base_sum, base_buckets))
+                if base_buckets.bounds == last_buckets.bounds
+                    && base_buckets.count <= last_buckets.count =>
+            {
+                let mut delta = last_buckets.clone();
+                delta.count -= base_buckets.count;
+                for (c, base_c) in delta.counts.iter_mut().zip(&base_buckets.counts) {
+                    *c = c.saturating_sub(*base_c);
+                }
+                Some((last.value - base_sum, delta))
+            }
+            // No baseline, or the histogram was reset: everything counts.
+            _ => Some((last.value, last_buckets.clone())),
+        }
+    }
+}
+
+/// Value used for `rate`: the observation count of histograms, the value
+/// of everything else.
+fn rate_value(point: &StoredMetric) -> f64 {
+    match &point.histogram {
+        Some(buckets) => buckets.count as f64,
+        None => point.value,
+    }
+}
+
+fn aggregate_window(slices: &[WindowSlice], agg: MetricAgg) -> Option<f64> {
+    let histograms = slices.iter().all(WindowSlice::is_histogram);
+    match agg {
+        MetricAgg::Rate => {
+            let rates: Vec<f64> = slices.iter().filter_map(slice_rate).collect();
+            (!rates.is_empty()).then(|| rates.iter().sum())
+        }
+        MetricAgg::Avg if histograms => {
+            let (sum, count) = slices
+                .iter()
+                .filter_map(WindowSlice::histogram_delta)
+                .fold((0.0, 0), |(sum, count), (s, b)| (sum + s, count + b.count));
+            (count > 0).then(|| sum / count as f64)
+        }
+        MetricAgg::Avg => {
+            let values: Vec<f64> = slices
+                .iter()
+                .flat_map(|s| s.points.iter().map(|p| p.value))
+                .collect();
+            (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+        }
+        _ => {
+            let q = agg.quantile()?;
+            if histograms {
+                let mut merged: Option<HistogramBuckets> = None;
+                for (_, delta) in slices.iter().filter_map(WindowSlice::histogram_delta) {
+                    match &mut merged {
+                        None => merged = Some(delta),
+                        Some(m) if m.bounds == delta.bounds => {
+                            for (total, c) in m.counts.iter_mut().zip(&delta.counts) {
+                                *total += c;
+                            }
+                        }
+                        Some(_) => {}
+                    }
+                }
+                let merged = merged?;
+                bucket_quantile(q, &merged.bounds, &merged.counts)
+            } else {
+                let mut values: Vec<f64> = slices
+                    .iter()
+                    .flat_map(|s| s.points.iter().map(|p| p.value))
+                    .collect();
+                values.sort_by(f64::total_cmp);
+                value_quantile(q, &values)
+            }
+        }
+    }
+}
+
+/// Per-second increase of one attribute set over a window, from its
+/// baseline to its last point. Gauges use the plain difference; counters
+/// and histograms treat a drop as a reset.
+fn slice_rate(slice: &WindowSlice) -> Option<f64> {
+    let points: Vec<&StoredMetric> = slice.with_baseline().collect();
+    let (first, last) = (points.first()?, points.last()?);
+    let secs = (last.timestamp - first.timestamp).num_milliseconds() as f64 / 1000.0;
+    if secs <= 0.0 {
+        return None;
+    }
+    let increase = if last.metric_type == MetricType::Gauge {
+        last.value - first.value
+    } else {
+        points
+            .windows(2)
+            .map(|pair| {
+                let (prev, next) = (rate_value(pair[0]), rate_value(pair[1]));
+                if next >= prev {
+                    next - prev
+                } else {
+                    next
+                }
+            })
+            .sum()
+    };
+    Some(increase / secs)
+}
+
+/// Quantile `q` of explicit-bucket counts, interpolating linearly within
+/// the bucket it falls in. The lowest bucket starts at 0 (or at its upper
+/// bound, if that is not positive); a quantile in the unbounded top bucket
+/// is reported as the highest bound.
+pub fn bucket_quantile(q: f64, bounds: &[f64], counts: &[u64]) -> Option<f64> {
+    let total: u64 = counts.iter().sum();
+    if total == 0 {
+        return None;
+    }
+    let rank = q * total as f64;
+    let mut below = 0u64;
+    for (i, &count) in counts.iter().enumerate() {
+        if count > 0 && (below + count) as f64 >= rank {
+            let Some(&upper) = bounds.get(i) else {
+                return bounds.last().copied();
+            };
+            let lower = match i {
+                0 if upper <= 0.0 => return Some(upper),
+                0 => 0.0,
+                _ => bounds[i - 1],
+            };
+            return Some(lower + (upper - lower) * (rank - below as f64) / count as f64);
+        }
+        below += count;
+    }
+    bounds.last().copied()
+}
+
+/// Quantile `q` of sorted values, interpolating between closest ranks.
+pub fn value_quantile(q: f64, sorted: &[f64]) -> Option<f64> {
+    let last = sorted.len().checked_sub(1)?;
+    let pos = q * last as f64;
+    let (lo, hi) = (pos.floor() as usize, pos.ceil() as usize);
+    Some(sorted[lo] + (sorted[hi] - sorted[lo]) * (pos - lo as f64))
+}
+
 // -----------------------------------------------------------------------
 // Query result types
 // -----------------------------------------------------------------------
@@ -89,6 +451,9 @@ pub struct TraceSummary {
 pub struct TraceDetail {
     pub trace_id: String,
     pub spans: Vec<StoredSpan>,
+    /// Logs recorded with this trace id, oldest first.
+    #[serde(default)]
+    pub logs: Vec<StoredLog>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -104,6 +469,24 @@ pub struct SystemStatus {
     pub metric_count: usize,
     pub services: Vec<String>,
     pub trace_count: usize,
+    /// Non-root spans whose parent span never arrived.
+    #[serde(default)]
+    pub orphan_span_count: usize,
+    /// Orphan counts grouped by the inferred caller/callee services.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub orphan_pairs: Vec<OrphanPair>,
+}
+
+/// A caller -> callee service pair with orphaned spans on the callee side,
+/// which usually means `traceparent` is not propagated between them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrphanPair {
+    /// Service that most likely made the call, if another span in the same
+    /// trace encloses the orphan.
+    pub upstream: Option<String>,
+    /// Service that reported the orphaned span.
+    pub downstream: String,
+    pub count: usize,
 }
 
 // -----------------------------------------------------------------------
@@ -136,10 +519,11 @@ impl TelemetryStore {
                 let has_error = spans.iter().any(|s| s.status == SpanStatus::Error);
 
                 // Find root span (no parent) or earliest span
-                let root = spans
-                    .iter()
-                    .find(|s| s.parent_span_id.is_none())
-                    .or_else(|| spans.iter().min_by_key(|s| s.start_time));
+                let true_root = spans.iter().find(|s| is_root_span(s));
+                if query.root_only && true_root.is_none() {
+                    return None;
+                }
+                let root = true_root.or_else(|| spans.iter().min_by_key(|s| s.start_time));
 
                 let root_operation = root.map(|s| s.operation_name.clone()).unwrap_or_default();
                 let start_time = root.map(|s| s.start_time).unwrap_or_else(Utc::now);
@@ -175,7 +559,12 @@ impl TelemetryStore {
 
                 // Apply filters
                 if let Some(ref svc) = query.service {
-                    if !summary.services.contains(svc) {
+                    let matches = if query.root_only {
+                        root.is_some_and(|r| &r.service_name == svc)
+                    } else {
+                        summary.services.contains(svc)
+                    };
+                    if !matches {
                         return None;
                     }
                 }
@@ -204,10 +593,8 @@ impl TelemetryStore {
                     }
                 }
 
-                if let Some(since) = query.since {
-                    if summary.start_time < since {
-                        return None;
-                    }
+                if !in_window(summary.start_time, query.since, query.until) {
+                    return None;
                 }
 
                 Some(summary)
@@ -237,9 +624,26 @@ impl TelemetryStore {
         Some(TraceDetail {
             trace_id: trace_id.to_string(),
             spans,
+            logs: self.logs_for_trace(trace_id),
         })
     }
 
+    /// Logs correlated with a trace by id, oldest first. Logs without a
+    /// trace id (most process output) are never included.
+    pub fn logs_for_trace(&self, trace_id: &str) -> Vec<StoredLog> {
+        let Some(record_ids) = self.trace_log_index().get(trace_id) else {
+            return Vec::new();
+        };
+        let mut logs: Vec<StoredLog> = self
+            .logs()
+            .iter()
+            .filter(|l| record_ids.contains(&l.record_id))
+            .cloned()
+            .collect();
+        logs.sort_by_key(|l| l.timestamp);
+        logs
+    }
+
     /// Query logs with optional filters.
     pub fn query_logs(&self, query: &LogQuery) -> Vec<StoredLog> {
         let limit = query.limit.unwrap_or(200);
@@ -271,10 +675,8 @@ impl TelemetryStore {
                         _ => return false,
                     }
                 }
-                if let Some(since) = query.since {
-                    if log.timestamp < since {
-                        return false;
-                    }
+                if !in_window(log.timestamp, query.since, query.until) {
+                    return false;
                 }
                 if let Some(ref src) = query.source {
                     let log_source = log.attributes.iter()
@@ -319,10 +721,8 @@ impl TelemetryStore {
                         return false;
                     }
                 }
-                if let Some(since) = query.since {
-                    if m.timestamp < since {
-                        return false;
-                    }
+                if !in_window(m.timestamp, query.since, query.until) {
+                    return false;
                 }
                 true
             })
@@ -335,18 +735,85 @@ impl TelemetryStore {
 
     /// Get system status summary.
     pub fn get_status(&self) -> SystemStatus {
+        let orphans = self.orphan_spans(Utc::now());
         SystemStatus {
             span_count: self.get_span_count(),
             log_count: self.get_log_count(),
             metric_count: self.get_metric_count(),
             services: self.service_names(),
             trace_count: self.trace_index().len(),
+            orphan_span_count: orphans.iter().map(|p| p.count).sum(),
+            orphan_pairs: orphans,
         }
     }
 
+    /// Find non-root spans whose parent is missing from the store, grouped by
+    /// inferred (upstream, downstream) service pair, most frequent first.
+    ///
+    /// The upstream is the narrowest span from another service in the same
+    /// trace whose time window contains the orphan's start, preferring client
+    /// spans. Orphans that ended within the grace window are skipped.
+    pub fn orphan_spans(&self, now: DateTime<Utc>) -> Vec<OrphanPair> {
+        let mut by_trace: HashMap<&str, Vec<&StoredSpan>> = HashMap::new();
+        for span in self.spans() {
+            by_trace.entry(span.trace_id.as_str()).or_default().push(span);
+        }
+        let cutoff = now - chrono::Duration::seconds(ORPHAN_GRACE_SECS);
+
+        let mut pairs: HashMap<(Option<String>, String), usize> = HashMap::new();
+        for span in self.spans() {
+            let Some(parent) = span.parent_span_id.as_deref().filter(|p| !p.is_empty()) else {
+                continue;
+            };
+            let trace_spans = &by_trace[span.trace_id.as_str()];
+            if span.end_time > cutoff || trace_spans.iter().any(|s| s.span_id == parent) {
+                continue;
+            }
+
+            let upstream = trace_spans
+                .iter()
+                .filter(|s| {
+                    s.service_name != span.service_name
+                        && s.start_time <= span.start_time
+                        && s.end_time >= span.start_time
+                })
+                .min_by_key(|s| (s.kind != SpanKind::Client, s.end_time - s.start_time))
+                .map(|s| s.service_name.clone());
+
+            *pairs
+                .entry((upstream, span.service_name.clone()))
+                .or_default() += 1;
+        }
+
+        let mut result: Vec<OrphanPair> = pairs
+            .into_iter()
+            .map(|((upstream, downstream), count)| OrphanPair {
+                upstream,
+                downstream,
+                count,
+            })
+            .collect();
+        result.sort_by(|a, b| {
+            b.count
+                .cmp(&a.count)
+                .then_with(|| a.downstream.cmp(&b.downstream))
+                .then_with(|| a.upstream.cmp(&b.upstream))
+        });
+        result
+    }
+
     /// Query metric time-series grouped by metric_name + service_name.
     pub fn query_metric_series(&self, query: &MetricSeriesQuery) -> MetricSeriesResponse {
         let since = query.since.unwrap_or_else(|| Utc::now() - chrono::Duration::minutes(5));
+        let window = query
+            .window()
+            .unwrap_or_else(|_| chrono::Duration::seconds(DEFAULT_AGG_WINDOW_SECS));
+        // Aggregation looks one window further back so the first window
+        // has a baseline.
+        let from = match query.agg {
+            Some(_) => since - window,
+            None => since,
+        };
 
         // Group metrics by (metric_name, service_name)
         let mut groups: HashMap<(String, String), Vec<&StoredMetric>> = HashMap::new();
@@ -360,7 +827,7 @@ impl TelemetryStore {
                     continue;
                 }
             }
-            if m.timestamp < since {
+            if m.timestamp < from {
                 continue;
             }
             groups
@@ -378,13 +845,20 @@ impl TelemetryStore {
                 let metric_type = metrics.first().map(|m| m.metric_type).unwrap_or(MetricType::Gauge);
                 let unit = metrics.first().and_then(|m| m.unit.clone());
 
-                let points: Vec<MetricSeriesPoint> = metrics
-                    .iter()
-                    .map(|m| MetricSeriesPoint {
-                        t: m.timestamp.timestamp_millis(),
-                        v: m.value,
-                    })
-                    .collect();
+                let points: Vec<MetricSeriesPoint> = match query.agg {
+                    Some(agg) => {
+                        let mut points = aggregate_points(&metrics, agg, window);
+                        points.retain(|p| p.t > since.timestamp_millis());
+                        points
+                    }
+                    None => metrics
+                        .iter()
+                        .map(|m| MetricSeriesPoint {
+                            t: m.timestamp.timestamp_millis(),
+                            v: m.value,
+                        })
+                        .collect(),
+                };
 
                 MetricSeries {
                     metric_name,
@@ -394,6 +868,7 @@ impl TelemetryStore {
                     points,
                 }
             })
+            .filter(|s| !s.points.is_empty())
             .collect();
 
         // Sort series by service_name for consistent ordering
@@ -530,6 +1005,7 @@ mod tests {
             value,
             attributes: vec![],
             unit: None,
+            histogram: None,
         }
     }
 
@@ -584,6 +1060,111 @@ mod tests {
         assert_eq!(results[0].trace_id, "t2");
     }
 
+    #[test]
+    fn query_time_range_is_inclusive() {
+        let base = Utc::now() - chrono::Duration::minutes(10);
+        let at = |mins: i64| base + chrono::Duration::minutes(mins);
+        let mut store = TelemetryStore::new(100, 100, 100, Duration::from_secs(3600));
+        for (i, mins) in [0, 2, 4, 6].iter().enumerate() {
+            let mut span = make_span(&format!("t{}", i), "api", "op", SpanStatus::Ok);
+            span.start_time = at(*mins);
+            span.end_time = at(*mins) + chrono::Duration::milliseconds(5);
+            store.insert_span(span);
+
+            let mut log = make_log_with_trace("api", LogSeverity::Error, None);
+            log.timestamp = at(*mins);
+            store.insert_log(log);
+
+            let mut metric = make_metric("api", "requests", *mins as f64);
+            metric.timestamp = at(*mins);
+            store.insert_metric(metric);
+        }
+        let mut noisy = make_log_with_trace("web", LogSeverity::Error, None);
+        noisy.timestamp = at(2);
+        store.insert_log(noisy);
+
+        let traces = store.query_traces(&TraceQuery {
+            since: Some(at(2)),
+            until: Some(at(4)),
+            ..Default::default()
+        });
+        let mut ids: Vec<&str> = traces.iter().map(|t| t.trace_id.as_str()).collect();
+        ids.sort();
+        assert_eq!(ids, vec!["t1", "t2"]);
+
+        let logs = store.query_logs(&LogQuery {
+            service: Some("api".to_string()),
+            severity: Some("error".to_string()),
+            since: Some(at(2)),
+            until: Some(at(4)),
+            ..Default::default()
+        });
+        assert_eq!(logs.len(), 2);
+        assert!(logs.iter().all(|l| l.service_name == "api"));
+
+        let metrics = store.query_metrics(&MetricQuery {
+            until: Some(at(2)),
+            ..Default::default()
+        });
+        let mut values: Vec<f64> = metrics.iter().map(|m| m.value).collect();
+        values.sort_by(f64::total_cmp);
+        assert_eq!(values, vec![0.0, 2.0]);
+    }
+
+    #[test]
+    fn last_window_includes_its_start() {
+        let now = Utc::now();
+        let at = |secs_ago: i64| now - chrono::Duration::seconds(secs_ago);
+        let mut store = TelemetryStore::new(100, 100, 100, Duration::from_secs(3600));
+        for secs_ago in [901, 900, 899, 0] {
+            let mut log = make_log_with_trace("api", LogSeverity::Info, None);
+            log.timestamp = at(secs_ago);
+            store.insert_log(log);
+        }
+
+        let (since, until) = time_window(Some("15m"), None, None, now).unwrap();
+        assert_eq!(since, Some(at(900)));
+        assert_eq!(until, None);
+        let logs = store.query_logs(&LogQuery {
+            since,
+            until,
+            ..Default::default()
+        });
+        let mut stamps: Vec<_> = logs.iter().map(|l| l.timestamp).collect();
+        stamps.sort();
+        assert_eq!(stamps, vec![at(900), at(899), at(0)]);
+    }
+
+    #[test]
+    fn time_window_validates_its_inputs() {
+        let now = Utc::now();
+        let earlier = now - chrono::Duration::minutes(5);
+        assert_eq!(
+            time_window(None, Some(earlier), Some(now), now),
+            Ok((Some(earlier), Some(now)))
+        );
+        assert!(time_window(None, Some(now), Some(earlier), now)
+            .unwrap_err()
+            .contains("is after end"));
+        assert!(time_window(Some("5m"), Some(earlier), None, now)
+            .unwrap_err()
+            .contains("can't be combined"));
+        assert!(time_window(Some("soon"), None, None, now)
+            .unwrap_err()
+            .contains("invalid last 'soon'"));
+    }
+
+    #[test]
+    fn check_time_range_rejects_inverted() {
+        let now = Utc::now();
+        let earlier = now - chrono::Duration::minutes(5);
+        assert!(check_time_range(Some(earlier), Some(now)).is_ok());
+        assert!(check_time_range(Some(now), Some(now)).is_ok());
+        assert!(check_time_range(Some(now), None).is_ok());
+        let err = check_time_range(Some(now), Some(earlier)).unwrap_err();
+        assert!(err.contains("is after end"));
+    }
+
     #[test]
     fn query_logs_by_severity() {
         let mut store = TelemetryStore::new(100, 100, 100, Duration::from_secs(3600));
@@ -615,6 +1196,31 @@ mod tests {
         assert_eq!(results.len(), 1);
     }
 
+    #[test]
+    fn trace_detail_includes_correlated_logs() {
+        let mut store = TelemetryStore::new(100, 100, 100, Duration::from_secs(3600));
+        store.insert_span(make_span("trace-abc", "api", "GET /users", SpanStatus::Ok));
+        let mut later = make_log_with_trace("web", LogSeverity::Warn, Some("trace-abc"));
+        later.timestamp += chrono::Duration::seconds(1);
+        store.insert_log(later);
+        for trace_id in [Some("trace-abc"), Some("trace-xyz"), None] {
+            store.insert_log(make_log_with_trace("api", LogSeverity::Info, trace_id));
+        }
+
+        let detail = store.get_trace("trace-abc").unwrap();
+        let logs: Vec<(&str, Option<&str>)> = detail
+            .logs
+            .iter()
+            .map(|l| (l.service_name.as_str(), l.trace_id.as_deref()))
+            .collect();
+        assert_eq!(
+            logs,
+            vec![("api", Some("trace-abc")), ("web", Some("trace-abc"))]
+        );
+
+        assert!(store.logs_for_trace("missing").is_empty());
+    }
+
     #[test]
     fn query_logs_by_search_text() {
         let mut store = TelemetryStore::new(100, 100, 100, Duration::from_secs(3600));
@@ -713,4 +1319,313 @@ mod tests {
         assert_eq!(related.logs.len(), 1);
         assert_eq!(related.metrics.len(), 1);
     }
+
+    /// Build a span that started `start_secs_ago` seconds ago and lasted `dur_secs`.
+    fn timed_span(
+        trace_id: &str,
+        span_id: &str,
+        parent: Option<&str>,
+        service: &str,
+        kind: SpanKind,
+        start_secs_ago: i64,
+        dur_secs: i64,
+    ) -> StoredSpan {
+        let start = Utc::now() - chrono::Duration::seconds(start_secs_ago);
+        let mut span = make_span(trace_id, service, span_id, SpanStatus::Ok);
+        span.span_id = span_id.to_string();
+        span.parent_span_id = parent.map(String::from);
+        span.kind = kind;
+        span.start_time = start;
+        span.end_time = start + chrono::Duration::seconds(dur_secs);
+        span
+    }
+
+    #[test]
+    fn orphan_spans_counted_and_paired_with_caller() {
+        let mut store = TelemetryStore::new(100, 100, 100, Duration::from_secs(3600));
+        // Healthy trace: web -> api with intact parent links
+        store.insert_span(timed_span("t1", "a", None, "web", SpanKind::Server, 120, 5));
+        store.insert_span(timed_span("t1", "b", Some("a"), "api", SpanKind::Server, 119, 2));
+
+        // api calls worker, but worker's parent (the api client span) was never exported
+        for trace in ["t2", "t3"] {
+            store.insert_span(timed_span(trace, "root", None, "api", SpanKind::Server, 100, 10));
+            store.insert_span(timed_span(trace, "call", None, "web", SpanKind::Client, 100, 10));
+            store.insert_span(timed_span(trace, "outbound", Some("root"), "api", SpanKind::Client, 99, 5));
+            store.insert_span(timed_span(trace, "w", Some("missing"), "worker", SpanKind::Server, 98, 1));
+        }
+
+        // Orphan alone in its trace: caller unknown
+        store.insert_span(timed_span("t4", "x", Some("gone"), "billing", SpanKind::Server, 90, 1));
+
+        // Recent orphan is still within the grace window
+        store.insert_span(timed_span("t5", "y", Some("later"), "billing", SpanKind::Server, 1, 0));
+
+        let status = store.get_status();
+        assert_eq!(status.orphan_span_count, 3);
+        assert_eq!(
+            status.orphan_pairs,
+            vec![
+                OrphanPair {
+                    upstream: Some("api".to_string()),
+                    downstream: "worker".to_string(),
+                    count: 2,
+                },
+                OrphanPair {
+                    upstream: None,
+                    downstream: "billing".to_string(),
+                    count: 1,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn no_orphans_when_parents_present() {
+        let mut store = TelemetryStore::new(100, 100, 100, Duration::from_secs(3600));
+        store.insert_span(timed_span("t1", "a", None, "web", SpanKind::Server, 60, 5));
+        store.insert_span(timed_span("t1", "b", Some("a"), "api", SpanKind::Server, 59, 1));
+        store.insert_span(timed_span("t1", "c", Some(""), "api", SpanKind::Internal, 59, 1));
+
+        let status = store.get_status();
+        assert_eq!(status.orphan_span_count, 0);
+        assert!(status.orphan_pairs.is_empty());
+        let json = serde_json::to_value(&status).unwrap();
+        assert!(json.get("orphan_pairs").is_none());
+    }
+
+    #[test]
+    fn root_only_excludes_child_only_traces_and_matches_root() {
+        let mut store = TelemetryStore::new(100, 100, 100, Duration::from_secs(3600));
+        // web-originated request that calls api
+        store.insert_span(timed_span("t1", "a", None, "web", SpanKind::Server, 60, 5));
+        store.insert_span(timed_span("t1", "b", Some("a"), "api", SpanKind::Server, 59, 1));
+        // api-originated request (root sent with an empty parent id)
+        store.insert_span(timed_span("t2", "c", Some(""), "api", SpanKind::Server, 50, 2));
+        // child-only trace: parent never arrived
+        store.insert_span(timed_span("t3", "d", Some("gone"), "api", SpanKind::Server, 40, 1));
+
+        let all = store.query_traces(&TraceQuery::default());
+        assert_eq!(all.len(), 3);
+
+        let roots = store.query_traces(&TraceQuery {
+            root_only: true,
+            ..Default::default()
+        });
+        let mut ids: Vec<&str> = roots.iter().map(|t| t.trace_id.as_str()).collect();
+        ids.sort();
+        assert_eq!(ids, vec!["t1", "t2"]);
+        let t1 = roots.iter().find(|t| t.trace_id == "t1").unwrap();
+        assert_eq!(t1.root_operation, "a");
+        assert_eq!(t1.span_count, 2);
+
+        // Without root_only, api matches every trace it appears in
+        let api_any = store.query_traces(&TraceQuery {
+            service: Some("api".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(api_any.len(), 3);
+
+        // With root_only, only requests that api originated
+        let api_roots = store.query_traces(&TraceQuery {
+            service: Some("api".to_string()),
+            root_only: true,
+            ..Default::default()
+        });
+        assert_eq!(api_roots.len(), 1);
+        assert_eq!(api_roots[0].trace_id, "t2");
+    }
+
+    /// A point `secs` after a time aligned to every window used below.
+    fn point_at(secs: i64, metric_type: MetricType, value: f64, route: &str) -> StoredMetric {
+        StoredMetric {
+            record_id: 0,
+            timestamp: DateTime::from_timestamp(1_700_000_010 + secs, 0).unwrap(),
+            service_name: "api".to_string(),
+            metric_name: "m".to_string(),
+            metric_type,
+            value,
+            attributes: vec![("route".to_string(), route.to_string())],
+            unit: None,
+            histogram: None,
+        }
+    }
+
+    fn histogram_at(secs: i64, sum: f64, counts: &[u64], cumulative: bool) -> StoredMetric {
+        StoredMetric {
+            histogram: Some(HistogramBuckets {
+                count: counts.iter().sum(),
+                bounds: vec![10.0, 50.0, 100.0],
+                counts: counts.to_vec(),
+                cumulative,
+            }),
+            ..point_at(secs, MetricType::Histogram, sum, "/")
+        }
+    }
+
+    fn values(points: &[MetricSeriesPoint]) -> Vec<(i64, f64)> {
+        let base = 1_700_000_010_000;
+        points.iter().map(|p| (p.t - base, p.v)).collect()
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn bucket_quantile_interpolates_within_buckets() {
+        let bounds = [10.0, 50.0, 100.0];
+        assert_eq!(bucket_quantile(0.5, &bounds, &[5, 3, 2, 0]), Some(10.0));
+        assert_eq!(bucket_quantile(0.95, &bounds, &[5, 3, 2, 0]), Some(87.5));
+        // The unbounded top bucket reports the highest bound.
+        assert_eq!(bucket_quantile(0.5, &bounds, &[0, 0, 0, 4]), Some(100.0));
+        assert_eq!(bucket_quantile(0.5, &bounds, &[0, 0, 0, 0]), None);
+        assert_eq!(bucket_quantile(0.5, &[-1.0, 5.0], &[2, 0, 0]), Some(-1.0));
+    }
+
+    #[test]
+    fn value_quantile_interpolates_between_ranks() {
+        assert_eq!(value_quantile(0.5, &[1.0, 2.0, 3.0, 4.0]), Some(2.5));
+        assert_eq!(value_quantile(0.99, &[7.0]), Some(7.0));
+        assert_eq!(value_quantile(0.5, &[]), None);
+    }
+
+    #[test]
+    fn rate_handles_resets_and_sums_attribute_sets() {
+        let points = [
+            point_at(0, MetricType::Counter, 0.0, "/a"),
+            point_at(2, MetricType::Counter, 100.0, "/b"),
+            point_at(5, MetricType::Counter, 10.0, "/a"),
+            point_at(8, MetricType::Counter, 130.0, "/b"),
+            point_at(10, MetricType::Counter, 20.0, "/a"),
+            point_at(15, MetricType::Counter, 5.0, "/a"),
+            point_at(19, MetricType::Counter, 9.0, "/a"),
+        ];
+        let refs: Vec<&StoredMetric> = points.iter().collect();
+        let out = values(&aggregate_points(
+            &refs,
+            MetricAgg::Rate,
+            chrono::Duration::seconds(10),
+        ));
+        assert_eq!(out.len(), 2);
+        // /a: 10 over 5s; /b: 30 over 6s.
+        assert_eq!(out[0].0, 10_000);
+        assert_close(out[0].1, 2.0 + 5.0);
+        // /a from its baseline at 5s: +10, reset to 5, +4 over 14s.
+        assert_eq!(out[1].0, 20_000);
+        assert_close(out[1].1, 19.0 / 14.0);
+    }
+
+    #[test]
+    fn histogram_percentiles_use_bucket_deltas() {
+        let points = [
+            histogram_at(1, 50.0, &[10, 0, 0, 0], true),
+            histogram_at(12, 500.0, &[10, 5, 5, 0], true),
+        ];
+        let refs: Vec<&StoredMetric> = points.iter().collect();
+        let window = chrono::Duration::seconds(10);
+
+        let p50 = values(&aggregate_points(&refs, MetricAgg::P50, window));
+        // The first point has no baseline, so all of its counts are used.
+        assert_eq!(p50, vec![(10_000, 5.0), (20_000, 50.0)]);
+
+        let p99 = values(&aggregate_points(&refs, MetricAgg::P99, window));
+        assert_close(p99[1].1, 50.0 + 50.0 * 4.9 / 5.0);
+
+        let avg = values(&aggregate_points(&refs, MetricAgg::Avg, window));
+        assert_eq!(avg, vec![(10_000, 5.0), (20_000, 45.0)]);
+
+        // Observations per second between the two points.
+        let rate = values(&aggregate_points(&refs, MetricAgg::Rate, window));
+        assert_eq!(rate.len(), 1);
+        assert_close(rate[0].1, 10.0 / 11.0);
+    }
+
+    #[test]
+    fn delta_histograms_are_summed_within_a_window() {
+        let points = [
+            histogram_at(1, 30.0, &[3, 0, 0, 0], false),
+            histogram_at(4, 300.0, &[0, 3, 3, 0], false),
+        ];
+        let refs: Vec<&StoredMetric> = points.iter().collect();
+        let window = chrono::Duration::seconds(10);
+        let p50 = values(&aggregate_points(&refs, MetricAgg::P50, window));
+        assert_eq!(p50, vec![(10_000, 30.0)]);
+        let avg = values(&aggregate_points(&refs, MetricAgg::Avg, window));
+        assert_eq!(avg, vec![(10_000, 330.0 / 9.0)]);
+    }
+
+    #[test]
+    fn gauges_aggregate_raw_values() {
+        let points: Vec<StoredMetric> = [1.0, 2.0, 3.0, 10.0]
+            .iter()
+            .enumerate()
+            .map(|(i, v)| point_at(i as i64 * 2, MetricType::Gauge, *v, "/"))
+            .collect();
+        let refs: Vec<&StoredMetric> = points.iter().collect();
+        let window = chrono::Duration::seconds(30);
+        assert_eq!(
+            values(&aggregate_points(&refs, MetricAgg::Avg, window)),
+            vec![(30_000, 4.0)]
+        );
+        assert_eq!(
+            values(&aggregate_points(&refs, MetricAgg::P50, window)),
+            vec![(30_000, 2.5)]
+        );
+        assert_eq!(
+            values(&aggregate_points(&refs, MetricAgg::Rate, window)),
+            vec![(30_000, 1.5)]
+        );
+    }
+
+    #[test]
+    fn series_window_is_validated() {
+        let query = |agg: Option<MetricAgg>, window: Option<&str>| MetricSeriesQuery {
+            name: "m".to_string(),
+            agg,
+            window: window.map(str::to_string),
+            ..Default::default()
+        };
+        assert_eq!(
+            query(Some(MetricAgg::Rate), None).window(),
+            Ok(chrono::Duration::seconds(30))
+        );
+        assert_eq!(
+            query(Some(MetricAgg::P95), Some("1m")).window(),
+            Ok(chrono::Duration::seconds(60))
+        );
+        assert!(query(Some(MetricAgg::P95), Some("0s")).window().is_err());
+        assert!(query(Some(MetricAgg::P95), Some("soon")).window().is_err());
+        assert!(query(None, Some("30s")).window().is_err());
+    }
+
+    #[test]
+    fn metric_series_aggregates_from_since() {
+        let mut store = TelemetryStore::new(100, 100, 100, Duration::from_secs(3600));
+        let base = Utc::now() - chrono::Duration::minutes(2);
+        for (i, v) in [0.0, 10.0, 20.0, 30.0].iter().enumerate() {
+            let mut m = make_metric("api", "requests", *v);
+            m.metric_type = MetricType::Counter;
+            m.timestamp = base + chrono::Duration::seconds(i as i64 * 20);
+            store.insert_metric(m);
+        }
+
+        let since = base + chrono::Duration::seconds(30);
+        let response = store.query_metric_series(&MetricSeriesQuery {
+            name: "requests".to_string(),
+            since: Some(since),
+            agg: Some(MetricAgg::Rate),
+            window: Some("1m".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(response.series.len(), 1);
+        let points = &response.series[0].points;
+        assert!(!points.is_empty());
+        assert!(points.iter().all(|p| p.t > since.timestamp_millis()));
+        // Every pair of points is 10 apart over 20s.
+        assert!(
+            points.iter().all(|p| (p.v - 0.5).abs() < 1e-9),
+            "{points:?}"
+        );
+    }
 }
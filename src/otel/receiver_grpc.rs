@@ -22,6 +22,9 @@ use opentelemetry_proto::tonic::collector::trace::v1::{
     ExportTraceServiceRequest, ExportTraceServiceResponse,
 };
 
+use crate::tls::{TlsIdentity, TlsListener, ALPN_H2};
+
+use super::forward::Forwarder;
 use super::storage::TelemetryStore;
 use super::types::{
     extract_service_name, proto_log_to_stored, proto_metrics_to_stored, proto_span_to_stored,
@@ -32,6 +35,7 @@ use super::types::{
 struct OtlpGrpcReceiver {
     store: Arc<RwLock<TelemetryStore>>,
     events_tx: broadcast::Sender<TelemetryEvent>,
+    forwarder: Option<Forwarder>,
 }
 
 #[tonic::async_trait]
@@ -73,6 +77,10 @@ impl TraceService for OtlpGrpcReceiver {
             let _ = self.events_tx.send(event);
         }
 
+        if let Some(forwarder) = &self.forwarder {
+            forwarder.traces(req.resource_spans);
+        }
+
         Ok(tonic::Response::new(ExportTraceServiceResponse {
             partial_success: None,
         }))
@@ -117,6 +125,10 @@ impl MetricsService for OtlpGrpcReceiver {
             let _ = self.events_tx.send(event);
         }
 
+        if let Some(forwarder) = &self.forwarder {
+            forwarder.metrics(req.resource_metrics);
+        }
+
         Ok(tonic::Response::new(ExportMetricsServiceResponse {
             partial_success: None,
         }))
@@ -161,6 +173,10 @@ impl LogsService for OtlpGrpcReceiver {
             let _ = self.events_tx.send(event);
         }
 
+        if let Some(forwarder) = &self.forwarder {
+            forwarder.logs(req.resource_logs);
+        }
+
         Ok(tonic::Response::new(ExportLogsServiceResponse {
             partial_success: None,
         }))
@@ -171,18 +187,37 @@ pub async fn start_grpc_server(
     port: u16,
     store: Arc<RwLock<TelemetryStore>>,
     events_tx: broadcast::Sender<TelemetryEvent>,
+    forwarder: Option<Forwarder>,
+    tls: Option<TlsIdentity>,
     cancel: CancellationToken,
 ) -> anyhow::Result<()> {
-    let receiver = OtlpGrpcReceiver { store, events_tx };
+    let receiver = OtlpGrpcReceiver {
+        store,
+        events_tx,
+        forwarder,
+    };
 
-    let addr = format!("0.0.0.0:{}", port).parse()?;
+    let addr: std::net::SocketAddr = format!("0.0.0.0:{}", port).parse()?;
 
-    tonic::transport::Server::builder()
+    let router = tonic::transport::Server::builder()
         .add_service(TraceServiceServer::new(receiver.clone()))
         .add_service(MetricsServiceServer::new(receiver.clone()))
-        .add_service(LogsServiceServer::new(receiver))
-        .serve_with_shutdown(addr, cancel.cancelled_owned())
-        .await?;
+        .add_service(LogsServiceServer::new(receiver));
+
+    match tls {
+        Some(identity) => {
+            let listener = tokio::net::TcpListener::bind(addr).await?;
+            let incoming = TlsListener::new(listener, identity.acceptor(ALPN_H2)?)?.into_incoming();
+            router
+                .serve_with_incoming_shutdown(incoming, cancel.cancelled_owned())
+                .await?;
+        }
+        None => {
+            router
+                .serve_with_shutdown(addr, cancel.cancelled_owned())
+                .await?;
+        }
+    }
 
     Ok(())
 }
@@ -20,6 +20,9 @@ use opentelemetry_proto::tonic::collector::trace::v1::{
     ExportTraceServiceRequest, ExportTraceServiceResponse,
 };
 
+use crate::tls::{TlsIdentity, TlsListener, ALPN_HTTP1};
+
+use super::forward::Forwarder;
 use super::storage::TelemetryStore;
 use super::types::{
     extract_service_name, proto_log_to_stored, proto_metrics_to_stored, proto_span_to_stored,
@@ -30,6 +33,7 @@ use super::types::{
 struct HttpOtlpState {
     store: Arc<RwLock<TelemetryStore>>,
     events_tx: broadcast::Sender<TelemetryEvent>,
+    forwarder: Option<Forwarder>,
 }
 
 async fn post_traces(
@@ -73,6 +77,10 @@ async fn post_traces(
         let _ = state.events_tx.send(event);
     }
 
+    if let Some(forwarder) = &state.forwarder {
+        forwarder.traces(req.resource_spans);
+    }
+
     let resp = ExportTraceServiceResponse {
         partial_success: None,
     };
@@ -120,6 +128,10 @@ async fn post_metrics(
         let _ = state.events_tx.send(event);
     }
 
+    if let Some(forwarder) = &state.forwarder {
+        forwarder.metrics(req.resource_metrics);
+    }
+
     let resp = ExportMetricsServiceResponse {
         partial_success: None,
     };
@@ -167,6 +179,10 @@ async fn post_logs(
         let _ = state.events_tx.send(event);
     }
 
+    if let Some(forwarder) = &state.forwarder {
+        forwarder.logs(req.resource_logs);
+    }
+
     let resp = ExportLogsServiceResponse {
         partial_success: None,
     };
@@ -227,8 +243,13 @@ fn encode_response<T: Message>(msg: &T) -> axum::response::Response {
 pub fn otlp_http_router(
     store: Arc<RwLock<TelemetryStore>>,
     events_tx: broadcast::Sender<TelemetryEvent>,
+    forwarder: Option<Forwarder>,
 ) -> Router {
-    let state = HttpOtlpState { store, events_tx };
+    let state = HttpOtlpState {
+        store,
+        events_tx,
+        forwarder,
+    };
 
     Router::new()
         .route("/v1/traces", post(post_traces))
@@ -242,15 +263,27 @@ pub async fn start_http_otlp_server(
     port: u16,
     store: Arc<RwLock<TelemetryStore>>,
     events_tx: broadcast::Sender<TelemetryEvent>,
+    forwarder: Option<Forwarder>,
+    tls: Option<TlsIdentity>,
     cancel: CancellationToken,
 ) -> anyhow::Result<()> {
-    let app = otlp_http_router(store, events_tx);
+    let app = otlp_http_router(store, events_tx, forwarder);
     let addr = format!("0.0.0.0:{}", port);
     let listener = tokio::net::TcpListener::bind(&addr).await?;
 
-    axum::serve(listener, app)
-        .with_graceful_shutdown(cancel.cancelled_owned())
-        .await?;
+    match tls {
+        Some(identity) => {
+            let listener = TlsListener::new(listener, identity.acceptor(ALPN_HTTP1)?)?;
+            axum::serve(listener, app)
+                .with_graceful_shutdown(cancel.cancelled_owned())
+                .await?;
+        }
+        None => {
+            axum::serve(listener, app)
+                .with_graceful_shutdown(cancel.cancelled_owned())
+                .await?;
+        }
+    }
 
     Ok(())
 }
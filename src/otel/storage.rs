@@ -2,8 +2,10 @@ use std::collections::{HashMap, HashSet, VecDeque};
 use std::time::Duration;
 
 use chrono::Utc;
+use tracing::warn;
 
 use super::types::{SpanStatus, StoredLog, StoredMetric, StoredSpan};
+use super::wal::{TelemetryWal, WalEntry};
 
 /// In-memory ring buffer storage for telemetry data with secondary indexes.
 pub struct TelemetryStore {
@@ -20,6 +22,7 @@ pub struct TelemetryStore {
 
     // Secondary indexes for logs
     service_log_index: HashMap<String, Vec<u64>>,
+    trace_log_index: HashMap<String, Vec<u64>>,
 
     // Secondary indexes for metrics
     service_metric_index: HashMap<String, Vec<u64>>,
@@ -29,6 +32,9 @@ pub struct TelemetryStore {
     max_logs: usize,
     max_metrics: usize,
     retention: Duration,
+
+    // Every inserted record is also appended here when set
+    wal: Option<TelemetryWal>,
 }
 
 impl TelemetryStore {
@@ -42,11 +48,38 @@ impl TelemetryStore {
             service_span_index: HashMap::new(),
             error_spans: HashSet::new(),
             service_log_index: HashMap::new(),
+            trace_log_index: HashMap::new(),
             service_metric_index: HashMap::new(),
             max_spans,
             max_logs,
             max_metrics,
             retention,
+            wal: None,
+        }
+    }
+
+    /// Append every record inserted from now on to `wal`. Attach after
+    /// replaying, or the replayed records are written again.
+    pub fn attach_wal(&mut self, wal: TelemetryWal) {
+        self.wal = Some(wal);
+    }
+
+    /// Flush buffered WAL writes to disk.
+    pub fn flush_wal(&mut self) {
+        if let Some(wal) = &mut self.wal {
+            if let Err(e) = wal.flush() {
+                warn!(error = %e, "telemetry WAL flush failed; persistence disabled");
+                self.wal = None;
+            }
+        }
+    }
+
+    fn append_wal(&mut self, entry: WalEntry<'_>) {
+        if let Some(wal) = &mut self.wal {
+            if let Err(e) = wal.append(&entry) {
+                warn!(error = %e, "telemetry WAL write failed; persistence disabled");
+                self.wal = None;
+            }
         }
     }
 
@@ -84,6 +117,7 @@ impl TelemetryStore {
             self.error_spans.insert(record_id);
         }
 
+        self.append_wal(WalEntry::Span(&span));
         self.spans.push_back(span);
     }
 
@@ -121,7 +155,14 @@ impl TelemetryStore {
             .entry(log.service_name.clone())
             .or_default()
             .push(record_id);
+        if let Some(trace_id) = &log.trace_id {
+            self.trace_log_index
+                .entry(trace_id.clone())
+                .or_default()
+                .push(record_id);
+        }
 
+        self.append_wal(WalEntry::Log(&log));
         self.logs.push_back(log);
     }
 
@@ -132,6 +173,14 @@ impl TelemetryStore {
                 self.service_log_index.remove(&log.service_name);
             }
         }
+        if let Some(trace_id) = &log.trace_id {
+            if let Some(ids) = self.trace_log_index.get_mut(trace_id) {
+                ids.retain(|&id| id != log.record_id);
+                if ids.is_empty() {
+                    self.trace_log_index.remove(trace_id);
+                }
+            }
+        }
     }
 
     // -----------------------------------------------------------------------
@@ -153,6 +202,7 @@ impl TelemetryStore {
             .or_default()
             .push(record_id);
 
+        self.append_wal(WalEntry::Metric(&metric));
         self.metrics.push_back(metric);
     }
 
@@ -232,6 +282,10 @@ impl TelemetryStore {
         &self.trace_index
     }
 
+    pub fn trace_log_index(&self) -> &HashMap<String, Vec<u64>> {
+        &self.trace_log_index
+    }
+
     pub fn error_spans(&self) -> &HashSet<u64> {
         &self.error_spans
     }
@@ -301,6 +355,7 @@ mod tests {
             value,
             attributes: vec![],
             unit: None,
+            histogram: None,
         }
     }
 
@@ -396,6 +451,20 @@ mod tests {
         assert!(!store.service_log_index.contains_key("svc1"));
     }
 
+    #[test]
+    fn trace_log_index_tracks_eviction() {
+        let mut store = TelemetryStore::new(10, 2, 10, Duration::from_secs(3600));
+        let mut traced = make_log("svc1", LogSeverity::Info);
+        traced.trace_id = Some("t1".to_string());
+        store.insert_log(traced);
+        store.insert_log(make_log("svc1", LogSeverity::Info));
+        assert_eq!(store.trace_log_index()["t1"], vec![1]);
+        assert_eq!(store.trace_log_index.len(), 1);
+
+        store.insert_log(make_log("svc2", LogSeverity::Info));
+        assert!(!store.trace_log_index.contains_key("t1"));
+    }
+
     #[test]
     fn index_cleanup_complete_after_eviction() {
         let mut store = TelemetryStore::new(2, 2, 2, Duration::from_secs(3600));
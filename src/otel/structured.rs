@@ -0,0 +1,263 @@
+//! Structured (JSON / logfmt) process output, for services that set
+//! `log_format`. Pulls each line's level, message and timestamp out so the
+//! dashboard shows the severity the process logged rather than a guess.
+
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+use super::types::LogSeverity;
+use crate::config::model::LogFormat;
+
+/// Keys that carry the level, message and timestamp, in order of preference.
+const LEVEL_KEYS: &[&str] = &["level", "severity", "lvl"];
+const MESSAGE_KEYS: &[&str] = &["msg", "message"];
+const TIME_KEYS: &[&str] = &["time", "timestamp", "ts", "@timestamp"];
+
+/// What a structured line says about itself. Fields other than the level,
+/// message and timestamp are kept as attributes.
+#[derive(Debug, Default, PartialEq)]
+pub struct StructuredLine {
+    pub severity: Option<LogSeverity>,
+    pub message: Option<String>,
+    pub timestamp: Option<DateTime<Utc>>,
+    pub attributes: Vec<(String, String)>,
+}
+
+/// Parse `text` as `format`. `None` when the line is not in that format, so
+/// callers fall back to treating it as plain text.
+pub fn parse_line(text: &str, format: LogFormat) -> Option<StructuredLine> {
+    match format {
+        LogFormat::Json => parse_json(text),
+        LogFormat::Logfmt => parse_logfmt(text),
+        LogFormat::Auto => parse_json(text).or_else(|| parse_logfmt(text)),
+    }
+}
+
+fn parse_json(text: &str) -> Option<StructuredLine> {
+    let Ok(Value::Object(fields)) = serde_json::from_str::<Value>(text.trim()) else {
+        return None;
+    };
+    let mut line = StructuredLine::default();
+    for (key, value) in fields {
+        let key_str = key.as_str();
+        if LEVEL_KEYS.contains(&key_str) && line.severity.is_none() {
+            line.severity = match &value {
+                Value::String(s) => severity_from_name(s),
+                Value::Number(n) => n.as_i64().and_then(severity_from_number),
+                _ => None,
+            };
+            if line.severity.is_some() {
+                continue;
+            }
+        } else if MESSAGE_KEYS.contains(&key_str) && line.message.is_none() {
+            if let Value::String(s) = &value {
+                line.message = Some(s.clone());
+                continue;
+            }
+        } else if TIME_KEYS.contains(&key_str) && line.timestamp.is_none() {
+            line.timestamp = match &value {
+                Value::String(s) => parse_timestamp(s),
+                Value::Number(n) => n.as_f64().and_then(epoch_to_datetime),
+                _ => None,
+            };
+            if line.timestamp.is_some() {
+                continue;
+            }
+        }
+        let value = match value {
+            Value::String(s) => s,
+            other => other.to_string(),
+        };
+        line.attributes.push((key, value));
+    }
+    Some(line)
+}
+
+fn parse_logfmt(text: &str) -> Option<StructuredLine> {
+    let pairs = logfmt_pairs(text)?;
+    let mut line = StructuredLine::default();
+    for (key, value) in pairs {
+        let key_str = key.as_str();
+        if LEVEL_KEYS.contains(&key_str) && line.severity.is_none() {
+            line.severity = severity_from_name(&value);
+            if line.severity.is_some() {
+                continue;
+            }
+        } else if MESSAGE_KEYS.contains(&key_str) && line.message.is_none() {
+            line.message = Some(value);
+            continue;
+        } else if TIME_KEYS.contains(&key_str) && line.timestamp.is_none() {
+            line.timestamp = parse_timestamp(&value);
+            if line.timestamp.is_some() {
+                continue;
+            }
+        }
+        line.attributes.push((key, value));
+    }
+    Some(line)
+}
+
+/// Split a logfmt line into `key=value` pairs. Values may be double-quoted
+/// with `\"` and `\\` escapes; a bare key is a pair with an empty value.
+/// `None` unless the line has at least one `key=value`, so free-form text
+/// is not mistaken for logfmt.
+fn logfmt_pairs(text: &str) -> Option<Vec<(String, String)>> {
+    let mut pairs = Vec::new();
+    let mut has_value = false;
+    let mut chars = text.trim().chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let mut key = String::new();
+        while let Some(c) = chars.next_if(|c| *c != '=' && !c.is_whitespace()) {
+            key.push(c);
+        }
+        if key.is_empty() {
+            break;
+        }
+        let mut value = String::new();
+        if chars.next_if_eq(&'=').is_some() {
+            has_value = true;
+            if chars.next_if_eq(&'"').is_some() {
+                let mut closed = false;
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => value.extend(chars.next()),
+                        '"' => {
+                            closed = true;
+                            break;
+                        }
+                        c => value.push(c),
+                    }
+                }
+                if !closed {
+                    return None;
+                }
+            } else {
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                    value.push(c);
+                }
+            }
+        }
+        pairs.push((key, value));
+    }
+    has_value.then_some(pairs)
+}
+
+fn severity_from_name(name: &str) -> Option<LogSeverity> {
+    match name.to_ascii_lowercase().as_str() {
+        "trace" => Some(LogSeverity::Trace),
+        "debug" | "dbug" => Some(LogSeverity::Debug),
+        "info" | "information" | "notice" => Some(LogSeverity::Info),
+        "warn" | "warning" => Some(LogSeverity::Warn),
+        "error" | "err" | "eror" => Some(LogSeverity::Error),
+        "fatal" | "critical" | "crit" | "panic" | "dpanic" | "alert" | "emerg" => {
+            Some(LogSeverity::Fatal)
+        }
+        _ => None,
+    }
+}
+
+/// Numeric levels as written by pino and bunyan (10 = trace ... 60 = fatal).
+fn severity_from_number(n: i64) -> Option<LogSeverity> {
+    match n {
+        10 => Some(LogSeverity::Trace),
+        20 => Some(LogSeverity::Debug),
+        30 => Some(LogSeverity::Info),
+        40 => Some(LogSeverity::Warn),
+        50 => Some(LogSeverity::Error),
+        60 => Some(LogSeverity::Fatal),
+        _ => None,
+    }
+}
+
+fn parse_timestamp(s: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|t| t.with_timezone(&Utc))
+        .ok()
+        .or_else(|| s.parse::<f64>().ok().and_then(epoch_to_datetime))
+}
+
+/// Unix time in seconds (zap's `ts`) or, when too large for that, in
+/// milliseconds (pino's `time`).
+fn epoch_to_datetime(epoch: f64) -> Option<DateTime<Utc>> {
+    let millis = if epoch.abs() < 1e11 {
+        epoch * 1000.0
+    } else {
+        epoch
+    };
+    DateTime::from_timestamp_millis(millis as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_level_message_and_time() {
+        let line = parse_line(
+            r#"{"level":"warn","time":"2026-03-01T12:00:00Z","msg":"slow query","ms":812}"#,
+            LogFormat::Json,
+        )
+        .unwrap();
+        assert_eq!(line.severity, Some(LogSeverity::Warn));
+        assert_eq!(line.message.as_deref(), Some("slow query"));
+        assert_eq!(
+            line.timestamp.unwrap().to_rfc3339(),
+            "2026-03-01T12:00:00+00:00"
+        );
+        assert_eq!(line.attributes, vec![("ms".to_string(), "812".to_string())]);
+
+        // pino: numeric level, epoch millis
+        let line = parse_line(
+            r#"{"level":50,"time":1772366400000,"msg":"boom"}"#,
+            LogFormat::Json,
+        )
+        .unwrap();
+        assert_eq!(line.severity, Some(LogSeverity::Error));
+        assert_eq!(line.timestamp.unwrap().timestamp(), 1772366400);
+
+        assert!(parse_line("plain text error", LogFormat::Json).is_none());
+        assert!(parse_line("[1, 2]", LogFormat::Json).is_none());
+    }
+
+    #[test]
+    fn logfmt_pairs_and_quoting() {
+        let line = parse_line(
+            r#"ts=1772366400.5 level=error msg="db \"orders\" unreachable" retry=3 cached"#,
+            LogFormat::Logfmt,
+        )
+        .unwrap();
+        assert_eq!(line.severity, Some(LogSeverity::Error));
+        assert_eq!(line.message.as_deref(), Some(r#"db "orders" unreachable"#));
+        assert_eq!(line.timestamp.unwrap().timestamp_millis(), 1772366400500);
+        assert_eq!(
+            line.attributes,
+            vec![
+                ("retry".to_string(), "3".to_string()),
+                ("cached".to_string(), String::new()),
+            ]
+        );
+
+        assert!(parse_line("server listening on :3000", LogFormat::Logfmt).is_none());
+        assert!(parse_line(r#"msg="unterminated"#, LogFormat::Logfmt).is_none());
+    }
+
+    #[test]
+    fn auto_tries_json_then_logfmt() {
+        let json = parse_line(r#"{"severity":"ERROR"}"#, LogFormat::Auto).unwrap();
+        assert_eq!(json.severity, Some(LogSeverity::Error));
+        let logfmt = parse_line("level=debug msg=tick", LogFormat::Auto).unwrap();
+        assert_eq!(logfmt.severity, Some(LogSeverity::Debug));
+        assert!(parse_line("just words", LogFormat::Auto).is_none());
+    }
+
+    #[test]
+    fn unknown_level_is_kept_as_an_attribute() {
+        let line = parse_line("level=verbose msg=hi", LogFormat::Logfmt).unwrap();
+        assert_eq!(line.severity, None);
+        assert_eq!(
+            line.attributes,
+            vec![("level".to_string(), "verbose".to_string())]
+        );
+    }
+}
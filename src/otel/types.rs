@@ -1,4 +1,7 @@
+use std::sync::LazyLock;
+
 use chrono::{DateTime, Utc};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 
 // -----------------------------------------------------------------------
@@ -124,6 +127,22 @@ pub struct StoredMetric {
     pub value: f64,
     pub attributes: Vec<(String, String)>,
     pub unit: Option<String>,
+    /// Bucket data for histogram points; `value` holds their sum.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub histogram: Option<HistogramBuckets>,
+}
+
+/// Buckets of an OTLP explicit-bucket histogram point.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistogramBuckets {
+    /// Number of observations.
+    pub count: u64,
+    /// Upper bounds of every bucket but the last, which is unbounded.
+    pub bounds: Vec<f64>,
+    pub counts: Vec<u64>,
+    /// Whether counts accumulate across exports (cumulative temporality)
+    /// rather than restarting each export (delta).
+    pub cumulative: bool,
 }
 
 // -----------------------------------------------------------------------
@@ -344,18 +363,85 @@ pub fn proto_span_to_stored(
 ///
 /// Tags the log with `log.source = "stdout"` or `"stderr"` so dashboard
 /// filters can distinguish process output from SDK-emitted OTLP logs.
-pub fn logline_to_stored(line: &crate::ui::logs::LogLine) -> StoredLog {
-    let severity = LogSeverity::from_log_level(line.level, line.is_stderr);
+///
+/// With a `format` (the service's `log_format`), the line's own level,
+/// message and timestamp are used and its other fields become attributes.
+/// Lines without a recognised level are INFO, or WARN on stderr; the
+/// keyword-guessed `line.level` is only used when no format is set.
+pub fn logline_to_stored(
+    line: &crate::ui::logs::LogLine,
+    format: Option<crate::config::model::LogFormat>,
+) -> StoredLog {
     let source = if line.is_stderr { "stderr" } else { "stdout" };
+    let (trace_id, span_id) = extract_trace_context(&line.text);
+    let mut attributes = vec![("log.source".to_string(), source.to_string())];
+    let (severity, body, timestamp) = match format {
+        None => (
+            LogSeverity::from_log_level(line.level, line.is_stderr),
+            line.text.clone(),
+            line.timestamp,
+        ),
+        Some(format) => {
+            let parsed = super::structured::parse_line(&line.text, format).unwrap_or_default();
+            attributes.extend(parsed.attributes);
+            (
+                parsed
+                    .severity
+                    .unwrap_or_else(|| LogSeverity::from_log_level(None, line.is_stderr)),
+                parsed.message.unwrap_or_else(|| line.text.clone()),
+                parsed.timestamp.unwrap_or(line.timestamp),
+            )
+        }
+    };
     StoredLog {
         record_id: 0,
-        timestamp: line.timestamp,
+        timestamp,
         service_name: line.service.clone(),
         severity,
-        body: line.text.clone(),
-        trace_id: None,
-        span_id: None,
-        attributes: vec![("log.source".to_string(), source.to_string())],
+        body,
+        trace_id,
+        span_id,
+        attributes,
+    }
+}
+
+/// `trace_id=<hex>` / `"traceId": "<hex>"` style fields in a log line.
+static TRACE_ID_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(?i)\btrace[_.-]?id"?\s*[:=]\s*"?([0-9a-f]{32})\b"#).unwrap());
+
+static SPAN_ID_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(?i)\bspan[_.-]?id"?\s*[:=]\s*"?([0-9a-f]{16})\b"#).unwrap());
+
+/// A W3C `traceparent` value: version, trace id, parent span id, flags.
+static TRACEPARENT_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b[0-9a-f]{2}-([0-9a-f]{32})-([0-9a-f]{16})-[0-9a-f]{2}\b").unwrap()
+});
+
+/// Find the trace and span ids a process logged alongside a line, either
+/// as `trace_id`/`span_id` fields (JSON or logfmt) or as a `traceparent`.
+/// Ids are lowercased to match the hex encoding of OTLP ids; all-zero ids
+/// are invalid and ignored.
+pub fn extract_trace_context(text: &str) -> (Option<String>, Option<String>) {
+    let valid = |id: &str| id.bytes().any(|b| b != b'0');
+    let fields = (
+        TRACE_ID_RE
+            .captures(text)
+            .map(|c| c[1].to_ascii_lowercase()),
+        SPAN_ID_RE.captures(text).map(|c| c[1].to_ascii_lowercase()),
+    );
+    let (trace_id, span_id) = match fields {
+        (Some(trace_id), span_id) => (Some(trace_id), span_id),
+        (None, _) => match TRACEPARENT_RE.captures(text) {
+            Some(c) => (
+                Some(c[1].to_ascii_lowercase()),
+                Some(c[2].to_ascii_lowercase()),
+            ),
+            None => (None, None),
+        },
+    };
+    match trace_id {
+        Some(trace_id) if valid(&trace_id) => (Some(trace_id), span_id.filter(|s| valid(s))),
+        _ => (None, None),
     }
 }
 
@@ -523,6 +609,7 @@ pub fn proto_metrics_to_stored(
                         value,
                         attributes: convert_attributes(&dp.attributes, 20),
                         unit: unit.clone(),
+                        histogram: None,
                     });
                 }
             }
@@ -539,10 +626,14 @@ pub fn proto_metrics_to_stored(
                         value,
                         attributes: convert_attributes(&dp.attributes, 20),
                         unit: unit.clone(),
+                        histogram: None,
                     });
                 }
             }
             Data::Histogram(hist) => {
+                use opentelemetry_proto::tonic::metrics::v1::AggregationTemporality;
+                let cumulative =
+                    hist.aggregation_temporality == AggregationTemporality::Cumulative as i32;
                 for dp in &hist.data_points {
                     let value = dp.sum.unwrap_or(0.0);
                     let timestamp = nanos_to_datetime(dp.time_unix_nano);
@@ -555,6 +646,12 @@ pub fn proto_metrics_to_stored(
                         value,
                         attributes: convert_attributes(&dp.attributes, 20),
                         unit: unit.clone(),
+                        histogram: (!dp.bucket_counts.is_empty()).then(|| HistogramBuckets {
+                            count: dp.count,
+                            bounds: dp.explicit_bounds.clone(),
+                            counts: dp.bucket_counts.clone(),
+                            cumulative,
+                        }),
                     });
                 }
             }
@@ -606,4 +703,70 @@ mod tests {
         ];
         assert_eq!(hex::encode(&bytes), "0102030405060708090a0b0c0d0e0f10");
     }
+
+    #[test]
+    fn extracts_trace_context_from_log_lines() {
+        let trace = "4bf92f3577b34da6a3ce929d0e0e4736";
+        let span = "00f067aa0ba902b7";
+        let both = (Some(trace.to_string()), Some(span.to_string()));
+        assert_eq!(
+            extract_trace_context(&format!(
+                r#"{{"level":"info","traceId":"{}","spanId":"00F067AA0BA902B7","msg":"ok"}}"#,
+                trace.to_uppercase()
+            )),
+            both.clone()
+        );
+        assert_eq!(
+            extract_trace_context(&format!("level=info trace_id={} handled", trace)),
+            (Some(trace.to_string()), None)
+        );
+        assert_eq!(
+            extract_trace_context(&format!("GET /users traceparent=00-{}-{}-01", trace, span)),
+            both
+        );
+        assert_eq!(
+            extract_trace_context(&format!("trace_id={}", "0".repeat(32))),
+            (None, None)
+        );
+        assert_eq!(extract_trace_context("trace_id=abc123"), (None, None));
+        assert_eq!(extract_trace_context("server listening"), (None, None));
+    }
+
+    #[test]
+    fn log_format_sets_severity_body_and_attributes() {
+        use crate::config::model::LogFormat;
+        use crate::ui::logs::{detect_log_level, LogLine};
+
+        let line = |text: &str, is_stderr: bool| LogLine {
+            timestamp: Utc::now(),
+            service: "api".to_string(),
+            text: text.to_string(),
+            is_stderr,
+            level: detect_log_level(text),
+        };
+
+        let json = line(
+            r#"{"level":"error","msg":"payment failed","order":7}"#,
+            false,
+        );
+        let stored = logline_to_stored(&json, Some(LogFormat::Json));
+        assert_eq!(stored.severity, LogSeverity::Error);
+        assert_eq!(stored.body, "payment failed");
+        assert!(stored
+            .attributes
+            .contains(&("order".to_string(), "7".to_string())));
+
+        // Without a format the raw line is kept and the level is guessed.
+        let stored = logline_to_stored(&json, None);
+        assert_eq!(stored.body, json.text);
+        assert_eq!(stored.attributes.len(), 1);
+
+        // Unparseable lines fall back to INFO / WARN, ignoring keywords.
+        let plain = line("retrying after error", false);
+        let stored = logline_to_stored(&plain, Some(LogFormat::Logfmt));
+        assert_eq!(stored.severity, LogSeverity::Info);
+        assert_eq!(stored.body, "retrying after error");
+        let stored = logline_to_stored(&line("retrying", true), Some(LogFormat::Auto));
+        assert_eq!(stored.severity, LogSeverity::Warn);
+    }
 }
@@ -0,0 +1,276 @@
+//! Append-only JSONL log of stored telemetry, so traces, logs and metrics
+//! outlive the devrig process.
+//!
+//! Records go to `wal.jsonl` under `.devrig/telemetry/`. Once that file
+//! reaches half the size cap it replaces `wal.1.jsonl`, so the two segments
+//! together stay under the cap. Replaying reads the older segment first.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::storage::TelemetryStore;
+use super::types::{StoredLog, StoredMetric, StoredSpan};
+
+/// Directory under the state dir that holds the WAL.
+pub const WAL_DIR: &str = "telemetry";
+const CURRENT: &str = "wal.jsonl";
+const PREVIOUS: &str = "wal.1.jsonl";
+
+/// Where the WAL for a project lives.
+pub fn wal_dir(state_dir: &Path) -> PathBuf {
+    state_dir.join(WAL_DIR)
+}
+
+/// One line of the WAL, as written.
+#[derive(Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WalEntry<'a> {
+    Span(&'a StoredSpan),
+    Log(&'a StoredLog),
+    Metric(&'a StoredMetric),
+}
+
+/// One line of the WAL, as read back.
+#[derive(Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum WalRecord {
+    Span(StoredSpan),
+    Log(StoredLog),
+    Metric(StoredMetric),
+}
+
+/// Writer for the current WAL segment.
+pub struct TelemetryWal {
+    dir: PathBuf,
+    file: BufWriter<File>,
+    written: u64,
+    segment_max: u64,
+}
+
+impl TelemetryWal {
+    /// Open (or create) the WAL in `dir`, appending to the current segment.
+    /// `max_bytes` caps both segments together.
+    pub fn open(dir: &Path, max_bytes: u64) -> io::Result<Self> {
+        std::fs::create_dir_all(dir)?;
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join(CURRENT))?;
+        let written = file.metadata()?.len();
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            file: BufWriter::new(file),
+            written,
+            segment_max: (max_bytes / 2).max(1),
+        })
+    }
+
+    pub fn append(&mut self, entry: &WalEntry<'_>) -> io::Result<()> {
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+        self.file.write_all(&line)?;
+        self.written += line.len() as u64;
+        if self.written >= self.segment_max {
+            self.rotate()?;
+        }
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+
+    /// Move the current segment over the previous one and start a new one.
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        std::fs::rename(self.dir.join(CURRENT), self.dir.join(PREVIOUS))?;
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.dir.join(CURRENT))?;
+        self.file = BufWriter::new(file);
+        self.written = 0;
+        Ok(())
+    }
+}
+
+/// Load every record in `dir` into `store`, oldest first, then drop what
+/// is past retention. The store's buffer caps apply as records go in.
+/// Unreadable lines (such as one cut short by a crash) are skipped.
+/// Returns the number of records loaded.
+pub fn replay(dir: &Path, store: &mut TelemetryStore) -> usize {
+    let mut loaded = 0;
+    for segment in [PREVIOUS, CURRENT] {
+        let Ok(file) = File::open(dir.join(segment)) else {
+            continue;
+        };
+        for line in BufReader::new(file).lines() {
+            let Ok(line) = line else { break };
+            match serde_json::from_str::<WalRecord>(&line) {
+                Ok(WalRecord::Span(span)) => store.insert_span(span),
+                Ok(WalRecord::Log(log)) => store.insert_log(log),
+                Ok(WalRecord::Metric(metric)) => store.insert_metric(metric),
+                Err(_) => continue,
+            }
+            loaded += 1;
+        }
+    }
+    store.sweep_expired();
+    loaded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::otel::types::{LogSeverity, MetricType, SpanKind, SpanStatus};
+    use chrono::{Duration as ChronoDuration, Utc};
+    use std::time::Duration;
+
+    fn span(trace_id: &str, age_secs: i64) -> StoredSpan {
+        let start = Utc::now() - ChronoDuration::seconds(age_secs);
+        StoredSpan {
+            record_id: 0,
+            trace_id: trace_id.to_string(),
+            span_id: format!("{trace_id}-span"),
+            parent_span_id: None,
+            service_name: "api".to_string(),
+            operation_name: "GET /".to_string(),
+            start_time: start,
+            end_time: start,
+            duration_ms: 5,
+            status: SpanStatus::Ok,
+            status_message: None,
+            attributes: vec![],
+            kind: SpanKind::Server,
+            events: vec![],
+        }
+    }
+
+    fn log(body: &str) -> StoredLog {
+        StoredLog {
+            record_id: 0,
+            timestamp: Utc::now(),
+            service_name: "api".to_string(),
+            severity: LogSeverity::Info,
+            body: body.to_string(),
+            trace_id: None,
+            span_id: None,
+            attributes: vec![],
+        }
+    }
+
+    fn metric(value: f64) -> StoredMetric {
+        StoredMetric {
+            record_id: 0,
+            timestamp: Utc::now(),
+            service_name: "api".to_string(),
+            metric_name: "requests".to_string(),
+            metric_type: MetricType::Counter,
+            value,
+            attributes: vec![],
+            unit: None,
+            histogram: None,
+        }
+    }
+
+    fn store(max: usize) -> TelemetryStore {
+        TelemetryStore::new(max, max, max, Duration::from_secs(3600))
+    }
+
+    #[test]
+    fn replay_restores_records_and_skips_torn_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut wal = TelemetryWal::open(dir.path(), 1 << 20).unwrap();
+        wal.append(&WalEntry::Span(&span("t1", 10))).unwrap();
+        wal.append(&WalEntry::Log(&log("hello"))).unwrap();
+        wal.append(&WalEntry::Metric(&metric(3.0))).unwrap();
+        wal.flush().unwrap();
+        drop(wal);
+
+        // A crash mid-write leaves a partial last line.
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(dir.path().join(CURRENT))
+            .unwrap();
+        file.write_all(b"{\"log\":{\"body\":").unwrap();
+
+        let mut restored = store(100);
+        assert_eq!(replay(dir.path(), &mut restored), 3);
+        assert_eq!(restored.spans()[0].trace_id, "t1");
+        assert_eq!(restored.logs()[0].body, "hello");
+        assert_eq!(restored.metrics()[0].value, 3.0);
+    }
+
+    #[test]
+    fn attached_store_persists_inserts_but_not_replayed_records() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut first = store(100);
+        first.attach_wal(TelemetryWal::open(dir.path(), 1 << 20).unwrap());
+        first.insert_span(span("t1", 1));
+        first.insert_log(log("persisted"));
+        first.flush_wal();
+
+        let mut second = store(100);
+        assert_eq!(replay(dir.path(), &mut second), 2);
+        second.attach_wal(TelemetryWal::open(dir.path(), 1 << 20).unwrap());
+        second.insert_metric(metric(1.0));
+        second.flush_wal();
+
+        let mut third = store(100);
+        assert_eq!(replay(dir.path(), &mut third), 3);
+        assert_eq!(third.get_span_count(), 1);
+        assert_eq!(third.get_log_count(), 1);
+        assert_eq!(third.get_metric_count(), 1);
+    }
+
+    #[test]
+    fn replay_respects_retention_and_caps() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut wal = TelemetryWal::open(dir.path(), 1 << 20).unwrap();
+        wal.append(&WalEntry::Span(&span("expired", 7200))).unwrap();
+        for i in 0..5 {
+            wal.append(&WalEntry::Span(&span(&format!("t{i}"), 60)))
+                .unwrap();
+        }
+        wal.flush().unwrap();
+
+        let mut restored = store(3);
+        replay(dir.path(), &mut restored);
+        let ids: Vec<&str> = restored
+            .spans()
+            .iter()
+            .map(|s| s.trace_id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["t2", "t3", "t4"]);
+    }
+
+    #[test]
+    fn rotation_keeps_two_segments_under_the_cap() {
+        let dir = tempfile::tempdir().unwrap();
+        let cap = 4096;
+        let mut wal = TelemetryWal::open(dir.path(), cap).unwrap();
+        for i in 0..200 {
+            wal.append(&WalEntry::Log(&log(&format!("line {i}"))))
+                .unwrap();
+        }
+        wal.flush().unwrap();
+
+        let size = |name: &str| std::fs::metadata(dir.path().join(name)).unwrap().len();
+        assert!(size(PREVIOUS) + size(CURRENT) <= cap + 512);
+        let files = std::fs::read_dir(dir.path()).unwrap().count();
+        assert_eq!(files, 2);
+
+        // The newest lines survive rotation and replay in order.
+        let mut restored = store(1000);
+        replay(dir.path(), &mut restored);
+        let bodies: Vec<&str> = restored.logs().iter().map(|l| l.body.as_str()).collect();
+        assert_eq!(bodies.last(), Some(&"line 199"));
+        assert!(bodies.windows(2).all(|w| {
+            let n = |s: &str| s[5..].parse::<u32>().unwrap();
+            n(w[0]) + 1 == n(w[1])
+        }));
+    }
+}
@@ -1,4 +1,5 @@
 use std::path::PathBuf;
+use std::time::Duration;
 use tokio::process::Command;
 
 #[cfg(unix)]
@@ -11,7 +12,7 @@ use unix as imp;
 #[cfg(windows)]
 use windows as imp;
 
-pub use imp::ProcessGroupHandle;
+pub use imp::{KeypressGuard, ProcessGroupHandle};
 
 /// Create a platform-appropriate shell command.
 /// Unix: `$SHELL -l -c <command>`, Windows: `cmd.exe /C <command>`
@@ -19,6 +20,82 @@ pub fn shell_command(command: &str) -> Command {
     imp::shell_command(command)
 }
 
+/// Create a plain, non-login shell command for short helper commands such
+/// as secret providers. Unix: `sh -c <command>`, Windows: `cmd /C <command>`
+pub fn script_command(command: &str) -> std::process::Command {
+    imp::script_command(command)
+}
+
+/// `PATH` for services that don't inherit the host's, see
+/// `shell_env_passthrough`.
+pub const MINIMAL_PATH: &str = imp::MINIMAL_PATH;
+
+/// How a service command is handed to the OS.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ServiceShell {
+    /// Platform default: `$SHELL -l -c` on Unix, `cmd.exe /C` on Windows.
+    #[default]
+    Default,
+    /// Split the command into argv and exec it without a shell.
+    Direct,
+    /// Custom interpreter argv; the command is appended as the final argument.
+    Custom(Vec<String>),
+}
+
+impl ServiceShell {
+    /// Parse a `[services.*].shell` value such as `"bash -c"` or `"none"`.
+    pub fn parse(shell: Option<&str>) -> Result<Self, String> {
+        let Some(shell) = shell else {
+            return Ok(ServiceShell::Default);
+        };
+        if shell.trim() == "none" {
+            return Ok(ServiceShell::Direct);
+        }
+        let argv = shlex::split(shell).ok_or_else(|| "unbalanced quotes".to_string())?;
+        if argv.is_empty() {
+            return Err("shell is empty".to_string());
+        }
+        Ok(ServiceShell::Custom(argv))
+    }
+
+    /// Human-readable interpreter name for log messages.
+    pub fn display_name(&self) -> String {
+        match self {
+            ServiceShell::Default => shell_name(),
+            ServiceShell::Direct => "exec".to_string(),
+            ServiceShell::Custom(argv) => argv.join(" "),
+        }
+    }
+}
+
+/// Split a command into argv for direct execution (`shell = "none"`).
+pub fn split_command(command: &str) -> Result<Vec<String>, String> {
+    let argv = shlex::split(command).ok_or_else(|| "unbalanced quotes".to_string())?;
+    if argv.is_empty() {
+        return Err("command is empty".to_string());
+    }
+    Ok(argv)
+}
+
+/// Create a command that runs `command` with the given service shell.
+pub fn service_command(command: &str, shell: &ServiceShell) -> anyhow::Result<Command> {
+    match shell {
+        ServiceShell::Default => Ok(shell_command(command)),
+        ServiceShell::Direct => {
+            let argv = split_command(command)
+                .map_err(|e| anyhow::anyhow!("cannot exec {:?} directly: {}", command, e))?;
+            let mut cmd = Command::new(&argv[0]);
+            cmd.args(&argv[1..]);
+            Ok(cmd)
+        }
+        ServiceShell::Custom(argv) => {
+            let mut cmd = Command::new(&argv[0]);
+            cmd.args(&argv[1..]).arg(command);
+            Ok(cmd)
+        }
+    }
+}
+
 /// Configure the command to run in a new process group.
 /// Unix: `process_group(0)`, Windows: `CREATE_NEW_PROCESS_GROUP`
 pub fn configure_process_group(cmd: &mut Command) {
@@ -46,6 +123,35 @@ pub fn is_process_alive(pid: u32) -> bool {
     imp::is_process_alive(pid)
 }
 
+/// Send the named signal (e.g. "SIGHUP") to the process group led by `pid`.
+pub fn signal_process_group(pid: u32, signal: &str) -> anyhow::Result<()> {
+    imp::signal_process_group(pid, signal)
+}
+
+/// Memory and CPU usage of a process group, as sampled by [`process_stats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProcessStats {
+    /// Resident memory, in bytes.
+    pub rss_bytes: u64,
+    /// User plus system CPU time consumed so far.
+    pub cpu_time: Duration,
+}
+
+/// Sample the process group led by `pid`, so a service's shell and the
+/// processes it started are counted together. Linux reads `/proc`, macOS and
+/// the BSDs ask `ps`. Returns `None` when the group has exited, or always on
+/// Windows, which has no lookup yet.
+pub fn process_stats(pid: u32) -> Option<ProcessStats> {
+    imp::process_stats(pid)
+}
+
+/// Switch stdin to unbuffered, no-echo input so single keypresses can be
+/// read. The previous mode is restored when the guard is dropped. Returns
+/// `None` when stdin is not a terminal or the platform keeps line input.
+pub fn keypress_input() -> Option<KeypressGuard> {
+    imp::keypress_input()
+}
+
 /// Get the current user's home directory.
 pub fn home_dir() -> Option<PathBuf> {
     dirs::home_dir()
@@ -78,6 +184,35 @@ pub fn identify_port_owner(port: u16) -> Option<String> {
     imp::identify_port_owner(port)
 }
 
+/// Configure `cmd` to keep running after this process exits, for
+/// `devrig start --detach`.
+pub fn configure_detached(cmd: &mut std::process::Command) {
+    imp::configure_detached(cmd)
+}
+
+/// Make `file` this process's stdout, replacing (and on Unix closing) the
+/// previous one.
+pub fn redirect_stdout(file: std::fs::File) -> std::io::Result<()> {
+    imp::redirect_stdout(file)
+}
+
+/// Open `url` in the default browser (`open` on macOS, `xdg-open` on other
+/// Unix systems, `start` on Windows).
+pub fn open_url(url: &str) -> anyhow::Result<()> {
+    let mut cmd = imp::open_url_command(url);
+    let opener = cmd.get_program().to_string_lossy().to_string();
+    let status = cmd
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .map_err(|e| anyhow::anyhow!("running {} to open {}: {}", opener, url, e))?;
+    if !status.success() {
+        anyhow::bail!("{} could not open {} ({})", opener, url, status);
+    }
+    Ok(())
+}
+
 /// Shell name for log messages.
 pub fn shell_name() -> String {
     imp::shell_name()
@@ -124,6 +259,39 @@ mod tests {
         // Tilde in the middle should NOT be expanded
         assert_eq!(expand_home("/some/~path"), "/some/~path");
     }
+
+    #[test]
+    fn service_shell_parse() {
+        assert_eq!(ServiceShell::parse(None).unwrap(), ServiceShell::Default);
+        assert_eq!(ServiceShell::parse(Some("none")).unwrap(), ServiceShell::Direct);
+        assert_eq!(
+            ServiceShell::parse(Some("bash -c")).unwrap(),
+            ServiceShell::Custom(vec!["bash".to_string(), "-c".to_string()])
+        );
+        assert!(ServiceShell::parse(Some("")).is_err());
+        assert!(ServiceShell::parse(Some("bash \"-c")).is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn service_command_none_execs_without_shell() {
+        // A shell would expand `$HOME` and the glob; exec'd directly, echo
+        // receives both verbatim.
+        let mut cmd = service_command("echo $HOME * \"a b\"", &ServiceShell::Direct).unwrap();
+        let output = cmd.output().await.expect("failed to spawn");
+        let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        assert_eq!(stdout, "$HOME * a b");
+        assert!(output.stderr.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn service_command_custom_shell() {
+        let shell = ServiceShell::parse(Some("sh -c")).unwrap();
+        let mut cmd = service_command("echo $0", &shell).unwrap();
+        let output = cmd.output().await.expect("failed to spawn");
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "sh");
+    }
 }
 
 #[cfg(test)]
@@ -174,4 +342,23 @@ pub mod test_commands {
     pub fn exit_failure() -> &'static str {
         "exit /b 1"
     }
+
+    /// Prints `run`, stays up briefly, then exits with code 1.
+    #[cfg(unix)]
+    pub fn echo_then_fail() -> &'static str {
+        "echo run && sleep 0.2 && exit 1"
+    }
+    #[cfg(windows)]
+    pub fn echo_then_fail() -> &'static str {
+        "echo run&& ping -n 2 127.0.0.1 > nul&& exit /b 1"
+    }
+
+    #[cfg(unix)]
+    pub fn print_500_lines() -> &'static str {
+        "seq 1 500"
+    }
+    #[cfg(windows)]
+    pub fn print_500_lines() -> &'static str {
+        "for /L %i in (1,1,500) do @echo %i"
+    }
 }
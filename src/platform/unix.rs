@@ -28,6 +28,14 @@ pub fn shell_command(command: &str) -> Command {
     cmd
 }
 
+pub const MINIMAL_PATH: &str = "/usr/local/bin:/usr/bin:/bin";
+
+pub fn script_command(command: &str) -> std::process::Command {
+    let mut cmd = std::process::Command::new("sh");
+    cmd.arg("-c").arg(command);
+    cmd
+}
+
 pub fn configure_process_group(cmd: &mut Command) {
     cmd.process_group(0);
 }
@@ -83,6 +91,40 @@ pub fn is_process_alive(pid: u32) -> bool {
     kill(Pid::from_raw(pid as i32), None).is_ok()
 }
 
+pub fn signal_process_group(pid: u32, signal: &str) -> anyhow::Result<()> {
+    let sig: Signal = signal
+        .parse()
+        .map_err(|_| anyhow::anyhow!("unknown signal '{}'", signal))?;
+    killpg(Pid::from_raw(pid as i32), sig)
+        .map_err(|e| anyhow::anyhow!("sending {} to pid {}: {}", signal, pid, e))
+}
+
+/// `open` on macOS, `xdg-open` on Linux and the BSDs.
+pub fn open_url_command(url: &str) -> std::process::Command {
+    let opener = if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+    let mut cmd = std::process::Command::new(opener);
+    cmd.arg(url);
+    cmd
+}
+
+/// Its own process group, so Ctrl+C in the launching terminal doesn't reach it.
+pub fn configure_detached(cmd: &mut std::process::Command) {
+    use std::os::unix::process::CommandExt;
+    cmd.process_group(0);
+}
+
+pub fn redirect_stdout(file: std::fs::File) -> std::io::Result<()> {
+    use std::os::fd::AsRawFd;
+    if unsafe { libc::dup2(file.as_raw_fd(), libc::STDOUT_FILENO) } == -1 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
 #[cfg(target_os = "linux")]
 pub fn identify_port_owner(port: u16) -> Option<String> {
     let tcp_content = std::fs::read_to_string("/proc/net/tcp").ok()?;
@@ -146,10 +188,213 @@ pub fn identify_port_owner(_port: u16) -> Option<String> {
     None
 }
 
+/// Fields of `/proc/<pid>/stat` used by [`process_stats`].
+#[cfg(target_os = "linux")]
+#[derive(Debug, PartialEq)]
+struct ProcStat {
+    pgrp: u32,
+    cpu_ticks: u64,
+    rss_pages: u64,
+}
+
+#[cfg(target_os = "linux")]
+fn parse_proc_stat(stat: &str) -> Option<ProcStat> {
+    // `comm` is parenthesised and may itself contain spaces or parens, so
+    // count fields from the last `)`. `rest[0]` is field 3 (state).
+    let rest: Vec<&str> = stat[stat.rfind(')')? + 1..].split_whitespace().collect();
+    let field = |n: usize| -> Option<u64> { rest.get(n - 3)?.parse().ok() };
+    Some(ProcStat {
+        pgrp: field(5)?.try_into().ok()?,
+        cpu_ticks: field(14)? + field(15)?,
+        rss_pages: field(24)?,
+    })
+}
+
+#[cfg(target_os = "linux")]
+pub fn process_stats(pid: u32) -> Option<super::ProcessStats> {
+    let ticks_per_sec = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    if ticks_per_sec <= 0 || page_size <= 0 {
+        return None;
+    }
+
+    let mut found = false;
+    let mut cpu_ticks = 0;
+    let mut rss_pages = 0;
+    for entry in std::fs::read_dir("/proc").ok()?.flatten() {
+        let name = entry.file_name();
+        if !name.to_string_lossy().chars().all(|c| c.is_ascii_digit()) {
+            continue;
+        }
+        let Ok(stat) = std::fs::read_to_string(entry.path().join("stat")) else {
+            continue;
+        };
+        let Some(stat) = parse_proc_stat(&stat) else {
+            continue;
+        };
+        if stat.pgrp == pid {
+            found = true;
+            cpu_ticks += stat.cpu_ticks;
+            rss_pages += stat.rss_pages;
+        }
+    }
+
+    found.then(|| super::ProcessStats {
+        rss_bytes: rss_pages * page_size as u64,
+        cpu_time: Duration::from_nanos(cpu_ticks * 1_000_000_000 / ticks_per_sec as u64),
+    })
+}
+
+/// Parse a `ps -o time` CPU time: `[[DD-]HH:]MM:SS` on Linux, `MM:SS.cc`
+/// (minutes past 59 included) on macOS and the BSDs.
+#[cfg(any(test, not(target_os = "linux")))]
+fn parse_ps_cpu_time(time: &str) -> Option<Duration> {
+    let (days, clock) = match time.split_once('-') {
+        Some((days, clock)) => (days.parse::<u64>().ok()?, clock),
+        None => (0, time),
+    };
+    let mut fields = clock.rsplit(':');
+    let seconds: f64 = fields.next()?.parse().ok()?;
+    let mut total = seconds + (days * 86_400) as f64;
+    for (field, unit) in fields.zip([60, 3600]) {
+        total += (field.parse::<u64>().ok()? * unit) as f64;
+    }
+    Duration::try_from_secs_f64(total).ok()
+}
+
+/// Sum `ps -A -o pgid=,rss=,time=` rows whose group is `pgid`. `rss` is in
+/// KiB.
+#[cfg(any(test, not(target_os = "linux")))]
+fn parse_ps_group_stats(ps: &str, pgid: u32) -> Option<super::ProcessStats> {
+    let mut found = false;
+    let mut rss_kib = 0;
+    let mut cpu_time = Duration::ZERO;
+    for line in ps.lines() {
+        let mut fields = line.split_whitespace();
+        let (Some(group), Some(rss), Some(time)) = (fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        if group.parse::<u32>().ok() != Some(pgid) {
+            continue;
+        }
+        let (Ok(rss), Some(time)) = (rss.parse::<u64>(), parse_ps_cpu_time(time)) else {
+            continue;
+        };
+        found = true;
+        rss_kib += rss;
+        cpu_time += time;
+    }
+    found.then_some(super::ProcessStats {
+        rss_bytes: rss_kib * 1024,
+        cpu_time,
+    })
+}
+
+/// Without `/proc`, ask `ps` for every process and keep the group's.
+#[cfg(not(target_os = "linux"))]
+pub fn process_stats(pid: u32) -> Option<super::ProcessStats> {
+    let output = std::process::Command::new("ps")
+        .args(["-A", "-o", "pgid=,rss=,time="])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_ps_group_stats(&String::from_utf8_lossy(&output.stdout), pid)
+}
+
+/// Restores the terminal mode saved by [`keypress_input`] when dropped.
+pub struct KeypressGuard(libc::termios);
+
+pub fn keypress_input() -> Option<KeypressGuard> {
+    unsafe {
+        if libc::isatty(libc::STDIN_FILENO) != 1 {
+            return None;
+        }
+        let mut original: libc::termios = std::mem::zeroed();
+        if libc::tcgetattr(libc::STDIN_FILENO, &mut original) != 0 {
+            return None;
+        }
+        // Keep ISIG so Ctrl+C still raises SIGINT.
+        let mut keypress = original;
+        keypress.c_lflag &= !(libc::ICANON | libc::ECHO);
+        keypress.c_cc[libc::VMIN] = 1;
+        keypress.c_cc[libc::VTIME] = 0;
+        if libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &keypress) != 0 {
+            return None;
+        }
+        Some(KeypressGuard(original))
+    }
+}
+
+impl Drop for KeypressGuard {
+    fn drop(&mut self) {
+        unsafe {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.0);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn parse_proc_stat_handles_spaces_in_comm() {
+        let stat = "4242 (my (odd) cmd) S 1 4200 4200 0 -1 4194560 500 0 0 0 \
+                    7 3 0 0 20 0 1 0 12345 1000000 256 18446744073709551615";
+        assert_eq!(
+            parse_proc_stat(stat),
+            Some(ProcStat {
+                pgrp: 4200,
+                cpu_ticks: 10,
+                rss_pages: 256,
+            })
+        );
+        assert_eq!(parse_proc_stat("garbage"), None);
+    }
+
+    #[test]
+    fn ps_cpu_times_parse_in_linux_and_bsd_formats() {
+        let secs = Duration::from_secs;
+        assert_eq!(parse_ps_cpu_time("00:01:05"), Some(secs(65)));
+        assert_eq!(
+            parse_ps_cpu_time("2-01:00:00"),
+            Some(secs(2 * 86_400 + 3600))
+        );
+        assert_eq!(
+            parse_ps_cpu_time("0:00.50"),
+            Some(Duration::from_millis(500))
+        );
+        assert_eq!(parse_ps_cpu_time("75:02.00"), Some(secs(75 * 60 + 2)));
+        assert_eq!(parse_ps_cpu_time("soon"), None);
+    }
+
+    #[test]
+    fn ps_rows_are_summed_for_the_group() {
+        let ps = " 4200  1024   0:01.50\n\
+                  4200   512   0:00.50\n\
+                  4300  9999   9:00.00\n";
+        assert_eq!(
+            parse_ps_group_stats(ps, 4200),
+            Some(crate::platform::ProcessStats {
+                rss_bytes: 1536 * 1024,
+                cpu_time: Duration::from_secs(2),
+            })
+        );
+        assert_eq!(parse_ps_group_stats(ps, 1), None);
+    }
+
+    #[test]
+    fn process_stats_samples_own_group() {
+        let pgrp = unsafe { libc::getpgrp() } as u32;
+        let stats = process_stats(pgrp).expect("own process group should exist");
+        assert!(stats.rss_bytes > 0);
+        assert!(process_stats(u32::MAX).is_none());
+    }
+
     #[test]
     fn user_shell_returns_shell_env() {
         let shell = std::env::var("SHELL").unwrap_or_else(|_| "sh".to_string());
@@ -4,12 +4,14 @@ use tokio::process::Command;
 use tracing::{debug, warn};
 
 use windows_sys::Win32::Foundation::CloseHandle;
-use windows_sys::Win32::System::Console::{GenerateConsoleCtrlEvent, CTRL_BREAK_EVENT};
+use windows_sys::Win32::System::Console::{
+    GenerateConsoleCtrlEvent, SetStdHandle, CTRL_BREAK_EVENT, STD_OUTPUT_HANDLE,
+};
 use windows_sys::Win32::System::JobObjects::{
     AssignProcessToJobObject, CreateJobObjectW, TerminateJobObject,
 };
 use windows_sys::Win32::System::Threading::{
-    GetExitCodeProcess, OpenProcess, CREATE_NEW_PROCESS_GROUP,
+    GetExitCodeProcess, OpenProcess, CREATE_NEW_PROCESS_GROUP, DETACHED_PROCESS,
     PROCESS_QUERY_LIMITED_INFORMATION, PROCESS_SET_QUOTA, PROCESS_TERMINATE,
 };
 
@@ -42,6 +44,14 @@ pub fn shell_command(command: &str) -> Command {
     cmd
 }
 
+pub const MINIMAL_PATH: &str = r"C:\Windows\system32;C:\Windows";
+
+pub fn script_command(command: &str) -> std::process::Command {
+    let mut cmd = std::process::Command::new("cmd");
+    cmd.arg("/C").arg(command);
+    cmd
+}
+
 pub fn configure_process_group(cmd: &mut Command) {
     cmd.creation_flags(CREATE_NEW_PROCESS_GROUP);
 }
@@ -127,6 +137,46 @@ pub fn is_process_alive(pid: u32) -> bool {
     }
 }
 
+pub fn signal_process_group(_pid: u32, signal: &str) -> anyhow::Result<()> {
+    anyhow::bail!("sending {} is only supported on unix platforms", signal)
+}
+
+pub fn process_stats(_pid: u32) -> Option<super::ProcessStats> {
+    None
+}
+
+/// Windows keeps line-buffered console input; see [`keypress_input`].
+pub struct KeypressGuard;
+
+pub fn keypress_input() -> Option<KeypressGuard> {
+    None
+}
+
+/// `cmd /C start "" <url>`; the empty title keeps `start` from taking the
+/// URL as the window title.
+pub fn open_url_command(url: &str) -> std::process::Command {
+    let mut cmd = std::process::Command::new("cmd");
+    cmd.args(["/C", "start", ""]).arg(url);
+    cmd
+}
+
+/// No console and its own process group, so closing or interrupting the
+/// launching console doesn't reach it.
+pub fn configure_detached(cmd: &mut std::process::Command) {
+    use std::os::windows::process::CommandExt;
+    cmd.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+}
+
+pub fn redirect_stdout(file: std::fs::File) -> std::io::Result<()> {
+    use std::os::windows::io::IntoRawHandle;
+    // The handle stays open for the rest of the process as its stdout.
+    let handle = file.into_raw_handle();
+    if unsafe { SetStdHandle(STD_OUTPUT_HANDLE, handle) } == 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
 pub fn identify_port_owner(_port: u16) -> Option<String> {
     // TODO: implement via GetExtendedTcpTable from Win32_NetworkManagement_IpHelper
     None
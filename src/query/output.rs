@@ -4,7 +4,7 @@ use comfy_table::{Cell, CellAlignment, ContentArrangement, Table};
 use is_terminal::IsTerminal;
 use owo_colors::OwoColorize;
 
-use crate::otel::query::{RelatedTelemetry, SystemStatus, TraceSummary};
+use crate::otel::query::{RelatedTelemetry, SystemStatus, TraceDetail, TraceSummary};
 use crate::otel::types::{LogSeverity, StoredLog, StoredMetric, StoredSpan};
 
 // -----------------------------------------------------------------------
@@ -214,24 +214,71 @@ fn print_spans_table(spans: &[StoredSpan]) {
     }
 }
 
+/// A trace's spans followed by its correlated logs. JSON formats print the
+/// whole detail object, as returned by `/api/traces/{id}`.
+pub fn print_trace_detail(detail: &TraceDetail, format: OutputFormat) {
+    match format {
+        OutputFormat::Json => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(detail).unwrap_or_default()
+            );
+        }
+        OutputFormat::Jsonl => {
+            println!("{}", serde_json::to_string(detail).unwrap_or_default());
+        }
+        OutputFormat::Table => {
+            print_spans_table(&detail.spans);
+            if !detail.logs.is_empty() {
+                println!();
+                println!("  Logs ({}):", detail.logs.len());
+                print_logs_table(&detail.logs);
+            }
+        }
+    }
+}
+
 // -----------------------------------------------------------------------
 // Log output
 // -----------------------------------------------------------------------
 
-pub fn print_logs(logs: &[StoredLog], format: OutputFormat) {
+/// Print logs. JSON formats carry only the time, service, severity and body
+/// of each record unless `include_attributes` is set, in which case the
+/// whole record is printed: attributes, trace and span ids included.
+pub fn print_logs(logs: &[StoredLog], format: OutputFormat, include_attributes: bool) {
     match format {
         OutputFormat::Json => {
-            println!("{}", serde_json::to_string_pretty(logs).unwrap_or_default());
+            let values: Vec<serde_json::Value> = logs
+                .iter()
+                .map(|l| log_to_json(l, include_attributes))
+                .collect();
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&values).unwrap_or_default()
+            );
         }
         OutputFormat::Jsonl => {
             for l in logs {
-                println!("{}", serde_json::to_string(l).unwrap_or_default());
+                println!("{}", log_to_json(l, include_attributes));
             }
         }
         OutputFormat::Table => print_logs_table(logs),
     }
 }
 
+fn log_to_json(log: &StoredLog, include_attributes: bool) -> serde_json::Value {
+    if include_attributes {
+        serde_json::to_value(log).unwrap_or_default()
+    } else {
+        serde_json::json!({
+            "timestamp": log.timestamp,
+            "service_name": log.service_name,
+            "severity": log.severity,
+            "body": log.body,
+        })
+    }
+}
+
 fn print_logs_table(logs: &[StoredLog]) {
     if logs.is_empty() {
         println!("  No logs found.");
@@ -391,6 +438,26 @@ fn print_status_table(status: &SystemStatus) {
         println!("  Services: {}", status.services.join(", "));
     }
     println!();
+
+    if status.orphan_span_count > 0 {
+        let heading = format!("Orphan spans: {}", status.orphan_span_count);
+        if use_color {
+            println!("  {} {}", "\u{26a0}".yellow(), heading.yellow());
+        } else {
+            println!("  {}", heading);
+        }
+        for pair in &status.orphan_pairs {
+            println!(
+                "    {} -> {}: {}",
+                pair.upstream.as_deref().unwrap_or("?"),
+                pair.downstream,
+                pair.count
+            );
+        }
+        println!("  Hint: parent spans are missing; check that trace context (traceparent)");
+        println!("  is propagated between these services and that the caller exports spans.");
+        println!();
+    }
 }
 
 // -----------------------------------------------------------------------
@@ -411,7 +478,7 @@ pub fn print_related(related: &RelatedTelemetry, format: OutputFormat) {
         OutputFormat::Table => {
             if !related.logs.is_empty() {
                 println!("  Related Logs ({}):", related.logs.len());
-                print_logs(&related.logs, OutputFormat::Table);
+                print_logs(&related.logs, OutputFormat::Table, false);
             }
             if !related.metrics.is_empty() {
                 println!("  Related Metrics ({}):", related.metrics.len());
@@ -453,6 +520,7 @@ fn format_metric_value(v: f64) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::Utc;
 
     #[test]
     fn format_duration_sub_ms() {
@@ -499,4 +567,45 @@ mod tests {
             OutputFormat::Table
         );
     }
+
+    fn log_with_attributes() -> StoredLog {
+        StoredLog {
+            record_id: 7,
+            timestamp: Utc::now(),
+            service_name: "api".to_string(),
+            severity: LogSeverity::Warn,
+            body: "slow query".to_string(),
+            trace_id: Some("4bf92f3577b34da6a3ce929d0e0e4736".to_string()),
+            span_id: Some("00f067aa0ba902b7".to_string()),
+            attributes: vec![
+                ("service.instance.id".to_string(), "api-1".to_string()),
+                ("db.statement".to_string(), "SELECT 1".to_string()),
+                ("log.source".to_string(), "otlp".to_string()),
+            ],
+        }
+    }
+
+    #[test]
+    fn include_attributes_round_trips_the_full_record() {
+        let log = log_with_attributes();
+        let back: StoredLog = serde_json::from_value(log_to_json(&log, true)).unwrap();
+        assert_eq!(back.attributes, log.attributes);
+        assert_eq!(back.trace_id, log.trace_id);
+        assert_eq!(back.span_id, log.span_id);
+        assert_eq!(back.record_id, 7);
+    }
+
+    #[test]
+    fn default_json_is_slim() {
+        let value = log_to_json(&log_with_attributes(), false);
+        let mut keys: Vec<&str> = value
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        keys.sort();
+        assert_eq!(keys, vec!["body", "service_name", "severity", "timestamp"]);
+        assert_eq!(value["severity"], "Warn");
+    }
 }
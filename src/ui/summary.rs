@@ -5,16 +5,63 @@ use is_terminal::IsTerminal;
 use owo_colors::OwoColorize;
 
 use crate::identity::ProjectIdentity;
+use crate::orchestrator::state::ClusterState;
 use std::collections::BTreeMap;
+use std::path::Path;
+
+/// File in the state dir holding the startup summary while devrig runs.
+pub const SUMMARY_FILE: &str = "summary.json";
+
+/// How `devrig start` reports the running stack once startup completes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SummaryFormat {
+    /// Banner plus a human-readable table.
+    #[default]
+    Table,
+    /// A single JSON object on stdout, for tooling that launches devrig.
+    Json,
+}
+
+impl SummaryFormat {
+    pub fn from_str_opt(s: Option<&str>) -> Self {
+        match s {
+            Some("json") => SummaryFormat::Json,
+            _ => SummaryFormat::Table,
+        }
+    }
+}
 
 pub struct StartupBannerInfo {
     pub services: Vec<String>,
     pub docker: Vec<String>,
     pub compose: Option<String>,
+    /// Whether the project has a `[cluster]`.
+    pub cluster: bool,
+    /// The pinned `[cluster] k3s_image`, if any.
+    pub cluster_image: Option<String>,
     pub cluster_addons: Vec<String>,
     pub dashboard_enabled: bool,
 }
 
+/// The banner's cluster line, naming the k3s image so version mismatches
+/// between machines are visible.
+fn cluster_banner(info: &StartupBannerInfo) -> Option<String> {
+    if !info.cluster {
+        return None;
+    }
+    let image = info.cluster_image.as_deref().unwrap_or("k3d default image");
+    let addons = match info.cluster_addons.len() {
+        0 => String::new(),
+        n => format!(
+            ", {} addon{}: {}",
+            n,
+            if n == 1 { "" } else { "s" },
+            info.cluster_addons.join(", "),
+        ),
+    };
+    Some(format!("k3s ({}{})", image, addons))
+}
+
 pub fn print_startup_banner(identity: &ProjectIdentity, info: &StartupBannerInfo) {
     let use_color = std::io::stdout().is_terminal();
 
@@ -40,13 +87,8 @@ pub fn print_startup_banner(identity: &ProjectIdentity, info: &StartupBannerInfo
     if let Some(compose) = &info.compose {
         println!("  Compose:     {}", compose);
     }
-    if !info.cluster_addons.is_empty() {
-        println!(
-            "  Cluster:     k3s ({} addon{}: {})",
-            info.cluster_addons.len(),
-            if info.cluster_addons.len() == 1 { "" } else { "s" },
-            info.cluster_addons.join(", "),
-        );
+    if let Some(cluster) = cluster_banner(info) {
+        println!("  Cluster:     {}", cluster);
     }
     if info.dashboard_enabled {
         println!("  Dashboard:   enabled");
@@ -58,6 +100,8 @@ pub struct RunningService {
     pub port: Option<u16>,
     pub port_auto: bool,
     pub status: String,
+    /// Served over HTTPS, so its URL uses `https://`.
+    pub tls: bool,
 }
 
 /// Print dashboard and OTLP endpoint info when dashboard is enabled.
@@ -75,9 +119,12 @@ pub fn print_dashboard_info(dash_port: u16, grpc_port: u16, http_port: u16) {
     println!("    OTLP HTTP: localhost:{}", http_port);
 }
 
+/// Print the table of running services. `detached` swaps the Ctrl+C hint
+/// for `devrig stop`, as a backgrounded devrig never sees the keypress.
 pub fn print_startup_summary(
     identity: &ProjectIdentity,
     services: &BTreeMap<String, RunningService>,
+    detached: bool,
 ) {
     let use_color = std::io::stdout().is_terminal();
 
@@ -110,11 +157,7 @@ pub fn print_startup_summary(
         let url = svc
             .port
             .map(|p| {
-                let base = if name.starts_with("[docker]") || name.starts_with("[cluster]") {
-                    format!("localhost:{}", p)
-                } else {
-                    format!("http://localhost:{}", p)
-                };
+                let base = service_url(name, p, svc.tls);
                 if svc.port_auto {
                     format!("{} (auto)", base)
                 } else {
@@ -127,7 +170,7 @@ pub fn print_startup_summary(
         let status_color = if use_color {
             match svc.status.as_str() {
                 "running" | "ready" => Some(Color::Green),
-                "starting" => Some(Color::Yellow),
+                "starting" | "restarting" | "unhealthy" => Some(Color::Yellow),
                 "failed" => Some(Color::Red),
                 _ => None,
             }
@@ -148,15 +191,12 @@ pub fn print_startup_summary(
         println!("  {}", line);
     }
 
-    if let Some(port) = resolve_dashboard_display_port(services) {
+    if let Some(url) = dashboard_display_url(services) {
         println!();
         if use_color {
-            println!(
-                "  Dashboard: {}",
-                format!("http://localhost:{}", port).cyan()
-            );
+            println!("  Dashboard: {}", url.cyan());
         } else {
-            println!("  Dashboard: http://localhost:{}", port);
+            println!("  Dashboard: {}", url);
         }
     }
 
@@ -170,7 +210,9 @@ pub fn print_startup_summary(
     }
 
     println!();
-    if use_color {
+    if detached {
+        println!("  Run `devrig stop` to stop");
+    } else if use_color {
         println!("  Press {} to stop", "Ctrl+C".bold());
     } else {
         println!("  Press Ctrl+C to stop");
@@ -178,6 +220,119 @@ pub fn print_startup_summary(
     println!();
 }
 
+/// Address shown for a summary entry: bare `host:port` for docker and
+/// cluster resources, an `http://` (or `https://` with `tls`) URL for
+/// everything else.
+fn service_url(name: &str, port: u16, tls: bool) -> String {
+    if name.starts_with("[docker]") || name.starts_with("[cluster]") {
+        format!("localhost:{}", port)
+    } else if tls {
+        format!("https://localhost:{}", port)
+    } else {
+        format!("http://localhost:{}", port)
+    }
+}
+
+/// Split a summary key such as `[docker] postgres` into its kind and name.
+/// Plain keys are services; bare tags such as `[dashboard]` use the tag as
+/// both kind and name.
+fn split_summary_key(key: &str) -> (&str, &str) {
+    match key.strip_prefix('[').and_then(|rest| rest.split_once(']')) {
+        Some((kind, name)) if name.trim().is_empty() => (kind, kind),
+        Some((kind, name)) => (kind, name.trim()),
+        None => ("service", key),
+    }
+}
+
+/// Build the `--summary-format json` object: every running resource with
+/// its resolved port, URL, and status, plus dashboard, OTLP and cluster
+/// info.
+pub fn startup_summary_json(
+    identity: &ProjectIdentity,
+    services: &BTreeMap<String, RunningService>,
+    cluster: Option<&ClusterState>,
+) -> serde_json::Value {
+    let entries: Vec<serde_json::Value> = services
+        .iter()
+        .map(|(key, svc)| {
+            let (kind, name) = split_summary_key(key);
+            serde_json::json!({
+                "name": name,
+                "kind": kind,
+                "port": svc.port,
+                "port_auto": svc.port_auto,
+                "url": svc.port.map(|p| service_url(key, p, svc.tls)),
+                "status": svc.status,
+            })
+        })
+        .collect();
+
+    let dashboard = dashboard_display_url(services).map(|url| serde_json::json!({ "url": url }));
+
+    let otlp_url = |key: &str| {
+        services
+            .get(key)
+            .and_then(|svc| svc.port.map(|port| service_url(key, port, svc.tls)))
+    };
+    let otel = match (otlp_url("[otel] grpc"), otlp_url("[otel] http")) {
+        (None, None) => None,
+        (grpc, http) => Some(serde_json::json!({ "grpc": grpc, "http": http })),
+    };
+
+    let cluster = cluster.map(|cs| {
+        serde_json::json!({
+            "name": cs.cluster_name,
+            "kubeconfig": cs.kubeconfig_path,
+            "registry": cs.registry_port.map(|p| format!("localhost:{}", p)),
+        })
+    });
+
+    serde_json::json!({
+        "project": { "name": identity.name, "id": identity.id, "slug": identity.slug },
+        "services": entries,
+        "dashboard": dashboard,
+        "otel": otel,
+        "cluster": cluster,
+    })
+}
+
+/// Print a [`startup_summary_json`] object as a single line on stdout.
+pub fn print_startup_summary_json(summary: &serde_json::Value) {
+    use std::io::Write;
+
+    let mut stdout = std::io::stdout().lock();
+    let _ = writeln!(stdout, "{}", summary);
+    let _ = stdout.flush();
+}
+
+/// Write `summary` to [`SUMMARY_FILE`] in `state_dir`, atomically.
+pub fn write_summary_file(state_dir: &Path, summary: &serde_json::Value) -> std::io::Result<()> {
+    let content = serde_json::to_string_pretty(summary)?;
+    let tmp_path = state_dir.join("summary.json.tmp");
+    std::fs::write(&tmp_path, content)?;
+    std::fs::rename(&tmp_path, state_dir.join(SUMMARY_FILE))
+}
+
+/// Set the status of the `kind`/`name` entry in [`SUMMARY_FILE`]. Does
+/// nothing when there is no summary or no such entry.
+pub fn update_summary_status(state_dir: &Path, kind: &str, name: &str, status: &str) {
+    let Some(mut summary) = std::fs::read_to_string(state_dir.join(SUMMARY_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+    else {
+        return;
+    };
+    let Some(entry) = summary["services"].as_array_mut().and_then(|entries| {
+        entries
+            .iter_mut()
+            .find(|e| e["kind"] == kind && e["name"] == name)
+    }) else {
+        return;
+    };
+    entry["status"] = status.into();
+    let _ = write_summary_file(state_dir, &summary);
+}
+
 /// Resolve which port to display as the dashboard URL.
 /// Prefers the Vite dev server (live reload) when available,
 /// otherwise falls back to the embedded dashboard port.
@@ -190,6 +345,15 @@ pub fn resolve_dashboard_display_port(
         .and_then(|svc| svc.port)
 }
 
+/// The URL to show for the dashboard: the Vite dev server when it has a
+/// port, otherwise the embedded dashboard.
+fn dashboard_display_url(services: &BTreeMap<String, RunningService>) -> Option<String> {
+    ["[vite]", "[dashboard]"].into_iter().find_map(|key| {
+        let svc = services.get(key)?;
+        Some(service_url(key, svc.port?, svc.tls))
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -199,9 +363,34 @@ mod tests {
             port: Some(port),
             port_auto: false,
             status: "running".to_string(),
+            tls: false,
         }
     }
 
+    #[test]
+    fn cluster_banner_names_image_and_addons() {
+        let mut info = StartupBannerInfo {
+            services: vec![],
+            docker: vec![],
+            compose: None,
+            cluster: false,
+            cluster_image: None,
+            cluster_addons: vec![],
+            dashboard_enabled: false,
+        };
+        assert_eq!(cluster_banner(&info), None);
+
+        info.cluster = true;
+        assert_eq!(cluster_banner(&info).unwrap(), "k3s (k3d default image)");
+
+        info.cluster_image = Some("rancher/k3s:v1.31.4-k3s1".to_string());
+        info.cluster_addons = vec!["traefik".to_string(), "cert-manager".to_string()];
+        assert_eq!(
+            cluster_banner(&info).unwrap(),
+            "k3s (rancher/k3s:v1.31.4-k3s1, 2 addons: traefik, cert-manager)"
+        );
+    }
+
     #[test]
     fn dashboard_port_shown_when_no_vite() {
         let mut services = BTreeMap::new();
@@ -231,4 +420,139 @@ mod tests {
         services.insert("[dashboard]".to_string(), svc(4001));
         assert_eq!(resolve_dashboard_display_port(&services), Some(4001));
     }
+
+    #[test]
+    fn json_summary_lists_each_resource_with_resolved_port() {
+        let identity = ProjectIdentity {
+            name: "shop".to_string(),
+            id: "abcd1234".to_string(),
+            slug: "shop-abcd1234".to_string(),
+            config_path: "devrig.toml".into(),
+        };
+        let mut services = BTreeMap::new();
+        services.insert("api".to_string(), svc(3000));
+        services.insert(
+            "[docker] postgres".to_string(),
+            RunningService {
+                port: Some(54321),
+                port_auto: true,
+                status: "running".to_string(),
+                tls: false,
+            },
+        );
+        services.insert("[dashboard]".to_string(), svc(4000));
+        services.insert(
+            "[cluster] web".to_string(),
+            RunningService {
+                port: None,
+                port_auto: false,
+                status: "deployed [abc]".to_string(),
+                tls: false,
+            },
+        );
+
+        let json = startup_summary_json(&identity, &services, None);
+        assert_eq!(json["project"]["name"], "shop");
+        assert_eq!(json["project"]["slug"], "shop-abcd1234");
+        assert_eq!(json["dashboard"]["url"], "http://localhost:4000");
+        assert!(json["otel"].is_null());
+        assert!(json["cluster"].is_null());
+
+        let entries = json["services"].as_array().unwrap();
+        assert_eq!(entries.len(), 4);
+        let find = |kind: &str, name: &str| {
+            entries
+                .iter()
+                .find(|e| e["kind"] == kind && e["name"] == name)
+                .unwrap_or_else(|| panic!("missing {kind} {name}"))
+        };
+
+        let api = find("service", "api");
+        assert_eq!(api["port"], 3000);
+        assert_eq!(api["url"], "http://localhost:3000");
+        assert_eq!(api["status"], "running");
+
+        let pg = find("docker", "postgres");
+        assert_eq!(pg["port"], 54321);
+        assert_eq!(pg["port_auto"], true);
+        assert_eq!(pg["url"], "localhost:54321");
+
+        assert_eq!(find("dashboard", "dashboard")["port"], 4000);
+
+        let web = find("cluster", "web");
+        assert!(web["port"].is_null());
+        assert!(web["url"].is_null());
+    }
+
+    #[test]
+    fn tls_entries_get_https_urls() {
+        let identity = ProjectIdentity {
+            name: "shop".to_string(),
+            id: "abcd1234".to_string(),
+            slug: "shop-abcd1234".to_string(),
+            config_path: "devrig.toml".into(),
+        };
+        let tls = |port| RunningService {
+            tls: true,
+            ..svc(port)
+        };
+        let mut services = BTreeMap::new();
+        services.insert("[dashboard]".to_string(), tls(4000));
+        services.insert("[otel] grpc".to_string(), tls(4317));
+        services.insert("[otel] http".to_string(), svc(4318));
+
+        let json = startup_summary_json(&identity, &services, None);
+        assert_eq!(json["dashboard"]["url"], "https://localhost:4000");
+        assert_eq!(json["otel"]["grpc"], "https://localhost:4317");
+        assert_eq!(json["otel"]["http"], "http://localhost:4318");
+
+        // The Vite dev server is plain HTTP even when the dashboard is not.
+        services.insert("[vite]".to_string(), svc(5173));
+        assert_eq!(
+            dashboard_display_url(&services).as_deref(),
+            Some("http://localhost:5173")
+        );
+    }
+
+    #[test]
+    fn summary_file_tracks_status_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let identity = ProjectIdentity {
+            name: "shop".to_string(),
+            id: "abcd1234".to_string(),
+            slug: "shop-abcd1234".to_string(),
+            config_path: "devrig.toml".into(),
+        };
+        let mut services = BTreeMap::new();
+        services.insert("api".to_string(), svc(3000));
+        services.insert("[docker] postgres".to_string(), svc(5432));
+        services.insert("[otel] grpc".to_string(), svc(4317));
+        services.insert("[otel] http".to_string(), svc(4318));
+
+        // Nothing to update before the summary exists
+        update_summary_status(dir.path(), "service", "api", "failed");
+        assert!(!dir.path().join(SUMMARY_FILE).exists());
+
+        let json = startup_summary_json(&identity, &services, None);
+        write_summary_file(dir.path(), &json).unwrap();
+        update_summary_status(dir.path(), "service", "api", "failed");
+        update_summary_status(dir.path(), "docker", "api", "restarting");
+
+        let written: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(dir.path().join(SUMMARY_FILE)).unwrap())
+                .unwrap();
+        assert_eq!(written["otel"]["grpc"], "http://localhost:4317");
+        assert_eq!(written["otel"]["http"], "http://localhost:4318");
+        let status = |kind: &str, name: &str| {
+            written["services"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|e| e["kind"] == kind && e["name"] == name)
+                .map(|e| e["status"].clone())
+                .unwrap()
+        };
+        assert_eq!(status("service", "api"), "failed");
+        assert_eq!(status("docker", "postgres"), "running");
+    }
 }
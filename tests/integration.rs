@@ -48,14 +48,20 @@ mod port_collision;
 mod ps_all;
 #[path = "integration/ready_checks.rs"]
 mod ready_checks;
+#[path = "integration/reload_env.rs"]
+mod reload_env;
 #[path = "integration/reset_command.rs"]
 mod reset_command;
+#[path = "integration/restart_command.rs"]
+mod restart_command;
 #[path = "integration/service_discovery.rs"]
 mod service_discovery;
 #[path = "integration/skill_install.rs"]
 mod skill_install;
 #[path = "integration/start_stop.rs"]
 mod start_stop;
+#[path = "integration/top_command.rs"]
+mod top_command;
 #[path = "integration/validate_command.rs"]
 mod validate_command;
 #[path = "integration/volume_cleanup.rs"]
@@ -263,3 +263,106 @@ fn addon_kustomize_config_parses() {
         _ => panic!("expected Kustomize addon"),
     }
 }
+
+/// Verify that a manifest addon can be applied into a namespace that doesn't
+/// exist yet — devrig creates it first when `create_namespace` is on (the
+/// default).
+#[tokio::test]
+async fn addon_manifest_creates_missing_namespace() {
+    if !crate::common::k3d_available() {
+        eprintln!("Skipping addon_manifest_creates_missing_namespace: k3d not found");
+        return;
+    }
+
+    let project = TestProject::new(
+        r#"
+        [project]
+        name = "test-addon-ns"
+
+        [cluster]
+        registry = false
+
+        [cluster.addons.settings]
+        type = "manifest"
+        path = "k8s/settings.yaml"
+        namespace = "devrig-fresh-ns"
+    "#,
+    );
+
+    let k8s_dir = project.dir.path().join("k8s");
+    std::fs::create_dir_all(&k8s_dir).unwrap();
+    std::fs::write(
+        k8s_dir.join("settings.yaml"),
+        r#"
+apiVersion: v1
+kind: ConfigMap
+metadata:
+  name: settings
+data:
+  greeting: hello
+"#,
+    )
+    .unwrap();
+
+    let (config, _source) = devrig::config::load_config(&project.config_path).unwrap();
+    let identity =
+        devrig::identity::ProjectIdentity::from_config(&config, &project.config_path).unwrap();
+    let cluster_name = identity.cluster_name();
+    let network_name = identity.network_name();
+
+    let guard_cluster = cluster_name.clone();
+    let guard_network = network_name.clone();
+    let _guard = scopeguard::guard((), move |_| {
+        crate::common::k3d_cleanup_sync(&guard_cluster);
+        let _ = Command::new("docker")
+            .args(["network", "rm", &guard_network])
+            .output();
+    });
+
+    let _ = Command::new("docker")
+        .args(["network", "create", &network_name])
+        .output();
+
+    let cluster_config = config.cluster.as_ref().unwrap();
+    let state_dir = project.dir.path().join(".devrig");
+    std::fs::create_dir_all(&state_dir).unwrap();
+
+    let k3d_mgr = devrig::cluster::K3dManager::new(
+        &identity.slug,
+        cluster_config,
+        &state_dir,
+        &network_name,
+        project.dir.path(),
+    );
+    k3d_mgr
+        .create_cluster()
+        .await
+        .expect("cluster create failed");
+    k3d_mgr
+        .write_kubeconfig()
+        .await
+        .expect("write_kubeconfig failed");
+
+    let cancel = tokio_util::sync::CancellationToken::new();
+    let states = devrig::cluster::addon::install_addons(
+        &cluster_config.addons,
+        &std::collections::HashMap::new(),
+        k3d_mgr.kubeconfig_path(),
+        project.dir.path(),
+        &cancel,
+    )
+    .await
+    .expect("manifest addon should install into a new namespace");
+    assert_eq!(states["settings"].namespace, "devrig-fresh-ns");
+
+    let configmap = k3d_mgr
+        .kubectl(&["get", "configmap/settings", "-n", "devrig-fresh-ns"])
+        .await
+        .expect("configmap should exist in the created namespace");
+    assert!(configmap.contains("settings"));
+
+    k3d_mgr
+        .delete_cluster()
+        .await
+        .expect("cluster delete failed");
+}
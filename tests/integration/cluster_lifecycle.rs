@@ -163,3 +163,429 @@ spec:
     // Disarm the guard since we cleaned up successfully
     std::mem::forget(_guard);
 }
+
+#[tokio::test]
+async fn cluster_deploy_env_reaches_pod() {
+    if !k3d_available() {
+        eprintln!("Skipping: k3d not found");
+        return;
+    }
+
+    let project = crate::common::TestProject::new(
+        r#"
+        [project]
+        name = "clenvtest"
+
+        [cluster]
+        registry = false
+        env = { SHARED = "from-cluster" }
+
+        [cluster.deploy.envcheck]
+        context = "./envcheck"
+        manifests = "./k8s/envcheck"
+        env = { GREETING = "hello-{{ project.name }}" }
+    "#,
+    );
+
+    let app_dir = project.dir.path().join("envcheck");
+    std::fs::create_dir_all(&app_dir).unwrap();
+    std::fs::write(
+        app_dir.join("Dockerfile"),
+        "FROM alpine:3.19\nCMD [\"sleep\", \"3600\"]\n",
+    )
+    .unwrap();
+
+    let k8s_dir = project.dir.path().join("k8s/envcheck");
+    std::fs::create_dir_all(&k8s_dir).unwrap();
+    std::fs::write(
+        k8s_dir.join("deployment.yaml"),
+        r#"
+apiVersion: apps/v1
+kind: Deployment
+metadata:
+  name: envcheck
+spec:
+  replicas: 1
+  selector:
+    matchLabels:
+      app: envcheck
+  template:
+    metadata:
+      labels:
+        app: envcheck
+    spec:
+      containers:
+      - name: envcheck
+        image: alpine:3.19
+        command: ["sleep", "3600"]
+"#,
+    )
+    .unwrap();
+
+    let (config, _source) = devrig::config::load_config(&project.config_path).unwrap();
+    let identity =
+        devrig::identity::ProjectIdentity::from_config(&config, &project.config_path).unwrap();
+    let slug = identity.slug.clone();
+    let cluster_name = format!("devrig-{}", slug);
+    let network_name = format!("devrig-{}-net", slug);
+
+    let guard_cluster = cluster_name.clone();
+    let guard_slug = slug.clone();
+    let guard_network = network_name.clone();
+    let _guard = scopeguard::guard((), move |_| {
+        k3d_cleanup_sync(&guard_cluster);
+        docker_cleanup(&guard_slug);
+        let _ = std::process::Command::new("docker")
+            .args(["network", "rm", &guard_network])
+            .output();
+    });
+
+    let _ = std::process::Command::new("docker")
+        .args(["network", "create", &network_name])
+        .output();
+
+    let cluster_config = config.cluster.as_ref().unwrap();
+    let state_dir = project.dir.path().join(".devrig");
+    std::fs::create_dir_all(&state_dir).unwrap();
+    let k3d_mgr = devrig::cluster::K3dManager::new(
+        &slug,
+        cluster_config,
+        &state_dir,
+        &network_name,
+        project.dir.path(),
+    );
+    k3d_mgr.create_cluster().await.expect("cluster create failed");
+    k3d_mgr.write_kubeconfig().await.expect("write_kubeconfig failed");
+
+    let vars = std::collections::HashMap::from([(
+        "project.name".to_string(),
+        config.project.name.clone(),
+    )]);
+    let mut deploy_config = cluster_config.deploy["envcheck"].clone();
+    deploy_config.env =
+        devrig::config::interpolate::resolve_cluster_deploy_env(cluster_config, "envcheck", &vars)
+            .unwrap();
+
+    let cancel = tokio_util::sync::CancellationToken::new();
+    let state = devrig::cluster::deploy::run_deploy(
+        "envcheck",
+        &deploy_config,
+        None,
+        k3d_mgr.kubeconfig_path(),
+        project.dir.path(),
+        &state_dir,
+        &cancel,
+    )
+    .await
+    .expect("deploy failed");
+    assert_eq!(state.config_map.as_deref(), Some("envcheck-devrig-env"));
+    assert_eq!(state.namespaces, ["default"]);
+
+    k3d_mgr
+        .kubectl(&[
+            "rollout",
+            "status",
+            "deployment/envcheck",
+            "--timeout=120s",
+        ])
+        .await
+        .expect("rollout did not complete");
+
+    let env = k3d_mgr
+        .kubectl(&["exec", "deployment/envcheck", "--", "env"])
+        .await
+        .expect("kubectl exec env failed");
+    assert!(env.contains("GREETING=hello-clenvtest"), "pod env: {env}");
+    assert!(env.contains("SHARED=from-cluster"), "pod env: {env}");
+
+    k3d_mgr.delete_cluster().await.expect("cluster delete failed");
+    let _ = std::process::Command::new("docker")
+        .args(["network", "rm", &network_name])
+        .output();
+    std::mem::forget(_guard);
+}
+
+#[tokio::test]
+async fn cluster_events_shows_failing_pod_warning() {
+    if !k3d_available() {
+        eprintln!("Skipping: k3d not found");
+        return;
+    }
+
+    let project = crate::common::TestProject::new(
+        r#"
+        [project]
+        name = "cleventstest"
+
+        [cluster]
+        registry = false
+    "#,
+    );
+
+    let (config, _source) = devrig::config::load_config(&project.config_path).unwrap();
+    let identity =
+        devrig::identity::ProjectIdentity::from_config(&config, &project.config_path).unwrap();
+    let slug = identity.slug.clone();
+    let cluster_name = format!("devrig-{}", slug);
+    let network_name = format!("devrig-{}-net", slug);
+
+    let guard_cluster = cluster_name.clone();
+    let guard_slug = slug.clone();
+    let guard_network = network_name.clone();
+    let _guard = scopeguard::guard((), move |_| {
+        k3d_cleanup_sync(&guard_cluster);
+        docker_cleanup(&guard_slug);
+        let _ = std::process::Command::new("docker")
+            .args(["network", "rm", &guard_network])
+            .output();
+    });
+
+    let _ = std::process::Command::new("docker")
+        .args(["network", "create", &network_name])
+        .output();
+
+    let cluster_config = config.cluster.as_ref().unwrap();
+    let state_dir = project.dir.path().join(".devrig");
+    std::fs::create_dir_all(&state_dir).unwrap();
+    let k3d_mgr = devrig::cluster::K3dManager::new(
+        &slug,
+        cluster_config,
+        &state_dir,
+        &network_name,
+        project.dir.path(),
+    );
+    k3d_mgr.create_cluster().await.expect("cluster create failed");
+    k3d_mgr.write_kubeconfig().await.expect("write_kubeconfig failed");
+
+    // An image that can never be pulled produces Warning events quickly.
+    k3d_mgr
+        .kubectl(&[
+            "run",
+            "broken",
+            "--image=devrig.invalid/does-not-exist:latest",
+            "--restart=Never",
+        ])
+        .await
+        .expect("kubectl run failed");
+
+    let mut stdout = String::new();
+    for _ in 0..30 {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_devrig"))
+            .args(["cluster", "events", "-f"])
+            .arg(&project.config_path)
+            .output()
+            .expect("failed to run devrig cluster events");
+        assert!(
+            output.status.success(),
+            "devrig cluster events failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        if stdout.contains("Pod/broken") {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+    }
+    assert!(stdout.contains("Warning"), "events output: {stdout}");
+    assert!(stdout.contains("Pod/broken"), "events output: {stdout}");
+    assert!(!stdout.contains("Scheduled"), "Normal events shown by default: {stdout}");
+
+    k3d_mgr.delete_cluster().await.expect("cluster delete failed");
+    let _ = std::process::Command::new("docker")
+        .args(["network", "rm", &network_name])
+        .output();
+    std::mem::forget(_guard);
+}
+
+#[tokio::test]
+async fn cluster_node_cordon_marks_unschedulable() {
+    if !k3d_available() {
+        eprintln!("Skipping: k3d not found");
+        return;
+    }
+
+    let project = crate::common::TestProject::new(
+        r#"
+        [project]
+        name = "clnodetest"
+
+        [cluster]
+        registry = false
+        agents = 1
+    "#,
+    );
+
+    let (config, _source) = devrig::config::load_config(&project.config_path).unwrap();
+    let identity =
+        devrig::identity::ProjectIdentity::from_config(&config, &project.config_path).unwrap();
+    let slug = identity.slug.clone();
+    let cluster_name = format!("devrig-{}", slug);
+    let network_name = format!("devrig-{}-net", slug);
+
+    let guard_cluster = cluster_name.clone();
+    let guard_slug = slug.clone();
+    let guard_network = network_name.clone();
+    let _guard = scopeguard::guard((), move |_| {
+        k3d_cleanup_sync(&guard_cluster);
+        docker_cleanup(&guard_slug);
+        let _ = std::process::Command::new("docker")
+            .args(["network", "rm", &guard_network])
+            .output();
+    });
+
+    let _ = std::process::Command::new("docker")
+        .args(["network", "create", &network_name])
+        .output();
+
+    let cluster_config = config.cluster.as_ref().unwrap();
+    let state_dir = project.dir.path().join(".devrig");
+    std::fs::create_dir_all(&state_dir).unwrap();
+    let k3d_mgr = devrig::cluster::K3dManager::new(
+        &slug,
+        cluster_config,
+        &state_dir,
+        &network_name,
+        project.dir.path(),
+    );
+    k3d_mgr.create_cluster().await.expect("cluster create failed");
+    k3d_mgr.write_kubeconfig().await.expect("write_kubeconfig failed");
+
+    let devrig_node = |action: &str| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_devrig"))
+            .args(["cluster", "node", action, "-f"])
+            .arg(&project.config_path)
+            .output()
+            .expect("failed to run devrig cluster node");
+        assert!(
+            output.status.success(),
+            "devrig cluster node {action} failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    };
+    let agent_nodes = || async {
+        let json = k3d_mgr
+            .kubectl(&["get", "nodes", "-o", "json"])
+            .await
+            .expect("kubectl get nodes failed");
+        devrig::cluster::nodes::parse_nodes(&json)
+            .unwrap()
+            .into_iter()
+            .filter(|n| !n.control_plane)
+            .collect::<Vec<_>>()
+    };
+
+    // With no node named, the agent is cordoned and the server is left alone.
+    devrig_node("cordon");
+    let agents = agent_nodes().await;
+    assert_eq!(agents.len(), 1, "agents: {agents:?}");
+    assert!(agents[0].unschedulable, "agent not cordoned: {agents:?}");
+
+    devrig_node("uncordon");
+    let agents = agent_nodes().await;
+    assert!(!agents[0].unschedulable, "agent still cordoned: {agents:?}");
+
+    k3d_mgr.delete_cluster().await.expect("cluster delete failed");
+    let _ = std::process::Command::new("docker")
+        .args(["network", "rm", &network_name])
+        .output();
+    std::mem::forget(_guard);
+}
+
+#[tokio::test]
+async fn cluster_diff_shows_manifest_changes() {
+    if !k3d_available() {
+        eprintln!("Skipping: k3d not found");
+        return;
+    }
+
+    let project = crate::common::TestProject::new(
+        r#"
+        [project]
+        name = "cldifftest"
+
+        [cluster]
+        registry = false
+
+        [cluster.deploy.app]
+        context = "."
+        manifests = "k8s/app.yaml"
+    "#,
+    );
+    let manifest_path = project.dir.path().join("k8s/app.yaml");
+    std::fs::create_dir_all(manifest_path.parent().unwrap()).unwrap();
+    let manifest = |greeting: &str| {
+        format!(
+            "apiVersion: v1\nkind: ConfigMap\nmetadata:\n  name: app-settings\n  namespace: default\ndata:\n  greeting: {greeting}\n"
+        )
+    };
+    std::fs::write(&manifest_path, manifest("hello")).unwrap();
+
+    let (config, _source) = devrig::config::load_config(&project.config_path).unwrap();
+    let identity =
+        devrig::identity::ProjectIdentity::from_config(&config, &project.config_path).unwrap();
+    let slug = identity.slug.clone();
+    let cluster_name = format!("devrig-{}", slug);
+    let network_name = format!("devrig-{}-net", slug);
+
+    let guard_cluster = cluster_name.clone();
+    let guard_slug = slug.clone();
+    let guard_network = network_name.clone();
+    let _guard = scopeguard::guard((), move |_| {
+        k3d_cleanup_sync(&guard_cluster);
+        docker_cleanup(&guard_slug);
+        let _ = std::process::Command::new("docker")
+            .args(["network", "rm", &guard_network])
+            .output();
+    });
+
+    let _ = std::process::Command::new("docker")
+        .args(["network", "create", &network_name])
+        .output();
+
+    let cluster_config = config.cluster.as_ref().unwrap();
+    let state_dir = project.dir.path().join(".devrig");
+    std::fs::create_dir_all(&state_dir).unwrap();
+    let k3d_mgr = devrig::cluster::K3dManager::new(
+        &slug,
+        cluster_config,
+        &state_dir,
+        &network_name,
+        project.dir.path(),
+    );
+    k3d_mgr.create_cluster().await.expect("cluster create failed");
+    k3d_mgr.write_kubeconfig().await.expect("write_kubeconfig failed");
+
+    let manifest_str = manifest_path.to_string_lossy().to_string();
+    k3d_mgr
+        .kubectl(&["apply", "-f", &manifest_str])
+        .await
+        .expect("kubectl apply failed");
+
+    let devrig_diff = || {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_devrig"))
+            .args(["cluster", "diff", "app", "-f"])
+            .arg(&project.config_path)
+            .output()
+            .expect("failed to run devrig cluster diff");
+        assert!(
+            output.status.success(),
+            "devrig cluster diff failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    let unchanged = devrig_diff();
+    assert!(unchanged.contains("app: no changes"), "diff output: {unchanged}");
+
+    std::fs::write(&manifest_path, manifest("goodbye")).unwrap();
+    let changed = devrig_diff();
+    assert!(changed.contains("-  greeting: hello"), "diff output: {changed}");
+    assert!(changed.contains("+  greeting: goodbye"), "diff output: {changed}");
+
+    k3d_mgr.delete_cluster().await.expect("cluster delete failed");
+    let _ = std::process::Command::new("docker")
+        .args(["network", "rm", &network_name])
+        .output();
+    std::mem::forget(_guard);
+}
@@ -86,6 +86,27 @@ services = ["redis"]
         state_json
     );
 
+    // Exec against the compose container by name
+    let exec = Command::new(env!("CARGO_BIN_EXE_devrig"))
+        .args([
+            "exec",
+            "-f",
+            &config_path_str,
+            "--compose",
+            "redis",
+            "--",
+            "redis-cli",
+            "ping",
+        ])
+        .output()
+        .await
+        .expect("failed to run devrig exec");
+    assert!(
+        String::from_utf8_lossy(&exec.stdout).contains("PONG"),
+        "exec in compose redis should print PONG: {}",
+        String::from_utf8_lossy(&exec.stderr)
+    );
+
     // Stop
     #[cfg(unix)]
     {
@@ -111,3 +132,187 @@ services = ["redis"]
         docker_cleanup(&slug);
     }
 }
+
+#[tokio::test]
+async fn compose_override_file_changes_port() {
+    if !docker_available() || !compose_available() {
+        eprintln!("Skipping: Docker or Docker Compose not available");
+        return;
+    }
+
+    let base_port = free_port();
+    let override_port = free_port();
+    let project = TestProject::new(
+        r#"
+[project]
+name = "test-compose-override"
+
+[compose]
+file = ["docker-compose.yml", "docker-compose.override.yml"]
+"#,
+    );
+    let dir = project.dir.path();
+    std::fs::write(
+        dir.join("docker-compose.yml"),
+        format!(
+            r#"services:
+  redis:
+    image: redis:7-alpine
+    ports:
+      - "{base_port}:6379"
+"#
+        ),
+    )
+    .expect("failed to write base compose file");
+    std::fs::write(
+        dir.join("docker-compose.override.yml"),
+        format!(
+            r#"services:
+  redis:
+    ports: !override
+      - "{override_port}:6379"
+"#
+        ),
+    )
+    .expect("failed to write override compose file");
+
+    let config_path_str = project.config_path.to_str().unwrap().to_string();
+    let mut child = Command::new(env!("CARGO_BIN_EXE_devrig"))
+        .args(["start", "-f", &config_path_str])
+        .kill_on_drop(true)
+        .spawn()
+        .expect("failed to start devrig");
+
+    assert!(
+        wait_for_port(override_port, Duration::from_secs(60)).await,
+        "Compose redis should be reachable on the override port {override_port}"
+    );
+    assert!(
+        !wait_for_port(base_port, Duration::from_secs(1)).await,
+        "the base port {base_port} should have been replaced by the override"
+    );
+
+    let state_file = dir.join(".devrig").join("state.json");
+    let start = std::time::Instant::now();
+    while start.elapsed() < Duration::from_secs(10) && !state_file.exists() {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    let state: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&state_file).unwrap()).unwrap();
+    assert_eq!(
+        state["compose_services"]["redis"]["port"],
+        serde_json::json!(override_port)
+    );
+
+    #[cfg(unix)]
+    {
+        let pid = child.id().unwrap();
+        nix::sys::signal::kill(
+            nix::unistd::Pid::from_raw(pid as i32),
+            nix::sys::signal::Signal::SIGINT,
+        )
+        .ok();
+    }
+    let _ = tokio::time::timeout(Duration::from_secs(20), child.wait()).await;
+
+    let slug = read_slug(&project);
+    let _ = std::process::Command::new(env!("CARGO_BIN_EXE_devrig"))
+        .args(["delete", "-f", &config_path_str])
+        .output();
+    if let Some(slug) = slug {
+        docker_cleanup(&slug);
+    }
+}
+
+#[tokio::test]
+async fn compose_exec_runs_in_service_container() {
+    if !docker_available() || !compose_available() {
+        eprintln!("Skipping: Docker or Docker Compose not available");
+        return;
+    }
+
+    let project = TestProject::new(
+        r#"
+[project]
+name = "test-compose-exec"
+
+[compose]
+file = "docker-compose.yml"
+"#,
+    );
+    let dir = project.dir.path();
+    std::fs::write(
+        dir.join("docker-compose.yml"),
+        r#"services:
+  box:
+    image: alpine:3.20
+    command: ["sleep", "infinity"]
+    environment:
+      GREETING: hello-from-compose
+"#,
+    )
+    .expect("failed to write compose file");
+
+    let config_path_str = project.config_path.to_str().unwrap().to_string();
+    let mut child = Command::new(env!("CARGO_BIN_EXE_devrig"))
+        .args(["start", "-f", &config_path_str])
+        .kill_on_drop(true)
+        .spawn()
+        .expect("failed to start devrig");
+
+    // The service has no port, so wait for it to show up in the state file.
+    let state_file = dir.join(".devrig").join("state.json");
+    let start = std::time::Instant::now();
+    while start.elapsed() < Duration::from_secs(60) {
+        let recorded = std::fs::read_to_string(&state_file)
+            .ok()
+            .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok())
+            .is_some_and(|state| state["compose_services"]["box"].is_object());
+        if recorded {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+
+    let exec = Command::new(env!("CARGO_BIN_EXE_devrig"))
+        .args([
+            "exec",
+            "-f",
+            &config_path_str,
+            "box",
+            "--",
+            "printenv",
+            "GREETING",
+        ])
+        .output()
+        .await
+        .expect("failed to run devrig exec");
+    assert!(
+        exec.status.success(),
+        "exec in compose box failed: {}",
+        String::from_utf8_lossy(&exec.stderr)
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&exec.stdout).trim(),
+        "hello-from-compose"
+    );
+
+    #[cfg(unix)]
+    {
+        let pid = child.id().unwrap();
+        nix::sys::signal::kill(
+            nix::unistd::Pid::from_raw(pid as i32),
+            nix::sys::signal::Signal::SIGINT,
+        )
+        .ok();
+    }
+    let _ = tokio::time::timeout(Duration::from_secs(20), child.wait()).await;
+
+    let slug = read_slug(&project);
+    let _ = std::process::Command::new(env!("CARGO_BIN_EXE_devrig"))
+        .args(["delete", "-f", &config_path_str])
+        .output();
+    if let Some(slug) = slug {
+        docker_cleanup(&slug);
+    }
+}
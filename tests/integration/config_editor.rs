@@ -36,6 +36,9 @@ async fn start_config_stack(
         metric_buffer: 100,
         log_buffer: 100,
         retention: "1h".to_string(),
+        wal: false,
+        wal_max_mb: 64,
+        forward: None,
     };
 
     let collector = OtelCollector::new(&otel_config);
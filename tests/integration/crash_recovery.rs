@@ -68,3 +68,40 @@ command = "exit 0"
         "devrig should exit on its own when service exits cleanly"
     );
 }
+
+#[tokio::test]
+async fn exit_on_failure_stops_everything_with_service_code() {
+    // With --exit-on-failure, a service that fails for good takes the whole
+    // project down, and devrig exits with that service's code.
+    let project = TestProject::new(
+        r#"
+[project]
+name = "test-exit-on-failure"
+[services.failer]
+command = "exit 3"
+[services.failer.restart]
+policy = "never"
+[services.sleeper]
+command = "sleep 300"
+"#,
+    );
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_devrig"))
+        .args([
+            "start",
+            "--exit-on-failure",
+            "-f",
+            project.config_path.to_str().unwrap(),
+        ])
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .expect("failed to start devrig");
+
+    let status = tokio::time::timeout(Duration::from_secs(30), child.wait())
+        .await
+        .expect("devrig should exit once failer fails, despite sleeper running")
+        .unwrap();
+    assert_eq!(status.code(), Some(3));
+}
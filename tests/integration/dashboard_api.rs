@@ -87,6 +87,9 @@ async fn start_stack(grpc_port: u16, http_port: u16, dashboard_port: u16) -> Can
         metric_buffer: 100,
         log_buffer: 100,
         retention: "1h".to_string(),
+        wal: false,
+        wal_max_mb: 64,
+        forward: None,
     };
 
     let collector = OtelCollector::new(&otel_config);
@@ -97,7 +100,18 @@ async fn start_stack(grpc_port: u16, http_port: u16, dashboard_port: u16) -> Can
 
     let dash_cancel = cancel.clone();
     tokio::spawn(async move {
-        let _ = start_dashboard_server(dashboard_port, store, events_tx, dash_cancel, None).await;
+        let _ = start_dashboard_server(
+            dashboard_port,
+            store,
+            events_tx,
+            dash_cancel,
+            None,
+            None,
+            "dashboard-test".to_string(),
+            None,
+            None,
+        )
+        .await;
     });
 
     // Give servers a moment to bind their ports.
@@ -136,6 +150,50 @@ async fn dashboard_status_endpoint_returns_empty_initially() {
     cancel.cancel();
 }
 
+#[tokio::test]
+async fn dashboard_meta_endpoint_returns_title() {
+    let cancel = start_stack(15337, 15338, 15520).await;
+
+    let meta: serde_json::Value = reqwest::get("http://127.0.0.1:15520/api/meta")
+        .await
+        .expect("GET /api/meta should succeed")
+        .json()
+        .await
+        .expect("response should be valid JSON");
+    assert_eq!(meta["title"], "dashboard-test");
+    assert_eq!(meta["version"], env!("CARGO_PKG_VERSION"));
+
+    cancel.cancel();
+}
+
+#[tokio::test]
+async fn service_control_refuses_cross_origin_requests() {
+    let cancel = start_stack(15357, 15358, 15540).await;
+
+    let client = reqwest::Client::new();
+    let url = "http://127.0.0.1:15540/api/services/api/restart";
+
+    let resp = client
+        .post(url)
+        .header("Origin", "https://evil.example")
+        .send()
+        .await
+        .expect("POST restart should get a response");
+    assert_eq!(resp.status(), 403);
+    assert!(resp.headers().get("access-control-allow-origin").is_none());
+
+    // Past the guard, there is no `devrig start` behind this dashboard.
+    let resp = client
+        .post(url)
+        .header("Origin", "http://localhost:15540")
+        .send()
+        .await
+        .expect("POST restart should get a response");
+    assert_eq!(resp.status(), 503);
+
+    cancel.cancel();
+}
+
 // ---------------------------------------------------------------------------
 // Test 2: The /api/traces and /api/logs and /api/metrics endpoints all return
 //         empty arrays when nothing has been ingested.
@@ -350,3 +408,61 @@ async fn websocket_receives_trace_update_event() {
 
     cancel.cancel();
 }
+
+// ---------------------------------------------------------------------------
+// Test 5: Logs pushed to the Loki endpoint show up in /api/logs, and
+//         /metrics serves the Prometheus text format.
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn loki_push_and_prometheus_scrape() {
+    let cancel = start_stack(15737, 15738, 15920).await;
+    let client = reqwest::Client::new();
+
+    let push = serde_json::json!({
+        "streams": [{
+            "stream": { "job": "nginx", "level": "error" },
+            "values": [["1700000000000000000", "upstream timed out"]]
+        }]
+    });
+    let resp = client
+        .post("http://127.0.0.1:15920/loki/api/v1/push")
+        .json(&push)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 204);
+
+    let logs: Vec<serde_json::Value> = client
+        .get("http://127.0.0.1:15920/api/logs?service=nginx")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs[0]["body"], "upstream timed out");
+
+    let resp = client
+        .post("http://127.0.0.1:15920/loki/api/v1/push")
+        .header("Content-Type", "application/x-protobuf")
+        .body(vec![0u8; 4])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 415);
+
+    let resp = client
+        .get("http://127.0.0.1:15920/metrics")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    assert!(resp.headers()["content-type"]
+        .to_str()
+        .unwrap()
+        .starts_with("text/plain; version=0.0.4"));
+
+    cancel.cancel();
+}
@@ -165,3 +165,86 @@ ready_check = {{ type = "tcp" }}
     // Fallback cleanup via Docker CLI
     docker_cleanup(&slug);
 }
+
+#[tokio::test]
+async fn delete_cleans_up_with_corrupt_state() {
+    if !docker_available() {
+        eprintln!("Skipping: Docker not available");
+        return;
+    }
+
+    let port = free_port();
+    let project = TestProject::new(&format!(
+        r#"
+[project]
+name = "test-corrupt-state"
+
+[docker.redis]
+image = "redis:7-alpine"
+port = {port}
+ready_check = {{ type = "tcp" }}
+"#
+    ));
+
+    let config_path_str = project.config_path.to_str().unwrap().to_string();
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_devrig"))
+        .args(["start", "-f", &config_path_str])
+        .kill_on_drop(true)
+        .spawn()
+        .expect("failed to start devrig");
+
+    assert!(
+        wait_for_port(port, Duration::from_secs(60)).await,
+        "Redis should be reachable on port {port}"
+    );
+
+    let state_file = project.dir.path().join(".devrig").join("state.json");
+    let start = std::time::Instant::now();
+    while start.elapsed() < Duration::from_secs(10) && !state_file.exists() {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    let slug = read_slug(&project).expect("state.json should name the slug");
+
+    // Kill devrig without a graceful shutdown so the container keeps
+    // running, then clobber the state file.
+    child.kill().await.ok();
+    std::fs::write(&state_file, "{ this is not json").unwrap();
+
+    let delete = std::process::Command::new(env!("CARGO_BIN_EXE_devrig"))
+        .args(["delete", "-f", &config_path_str])
+        .output()
+        .expect("failed to run delete");
+    assert!(
+        delete.status.success(),
+        "delete should succeed: {}",
+        String::from_utf8_lossy(&delete.stderr)
+    );
+    let delete_output = format!(
+        "{}{}",
+        String::from_utf8_lossy(&delete.stdout),
+        String::from_utf8_lossy(&delete.stderr)
+    );
+    assert!(
+        delete_output.contains("corrupt"),
+        "delete should warn about the corrupt state file: {delete_output}"
+    );
+
+    tokio::time::sleep(Duration::from_secs(2)).await;
+
+    let (containers, volumes, networks) = count_docker_resources(&slug);
+    assert_eq!(
+        containers, 0,
+        "No containers should remain after delete for slug {slug}"
+    );
+    assert_eq!(
+        volumes, 0,
+        "No volumes should remain after delete for slug {slug}"
+    );
+    assert_eq!(
+        networks, 0,
+        "No networks should remain after delete for slug {slug}"
+    );
+
+    docker_cleanup(&slug);
+}
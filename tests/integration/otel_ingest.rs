@@ -146,6 +146,9 @@ async fn start_stack(grpc_port: u16, http_port: u16, dashboard_port: u16) -> Can
         metric_buffer: 100,
         log_buffer: 100,
         retention: "1h".to_string(),
+        wal: false,
+        wal_max_mb: 64,
+        forward: None,
     };
 
     let collector = OtelCollector::new(&otel_config);
@@ -296,8 +299,16 @@ async fn otel_env_injection() {
                     command: "cargo run".to_string(),
                     port: Some(devrig::config::model::Port::Fixed(3000)),
                     env: BTreeMap::new(),
-                    depends_on: Vec::new(),
+                    depends_on: Default::default(),
+                    ready_check: None,
                     restart: None,
+                    shell: None,
+                    reload_strategy: None,
+                    reload_signal: None,
+                    max_log_lines_per_sec: None,
+                    pre_start: None,
+                    post_stop: None,
+                    log_format: None,
                 },
             );
             m
@@ -315,6 +326,9 @@ async fn otel_env_injection() {
                 metric_buffer: 10000,
                 log_buffer: 10000,
                 retention: "1h".to_string(),
+                wal: false,
+                wal_max_mb: 64,
+                forward: None,
             }),
         }),
         env: BTreeMap::new(),
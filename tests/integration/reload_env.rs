@@ -0,0 +1,133 @@
+use crate::common::*;
+use std::time::Duration;
+use tokio::process::Command;
+
+fn service_state(project: &TestProject, name: &str) -> serde_json::Value {
+    let state_path = project.dir.path().join(".devrig/state.json");
+    let state: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(state_path).unwrap()).unwrap();
+    state["services"][name].clone()
+}
+
+async fn reload_env(project: &TestProject) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_devrig"))
+        .args(["reload-env", "-f", project.config_path.to_str().unwrap()])
+        .output()
+        .await
+        .expect("failed to run devrig reload-env")
+}
+
+#[tokio::test]
+async fn reload_env_only_touches_changed_services() {
+    let project = TestProject::new(
+        r#"
+[project]
+name = "test-reload-env"
+
+[services.api]
+command = "echo start >> api-starts.log; trap 'echo usr1 >> api-signals.log' USR1; while true; do sleep 0.1; done"
+env_file = ".env.api"
+reload_strategy = "signal"
+reload_signal = "SIGUSR1"
+
+[services.worker]
+command = "echo start >> worker-starts.log; sleep 600"
+env_file = ".env.worker"
+"#,
+    );
+    let dir = project.dir.path();
+    std::fs::write(dir.join(".env.api"), "GREETING=hello\n").unwrap();
+    std::fs::write(dir.join(".env.worker"), "QUEUE=jobs\n").unwrap();
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_devrig"))
+        .args(["start", "-f", project.config_path.to_str().unwrap()])
+        .stdout(std::process::Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .expect("failed to start devrig");
+
+    let api_starts = dir.join("api-starts.log");
+    let worker_starts = dir.join("worker-starts.log");
+    for _ in 0..50 {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        if api_starts.exists()
+            && worker_starts.exists()
+            && service_state(&project, "api")["pid"].is_u64()
+        {
+            break;
+        }
+    }
+    assert!(
+        api_starts.exists() && worker_starts.exists(),
+        "services never started"
+    );
+
+    // Nothing changed yet.
+    let output = reload_env(&project).await;
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).contains("No env changes"));
+
+    std::fs::write(dir.join(".env.api"), "GREETING=hi\n").unwrap();
+    let output = reload_env(&project).await;
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Sent SIGUSR1 to api (GREETING)"),
+        "stdout: {stdout}"
+    );
+    assert!(!stdout.contains("worker"), "stdout: {stdout}");
+
+    let signals = dir.join("api-signals.log");
+    for _ in 0..25 {
+        if signals.exists() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+    assert!(signals.exists(), "api never received SIGUSR1");
+    let count_lines =
+        |path: &std::path::Path| std::fs::read_to_string(path).unwrap().lines().count();
+    assert_eq!(count_lines(&api_starts), 1, "api should not restart");
+    assert_eq!(count_lines(&worker_starts), 1, "worker should not restart");
+
+    // The worker uses the default strategy and is restarted.
+    std::fs::write(dir.join(".env.worker"), "QUEUE=emails\n").unwrap();
+    let output = reload_env(&project).await;
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Restarted worker (QUEUE)"),
+        "stdout: {stdout}"
+    );
+    for _ in 0..25 {
+        if count_lines(&worker_starts) == 2 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+    assert_eq!(count_lines(&worker_starts), 2, "worker should restart once");
+    assert_eq!(count_lines(&api_starts), 1, "api should not restart");
+
+    #[cfg(unix)]
+    {
+        let pid = child.id().unwrap();
+        nix::sys::signal::kill(
+            nix::unistd::Pid::from_raw(pid as i32),
+            nix::sys::signal::Signal::SIGINT,
+        )
+        .ok();
+    }
+    let _ = tokio::time::timeout(Duration::from_secs(15), child.wait()).await;
+}
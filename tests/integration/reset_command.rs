@@ -115,3 +115,100 @@ ready_check = {{ type = "tcp" }}
         docker_cleanup(&slug);
     }
 }
+
+#[tokio::test]
+async fn reset_all_recreates_container_and_volumes() {
+    if !docker_available() {
+        eprintln!("Skipping: Docker not available");
+        return;
+    }
+
+    let port = free_port();
+    let project = TestProject::new(&format!(
+        r#"
+[project]
+name = "test-reset-all"
+
+[docker.redis]
+image = "redis:7-alpine"
+port = {port}
+volumes = ["redisdata:/data"]
+ready_check = {{ type = "tcp" }}
+"#
+    ));
+
+    let config_path_str = project.config_path.to_str().unwrap().to_string();
+    let state_dir = project.dir.path().join(".devrig");
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_devrig"))
+        .args(["start", "-f", &config_path_str])
+        .kill_on_drop(true)
+        .spawn()
+        .expect("failed to start devrig");
+
+    assert!(
+        wait_for_port(port, Duration::from_secs(60)).await,
+        "Redis container should be reachable on port {}",
+        port
+    );
+
+    #[cfg(unix)]
+    {
+        let pid = child.id().unwrap();
+        nix::sys::signal::kill(
+            nix::unistd::Pid::from_raw(pid as i32),
+            nix::sys::signal::Signal::SIGINT,
+        )
+        .ok();
+    }
+    let _ = tokio::time::timeout(Duration::from_secs(20), child.wait()).await;
+
+    let slug = read_slug(&project);
+    let old_state =
+        std::fs::read_to_string(state_dir.join("state.json")).expect("state.json should exist");
+
+    // Without a terminal, a destructive reset needs --yes
+    let refused = std::process::Command::new(env!("CARGO_BIN_EXE_devrig"))
+        .args(["reset", "-f", &config_path_str, "--all"])
+        .output()
+        .expect("failed to run reset");
+    assert!(!refused.status.success(), "reset --all without --yes should fail");
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_devrig"))
+        .args(["reset", "-f", &config_path_str, "--all", "--yes"])
+        .output()
+        .expect("failed to run reset");
+    assert!(
+        output.status.success(),
+        "reset --all should succeed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Recreated 'redis'"), "stdout: {}", stdout);
+    assert!(
+        wait_for_port(port, Duration::from_secs(30)).await,
+        "recreated container should listen on the same port"
+    );
+
+    let new_state =
+        std::fs::read_to_string(state_dir.join("state.json")).expect("state.json should exist");
+    let container_id = |json: &str| -> String {
+        let v: serde_json::Value = serde_json::from_str(json).unwrap();
+        v["docker"]["redis"]["container_id"]
+            .as_str()
+            .unwrap()
+            .to_string()
+    };
+    assert_ne!(
+        container_id(&old_state),
+        container_id(&new_state),
+        "reset --all should create a new container"
+    );
+
+    let _ = std::process::Command::new(env!("CARGO_BIN_EXE_devrig"))
+        .args(["delete", "-f", &config_path_str])
+        .output();
+    if let Some(slug) = slug {
+        docker_cleanup(&slug);
+    }
+}
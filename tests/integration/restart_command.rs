@@ -0,0 +1,109 @@
+use crate::common::*;
+use std::time::Duration;
+use tokio::process::Command;
+
+fn service_state(project: &TestProject, name: &str) -> serde_json::Value {
+    let state_path = project.dir.path().join(".devrig/state.json");
+    let state: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(state_path).unwrap()).unwrap();
+    state["services"][name].clone()
+}
+
+#[tokio::test]
+async fn restart_respawns_service_with_same_port() {
+    let project = TestProject::new(
+        r#"
+[project]
+name = "test-restart"
+[services.web]
+command = "echo start >> starts.log && python3 -m http.server $PORT"
+port = "auto"
+"#,
+    );
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_devrig"))
+        .args(["start", "-f", project.config_path.to_str().unwrap()])
+        .stdout(std::process::Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .expect("failed to start devrig");
+
+    let starts = project.dir.path().join("starts.log");
+    let mut port = None;
+    for _ in 0..50 {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        if starts.exists() {
+            port = service_state(&project, "web")["port"].as_u64();
+            if port.is_some() {
+                break;
+            }
+        }
+    }
+    let port = port.expect("web never started") as u16;
+    assert!(wait_for_port(port, Duration::from_secs(10)).await);
+    let first_pid = service_state(&project, "web")["pid"].clone();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_devrig"))
+        .args([
+            "restart",
+            "web",
+            "-f",
+            project.config_path.to_str().unwrap(),
+        ])
+        .output()
+        .await
+        .expect("failed to run devrig restart");
+    assert!(
+        output.status.success(),
+        "restart failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    assert!(wait_for_port(port, Duration::from_secs(10)).await);
+    let lines = std::fs::read_to_string(&starts).unwrap();
+    assert_eq!(
+        lines.lines().count(),
+        2,
+        "service should have started twice"
+    );
+    let state = service_state(&project, "web");
+    assert_eq!(state["port"].as_u64(), Some(port as u64));
+    assert_ne!(state["pid"], first_pid);
+
+    #[cfg(unix)]
+    {
+        let pid = child.id().unwrap();
+        nix::sys::signal::kill(
+            nix::unistd::Pid::from_raw(pid as i32),
+            nix::sys::signal::Signal::SIGINT,
+        )
+        .ok();
+    }
+    let _ = tokio::time::timeout(Duration::from_secs(15), child.wait()).await;
+}
+
+#[tokio::test]
+async fn restart_when_not_running_errors() {
+    let project = TestProject::new(
+        r#"
+[project]
+name = "test-restart-idle"
+[services.web]
+command = "sleep 60"
+"#,
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_devrig"))
+        .args([
+            "restart",
+            "web",
+            "-f",
+            project.config_path.to_str().unwrap(),
+        ])
+        .output()
+        .await
+        .expect("failed to run devrig restart");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("not running"), "stderr: {stderr}");
+}
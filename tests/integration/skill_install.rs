@@ -16,7 +16,7 @@ fn skill_install_writes_to_project_dir() {
     // Run the skill install command using the library function
     let rt = tokio::runtime::Runtime::new().unwrap();
     rt.block_on(async {
-        devrig::commands::skill::run_install(false, Some(project.config_path.as_path()))
+        devrig::commands::skill::run_install(&[], false, Some(project.config_path.as_path()))
             .await
             .unwrap();
     });
@@ -56,7 +56,7 @@ fn skill_install_global() {
 
     let rt = tokio::runtime::Runtime::new().unwrap();
     rt.block_on(async {
-        devrig::commands::skill::run_install(true, None)
+        devrig::commands::skill::run_install(&[], true, None)
             .await
             .unwrap();
     });
@@ -88,10 +88,10 @@ fn skill_install_is_idempotent() {
 
     let rt = tokio::runtime::Runtime::new().unwrap();
     rt.block_on(async {
-        devrig::commands::skill::run_install(false, Some(project.config_path.as_path()))
+        devrig::commands::skill::run_install(&[], false, Some(project.config_path.as_path()))
             .await
             .unwrap();
-        devrig::commands::skill::run_install(false, Some(project.config_path.as_path()))
+        devrig::commands::skill::run_install(&[], false, Some(project.config_path.as_path()))
             .await
             .unwrap();
     });
@@ -102,3 +102,53 @@ fn skill_install_is_idempotent() {
         "SKILL.md should still exist after second install"
     );
 }
+
+/// Verify `--target` writes each editor's file and a bare install refreshes them.
+#[test]
+fn skill_install_targets_and_detects_installed() {
+    use devrig::commands::skill::{installed_targets, SkillTarget};
+
+    let project = TestProject::new(
+        r#"
+        [project]
+        name = "test-targets"
+
+        [services.api]
+        command = "echo hi"
+    "#,
+    );
+    let dir = project.dir.path();
+
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    rt.block_on(async {
+        devrig::commands::skill::run_install(
+            &[SkillTarget::Cursor, SkillTarget::Vscode],
+            false,
+            Some(project.config_path.as_path()),
+        )
+        .await
+        .unwrap();
+    });
+
+    let cursor = std::fs::read_to_string(dir.join(".cursor/rules/devrig.mdc")).unwrap();
+    assert!(cursor.contains("alwaysApply"), "cursor rule frontmatter");
+    let vscode =
+        std::fs::read_to_string(dir.join(".github/instructions/devrig.instructions.md")).unwrap();
+    assert!(
+        vscode.contains("applyTo"),
+        "vscode instructions frontmatter"
+    );
+    assert!(!dir.join(".claude").exists(), "claude not requested");
+
+    // A bare install only refreshes what is already there
+    rt.block_on(async {
+        devrig::commands::skill::run_install(&[], false, Some(project.config_path.as_path()))
+            .await
+            .unwrap();
+    });
+    assert!(!dir.join(".claude").exists());
+    assert_eq!(
+        installed_targets(Some(dir)),
+        vec![SkillTarget::Cursor, SkillTarget::Vscode]
+    );
+}
@@ -0,0 +1,78 @@
+use crate::common::*;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn top_reports_service_usage_and_quits_on_q() {
+    let project = TestProject::new(
+        r#"
+[project]
+name = "test-top"
+
+[services.burn]
+command = "while true; do :; done"
+
+[services.idle]
+command = "sleep 600"
+"#,
+    );
+    let config = project.config_path.to_str().unwrap();
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_devrig"))
+        .args(["start", "-f", config])
+        .stdout(std::process::Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .expect("failed to start devrig");
+
+    let state_path = project.dir.path().join(".devrig/state.json");
+    for _ in 0..50 {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let started = std::fs::read_to_string(&state_path)
+            .ok()
+            .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok())
+            .is_some_and(|s| s["services"]["idle"]["pid"].is_u64());
+        if started {
+            break;
+        }
+    }
+
+    let mut top = Command::new(env!("CARGO_BIN_EXE_devrig"))
+        .args(["top", "-f", config])
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .expect("failed to run devrig top");
+    tokio::time::sleep(Duration::from_millis(2500)).await;
+    top.stdin.take().unwrap().write_all(b"q").await.unwrap();
+    let output = tokio::time::timeout(Duration::from_secs(5), top.wait_with_output())
+        .await
+        .expect("devrig top did not quit on q")
+        .unwrap();
+    assert!(output.status.success());
+
+    // The last frame has a CPU% for every service.
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let frame = stdout.rsplit("Project:").next().unwrap();
+    let cpu = |name: &str| -> f64 {
+        let line = frame
+            .lines()
+            .find(|l| l.trim_start().starts_with(name))
+            .unwrap_or_else(|| panic!("no row for {name}: {stdout}"));
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        fields[3].parse().unwrap()
+    };
+    assert!(cpu("burn") > 50.0, "stdout: {stdout}");
+    assert!(cpu("idle") < 10.0, "stdout: {stdout}");
+
+    let pid = child.id().unwrap();
+    nix::sys::signal::kill(
+        nix::unistd::Pid::from_raw(pid as i32),
+        nix::sys::signal::Signal::SIGINT,
+    )
+    .ok();
+    let _ = tokio::time::timeout(Duration::from_secs(15), child.wait()).await;
+}